@@ -0,0 +1,79 @@
+//! Build script for ASUM.
+//!
+//! Embeds the git commit and build date so `asum version` can report them.
+
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    let git_sha = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|sha| !sha.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    // Honor SOURCE_DATE_EPOCH for reproducible builds
+    let epoch_secs = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        });
+
+    println!("cargo:rustc-env=ASUM_GIT_SHA={}", git_sha);
+    println!(
+        "cargo:rustc-env=ASUM_BUILD_DATE={}",
+        format_date(epoch_secs)
+    );
+    // HEAD only changes when switching branches; watch the branch ref for new commits.
+    // Refs may be loose files or packed into packed-refs, and in a linked worktree HEAD
+    // and the refs live in different directories, so git resolves each path.
+    if let Some(head) = git_path("HEAD") {
+        println!("cargo:rerun-if-changed={}", head);
+        let branch_ref = std::fs::read_to_string(&head)
+            .ok()
+            .and_then(|head| head.strip_prefix("ref: ").map(|r| r.trim().to_string()));
+        for name in branch_ref.iter().map(String::as_str).chain(["packed-refs"]) {
+            // A missing file would rerun the script on every build; packing refs
+            // removes the loose one, which is itself a change
+            if let Some(path) = git_path(name).filter(|path| Path::new(path).exists()) {
+                println!("cargo:rerun-if-changed={}", path);
+            }
+        }
+    }
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+}
+
+/// Path of `name` inside the git directory, as `git rev-parse --git-path` resolves it.
+fn git_path(name: &str) -> Option<String> {
+    Command::new("git")
+        .args(["rev-parse", "--git-path", name])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|path| !path.is_empty())
+}
+
+/// Formats seconds since the Unix epoch as a `YYYY-MM-DD` date (UTC).
+fn format_date(epoch_secs: u64) -> String {
+    // Civil-from-days conversion (Howard Hinnant's algorithm)
+    let days = (epoch_secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -0,0 +1,250 @@
+//! Generated code detection.
+//!
+//! Recognizes files that look generated even when no exclude pattern matches them:
+//! files with a "DO NOT EDIT" style marker, minified files with very long lines, and
+//! files whose lines all look alike (lookup tables, embedded data). They are flagged
+//! in the prompt and moved to the end of the diff, so truncation cuts them first.
+
+use super::DiffAnalyzer;
+use crate::diff_source::{file_sections, preamble};
+
+/// Lowercase markers that code generators put in the files they write.
+const MARKERS: &[&str] = &[
+    "do not edit",
+    "@generated",
+    "auto-generated",
+    "autogenerated",
+    "code generated by",
+];
+
+/// Changed lines longer than this (in characters) suggest minified output.
+const LONG_LINE: usize = 500;
+
+/// Average changed line length above which a file counts as minified.
+const LONG_AVERAGE: usize = 200;
+
+/// Smallest number of changed lines before their entropy is compared; short
+/// changes are too small to judge.
+const MIN_UNIFORM_LINES: usize = 40;
+
+/// Lines shorter than this are left out of the entropy comparison.
+const MIN_ENTROPY_LINE: usize = 16;
+
+/// Standard deviation of the per-line entropy (in bits) below which the lines
+/// count as uniform. Hand-written code varies far more from line to line.
+const MAX_ENTROPY_DEVIATION: f64 = 0.1;
+
+/// Flags files that look generated.
+pub struct GeneratedCode;
+
+impl DiffAnalyzer for GeneratedCode {
+    fn name(&self) -> &'static str {
+        "generated"
+    }
+
+    fn analyze(&self, diff: &str) -> Option<String> {
+        let lines: Vec<String> = file_sections(diff)
+            .into_iter()
+            .filter_map(|(path, section)| Some(format!("- {} ({})", path, reason(section)?)))
+            .collect();
+        (!lines.is_empty()).then(|| {
+            format!(
+                "[GENERATED FILES]\nThese files look generated; mention them briefly (e.g. \
+                 \"regenerate the client\") instead of describing their contents:\n{}",
+                lines.join("\n")
+            )
+        })
+    }
+}
+
+/// Moves the sections of generated files to the end of the diff, keeping the order
+/// within both groups, so truncation cuts generated code before hand-written code.
+pub fn demote(diff: &str) -> String {
+    let sections = file_sections(diff);
+    if sections
+        .iter()
+        .all(|(_, section)| reason(section).is_none())
+    {
+        return diff.to_string();
+    }
+
+    let (generated, written): (Vec<_>, Vec<_>) = sections
+        .iter()
+        .partition(|(_, section)| reason(section).is_some());
+    let mut demoted = preamble(diff).to_string();
+    for (_, section) in written.into_iter().chain(generated) {
+        demoted.push_str(section);
+        if !demoted.ends_with('\n') {
+            demoted.push('\n');
+        }
+    }
+    demoted
+}
+
+/// Whether a file section looks generated.
+pub fn is_generated(section: &str) -> bool {
+    reason(section).is_some()
+}
+
+/// Why a file section looks generated, or `None` when it looks hand-written.
+fn reason(section: &str) -> Option<&'static str> {
+    // Markers sit in the file header, which is often unchanged context
+    let marked = section.lines().skip(1).any(|line| {
+        let line = line.to_lowercase();
+        MARKERS.iter().any(|marker| line.contains(marker))
+    });
+    if marked {
+        return Some("generated-code marker");
+    }
+
+    let changed: Vec<&str> = section
+        .lines()
+        .filter(|line| {
+            (line.starts_with('+') || line.starts_with('-'))
+                && !line.starts_with("+++")
+                && !line.starts_with("---")
+        })
+        .map(|line| &line[1..])
+        .collect();
+    if changed.is_empty() {
+        return None;
+    }
+
+    let lengths: Vec<usize> = changed.iter().map(|line| line.chars().count()).collect();
+    let average = lengths.iter().sum::<usize>() / lengths.len();
+    if lengths.iter().any(|&length| length > LONG_LINE) || average > LONG_AVERAGE {
+        return Some("very long lines");
+    }
+
+    let entropies: Vec<f64> = changed
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| line.len() >= MIN_ENTROPY_LINE)
+        .map(entropy)
+        .collect();
+    if entropies.len() >= MIN_UNIFORM_LINES && deviation(&entropies) < MAX_ENTROPY_DEVIATION {
+        return Some("uniform lines");
+    }
+    None
+}
+
+/// Shannon entropy of the bytes of `line`, in bits per byte.
+pub fn entropy(line: &str) -> f64 {
+    let mut counts = [0usize; 256];
+    for byte in line.bytes() {
+        counts[byte as usize] += 1;
+    }
+    let total = line.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Population standard deviation of `values`.
+fn deviation(values: &[f64]) -> f64 {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    variance.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(path: &str, lines: &[String]) -> String {
+        let added: String = lines.iter().map(|line| format!("+{}\n", line)).collect();
+        format!("diff --git a/{0} b/{0}\n@@ -0,0 +1 @@\n{1}", path, added)
+    }
+
+    fn handwritten() -> Vec<String> {
+        [
+            "fn parse(input: &str) -> Result<Config> {",
+            "    let mut config = Config::default();",
+            "    for line in input.lines() {",
+            "        if line.trim().is_empty() { continue; }",
+            "        let (key, value) = line.split_once('=').context(\"missing =\")?;",
+            "        config.set(key.trim(), value.trim())?;",
+            "    }",
+            "    Ok(config)",
+            "}",
+        ]
+        .iter()
+        .cycle()
+        .take(60)
+        .map(|line| line.to_string())
+        .collect()
+    }
+
+    #[test]
+    fn test_reason_table_driven() {
+        // A byte table as written by a code generator
+        let table: Vec<String> = (0..60)
+            .map(|i| {
+                let row: Vec<String> = (0..8)
+                    .map(|j| ((i * 8 + j) * 37 % 256).to_string())
+                    .collect();
+                format!("    {},", row.join(", "))
+            })
+            .collect();
+        let cases = vec![
+            (section("src/lib.rs", &handwritten()), None),
+            (
+                section(
+                    "api/client.go",
+                    &["// Code generated by protoc-gen-go. DO NOT EDIT.".to_string()],
+                ),
+                Some("generated-code marker"),
+            ),
+            (
+                section("dist/app.min.js", &["x".repeat(LONG_LINE + 1)]),
+                Some("very long lines"),
+            ),
+            (section("src/crc_table.rs", &table), Some("uniform lines")),
+            // Too few lines to judge their entropy
+            (section("src/crc_table.rs", &table[..5]), None),
+        ];
+        for (section, expected) in cases {
+            assert_eq!(reason(&section), expected, "section: {}", section);
+        }
+    }
+
+    #[test]
+    fn test_entropy() {
+        assert_eq!(entropy("aaaa"), 0.0);
+        assert_eq!(entropy("abab"), 1.0);
+        assert_eq!(entropy("abcd"), 2.0);
+    }
+
+    #[test]
+    fn test_demote_moves_generated_files_last() {
+        let generated = section("gen/api.rs", &["// @generated by build.rs".to_string()]);
+        let first = section("src/a.rs", &["fn a() {}".to_string()]);
+        let second = section("src/b.rs", &["fn b() {}".to_string()]);
+
+        let diff = format!("{}{}{}", generated, first, second);
+        assert_eq!(demote(&diff), format!("{}{}{}", first, second, generated));
+
+        let plain = format!("{}{}", first, second);
+        assert_eq!(demote(&plain), plain);
+    }
+
+    #[test]
+    fn test_analyze() {
+        let diff = section("gen/api.rs", &["// @generated by build.rs".to_string()]);
+        assert_eq!(
+            GeneratedCode.analyze(&diff).unwrap(),
+            "[GENERATED FILES]\nThese files look generated; mention them briefly (e.g. \
+             \"regenerate the client\") instead of describing their contents:\n\
+             - gen/api.rs (generated-code marker)"
+        );
+        assert_eq!(
+            GeneratedCode.analyze(&section("a.rs", &handwritten())),
+            None
+        );
+    }
+}
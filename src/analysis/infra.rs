@@ -0,0 +1,443 @@
+//! Infrastructure-as-code analysis.
+//!
+//! Recognizes Terraform resources that were added, removed or changed, container
+//! image tag bumps in Kubernetes manifests and changed Helm values, and outlines
+//! them so the model can name the exact resources in the message.
+
+use super::DiffAnalyzer;
+use crate::diff_source::file_sections;
+use std::path::Path;
+
+/// Terraform block types that are reported by name.
+const TERRAFORM_BLOCKS: &[&str] = &["resource", "data", "module"];
+
+/// Largest number of outline entries per file; the rest is summarized as a count.
+const MAX_ENTRIES: usize = 20;
+
+/// Outlines Terraform, Kubernetes and Helm changes.
+pub struct InfraChanges;
+
+impl DiffAnalyzer for InfraChanges {
+    fn name(&self) -> &'static str {
+        "infra"
+    }
+
+    fn analyze(&self, diff: &str) -> Option<String> {
+        let mut outline = String::new();
+        for (path, section) in file_sections(diff) {
+            let file = Path::new(path);
+            let name = file
+                .file_name()
+                .map(|n| n.to_string_lossy())
+                .unwrap_or_default();
+            let extension = file
+                .extension()
+                .map(|e| e.to_string_lossy())
+                .unwrap_or_default();
+
+            let (title, entries) = match extension.as_ref() {
+                "tf" => ("Terraform", terraform_changes(section)),
+                "yaml" | "yml" if name.starts_with("values") => {
+                    ("Helm values", helm_value_changes(section))
+                }
+                "yaml" | "yml" => ("Container images", image_changes(section)),
+                _ => continue,
+            };
+            if entries.is_empty() {
+                continue;
+            }
+
+            outline.push_str(&format!("\n{} ({}):", title, path));
+            for entry in entries.iter().take(MAX_ENTRIES) {
+                outline.push_str(&format!("\n- {}", entry));
+            }
+            if entries.len() > MAX_ENTRIES {
+                outline.push_str(&format!("\n- ... and {} more", entries.len() - MAX_ENTRIES));
+            }
+        }
+
+        (!outline.is_empty()).then(|| {
+            format!(
+                "[INFRA CHANGES]\nInfrastructure changes found in the diff. Name the affected resources precisely.{}",
+                outline
+            )
+        })
+    }
+}
+
+/// Splits a changed line into its side (`'+'` or `'-'`) and content. Context lines,
+/// file headers and hunk headers return `None`.
+fn changed_line(line: &str) -> Option<(char, &str)> {
+    if line.starts_with("+++") || line.starts_with("---") {
+        return None;
+    }
+    let side = line.chars().next()?;
+    matches!(side, '+' | '-').then(|| (side, &line[1..]))
+}
+
+/// Returns the label of a Terraform block header such as
+/// `resource "aws_instance" "web" {` (`resource aws_instance.web`).
+fn terraform_block(line: &str) -> Option<String> {
+    let line = line.trim();
+    let (kind, rest) = line.split_once(' ')?;
+    if !TERRAFORM_BLOCKS.contains(&kind) || !rest.trim_end().ends_with('{') {
+        return None;
+    }
+    let labels: Vec<&str> = rest.split('"').skip(1).step_by(2).collect();
+    if labels.is_empty() {
+        return None;
+    }
+    Some(format!("{} {}", kind, labels.join(".")))
+}
+
+/// Lists the Terraform blocks added, removed or changed in one file's diff.
+fn terraform_changes(section: &str) -> Vec<String> {
+    let (mut added, mut removed, mut changed) = (Vec::new(), Vec::new(), Vec::new());
+    let mut current: Option<String> = None;
+
+    for line in section.lines() {
+        if let Some(hunk) = line.strip_prefix("@@") {
+            // Git puts the enclosing block header after the hunk range
+            current = hunk
+                .split_once("@@")
+                .and_then(|(_, ctx)| terraform_block(ctx));
+            continue;
+        }
+        let content = line.strip_prefix(' ');
+        if let Some(block) = content.and_then(terraform_block) {
+            current = Some(block);
+            continue;
+        }
+        let Some((side, content)) = changed_line(line) else {
+            continue;
+        };
+        match (terraform_block(content), side) {
+            (Some(block), '+') => {
+                push_unique(&mut added, block.clone());
+                current = Some(block);
+            }
+            (Some(block), _) => {
+                push_unique(&mut removed, block.clone());
+                current = Some(block);
+            }
+            (None, _) if content.trim().is_empty() => {}
+            (None, _) => {
+                if let Some(block) = &current {
+                    push_unique(&mut changed, block.clone());
+                }
+            }
+        }
+    }
+
+    // A block both removed and added was rewritten in place and counts as changed
+    let is_new = |block: &String| added.contains(block) && !removed.contains(block);
+    let is_gone = |block: &String| removed.contains(block) && !added.contains(block);
+    let mut entries: Vec<String> = added
+        .iter()
+        .filter(|block| is_new(block))
+        .map(|block| format!("added {}", block))
+        .collect();
+    entries.extend(
+        removed
+            .iter()
+            .filter(|block| is_gone(block))
+            .map(|block| format!("removed {}", block)),
+    );
+    for block in changed.iter().chain(&added) {
+        if !is_new(block) && !is_gone(block) {
+            push_unique(&mut entries, format!("changed {}", block));
+        }
+    }
+    entries
+}
+
+/// Splits a container image reference into repository and tag (or digest).
+fn split_image(image: &str) -> (&str, &str) {
+    if let Some((repository, digest)) = image.split_once('@') {
+        return (repository, digest);
+    }
+    match image.rsplit_once(':') {
+        // A colon before the last `/` belongs to a registry port
+        Some((repository, tag)) if !tag.contains('/') => (repository, tag),
+        _ => (image, "latest"),
+    }
+}
+
+/// Lists the image tag bumps in one manifest's diff, such as `nginx: 1.25 -> 1.26`.
+fn image_changes(section: &str) -> Vec<String> {
+    let (mut added, mut removed) = (Vec::new(), Vec::new());
+    for line in section.lines() {
+        let Some((side, content)) = changed_line(line) else {
+            continue;
+        };
+        let content = content.trim().trim_start_matches("- ");
+        let Some(image) = content.strip_prefix("image:") else {
+            continue;
+        };
+        let image = image.trim().trim_matches(|c| c == '"' || c == '\'');
+        if image.is_empty() {
+            continue;
+        }
+        let (repository, tag) = split_image(image);
+        match side {
+            '+' => added.push((repository, tag)),
+            _ => removed.push((repository, tag)),
+        }
+    }
+
+    let mut entries = Vec::new();
+    for (repository, tag) in &added {
+        match removed.iter().find(|(old, _)| old == repository) {
+            Some((_, old_tag)) if old_tag == tag => {}
+            Some((_, old_tag)) => {
+                push_unique(
+                    &mut entries,
+                    format!("{}: {} -> {}", repository, old_tag, tag),
+                );
+            }
+            None => push_unique(&mut entries, format!("added image {}:{}", repository, tag)),
+        }
+    }
+    for (repository, tag) in &removed {
+        if !added.iter().any(|(new, _)| new == repository) {
+            push_unique(
+                &mut entries,
+                format!("removed image {}:{}", repository, tag),
+            );
+        }
+    }
+    entries
+}
+
+/// Splits a YAML line into its indentation, key and value. Comments and lines
+/// without a key return `None`.
+fn yaml_entry(line: &str) -> Option<(usize, &str, &str)> {
+    let trimmed = line.trim_start();
+    let mut indent = line.len() - trimmed.len();
+    let trimmed = match trimmed.strip_prefix("- ") {
+        Some(item) => {
+            indent += 2;
+            item
+        }
+        None => trimmed,
+    };
+    if trimmed.starts_with('#') {
+        return None;
+    }
+    let (key, value) = trimmed.split_once(':')?;
+    if key.is_empty() || key.contains(' ') {
+        return None;
+    }
+    Some((indent, key, value.trim()))
+}
+
+/// Lists the changed keys of a Helm values file by their dotted path, such as
+/// `image.tag: "1.0" -> "1.1"`.
+fn helm_value_changes(section: &str) -> Vec<String> {
+    let mut added: Vec<(String, String)> = Vec::new();
+    let mut removed: Vec<(String, String)> = Vec::new();
+    let mut parents: Vec<(usize, String)> = Vec::new();
+
+    for line in section.lines() {
+        if let Some(hunk) = line.strip_prefix("@@") {
+            // Git puts the nearest top-level line after the hunk range
+            parents.clear();
+            if let Some((_, ctx)) = hunk.split_once("@@")
+                && let Some((0, key, "")) = yaml_entry(ctx.trim())
+            {
+                parents.push((0, key.to_string()));
+            }
+            continue;
+        }
+        let (side, content) = match changed_line(line) {
+            Some(changed) => changed,
+            None => match line.strip_prefix(' ') {
+                Some(content) => (' ', content),
+                None => continue,
+            },
+        };
+        let Some((indent, key, value)) = yaml_entry(content) else {
+            continue;
+        };
+
+        while parents.last().is_some_and(|(level, _)| *level >= indent) {
+            parents.pop();
+        }
+        let path: Vec<&str> = parents
+            .iter()
+            .map(|(_, key)| key.as_str())
+            .chain([key])
+            .collect();
+        let path = path.join(".");
+        if value.is_empty() {
+            parents.push((indent, key.to_string()));
+            continue;
+        }
+        match side {
+            '+' => added.push((path, value.to_string())),
+            '-' => removed.push((path, value.to_string())),
+            _ => {}
+        }
+    }
+
+    let mut entries = Vec::new();
+    for (path, value) in &added {
+        match removed.iter().find(|(old, _)| old == path) {
+            Some((_, old_value)) if old_value == value => {}
+            Some((_, old_value)) => entries.push(format!("{}: {} -> {}", path, old_value, value)),
+            None => entries.push(format!("{}: {} (added)", path, value)),
+        }
+    }
+    for (path, _) in &removed {
+        if !added.iter().any(|(new, _)| new == path) {
+            entries.push(format!("{} (removed)", path));
+        }
+    }
+    entries
+}
+
+/// Appends `entry` unless it is already listed.
+fn push_unique(entries: &mut Vec<String>, entry: String) {
+    if !entries.contains(&entry) {
+        entries.push(entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terraform_block_table_driven() {
+        let cases = vec![
+            (
+                r#"resource "aws_instance" "web" {"#,
+                Some("resource aws_instance.web"),
+            ),
+            (r#"  module "vpc" {"#, Some("module vpc")),
+            (r#"data "aws_ami" "ubuntu" {"#, Some("data aws_ami.ubuntu")),
+            (r#"variable "region" {"#, None),
+            (r#"resource = "x""#, None),
+            ("  ami = \"ami-123\"", None),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(terraform_block(line).as_deref(), expected, "line: {}", line);
+        }
+    }
+
+    #[test]
+    fn test_terraform_changes() {
+        let section = r#"diff --git a/main.tf b/main.tf
+--- a/main.tf
++++ b/main.tf
+@@ -1,4 +1,4 @@ resource "aws_instance" "web" {
+   ami           = "ami-123"
+-  instance_type = "t3.micro"
++  instance_type = "t3.large"
+ }
+@@ -10,6 +10,6 @@
+-resource "aws_s3_bucket" "old" {
+-  bucket = "old"
+-}
++resource "aws_s3_bucket" "logs" {
++  bucket = "logs"
++}
+@@ -20,3 +20,3 @@
+ module "vpc" {
+-  cidr = "10.0.0.0/16"
++  cidr = "10.1.0.0/16"
+ }
+"#;
+        assert_eq!(
+            terraform_changes(section),
+            vec![
+                "added resource aws_s3_bucket.logs",
+                "removed resource aws_s3_bucket.old",
+                "changed resource aws_instance.web",
+                "changed module vpc",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_image_table_driven() {
+        let cases = vec![
+            ("nginx:1.25", ("nginx", "1.25")),
+            ("nginx", ("nginx", "latest")),
+            ("registry:5000/app/api:v2", ("registry:5000/app/api", "v2")),
+            ("registry:5000/app/api", ("registry:5000/app/api", "latest")),
+            ("app@sha256:abc", ("app", "sha256:abc")),
+        ];
+        for (image, expected) in cases {
+            assert_eq!(split_image(image), expected, "image: {}", image);
+        }
+    }
+
+    #[test]
+    fn test_image_changes() {
+        let section = "diff --git a/k8s/deploy.yaml b/k8s/deploy.yaml
+@@ -10,7 +10,7 @@ spec:
+       containers:
+-        - image: nginx:1.25
++        - image: nginx:1.26
+-          image: \"ghcr.io/acme/worker:v1\"
++          image: \"ghcr.io/acme/worker:v1\"
++        - image: redis:7
+-        - image: memcached:1.6
+";
+        assert_eq!(
+            image_changes(section),
+            vec![
+                "nginx: 1.25 -> 1.26",
+                "added image redis:7",
+                "removed image memcached:1.6",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_helm_value_changes() {
+        let section = "diff --git a/charts/app/values.yaml b/charts/app/values.yaml
+@@ -1,8 +1,9 @@
+-replicaCount: 1
++replicaCount: 3
+ image:
+   repository: acme/app
+-  tag: \"1.0\"
++  tag: \"1.1\"
++  pullPolicy: Always
+@@ -30,4 +31,3 @@ resources:
+   limits:
+-    cpu: 500m
+     # memory: 1Gi
+";
+        assert_eq!(
+            helm_value_changes(section),
+            vec![
+                "replicaCount: 1 -> 3",
+                "image.tag: \"1.0\" -> \"1.1\"",
+                "image.pullPolicy: Always (added)",
+                "resources.limits.cpu (removed)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analyze() {
+        let diff = "diff --git a/src/main.rs b/src/main.rs
++fn main() {}
+diff --git a/main.tf b/main.tf
++resource \"aws_s3_bucket\" \"logs\" {
+diff --git a/charts/app/values-prod.yaml b/charts/app/values-prod.yaml
+-replicaCount: 1
++replicaCount: 3
+";
+        assert_eq!(
+            InfraChanges.analyze(diff).unwrap(),
+            "[INFRA CHANGES]\nInfrastructure changes found in the diff. Name the affected resources precisely.\n\
+             Terraform (main.tf):\n- added resource aws_s3_bucket.logs\n\
+             Helm values (charts/app/values-prod.yaml):\n- replicaCount: 1 -> 3"
+        );
+        assert_eq!(InfraChanges.analyze("diff --git a/a.rs b/a.rs\n+x\n"), None);
+    }
+}
@@ -0,0 +1,197 @@
+//! License header and copyright year detection.
+//!
+//! Annual header refreshes touch many files with nearly identical one-line changes,
+//! which floods the prompt and buries any real change. When enough files only change
+//! their license header, their diffs are left out and replaced by a one-line note.
+
+use super::DiffAnalyzer;
+use crate::diff_source::{file_sections, without_sections};
+
+/// Smallest number of header-only files that counts as a bulk update. Below this,
+/// the diffs are short enough to keep.
+const MIN_FILES: usize = 3;
+
+/// Largest number of files listed by name in the note.
+const MAX_LISTED: usize = 5;
+
+/// Lowercase fragments that mark a license or copyright line.
+const MARKERS: &[&str] = &[
+    "copyright",
+    "(c)",
+    "©",
+    "spdx-license-identifier",
+    "license",
+    "all rights reserved",
+];
+
+/// Prefixes of comment lines, which surround the markers in license headers.
+const COMMENT_PREFIXES: &[&str] = &["//", "/*", "*", "#", "--", ";", "<!--", "-->", "\"\"\""];
+
+/// Notes bulk license header and copyright year updates.
+pub struct LicenseHeaders;
+
+impl DiffAnalyzer for LicenseHeaders {
+    fn name(&self) -> &'static str {
+        "license"
+    }
+
+    fn analyze(&self, diff: &str) -> Option<String> {
+        let paths = header_only_paths(diff);
+        if paths.len() < MIN_FILES {
+            return None;
+        }
+
+        let mut listed = paths[..paths.len().min(MAX_LISTED)].join(", ");
+        if paths.len() > MAX_LISTED {
+            listed.push_str(&format!(" and {} more", paths.len() - MAX_LISTED));
+        }
+        let template = match latest_year(diff) {
+            Some(year) => format!("chore(license): update copyright year to {}", year),
+            None => "chore(license): update license headers".to_string(),
+        };
+        Some(format!(
+            "[LICENSE HEADER UPDATE]\n{} files only change their license header or copyright \
+             year; their diffs were left out: {}.\nMention this in one line. If nothing else \
+             changed, use a message like \"{}\".",
+            paths.len(),
+            listed,
+            template
+        ))
+    }
+}
+
+/// Removes the sections of files that only change their license header, when at
+/// least `MIN_FILES` do. Other diffs are returned unchanged.
+pub fn collapse(diff: &str) -> String {
+    if header_only_paths(diff).len() < MIN_FILES {
+        return diff.to_string();
+    }
+    without_sections(diff, |_, section| is_header_only(section))
+}
+
+/// Paths of the files whose changes are all license header lines.
+fn header_only_paths(diff: &str) -> Vec<&str> {
+    file_sections(diff)
+        .into_iter()
+        .filter(|(_, section)| is_header_only(section))
+        .map(|(path, _)| path)
+        .collect()
+}
+
+/// Whether every changed line of a file section is blank, a comment or a license
+/// line, and at least one is a license line.
+fn is_header_only(section: &str) -> bool {
+    let mut license = false;
+    for line in changed_lines(section) {
+        let line = line.trim();
+        if is_license_line(line) {
+            license = true;
+        } else if !line.is_empty() && !COMMENT_PREFIXES.iter().any(|p| line.starts_with(p)) {
+            return false;
+        }
+    }
+    license
+}
+
+/// Added and removed lines of a section, without their `+`/`-` prefix.
+fn changed_lines(section: &str) -> impl Iterator<Item = &str> {
+    section
+        .lines()
+        .filter(|line| {
+            (line.starts_with('+') || line.starts_with('-'))
+                && !line.starts_with("+++")
+                && !line.starts_with("---")
+        })
+        .map(|line| &line[1..])
+}
+
+/// Whether `line` mentions a license or copyright.
+fn is_license_line(line: &str) -> bool {
+    let line = line.to_lowercase();
+    MARKERS.iter().any(|marker| line.contains(marker))
+}
+
+/// The latest four-digit year on an added license line, e.g. `2025` in
+/// `+// Copyright 2019-2025 Acme`.
+fn latest_year(diff: &str) -> Option<u32> {
+    diff.lines()
+        .filter_map(|line| line.strip_prefix('+'))
+        .filter(|line| !line.starts_with("++") && is_license_line(line))
+        .flat_map(|line| line.split(|c: char| !c.is_ascii_digit()))
+        .filter(|word| word.len() == 4)
+        .filter_map(|word| word.parse().ok())
+        .filter(|year| (1970..=2999).contains(year))
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header_diff(path: &str) -> String {
+        format!(
+            "diff --git a/{0} b/{0}\n--- a/{0}\n+++ b/{0}\n@@ -1,2 +1,2 @@\n\
+             -// Copyright 2019-2024 Acme Inc.\n+// Copyright 2019-2025 Acme Inc.\n \n",
+            path
+        )
+    }
+
+    const CODE_DIFF: &str = "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n\
+                             @@ -1 +1 @@\n-// Copyright 2024\n+// Copyright 2025\n+fn added() {}\n";
+
+    #[test]
+    fn test_is_header_only_table_driven() {
+        let cases = vec![
+            (header_diff("a.rs"), true),
+            (CODE_DIFF.to_string(), false),
+            (
+                "diff --git a/x.py b/x.py\n+# SPDX-License-Identifier: MIT\n+#\n+# Licensed to Acme\n"
+                    .to_string(),
+                true,
+            ),
+            (
+                "diff --git a/LICENSE b/LICENSE\n-Copyright (c) 2024 Acme\n+Copyright (c) 2025 Acme\n"
+                    .to_string(),
+                true,
+            ),
+            // Comment-only changes without a license line are ordinary edits
+            (
+                "diff --git a/a.rs b/a.rs\n-// old note\n+// new note\n".to_string(),
+                false,
+            ),
+        ];
+        for (section, expected) in cases {
+            assert_eq!(is_header_only(&section), expected, "section: {}", section);
+        }
+    }
+
+    #[test]
+    fn test_collapse_needs_bulk_update() {
+        let few = format!("{}{}", header_diff("a.rs"), CODE_DIFF);
+        assert_eq!(collapse(&few), few);
+        assert_eq!(LicenseHeaders.analyze(&few), None);
+
+        let many = format!(
+            "{}{}{}{}",
+            header_diff("a.rs"),
+            CODE_DIFF,
+            header_diff("b.rs"),
+            header_diff("c.rs")
+        );
+        assert_eq!(collapse(&many), CODE_DIFF);
+    }
+
+    #[test]
+    fn test_analyze() {
+        let diff: String = (1..=7)
+            .map(|i| header_diff(&format!("f{}.rs", i)))
+            .collect();
+        assert_eq!(
+            LicenseHeaders.analyze(&diff).unwrap(),
+            "[LICENSE HEADER UPDATE]\n7 files only change their license header or copyright \
+             year; their diffs were left out: f1.rs, f2.rs, f3.rs, f4.rs, f5.rs and 2 more.\n\
+             Mention this in one line. If nothing else changed, use a message like \
+             \"chore(license): update copyright year to 2025\"."
+        );
+    }
+}
@@ -0,0 +1,72 @@
+//! Diff analysis for ASUM.
+//!
+//! Analyzers look at the whole diff before it is truncated and add short, structured
+//! notes to the prompt, so the model sees facts it would otherwise have to dig out of
+//! a long (or cut-off) diff. Each analyzer implements `DiffAnalyzer` and is listed in
+//! `analyzers`.
+
+pub mod generated;
+pub mod infra;
+pub mod license;
+pub mod noise;
+pub mod osv;
+pub mod oversized;
+pub mod renames;
+pub mod security;
+pub mod snapshots;
+pub mod stat;
+
+use tracing::debug;
+
+/// Adds notes about one kind of change to the prompt.
+pub trait DiffAnalyzer {
+    /// Short name used in logs.
+    fn name(&self) -> &'static str;
+
+    /// Returns a notes section (starting with a `[HEADING]` line) for `diff`, or
+    /// `None` when the diff has nothing this analyzer recognizes.
+    fn analyze(&self, diff: &str) -> Option<String>;
+}
+
+/// All analyzers, in the order their notes appear in the prompt.
+pub fn analyzers() -> Vec<Box<dyn DiffAnalyzer>> {
+    vec![
+        Box::new(renames::RenamedFiles),
+        Box::new(license::LicenseHeaders),
+        Box::new(snapshots::SnapshotChanges),
+        Box::new(generated::GeneratedCode),
+        Box::new(infra::InfraChanges),
+        Box::new(security::SecuritySensitive),
+    ]
+}
+
+/// Runs every analyzer on `diff` and joins their notes, or returns `None` when
+/// none of them had anything to add.
+pub fn notes(diff: &str) -> Option<String> {
+    let sections: Vec<String> = analyzers()
+        .iter()
+        .filter_map(|analyzer| {
+            let section = analyzer.analyze(diff)?;
+            debug!(
+                "The {} analyzer added notes to the prompt.",
+                analyzer.name()
+            );
+            Some(section)
+        })
+        .collect();
+    (!sections.is_empty()).then(|| sections.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notes() {
+        assert_eq!(notes("diff --git a/a.rs b/a.rs\n+fn a() {}\n"), None);
+
+        let diff = "diff --git a/main.tf b/main.tf\n+resource \"aws_s3_bucket\" \"logs\" {\n";
+        let notes = notes(diff).unwrap();
+        assert!(notes.starts_with("[INFRA CHANGES]\n"));
+    }
+}
@@ -0,0 +1,239 @@
+//! Noise reduction before a diff is sent.
+//!
+//! Some changes carry no meaning for a commit message but take up much of the prompt:
+//! re-indented code, import blocks sorted by a formatter, regenerated files. The
+//! filters listed in `[diff] noise_filters` leave them out of the diff, and a note
+//! tells the model what was left out so the message can still mention it.
+
+use super::generated;
+use crate::diff_source::{file_sections, preamble};
+
+/// Names accepted in `noise_filters`.
+pub const FILTERS: &[&str] = &["whitespace", "imports", "generated"];
+
+/// Largest number of files listed by name per line of the note.
+const MAX_LISTED: usize = 5;
+
+/// Prefixes of import lines, after leading whitespace.
+const IMPORT_PREFIXES: &[&str] = &[
+    "use ",
+    "pub use ",
+    "import ",
+    "from ",
+    "#include ",
+    "@import ",
+    "using ",
+    "require ",
+    "require_once ",
+];
+
+/// Applies the `filters` to `diff`. Returns the filtered diff and a note on what
+/// was left out, or `None` when nothing was.
+pub fn filter(diff: &str, filters: &[String]) -> (String, Option<String>) {
+    let enabled = |name: &str| filters.iter().any(|filter| filter == name);
+    let mut filtered = preamble(diff).to_string();
+    let mut whitespace = Vec::new();
+    let mut imports = Vec::new();
+    let mut generated = Vec::new();
+
+    for (path, section) in file_sections(diff) {
+        if enabled("generated") && generated::is_generated(section) {
+            generated.push(path);
+            continue;
+        }
+        let (header, mut hunks) = split_hunks(section);
+        let count = hunks.len();
+        if enabled("whitespace") {
+            hunks.retain(|hunk| !is_whitespace_only(hunk));
+            if hunks.len() < count {
+                whitespace.push(path);
+            }
+        }
+        if enabled("imports") && is_import_reordering(&hunks) {
+            hunks.retain(|hunk| !is_imports_only(hunk));
+            imports.push(path);
+        }
+        // Files left without changes are dropped, not shown as a bare header
+        if count > 0 && hunks.is_empty() {
+            continue;
+        }
+        filtered.push_str(header);
+        hunks.iter().for_each(|hunk| filtered.push_str(hunk));
+    }
+
+    let lines: Vec<String> = [
+        ("whitespace-only changes in", whitespace),
+        ("reordered imports in", imports),
+        ("the diffs of generated files", generated),
+    ]
+    .into_iter()
+    .filter(|(_, paths)| !paths.is_empty())
+    .map(|(what, paths)| format!("- {} {}", what, list(&paths)))
+    .collect();
+    let note = (!lines.is_empty()).then(|| {
+        format!(
+            "[NOISE FILTERED]\nThese changes were left out of the diff; mention them in a few \
+             words at most:\n{}",
+            lines.join("\n")
+        )
+    });
+    (filtered, note)
+}
+
+/// Joins `paths`, naming at most `MAX_LISTED` of them.
+fn list(paths: &[&str]) -> String {
+    let mut listed = paths[..paths.len().min(MAX_LISTED)].join(", ");
+    if paths.len() > MAX_LISTED {
+        listed.push_str(&format!(" and {} more", paths.len() - MAX_LISTED));
+    }
+    listed
+}
+
+/// Splits a file section into its header and its hunks.
+fn split_hunks(section: &str) -> (&str, Vec<&str>) {
+    let starts: Vec<usize> = section
+        .match_indices("\n@@")
+        .map(|(index, _)| index + 1)
+        .collect();
+    let Some(&first) = starts.first() else {
+        return (section, Vec::new());
+    };
+    let hunks = starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| &section[start..starts.get(i + 1).copied().unwrap_or(section.len())])
+        .collect();
+    (&section[..first], hunks)
+}
+
+/// Removed and added lines of a hunk, without their `-`/`+` prefix.
+fn changed_lines(hunk: &str) -> (Vec<&str>, Vec<&str>) {
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    for line in hunk.lines().skip(1) {
+        if let Some(line) = line.strip_prefix('-') {
+            removed.push(line);
+        } else if let Some(line) = line.strip_prefix('+') {
+            added.push(line);
+        }
+    }
+    (removed, added)
+}
+
+/// Whether a hunk changes nothing but whitespace: its removed and added lines are
+/// the same once all whitespace is taken out.
+fn is_whitespace_only(hunk: &str) -> bool {
+    let (removed, added) = changed_lines(hunk);
+    let squeeze = |lines: &[&str]| -> String {
+        lines
+            .iter()
+            .flat_map(|line| line.chars())
+            .filter(|c| !c.is_whitespace())
+            .collect()
+    };
+    !(removed.is_empty() && added.is_empty()) && squeeze(&removed) == squeeze(&added)
+}
+
+/// Whether every changed line of a hunk is an import, and there is one.
+fn is_imports_only(hunk: &str) -> bool {
+    let (removed, added) = changed_lines(hunk);
+    let lines: Vec<&str> = removed.into_iter().chain(added).collect();
+    !lines.is_empty()
+        && lines.iter().all(|line| {
+            let line = line.trim_start();
+            IMPORT_PREFIXES
+                .iter()
+                .any(|prefix| line.starts_with(prefix))
+        })
+}
+
+/// Whether the import-only hunks of a file remove exactly the imports they add,
+/// in another order.
+fn is_import_reordering(hunks: &[&str]) -> bool {
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    for hunk in hunks.iter().filter(|hunk| is_imports_only(hunk)) {
+        let (hunk_removed, hunk_added) = changed_lines(hunk);
+        removed.extend(hunk_removed.into_iter().map(str::trim));
+        added.extend(hunk_added.into_iter().map(str::trim));
+    }
+    removed.sort_unstable();
+    added.sort_unstable();
+    !removed.is_empty() && removed == added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filters(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    const REINDENT: &str = "diff --git a/a.py b/a.py\n--- a/a.py\n+++ b/a.py\n\
+                            @@ -1,2 +1,2 @@\n-if x:\n-  run()\n+if x:\n+    run()\n\
+                            @@ -9 +9 @@\n-old()\n+new()\n";
+    const IMPORTS: &str = "diff --git a/b.rs b/b.rs\n--- a/b.rs\n+++ b/b.rs\n\
+                           @@ -1,2 +1,2 @@\n-use std::io;\n-use anyhow::Result;\n\
+                           +use anyhow::Result;\n+use std::io;\n";
+    const GENERATED: &str = "diff --git a/gen.rs b/gen.rs\n@@ -1 +1 @@\n\
+                             // Code generated by protoc. DO NOT EDIT.\n-a\n+b\n";
+
+    #[test]
+    fn test_filter_table_driven() {
+        let diff = format!("{}{}{}", REINDENT, IMPORTS, GENERATED);
+        let cases = vec![
+            ("no filters", filters(&[]), diff.clone(), None),
+            (
+                "whitespace",
+                filters(&["whitespace"]),
+                format!(
+                    "diff --git a/a.py b/a.py\n--- a/a.py\n+++ b/a.py\n@@ -9 +9 @@\n-old()\n\
+                     +new()\n{}{}",
+                    IMPORTS, GENERATED
+                ),
+                Some("- whitespace-only changes in a.py"),
+            ),
+            (
+                "imports",
+                filters(&["imports"]),
+                format!("{}{}", REINDENT, GENERATED),
+                Some("- reordered imports in b.rs"),
+            ),
+            (
+                "generated",
+                filters(&["generated"]),
+                format!("{}{}", REINDENT, IMPORTS),
+                Some("- the diffs of generated files gen.rs"),
+            ),
+        ];
+
+        for (name, filters, expected, line) in cases {
+            let (filtered, note) = filter(&diff, &filters);
+            assert_eq!(filtered, expected, "{}", name);
+            match line {
+                Some(line) => {
+                    let note = note.unwrap();
+                    assert!(note.starts_with("[NOISE FILTERED]\n"), "{}", name);
+                    assert!(note.ends_with(line), "{}: {}", name, note);
+                }
+                None => assert_eq!(note, None, "{}", name),
+            }
+        }
+    }
+
+    #[test]
+    fn test_new_imports_are_kept() {
+        let diff = "diff --git a/b.rs b/b.rs\n@@ -1 +1,2 @@\n use std::io;\n+use std::fs;\n";
+        assert_eq!(
+            filter(diff, &filters(&["imports"])),
+            (diff.to_string(), None)
+        );
+    }
+
+    #[test]
+    fn test_list_names_at_most_five() {
+        let paths = ["a", "b", "c", "d", "e", "f", "g"];
+        assert_eq!(list(&paths), "a, b, c, d, e and 2 more");
+    }
+}
@@ -0,0 +1,445 @@
+//! Vulnerability context for dependency bumps.
+//!
+//! When a dependency manifest changes, the old and new versions of each bumped
+//! package are looked up in the OSV database (<https://osv.dev>). Advisories that
+//! affect the old version but not the new one are fixed by the change, and the
+//! model is asked to mention them ("fixes RUSTSEC-…").
+
+use crate::diff_source::file_sections;
+use serde::Deserialize;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// OSV query endpoint.
+pub const OSV_QUERY_URL: &str = "https://api.osv.dev/v1/query";
+
+/// Largest number of bumps looked up per change, to bound the number of requests.
+const MAX_LOOKUPS: usize = 20;
+
+/// Keys that look like dependencies in manifests but describe the package itself.
+const NON_DEPENDENCY_KEYS: &[&str] = &["version", "rust-version", "edition", "node", "npm"];
+
+/// Parses one manifest line into a dependency name and version.
+type ManifestParser = fn(&str) -> Option<(String, String)>;
+
+/// A dependency whose version changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DependencyBump {
+    /// OSV ecosystem name, e.g. `crates.io` or `npm`.
+    pub ecosystem: &'static str,
+    pub name: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// An OSV advisory and its aliases (usually CVE identifiers).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Advisory {
+    pub id: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+impl Advisory {
+    /// The id followed by its CVE aliases, e.g. `RUSTSEC-2023-0044 (CVE-2023-1234)`.
+    fn label(&self) -> String {
+        let cves: Vec<&str> = self
+            .aliases
+            .iter()
+            .map(String::as_str)
+            .filter(|alias| alias.starts_with("CVE-") && *alias != self.id)
+            .collect();
+        if cves.is_empty() {
+            self.id.clone()
+        } else {
+            format!("{} ({})", self.id, cves.join(", "))
+        }
+    }
+}
+
+/// Looks up the advisories fixed by the dependency bumps in `diff` and returns prompt
+/// notes listing them, or `None` when there are no bumps or nothing was fixed.
+pub async fn notes(diff: &str) -> Option<String> {
+    let bumps = bumps(diff);
+    if bumps.is_empty() {
+        return None;
+    }
+    info!(
+        "Looking up advisories for {} dependency update(s) in the OSV database...",
+        bumps.len().min(MAX_LOOKUPS)
+    );
+    let fixed = fixed_advisories(OSV_QUERY_URL, &bumps).await;
+    render(&fixed)
+}
+
+/// Renders the fixed advisories as prompt notes.
+fn render(fixed: &[(DependencyBump, Vec<Advisory>)]) -> Option<String> {
+    let lines: Vec<String> = fixed
+        .iter()
+        .filter(|(_, advisories)| !advisories.is_empty())
+        .map(|(bump, advisories)| {
+            let labels: Vec<String> = advisories.iter().map(Advisory::label).collect();
+            format!(
+                "- {} {} -> {}: {}",
+                bump.name,
+                bump.old,
+                bump.new,
+                labels.join(", ")
+            )
+        })
+        .collect();
+    if lines.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "[FIXED ADVISORIES]\nThe dependency updates in this change fix known vulnerabilities:\n{}\n\
+         Mention each one in the body as \"fixes <ID>\", e.g. \"fixes {}\".",
+        lines.join("\n"),
+        fixed
+            .iter()
+            .find_map(|(_, advisories)| advisories.first())
+            .map(|advisory| advisory.id.as_str())
+            .unwrap_or_default()
+    ))
+}
+
+/// Finds the dependencies whose version changed in the manifests of `diff`.
+pub fn bumps(diff: &str) -> Vec<DependencyBump> {
+    let mut bumps = Vec::new();
+    for (path, section) in file_sections(diff) {
+        let name = Path::new(path)
+            .file_name()
+            .map(|n| n.to_string_lossy())
+            .unwrap_or_default();
+        let (ecosystem, parse): (&'static str, ManifestParser) = match name.as_ref() {
+            "Cargo.toml" => ("crates.io", parse_cargo),
+            "package.json" => ("npm", parse_package_json),
+            "requirements.txt" => ("PyPI", parse_requirement),
+            "go.mod" => ("Go", parse_go_mod),
+            _ => continue,
+        };
+
+        let (mut removed, mut added) = (Vec::new(), Vec::new());
+        for line in section.lines() {
+            if line.starts_with("---") || line.starts_with("+++") {
+                continue;
+            }
+            if let Some(dependency) = line.strip_prefix('-').and_then(parse) {
+                removed.push(dependency);
+            } else if let Some(dependency) = line.strip_prefix('+').and_then(parse) {
+                added.push(dependency);
+            }
+        }
+        for (name, new) in added {
+            if let Some((_, old)) = removed.iter().find(|(old_name, _)| *old_name == name)
+                && *old != new
+            {
+                bumps.push(DependencyBump {
+                    ecosystem,
+                    name,
+                    old: old.clone(),
+                    new,
+                });
+            }
+        }
+    }
+    bumps
+}
+
+/// Normalizes a version requirement such as `^1.2.3` or `v1.2.3` to `1.2.3`.
+/// Returns `None` for anything that does not start with a digit after that.
+fn normalize_version(requirement: &str) -> Option<String> {
+    let version = requirement
+        .trim()
+        .trim_start_matches(['^', '~', '=', '>', '<', ' ', 'v'])
+        .split([' ', ',', '"', '\''])
+        .next()?;
+    version
+        .starts_with(|c: char| c.is_ascii_digit())
+        .then(|| version.to_string())
+}
+
+/// Parses `name = "1.2"` or `name = { version = "1.2", ... }` from `Cargo.toml`.
+fn parse_cargo(line: &str) -> Option<(String, String)> {
+    let (name, value) = line.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() || name.contains(' ') || NON_DEPENDENCY_KEYS.contains(&name) {
+        return None;
+    }
+    let value = value.trim();
+    let version = match value.strip_prefix('{') {
+        Some(table) => table
+            .split(',')
+            .find_map(|field| field.trim().strip_prefix("version"))?
+            .trim_start()
+            .strip_prefix('=')?,
+        None => value,
+    };
+    let version = normalize_version(version.trim().trim_matches('"'))?;
+    Some((name.to_string(), version))
+}
+
+/// Parses `"name": "^1.2.3",` from `package.json`.
+fn parse_package_json(line: &str) -> Option<(String, String)> {
+    let (name, value) = line.trim().split_once(':')?;
+    let name = name.trim().strip_prefix('"')?.strip_suffix('"')?;
+    if NON_DEPENDENCY_KEYS.contains(&name) {
+        return None;
+    }
+    let version = normalize_version(value.trim().trim_end_matches(',').trim_matches('"'))?;
+    Some((name.to_string(), version))
+}
+
+/// Parses `name==1.2.3` from `requirements.txt`; other specifiers have no single version.
+fn parse_requirement(line: &str) -> Option<(String, String)> {
+    let line = line.split('#').next()?.trim();
+    let (name, version) = line.split_once("==")?;
+    let name = name.split('[').next()?.trim();
+    Some((name.to_string(), normalize_version(version)?))
+}
+
+/// Parses `module v1.2.3` lines (inside or outside a `require` block) from `go.mod`.
+fn parse_go_mod(line: &str) -> Option<(String, String)> {
+    let line = line.split("//").next()?.trim();
+    let line = line.strip_prefix("require ").unwrap_or(line);
+    let mut parts = line.split_whitespace();
+    let (module, version) = (parts.next()?, parts.next()?);
+    if parts.next().is_some() || !version.starts_with('v') {
+        return None;
+    }
+    Some((module.to_string(), normalize_version(version)?))
+}
+
+/// Returns the advisories each bump fixes: those affecting the old version but not
+/// the new one. Bumps whose lookup fails are skipped with a warning.
+pub async fn fixed_advisories(
+    url: &str,
+    bumps: &[DependencyBump],
+) -> Vec<(DependencyBump, Vec<Advisory>)> {
+    let mut fixed = Vec::new();
+    for bump in bumps.iter().take(MAX_LOOKUPS) {
+        let lookup = async {
+            let old = query(url, bump, &bump.old).await?;
+            let new = query(url, bump, &bump.new).await?;
+            anyhow::Ok(
+                old.into_iter()
+                    .filter(|advisory| !new.iter().any(|n| n.id == advisory.id))
+                    .collect::<Vec<_>>(),
+            )
+        };
+        match lookup.await {
+            Ok(advisories) => fixed.push((bump.clone(), advisories)),
+            Err(e) => warn!("Could not look up advisories for {}: {:#}", bump.name, e),
+        }
+    }
+    fixed
+}
+
+/// Lists the advisories that affect one version of a package.
+#[cfg(any(feature = "gemini", feature = "ollama"))]
+async fn query(url: &str, bump: &DependencyBump, version: &str) -> anyhow::Result<Vec<Advisory>> {
+    use anyhow::Context;
+
+    #[derive(Deserialize)]
+    struct QueryResponse {
+        #[serde(default)]
+        vulns: Vec<Advisory>,
+    }
+
+    let response = reqwest::Client::new()
+        .post(url)
+        .timeout(std::time::Duration::from_secs(10))
+        .json(&serde_json::json!({
+            "version": version,
+            "package": { "name": bump.name, "ecosystem": bump.ecosystem },
+        }))
+        .send()
+        .await
+        .context("Failed to reach the OSV API")?;
+    if !response.status().is_success() {
+        anyhow::bail!("OSV API returned error: {}", response.status());
+    }
+    Ok(response.json::<QueryResponse>().await?.vulns)
+}
+
+/// Lookups need the HTTP client that is compiled in with the providers.
+#[cfg(not(any(feature = "gemini", feature = "ollama")))]
+async fn query(
+    _url: &str,
+    _bump: &DependencyBump,
+    _version: &str,
+) -> anyhow::Result<Vec<Advisory>> {
+    anyhow::bail!("Vulnerability lookups are not available in a build without AI providers")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parser, line and the expected name and version.
+    type ParserCase = (
+        ManifestParser,
+        &'static str,
+        Option<(&'static str, &'static str)>,
+    );
+
+    fn bump(ecosystem: &'static str, name: &str, old: &str, new: &str) -> DependencyBump {
+        DependencyBump {
+            ecosystem,
+            name: name.to_string(),
+            old: old.to_string(),
+            new: new.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parsers_table_driven() {
+        let cases: Vec<ParserCase> = vec![
+            (
+                parse_cargo,
+                r#"openssl = "0.10.55""#,
+                Some(("openssl", "0.10.55")),
+            ),
+            (
+                parse_cargo,
+                r#"tokio = { version = "^1.38", features = ["full"] }"#,
+                Some(("tokio", "1.38")),
+            ),
+            (parse_cargo, r#"version = "0.1.0""#, None),
+            (parse_cargo, r#"serde = { path = "../serde" }"#, None),
+            (
+                parse_package_json,
+                r#"    "lodash": "^4.17.20","#,
+                Some(("lodash", "4.17.20")),
+            ),
+            (parse_package_json, r#"  "version": "1.0.0","#, None),
+            (parse_package_json, r#"  "build": "tsc","#, None),
+            (
+                parse_requirement,
+                "Django[argon2]==4.2.1  # web",
+                Some(("Django", "4.2.1")),
+            ),
+            (parse_requirement, "requests>=2.0", None),
+            (
+                parse_go_mod,
+                "\tgolang.org/x/net v0.17.0 // indirect",
+                Some(("golang.org/x/net", "0.17.0")),
+            ),
+            (
+                parse_go_mod,
+                "require github.com/a/b v1.2.3",
+                Some(("github.com/a/b", "1.2.3")),
+            ),
+            (parse_go_mod, "go 1.22", None),
+        ];
+        for (parse, line, expected) in cases {
+            let parsed = parse(line);
+            assert_eq!(
+                parsed.as_ref().map(|(n, v)| (n.as_str(), v.as_str())),
+                expected,
+                "line: {}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn test_bumps() {
+        let diff = r#"diff --git a/Cargo.toml b/Cargo.toml
+--- a/Cargo.toml
++++ b/Cargo.toml
+@@ -1,6 +1,6 @@
+-version = "0.1.0"
++version = "0.2.0"
+-openssl = "0.10.55"
++openssl = "0.10.66"
+-serde = "1.0"
++serde = { version = "1.0", features = ["derive"] }
++rand = "0.8"
+diff --git a/web/package.json b/web/package.json
+-    "lodash": "4.17.20",
++    "lodash": "4.17.21",
+diff --git a/src/main.rs b/src/main.rs
+-let a = "1.0";
++let a = "2.0";
+"#;
+        assert_eq!(
+            bumps(diff),
+            vec![
+                bump("crates.io", "openssl", "0.10.55", "0.10.66"),
+                bump("npm", "lodash", "4.17.20", "4.17.21"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let advisory = |id: &str, aliases: &[&str]| Advisory {
+            id: id.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+        };
+        let fixed = vec![
+            (bump("npm", "left-pad", "1.0.0", "1.1.0"), vec![]),
+            (
+                bump("crates.io", "openssl", "0.10.55", "0.10.66"),
+                vec![
+                    advisory("RUSTSEC-2023-0044", &["CVE-2023-1234", "GHSA-xxxx"]),
+                    advisory("GHSA-yyyy", &[]),
+                ],
+            ),
+        ];
+        assert_eq!(
+            render(&fixed).unwrap(),
+            "[FIXED ADVISORIES]\nThe dependency updates in this change fix known vulnerabilities:\n\
+             - openssl 0.10.55 -> 0.10.66: RUSTSEC-2023-0044 (CVE-2023-1234), GHSA-yyyy\n\
+             Mention each one in the body as \"fixes <ID>\", e.g. \"fixes RUSTSEC-2023-0044\"."
+        );
+        assert_eq!(render(&fixed[..1]), None);
+    }
+
+    #[cfg(feature = "ollama")]
+    #[tokio::test]
+    async fn test_fixed_advisories_compares_versions() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Answers every query: the old version has two advisories, the new one has one
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/v1/query", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = String::new();
+                let mut buffer = vec![0; 4096];
+                // The JSON body is the end of the request
+                while !request.ends_with('}') {
+                    let read = stream.read(&mut buffer).await.unwrap();
+                    request.push_str(&String::from_utf8_lossy(&buffer[..read]));
+                }
+                let body = if request.contains("\"0.10.55\"") {
+                    r#"{"vulns":[{"id":"RUSTSEC-1","aliases":["CVE-1"]},{"id":"RUSTSEC-2"}]}"#
+                } else {
+                    r#"{"vulns":[{"id":"RUSTSEC-2"}]}"#
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        let bumps = vec![bump("crates.io", "openssl", "0.10.55", "0.10.66")];
+        let fixed = fixed_advisories(&url, &bumps).await;
+        assert_eq!(fixed.len(), 1);
+        let ids: Vec<&str> = fixed[0].1.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, vec!["RUSTSEC-1"]);
+
+        // Unreachable servers are skipped
+        assert!(
+            fixed_advisories("http://127.0.0.1:1/v1/query", &bumps)
+                .await
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! Oversized file diffs.
+//!
+//! One enormous file, such as a regenerated SQL dump or a large fixture, can use up
+//! the whole diff budget, so truncation leaves no room for the rest of the change.
+//! With `max_file_diff_length` set, the diff of a file longer than that is left out,
+//! and a note describes the file in one line with its line counts instead.
+
+use super::stat;
+use crate::binary_files::human_size;
+use crate::diff_source::{file_sections, preamble};
+
+/// Leaves out the file sections of `diff` longer than `max_length` bytes; 0 keeps
+/// them all. Returns the diff and a note on what was left out, or `None` when
+/// nothing was.
+pub fn collapse(diff: &str, max_length: usize) -> (String, Option<String>) {
+    if max_length == 0 {
+        return (diff.to_string(), None);
+    }
+    let mut kept = preamble(diff).to_string();
+    let mut lines = Vec::new();
+    for (path, section) in file_sections(diff) {
+        if section.len() <= max_length {
+            kept.push_str(section);
+            continue;
+        }
+        let size = human_size(section.len() as u64);
+        lines.push(match stat::counts(section) {
+            Some((added, removed)) => {
+                format!("- {} (+{} -{}, {} diff)", path, added, removed, size)
+            }
+            None => format!("- {} (binary, {} diff)", path, size),
+        });
+    }
+    let note = (!lines.is_empty()).then(|| {
+        format!(
+            "[OVERSIZED FILES]\nThe diffs of these files were too long to show; mention them \
+             from their path and line counts:\n{}",
+            lines.join("\n")
+        )
+    });
+    (kept, note)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(path: &str, added: usize) -> String {
+        format!(
+            "diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n@@ -1 +1,{added} @@\n-old\n{}",
+            "+row\n".repeat(added)
+        )
+    }
+
+    #[test]
+    fn test_collapse_table_driven() {
+        let code = section("src/lib.rs", 2);
+        let dump = section("db/dump.sql", 1000);
+        let diff = format!("{}{}", dump, code);
+        let heading = "[OVERSIZED FILES]\nThe diffs of these files were too long to show; \
+                       mention them from their path and line counts:\n";
+        let dump_line = "- db/dump.sql (+1000 -1, 5.0 KB diff)";
+        let cases = [
+            (0, diff.clone(), None),
+            (10_000, diff.clone(), None),
+            (
+                1000,
+                code.clone(),
+                Some(format!("{}{}", heading, dump_line)),
+            ),
+            (
+                10,
+                String::new(),
+                Some(format!(
+                    "{}{}\n- src/lib.rs (+2 -1, 100 B diff)",
+                    heading, dump_line
+                )),
+            ),
+        ];
+        for (max_length, expected, expected_note) in cases {
+            let (kept, note) = collapse(&diff, max_length);
+            assert_eq!(kept, expected, "{}", max_length);
+            assert_eq!(note, expected_note, "{}", max_length);
+        }
+    }
+}
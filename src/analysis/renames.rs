@@ -0,0 +1,123 @@
+//! Renamed and copied files.
+//!
+//! Diffs are read with rename and copy detection, so a moved file shows up as a
+//! `rename from`/`rename to` header instead of a deletion and a new file. The header
+//! is easy to miss in a long diff, and a truncated diff may leave it out entirely, so
+//! the moves are listed up front and the model is asked not to describe them as
+//! removals.
+
+use super::DiffAnalyzer;
+use crate::diff_source::file_sections;
+
+/// Largest number of moves listed; the rest is summarized as a count.
+const MAX_ENTRIES: usize = 20;
+
+/// Lists renamed and copied files.
+pub struct RenamedFiles;
+
+impl DiffAnalyzer for RenamedFiles {
+    fn name(&self) -> &'static str {
+        "renames"
+    }
+
+    fn analyze(&self, diff: &str) -> Option<String> {
+        let entries: Vec<String> = file_sections(diff)
+            .into_iter()
+            .filter_map(|(_, section)| describe(section))
+            .collect();
+        if entries.is_empty() {
+            return None;
+        }
+
+        let mut lines: Vec<String> = entries
+            .iter()
+            .take(MAX_ENTRIES)
+            .map(|entry| format!("- {}", entry))
+            .collect();
+        if entries.len() > MAX_ENTRIES {
+            lines.push(format!("- ... and {} more", entries.len() - MAX_ENTRIES));
+        }
+        Some(format!(
+            "[RENAMES AND COPIES]\nThese files were moved or copied, not deleted and created \
+             anew; describe them as renames or copies, not as removed files:\n{}",
+            lines.join("\n")
+        ))
+    }
+}
+
+/// Describes the rename or copy in a file section's header, or returns `None` when
+/// the section is neither.
+fn describe(section: &str) -> Option<String> {
+    let mut similarity = None;
+    let mut from = None;
+    let mut to = None;
+    let mut verb = "renamed";
+    // Header lines end where the first hunk starts
+    for line in section
+        .lines()
+        .skip(1)
+        .take_while(|line| !line.starts_with("@@"))
+    {
+        if let Some(value) = line.strip_prefix("similarity index ") {
+            similarity = value.strip_suffix('%').and_then(|v| v.parse::<u32>().ok());
+        } else if let Some(path) = line.strip_prefix("rename from ") {
+            from = Some(path);
+        } else if let Some(path) = line.strip_prefix("rename to ") {
+            to = Some(path);
+        } else if let Some(path) = line.strip_prefix("copy from ") {
+            (from, verb) = (Some(path), "copied");
+        } else if let Some(path) = line.strip_prefix("copy to ") {
+            to = Some(path);
+        }
+    }
+    let detail = match similarity {
+        Some(100) => "unchanged".to_string(),
+        Some(similarity) => format!("{}% similar", similarity),
+        None => "with changes".to_string(),
+    };
+    Some(format!("{} {} to {} ({})", from?, verb, to?, detail))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_table_driven() {
+        let cases = [
+            (
+                "diff --git a/src/old.rs b/src/new.rs\nsimilarity index 100%\n\
+                 rename from src/old.rs\nrename to src/new.rs\n",
+                Some("src/old.rs renamed to src/new.rs (unchanged)"),
+            ),
+            (
+                "diff --git a/a.rs b/b.rs\nsimilarity index 87%\nrename from a.rs\n\
+                 rename to b.rs\nindex 1..2 100644\n--- a/a.rs\n+++ b/b.rs\n@@ -1 +1 @@\n\
+                 -rename from x\n+rename to y\n",
+                Some("a.rs renamed to b.rs (87% similar)"),
+            ),
+            (
+                "diff --git a/a.rs b/c.rs\nsimilarity index 92%\ncopy from a.rs\ncopy to c.rs\n",
+                Some("a.rs copied to c.rs (92% similar)"),
+            ),
+            (
+                "diff --git a/a.rs b/a.rs\nindex 1..2 100644\n--- a/a.rs\n+++ b/a.rs\n\
+                 @@ -1 +1 @@\n-a\n+b\n",
+                None,
+            ),
+        ];
+        for (section, expected) in cases {
+            assert_eq!(describe(section).as_deref(), expected, "{}", section);
+        }
+    }
+
+    #[test]
+    fn test_analyze_lists_moves() {
+        let diff = "diff --git a/a.rs b/b.rs\nsimilarity index 100%\nrename from a.rs\n\
+                    rename to b.rs\ndiff --git a/c.rs b/c.rs\n@@ -1 +1 @@\n-c\n+d\n";
+        let notes = RenamedFiles.analyze(diff).unwrap();
+        assert!(notes.starts_with("[RENAMES AND COPIES]\n"));
+        assert!(notes.ends_with(":\n- a.rs renamed to b.rs (unchanged)"));
+        assert_eq!(RenamedFiles.analyze("diff --git a/c.rs b/c.rs\n+d\n"), None);
+    }
+}
@@ -0,0 +1,305 @@
+//! Security-sensitive change detection.
+//!
+//! Flags changes that touch authentication, cryptography or permissions, and
+//! dependency updates that look like security patches, so the model calls them
+//! out with a `Security:` trailer that security teams can grep for.
+
+use super::DiffAnalyzer;
+use crate::diff_source::file_sections;
+use std::path::Path;
+use tracing::warn;
+
+/// Words that mark a security-relevant area, by category. A trailing `*` matches
+/// any word starting with the stem.
+const CATEGORIES: &[(&str, &[&str])] = &[
+    (
+        "authentication",
+        &[
+            "auth",
+            "authn",
+            "authz",
+            "authenticat*",
+            "authoriz*",
+            "login",
+            "logout",
+            "passw*",
+            "session*",
+            "oauth*",
+            "jwt",
+            "saml",
+            "sso",
+            "credential*",
+            "mfa",
+            "totp",
+            "csrf",
+        ],
+    ),
+    (
+        "cryptography",
+        &[
+            "crypt*",
+            "encrypt*",
+            "decrypt*",
+            "cipher*",
+            "hmac",
+            "sha1",
+            "md5",
+            "aes",
+            "rsa",
+            "ecdsa",
+            "ed25519",
+            "tls",
+            "ssl",
+            "x509",
+            "cert",
+            "certs",
+            "certificate*",
+            "bcrypt",
+            "argon2",
+            "pbkdf2",
+            "nonce",
+        ],
+    ),
+    (
+        "permissions",
+        &[
+            "permission*",
+            "chmod",
+            "chown",
+            "acl",
+            "acls",
+            "rbac",
+            "sudo*",
+            "setuid",
+            "privilege*",
+            "iam",
+        ],
+    ),
+];
+
+/// Dependency manifests whose version changes may be security patches.
+const MANIFESTS: &[&str] = &[
+    "Cargo.toml",
+    "package.json",
+    "requirements.txt",
+    "pyproject.toml",
+    "go.mod",
+    "Gemfile",
+    "pom.xml",
+    "build.gradle",
+    "build.gradle.kts",
+    "composer.json",
+];
+
+/// Packages whose updates are usually security-relevant.
+const SECURITY_PACKAGES: &[&str] = &[
+    "openssl",
+    "rustls",
+    "ring",
+    "jsonwebtoken",
+    "cryptography",
+    "pyjwt",
+    "bcrypt",
+    "passport",
+    "helmet",
+    "nokogiri",
+    "log4j",
+];
+
+/// Prefixes of advisory identifiers that mark a dependency security patch.
+const ADVISORY_PREFIXES: &[&str] = &["cve-", "ghsa-", "rustsec-"];
+
+/// Instructions appended to the findings.
+const INSTRUCTIONS: &str = "Call this out in the message: end the body with a trailer line \
+\"Security: <what changed and why it matters>\".";
+
+/// Flags security-sensitive changes.
+pub struct SecuritySensitive;
+
+impl DiffAnalyzer for SecuritySensitive {
+    fn name(&self) -> &'static str {
+        "security"
+    }
+
+    fn analyze(&self, diff: &str) -> Option<String> {
+        let findings = findings(diff);
+        if findings.is_empty() {
+            return None;
+        }
+
+        let mut categories: Vec<&str> = findings.iter().map(|(category, _)| *category).collect();
+        categories.dedup();
+        warn!(
+            "This change looks security-sensitive ({}); asking for a Security: trailer.",
+            categories.join(", ")
+        );
+
+        let lines: Vec<String> = findings
+            .iter()
+            .map(|(category, path)| format!("- {}: {}", category, path))
+            .collect();
+        Some(format!(
+            "[SECURITY-SENSITIVE CHANGE]\nThis change looks security-relevant:\n{}\n{}",
+            lines.join("\n"),
+            INSTRUCTIONS
+        ))
+    }
+}
+
+/// Lists `(category, path)` pairs for every flagged file, grouped by category.
+fn findings(diff: &str) -> Vec<(&'static str, String)> {
+    let mut findings = Vec::new();
+    for (path, section) in file_sections(diff) {
+        let changed: Vec<&str> = section
+            .lines()
+            .filter(|line| {
+                (line.starts_with('+') || line.starts_with('-'))
+                    && !line.starts_with("+++")
+                    && !line.starts_with("---")
+            })
+            .map(|line| &line[1..])
+            .collect();
+        let path_words = words(path);
+        let changed_words: Vec<String> = changed.iter().flat_map(|line| words(line)).collect();
+
+        for (category, stems) in CATEGORIES {
+            if path_words
+                .iter()
+                .chain(&changed_words)
+                .any(|word| matches_stem(word, stems))
+            {
+                findings.push((*category, path.to_string()));
+            }
+        }
+        if is_security_patch(path, &changed, &changed_words) {
+            findings.push(("dependency security patch", path.to_string()));
+        }
+    }
+    findings.sort_by_key(|(category, _)| {
+        CATEGORIES
+            .iter()
+            .position(|(name, _)| name == category)
+            .unwrap_or(CATEGORIES.len())
+    });
+    findings
+}
+
+/// Whether a manifest change updates a security-relevant package, or any changed
+/// line mentions an advisory identifier.
+fn is_security_patch(path: &str, changed: &[&str], changed_words: &[String]) -> bool {
+    let advisory = changed.iter().any(|line| {
+        let line = line.to_lowercase();
+        ADVISORY_PREFIXES.iter().any(|prefix| line.contains(prefix))
+    });
+    let name = Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
+    let manifest = MANIFESTS.contains(&name.as_ref())
+        && changed_words
+            .iter()
+            .any(|word| SECURITY_PACKAGES.contains(&word.as_str()));
+    advisory || manifest
+}
+
+/// Whether `word` equals one of `stems`, or starts with a stem ending in `*`.
+fn matches_stem(word: &str, stems: &[&str]) -> bool {
+    stems.iter().any(|stem| match stem.strip_suffix('*') {
+        Some(prefix) => word.starts_with(prefix),
+        None => word == *stem,
+    })
+}
+
+/// Splits text into lowercase words at punctuation and camelCase boundaries,
+/// e.g. `verifyPassword(AUTH_TOKEN)` into `verify`, `password`, `auth`, `token`.
+fn words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in text.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let mut word = String::new();
+        let mut previous_lower = false;
+        for c in part.chars() {
+            if c.is_ascii_uppercase() && previous_lower {
+                words.push(std::mem::take(&mut word));
+            }
+            previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+            word.push(c.to_ascii_lowercase());
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words() {
+        assert_eq!(
+            words("verifyPassword(AUTH_TOKEN) sha256"),
+            vec!["verify", "password", "auth", "token", "sha256"]
+        );
+        assert!(words("  ").is_empty());
+    }
+
+    #[test]
+    fn test_findings_table_driven() {
+        let cases = vec![
+            (
+                "diff --git a/src/auth/login.rs b/src/auth/login.rs\n+fn check() {}\n",
+                vec![("authentication", "src/auth/login.rs")],
+            ),
+            (
+                "diff --git a/src/store.rs b/src/store.rs\n+let digest = Md5::new();\n",
+                vec![("cryptography", "src/store.rs")],
+            ),
+            (
+                "diff --git a/deploy.sh b/deploy.sh\n-chmod 600 key\n+chmod 644 key\n",
+                vec![("permissions", "deploy.sh")],
+            ),
+            (
+                "diff --git a/Cargo.toml b/Cargo.toml\n-openssl = \"0.10.55\"\n+openssl = \"0.10.66\"\n",
+                vec![("dependency security patch", "Cargo.toml")],
+            ),
+            (
+                "diff --git a/package.json b/package.json\n+  \"lodash\": \"4.17.21\", // GHSA-35jh-r3h4-6jhm\n",
+                vec![("dependency security patch", "package.json")],
+            ),
+            // Context lines and look-alike words are not flagged
+            (
+                "diff --git a/src/ui.rs b/src/ui.rs\n fn login() {}\n+let certain = authorship;\n",
+                vec![],
+            ),
+            (
+                "diff --git a/Cargo.toml b/Cargo.toml\n-serde = \"1.0.1\"\n+serde = \"1.0.2\"\n",
+                vec![],
+            ),
+        ];
+
+        for (diff, expected) in cases {
+            let found = findings(diff);
+            let found: Vec<(&str, &str)> = found.iter().map(|(c, p)| (*c, p.as_str())).collect();
+            assert_eq!(found, expected, "diff: {}", diff);
+        }
+    }
+
+    #[test]
+    fn test_analyze() {
+        let diff = "diff --git a/src/session.rs b/src/session.rs\n+let key = aes_key();\n";
+        let notes = SecuritySensitive.analyze(diff).unwrap();
+        assert_eq!(
+            notes,
+            format!(
+                "[SECURITY-SENSITIVE CHANGE]\nThis change looks security-relevant:\n\
+                 - authentication: src/session.rs\n- cryptography: src/session.rs\n{}",
+                INSTRUCTIONS
+            )
+        );
+        assert_eq!(
+            SecuritySensitive.analyze("diff --git a/a.rs b/a.rs\n+x\n"),
+            None
+        );
+    }
+}
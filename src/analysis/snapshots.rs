@@ -0,0 +1,216 @@
+//! Snapshot test churn.
+//!
+//! Jest and insta snapshots and golden files change wholesale whenever the output
+//! they record changes, and their diffs can use up the whole diff budget while
+//! telling the model nothing new. Their diffs are left out and summarized as
+//! "updated N snapshots for X".
+
+use super::DiffAnalyzer;
+use crate::diff_source::{file_sections, without_sections};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Summarizes changed snapshot files.
+pub struct SnapshotChanges;
+
+impl DiffAnalyzer for SnapshotChanges {
+    fn name(&self) -> &'static str {
+        "snapshots"
+    }
+
+    fn analyze(&self, diff: &str) -> Option<String> {
+        // (verb, owner, snapshot count), in order of first appearance
+        let mut groups: Vec<(&str, String, usize)> = Vec::new();
+        for (path, section) in file_sections(diff) {
+            if !is_snapshot(path) {
+                continue;
+            }
+            let verb = if section.contains("\nnew file mode") {
+                "added"
+            } else if section.contains("\ndeleted file mode") {
+                "removed"
+            } else {
+                "updated"
+            };
+            let owner = owner(path);
+            let count = snapshot_count(section);
+            match groups
+                .iter_mut()
+                .find(|(v, o, _)| *v == verb && *o == owner)
+            {
+                Some(group) => group.2 += count,
+                None => groups.push((verb, owner, count)),
+            }
+        }
+        if groups.is_empty() {
+            return None;
+        }
+
+        let lines: Vec<String> = groups
+            .iter()
+            .map(|(verb, owner, count)| {
+                let noun = if *count == 1 { "snapshot" } else { "snapshots" };
+                format!("- {} {} {} for {}", verb, count, noun, owner)
+            })
+            .collect();
+        Some(format!(
+            "[SNAPSHOT UPDATES]\nThe diffs of these snapshot files were left out; mention them \
+             in one line at most instead of describing their contents:\n{}",
+            lines.join("\n")
+        ))
+    }
+}
+
+/// Removes the sections of snapshot files from `diff`.
+pub fn collapse(diff: &str) -> String {
+    without_sections(diff, |path, _| is_snapshot(path))
+}
+
+/// Whether `path` is a Jest or insta snapshot or a golden file.
+fn is_snapshot(path: &str) -> bool {
+    path.ends_with(".snap")
+        || path.ends_with(".snap.new")
+        || path.ends_with(".golden")
+        || path.contains("__snapshots__/")
+        || path
+            .split('/')
+            .any(|part| part == "golden" || part == "goldens")
+}
+
+/// What the snapshots belong to: the test file for Jest (`Button.test.tsx` from
+/// `__snapshots__/Button.test.tsx.snap`), the module for insta
+/// (`asum::parser::tests` from `snapshots/asum__parser__tests__empty.snap`), and
+/// the directory for golden files.
+fn owner(path: &str) -> String {
+    let file = Path::new(path);
+    let name = file
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let stem = name
+        .strip_suffix(".snap.new")
+        .or_else(|| name.strip_suffix(".snap"))
+        .unwrap_or(&name);
+
+    if path.contains("__snapshots__/") {
+        return stem.to_string();
+    }
+    if name.ends_with(".snap") || name.ends_with(".snap.new") {
+        return match stem.rsplit_once("__") {
+            Some((module, _)) => module.replace("__", "::"),
+            None => stem.to_string(),
+        };
+    }
+    file.parent()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .filter(|dir| !dir.is_empty())
+        .unwrap_or(name)
+}
+
+/// Number of snapshots changed in a file section: the changed `exports[...]`
+/// entries of a Jest snapshot file, or 1 for files holding a single snapshot.
+fn snapshot_count(section: &str) -> usize {
+    let mut changed = BTreeSet::new();
+    let mut current: Option<&str> = None;
+    for line in section.lines() {
+        // Hunk headers repeat the enclosing entry after the second `@@`
+        let content = match line.strip_prefix("@@") {
+            Some(header) => header.split_once("@@").map_or("", |(_, rest)| rest.trim()),
+            None if line.starts_with("+++") || line.starts_with("---") => continue,
+            None => line.get(1..).unwrap_or(""),
+        };
+        if let Some(entry) = content.strip_prefix("exports[") {
+            current = entry.split_once(']').map(|(name, _)| name);
+        }
+        if (line.starts_with('+') || line.starts_with('-'))
+            && let Some(entry) = current
+        {
+            changed.insert(entry);
+        }
+    }
+    changed.len().max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JEST: &str = "diff --git a/src/__snapshots__/Button.test.tsx.snap b/src/__snapshots__/Button.test.tsx.snap
+--- a/src/__snapshots__/Button.test.tsx.snap
++++ b/src/__snapshots__/Button.test.tsx.snap
+@@ -1,5 +1,5 @@
+ exports[`Button renders 1`] = `
+-<button class=\"a\">
++<button class=\"b\">
+ `;
+
+ exports[`Button disabled 1`] = `
+@@ -20,3 +20,3 @@ exports[`Button hover 1`] = `
+-  color: red;
++  color: blue;
+";
+
+    #[test]
+    fn test_is_snapshot_table_driven() {
+        let cases = vec![
+            ("src/__snapshots__/Button.test.tsx.snap", true),
+            ("tests/snapshots/asum__parser__tests__empty.snap", true),
+            ("tests/snapshots/asum__parser__tests__empty.snap.new", true),
+            ("testdata/render.golden", true),
+            ("internal/golden/output.txt", true),
+            ("src/snapshot.rs", false),
+            ("src/goldenrod.css", false),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(is_snapshot(path), expected, "path: {}", path);
+        }
+    }
+
+    #[test]
+    fn test_owner_table_driven() {
+        let cases = vec![
+            ("src/__snapshots__/Button.test.tsx.snap", "Button.test.tsx"),
+            (
+                "tests/snapshots/asum__parser__tests__empty.snap",
+                "asum::parser::tests",
+            ),
+            ("snapshots/plain.snap", "plain"),
+            ("testdata/render.golden", "testdata"),
+            ("render.golden", "render.golden"),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(owner(path), expected, "path: {}", path);
+        }
+    }
+
+    #[test]
+    fn test_snapshot_count() {
+        assert_eq!(snapshot_count(JEST), 2);
+        assert_eq!(
+            snapshot_count("diff --git a/a.snap b/a.snap\n-old\n+new\n"),
+            1
+        );
+    }
+
+    #[test]
+    fn test_analyze_and_collapse() {
+        let code = "diff --git a/src/Button.tsx b/src/Button.tsx\n+export const b = 1;\n";
+        let insta = "diff --git a/tests/snapshots/asum__cli__tests__help.snap b/tests/snapshots/asum__cli__tests__help.snap
+new file mode 100644
++---
++source: src/cli.rs
++---
+";
+        let diff = format!("{}{}{}", JEST, code, insta);
+
+        assert_eq!(
+            SnapshotChanges.analyze(&diff).unwrap(),
+            "[SNAPSHOT UPDATES]\nThe diffs of these snapshot files were left out; mention them \
+             in one line at most instead of describing their contents:\n\
+             - updated 2 snapshots for Button.test.tsx\n\
+             - added 1 snapshot for asum::cli::tests"
+        );
+        assert_eq!(collapse(&diff), code);
+        assert_eq!(SnapshotChanges.analyze(code), None);
+    }
+}
@@ -0,0 +1,115 @@
+//! Per-file line counts, like `git diff --stat`.
+//!
+//! When a diff is truncated or split into chunks, the model only sees part of it and
+//! can mistake that part for the whole change. A short table of every changed file
+//! with its added and removed line counts goes in front of the diff, so the overall
+//! shape of the change survives truncation.
+
+use crate::diff_source::file_sections;
+
+/// Largest number of files listed in the header; the rest are counted in one line.
+const MAX_FILES: usize = 40;
+
+/// Added and removed lines of one file section, or `None` for a binary file.
+pub fn counts(section: &str) -> Option<(usize, usize)> {
+    let mut added = 0;
+    let mut removed = 0;
+    let mut in_hunk = false;
+    for line in section.lines() {
+        if line.starts_with("@@") {
+            in_hunk = true;
+        } else if !in_hunk {
+            if line.starts_with("Binary files ") || line == "GIT binary patch" {
+                return None;
+            }
+        } else if line.starts_with('+') {
+            added += 1;
+        } else if line.starts_with('-') {
+            removed += 1;
+        }
+    }
+    Some((added, removed))
+}
+
+/// Returns the `[DIFF STAT]` section for `diff`, or `None` when it changes no files.
+pub fn header(diff: &str) -> Option<String> {
+    let files: Vec<(&str, Option<(usize, usize)>)> = file_sections(diff)
+        .into_iter()
+        .map(|(path, section)| (path, counts(section)))
+        .collect();
+    if files.is_empty() {
+        return None;
+    }
+    let (added, removed) = files
+        .iter()
+        .filter_map(|(_, counts)| *counts)
+        .fold((0, 0), |(a, r), (added, removed)| (a + added, r + removed));
+    let listed = &files[..files.len().min(MAX_FILES)];
+    let width = listed.iter().map(|(path, _)| path.len()).max().unwrap_or(0);
+    let mut lines = vec![format!(
+        "{} file(s) changed, {} insertion(s)(+), {} deletion(s)(-)",
+        files.len(),
+        added,
+        removed
+    )];
+    lines.extend(listed.iter().map(|(path, counts)| match counts {
+        Some((added, removed)) => format!("{:width$} | +{} -{}", path, added, removed),
+        None => format!("{:width$} | binary", path),
+    }));
+    if files.len() > MAX_FILES {
+        lines.push(format!("... and {} more file(s)", files.len() - MAX_FILES));
+    }
+    Some(format!(
+        "[DIFF STAT]\nEvery changed file with its added and removed lines, even if the diff \
+         below is cut short:\n{}",
+        lines.join("\n")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_table_driven() {
+        let cases = [
+            ("", None),
+            (
+                "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n\
+                 @@ -1,3 +1,3 @@\n-old\n+new\n+more\n context\n--- not a header\n\
+                 diff --git a/logo.png b/logo.png\nBinary files a/logo.png and b/logo.png differ\n",
+                Some(
+                    "2 file(s) changed, 2 insertion(s)(+), 2 deletion(s)(-)\n\
+                     src/lib.rs | +2 -2\n\
+                     logo.png   | binary",
+                ),
+            ),
+            (
+                "diff --git a/a.rs b/a.rs\nnew file mode 100644\n--- /dev/null\n+++ b/a.rs\n\
+                 @@ -0,0 +1 @@\n+fn a() {}\n",
+                Some("1 file(s) changed, 1 insertion(s)(+), 0 deletion(s)(-)\na.rs | +1 -0"),
+            ),
+        ];
+        for (diff, expected) in cases {
+            let header = header(diff);
+            match expected {
+                Some(table) => {
+                    let header = header.unwrap();
+                    assert!(header.starts_with("[DIFF STAT]\n"), "{}", header);
+                    assert!(header.ends_with(table), "{}", header);
+                }
+                None => assert_eq!(header, None),
+            }
+        }
+    }
+
+    #[test]
+    fn test_header_lists_at_most_max_files() {
+        let diff: String = (0..MAX_FILES + 3)
+            .map(|i| format!("diff --git a/f{i} b/f{i}\n@@ -1 +1 @@\n-a\n+b\n"))
+            .collect();
+        let header = header(&diff).unwrap();
+        assert_eq!(header.matches(" | +1 -1").count(), MAX_FILES);
+        assert!(header.ends_with("\n... and 3 more file(s)"));
+    }
+}
@@ -0,0 +1,377 @@
+//! Anonymized diff mode for ASUM (experimental).
+//!
+//! Consistently renames identifiers and string literals in the code lines of a
+//! diff before it is sent to the provider, keeping keywords, punctuation and the
+//! diff structure intact. Placeholders found in the generated message are mapped
+//! back to the original names. File paths in the diff headers are sent as is.
+
+use std::collections::HashMap;
+
+/// Words that are kept as is because they carry structure rather than project
+/// specific information: keywords of common languages and well-known std types.
+const KEPT_WORDS: &[&str] = &[
+    "and",
+    "as",
+    "async",
+    "await",
+    "bool",
+    "boolean",
+    "break",
+    "case",
+    "catch",
+    "chan",
+    "char",
+    "class",
+    "const",
+    "continue",
+    "crate",
+    "def",
+    "default",
+    "defer",
+    "del",
+    "double",
+    "dyn",
+    "elif",
+    "else",
+    "enum",
+    "Err",
+    "except",
+    "export",
+    "extends",
+    "extern",
+    "false",
+    "False",
+    "final",
+    "finally",
+    "float",
+    "fn",
+    "for",
+    "from",
+    "func",
+    "function",
+    "global",
+    "impl",
+    "import",
+    "in",
+    "instanceof",
+    "int",
+    "interface",
+    "is",
+    "lambda",
+    "let",
+    "long",
+    "loop",
+    "map",
+    "match",
+    "mod",
+    "move",
+    "mut",
+    "new",
+    "nil",
+    "None",
+    "not",
+    "null",
+    "Ok",
+    "Option",
+    "or",
+    "package",
+    "pass",
+    "private",
+    "protected",
+    "pub",
+    "public",
+    "raise",
+    "range",
+    "ref",
+    "Result",
+    "return",
+    "self",
+    "Self",
+    "Some",
+    "static",
+    "str",
+    "string",
+    "String",
+    "struct",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "trait",
+    "true",
+    "True",
+    "try",
+    "type",
+    "typeof",
+    "undefined",
+    "unsafe",
+    "use",
+    "var",
+    "Vec",
+    "void",
+    "where",
+    "while",
+    "with",
+    "yield",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+    "f32",
+    "f64",
+    "usize",
+    "isize",
+];
+
+/// Prefix of identifier placeholders (`ident1`, `ident2`, ...).
+const IDENT_PREFIX: &str = "ident";
+
+/// Prefix of string literal placeholders (`text1`, `text2`, ...).
+const TEXT_PREFIX: &str = "text";
+
+/// Consistent mapping between original names and placeholders for one diff.
+#[derive(Debug, Default)]
+pub struct Anonymizer {
+    placeholders: HashMap<String, String>,
+    originals: HashMap<String, String>,
+    idents: usize,
+    texts: usize,
+}
+
+impl Anonymizer {
+    /// Number of distinct identifiers and strings that were replaced.
+    pub fn replaced(&self) -> usize {
+        self.placeholders.len()
+    }
+
+    /// Returns `diff` with identifiers and string literals replaced in added, removed
+    /// and context lines and in the function context of hunk headers.
+    pub fn anonymize_diff(&mut self, diff: &str) -> String {
+        let mut in_hunk = false;
+        diff.lines()
+            .map(|line| {
+                if line.starts_with("diff ") {
+                    in_hunk = false;
+                    line.to_string()
+                } else if !in_hunk && (line.starts_with("+++ ") || line.starts_with("--- ")) {
+                    line.to_string()
+                } else if let Some(code) = line.strip_prefix(['+', '-', ' ']) {
+                    format!("{}{}", &line[..1], self.anonymize_code(code))
+                } else if let Some(header) = line.strip_prefix("@@") {
+                    in_hunk = true;
+                    match header.find("@@") {
+                        Some(end) => {
+                            let (range, context) = line.split_at(end + 4);
+                            format!("{}{}", range, self.anonymize_code(context))
+                        }
+                        None => line.to_string(),
+                    }
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Replaces the placeholders in a generated `message` with the original names.
+    pub fn restore(&self, message: &str) -> String {
+        map_words(message, |word| {
+            self.originals
+                .get(word)
+                .cloned()
+                .unwrap_or_else(|| word.to_string())
+        })
+    }
+
+    /// Anonymizes one line of code: double-quoted string literals first, then words.
+    fn anonymize_code(&mut self, code: &str) -> String {
+        let mut out = String::new();
+        let mut rest = code;
+
+        while let Some(start) = rest.find('"') {
+            let Some(len) = closing_quote(&rest[start + 1..]) else {
+                break;
+            };
+            let (before, literal) = rest.split_at(start);
+            out.push_str(&self.anonymize_words(before));
+
+            let content = &literal[1..1 + len];
+            out.push('"');
+            if !content.is_empty() {
+                let placeholder = self.placeholder(content, TEXT_PREFIX);
+                out.push_str(&placeholder);
+            }
+            out.push('"');
+            rest = &literal[len + 2..];
+        }
+
+        out.push_str(&self.anonymize_words(rest));
+        out
+    }
+
+    /// Replaces every word that is not a kept word, a number or shorter than three characters.
+    fn anonymize_words(&mut self, text: &str) -> String {
+        map_words(text, |word| {
+            let kept = word.chars().count() < 3
+                || word.starts_with(|c: char| c.is_ascii_digit())
+                || KEPT_WORDS.contains(&word);
+            if kept {
+                word.to_string()
+            } else {
+                self.placeholder(word, IDENT_PREFIX)
+            }
+        })
+    }
+
+    /// Returns the placeholder for `original`, creating one with `prefix` if needed.
+    fn placeholder(&mut self, original: &str, prefix: &str) -> String {
+        if let Some(existing) = self.placeholders.get(original) {
+            return existing.clone();
+        }
+        let counter = if prefix == IDENT_PREFIX {
+            &mut self.idents
+        } else {
+            &mut self.texts
+        };
+        *counter += 1;
+        let placeholder = format!("{}{}", prefix, counter);
+        self.placeholders
+            .insert(original.to_string(), placeholder.clone());
+        self.originals
+            .insert(placeholder.clone(), original.to_string());
+        placeholder
+    }
+}
+
+/// Returns the byte length of a string literal's content up to its closing quote,
+/// skipping backslash escapes, or `None` if the literal is not closed on this line.
+fn closing_quote(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Rebuilds `text` with every word (a run of alphanumeric characters and `_`)
+/// passed through `replace`.
+fn map_words<F: FnMut(&str) -> String>(text: &str, mut replace: F) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut word_start = None;
+
+    for (i, c) in text.char_indices() {
+        let is_word = c.is_alphanumeric() || c == '_';
+        match (is_word, word_start) {
+            (true, None) => word_start = Some(i),
+            (false, Some(start)) => {
+                out.push_str(&replace(&text[start..i]));
+                out.push(c);
+                word_start = None;
+            }
+            (false, None) => out.push(c),
+            (true, Some(_)) => {}
+        }
+    }
+    if let Some(start) = word_start {
+        out.push_str(&replace(&text[start..]));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/src/billing.rs b/src/billing.rs
+--- a/src/billing.rs
++++ b/src/billing.rs
+@@ -1,3 +1,4 @@ fn compute_invoice_total(order: &Order) -> u64 {
+     let subtotal = order.items.len();
+-    let discount = 0;
++    let discount = apply_vip_discount(order, \"ACME \\\"gold\\\" tier\");
++    // Applies the partner discount
+     subtotal - discount";
+
+    #[test]
+    fn test_anonymize_diff_hides_names_and_keeps_structure() {
+        let mut anonymizer = Anonymizer::default();
+        let anonymized = anonymizer.anonymize_diff(DIFF);
+        let lines: Vec<&str> = anonymized.lines().collect();
+
+        // Headers and file paths are untouched
+        assert_eq!(lines[0], "diff --git a/src/billing.rs b/src/billing.rs");
+        assert_eq!(lines[2], "+++ b/src/billing.rs");
+        assert_eq!(
+            lines[3],
+            "@@ -1,3 +1,4 @@ fn ident1(ident2: &ident3) -> u64 {"
+        );
+
+        // Names are replaced consistently, keywords, numbers and prefixes are kept
+        assert_eq!(lines[4], "     let ident4 = ident2.ident5.ident6();");
+        assert_eq!(lines[5], "-    let ident7 = 0;");
+        assert_eq!(lines[6], "+    let ident7 = ident8(ident2, \"text1\");");
+        assert_eq!(lines[7], "+    // ident9 ident10 ident11 ident7");
+        assert_eq!(lines[8], "     ident4 - ident7");
+
+        for secret in [
+            "invoice", "order", "discount", "vip", "ACME", "gold", "partner",
+        ] {
+            assert!(
+                !anonymized.lines().skip(3).any(|l| l.contains(secret)),
+                "{} leaked",
+                secret
+            );
+        }
+        assert_eq!(anonymizer.replaced(), 12);
+    }
+
+    #[test]
+    fn test_restore_table_driven() {
+        let mut anonymizer = Anonymizer::default();
+        anonymizer.anonymize_diff(DIFF);
+
+        let cases = vec![
+            (
+                "feat(billing): apply ident8 in ident1",
+                "feat(billing): apply apply_vip_discount in compute_invoice_total",
+            ),
+            (
+                "- use text1 tier name for ident7",
+                "- use ACME \\\"gold\\\" tier tier name for discount",
+            ),
+            ("fix: ident99 stays unknown", "fix: ident99 stays unknown"),
+        ];
+        for (message, expected) in cases {
+            assert_eq!(anonymizer.restore(message), expected);
+        }
+    }
+
+    #[test]
+    fn test_removed_sql_comment_is_not_a_header() {
+        let mut anonymizer = Anonymizer::default();
+        let diff = "--- a/q.sql\n+++ b/q.sql\n@@ -1 +1 @@\n-- secret_table\n+-- other_table";
+        assert_eq!(
+            anonymizer.anonymize_diff(diff),
+            "--- a/q.sql\n+++ b/q.sql\n@@ -1 +1 @@\n-- ident1\n+-- ident2"
+        );
+    }
+
+    #[test]
+    fn test_anonymize_unterminated_string() {
+        let mut anonymizer = Anonymizer::default();
+        assert_eq!(
+            anonymizer.anonymize_diff("+let message = \"multi"),
+            "+let ident1 = \"ident2"
+        );
+    }
+}
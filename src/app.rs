@@ -0,0 +1,760 @@
+//! Command dispatch for ASUM.
+//!
+//! Runs a parsed command line: each subcommand is handed to its module in
+//! `commands`, and without a subcommand a commit message is generated for the
+//! staged changes (or the `--source`) and reviewed interactively.
+
+use crate::cli::{Cli, Command, ConfigAction};
+use crate::config::AsumConfig;
+use crate::interactive::{Review, choose, edit_in_editor, review};
+use crate::pipeline::{
+    DryRun, expand_subject, prepare_commit_message, save_generation, summarize_candidates,
+    summarize_subjects, with_instruction,
+};
+use crate::{clipboard, commands, pager};
+use clap::Parser;
+use clap::error::ErrorKind;
+use std::io::IsTerminal;
+use std::path::Path;
+use tracing::{error, info, warn};
+
+/// Parses command line arguments and executes the requested command.
+///
+/// # Arguments
+/// * `args` - A vector of string arguments from the command line.
+pub async fn run_app(args: Vec<String>) -> anyhow::Result<()> {
+    let cli = match Cli::try_parse_from(args) {
+        Ok(cli) => cli,
+        Err(e) => match e.kind() {
+            // Help and version output are not failures
+            ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => {
+                print!("{}", e);
+                return Ok(());
+            }
+            ErrorKind::InvalidSubcommand | ErrorKind::UnknownArgument => {
+                error!("{}", e.to_string().lines().next().unwrap_or_default());
+                eprint!("{}", e);
+                return Err(anyhow::anyhow!("Unknown command"));
+            }
+            _ => return Err(anyhow::anyhow!(e.to_string())),
+        },
+    };
+    run(cli).await
+}
+
+/// Core logic for executing a parsed command line.
+pub async fn run(cli: Cli) -> anyhow::Result<()> {
+    match cli.command {
+        // Validates the local 'asum.toml' file (or the one given via --config)
+        Some(Command::Verify { online }) => commands::verify::run(&cli, online).await,
+        Some(Command::Version) => commands::version::run(cli.config.as_deref()),
+        Some(Command::Manpage) => commands::manpage::run(),
+        Some(Command::Init { global, force }) => commands::init::run(global, force),
+        Some(Command::Setup { force }) => commands::setup::run(force).await,
+        Some(Command::Commit { yes, edit }) => commands::commit::run(&cli, yes, edit).await,
+        Some(Command::Jj { yes }) => commands::jj::run(&cli, yes).await,
+        Some(Command::Amend { yes, edit }) => commands::amend::run(&cli, yes, edit).await,
+        Some(Command::Branch { create }) => commands::branch::run(&cli, create).await,
+        Some(Command::Fixup { create, depth }) => commands::fixup::run(&cli, create, depth),
+        Some(Command::Stash { include_untracked }) => {
+            commands::stash::run(&cli, include_untracked).await
+        }
+        Some(Command::Wip { create }) => commands::wip::run(&cli, create).await,
+        Some(Command::Explain { ref commit }) => commands::explain::run(&cli, commit).await,
+        Some(Command::Changelog { ref range }) => commands::changelog::run(&cli, range).await,
+        Some(Command::Split { commit, yes }) => {
+            commands::split::run(&cli, commit || yes, yes).await
+        }
+        Some(Command::Squash { ref range }) => commands::squash::run(&cli, range).await,
+        Some(Command::SquashPrep { ref base }) => {
+            commands::squash_prep::run(&cli, base.as_deref()).await
+        }
+        Some(Command::Range { ref range }) => commands::range::run(&cli, range).await,
+        Some(Command::Tag { ref name, create }) => commands::tag::run(&cli, name, create).await,
+        Some(Command::Translate { ref target, ref to }) => {
+            commands::translate::run(&cli, target, to).await
+        }
+        Some(Command::Serve { ref listen }) => commands::serve::run(&cli, listen).await,
+        Some(Command::Tui) => commands::tui::run(&cli).await,
+        Some(Command::Review) => commands::review::run(&cli).await,
+        Some(Command::Comment {
+            ref file,
+            ref lines,
+        }) => commands::comment::run(&cli, file, lines.as_deref()).await,
+        Some(Command::Standup {
+            ref since,
+            ref author,
+            ref output,
+            cron_friendly,
+        }) => {
+            commands::standup::run(
+                &cli,
+                since,
+                author.as_deref(),
+                output.as_deref(),
+                cron_friendly,
+            )
+            .await
+        }
+        Some(Command::Report {
+            ref since,
+            format,
+            ref repos,
+            ref output,
+        }) => commands::report::run(&cli, since, format, repos, output.as_deref()).await,
+        Some(Command::Pr { ref base, create }) => commands::pr::run(&cli, base, create).await,
+        Some(Command::Redo { ref hint }) => commands::redo::run(&cli, hint.as_deref()).await,
+        Some(Command::History {
+            ref search,
+            limit,
+            ref repo,
+        }) => commands::history::run(search.as_deref(), limit, repo.as_deref()),
+        Some(Command::Feedback) => commands::feedback::run(),
+        Some(Command::Stats { days }) => commands::stats::run(days),
+        Some(Command::Models) => commands::config::run(&cli, &ConfigAction::Models).await,
+        Some(Command::Cache { ref action }) => commands::cache::run(action),
+        Some(Command::Last) => commands::last::run(&cli),
+        Some(Command::Logs {
+            ref since,
+            level,
+            follow,
+            limit,
+        }) => commands::logs::run(since.as_deref(), level, follow, limit),
+        Some(Command::Prompt { ref action }) => commands::prompt::run(&cli, action),
+        Some(Command::Config { ref action }) => commands::config::run(&cli, action).await,
+        Some(Command::Hook { ref action }) => commands::hook::run(&cli, action).await,
+        None => summarize_staged(&cli, Path::new(".")).await,
+    }
+}
+
+/// Generates a commit message for the staged changes (or the `--source`) of the
+/// repository at `repo` and prints it. With `-n`, several candidates are generated and
+/// the user picks one. In a terminal, the message can then be accepted, edited or
+/// regenerated.
+async fn summarize_staged(cli: &Cli, repo: &Path) -> anyhow::Result<()> {
+    let mut config = cli.load_config()?;
+    let prepared = prepare_commit_message(&mut config, &cli.source_spec(), repo).await?;
+    let diff = prepared.diff_text;
+    if let Some(message) = prepared.revert {
+        println!("{}", cli.format_message(&message));
+        save_generation(&config, repo, &diff, &message);
+        clipboard::deliver(&message, cli.clipboard(&config).as_deref());
+        return Ok(());
+    }
+
+    match pick_message(cli, &config, repo, &diff).await {
+        Ok(Some(final_msg)) => {
+            save_generation(&config, repo, &diff, &final_msg);
+
+            // Automatically copy the generated message to the system clipboard
+            clipboard::deliver(&final_msg, cli.clipboard(&config).as_deref());
+        }
+        Ok(None) => {}
+        Err(e) if e.is::<DryRun>() => return Err(e),
+        Err(e) => {
+            error!("Summarization failed: {}", e);
+            return Err(e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Generates the candidates for `diff` of the repository at `repo`, lets the user pick
+/// one and, when run in a terminal, review it. Returns `Ok(None)` when the user quits.
+async fn pick_message(
+    cli: &Cli,
+    config: &AsumConfig,
+    repo: &Path,
+    diff: &str,
+) -> anyhow::Result<Option<String>> {
+    let interactive = std::io::stdin().is_terminal() && std::io::stderr().is_terminal();
+    let mut current_config = config.clone();
+
+    loop {
+        // Menus go to stderr so stdout only carries the messages
+        let stdin = std::io::stdin();
+        let mut message = if cli.candidates > 1 && current_config.candidate_bodies == "picked" {
+            let subjects = summarize_subjects(
+                &current_config,
+                repo,
+                diff.to_string(),
+                cli.candidates.into(),
+            )
+            .await?;
+            let index = if subjects.len() > 1 {
+                choose(
+                    &mut stdin.lock(),
+                    &mut std::io::stderr(),
+                    "Pick a subject",
+                    &subjects,
+                )?
+            } else {
+                0
+            };
+            info!("Writing the body for the picked subject...");
+            expand_subject(&current_config, repo, diff.to_string(), &subjects[index]).await?
+        } else {
+            let mut candidates = summarize_candidates(
+                current_config.clone(),
+                repo,
+                diff.to_string(),
+                cli.candidates.into(),
+            )
+            .await?;
+            let index = if candidates.len() > 1 {
+                choose(
+                    &mut stdin.lock(),
+                    &mut std::io::stderr(),
+                    "Pick a message",
+                    &candidates,
+                )?
+            } else {
+                0
+            };
+            candidates.swap_remove(index)
+        };
+
+        loop {
+            println!("{}", cli.format_message(&message));
+            if !interactive {
+                return Ok(Some(message));
+            }
+            match review(&mut stdin.lock(), &mut std::io::stderr())? {
+                Review::Accept => return Ok(Some(message)),
+                Review::Edit => {
+                    message = edit_in_editor(&message)?;
+                    if message.is_empty() {
+                        warn!("The edited message is empty; nothing was copied.");
+                        return Ok(None);
+                    }
+                }
+                Review::Regenerate(instruction) => {
+                    current_config = match instruction {
+                        Some(instruction) => with_instruction(config, &instruction),
+                        None => config.clone(),
+                    };
+                    // Regenerating asks for a new message, not the cached one
+                    current_config.no_cache = true;
+                    break;
+                }
+                Review::Diff => pager::show(diff),
+                Review::Quit => return Ok(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+// The std mutex only serializes tests that use or replace HOME; holding it across
+// awaits is intended so no other test observes the temporary home directory.
+#[allow(clippy::await_holding_lock)]
+mod tests {
+    use super::*;
+    use crate::failure;
+    use crate::summarizer::{MockSummarizer, Summarizer};
+
+    /// Arguments of `asum verify` for the configuration file at `path`.
+    fn verify_args(path: &Path) -> Vec<String> {
+        vec![
+            "asum".to_string(),
+            "verify".to_string(),
+            "--config".to_string(),
+            path.to_str().unwrap().to_string(),
+        ]
+    }
+
+    /// Runs `asum` without a subcommand for the repository at `repo`, with the
+    /// `asum.toml` in it.
+    async fn summarize_in(repo: &Path) -> anyhow::Result<()> {
+        let config = repo.join("asum.toml");
+        let cli = Cli::try_parse_from(["asum", "--config", config.to_str().unwrap()]).unwrap();
+        summarize_staged(&cli, repo).await
+    }
+
+    #[tokio::test]
+    async fn test_summarize_with_mock() {
+        let mut mock = MockSummarizer::new();
+        mock.expect_summarize()
+            .with(mockall::predicate::eq("fake diff"))
+            .times(1)
+            .returning(|_| Ok("feat: mock summary".to_string()));
+
+        let result = mock.summarize("fake diff").await.unwrap();
+        assert_eq!(result, "feat: mock summary");
+    }
+
+    #[test]
+    fn test_help_args() {
+        // Since main() uses std::process::exit and println!,
+        // we test the logic around argument matching if possible.
+        let args = ["asum".to_string(), "help".to_string()];
+        assert_eq!(args[1], "help");
+    }
+
+    #[test]
+    fn test_verify_args() {
+        let args = ["asum".to_string(), "verify".to_string()];
+        assert_eq!(args[1], "verify");
+    }
+
+    #[tokio::test]
+    async fn test_run_app_help() {
+        let args = vec!["asum".to_string(), "help".to_string()];
+        let result = run_app(args).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_app_unknown_command() {
+        let args = vec!["asum".to_string(), "unknown".to_string()];
+        let result = run_app(args).await;
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), "Unknown command");
+    }
+
+    #[tokio::test]
+    async fn test_run_app_version_flag() {
+        let args = vec!["asum".to_string(), "--version".to_string()];
+        assert!(run_app(args).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_app_version_command() {
+        let args = vec!["asum".to_string(), "version".to_string()];
+        assert!(run_app(args).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_app_verify_custom_config_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("custom.toml");
+        std::fs::write(
+            &config_path,
+            r#"
+            [general]
+            active_provider = "ollama"
+            max_diff_length = 1000
+            [ai_params]
+            num_predict = 100
+            temperature = 0.7
+            top_p = 1.0
+            [ollama]
+            url = "http://localhost:11434/api/chat"
+            model = "llama3"
+            "#,
+        )
+        .unwrap();
+
+        let args = vec![
+            "asum".to_string(),
+            "verify".to_string(),
+            "--config".to_string(),
+            config_path.to_str().unwrap().to_string(),
+        ];
+        assert!(run_app(args).await.is_ok());
+
+        let invalid = dir.path().join("invalid.toml");
+        std::fs::write(
+            &invalid,
+            std::fs::read_to_string(&config_path)
+                .unwrap()
+                .replace("temperature = 0.7", "temperature = 3.0"),
+        )
+        .unwrap();
+        let args = vec![
+            "asum".to_string(),
+            "verify".to_string(),
+            "--config".to_string(),
+            invalid.to_str().unwrap().to_string(),
+        ];
+        let result = run_app(args).await;
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("temperature must be between 0 and 2 (got 3).")
+        );
+
+        let missing = dir.path().join("missing.toml");
+        let args = vec![
+            "asum".to_string(),
+            "--config".to_string(),
+            missing.to_str().unwrap().to_string(),
+            "verify".to_string(),
+        ];
+        let result = run_app(args).await;
+        assert!(result.unwrap_err().to_string().contains("not found"));
+    }
+
+    #[tokio::test]
+    async fn test_run_app_verify_not_found() {
+        // A temp dir where asum.toml doesn't exist
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("asum.toml");
+
+        let result = run_app(verify_args(&config_path)).await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!("{} not found", config_path.display())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_app_verify_valid() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("asum.toml");
+        let mut file = std::fs::File::create(&config_path).unwrap();
+        use std::io::Write;
+        writeln!(
+            file,
+            r#"
+            [general]
+            active_provider = "ollama"
+            max_diff_length = 1000
+            [ai_params]
+            num_predict = 100
+            temperature = 0.7
+            top_p = 1.0
+            [ollama]
+            url = "http://localhost:11434/api/chat"
+            model = "llama3"
+            "#
+        )
+        .unwrap();
+
+        let result = run_app(verify_args(&config_path)).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_app_full_flow_no_staged() {
+        let _guard = crate::test_utils::TEST_MUTEX.lock().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let repo_path = dir.path();
+
+        // Init git
+        std::process::Command::new("git")
+            .arg("init")
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+
+        // Create config
+        let config_path = repo_path.join("asum.toml");
+        let mut file = std::fs::File::create(config_path).unwrap();
+        use std::io::Write;
+        writeln!(
+            file,
+            r#"
+            [general]
+            active_provider = "ollama"
+            max_diff_length = 1000
+            [ai_params]
+            num_predict = 100
+            temperature = 0.7
+            top_p = 1.0
+            "#
+        )
+        .unwrap();
+
+        let result = summarize_in(repo_path).await;
+
+        // Scripts can tell "nothing to do" apart from a failure
+        let err = result.unwrap_err();
+        assert_eq!(failure::failure_of(&err), Some(failure::Failure::NoChanges));
+        assert_eq!(failure::exit_code(&err), 3);
+    }
+
+    #[cfg(feature = "ollama")]
+    #[tokio::test]
+    async fn test_run_app_full_flow_with_staged() {
+        let _guard = crate::test_utils::TEST_MUTEX.lock().unwrap();
+        let _home = crate::test_utils::TempHome::create();
+        let dir = tempfile::tempdir().unwrap();
+        let repo_path = dir.path();
+
+        // Init git
+        std::process::Command::new("git")
+            .arg("init")
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+
+        // Create a file and stage it
+        let test_file = repo_path.join("test.rs");
+        std::fs::write(&test_file, "fn main() {}").unwrap();
+        std::process::Command::new("git")
+            .args(["add", "test.rs"])
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+
+        // Mock server
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let url = format!("http://{}", addr);
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 2048];
+            let _ = tokio::io::AsyncReadExt::read(&mut socket, &mut buf)
+                .await
+                .unwrap();
+
+            let response = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"message\": {\"content\": \"feat: integration success\"}}";
+            tokio::io::AsyncWriteExt::write_all(&mut socket, response.as_bytes())
+                .await
+                .unwrap();
+        });
+
+        // Create config pointing to mock server
+        let config_path = repo_path.join("asum.toml");
+        let mut file = std::fs::File::create(config_path).unwrap();
+        use std::io::Write;
+        writeln!(
+            file,
+            r#"
+            [general]
+            active_provider = "ollama"
+            max_diff_length = 1000
+            [ai_params]
+            num_predict = 100
+            temperature = 0.7
+            top_p = 1.0
+            [ollama]
+            model = "llama3"
+            url = "{}"
+            "#,
+            url
+        )
+        .unwrap();
+
+        let result = summarize_in(repo_path).await;
+
+        assert!(result.is_ok());
+
+        // The generated message is saved for `asum feedback`
+        let db = crate::db::Db::open(&_home.dir.path().join(".asum").join("asum.db")).unwrap();
+        let repo = crate::git::get_repo_root_in_path(repo_path).unwrap();
+        let saved = db
+            .latest_pending_generation(&repo.to_string_lossy())
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.message, "feat: integration success");
+        assert_eq!(saved.model, "llama3");
+    }
+
+    #[cfg(feature = "ollama")]
+    #[tokio::test]
+    async fn test_run_app_full_flow_with_truncation() {
+        let _guard = crate::test_utils::TEST_MUTEX.lock().unwrap();
+        let _home = crate::test_utils::TempHome::create();
+        let dir = tempfile::tempdir().unwrap();
+        let repo_path = dir.path();
+
+        // Init git
+        std::process::Command::new("git")
+            .arg("init")
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+
+        // Create a large file and stage it
+        let test_file = repo_path.join("test.rs");
+        let large_content = "fn main() {".to_string() + &" ".repeat(2000) + "}";
+        std::fs::write(&test_file, large_content).unwrap();
+        std::process::Command::new("git")
+            .args(["add", "test.rs"])
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+
+        // Mock server
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let url = format!("http://{}", addr);
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 4096];
+            let _ = tokio::io::AsyncReadExt::read(&mut socket, &mut buf)
+                .await
+                .unwrap();
+
+            let response = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"message\": {\"content\": \"feat: truncation success\"}}";
+            tokio::io::AsyncWriteExt::write_all(&mut socket, response.as_bytes())
+                .await
+                .unwrap();
+        });
+
+        // Create config with SMALL max_diff_length
+        let config_path = repo_path.join("asum.toml");
+        let mut file = std::fs::File::create(config_path).unwrap();
+        use std::io::Write;
+        writeln!(
+            file,
+            r#"
+            [general]
+            active_provider = "ollama"
+            max_diff_length = 10
+            [ai_params]
+            num_predict = 100
+            temperature = 0.7
+            top_p = 1.0
+            [ollama]
+            model = "llama3"
+            url = "{}"
+            "#,
+            url
+        )
+        .unwrap();
+
+        let result = summarize_in(repo_path).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_app_verify_invalid_toml() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("asum.toml");
+        let mut file = std::fs::File::create(&config_path).unwrap();
+        use std::io::Write;
+        writeln!(file, "invalid = [").unwrap(); // Unclosed bracket is invalid TOML
+
+        let result = run_app(verify_args(&config_path)).await;
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("syntax error"));
+    }
+
+    #[cfg(feature = "ollama")]
+    #[tokio::test]
+    async fn test_run_app_full_flow_fallback() {
+        let _guard = crate::test_utils::TEST_MUTEX.lock().unwrap();
+        let _home = crate::test_utils::TempHome::create();
+        let dir = tempfile::tempdir().unwrap();
+        let repo_path = dir.path();
+
+        // Init git
+        std::process::Command::new("git")
+            .arg("init")
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+
+        // Create a file with unsupported extension and stage it
+        let test_file = repo_path.join("test.unsupported");
+        std::fs::write(&test_file, "some content").unwrap();
+        std::process::Command::new("git")
+            .args(["add", "test.unsupported"])
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+
+        // Mock server
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let url = format!("http://{}", addr);
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 2048];
+            let _ = tokio::io::AsyncReadExt::read(&mut socket, &mut buf)
+                .await
+                .unwrap();
+
+            let response = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"message\": {\"content\": \"chore: fallback success\"}}";
+            tokio::io::AsyncWriteExt::write_all(&mut socket, response.as_bytes())
+                .await
+                .unwrap();
+        });
+
+        // Create config
+        let config_path = repo_path.join("asum.toml");
+        use std::io::Write;
+        let mut file = std::fs::File::create(config_path).unwrap();
+        writeln!(
+            file,
+            r#"
+            [general]
+            active_provider = "ollama"
+            max_diff_length = 1000
+            git_extensions = [".rs"]
+            [ai_params]
+            num_predict = 100
+            temperature = 0.7
+            top_p = 1.0
+            [ollama]
+            model = "llama3"
+            url = "{}"
+            "#,
+            url
+        )
+        .unwrap();
+
+        let result = summarize_in(repo_path).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[cfg(feature = "ollama")]
+    #[tokio::test]
+    async fn test_run_app_summarize_fail() {
+        let _guard = crate::test_utils::TEST_MUTEX.lock().unwrap();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let url = format!("http://{}", addr);
+
+        tokio::spawn(async move {
+            if let Ok((mut stream, _)) = listener.accept() {
+                use std::io::Write;
+                let response = "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\n\r\n";
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        let repo_path = tempfile::tempdir().unwrap();
+        let _ = std::process::Command::new("git")
+            .arg("init")
+            .current_dir(repo_path.path())
+            .output()
+            .unwrap();
+
+        std::fs::write(repo_path.path().join("main.rs"), "fn main() {}").unwrap();
+        let _ = std::process::Command::new("git")
+            .args(["add", "main.rs"])
+            .current_dir(repo_path.path())
+            .output()
+            .unwrap();
+
+        let config_path = repo_path.path().join("asum.toml");
+        std::fs::write(
+            &config_path,
+            format!(
+                r#"
+            [general]
+            active_provider = "ollama"
+            max_diff_length = 1000
+            [ai_params]
+            num_predict = 100
+            temperature = 0.7
+            top_p = 1.0
+            [ollama]
+            model = "llama3"
+            url = "{}"
+            "#,
+                url
+            ),
+        )
+        .unwrap();
+
+        let result = summarize_in(repo_path.path()).await;
+
+        assert_eq!(failure::exit_code(&result.unwrap_err()), 5);
+    }
+}
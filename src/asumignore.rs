@@ -0,0 +1,323 @@
+//! `.asumignore` support for ASUM.
+//!
+//! A `.asumignore` file in the repository root lists, in gitignore syntax, paths
+//! whose changes must never reach the model: vendored code, fixtures, sensitive
+//! files. It is shared through the repository, so nobody has to change their own
+//! configuration. The file sections of matching paths are removed from the diff
+//! before anything else looks at it.
+
+use crate::diff_source::{file_sections, old_path, without_sections};
+use crate::failure::Failure;
+use crate::git::get_repo_root_in_path;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// Name of the file in the repository root.
+pub const FILE_NAME: &str = ".asumignore";
+
+/// One pattern line of a `.asumignore` file.
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    pattern: Vec<char>,
+    /// `!pattern`: paths matching it are no longer ignored.
+    negated: bool,
+    /// `pattern/`: only directories match.
+    dir_only: bool,
+    /// The pattern has a `/` before its end, so it matches from the root only.
+    anchored: bool,
+}
+
+/// The rules of a `.asumignore` file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AsumIgnore {
+    rules: Vec<Rule>,
+}
+
+impl AsumIgnore {
+    /// Reads `.asumignore` from the root of the repository at `repo` (or from `repo`
+    /// itself outside a git repository). A missing file means no rules; an unreadable
+    /// one is reported and ignored.
+    pub fn load(repo: &Path) -> Self {
+        let root = get_repo_root_in_path(repo).unwrap_or_else(|_| repo.to_path_buf());
+        let path = root.join(FILE_NAME);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                warn!("Could not read {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Parses the lines of a `.asumignore` file.
+    pub fn parse(text: &str) -> Self {
+        let rules = text
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim_end();
+                if line.is_empty() || line.starts_with('#') {
+                    return None;
+                }
+                let (negated, line) = match line.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let (dir_only, line) = match line.strip_suffix('/') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let anchored = line.contains('/');
+                let line = line.strip_prefix('/').unwrap_or(line);
+                (!line.is_empty()).then(|| Rule {
+                    pattern: line.chars().collect(),
+                    negated,
+                    dir_only,
+                    anchored,
+                })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Whether there are no rules.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether the file at `path`, relative to the repository root, is ignored. As in
+    /// git, a file in an ignored directory stays ignored even if a later `!` rule
+    /// matches the file.
+    pub fn is_ignored(&self, path: &str) -> bool {
+        let mut dirs = path.match_indices('/').map(|(index, _)| &path[..index]);
+        dirs.any(|dir| self.decide(dir, true)) || self.decide(path, false)
+    }
+
+    /// Whether the file section of `path` is ignored: by its path, or by the path it
+    /// had before a rename or copy, so moving a file out of an ignored directory does
+    /// not expose it.
+    pub fn is_section_ignored(&self, path: &str, section: &str) -> bool {
+        self.is_ignored(path) || old_path(section).is_some_and(|old| self.is_ignored(old))
+    }
+
+    /// Applies the last rule that matches `candidate`, if any.
+    fn decide(&self, candidate: &str, is_dir: bool) -> bool {
+        let name = candidate.rsplit('/').next().unwrap_or(candidate);
+        let candidate: Vec<char> = candidate.chars().collect();
+        let name: Vec<char> = name.chars().collect();
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                (is_dir || !rule.dir_only)
+                    && if rule.anchored {
+                        wildmatch(&rule.pattern, &candidate)
+                    } else {
+                        wildmatch(&rule.pattern, &name)
+                    }
+            })
+            .is_some_and(|rule| !rule.negated)
+    }
+
+    /// Removes the file sections of ignored paths from `diff`. Returns the diff and the
+    /// paths that were removed.
+    pub fn filter<'a>(&self, diff: &'a str) -> (String, Vec<&'a str>) {
+        if self.is_empty() {
+            return (diff.to_string(), Vec::new());
+        }
+        let ignored: Vec<&str> = file_sections(diff)
+            .into_iter()
+            .filter(|(path, section)| self.is_section_ignored(path, section))
+            .map(|(path, _)| path)
+            .collect();
+        let filtered =
+            without_sections(diff, |path, section| self.is_section_ignored(path, section));
+        (filtered, ignored)
+    }
+}
+
+/// Removes the changes to paths listed in the `.asumignore` file of the repository at
+/// `repo` from `diff`. Fails with `Failure::NoChanges` when every changed file is
+/// ignored.
+pub fn apply(diff: &str, repo: &Path) -> anyhow::Result<String> {
+    let (filtered, ignored) = AsumIgnore::load(repo).filter(diff);
+    if ignored.is_empty() {
+        return Ok(filtered);
+    }
+    info!(
+        "Left out {} file(s) matching {}: {}",
+        ignored.len(),
+        FILE_NAME,
+        ignored.join(", ")
+    );
+    if file_sections(&filtered).is_empty() {
+        return Err(Failure::NoChanges.error(format!(
+            "Every changed file matches {}; nothing is left to summarize.",
+            FILE_NAME
+        )));
+    }
+    Ok(filtered)
+}
+
+/// Matches `text` against a gitignore glob: `*` and `?` stop at `/`, `**` crosses
+/// directories (and `**/` may match nothing), `[...]` is a character class, and `\`
+/// escapes the next character.
+fn wildmatch(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', rest @ ..] => {
+            if let ['/', after @ ..] = rest
+                && wildmatch(after, text)
+            {
+                return true;
+            }
+            (0..=text.len()).any(|start| wildmatch(rest, &text[start..]))
+        }
+        ['*', rest @ ..] => {
+            for start in 0..=text.len() {
+                if wildmatch(rest, &text[start..]) {
+                    return true;
+                }
+                if text.get(start) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        ['?', rest @ ..] => {
+            matches!(text.first(), Some(c) if *c != '/') && wildmatch(rest, &text[1..])
+        }
+        ['[', rest @ ..] => match (class_match(rest, text.first()), text.first()) {
+            (Some((true, len)), Some(_)) => wildmatch(&rest[len..], &text[1..]),
+            (Some(_), _) => false,
+            // An unclosed bracket is a literal
+            (None, Some('[')) => wildmatch(rest, &text[1..]),
+            (None, _) => false,
+        },
+        ['\\', escaped, rest @ ..] => text.first() == Some(escaped) && wildmatch(rest, &text[1..]),
+        [c, rest @ ..] => text.first() == Some(c) && wildmatch(rest, &text[1..]),
+    }
+}
+
+/// Matches `c` against the class that starts after a `[`. Returns whether it matched
+/// and the length of the class up to and including `]`, or `None` when the class is
+/// never closed.
+fn class_match(class: &[char], c: Option<&char>) -> Option<(bool, usize)> {
+    let (negated, start) = match class.first() {
+        Some('!') | Some('^') => (true, 1),
+        _ => (false, 0),
+    };
+    // A `]` right after the opening bracket is part of the class
+    let end = class
+        .iter()
+        .skip(start + 1)
+        .position(|&c| c == ']')
+        .map(|index| index + start + 1)?;
+    let members = &class[start..end];
+    let Some(&c) = c.filter(|&&c| c != '/') else {
+        return Some((false, end + 1));
+    };
+    let mut matched = false;
+    let mut i = 0;
+    while i < members.len() {
+        if i + 2 < members.len() && members[i + 1] == '-' {
+            matched |= members[i] <= c && c <= members[i + 2];
+            i += 3;
+        } else {
+            matched |= members[i] == c;
+            i += 1;
+        }
+    }
+    Some((matched != negated, end + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_ignored_table_driven() {
+        let ignore = AsumIgnore::parse(
+            "# vendored code\n\
+             vendor/\n\
+             /fixtures/*.json\n\
+             *.pem\n\
+             !public.pem\n\
+             secrets/**\n\
+             docs/**/draft-?.md\n\
+             config/[ab]*.toml\n\
+             \n",
+        );
+        let cases = [
+            ("vendor/lib.rs", true),
+            ("src/vendor/lib.rs", true),
+            ("vendor", false),
+            ("fixtures/users.json", true),
+            ("fixtures/deep/users.json", false),
+            ("tests/fixtures/users.json", false),
+            ("keys/server.pem", true),
+            ("keys/public.pem", false),
+            ("secrets/a/b/token.txt", true),
+            ("docs/draft-1.md", true),
+            ("docs/guide/v2/draft-a.md", true),
+            ("docs/draft-10.md", false),
+            ("config/app.toml", true),
+            ("config/cli.toml", false),
+            ("src/main.rs", false),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(ignore.is_ignored(path), expected, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_ignored_directory_cannot_be_reincluded() {
+        let ignore = AsumIgnore::parse("vendor/\n!vendor/keep.rs\n");
+        assert!(ignore.is_ignored("vendor/keep.rs"));
+    }
+
+    #[test]
+    fn test_filter_removes_ignored_sections() {
+        let diff = "diff --git a/src/main.rs b/src/main.rs\n+fn main() {}\n\
+                    diff --git a/vendor/dep.rs b/vendor/dep.rs\n+fn dep() {}\n";
+        let ignore = AsumIgnore::parse("vendor/\n");
+        let (filtered, ignored) = ignore.filter(diff);
+        assert_eq!(
+            filtered,
+            "diff --git a/src/main.rs b/src/main.rs\n+fn main() {}\n"
+        );
+        assert_eq!(ignored, vec!["vendor/dep.rs"]);
+
+        let (filtered, ignored) = AsumIgnore::default().filter(diff);
+        assert_eq!(filtered, diff);
+        assert!(ignored.is_empty());
+    }
+
+    #[test]
+    fn test_filter_removes_files_renamed_out_of_ignored_paths() {
+        let kept = "diff --git a/src/main.rs b/src/main.rs\n+fn main() {}\n";
+        let diff = format!(
+            "{}diff --git a/vendor/dep.rs b/src/dep.rs\nsimilarity index 90%\n\
+             rename from vendor/dep.rs\nrename to src/dep.rs\n--- a/vendor/dep.rs\n\
+             +++ b/src/dep.rs\n@@ -1 +1 @@\n-fn dep() {{}}\n+pub fn dep() {{}}\n",
+            kept
+        );
+        let (filtered, ignored) = AsumIgnore::parse("vendor/\n").filter(&diff);
+        assert_eq!(filtered, kept);
+        assert_eq!(ignored, vec!["src/dep.rs"]);
+    }
+
+    #[test]
+    fn test_apply_reads_the_repository_root() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(FILE_NAME), "*.lock\n").unwrap();
+        let diff = "diff --git a/a.rs b/a.rs\n+a\ndiff --git a/Cargo.lock b/Cargo.lock\n+b\n";
+        assert_eq!(
+            apply(diff, dir.path()).unwrap(),
+            "diff --git a/a.rs b/a.rs\n+a\n"
+        );
+
+        let err = apply("diff --git a/Cargo.lock b/Cargo.lock\n+b\n", dir.path()).unwrap_err();
+        assert_eq!(crate::failure::failure_of(&err), Some(Failure::NoChanges));
+    }
+}
@@ -0,0 +1,344 @@
+//! Rate-limited batch generation for ASUM.
+//!
+//! Commands that need many generations in a row (such as `asum changelog`) run
+//! them through a `Batch`, which spaces requests to respect the provider's rate
+//! limit, reports progress with an ETA, and stores every result in the database
+//! so an interrupted run resumes where it stopped.
+
+use crate::config::AsumConfig;
+use crate::db::Db;
+use anyhow::Result;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+/// Requests per minute used for Gemini when `requests_per_minute` is not configured
+/// (the free tier limit).
+const GEMINI_REQUESTS_PER_MINUTE: u32 = 15;
+
+/// How often an item is retried after the provider reports a rate limit.
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// Pause after a rate limit error before the item is retried.
+const RATE_LIMIT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// One generation in a batch.
+#[derive(Debug, Clone)]
+pub struct BatchItem {
+    /// Stable identifier, e.g. a commit hash, used to resume the batch.
+    pub key: String,
+    /// Text passed to the generator.
+    pub input: String,
+}
+
+/// Runs a list of generations with rate limiting, progress and resumption.
+pub struct Batch {
+    id: String,
+    interval: Duration,
+    cooldown: Duration,
+    db: Option<Db>,
+}
+
+impl Batch {
+    /// Creates a batch identified by `id`, paced for the active provider in `config`.
+    /// Results are persisted in the default database when it can be opened.
+    pub fn new(id: &str, config: &AsumConfig) -> Self {
+        let db = Db::open_default()
+            .inspect_err(|e| warn!("Batch results will not be saved for resuming: {:#}", e))
+            .ok();
+        Self {
+            id: id.to_string(),
+            interval: request_interval(config),
+            cooldown: RATE_LIMIT_COOLDOWN,
+            db,
+        }
+    }
+
+    /// Generates an output for every item, in order, calling `generate` with the item input.
+    /// Items finished by an earlier, interrupted run are taken from the database. The stored
+    /// results are deleted once the whole batch has succeeded.
+    pub async fn run<F, Fut>(&mut self, items: &[BatchItem], mut generate: F) -> Result<Vec<String>>
+    where
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = Result<String>>,
+    {
+        let mut done = match &self.db {
+            Some(db) => db.batch_results(&self.id)?,
+            None => Default::default(),
+        };
+        done.retain(|key, _| items.iter().any(|item| &item.key == key));
+
+        let total = items.len();
+        let pending = total - done.len();
+        if !done.is_empty() {
+            info!(
+                "Resuming: {} of {} items were done by an earlier run.",
+                done.len(),
+                total
+            );
+        }
+
+        let started = Instant::now();
+        let mut last_request: Option<Instant> = None;
+        let mut generated = 0;
+        let mut outputs = Vec::with_capacity(total);
+
+        for item in items {
+            if let Some(output) = done.remove(&item.key) {
+                outputs.push(output);
+                continue;
+            }
+
+            let output = self
+                .generate_item(item, &mut last_request, &mut generate)
+                .await
+                .map_err(|e| {
+                    e.context(format!(
+                        "Batch stopped after {} of {} items; run the same command again to resume.",
+                        outputs.len(),
+                        total
+                    ))
+                })?;
+
+            if let Some(db) = &self.db
+                && let Err(e) = db.save_batch_result(&self.id, &item.key, &output)
+            {
+                warn!("Could not save the batch result: {:#}", e);
+            }
+            outputs.push(output);
+            generated += 1;
+
+            let remaining = pending - generated;
+            let per_item = (started.elapsed() / generated as u32).max(self.interval);
+            info!(
+                "[{}/{}] {} done{}",
+                outputs.len(),
+                total,
+                item.key,
+                if remaining > 0 {
+                    format!(
+                        ", about {} left",
+                        format_duration(per_item * remaining as u32)
+                    )
+                } else {
+                    String::new()
+                }
+            );
+        }
+
+        if let Some(db) = &self.db
+            && let Err(e) = db.clear_batch(&self.id)
+        {
+            warn!("Could not clear the finished batch: {:#}", e);
+        }
+        Ok(outputs)
+    }
+
+    /// Generates one item, waiting for the next free slot and slowing down when the
+    /// provider still reports a rate limit.
+    async fn generate_item<F, Fut>(
+        &mut self,
+        item: &BatchItem,
+        last_request: &mut Option<Instant>,
+        generate: &mut F,
+    ) -> Result<String>
+    where
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = Result<String>>,
+    {
+        let mut retries = 0;
+        loop {
+            if let Some(last) = *last_request {
+                let next = last + self.interval;
+                let now = Instant::now();
+                if next > now {
+                    sleep(next - now).await;
+                }
+            }
+            *last_request = Some(Instant::now());
+
+            match generate(item.input.clone()).await {
+                Ok(output) => return Ok(output),
+                Err(e) if is_rate_limited(&e) && retries < MAX_RATE_LIMIT_RETRIES => {
+                    retries += 1;
+                    // Unlimited providers that still return 429s get a tenth of the cooldown
+                    self.interval = (self.interval * 2).max(self.cooldown / 10);
+                    warn!(
+                        "Rate limited on {}; waiting {} and slowing down to one request every {} (attempt {}/{}).",
+                        item.key,
+                        format_duration(self.cooldown),
+                        format_duration(self.interval),
+                        retries,
+                        MAX_RATE_LIMIT_RETRIES
+                    );
+                    sleep(self.cooldown).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Minimum delay between two requests for the active provider. `requests_per_minute`
+/// overrides the provider default; `0` means no limit.
+pub fn request_interval(config: &AsumConfig) -> Duration {
+    let per_minute = config
+        .requests_per_minute
+        .unwrap_or(match config.active_provider.as_str() {
+            "gemini" => GEMINI_REQUESTS_PER_MINUTE,
+            _ => 0,
+        });
+    if per_minute == 0 {
+        Duration::ZERO
+    } else {
+        Duration::from_secs(60) / per_minute
+    }
+}
+
+/// Whether `error` was caused by the provider's rate limit (HTTP 429).
+fn is_rate_limited(error: &anyhow::Error) -> bool {
+    let text = format!("{:#}", error);
+    text.contains("429") || text.to_lowercase().contains("too many requests")
+}
+
+/// Formats a duration as e.g. `45s` or `3m05s`.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use std::cell::RefCell;
+
+    fn items(keys: &[&str]) -> Vec<BatchItem> {
+        keys.iter()
+            .map(|key| BatchItem {
+                key: key.to_string(),
+                input: format!("input {}", key),
+            })
+            .collect()
+    }
+
+    fn batch(db: Option<Db>) -> Batch {
+        Batch {
+            id: "test".to_string(),
+            interval: Duration::ZERO,
+            cooldown: Duration::from_millis(10),
+            db,
+        }
+    }
+
+    fn rate_limit_error() -> anyhow::Error {
+        anyhow!("Gemini API returned error: 429 Too Many Requests - quota exceeded")
+    }
+
+    #[test]
+    fn test_request_interval_table_driven() {
+        let mut config = AsumConfig::from_defaults().unwrap();
+        let cases = vec![
+            ("ollama", None, Duration::ZERO),
+            ("gemini", None, Duration::from_secs(4)),
+            ("gemini", Some(0), Duration::ZERO),
+            ("ollama", Some(30), Duration::from_secs(2)),
+        ];
+
+        for (provider, per_minute, expected) in cases {
+            config.active_provider = provider.to_string();
+            config.requests_per_minute = per_minute;
+            assert_eq!(
+                request_interval(&config),
+                expected,
+                "provider: {}, per minute: {:?}",
+                provider,
+                per_minute
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_duration_table_driven() {
+        let cases = vec![(0, "0s"), (59, "59s"), (60, "1m00s"), (185, "3m05s")];
+        for (secs, expected) in cases {
+            assert_eq!(format_duration(Duration::from_secs(secs)), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_resumes_interrupted_batch() {
+        let db = Db::open_in_memory().unwrap();
+        let mut batch = batch(Some(db));
+        let items = items(&["a", "b", "c"]);
+        let calls = RefCell::new(Vec::new());
+
+        // The first run fails on "b" with a non rate limit error and keeps "a"
+        let err = batch
+            .run(&items, |input| {
+                calls.borrow_mut().push(input.clone());
+                async move {
+                    if input.ends_with('b') {
+                        Err(anyhow!("connection refused"))
+                    } else {
+                        Ok(input.to_uppercase())
+                    }
+                }
+            })
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("after 1 of 3 items"));
+
+        // The second run only generates the missing items
+        calls.borrow_mut().clear();
+        let outputs = batch
+            .run(&items, |input| {
+                calls.borrow_mut().push(input.clone());
+                async move { Ok(input.to_uppercase()) }
+            })
+            .await
+            .unwrap();
+        assert_eq!(outputs, vec!["INPUT A", "INPUT B", "INPUT C"]);
+        assert_eq!(*calls.borrow(), vec!["input b", "input c"]);
+
+        // A finished batch is cleared
+        let db = batch.db.as_ref().unwrap();
+        assert!(db.batch_results("test").unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_run_retries_rate_limits_and_slows_down() {
+        let mut batch = batch(None);
+        let attempts = RefCell::new(0);
+
+        let outputs = batch
+            .run(&items(&["a"]), |input| {
+                *attempts.borrow_mut() += 1;
+                let attempt = *attempts.borrow();
+                async move {
+                    if attempt < 3 {
+                        Err(rate_limit_error())
+                    } else {
+                        Ok(input)
+                    }
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(outputs, vec!["input a"]);
+        assert_eq!(*attempts.borrow(), 3);
+        assert!(batch.interval >= Duration::from_millis(1));
+
+        // Persistent rate limits eventually give up
+        let err = batch
+            .run(&items(&["b"]), |_| async { Err(rate_limit_error()) })
+            .await
+            .unwrap_err();
+        assert!(is_rate_limited(&err));
+    }
+}
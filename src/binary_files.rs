@@ -0,0 +1,233 @@
+//! Staged binary and Git LFS files as prompt context for ASUM.
+//!
+//! Images, fonts and other binary files are usually outside `git_extensions`, so
+//! they never reach the diff, and when they do the diff only says
+//! `Binary files differ`. Files kept in Git LFS show up as pointer files full of
+//! hashes. With `binary_files` on, such staged files are listed after the diff in
+//! one line each, such as `binary file added: logo.png (120 KB)`, and LFS pointer
+//! diffs are dropped, so the model knows the files changed without seeing garbage.
+
+use crate::asumignore::AsumIgnore;
+use crate::config::AsumConfig;
+use crate::diff_source::without_sections;
+use crate::git::{BinaryFile, get_staged_binary_files_in_path};
+use std::path::Path;
+use tracing::debug;
+
+/// Most files listed, so a commit adding a folder of images cannot fill the prompt.
+const MAX_FILES: usize = 50;
+
+/// Appends the staged binary and LFS files of the repository at `repo` to
+/// `diff_text` and drops the diffs of LFS pointers, when `binary_files` is on. Git
+/// errors are only logged, since the context must never block message generation.
+pub fn append(config: &AsumConfig, repo: &Path, diff_text: String) -> String {
+    if !config.binary_files {
+        return diff_text;
+    }
+    let ignore = AsumIgnore::load(repo);
+    let files: Vec<BinaryFile> = match get_staged_binary_files_in_path(repo) {
+        Ok(files) => files
+            .into_iter()
+            .filter(|file| !ignore.is_ignored(&file.path))
+            .collect(),
+        Err(e) => {
+            debug!("Skipping binary files: {}", e);
+            return diff_text;
+        }
+    };
+    if files.is_empty() {
+        return diff_text;
+    }
+
+    let diff_text = without_sections(&diff_text, |path, _| {
+        files.iter().any(|file| file.lfs && file.path == path)
+    });
+    format!("{}\n\n{}", diff_text.trim_end(), render(&files))
+}
+
+/// Renders the `[BINARY FILES]` section for `files`, listing at most `MAX_FILES`.
+pub fn render(files: &[BinaryFile]) -> String {
+    let mut section = String::from(
+        "[BINARY FILES]\nThese staged files hold binary data or are stored in Git LFS, so \
+         their content is not shown. Mention them where they matter to the change.\n",
+    );
+    for file in files.iter().take(MAX_FILES) {
+        section.push_str(&format!("{}\n", describe(file)));
+    }
+    if files.len() > MAX_FILES {
+        section.push_str(&format!("... and {} more\n", files.len() - MAX_FILES));
+    }
+    section
+}
+
+/// Describes one file, e.g. `binary file added: logo.png (120 KB)`.
+fn describe(file: &BinaryFile) -> String {
+    let kind = if file.lfs { "LFS file" } else { "binary file" };
+    let size = |size: Option<u64>| size.map(human_size).unwrap_or_default();
+    match file.status {
+        'A' => format!("{} added: {} ({})", kind, file.path, size(file.new_size)),
+        'D' => format!("{} deleted: {} ({})", kind, file.path, size(file.old_size)),
+        status @ ('R' | 'C') => format!(
+            "{} {}: {} -> {} ({})",
+            kind,
+            if status == 'R' { "renamed" } else { "copied" },
+            file.old_path,
+            file.path,
+            size(file.new_size)
+        ),
+        _ if file.old_size == file.new_size => format!(
+            "{} modified: {} ({}, same size)",
+            kind,
+            file.path,
+            size(file.new_size)
+        ),
+        _ => format!(
+            "{} modified: {} ({} -> {})",
+            kind,
+            file.path,
+            size(file.old_size),
+            size(file.new_size)
+        ),
+    }
+}
+
+/// Formats `bytes` with a binary unit, e.g. `512 B`, `1.5 KB` or `120 KB`.
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    if value < 10.0 {
+        format!("{:.1} {}", value, unit)
+    } else {
+        format!("{:.0} {}", value, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn file(status: char, old_size: Option<u64>, new_size: Option<u64>, lfs: bool) -> BinaryFile {
+        BinaryFile {
+            old_path: if status == 'R' { "old.png" } else { "logo.png" }.to_string(),
+            path: "logo.png".to_string(),
+            status,
+            old_size,
+            new_size,
+            lfs,
+        }
+    }
+
+    #[test]
+    fn test_human_size_table_driven() {
+        let cases = [
+            (0, "0 B"),
+            (512, "512 B"),
+            (1536, "1.5 KB"),
+            (120 * 1024, "120 KB"),
+            (5 * 1024 * 1024 + 300 * 1024, "5.3 MB"),
+            (3 * 1024 * 1024 * 1024, "3.0 GB"),
+        ];
+        for (bytes, expected) in cases {
+            assert_eq!(human_size(bytes), expected, "{}", bytes);
+        }
+    }
+
+    #[test]
+    fn test_describe_table_driven() {
+        let cases = [
+            (
+                file('A', None, Some(120 * 1024), false),
+                "binary file added: logo.png (120 KB)",
+            ),
+            (
+                file('D', Some(2048), None, false),
+                "binary file deleted: logo.png (2.0 KB)",
+            ),
+            (
+                file('M', Some(2048), Some(4096), false),
+                "binary file modified: logo.png (2.0 KB -> 4.0 KB)",
+            ),
+            (
+                file('M', Some(100), Some(100), false),
+                "binary file modified: logo.png (100 B, same size)",
+            ),
+            (
+                file('R', Some(100), Some(100), false),
+                "binary file renamed: old.png -> logo.png (100 B)",
+            ),
+            (
+                file('A', None, Some(3 * 1024 * 1024 * 1024), true),
+                "LFS file added: logo.png (3.0 GB)",
+            ),
+        ];
+        for (file, expected) in cases {
+            assert_eq!(describe(&file), expected);
+        }
+    }
+
+    #[test]
+    fn test_render_lists_at_most_max_files() {
+        let files = vec![file('A', None, Some(10), false); MAX_FILES + 2];
+        let section = render(&files);
+        assert!(section.starts_with("[BINARY FILES]\n"));
+        assert_eq!(section.matches("binary file added").count(), MAX_FILES);
+        assert!(section.ends_with("... and 2 more\n"));
+    }
+
+    #[test]
+    fn test_append_lists_binary_and_lfs_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(repo)
+                .output()
+                .unwrap();
+        };
+        git(&["init"]);
+        std::fs::write(repo.join("logo.png"), [0u8; 3000]).unwrap();
+        std::fs::write(
+            repo.join("model.json"),
+            "version https://git-lfs.github.com/spec/v1\noid sha256:4d7a\nsize 5242880\n",
+        )
+        .unwrap();
+        std::fs::write(repo.join("main.rs"), "fn main() {}\n").unwrap();
+        git(&["add", "."]);
+
+        let mut config = AsumConfig::from_defaults().unwrap();
+        let diff = "diff --git a/main.rs b/main.rs\n+fn main() {}\n\
+                    diff --git a/model.json b/model.json\n+version https://git-lfs.github.com/spec/v1\n";
+        let text = append(&config, repo, diff.to_string());
+        assert!(!text.contains("+version"), "{}", text);
+        assert!(
+            text.starts_with("diff --git a/main.rs b/main.rs\n+fn main() {}\n\n[BINARY FILES]\n")
+        );
+        assert!(
+            text.contains("\nbinary file added: logo.png (2.9 KB)\n"),
+            "{}",
+            text
+        );
+        assert!(
+            text.contains("\nLFS file added: model.json (5.0 MB)\n"),
+            "{}",
+            text
+        );
+        assert!(!text.contains("main.rs ("), "{}", text);
+
+        config.binary_files = false;
+        assert_eq!(append(&config, repo, diff.to_string()), diff);
+    }
+}
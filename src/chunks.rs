@@ -0,0 +1,168 @@
+//! Map-reduce summarization of oversized diffs for ASUM.
+//!
+//! Truncating a large refactor at `max_diff_length` hides most of it from the model.
+//! With `large_diffs = "chunk"`, such a diff is split into chunks of whole files
+//! instead, each chunk is summarized on its own, and the commit message is written
+//! from the chunk summaries.
+
+use crate::diff_source::file_sections;
+
+/// Values accepted in `large_diffs`.
+pub const MODES: &[&str] = &["truncate", "chunk"];
+
+/// Most chunks summarized for one message; files past them are only listed by name.
+pub const MAX_CHUNKS: usize = 8;
+
+/// System prompt of the chunk requests.
+pub const CHUNK_SYSTEM_PROMPT: &str = r#"You summarize one part of a large git diff.
+Describe what the changes in this part do as 1-5 short bullet points ("- "), naming the
+affected modules, functions or settings. Describe only what the diff shows.
+Return ONLY the bullet points. No preamble, no commit message."#;
+
+/// User prompt template of the chunk requests; `{{diff}}` is replaced with the chunk.
+pub const CHUNK_USER_PROMPT: &str = "[DIFF PART]\n{{diff}}\n\n[SUMMARY]";
+
+/// Consecutive files of a diff that are summarized together.
+#[derive(Debug, PartialEq)]
+pub struct Chunk {
+    pub paths: Vec<String>,
+    pub diff: String,
+}
+
+/// Splits `diff` into chunks of whole files of at most `max` bytes each, in diff
+/// order. A file larger than `max` on its own gets a chunk cut at `max` bytes.
+pub fn split(diff: &str, max: usize) -> Vec<Chunk> {
+    let mut chunks: Vec<Chunk> = Vec::new();
+    for (path, section) in file_sections(diff) {
+        let section = &section[..section.floor_char_boundary(max)];
+        match chunks.last_mut() {
+            Some(chunk) if chunk.diff.len() + section.len() <= max => {
+                chunk.paths.push(path.to_string());
+                chunk.diff.push_str(section);
+            }
+            _ => chunks.push(Chunk {
+                paths: vec![path.to_string()],
+                diff: section.to_string(),
+            }),
+        }
+    }
+    chunks
+}
+
+/// Renders the input of the final request from the `summaries` of the first chunks;
+/// `None` marks a chunk whose request failed. Chunks without a summary slot are
+/// listed by file name only.
+pub fn reduce_input(chunks: &[Chunk], summaries: &[Option<String>]) -> String {
+    let mut input = format!(
+        "[CHUNKED DIFF]\nThis change is too large to show in one piece. It was split into {} \
+         parts of whole files, and each part was summarized separately. Write one commit \
+         message that covers all parts.\n",
+        chunks.len()
+    );
+    for (index, (chunk, summary)) in chunks.iter().zip(summaries).enumerate() {
+        input.push_str(&format!(
+            "\nPart {} ({}):\n{}\n",
+            index + 1,
+            chunk.paths.join(", "),
+            summary
+                .as_deref()
+                .map(str::trim)
+                .unwrap_or("(no summary available)")
+        ));
+    }
+    let skipped: Vec<&str> = chunks
+        .iter()
+        .skip(summaries.len())
+        .flat_map(|chunk| chunk.paths.iter().map(String::as_str))
+        .collect();
+    if !skipped.is_empty() {
+        input.push_str(&format!(
+            "\nNot summarized ({} files): {}\n",
+            skipped.len(),
+            skipped.join(", ")
+        ));
+    }
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(path: &str, body: &str) -> String {
+        format!("diff --git a/{0} b/{0}\n{1}", path, body)
+    }
+
+    #[test]
+    fn test_split_table_driven() {
+        let a = section("a.rs", "+a\n");
+        let b = section("b.rs", "+b\n");
+        let big = section("big.rs", &"+x\n".repeat(20));
+        let cases = vec![
+            (
+                "files packed together",
+                format!("{}{}", a, b),
+                100,
+                vec![(vec!["a.rs", "b.rs"], format!("{}{}", a, b))],
+            ),
+            (
+                "new chunk when full",
+                format!("{}{}", a, b),
+                a.len() + 1,
+                vec![(vec!["a.rs"], a.clone()), (vec!["b.rs"], b.clone())],
+            ),
+            (
+                "oversized file is cut",
+                format!("{}{}", big, a),
+                40,
+                vec![
+                    (vec!["big.rs"], big[..40].to_string()),
+                    (vec!["a.rs"], a.clone()),
+                ],
+            ),
+            ("no files", String::new(), 100, vec![]),
+        ];
+
+        for (name, diff, max, expected) in cases {
+            let expected: Vec<Chunk> = expected
+                .into_iter()
+                .map(|(paths, diff)| Chunk {
+                    paths: paths.into_iter().map(String::from).collect(),
+                    diff,
+                })
+                .collect();
+            assert_eq!(split(&diff, max), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_reduce_input() {
+        let chunk = |paths: &[&str]| Chunk {
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+            diff: String::new(),
+        };
+        let chunks = vec![
+            chunk(&["src/a.rs", "src/b.rs"]),
+            chunk(&["src/c.rs"]),
+            chunk(&["docs/x.md", "docs/y.md"]),
+        ];
+        let input = reduce_input(&chunks, &[Some("- rename parser\n".to_string()), None]);
+        assert!(input.starts_with("[CHUNKED DIFF]\n"), "{}", input);
+        assert!(input.contains("split into 3 parts"), "{}", input);
+        assert!(
+            input.contains("\nPart 1 (src/a.rs, src/b.rs):\n- rename parser\n"),
+            "{}",
+            input
+        );
+        assert!(
+            input.contains("\nPart 2 (src/c.rs):\n(no summary available)\n"),
+            "{}",
+            input
+        );
+        assert!(
+            input.ends_with("\nNot summarized (2 files): docs/x.md, docs/y.md\n"),
+            "{}",
+            input
+        );
+    }
+}
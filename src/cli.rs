@@ -0,0 +1,1068 @@
+//! Command line interface for ASUM.
+//!
+//! This module declares the global flags and subcommands using clap's derive API.
+
+use crate::clipboard::{self, ClipboardSink};
+use crate::config::{AsumConfig, resolve_config_path};
+use crate::environment::is_remote_container;
+use crate::failure::Failure;
+use crate::untracked;
+use anyhow::Context;
+use clap::builder::PossibleValuesParser;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+use tracing::{Level, info, warn};
+
+/// Generate professional commit messages from staged changes using AI.
+#[derive(Debug, Parser)]
+#[command(name = "asum", version)]
+pub struct Cli {
+    /// Override the active AI provider (e.g., "gemini" or "ollama").
+    #[arg(long, global = true)]
+    pub provider: Option<String>,
+
+    /// Override the model used by the active provider.
+    #[arg(long, global = true)]
+    pub model: Option<String>,
+
+    /// Load configuration from this file instead of the default locations.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Print the message without copying it to the clipboard.
+    #[arg(long, global = true)]
+    pub no_clipboard: bool,
+
+    /// Only print the generated message and errors.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Print debug information while running, including a pre-flight report of what
+    /// is sent to the provider.
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    /// Screen-reader friendly output: plain progress lines without colors or
+    /// timestamps, and results labeled with SUBJECT:/BODY:.
+    #[arg(long, global = true)]
+    pub a11y: bool,
+
+    /// Print the prompts and diff that would be sent to the AI provider, with size
+    /// estimates, without sending anything.
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Always ask the provider, even when a message for identical changes is cached.
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+
+    /// Generate this many candidate messages and pick one from a numbered list.
+    #[arg(short = 'n', long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=10))]
+    pub candidates: u8,
+
+    /// Where to read the changes from: staged, worktree, all, stdin (or -), hg, jj,
+    /// patch:<FILE> or range:<A..B>.
+    #[arg(long, default_value = "staged", value_name = "SOURCE")]
+    pub source: String,
+
+    /// Summarize the staged and unstaged changes of tracked files, as committed by
+    /// `git commit -a` (same as `--source all`).
+    #[arg(short, long, conflicts_with_all = ["source", "unstaged"])]
+    pub all: bool,
+
+    /// Summarize only the unstaged changes of tracked files (same as `--source worktree`).
+    #[arg(long, conflicts_with = "source")]
+    pub unstaged: bool,
+
+    /// Summarize the commits and staged changes since the current branch left this
+    /// branch or commit (same as `--source base:<REF>`), e.g. before pushing.
+    #[arg(long, value_name = "REF", conflicts_with_all = ["source", "all", "unstaged"])]
+    pub base: Option<String>,
+
+    /// Show untracked files to the model as context: names (the default) or content,
+    /// overriding `untracked_files`.
+    #[arg(
+        long,
+        value_name = "MODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "names",
+        value_parser = PossibleValuesParser::new(untracked::MODES)
+    )]
+    pub untracked: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Subcommands supported by ASUM. Without a subcommand, a commit summary is generated.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Verify the syntax and values of asum.toml
+    Verify {
+        /// Also contact the provider and confirm the configured model exists
+        #[arg(long)]
+        online: bool,
+    },
+    /// Show version, build information and resolved paths
+    Version,
+    /// Print a man page covering every command, option, config key and environment variable
+    Manpage,
+    /// Create an asum.toml interactively
+    Init {
+        /// Write to ~/.asum/asum.toml instead of asking for a location
+        #[arg(long)]
+        global: bool,
+        /// Overwrite an existing configuration file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Set up this repository: commit hook, asum.toml with scopes, conventions and a check
+    Setup {
+        /// Replace an existing hook and asum.toml
+        #[arg(long)]
+        force: bool,
+    },
+    /// Generate a message and commit the staged changes with it
+    Commit {
+        /// Commit without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+        /// Open the editor with the generated message before committing
+        #[arg(short, long)]
+        edit: bool,
+    },
+    /// Describe the current Jujutsu (jj) change with a generated message
+    Jj {
+        /// Run `jj describe` without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Rewrite the last commit's message from its diff
+    Amend {
+        /// Amend without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+        /// Open the editor with the generated message before amending
+        #[arg(short, long)]
+        edit: bool,
+    },
+    /// Suggest a branch name for the staged or working tree changes
+    Branch {
+        /// Create the branch and switch to it
+        #[arg(short, long)]
+        create: bool,
+    },
+    /// Find the recent commit the staged changes belong to and print its fixup subject
+    Fixup {
+        /// Commit the staged changes as `fixup! <subject>` for a later autosquash
+        #[arg(short, long)]
+        create: bool,
+        /// Number of recent commits to consider
+        #[arg(long, default_value_t = 20)]
+        depth: usize,
+    },
+    /// Stash the local changes under a generated description
+    Stash {
+        /// Also stash untracked files
+        #[arg(short = 'u', long)]
+        include_untracked: bool,
+    },
+    /// Generate a terse, numbered `wip:` message for the staged changes
+    Wip {
+        /// Commit the staged changes with the message
+        #[arg(short, long)]
+        create: bool,
+    },
+    /// Explain in plain English what an existing commit does
+    Explain {
+        /// Commit to explain (any revision git understands)
+        #[arg(default_value = "HEAD")]
+        commit: String,
+    },
+    /// Write a user-facing changelog entry for each commit in a range
+    Changelog {
+        /// Commit range such as v1.2.0..HEAD; a single revision means <rev>..HEAD
+        range: String,
+    },
+    /// Propose splitting the staged changes into several commits, each with a message
+    Split {
+        /// Create the proposed commits, asking before each
+        #[arg(short, long)]
+        commit: bool,
+        /// Create the commits without asking (implies --commit)
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Propose one message for squashing a range of commits
+    Squash {
+        /// Commit range such as HEAD~5..HEAD; a single revision means <rev>..HEAD
+        range: String,
+    },
+    /// Propose one message for squash-merging the commits of the current branch
+    SquashPrep {
+        /// Branch the current branch will be merged into (default: origin/HEAD, main or master)
+        #[arg(long)]
+        base: Option<String>,
+    },
+    /// Summarize a range of commits as a whole, e.g. for a cover letter
+    Range {
+        /// Commit range such as v1.2.0..v1.3.0; a single revision means <rev>..HEAD
+        range: String,
+    },
+    /// Write an annotated tag message for the changes since the previous tag
+    Tag {
+        /// Name of the new tag, e.g. v2.0.0
+        name: String,
+        /// Create the tag with `git tag -a` instead of printing the message
+        #[arg(short, long)]
+        create: bool,
+    },
+    /// Translate existing commit messages, keeping the Conventional Commits header
+    Translate {
+        /// Commit (any revision git understands) or range such as v1.0..HEAD
+        target: String,
+        /// Target language, e.g. en or German
+        #[arg(long, default_value = "en")]
+        to: String,
+    },
+    /// Review the staged changes for bugs, missing tests and style issues
+    Review,
+    /// Summarize a staged region as a Conventional Comments note for a review thread
+    Comment {
+        /// File the region is in
+        #[arg(long)]
+        file: String,
+        /// Lines of the staged file, e.g. 10-42 (default: every staged change in the file)
+        #[arg(long)]
+        lines: Option<String>,
+    },
+    /// Serve a small HTTP API (POST /summarize, GET /health) for editors and tools
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:7821", value_name = "ADDR")]
+        listen: String,
+    },
+    /// Open a full-screen interface for the staged changes (needs the `tui` feature)
+    Tui,
+    /// Summarize your recent commits as a standup update
+    Standup {
+        /// Include commits since this date, e.g. "yesterday", "3 days ago" or "2024-05-01" (UTC)
+        #[arg(long, default_value = "24 hours ago")]
+        since: String,
+        /// Only include commits by this author (defaults to git's user.email)
+        #[arg(long)]
+        author: Option<String>,
+        /// Write the digest to a markdown file, or post it to a Slack webhook URL
+        #[arg(long, value_name = "FILE|URL")]
+        output: Option<String>,
+        /// Run unattended: no clipboard, no prompts, and nothing is written without new commits
+        #[arg(long)]
+        cron_friendly: bool,
+    },
+    /// Write a stakeholder report of the changes in one or more repositories
+    Report {
+        /// Include commits since this date, e.g. "2 weeks ago" or "2024-05-01" (UTC)
+        #[arg(long, default_value = "1 week ago")]
+        since: String,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ReportFormat::Markdown)]
+        format: ReportFormat,
+        /// Repository to include; repeat to combine several (defaults to the current one)
+        #[arg(long = "repo", value_name = "PATH", default_value = ".")]
+        repos: Vec<PathBuf>,
+        /// Write the report to a file instead of printing it
+        #[arg(long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Draft a pull request title and description for the current branch
+    Pr {
+        /// Branch or ref the pull request will be merged into
+        #[arg(long, default_value = "main")]
+        base: String,
+        /// Open the pull request with the GitHub CLI (`gh pr create`)
+        #[arg(long)]
+        create: bool,
+    },
+    /// Regenerate the last message of this repository from the stored diff
+    Redo {
+        /// Extra instruction for the model, e.g. "mention the migration"
+        #[arg(long)]
+        hint: Option<String>,
+    },
+    /// List previously generated messages, newest first
+    History {
+        /// Only show messages containing all of these words
+        search: Option<String>,
+        /// Largest number of messages to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Only show messages generated in this repository, e.g. `--repo .`
+        #[arg(long, value_name = "PATH")]
+        repo: Option<PathBuf>,
+    },
+    /// Print the last generated message for this repository and copy it again
+    Last,
+    /// Print recent entries from the log files in ~/.asum/logs
+    Logs {
+        /// Only show entries from this long ago (e.g. 30m, 2h, 1d, 1w) or this UTC date (e.g. 2024-05-01)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only show entries at this level or more severe
+        #[arg(long, value_enum)]
+        level: Option<LogLevel>,
+        /// Keep printing new entries as they are written
+        #[arg(short, long)]
+        follow: bool,
+        /// Largest number of entries to show before following
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+    },
+    /// Record whether the last generated message was used in the HEAD commit
+    Feedback,
+    /// Show acceptance rates, token usage and estimated cost per model
+    Stats {
+        /// Number of days of token usage to show
+        #[arg(long, default_value_t = 30)]
+        days: u32,
+    },
+    /// List the models offered by the active provider (same as `config models`)
+    Models,
+    /// Manage the cache of generated messages
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Show the effective prompts or edit them in $EDITOR
+    Prompt {
+        #[command(subcommand)]
+        action: PromptAction,
+    },
+    /// Inspect configuration options
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Manage the prepare-commit-msg git hook
+    Hook {
+        #[command(subcommand)]
+        action: HookAction,
+    },
+}
+
+/// Output formats of `asum report`.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+/// Actions of the `asum config` subcommand.
+#[derive(Debug, Subcommand)]
+pub enum ConfigAction {
+    /// Print a fully commented example asum.toml with every option and its default
+    Schema,
+    /// List the models offered by the active provider
+    Models,
+    /// Pick a model from the active provider and save it to the config file
+    SetModel {
+        /// Model to use; prompts with the available models when omitted
+        model: Option<String>,
+    },
+}
+
+/// Actions of the `asum cache` subcommand.
+#[derive(Debug, Subcommand)]
+pub enum CacheAction {
+    /// Delete every cached message
+    Clear,
+}
+
+/// Actions of the `asum prompt` subcommand.
+#[derive(Debug, Subcommand)]
+pub enum PromptAction {
+    /// Print the system and user prompts after defaults and overrides
+    Show,
+    /// Open a prompt in $EDITOR and save the result to the config file
+    Edit {
+        /// Prompt to edit
+        #[arg(value_enum, default_value_t = PromptKind::System)]
+        prompt: PromptKind,
+    },
+}
+
+/// Log levels, from the most to the least severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+/// The two prompts sent with every request.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum PromptKind {
+    System,
+    User,
+}
+
+/// Actions of the `asum hook` subcommand.
+#[derive(Debug, Subcommand)]
+pub enum HookAction {
+    /// Install the prepare-commit-msg hook (honors core.hooksPath)
+    Install {
+        /// Replace an existing hook that was not installed by asum
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove the hook installed by asum
+    Uninstall,
+    /// Show whether the hook is installed
+    Status,
+    /// Run the hook (invoked by git)
+    #[command(hide = true)]
+    Run {
+        /// Path of the commit message file
+        file: PathBuf,
+        /// Source of the commit message (message, template, merge, squash or commit)
+        source: Option<String>,
+        /// Commit object name, given when amending
+        sha: Option<String>,
+    },
+}
+
+impl Cli {
+    /// Returns the configuration file that settings are saved to: the `--config`
+    /// path, otherwise the file `load_config` would read.
+    pub fn config_file(&self) -> anyhow::Result<PathBuf> {
+        self.config
+            .clone()
+            .or_else(resolve_config_path)
+            .context("No configuration file found. Run 'asum init' to create one.")
+            .map_err(|e| Failure::Config.tag(e))
+    }
+
+    /// Loads the configuration (an explicit `--config` path, otherwise local `asum.toml`,
+    /// then `~/.asum/asum.toml`), then applies `ASUM_*` environment variables and
+    /// finally the `--provider`/`--model` overrides.
+    pub fn load_config(&self) -> anyhow::Result<AsumConfig> {
+        let mut config = match &self.config {
+            Some(path) => AsumConfig::load_from(path),
+            // Containers are usually configured through environment variables only
+            None => AsumConfig::load().or_else(|e| {
+                if is_remote_container() {
+                    info!("No asum.toml found; using defaults and ASUM_* environment variables.");
+                    AsumConfig::from_defaults()
+                } else {
+                    Err(e)
+                }
+            }),
+        }
+        .context("Failed to load configuration")
+        .map_err(|e| Failure::Config.tag(e))?;
+        config.apply_env(|name| std::env::var(name).ok());
+        config.apply_overrides(self.provider.as_deref(), self.model.as_deref());
+        config.dry_run = self.dry_run;
+        config.no_cache = self.no_cache;
+        config.preflight_report |= self.verbose;
+        if let Some(mode) = &self.untracked {
+            config.untracked_files = mode.clone();
+        }
+        Ok(config)
+    }
+
+    /// Where messages are copied: nowhere with `--no-clipboard`, otherwise the
+    /// `clipboard` backend of `config`. Unknown backends fall back to "auto".
+    pub fn clipboard(&self, config: &AsumConfig) -> Option<Box<dyn ClipboardSink>> {
+        if self.no_clipboard {
+            return None;
+        }
+        clipboard::sink(&config.clipboard).unwrap_or_else(|e| {
+            warn!("{} Using auto.", e);
+            Some(Box::new(clipboard::Auto))
+        })
+    }
+
+    /// The diff source selected by `--source`, `--all`, `--unstaged` or `--base`.
+    pub fn source_spec(&self) -> String {
+        if self.all {
+            "all".to_string()
+        } else if self.unstaged {
+            "worktree".to_string()
+        } else if let Some(base) = &self.base {
+            format!("base:{}", base)
+        } else {
+            self.source.clone()
+        }
+    }
+
+    /// Formats a generated commit message for the terminal. With `--a11y` the subject
+    /// and body are labeled so screen readers announce where each part starts.
+    pub fn format_message(&self, message: &str) -> String {
+        if !self.a11y {
+            return message.to_string();
+        }
+        let mut lines = message.lines();
+        let subject = lines.next().unwrap_or_default();
+        let body: Vec<&str> = lines.filter(|line| !line.trim().is_empty()).collect();
+        if body.is_empty() {
+            format!("SUBJECT: {}\nBODY: none", subject)
+        } else {
+            format!("SUBJECT: {}\nBODY:\n{}", subject, body.join("\n"))
+        }
+    }
+
+    /// Returns the log level for console output based on `--quiet` and `--verbose`.
+    pub fn log_level(&self) -> Level {
+        if self.quiet {
+            Level::ERROR
+        } else if self.verbose {
+            Level::DEBUG
+        } else {
+            Level::INFO
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_no_args() {
+        let cli = Cli::try_parse_from(["asum"]).unwrap();
+        assert!(cli.command.is_none());
+        assert!(cli.provider.is_none());
+        assert!(!cli.no_clipboard);
+        assert_eq!(cli.log_level(), Level::INFO);
+    }
+
+    #[test]
+    fn test_parse_global_flags() {
+        let cli = Cli::try_parse_from([
+            "asum",
+            "--provider",
+            "gemini",
+            "--model",
+            "gemini-pro",
+            "--config",
+            "/tmp/asum.toml",
+            "--no-clipboard",
+            "--verbose",
+        ])
+        .unwrap();
+        assert_eq!(cli.provider.as_deref(), Some("gemini"));
+        assert_eq!(cli.model.as_deref(), Some("gemini-pro"));
+        assert_eq!(cli.config, Some(PathBuf::from("/tmp/asum.toml")));
+        assert!(cli.no_clipboard);
+        assert_eq!(cli.log_level(), Level::DEBUG);
+    }
+
+    #[test]
+    fn test_format_message_table_driven() {
+        let cases = vec![
+            (vec!["asum"], "feat: a\n- b", "feat: a\n- b"),
+            (
+                vec!["asum", "--a11y"],
+                "feat: add login\n\n- add form\n- add route",
+                "SUBJECT: feat: add login\nBODY:\n- add form\n- add route",
+            ),
+            (
+                vec!["asum", "commit", "--a11y"],
+                "fix: typo",
+                "SUBJECT: fix: typo\nBODY: none",
+            ),
+        ];
+
+        for (args, message, expected) in cases {
+            let cli = Cli::try_parse_from(&args).unwrap();
+            assert_eq!(cli.format_message(message), expected, "args: {:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_parse_candidates() {
+        assert_eq!(Cli::try_parse_from(["asum"]).unwrap().candidates, 1);
+        assert_eq!(
+            Cli::try_parse_from(["asum", "-n", "3"]).unwrap().candidates,
+            3
+        );
+        assert!(Cli::try_parse_from(["asum", "-n", "0"]).is_err());
+        assert!(Cli::try_parse_from(["asum", "--candidates", "11"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_source() {
+        assert_eq!(Cli::try_parse_from(["asum"]).unwrap().source, "staged");
+        assert_eq!(
+            Cli::try_parse_from(["asum", "--source", "patch:fix.patch"])
+                .unwrap()
+                .source,
+            "patch:fix.patch"
+        );
+    }
+
+    #[test]
+    fn test_parse_untracked() {
+        let untracked = |args: &[&str]| {
+            Cli::try_parse_from(args)
+                .ok()
+                .map(|cli| cli.untracked.unwrap_or_default())
+        };
+        assert_eq!(untracked(&["asum"]).as_deref(), Some(""));
+        assert_eq!(
+            untracked(&["asum", "--untracked"]).as_deref(),
+            Some("names")
+        );
+        assert_eq!(
+            untracked(&["asum", "--untracked=content"]).as_deref(),
+            Some("content")
+        );
+        assert_eq!(untracked(&["asum", "--untracked=all"]), None);
+        // The value needs `=`, so a following subcommand is not taken for it
+        assert_eq!(
+            untracked(&["asum", "--untracked", "commit"]).as_deref(),
+            Some("names")
+        );
+    }
+
+    #[test]
+    fn test_parse_source_shortcuts_table_driven() {
+        let cases = vec![
+            (vec!["asum"], Some("staged")),
+            (vec!["asum", "--all"], Some("all")),
+            (vec!["asum", "-a"], Some("all")),
+            (vec!["asum", "--unstaged"], Some("worktree")),
+            (vec!["asum", "--source", "stdin"], Some("stdin")),
+            (vec!["asum", "--base", "main"], Some("base:main")),
+            (vec!["asum", "--base", "main", "--all"], None),
+            (vec!["asum", "--base", "main", "--source", "stdin"], None),
+            (vec!["asum", "--all", "--unstaged"], None),
+            (vec!["asum", "--all", "--source", "stdin"], None),
+            (vec!["asum", "--unstaged", "--source", "hg"], None),
+        ];
+        for (args, expected) in cases {
+            let cli = Cli::try_parse_from(&args);
+            assert_eq!(
+                cli.as_ref().ok().map(|cli| cli.source_spec()).as_deref(),
+                expected,
+                "args: {:?}",
+                args
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_global_flags_after_subcommand() {
+        let cli = Cli::try_parse_from(["asum", "verify", "--config", "custom.toml", "-q"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Verify { online: false })
+        ));
+        assert_eq!(cli.config, Some(PathBuf::from("custom.toml")));
+        assert_eq!(cli.log_level(), Level::ERROR);
+    }
+
+    #[test]
+    fn test_parse_version_subcommand() {
+        let cli = Cli::try_parse_from(["asum", "version"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Version)));
+    }
+
+    #[test]
+    fn test_parse_manpage() {
+        let cli = Cli::try_parse_from(["asum", "manpage"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Manpage)));
+    }
+
+    #[test]
+    fn test_parse_init_flags() {
+        let cli = Cli::try_parse_from(["asum", "init", "--global", "--force"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Init {
+                global: true,
+                force: true
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_setup() {
+        let cli = Cli::try_parse_from(["asum", "setup", "--force"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Setup { force: true })));
+    }
+
+    #[test]
+    fn test_parse_commit_flags() {
+        let cli = Cli::try_parse_from(["asum", "commit", "--yes"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Commit {
+                yes: true,
+                edit: false
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_split() {
+        let cli = Cli::try_parse_from(["asum", "split"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Split {
+                commit: false,
+                yes: false
+            })
+        ));
+        let cli = Cli::try_parse_from(["asum", "split", "--commit", "--yes"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Split {
+                commit: true,
+                yes: true
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_translate() {
+        let cli = Cli::try_parse_from(["asum", "translate", "v1..HEAD", "--to", "de"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Translate { ref target, ref to }) if target == "v1..HEAD" && to == "de"
+        ));
+    }
+
+    #[test]
+    fn test_parse_tag() {
+        let cli = Cli::try_parse_from(["asum", "tag", "v2.0.0"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Tag { ref name, create: false }) if name == "v2.0.0"
+        ));
+
+        let cli = Cli::try_parse_from(["asum", "tag", "v2.0.0", "--create"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Tag { create: true, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_comment() {
+        let cli =
+            Cli::try_parse_from(["asum", "comment", "--file", "src/x.rs", "--lines", "10-42"])
+                .unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Comment { ref file, lines: Some(ref lines) })
+                if file == "src/x.rs" && lines == "10-42"
+        ));
+
+        assert!(Cli::try_parse_from(["asum", "comment", "--lines", "10-42"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_logs() {
+        let cli = Cli::try_parse_from(["asum", "logs"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Logs {
+                since: None,
+                level: None,
+                follow: false,
+                limit: 50
+            })
+        ));
+
+        let cli = Cli::try_parse_from(["asum", "logs", "--since", "2h", "--level", "warn", "-f"])
+            .unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Logs {
+                since: Some(ref since),
+                level: Some(LogLevel::Warn),
+                follow: true,
+                ..
+            }) if since == "2h"
+        ));
+    }
+
+    #[test]
+    fn test_parse_jj() {
+        let cli = Cli::try_parse_from(["asum", "jj", "-y"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Jj { yes: true })));
+    }
+
+    #[test]
+    fn test_parse_fixup() {
+        let cli = Cli::try_parse_from(["asum", "fixup"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Fixup {
+                create: false,
+                depth: 20
+            })
+        ));
+
+        let cli = Cli::try_parse_from(["asum", "fixup", "-c", "--depth", "5"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Fixup {
+                create: true,
+                depth: 5
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_stash() {
+        let cli = Cli::try_parse_from(["asum", "stash"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Stash {
+                include_untracked: false
+            })
+        ));
+
+        let cli = Cli::try_parse_from(["asum", "stash", "-u"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Stash {
+                include_untracked: true
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_squash_prep() {
+        let cli = Cli::try_parse_from(["asum", "squash-prep"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::SquashPrep { base: None })
+        ));
+
+        let cli = Cli::try_parse_from(["asum", "squash-prep", "--base", "develop"]).unwrap();
+        assert!(
+            matches!(cli.command, Some(Command::SquashPrep { base: Some(ref base) }) if base == "develop")
+        );
+    }
+
+    #[test]
+    fn test_parse_wip() {
+        let cli = Cli::try_parse_from(["asum", "wip"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Wip { create: false })));
+
+        let cli = Cli::try_parse_from(["asum", "wip", "-c"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Wip { create: true })));
+    }
+
+    #[test]
+    fn test_parse_explain_defaults_to_head() {
+        let cli = Cli::try_parse_from(["asum", "explain"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Explain { ref commit }) if commit == "HEAD"));
+
+        let cli = Cli::try_parse_from(["asum", "explain", "3f2c1ab"]).unwrap();
+        assert!(
+            matches!(cli.command, Some(Command::Explain { ref commit }) if commit == "3f2c1ab")
+        );
+    }
+
+    #[test]
+    fn test_parse_models_shortcut() {
+        let cli = Cli::try_parse_from(["asum", "models"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Models)));
+
+        let cli = Cli::try_parse_from(["asum", "config", "models"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Config {
+                action: ConfigAction::Models
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_standup_flags() {
+        let cli = Cli::try_parse_from(["asum", "standup", "--cron-friendly", "--output", "s.md"])
+            .unwrap();
+        match cli.command {
+            Some(Command::Standup {
+                since,
+                author,
+                output,
+                cron_friendly,
+            }) => {
+                assert_eq!(since, "24 hours ago");
+                assert!(author.is_none());
+                assert_eq!(output.as_deref(), Some("s.md"));
+                assert!(cron_friendly);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_serve() {
+        let cli = Cli::try_parse_from(["asum", "serve"]).unwrap();
+        assert!(
+            matches!(cli.command, Some(Command::Serve { ref listen }) if listen == "127.0.0.1:7821")
+        );
+    }
+
+    #[test]
+    fn test_parse_report() {
+        let cli = Cli::try_parse_from(["asum", "report"]).unwrap();
+        match cli.command {
+            Some(Command::Report {
+                since,
+                format,
+                repos,
+                output,
+            }) => {
+                assert_eq!(since, "1 week ago");
+                assert_eq!(format, ReportFormat::Markdown);
+                assert_eq!(repos, vec![PathBuf::from(".")]);
+                assert!(output.is_none());
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        let cli = Cli::try_parse_from([
+            "asum", "report", "--format", "html", "--repo", "a", "--repo", "b",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Report { format: ReportFormat::Html, ref repos, .. }) if repos.len() == 2
+        ));
+        assert!(Cli::try_parse_from(["asum", "report", "--format", "pdf"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_redo() {
+        let cli = Cli::try_parse_from(["asum", "redo"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Redo { hint: None })));
+
+        let cli = Cli::try_parse_from(["asum", "redo", "--hint", "mention the migration"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Redo { hint: Some(ref hint) }) if hint == "mention the migration"
+        ));
+    }
+
+    #[test]
+    fn test_parse_history() {
+        let cli = Cli::try_parse_from(["asum", "history"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::History {
+                search: None,
+                limit: 20,
+                repo: None
+            })
+        ));
+
+        let cli = Cli::try_parse_from(["asum", "history", "login", "--limit", "5", "--repo", "."])
+            .unwrap();
+        match cli.command {
+            Some(Command::History {
+                search,
+                limit,
+                repo,
+            }) => {
+                assert_eq!(search.as_deref(), Some("login"));
+                assert_eq!(limit, 5);
+                assert_eq!(repo, Some(PathBuf::from(".")));
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_last() {
+        let cli = Cli::try_parse_from(["asum", "last"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Last)));
+    }
+
+    #[test]
+    fn test_parse_stats() {
+        let cli = Cli::try_parse_from(["asum", "stats"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Stats { days: 30 })));
+
+        let cli = Cli::try_parse_from(["asum", "stats", "--days", "7"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Stats { days: 7 })));
+    }
+
+    #[test]
+    fn test_parse_cache() {
+        let cli = Cli::try_parse_from(["asum", "cache", "clear"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Cache {
+                action: CacheAction::Clear
+            })
+        ));
+        assert!(!cli.no_cache);
+
+        let cli = Cli::try_parse_from(["asum", "commit", "--no-cache"]).unwrap();
+        assert!(cli.no_cache);
+    }
+
+    #[test]
+    fn test_parse_prompt() {
+        let cli = Cli::try_parse_from(["asum", "prompt", "show"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Prompt {
+                action: PromptAction::Show
+            })
+        ));
+
+        let cases = vec![
+            (vec!["asum", "prompt", "edit"], PromptKind::System),
+            (vec!["asum", "prompt", "edit", "user"], PromptKind::User),
+        ];
+        for (args, expected) in cases {
+            let cli = Cli::try_parse_from(&args).unwrap();
+            match cli.command {
+                Some(Command::Prompt {
+                    action: PromptAction::Edit { prompt },
+                }) => assert_eq!(prompt, expected, "args: {:?}", args),
+                other => panic!("unexpected command: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_hook_actions() {
+        let cli = Cli::try_parse_from(["asum", "hook", "install", "--force"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Hook {
+                action: HookAction::Install { force: true }
+            })
+        ));
+
+        let cli =
+            Cli::try_parse_from(["asum", "hook", "run", ".git/COMMIT_EDITMSG", "message"]).unwrap();
+        match cli.command {
+            Some(Command::Hook {
+                action: HookAction::Run { file, source, sha },
+            }) => {
+                assert_eq!(file, PathBuf::from(".git/COMMIT_EDITMSG"));
+                assert_eq!(source.as_deref(), Some("message"));
+                assert!(sha.is_none());
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_quiet_conflicts_with_verbose() {
+        assert!(Cli::try_parse_from(["asum", "--quiet", "--verbose"]).is_err());
+    }
+}
@@ -0,0 +1,443 @@
+//! Clipboard and message output for ASUM.
+//!
+//! Copies generated messages through a `ClipboardSink`: the desktop clipboard, the
+//! OSC 52 terminal escape sequence, `wl-copy`, tmux or a file, as selected by the
+//! `clipboard` setting. The default picks a working backend automatically, using
+//! OSC 52 in remote containers where no desktop clipboard exists.
+
+use crate::config::last_message_path;
+use crate::environment::is_remote_container;
+use anyhow::{Context, Result, anyhow};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use tracing::{debug, error, info, warn};
+
+/// Environment variable naming a file the generated message is also written to.
+pub const MESSAGE_FILE_VAR: &str = "ASUM_MESSAGE_FILE";
+
+/// Names accepted by the `clipboard` setting besides `file:<PATH>`.
+pub const BACKENDS: &[&str] = &["auto", "arboard", "osc52", "wl-copy", "tmux", "none"];
+
+/// A place generated messages are copied to.
+pub trait ClipboardSink: Send + Sync {
+    /// Name of the backend, used in messages.
+    fn name(&self) -> &str;
+
+    /// Copies `message`, failing when the backend is not available.
+    fn copy(&self, message: &str) -> Result<()>;
+}
+
+/// The desktop clipboard, through `arboard`.
+pub struct Arboard;
+
+impl ClipboardSink for Arboard {
+    fn name(&self) -> &str {
+        "arboard"
+    }
+
+    fn copy(&self, message: &str) -> Result<()> {
+        arboard::Clipboard::new()?.set_text(message)?;
+        Ok(())
+    }
+}
+
+/// The clipboard of the local terminal, through the OSC 52 escape sequence. Works
+/// over SSH and in containers when the terminal supports it.
+pub struct Osc52;
+
+impl ClipboardSink for Osc52 {
+    fn name(&self) -> &str {
+        "osc52"
+    }
+
+    fn copy(&self, message: &str) -> Result<()> {
+        Ok(copy_osc52(message)?)
+    }
+}
+
+/// The Wayland clipboard, through `wl-copy` from wl-clipboard.
+pub struct WlCopy;
+
+impl ClipboardSink for WlCopy {
+    fn name(&self) -> &str {
+        "wl-copy"
+    }
+
+    fn copy(&self, message: &str) -> Result<()> {
+        pipe_to(Command::new("wl-copy"), message)
+    }
+}
+
+/// A tmux paste buffer, also forwarded to the terminal clipboard when tmux's
+/// `set-clipboard` option allows it (tmux 3.2 or later).
+pub struct Tmux;
+
+impl ClipboardSink for Tmux {
+    fn name(&self) -> &str {
+        "tmux"
+    }
+
+    fn copy(&self, message: &str) -> Result<()> {
+        let mut command = Command::new("tmux");
+        command.args(["load-buffer", "-w", "-"]);
+        pipe_to(command, message)
+    }
+}
+
+/// A file that is overwritten with every message.
+pub struct FileSink {
+    pub path: PathBuf,
+}
+
+impl ClipboardSink for FileSink {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    fn copy(&self, message: &str) -> Result<()> {
+        std::fs::write(&self.path, message)
+            .with_context(|| format!("Could not write {}", self.path.display()))
+    }
+}
+
+/// Keeps the last message in memory, for tests and programs embedding ASUM.
+#[derive(Default)]
+pub struct MemoryClipboard {
+    contents: Mutex<Option<String>>,
+}
+
+impl MemoryClipboard {
+    /// The last copied message, if any.
+    pub fn contents(&self) -> Option<String> {
+        self.contents.lock().unwrap().clone()
+    }
+}
+
+impl ClipboardSink for MemoryClipboard {
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    fn copy(&self, message: &str) -> Result<()> {
+        *self.contents.lock().unwrap() = Some(message.to_string());
+        Ok(())
+    }
+}
+
+/// Tries the backends that fit the environment until one works: OSC 52 in remote
+/// containers, whose desktop clipboard belongs to the host; elsewhere the desktop
+/// clipboard, then `wl-copy` under Wayland, tmux inside tmux, and OSC 52 when
+/// stderr is a terminal.
+pub struct Auto;
+
+impl ClipboardSink for Auto {
+    fn name(&self) -> &str {
+        "auto"
+    }
+
+    fn copy(&self, message: &str) -> Result<()> {
+        let mut failures = Vec::new();
+        for backend in auto_backends() {
+            match backend.copy(message) {
+                Ok(()) => {
+                    debug!("Copied with {}", backend.name());
+                    return Ok(());
+                }
+                Err(e) => failures.push(format!("{}: {:#}", backend.name(), e)),
+            }
+        }
+        Err(anyhow!("no backend worked ({})", failures.join("; ")))
+    }
+}
+
+/// Backends `Auto` tries, in order.
+fn auto_backends() -> Vec<Box<dyn ClipboardSink>> {
+    if is_remote_container() {
+        return vec![Box::new(Osc52)];
+    }
+    let mut backends: Vec<Box<dyn ClipboardSink>> = vec![Box::new(Arboard)];
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        backends.push(Box::new(WlCopy));
+    }
+    if std::env::var_os("TMUX").is_some() {
+        backends.push(Box::new(Tmux));
+    }
+    if std::io::stderr().is_terminal() {
+        backends.push(Box::new(Osc52));
+    }
+    backends
+}
+
+/// Returns the sink for a `clipboard` setting: one of `BACKENDS` or `file:<PATH>`.
+/// `none` gives `None`, meaning messages are not copied.
+pub fn sink(backend: &str) -> Result<Option<Box<dyn ClipboardSink>>> {
+    if let Some(path) = backend.strip_prefix("file:") {
+        return Ok(Some(Box::new(FileSink {
+            path: PathBuf::from(path),
+        })));
+    }
+    let sink: Box<dyn ClipboardSink> = match backend {
+        "auto" => Box::new(Auto),
+        "arboard" => Box::new(Arboard),
+        "osc52" => Box::new(Osc52),
+        "wl-copy" => Box::new(WlCopy),
+        "tmux" => Box::new(Tmux),
+        "none" => return Ok(None),
+        other => {
+            return Err(anyhow!(
+                "Unknown clipboard '{}'; expected {} or file:<PATH>.",
+                other,
+                BACKENDS.join(", ")
+            ));
+        }
+    };
+    Ok(Some(sink))
+}
+
+/// Delivers the message to `clipboard` and, if `ASUM_MESSAGE_FILE` is set, to that file.
+/// Failures are reported but never fatal, since the message has already been printed to stdout.
+/// When every output fails, the message is kept as a restore point.
+pub fn deliver(message: &str, clipboard: Option<&dyn ClipboardSink>) {
+    let mut attempted = false;
+    let mut delivered = false;
+    if let Some(path) = std::env::var_os(MESSAGE_FILE_VAR).filter(|p| !p.is_empty()) {
+        attempted = true;
+        match std::fs::write(Path::new(&path), message) {
+            Ok(_) => {
+                info!("Message written to {}", Path::new(&path).display());
+                delivered = true;
+            }
+            Err(e) => error!(
+                "Could not write message to {}: {}",
+                Path::new(&path).display(),
+                e
+            ),
+        }
+    }
+
+    if let Some(clipboard) = clipboard {
+        attempted = true;
+        match clipboard.copy(message) {
+            Ok(()) => {
+                info!("Message copied to the clipboard ({}).", clipboard.name());
+                delivered = true;
+            }
+            Err(e) => warn!(
+                "Could not copy the message with {} ({:#}). The message was printed to stdout only.",
+                clipboard.name(),
+                e
+            ),
+        }
+    }
+
+    if attempted && !delivered {
+        save_restore_point(message);
+    }
+}
+
+/// Writes the message to `~/.asum/last-message.txt` and reports the path, so a
+/// generated message is not lost when it could not be delivered. `asum last`
+/// prints it again.
+pub fn save_restore_point(message: &str) {
+    let Some(path) = last_message_path() else {
+        error!("Could not determine the home directory to save the message.");
+        return;
+    };
+    let saved = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, message));
+    match saved {
+        Ok(_) => warn!(
+            "The message was saved to {}. Run `asum last` to print it again.",
+            path.display()
+        ),
+        Err(e) => error!("Could not save the message to {}: {}", path.display(), e),
+    }
+}
+
+/// Runs `command` with `message` on its stdin and waits for it to succeed.
+fn pipe_to(mut command: Command, message: &str) -> Result<()> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Could not run {}", program))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(message.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow!("{} failed ({})", program, status));
+    }
+    Ok(())
+}
+
+/// Writes the OSC 52 sequence to the controlling terminal, or to stderr if it is a terminal.
+/// Stdout is avoided so piping the message elsewhere stays clean.
+fn copy_osc52(message: &str) -> std::io::Result<()> {
+    let sequence = osc52_sequence(message);
+    if let Ok(mut tty) = std::fs::OpenOptions::new().write(true).open("/dev/tty") {
+        tty.write_all(sequence.as_bytes())?;
+        return tty.flush();
+    }
+
+    let mut stderr = std::io::stderr();
+    if !stderr.is_terminal() {
+        return Err(std::io::Error::other("no terminal attached"));
+    }
+    stderr.write_all(sequence.as_bytes())?;
+    stderr.flush()
+}
+
+/// Builds the OSC 52 "set clipboard" escape sequence for the message.
+pub fn osc52_sequence(message: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64_encode(message.as_bytes()))
+}
+
+/// Standard base64 encoding with padding.
+fn base64_encode(input: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+
+        encoded.push(ALPHABET[(n >> 18) as usize & 63] as char);
+        encoded.push(ALPHABET[(n >> 12) as usize & 63] as char);
+        encoded.push(if chunk.len() > 1 {
+            ALPHABET[(n >> 6) as usize & 63] as char
+        } else {
+            '='
+        });
+        encoded.push(if chunk.len() > 2 {
+            ALPHABET[n as usize & 63] as char
+        } else {
+            '='
+        });
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_encode_table_driven() {
+        let cases = vec![
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("feat: add x", "ZmVhdDogYWRkIHg="),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(
+                base64_encode(input.as_bytes()),
+                expected,
+                "input: {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(osc52_sequence("foo"), "\x1b]52;c;Zm9v\x07");
+    }
+
+    #[test]
+    fn test_sink_table_driven() {
+        let cases = vec![
+            ("auto", Some("auto")),
+            ("arboard", Some("arboard")),
+            ("osc52", Some("osc52")),
+            ("wl-copy", Some("wl-copy")),
+            ("tmux", Some("tmux")),
+            ("file:/tmp/message.txt", Some("file")),
+            ("none", None),
+        ];
+        for (backend, expected) in cases {
+            let sink = sink(backend).unwrap();
+            assert_eq!(sink.as_ref().map(|s| s.name()), expected, "{}", backend);
+        }
+        assert!(sink("xclip").is_err());
+    }
+
+    #[test]
+    fn test_file_sink_overwrites() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("message.txt");
+        let sink = FileSink { path: path.clone() };
+        sink.copy("feat: first").unwrap();
+        sink.copy("feat: second").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "feat: second");
+
+        let missing = FileSink {
+            path: dir.path().join("missing").join("message.txt"),
+        };
+        assert!(missing.copy("feat: lost").is_err());
+    }
+
+    #[test]
+    fn test_deliver_copies_to_the_sink() {
+        let _guard = crate::test_utils::TEST_MUTEX.lock().unwrap();
+        let home = crate::test_utils::TempHome::create();
+        let memory = MemoryClipboard::default();
+        deliver("feat: copied", Some(&memory));
+        assert_eq!(memory.contents().as_deref(), Some("feat: copied"));
+        assert!(!last_message_path().unwrap().exists());
+
+        // A failing sink keeps the message as a restore point
+        let broken = FileSink {
+            path: home.dir.path().join("missing").join("message.txt"),
+        };
+        deliver("feat: keep me", Some(&broken));
+        assert_eq!(
+            std::fs::read_to_string(last_message_path().unwrap()).unwrap(),
+            "feat: keep me"
+        );
+    }
+
+    #[test]
+    fn test_deliver_writes_message_file() {
+        let _guard = crate::test_utils::TEST_MUTEX.lock().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("message.txt");
+
+        unsafe { std::env::set_var(MESSAGE_FILE_VAR, &path) };
+        deliver("feat: write file", None);
+        unsafe { std::env::remove_var(MESSAGE_FILE_VAR) };
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "feat: write file");
+    }
+
+    #[test]
+    fn test_deliver_keeps_restore_point_when_every_output_fails() {
+        let _guard = crate::test_utils::TEST_MUTEX.lock().unwrap();
+        let home = crate::test_utils::TempHome::create();
+        let restore = last_message_path().unwrap();
+
+        // Only stdout was asked for, so nothing failed
+        deliver("feat: stdout only", None);
+        assert!(!restore.exists());
+
+        let unwritable = home.dir.path().join("missing").join("message.txt");
+        unsafe { std::env::set_var(MESSAGE_FILE_VAR, &unwritable) };
+        deliver("feat: keep me", None);
+        unsafe { std::env::remove_var(MESSAGE_FILE_VAR) };
+
+        assert_eq!(std::fs::read_to_string(&restore).unwrap(), "feat: keep me");
+    }
+}
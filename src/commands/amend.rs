@@ -0,0 +1,49 @@
+//! `asum amend` command.
+//!
+//! Generates an improved message for the last commit from its diff and
+//! rewrites the commit message once the user confirms.
+
+use crate::cli::Cli;
+use crate::clipboard;
+use crate::commands::commit::{Decision, check_message, decide};
+use crate::git::{amend_message, get_commit_diff_in_path, get_commit_message_in_path};
+use crate::pipeline::{adapt_prompts, save_generation, summarize_diff};
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Generates a new message for `HEAD` and amends the commit with it.
+///
+/// # Arguments
+/// * `yes` - Amend without asking for confirmation.
+/// * `edit` - Open the editor with the generated message before amending.
+pub async fn run(cli: &Cli, yes: bool, edit: bool) -> Result<()> {
+    let mut config = cli.load_config()?;
+    let repo = Path::new(".");
+
+    let current = get_commit_message_in_path("HEAD", repo)?;
+    let diff_text = get_commit_diff_in_path("HEAD", &config.pathspecs(), repo)
+        .context("Failed to get the last commit's diff")?;
+
+    adapt_prompts(&mut config, repo, &diff_text).await;
+    let message = summarize_diff(config.clone(), diff_text.clone()).await?;
+    save_generation(&config, repo, &diff_text, &message);
+
+    println!("Current message:\n{}\n", current);
+    println!("Suggested message:\n{}\n", cli.format_message(&message));
+    check_message(&message, yes && !edit)?;
+
+    let stdin = std::io::stdin();
+    match decide(&mut stdin.lock(), &mut std::io::stdout(), yes, edit)? {
+        Decision::Commit => {
+            amend_message(&message, false).inspect_err(|_| clipboard::save_restore_point(&message))
+        }
+        Decision::Edit => {
+            amend_message(&message, true).inspect_err(|_| clipboard::save_restore_point(&message))
+        }
+        Decision::Abort => {
+            println!("Aborted. The last commit was not changed.");
+            clipboard::deliver(&message, cli.clipboard(&config).as_deref());
+            Ok(())
+        }
+    }
+}
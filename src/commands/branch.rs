@@ -0,0 +1,136 @@
+//! `asum branch` command.
+//!
+//! Suggests a kebab-case branch name such as `feat/oauth2-login-flow` for the
+//! staged changes (or the working tree when nothing is staged) and can create it.
+
+use crate::cli::Cli;
+use crate::clipboard;
+use crate::failure::Failure;
+use crate::git::{
+    create_branch_in_path, get_git_diff, get_staged_files, get_worktree_diff_in_path,
+};
+use crate::pipeline::summarize_diff;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// System prompt of the branch name suggestion, such as `feat/oauth2-login-flow`.
+const BRANCH_SYSTEM_PROMPT: &str = "You are a git expert. Suggest a short git branch name for the given changes. \
+Use the form <type>/<kebab-case-description>, where <type> is one of feat, fix, refactor, docs, test, chore or perf, \
+and the description has 2 to 5 lowercase words. Reply with the branch name only.";
+
+/// User prompt template; `{{diff}}` is replaced with the changes.
+const BRANCH_USER_PROMPT: &str = "Changes:\n{{diff}}\n\nBranch name:";
+
+/// Maximum length of the generated branch name.
+const MAX_BRANCH_LENGTH: usize = 60;
+
+/// Generates a branch name and prints it, or creates and switches to it with `create`.
+pub async fn run(cli: &Cli, create: bool) -> Result<()> {
+    let mut config = cli.load_config()?;
+    let sink = cli.clipboard(&config);
+
+    let mut diff_text = get_git_diff(&config.pathspecs()).context("Failed to get git diff")?;
+    if diff_text.is_empty() {
+        diff_text = get_worktree_diff_in_path(&config.pathspecs(), ".")
+            .context("Failed to get working tree diff")?;
+    }
+    if diff_text.is_empty() {
+        diff_text = get_staged_files().context("Failed to get staged files")?;
+    }
+    if diff_text.is_empty() {
+        return Err(Failure::NoChanges.error("No changes found."));
+    }
+
+    config.system_prompt = BRANCH_SYSTEM_PROMPT.to_string();
+    config.user_prompt = BRANCH_USER_PROMPT.to_string();
+    let reply = summarize_diff(config, diff_text).await?;
+    let name = sanitize_branch_name(&reply).ok_or_else(|| {
+        Failure::InvalidOutput.error(format!("AI did not return a usable branch name: {}", reply))
+    })?;
+
+    if create {
+        create_branch_in_path(&name, Path::new("."))?;
+        println!("[OK] Switched to a new branch '{}'", name);
+    } else {
+        println!("{}", name);
+        clipboard::deliver(&name, sink.as_deref());
+    }
+    Ok(())
+}
+
+/// Turns the model's reply into a valid kebab-case branch name.
+/// Uses the first non-empty line, lowercases it, replaces anything other than
+/// letters, digits and `/` with dashes, and keeps at most a `type/` prefix.
+pub fn sanitize_branch_name(reply: &str) -> Option<String> {
+    let line = reply
+        .lines()
+        .map(|l| l.trim().trim_matches(|c| c == '`' || c == '"' || c == '\''))
+        .find(|l| !l.is_empty())?;
+    // Drop a leading label such as "Branch name:"
+    let line = line.rsplit(": ").next().unwrap_or(line);
+
+    let segments: Vec<String> = line
+        .to_lowercase()
+        .split('/')
+        .map(|segment| {
+            segment
+                .split(|c: char| !c.is_ascii_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>()
+                .join("-")
+        })
+        .filter(|segment| !segment.is_empty())
+        .collect();
+
+    let name = match segments.as_slice() {
+        [] => return None,
+        [description] => description.clone(),
+        [kind, rest @ ..] => format!("{}/{}", kind, rest.join("-")),
+    };
+
+    let mut name: String = name.chars().take(MAX_BRANCH_LENGTH).collect();
+    while name.ends_with(['-', '/']) {
+        name.pop();
+    }
+    Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_branch_name_table_driven() {
+        let cases = vec![
+            ("feat/oauth2-login-flow", Some("feat/oauth2-login-flow")),
+            (
+                "  `Fix/Null Pointer_Crash`  \n",
+                Some("fix/null-pointer-crash"),
+            ),
+            ("Branch name: feat/add-login", Some("feat/add-login")),
+            ("\n\nrefactor/config/loader", Some("refactor/config-loader")),
+            ("\"docs/update readme!\"", Some("docs/update-readme")),
+            ("add-caching", Some("add-caching")),
+            ("", None),
+            ("```", None),
+            ("!!!", None),
+        ];
+
+        for (reply, expected) in cases {
+            assert_eq!(
+                sanitize_branch_name(reply).as_deref(),
+                expected,
+                "reply: {:?}",
+                reply
+            );
+        }
+    }
+
+    #[test]
+    fn test_sanitize_branch_name_truncates() {
+        let reply = format!("feat/{}", "word-".repeat(30));
+        let name = sanitize_branch_name(&reply).unwrap();
+        assert!(name.len() <= MAX_BRANCH_LENGTH);
+        assert!(!name.ends_with('-'));
+    }
+}
@@ -0,0 +1,19 @@
+//! `asum cache` command.
+//!
+//! Manages the messages cached for identical changes, which are returned
+//! instead of asking the provider again.
+
+use crate::cli::CacheAction;
+use crate::db::Db;
+use anyhow::Result;
+
+/// Runs a cache action.
+pub fn run(action: &CacheAction) -> Result<()> {
+    match action {
+        CacheAction::Clear => {
+            let removed = Db::open_default()?.clear_cache()?;
+            println!("Removed {} cached message(s).", removed);
+        }
+    }
+    Ok(())
+}
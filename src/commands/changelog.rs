@@ -0,0 +1,124 @@
+//! `asum changelog` command.
+//!
+//! Writes one user-facing changelog entry per commit in a range. The entries are
+//! generated as a rate-limited, resumable batch.
+
+use crate::batch::{Batch, BatchItem};
+use crate::cli::Cli;
+use crate::clipboard;
+use crate::commands::squash::normalize_range;
+use crate::git::{
+    get_commit_diff_in_path, get_commit_message_in_path, get_range_commits_in_path,
+    get_repo_root_in_path,
+};
+use crate::pipeline::summarize_diff;
+use anyhow::{Result, anyhow};
+use std::path::Path;
+
+/// System prompt of one changelog entry: the user-visible effect of a single commit.
+const CHANGELOG_SYSTEM_PROMPT: &str = r#"You write release notes for end users.
+Given one commit message and its diff, reply with a single changelog line that describes
+the user-visible effect, starting with a past-tense verb (e.g. "Added", "Fixed", "Changed").
+Do not add a leading "- ", a commit hash or any other text.
+If the commit has no user-visible effect (tests, CI, refactoring, formatting), reply with SKIP."#;
+
+/// User prompt template; `{{diff}}` is replaced with the commit message and diff.
+const CHANGELOG_USER_PROMPT: &str = "[COMMIT]\n{{diff}}\n\n[CHANGELOG LINE]";
+
+/// Reply the model uses for commits that do not belong in the changelog.
+const SKIP_REPLY: &str = "SKIP";
+
+/// Generates the changelog for `range` and prints it.
+pub async fn run(cli: &Cli, range: &str) -> Result<()> {
+    let mut config = cli.load_config()?;
+    let repo = Path::new(".");
+    let range = normalize_range(range);
+
+    let commits = get_range_commits_in_path(&range, repo)?;
+    if commits.is_empty() {
+        return Err(anyhow!("No commits found in {}.", range));
+    }
+
+    let mut items = Vec::with_capacity(commits.len());
+    for (hash, _) in &commits {
+        let message = get_commit_message_in_path(hash, repo)?;
+        let diff = get_commit_diff_in_path(hash, &config.pathspecs(), repo)?;
+        items.push(BatchItem {
+            key: hash.clone(),
+            input: format!("Message:\n{}\n\nDiff:\n{}", message, diff),
+        });
+    }
+
+    config.system_prompt = CHANGELOG_SYSTEM_PROMPT.to_string();
+    config.user_prompt = CHANGELOG_USER_PROMPT.to_string();
+
+    // Results are shared between runs of the same repository and model, so an
+    // interrupted changelog resumes even if the range was written differently
+    let batch_id = format!(
+        "changelog:{}:{}:{}",
+        get_repo_root_in_path(repo)?.display(),
+        config.active_provider,
+        config.active_model().unwrap_or_default()
+    );
+    let mut batch = Batch::new(&batch_id, &config);
+    let entries = batch
+        .run(&items, |input| summarize_diff(config.clone(), input))
+        .await?;
+
+    let changelog = render(&range, &commits, &entries);
+    println!("{}", changelog);
+    clipboard::deliver(&changelog, cli.clipboard(&config).as_deref());
+    Ok(())
+}
+
+/// Renders the entries as a Markdown list with short commit hashes, leaving out
+/// commits the model marked as `SKIP`.
+pub fn render(range: &str, commits: &[(String, String)], entries: &[String]) -> String {
+    let mut changelog = format!("## Changes in {}\n", range);
+    let mut listed = 0;
+    for ((hash, _), entry) in commits.iter().zip(entries) {
+        let entry = entry.lines().next().unwrap_or_default().trim();
+        let entry = entry.trim_start_matches("- ").trim();
+        if entry.is_empty() || entry.eq_ignore_ascii_case(SKIP_REPLY) {
+            continue;
+        }
+        changelog.push_str(&format!("\n- {} ({})", entry, &hash[..hash.len().min(7)]));
+        listed += 1;
+    }
+    if listed == 0 {
+        changelog.push_str("\nNo user-visible changes.");
+    }
+    changelog
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_table_driven() {
+        let commits = vec![
+            ("1234567890".to_string(), "feat: login".to_string()),
+            ("abcdef1234".to_string(), "test: more tests".to_string()),
+        ];
+        let cases = vec![
+            (
+                vec!["Added login with OAuth2", "SKIP"],
+                "## Changes in v1..HEAD\n\n- Added login with OAuth2 (1234567)",
+            ),
+            (
+                vec!["- Added login\nextra line", "Fixed a crash"],
+                "## Changes in v1..HEAD\n\n- Added login (1234567)\n- Fixed a crash (abcdef1)",
+            ),
+            (
+                vec!["skip", ""],
+                "## Changes in v1..HEAD\n\nNo user-visible changes.",
+            ),
+        ];
+
+        for (entries, expected) in cases {
+            let entries: Vec<String> = entries.into_iter().map(String::from).collect();
+            assert_eq!(render("v1..HEAD", &commits, &entries), expected);
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! `asum comment` command.
+//!
+//! Summarizes a region of the staged changes into a note in the Conventional
+//! Comments format (`<label> [decorations]: <subject>`), ready to paste into a
+//! review thread. The region is read with the same pathspec diff as commit
+//! messages, narrowed to the hunks that touch the requested lines.
+
+use crate::cli::Cli;
+use crate::clipboard;
+use crate::failure::Failure;
+use crate::git::get_git_diff_in_path;
+use crate::pipeline::summarize_diff;
+use anyhow::{Result, anyhow};
+
+/// System prompt of the review comment, in the Conventional Comments format.
+const COMMENT_SYSTEM_PROMPT: &str = r#"You are a senior engineer leaving a comment in a code review thread.
+Given a region of a change, write one comment in the Conventional Comments format:
+
+<label> [decorations]: <subject>
+
+<discussion>
+
+- label: one of praise, nitpick, suggestion, issue, todo, question, thought, chore, note.
+- decorations: optional, in parentheses, e.g. (non-blocking), (blocking) or (if-minor).
+- subject: one line with the main point.
+- discussion: optional, a few sentences of reasoning or a concrete suggestion.
+Write markdown that can be pasted into the thread as is, with no preamble."#;
+
+/// User prompt template; `{{diff}}` is replaced with the selected hunks.
+const COMMENT_USER_PROMPT: &str = "[REGION]\n{{diff}}\n\n[COMMENT]";
+
+/// Writes the comment for the staged changes to `file`, limited to `lines`
+/// (e.g. `10-42`) when given, and prints it.
+pub async fn run(cli: &Cli, file: &str, lines: Option<&str>) -> Result<()> {
+    let mut config = cli.load_config()?;
+    let sink = cli.clipboard(&config);
+    let range = lines.map(parse_lines).transpose()?;
+
+    // The file itself is the pathspec, so it is read whatever `git_extensions` says
+    let diff = get_git_diff_in_path(&[file.to_string()], ".")?;
+    if diff.trim().is_empty() {
+        return Err(Failure::NoChanges.error(format!("No staged changes in {}.", file)));
+    }
+    let region = match range {
+        Some((first, last)) => select_hunks(&diff, first, last),
+        None => diff,
+    };
+    if !region.contains("\n@@") {
+        return Err(Failure::NoChanges.error(format!(
+            "No staged changes in {} lines {}.",
+            file,
+            lines.unwrap_or_default()
+        )));
+    }
+
+    config.system_prompt = COMMENT_SYSTEM_PROMPT.to_string();
+    config.user_prompt = COMMENT_USER_PROMPT.to_string();
+    let input = match lines {
+        Some(lines) => format!("File: {} (lines {})\n\n{}", file, lines, region),
+        None => format!("File: {}\n\n{}", file, region),
+    };
+    let comment = summarize_diff(config, input).await?;
+    println!("{}", comment);
+    clipboard::deliver(&comment, sink.as_deref());
+    Ok(())
+}
+
+/// Parses a line range such as `10-42`, or a single line such as `10`.
+pub fn parse_lines(spec: &str) -> Result<(u32, u32)> {
+    let invalid = || anyhow!("Invalid line range '{}': expected e.g. 10-42.", spec);
+    let (first, last) = spec.split_once('-').unwrap_or((spec, spec));
+    let first: u32 = first.trim().parse().map_err(|_| invalid())?;
+    let last: u32 = last.trim().parse().map_err(|_| invalid())?;
+    if first == 0 || last < first {
+        return Err(invalid());
+    }
+    Ok((first, last))
+}
+
+/// Keeps the file headers of `diff` and the hunks whose new lines overlap
+/// `first..=last`.
+pub fn select_hunks(diff: &str, first: u32, last: u32) -> String {
+    let mut selected = String::new();
+    let mut keep = true;
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            keep = true;
+        } else if let Some(header) = line.strip_prefix("@@ ") {
+            keep = new_range(header).is_some_and(|(start, end)| start <= last && first <= end);
+        }
+        if keep {
+            selected.push_str(line);
+            selected.push('\n');
+        }
+    }
+    selected
+}
+
+/// Reads the new-side line range (first and last line) from a hunk header.
+/// Pure deletions count as touching the line they follow.
+fn new_range(header: &str) -> Option<(u32, u32)> {
+    let range = header
+        .split_whitespace()
+        .find_map(|part| part.strip_prefix('+'))?;
+    let (start, count) = match range.split_once(',') {
+        Some((start, count)) => (start.parse::<u32>().ok()?, count.parse::<u32>().ok()?),
+        None => (range.parse::<u32>().ok()?, 1),
+    };
+    Some((
+        start.max(1),
+        (start + count.saturating_sub(1)).max(start).max(1),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/src/x.rs b/src/x.rs
+index 1..2 100644
+--- a/src/x.rs
++++ b/src/x.rs
+@@ -1,4 +1,5 @@
+ fn a() {
++    one();
+ }
+@@ -20,3 +21,4 @@ fn b() {
+ let x = 1;
++let y = 2;
+ }
+";
+
+    #[test]
+    fn test_parse_lines_table_driven() {
+        let cases = vec![
+            ("10-42", Some((10, 42))),
+            ("7", Some((7, 7))),
+            (" 3 - 4 ", Some((3, 4))),
+            ("42-10", None),
+            ("0-5", None),
+            ("a-b", None),
+            ("", None),
+        ];
+        for (spec, expected) in cases {
+            assert_eq!(parse_lines(spec).ok(), expected, "spec: {:?}", spec);
+        }
+    }
+
+    #[test]
+    fn test_select_hunks_table_driven() {
+        let headers =
+            "diff --git a/src/x.rs b/src/x.rs\nindex 1..2 100644\n--- a/src/x.rs\n+++ b/src/x.rs\n";
+        let first = "@@ -1,4 +1,5 @@\n fn a() {\n+    one();\n }\n";
+        let second = "@@ -20,3 +21,4 @@ fn b() {\n let x = 1;\n+let y = 2;\n }\n";
+        let cases = vec![
+            ((2, 3), format!("{}{}", headers, first)),
+            ((22, 22), format!("{}{}", headers, second)),
+            ((5, 21), format!("{}{}{}", headers, first, second)),
+            ((10, 15), headers.to_string()),
+        ];
+        for ((from, to), expected) in cases {
+            assert_eq!(
+                select_hunks(DIFF, from, to),
+                expected,
+                "lines: {}-{}",
+                from,
+                to
+            );
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! `asum commit` command.
+//!
+//! Generates a message for the staged changes, shows it, and commits with it
+//! once the user confirms.
+
+use crate::cli::Cli;
+use crate::clipboard;
+use crate::failure::Failure;
+use crate::git;
+use crate::guard;
+use crate::interactive::confirm;
+use crate::pipeline::generate_commit_message;
+use anyhow::Result;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+/// What to do with a generated message.
+#[derive(Debug, PartialEq)]
+pub enum Decision {
+    /// Commit with the message as is.
+    Commit,
+    /// Commit, but let the user edit the message in their editor first.
+    Edit,
+    /// Do not commit.
+    Abort,
+}
+
+/// Generates the message and commits the staged changes with it.
+///
+/// # Arguments
+/// * `yes` - Commit without asking for confirmation.
+/// * `edit` - Open the editor with the generated message before committing.
+pub async fn run(cli: &Cli, yes: bool, edit: bool) -> Result<()> {
+    let config = cli.load_config()?;
+    let sink = cli.clipboard(&config);
+    let message = generate_commit_message(config, Path::new(".")).await?;
+
+    println!("{}\n", cli.format_message(&message));
+    check_message(&message, yes && !edit)?;
+
+    let stdin = std::io::stdin();
+    let decision = decide(&mut stdin.lock(), &mut std::io::stdout(), yes, edit)?;
+    match decision {
+        Decision::Commit => {
+            git::commit(&message, false).inspect_err(|_| clipboard::save_restore_point(&message))
+        }
+        Decision::Edit => {
+            git::commit(&message, true).inspect_err(|_| clipboard::save_restore_point(&message))
+        }
+        Decision::Abort => {
+            println!("Aborted. Nothing was committed.");
+            clipboard::deliver(&message, sink.as_deref());
+            Ok(())
+        }
+    }
+}
+
+/// Runs the guardrails on a generated message. Messages that would be committed
+/// without review must pass them; otherwise problems are only reported.
+pub fn check_message(message: &str, unattended: bool) -> Result<()> {
+    if unattended {
+        guard::ensure_committable(message, Path::new("."))
+            .map_err(|e| Failure::InvalidOutput.tag(e))
+    } else {
+        guard::warn_problems(message);
+        Ok(())
+    }
+}
+
+/// Decides whether to commit (used by `commit` and `amend`). The editor is itself
+/// a confirmation step, so `--edit` and `--yes` skip the prompt.
+pub fn decide<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    yes: bool,
+    edit: bool,
+) -> Result<Decision> {
+    if edit {
+        return Ok(Decision::Edit);
+    }
+    if yes || confirm(input, output, "Use this message?", true)? {
+        Ok(Decision::Commit)
+    } else {
+        Ok(Decision::Abort)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_decide_table_driven() {
+        struct TestCase {
+            input: &'static str,
+            yes: bool,
+            edit: bool,
+            expected: Decision,
+        }
+
+        let cases = vec![
+            TestCase {
+                input: "",
+                yes: true,
+                edit: false,
+                expected: Decision::Commit,
+            },
+            TestCase {
+                input: "",
+                yes: false,
+                edit: true,
+                expected: Decision::Edit,
+            },
+            TestCase {
+                input: "\n",
+                yes: false,
+                edit: false,
+                expected: Decision::Commit,
+            },
+            TestCase {
+                input: "n\n",
+                yes: false,
+                edit: false,
+                expected: Decision::Abort,
+            },
+            TestCase {
+                input: "",
+                yes: false,
+                edit: false,
+                expected: Decision::Abort,
+            },
+        ];
+
+        for case in cases {
+            let mut output = Vec::new();
+            let decision = decide(
+                &mut Cursor::new(case.input),
+                &mut output,
+                case.yes,
+                case.edit,
+            )
+            .unwrap();
+            assert_eq!(decision, case.expected, "input: {:?}", case.input);
+        }
+    }
+}
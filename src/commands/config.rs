@@ -0,0 +1,218 @@
+//! `asum config` command.
+//!
+//! Helps users discover and inspect the options of `asum.toml`, and pick a
+//! model from the list the active provider reports.
+
+use crate::cli::{Cli, ConfigAction};
+use crate::config::{render_schema, set_model};
+use crate::interactive::ask;
+use crate::summarizer::{ModelInfo, get_summarizer};
+use anyhow::{Result, anyhow};
+use std::io::{BufRead, Write};
+use tracing::warn;
+
+/// Executes a `asum config` action.
+pub async fn run(cli: &Cli, action: &ConfigAction) -> Result<()> {
+    match action {
+        ConfigAction::Schema => print!("{}", render_schema()?),
+        ConfigAction::Models => {
+            let config = cli.load_config()?;
+            let current = config.active_model().map(String::from);
+            let models = get_summarizer(config).await?.list_models().await?;
+            if models.is_empty() {
+                println!("No models available.");
+            }
+            for line in format_models(&models, current.as_deref()) {
+                println!("{}", line);
+            }
+        }
+        ConfigAction::SetModel { model } => {
+            let path = cli.config_file()?;
+            let config = cli.load_config()?;
+            let provider = config.active_provider.clone();
+            let current = config.active_model().map(String::from);
+            let models = get_summarizer(config).await?.list_models().await;
+
+            let selected = match (model, models) {
+                (Some(model), Ok(models)) => {
+                    if !models.iter().any(|m| &m.name == model) {
+                        return Err(anyhow!(
+                            "Model '{}' is not offered by {}. Run 'asum models' to see the available models.",
+                            model,
+                            provider
+                        ));
+                    }
+                    model.clone()
+                }
+                (Some(model), Err(e)) => {
+                    warn!(
+                        "Could not verify the model name ({:#}); saving it as given.",
+                        e
+                    );
+                    model.clone()
+                }
+                (None, Ok(models)) => {
+                    let stdin = std::io::stdin();
+                    pick_model(
+                        &mut stdin.lock(),
+                        &mut std::io::stdout(),
+                        &models,
+                        current.as_deref(),
+                    )?
+                }
+                (None, Err(e)) => return Err(e.context("Could not list models")),
+            };
+
+            set_model(&path, &provider, &selected)?;
+            println!(
+                "[OK] {} model set to {} in {}",
+                provider,
+                selected,
+                path.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Formats one numbered line per model, marking the current one with `*`.
+pub fn format_models(models: &[ModelInfo], current: Option<&str>) -> Vec<String> {
+    let width = models.iter().map(|m| m.name.len()).max().unwrap_or(0);
+    models
+        .iter()
+        .enumerate()
+        .map(|(i, model)| {
+            let marker = if Some(model.name.as_str()) == current {
+                "*"
+            } else {
+                " "
+            };
+            let mut line = format!("{} {:>3}) {:<width$}", marker, i + 1, model.name);
+            if let Some(size) = model.size {
+                line.push_str(&format!("  {:>8}", format_size(size)));
+            }
+            if let Some(details) = &model.details {
+                line.push_str(&format!("  {}", details));
+            }
+            line.trim_end().to_string()
+        })
+        .collect()
+}
+
+/// Formats a byte count with a binary unit, e.g. `4.3 GB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Shows the models and asks for a number or an exact name until a valid choice is made.
+/// Pressing Enter keeps the current model when it is in the list.
+pub fn pick_model<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    models: &[ModelInfo],
+    current: Option<&str>,
+) -> Result<String> {
+    if models.is_empty() {
+        return Err(anyhow!("The provider reported no available models."));
+    }
+    for line in format_models(models, current) {
+        writeln!(output, "{}", line)?;
+    }
+
+    let default = current.filter(|c| models.iter().any(|m| m.name == *c));
+    loop {
+        let answer = ask(input, output, "Select a model (number or name)", default)?;
+        if let Some(model) = answer
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|i| models.get(i))
+            .or_else(|| models.iter().find(|m| m.name == answer))
+        {
+            return Ok(model.name.clone());
+        }
+        writeln!(output, "'{}' is not in the list.", answer)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn models() -> Vec<ModelInfo> {
+        vec![
+            ModelInfo {
+                name: "llama3:latest".to_string(),
+                size: Some(4661224676),
+                details: Some("8.0B".to_string()),
+            },
+            ModelInfo {
+                name: "qwen2.5-coder:3b".to_string(),
+                size: Some(1929912432),
+                details: Some("3.1B".to_string()),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_format_size_table_driven() {
+        let cases = vec![
+            (0, "0 B"),
+            (1023, "1023 B"),
+            (1536, "1.5 KB"),
+            (4661224676, "4.3 GB"),
+        ];
+        for (bytes, expected) in cases {
+            assert_eq!(format_size(bytes), expected, "bytes: {}", bytes);
+        }
+    }
+
+    #[test]
+    fn test_format_models_marks_current() {
+        let lines = format_models(&models(), Some("qwen2.5-coder:3b"));
+        assert_eq!(lines[0], "    1) llama3:latest       4.3 GB  8.0B");
+        assert_eq!(lines[1], "*   2) qwen2.5-coder:3b    1.8 GB  3.1B");
+    }
+
+    #[test]
+    fn test_pick_model_table_driven() {
+        let cases = vec![
+            ("1\n", Some("qwen2.5-coder:3b"), "llama3:latest"),
+            ("\n", Some("qwen2.5-coder:3b"), "qwen2.5-coder:3b"),
+            ("llama3\n0\nqwen2.5-coder:3b\n", None, "qwen2.5-coder:3b"),
+            ("3\n2\n", Some("unknown"), "qwen2.5-coder:3b"),
+        ];
+
+        for (answers, current, expected) in cases {
+            let mut input = Cursor::new(answers);
+            let mut output = Vec::new();
+            let picked = pick_model(&mut input, &mut output, &models(), current).unwrap();
+            assert_eq!(picked, expected, "answers: {:?}", answers);
+        }
+    }
+
+    #[test]
+    fn test_pick_model_rejects_typos_until_end_of_input() {
+        let mut input = Cursor::new("lama3\n");
+        let mut output = Vec::new();
+        assert!(pick_model(&mut input, &mut output, &models(), None).is_err());
+        assert!(
+            String::from_utf8(output)
+                .unwrap()
+                .contains("'lama3' is not in the list.")
+        );
+        assert!(pick_model(&mut Cursor::new(""), &mut Vec::new(), &[], None).is_err());
+    }
+}
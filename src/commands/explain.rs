@@ -0,0 +1,39 @@
+//! `asum explain` command.
+//!
+//! Explains in plain English what an existing commit does, using its message
+//! and `git show` diff.
+
+use crate::cli::Cli;
+use crate::git::{get_commit_diff_in_path, get_commit_message_in_path};
+use crate::pipeline::{LONG_REPLY_NUM_PREDICT, summarize_diff};
+use anyhow::Result;
+use std::path::Path;
+
+/// System prompt of the explanation: what a commit changes, in plain English.
+const EXPLAIN_SYSTEM_PROMPT: &str = r#"You explain git commits to developers who are new to the codebase.
+Given a commit message and its diff, explain in plain English:
+- what the commit changes and why it was likely made
+- how the behavior differs before and after the commit
+- anything a reader should watch out for, such as breaking changes
+Keep it short: one paragraph followed by a few "- " bullet points. Do not repeat the diff."#;
+
+/// User prompt template; `{{diff}}` is replaced with the commit message and diff.
+const EXPLAIN_USER_PROMPT: &str = "[COMMIT]\n{{diff}}\n\n[EXPLANATION]";
+
+/// Explains `commit` and prints the result.
+pub async fn run(cli: &Cli, commit: &str) -> Result<()> {
+    let mut config = cli.load_config()?;
+    let repo = Path::new(".");
+
+    let message = get_commit_message_in_path(commit, repo)?;
+    let diff = get_commit_diff_in_path(commit, &config.pathspecs(), repo)?;
+
+    config.system_prompt = EXPLAIN_SYSTEM_PROMPT.to_string();
+    config.user_prompt = EXPLAIN_USER_PROMPT.to_string();
+    config.ai_num_predict = config.ai_num_predict.max(LONG_REPLY_NUM_PREDICT);
+
+    let input = format!("Message:\n{}\n\nDiff:\n{}", message, diff);
+    let explanation = summarize_diff(config, input).await?;
+    println!("{}", explanation);
+    Ok(())
+}
@@ -0,0 +1,116 @@
+//! `asum feedback` command.
+//!
+//! Compares the message of the HEAD commit with the last message generated in
+//! this repository and records whether it was accepted, edited or rejected.
+
+use crate::db::{Db, Verdict};
+use crate::git::{get_commit_message_in_path, get_commit_time_in_path, get_repo_root_in_path};
+use anyhow::{Result, anyhow};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Minimum word overlap (Jaccard index) for a committed message to count as an edit
+/// of the generated one rather than a replacement.
+const EDIT_SIMILARITY: f64 = 0.5;
+
+/// Records feedback for the most recent unrated generation in the current repository.
+pub fn run() -> Result<()> {
+    let repo = Path::new(".");
+    let root = get_repo_root_in_path(repo)?;
+    let db = Db::open_default()?;
+
+    let generation = db
+        .latest_pending_generation(&root.to_string_lossy())?
+        .ok_or_else(|| {
+            anyhow!("No generated message is waiting for feedback in this repository.")
+        })?;
+
+    let committed_at = get_commit_time_in_path("HEAD", repo)?;
+    if committed_at < generation.created_at {
+        return Err(anyhow!(
+            "HEAD was committed before the last message was generated. Commit first, then run 'asum feedback'."
+        ));
+    }
+
+    let committed = get_commit_message_in_path("HEAD", repo)?;
+    let verdict = classify(&generation.message, &committed);
+    db.record_feedback(generation.id, verdict, &committed)?;
+
+    println!(
+        "[OK] Recorded '{}' for the message generated by {}/{}.",
+        verdict.as_str(),
+        generation.provider,
+        generation.model
+    );
+    Ok(())
+}
+
+/// Decides how the generated message was used, given the committed message.
+/// Whitespace differences are ignored; sufficiently similar messages count as edits.
+pub fn classify(generated: &str, committed: &str) -> Verdict {
+    let normalize = |text: &str| {
+        text.lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    if normalize(generated) == normalize(committed) {
+        return Verdict::Accepted;
+    }
+
+    let words = |text: &str| -> HashSet<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let (generated, committed) = (words(generated), words(committed));
+    let union = generated.union(&committed).count();
+    if union == 0 {
+        return Verdict::Rejected;
+    }
+
+    let similarity = generated.intersection(&committed).count() as f64 / union as f64;
+    if similarity >= EDIT_SIMILARITY {
+        Verdict::Edited
+    } else {
+        Verdict::Rejected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_table_driven() {
+        let cases = vec![
+            ("feat: add login", "feat: add login", Verdict::Accepted),
+            (
+                "feat: add login\n\n- add form",
+                "  feat:  add login\n- add form\n",
+                Verdict::Accepted,
+            ),
+            (
+                "feat(auth): add login form\n\n- validate input",
+                "feat(auth): add login page\n\n- validate input",
+                Verdict::Edited,
+            ),
+            ("feat: add login", "fix: typo in readme", Verdict::Rejected),
+            ("feat: add login", "WIP", Verdict::Rejected),
+            ("", "", Verdict::Accepted),
+            ("---", "...", Verdict::Rejected),
+        ];
+
+        for (generated, committed, expected) in cases {
+            assert_eq!(
+                classify(generated, committed),
+                expected,
+                "generated: {:?}, committed: {:?}",
+                generated,
+                committed
+            );
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! `asum fixup` command.
+//!
+//! Finds the recent commit the staged changes most plausibly belong to, by the
+//! files and lines both touch, and prints or creates a `fixup! <subject>` commit
+//! for a later `git rebase --autosquash`. No provider is involved.
+
+use crate::cli::Cli;
+use crate::clipboard;
+use crate::config::AsumConfig;
+use crate::failure::Failure;
+use crate::git::{
+    commit_fixup_in_path, get_commit_hunks_in_path, get_recent_commits_in_path,
+    get_staged_hunks_in_path,
+};
+use anyhow::{Result, anyhow};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::info;
+
+/// Score of a file touched by both the staged changes and a commit.
+const FILE_SCORE: usize = 1;
+
+/// Score of a staged hunk that overlaps (or borders) a hunk of the commit.
+const HUNK_SCORE: usize = 3;
+
+/// Lines between two hunks that still count as touching the same code.
+const HUNK_SLACK: u32 = 3;
+
+/// Changed line ranges (first and last line, inclusive) per file.
+pub type Hunks = BTreeMap<String, Vec<(u32, u32)>>;
+
+/// Which side of a hunk header to read.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Side {
+    /// Lines before the change (`-a,b`): the staged diff against `HEAD`.
+    Old,
+    /// Lines after the change (`+c,d`): what a commit left behind.
+    New,
+}
+
+/// Finds the target commit among the last `depth` commits and prints the fixup
+/// subject, or commits the staged changes as a fixup with `create`.
+pub fn run(cli: &Cli, create: bool, depth: usize) -> Result<()> {
+    let repo = Path::new(".");
+    let staged = parse_hunks(&get_staged_hunks_in_path(repo)?, Side::Old);
+    if staged.is_empty() {
+        return Err(Failure::NoChanges.error("No staged changes found."));
+    }
+
+    let mut best: Option<(usize, String, String)> = None;
+    for (hash, subject) in get_recent_commits_in_path(depth, repo)? {
+        // Fixups are squashed into their target, so aim at the original commit
+        if subject.starts_with("fixup! ") || subject.starts_with("squash! ") {
+            continue;
+        }
+        let commit = parse_hunks(&get_commit_hunks_in_path(&hash, repo)?, Side::New);
+        let score = score(&staged, &commit);
+        // Commits come newest first, so ties go to the most recent one
+        if score > best.as_ref().map_or(0, |(best, _, _)| *best) {
+            best = Some((score, hash, subject));
+        }
+    }
+    let (_, hash, subject) =
+        best.ok_or_else(|| anyhow!("None of the last {} commits touch the staged files.", depth))?;
+    let short = &hash[..hash.len().min(7)];
+
+    if create {
+        commit_fixup_in_path(&hash, repo)?;
+        println!("[OK] Created fixup! {} (for {})", subject, short);
+    } else {
+        let message = format!("fixup! {}", subject);
+        println!("{}", message);
+        info!(
+            "Target: {}. Run `asum fixup --create` or `git commit --fixup={}` to commit.",
+            short, short
+        );
+        // Only the clipboard setting is used, so this works without asum.toml
+        let config = cli.load_config().or_else(|_| AsumConfig::from_defaults())?;
+        clipboard::deliver(&message, cli.clipboard(&config).as_deref());
+    }
+    Ok(())
+}
+
+/// Reads the changed line ranges of each file from a diff without context lines.
+/// Pure insertions (`-5,0`) count as touching the line they follow.
+pub fn parse_hunks(diff: &str, side: Side) -> Hunks {
+    let mut hunks = Hunks::new();
+    let mut current: Option<String> = None;
+    for line in diff.lines() {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            current = header.rsplit_once(" b/").map(|(_, path)| path.to_string());
+            continue;
+        }
+        let (Some(path), Some(header)) = (&current, line.strip_prefix("@@ ")) else {
+            continue;
+        };
+        let prefix = match side {
+            Side::Old => '-',
+            Side::New => '+',
+        };
+        let Some(range) = header
+            .split_whitespace()
+            .find_map(|part| part.strip_prefix(prefix))
+        else {
+            continue;
+        };
+        let mut numbers = range.splitn(2, ',').map(|n| n.parse::<u32>().ok());
+        let (Some(Some(start)), count) = (numbers.next(), numbers.next()) else {
+            continue;
+        };
+        let count = count.flatten().unwrap_or(1);
+        let last = start + count.saturating_sub(1);
+        hunks
+            .entry(path.clone())
+            .or_default()
+            .push((start.max(1), last.max(start).max(1)));
+    }
+    hunks
+}
+
+/// How strongly the staged hunks point at a commit: every shared file scores
+/// `FILE_SCORE`, and every staged hunk near one of the commit's hunks `HUNK_SCORE`.
+pub fn score(staged: &Hunks, commit: &Hunks) -> usize {
+    staged
+        .iter()
+        .filter_map(|(path, ranges)| Some((ranges, commit.get(path)?)))
+        .map(|(ranges, theirs)| {
+            let near = ranges
+                .iter()
+                .filter(|(first, last)| {
+                    theirs.iter().any(|(their_first, their_last)| {
+                        *first <= their_last + HUNK_SLACK && *their_first <= last + HUNK_SLACK
+                    })
+                })
+                .count();
+            FILE_SCORE + near * HUNK_SCORE
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/src/a.rs b/src/a.rs
+index 1..2 100644
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -3 +3,2 @@ fn a() {
+-old
++new
++newer
+@@ -10,0 +12 @@
++inserted
+diff --git a/src/new.rs b/src/new.rs
+new file mode 100644
+--- /dev/null
++++ b/src/new.rs
+@@ -0,0 +1,4 @@
++one
+";
+
+    #[test]
+    fn test_parse_hunks_table_driven() {
+        let cases = vec![
+            (
+                Side::Old,
+                vec![
+                    ("src/a.rs", vec![(3, 3), (10, 10)]),
+                    ("src/new.rs", vec![(1, 1)]),
+                ],
+            ),
+            (
+                Side::New,
+                vec![
+                    ("src/a.rs", vec![(3, 4), (12, 12)]),
+                    ("src/new.rs", vec![(1, 4)]),
+                ],
+            ),
+        ];
+        for (side, expected) in cases {
+            let expected: Hunks = expected
+                .into_iter()
+                .map(|(path, ranges)| (path.to_string(), ranges))
+                .collect();
+            assert_eq!(parse_hunks(DIFF, side), expected, "side: {:?}", side);
+        }
+    }
+
+    #[test]
+    fn test_score_table_driven() {
+        let hunks = |entries: &[(&str, (u32, u32))]| -> Hunks {
+            let mut hunks = Hunks::new();
+            for (path, range) in entries {
+                hunks.entry(path.to_string()).or_default().push(*range);
+            }
+            hunks
+        };
+        let staged = hunks(&[("a.rs", (10, 12)), ("a.rs", (40, 40)), ("b.rs", (1, 1))]);
+        let cases = vec![
+            (hunks(&[("c.rs", (10, 12))]), 0),
+            (hunks(&[("a.rs", (100, 120))]), FILE_SCORE),
+            (hunks(&[("a.rs", (14, 20))]), FILE_SCORE + HUNK_SCORE),
+            (
+                hunks(&[("a.rs", (1, 50)), ("b.rs", (30, 31))]),
+                2 * FILE_SCORE + 2 * HUNK_SCORE,
+            ),
+        ];
+        for (commit, expected) in cases {
+            assert_eq!(score(&staged, &commit), expected, "commit: {:?}", commit);
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! `asum history` command.
+//!
+//! Lists the messages recorded in the database, newest first, optionally only
+//! those of one repository or those matching a full-text search.
+
+use crate::db::{Db, Generation};
+use crate::pipeline::repo_key;
+use anyhow::Result;
+use std::path::Path;
+
+/// Prints up to `limit` past messages.
+///
+/// # Arguments
+/// * `search` - Only show messages containing all of these words.
+/// * `repo` - Only show messages generated in this repository.
+pub fn run(search: Option<&str>, limit: usize, repo: Option<&Path>) -> Result<()> {
+    let repo = repo.map(repo_key).transpose()?;
+
+    let generations = Db::open_default()?.history(repo.as_deref(), search, limit)?;
+    if generations.is_empty() {
+        println!("No generated messages found.");
+        return Ok(());
+    }
+    println!("{}", render(&generations));
+    Ok(())
+}
+
+/// Renders one block per generation: a header line with the time (UTC),
+/// repository, branch and model, followed by the indented message.
+pub fn render(generations: &[Generation]) -> String {
+    generations
+        .iter()
+        .map(|g| {
+            let branch = if g.branch.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", g.branch)
+            };
+            let message: Vec<String> = g
+                .message
+                .lines()
+                .map(|line| format!("    {}", line).trim_end().to_string())
+                .collect();
+            format!(
+                "{}  {}{}  {}/{}\n{}",
+                format_time(g.created_at),
+                g.repo,
+                branch,
+                g.provider,
+                g.model,
+                message.join("\n")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Formats seconds since the Unix epoch as `YYYY-MM-DD HH:MM` in UTC.
+pub fn format_time(timestamp: i64) -> String {
+    let days = timestamp.div_euclid(86_400);
+    let seconds = timestamp.rem_euclid(86_400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_time_table_driven() {
+        let cases = vec![
+            (0, "1970-01-01 00:00"),
+            (951_782_400, "2000-02-29 00:00"),
+            (1_718_454_645, "2024-06-15 12:30"),
+            (-60, "1969-12-31 23:59"),
+        ];
+        for (timestamp, expected) in cases {
+            assert_eq!(format_time(timestamp), expected, "timestamp: {}", timestamp);
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let generation = |branch: &str, message: &str| Generation {
+            id: 1,
+            created_at: 1_718_454_645,
+            repo: "/repo".to_string(),
+            branch: branch.to_string(),
+            diff_hash: String::new(),
+            provider: "ollama".to_string(),
+            model: "llama3".to_string(),
+            message: message.to_string(),
+        };
+        let output = render(&[
+            generation("main", "feat: add login\n\n- Add the endpoint"),
+            generation("", "fix: typo"),
+        ]);
+        assert_eq!(
+            output,
+            "2024-06-15 12:30  /repo (main)  ollama/llama3\n    feat: add login\n\n    - Add the endpoint\n\n\
+             2024-06-15 12:30  /repo  ollama/llama3\n    fix: typo"
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! `asum hook` command.
+//!
+//! Installs, removes and inspects a `prepare-commit-msg` git hook that runs
+//! asum and pre-fills the commit editor with the generated message.
+
+use crate::cli::{Cli, HookAction};
+use crate::git::get_hooks_dir_in_path;
+use crate::guard::ensure_committable;
+use crate::pipeline::generate_commit_message;
+use anyhow::{Context, Result, anyhow};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// File name of the hook managed by asum.
+const HOOK_NAME: &str = "prepare-commit-msg";
+
+/// Marker line identifying hooks written by asum, so foreign hooks are never touched.
+const HOOK_MARKER: &str = "# Installed by asum";
+
+/// Shell script installed as the `prepare-commit-msg` hook.
+const HOOK_SCRIPT: &str = r#"#!/bin/sh
+# Installed by asum. Remove with `asum hook uninstall`.
+# Pre-fills the commit message with an AI generated summary of the staged changes.
+command -v asum >/dev/null 2>&1 || exit 0
+asum --quiet hook run "$@" </dev/null || true
+"#;
+
+/// Installation state of the hook in a repository.
+#[derive(Debug, PartialEq)]
+pub enum HookStatus {
+    /// No `prepare-commit-msg` hook exists.
+    NotInstalled,
+    /// The asum hook is installed.
+    Installed,
+    /// A different `prepare-commit-msg` hook exists.
+    Foreign,
+}
+
+/// Returns the path of the `prepare-commit-msg` hook for the repository at `repo`.
+pub fn hook_path(repo: &Path) -> Result<PathBuf> {
+    Ok(get_hooks_dir_in_path(repo)?.join(HOOK_NAME))
+}
+
+/// Reports whether the asum hook, another hook, or no hook is installed.
+pub fn status(repo: &Path) -> Result<HookStatus> {
+    let path = hook_path(repo)?;
+    if !path.exists() {
+        return Ok(HookStatus::NotInstalled);
+    }
+    let content = fs::read_to_string(&path).unwrap_or_default();
+    if content.contains(HOOK_MARKER) {
+        Ok(HookStatus::Installed)
+    } else {
+        Ok(HookStatus::Foreign)
+    }
+}
+
+/// Writes the hook script. An existing foreign hook is only replaced with `force`.
+pub fn install(repo: &Path, force: bool) -> Result<PathBuf> {
+    let path = hook_path(repo)?;
+    if status(repo)? == HookStatus::Foreign && !force {
+        return Err(anyhow!(
+            "A different {} hook already exists at {}. Use --force to replace it.",
+            HOOK_NAME,
+            path.display()
+        ));
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create hooks directory {}", parent.display()))?;
+    }
+    fs::write(&path, HOOK_SCRIPT)
+        .with_context(|| format!("Failed to write hook {}", path.display()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    }
+
+    Ok(path)
+}
+
+/// Removes the hook if it was installed by asum.
+pub fn uninstall(repo: &Path) -> Result<PathBuf> {
+    let path = hook_path(repo)?;
+    match status(repo)? {
+        HookStatus::Installed => {
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove hook {}", path.display()))?;
+            Ok(path)
+        }
+        HookStatus::Foreign => Err(anyhow!(
+            "The {} hook at {} was not installed by asum; leaving it untouched.",
+            HOOK_NAME,
+            path.display()
+        )),
+        HookStatus::NotInstalled => Err(anyhow!("No asum hook is installed.")),
+    }
+}
+
+/// Prepends the generated message to the commit message file, keeping git's
+/// commented template below it.
+pub fn prefill_message_file(file: &Path, message: &str) -> Result<()> {
+    let existing = fs::read_to_string(file).unwrap_or_default();
+    fs::write(file, format!("{}\n{}", message.trim_end(), existing))
+        .with_context(|| format!("Failed to write commit message file {}", file.display()))
+}
+
+/// Executes a `asum hook` action in the current repository.
+pub async fn run(cli: &Cli, action: &HookAction) -> Result<()> {
+    let repo = Path::new(".");
+    match action {
+        HookAction::Install { force } => {
+            let path = install(repo, *force)?;
+            println!("[OK] Installed {} hook at {}", HOOK_NAME, path.display());
+        }
+        HookAction::Uninstall => {
+            let path = uninstall(repo)?;
+            println!("[OK] Removed {} hook from {}", HOOK_NAME, path.display());
+        }
+        HookAction::Status => {
+            let path = hook_path(repo)?;
+            match status(repo)? {
+                HookStatus::Installed => println!("installed: {}", path.display()),
+                HookStatus::Foreign => println!(
+                    "not installed: a different {} hook exists at {}",
+                    HOOK_NAME,
+                    path.display()
+                ),
+                HookStatus::NotInstalled => println!("not installed"),
+            }
+        }
+        HookAction::Run { file, source, .. } => {
+            run_hook(cli, file, source.as_deref()).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Entry point of `asum hook run`, invoked by git with the hook arguments.
+/// Only plain `git commit` invocations are filled in; messages from `-m`, templates,
+/// merges, squashes and amends are left alone. Failures never block the commit, and
+/// messages rejected by the guardrails are saved as a draft instead of pre-filled, so
+/// `git commit --no-edit` cannot commit them.
+pub async fn run_hook(cli: &Cli, file: &Path, source: Option<&str>) -> Result<()> {
+    if source.is_some_and(|s| !s.is_empty()) {
+        return Ok(());
+    }
+
+    let result = async {
+        let config = cli.load_config()?;
+        generate_commit_message(config, Path::new(".")).await
+    }
+    .await;
+
+    match result {
+        Ok(message) => match ensure_committable(&message, Path::new(".")) {
+            Ok(()) => prefill_message_file(file, &message),
+            Err(e) => {
+                warn!("{:#}", e);
+                Ok(())
+            }
+        },
+        Err(e) => {
+            warn!("asum could not generate a commit message: {:#}", e);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::tempdir;
+
+    fn init_repo() -> tempfile::TempDir {
+        let dir = tempdir().unwrap();
+        Command::new("git")
+            .arg("init")
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_install_status_uninstall() {
+        let dir = init_repo();
+        let repo = dir.path();
+
+        assert_eq!(status(repo).unwrap(), HookStatus::NotInstalled);
+
+        let path = install(repo, false).unwrap();
+        assert_eq!(path, repo.join(".git/hooks/prepare-commit-msg"));
+        assert_eq!(status(repo).unwrap(), HookStatus::Installed);
+        assert!(
+            fs::read_to_string(&path)
+                .unwrap()
+                .contains("hook run \"$@\"")
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o111, 0o111);
+        }
+
+        // Reinstalling over our own hook is allowed
+        assert!(install(repo, false).is_ok());
+
+        uninstall(repo).unwrap();
+        assert_eq!(status(repo).unwrap(), HookStatus::NotInstalled);
+        assert!(uninstall(repo).is_err());
+    }
+
+    #[test]
+    fn test_foreign_hook_is_protected() {
+        let dir = init_repo();
+        let repo = dir.path();
+        let path = hook_path(repo).unwrap();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "#!/bin/sh\necho custom\n").unwrap();
+
+        assert_eq!(status(repo).unwrap(), HookStatus::Foreign);
+        assert!(install(repo, false).is_err());
+        assert!(uninstall(repo).is_err());
+        assert!(fs::read_to_string(&path).unwrap().contains("custom"));
+
+        install(repo, true).unwrap();
+        assert_eq!(status(repo).unwrap(), HookStatus::Installed);
+    }
+
+    #[test]
+    fn test_prefill_message_file() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("COMMIT_EDITMSG");
+        fs::write(&file, "\n# Please enter the commit message\n").unwrap();
+
+        prefill_message_file(&file, "feat: add hook\n").unwrap();
+        let content = fs::read_to_string(&file).unwrap();
+        assert!(content.starts_with("feat: add hook\n\n# Please enter"));
+    }
+
+    #[tokio::test]
+    async fn test_run_hook_skips_explicit_messages() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("COMMIT_EDITMSG");
+        fs::write(&file, "fix: typed by hand\n").unwrap();
+
+        let cli = <Cli as clap::Parser>::try_parse_from(["asum"]).unwrap();
+        run_hook(&cli, &file, Some("message")).await.unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "fix: typed by hand\n");
+    }
+}
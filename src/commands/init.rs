@@ -0,0 +1,280 @@
+//! `asum init` command.
+//!
+//! Interactive setup wizard that asks for the provider, model and credentials,
+//! then writes a ready-to-use `asum.toml` locally or to `~/.asum`.
+
+use crate::config::{InitSettings, asum_home, render_config};
+use crate::interactive::{ask, ask_secret};
+use crate::summarizer::available_providers;
+use anyhow::{Context, Result, anyhow};
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// Environment variables the Gemini API key is read from at run time, see
+/// `AsumConfig::apply_env`.
+const KEY_VARS: &[&str] = &["ASUM_GEMINI_API_KEY", "GEMINI_API_KEY"];
+
+/// Where the generated configuration file should be written.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigLocation {
+    /// `asum.toml` in the current directory.
+    Local,
+    /// `~/.asum/asum.toml`.
+    Global,
+}
+
+impl ConfigLocation {
+    /// Resolves the file path for this location.
+    pub fn path(&self) -> Result<PathBuf> {
+        match self {
+            ConfigLocation::Local => Ok(PathBuf::from("asum.toml")),
+            ConfigLocation::Global => asum_home()
+                .map(|dir| dir.join("asum.toml"))
+                .ok_or_else(|| anyhow!("Could not find home directory")),
+        }
+    }
+}
+
+/// Runs the wizard on stdin/stdout and writes the resulting configuration.
+///
+/// # Arguments
+/// * `global` - Write to `~/.asum/asum.toml` without asking for a location.
+/// * `force` - Overwrite an existing configuration file.
+pub fn run(global: bool, force: bool) -> Result<()> {
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock();
+    let mut output = std::io::stdout();
+
+    let (settings, location) = ask_settings(&mut input, &mut output, global)?;
+    let path = location.path()?;
+    write_config(&path, &settings, force)?;
+
+    writeln!(output, "\n[OK] Configuration written to {}", path.display())?;
+    writeln!(output, "Stage some changes and run 'asum' to try it out.")?;
+    Ok(())
+}
+
+/// Asks the user for provider, model, credentials and the file location.
+pub fn ask_settings<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    global: bool,
+) -> Result<(InitSettings, ConfigLocation)> {
+    writeln!(output, "ASUM setup - press Enter to accept the [default].")?;
+    let settings = ask_provider(input, output)?;
+
+    let location = if global {
+        ConfigLocation::Global
+    } else {
+        loop {
+            let answer = ask(
+                input,
+                output,
+                "Save to (l)ocal ./asum.toml or (g)lobal ~/.asum/asum.toml",
+                Some("l"),
+            )?
+            .to_lowercase();
+            match answer.as_str() {
+                "l" | "local" => break ConfigLocation::Local,
+                "g" | "global" => break ConfigLocation::Global,
+                _ => writeln!(output, "Please answer 'l' or 'g'.")?,
+            }
+        }
+    };
+
+    Ok((settings, location))
+}
+
+/// Asks the user for provider, model and credentials.
+pub fn ask_provider<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> Result<InitSettings> {
+    let providers = available_providers();
+    let default_provider = if providers.contains(&"ollama") {
+        "ollama"
+    } else {
+        providers
+            .first()
+            .copied()
+            .ok_or_else(|| anyhow!("No AI providers are compiled into this build"))?
+    };
+    let provider = loop {
+        let answer = ask(
+            input,
+            output,
+            &format!("Provider ({})", providers.join("/")),
+            Some(default_provider),
+        )?
+        .to_lowercase();
+        if providers.contains(&answer.as_str()) {
+            break answer;
+        }
+        writeln!(output, "Unknown provider '{}'.", answer)?;
+    };
+
+    let default_model = match provider.as_str() {
+        "gemini" => "gemini-2.0-flash",
+        _ => "qwen2.5-coder:3b",
+    };
+    let model = ask(input, output, "Model", Some(default_model))?;
+
+    let (api_key, ollama_url) = match provider.as_str() {
+        "gemini" => {
+            let key = match key_var(|name| std::env::var(name).ok()) {
+                Some(name) => {
+                    writeln!(
+                        output,
+                        "Using the API key in {}; it is not written to the file.",
+                        name
+                    )?;
+                    String::new()
+                }
+                None => {
+                    let key = ask_secret(input, output, "Gemini API key (not shown)")?;
+                    if key.is_empty() {
+                        writeln!(
+                            output,
+                            "No API key given; set GEMINI_API_KEY, or 'api_key' in the [gemini] section later."
+                        )?;
+                    }
+                    key
+                }
+            };
+            (Some(key), None)
+        }
+        _ => {
+            let url = ask(
+                input,
+                output,
+                "Ollama URL",
+                Some("http://localhost:11434/api/chat"),
+            )?;
+            (None, Some(url))
+        }
+    };
+
+    Ok(InitSettings {
+        provider,
+        model,
+        api_key,
+        ollama_url,
+        scopes: Vec::new(),
+        strict_format: false,
+    })
+}
+
+/// Returns the first of `KEY_VARS` that holds a key, looked up through `var`.
+pub fn key_var<F: Fn(&str) -> Option<String>>(var: F) -> Option<&'static str> {
+    KEY_VARS
+        .iter()
+        .copied()
+        .find(|name| var(name).is_some_and(|key| !key.trim().is_empty()))
+}
+
+/// Renders and writes the configuration file, creating parent directories as needed.
+/// Refuses to replace an existing file unless `force` is set.
+pub fn write_config(path: &Path, settings: &InitSettings, force: bool) -> Result<()> {
+    if path.exists() && !force {
+        return Err(anyhow!(
+            "{} already exists. Use --force to overwrite it.",
+            path.display()
+        ));
+    }
+
+    let content = render_config(settings)?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+    fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "ollama")]
+    #[test]
+    fn test_ask_settings_defaults() {
+        let mut input = std::io::Cursor::new("\n\n\n\n");
+        let mut output = Vec::new();
+
+        let (settings, location) = ask_settings(&mut input, &mut output, false).unwrap();
+        assert_eq!(settings.provider, "ollama");
+        assert_eq!(settings.model, "qwen2.5-coder:3b");
+        assert_eq!(
+            settings.ollama_url.as_deref(),
+            Some("http://localhost:11434/api/chat")
+        );
+        assert!(settings.api_key.is_none());
+        assert_eq!(location, ConfigLocation::Local);
+    }
+
+    #[cfg(feature = "gemini")]
+    #[test]
+    fn test_ask_settings_gemini_global() {
+        let mut input = std::io::Cursor::new("openai\ngemini\ngemini-pro\nmy-key\n");
+        let mut output = Vec::new();
+
+        let (settings, location) = ask_settings(&mut input, &mut output, true).unwrap();
+        assert_eq!(settings.provider, "gemini");
+        assert_eq!(settings.model, "gemini-pro");
+        assert_eq!(settings.api_key.as_deref(), Some("my-key"));
+        assert_eq!(location, ConfigLocation::Global);
+
+        let printed = String::from_utf8(output).unwrap();
+        assert!(printed.contains("Unknown provider 'openai'"));
+    }
+
+    #[cfg(feature = "ollama")]
+    #[test]
+    fn test_ask_settings_retries_location() {
+        let mut input = std::io::Cursor::new("\n\n\nx\nglobal\n");
+        let mut output = Vec::new();
+
+        let (_, location) = ask_settings(&mut input, &mut output, false).unwrap();
+        assert_eq!(location, ConfigLocation::Global);
+    }
+
+    #[test]
+    fn test_key_var_table_driven() {
+        let cases = [
+            (vec![], None),
+            (vec![("GEMINI_API_KEY", " ")], None),
+            (vec![("GEMINI_API_KEY", "key")], Some("GEMINI_API_KEY")),
+            (
+                vec![("GEMINI_API_KEY", "key"), ("ASUM_GEMINI_API_KEY", "key")],
+                Some("ASUM_GEMINI_API_KEY"),
+            ),
+        ];
+        for (vars, expected) in cases {
+            let found = key_var(|name| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            });
+            assert_eq!(found, expected, "{:?}", vars);
+        }
+    }
+
+    #[test]
+    fn test_write_config_refuses_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("asum.toml");
+        let settings = InitSettings {
+            provider: "ollama".to_string(),
+            model: "llama3".to_string(),
+            api_key: None,
+            ollama_url: None,
+            scopes: Vec::new(),
+            strict_format: false,
+        };
+
+        write_config(&path, &settings, false).unwrap();
+        assert!(crate::config::verify_toml(&path).is_ok());
+
+        let result = write_config(&path, &settings, false);
+        assert!(result.unwrap_err().to_string().contains("already exists"));
+        assert!(write_config(&path, &settings, true).is_ok());
+    }
+}
@@ -0,0 +1,64 @@
+//! `asum jj` command.
+//!
+//! Generates a message for the current Jujutsu (jj) change and sets it as the
+//! change description with `jj describe` once the user confirms.
+
+use crate::cli::Cli;
+use crate::clipboard;
+use crate::commands::commit::{Decision, decide};
+use crate::diff_source::{Jujutsu, run_vcs};
+use crate::failure::Failure;
+use crate::guard;
+use crate::pipeline::{adapt_prompts, read_diff, save_generation, summarize_diff};
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Generates a description for the working-copy change (`@`) and applies it.
+///
+/// # Arguments
+/// * `yes` - Describe the change without asking for confirmation.
+pub async fn run(cli: &Cli, yes: bool) -> Result<()> {
+    let mut config = cli.load_config()?;
+    let source = Jujutsu {
+        path: PathBuf::from("."),
+    };
+    let diff_text = read_diff(&config, &source, Path::new("."))?;
+
+    adapt_prompts(&mut config, Path::new("."), &diff_text).await;
+    let message = summarize_diff(config.clone(), diff_text.clone()).await?;
+    save_generation(&config, Path::new("."), &diff_text, &message);
+    println!("{}\n", cli.format_message(&message));
+
+    // Without a git directory there is nowhere to keep a draft, so problems only block `--yes`
+    let problems = guard::validate(&message);
+    if yes && !problems.is_empty() {
+        return Err(Failure::InvalidOutput.error(format!(
+            "Refusing to describe the change with the generated message:\n  - {}",
+            problems.join("\n  - ")
+        )));
+    }
+    guard::warn_problems(&message);
+
+    let stdin = std::io::stdin();
+    match decide(&mut stdin.lock(), &mut std::io::stdout(), yes, false)? {
+        Decision::Commit | Decision::Edit => describe(&message, Path::new("."))
+            .inspect_err(|_| clipboard::save_restore_point(&message)),
+        Decision::Abort => {
+            println!("Aborted. The change description was not changed.");
+            clipboard::deliver(&message, cli.clipboard(&config).as_deref());
+            Ok(())
+        }
+    }
+}
+
+/// Sets the description of the working-copy change in the jj repository at `path`.
+pub fn describe(message: &str, path: &Path) -> Result<()> {
+    let mut command = Command::new("jj");
+    command
+        .args(["describe", "--no-pager", "-m", message])
+        .current_dir(path);
+    run_vcs(command, "Jujutsu (jj)")?;
+    println!("[OK] Described the current jj change.");
+    Ok(())
+}
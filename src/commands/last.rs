@@ -0,0 +1,137 @@
+//! `asum last` command.
+//!
+//! Prints the most recently generated message for the current repository and
+//! copies it to the clipboard again. It is taken from whichever was saved last:
+//! the history database, the repository's draft, or the restore point kept when
+//! a message could not be delivered.
+
+use crate::cli::Cli;
+use crate::clipboard;
+use crate::config::{AsumConfig, last_message_path};
+use crate::db::Db;
+use crate::guard::draft_path;
+use crate::pipeline::repo_key;
+use anyhow::{Result, anyhow};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use tracing::debug;
+
+/// A saved message, with when it was saved (seconds since the Unix epoch) and where.
+#[derive(Debug, PartialEq)]
+pub struct Saved {
+    pub saved_at: i64,
+    pub source: &'static str,
+    pub message: String,
+}
+
+/// Prints the last message and copies it to the clipboard.
+pub fn run(cli: &Cli) -> Result<()> {
+    let saved = find_last(Path::new("."))?.ok_or_else(|| anyhow!("No generated message found."))?;
+    debug!("Last message taken from the {}", saved.source);
+    println!("{}", cli.format_message(&saved.message));
+    // Only the clipboard setting is used, so this works without asum.toml
+    let config = cli.load_config().or_else(|_| AsumConfig::from_defaults())?;
+    clipboard::deliver(&saved.message, cli.clipboard(&config).as_deref());
+    Ok(())
+}
+
+/// Returns the most recently saved message for the repository at `repo`.
+/// Sources that cannot be read are skipped.
+pub fn find_last(repo: &Path) -> Result<Option<Saved>> {
+    let mut candidates = Vec::new();
+    if let Some(path) = last_message_path() {
+        candidates.extend(read_file(&path, "restore point"));
+    }
+    if let Ok(path) = draft_path(repo) {
+        candidates.extend(read_file(&path, "draft"));
+    }
+    let generation = Db::open_default()
+        .and_then(|db| db.history(Some(&repo_key(repo)?), None, 1))
+        .map(|mut generations| generations.pop());
+    match generation {
+        Ok(Some(g)) => candidates.push(Saved {
+            saved_at: g.created_at,
+            source: "history",
+            message: g.message,
+        }),
+        Ok(None) => {}
+        Err(e) => debug!("Could not read the history: {:#}", e),
+    }
+
+    // On a tie, the later candidate (the history) wins
+    Ok(candidates.into_iter().max_by_key(|saved| saved.saved_at))
+}
+
+/// Reads a message file, using its modification time as the time it was saved.
+fn read_file(path: &Path, source: &'static str) -> Option<Saved> {
+    let saved_at = std::fs::metadata(path)
+        .ok()?
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_secs() as i64;
+    let message = std::fs::read_to_string(path).ok()?.trim_end().to_string();
+    (!message.is_empty()).then_some(Saved {
+        saved_at,
+        source,
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use std::time::{Duration, SystemTime};
+
+    fn set_modified(path: &Path, seconds_ago: u64) {
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(seconds_ago))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_find_last_prefers_newest_source() {
+        let _guard = crate::test_utils::TEST_MUTEX.lock().unwrap();
+        let _home = crate::test_utils::TempHome::create();
+        let repo = tempfile::tempdir().unwrap();
+        Command::new("git")
+            .args(["init", "-b", "main"])
+            .current_dir(repo.path())
+            .output()
+            .unwrap();
+        assert_eq!(find_last(repo.path()).unwrap(), None);
+
+        // An older restore point loses to a newer draft
+        let restore = last_message_path().unwrap();
+        std::fs::create_dir_all(restore.parent().unwrap()).unwrap();
+        std::fs::write(&restore, "feat: restored").unwrap();
+        set_modified(&restore, 120);
+        crate::guard::save_draft("fix: drafted", repo.path()).unwrap();
+        set_modified(&draft_path(repo.path()).unwrap(), 60);
+        let saved = find_last(repo.path()).unwrap().unwrap();
+        assert_eq!(
+            (saved.source, saved.message.as_str()),
+            ("draft", "fix: drafted")
+        );
+
+        // A message recorded just now is newer than both
+        Db::open_default()
+            .unwrap()
+            .record_generation(
+                &repo_key(repo.path()).unwrap(),
+                "main",
+                "hash",
+                "ollama",
+                "llama3",
+                "docs: latest",
+            )
+            .unwrap();
+        let saved = find_last(repo.path()).unwrap().unwrap();
+        assert_eq!(
+            (saved.source, saved.message.as_str()),
+            ("history", "docs: latest")
+        );
+    }
+}
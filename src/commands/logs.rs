@@ -0,0 +1,322 @@
+//! `asum logs` command.
+//!
+//! Prints recent entries from the daily log files in `~/.asum/logs`, so failures
+//! can be debugged without knowing where the files live, and optionally keeps
+//! printing new entries as they are written.
+
+use crate::cli::LogLevel;
+use crate::commands::history::format_time;
+use crate::config::log_dir;
+use anyhow::{Context, Result, anyhow};
+use clap::ValueEnum;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Prefix of the log file names; the daily appender adds the date.
+const LOG_FILE_PREFIX: &str = "asum.log";
+
+/// How often `--follow` checks the log files for new entries.
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+/// One log entry: its first line and any continuation lines.
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    /// RFC 3339 timestamp in UTC, as written by the file logger.
+    pub timestamp: String,
+    pub level: LogLevel,
+    pub text: String,
+}
+
+/// Prints the last `limit` matching entries, then keeps printing new ones with `follow`.
+pub fn run(since: Option<&str>, level: Option<LogLevel>, follow: bool, limit: usize) -> Result<()> {
+    let dir = log_dir().context("Could not find home directory")?;
+    let cutoff = since.map(|spec| parse_since(spec, now())).transpose()?;
+
+    let files = log_files(&dir);
+    if files.is_empty() && !follow {
+        println!("No log files found in {}.", dir.display());
+        return Ok(());
+    }
+    let mut content = String::new();
+    for file in &files {
+        content.push_str(&std::fs::read_to_string(file).unwrap_or_default());
+    }
+    let entries = parse_entries(&content);
+    for entry in select(&entries, cutoff.as_deref(), level, limit) {
+        println!("{}", entry.text);
+    }
+
+    if follow {
+        follow_logs(&dir, files.last().cloned(), level)?;
+    }
+    Ok(())
+}
+
+/// Turns `--since` into the earliest timestamp to show: a duration back from `now`
+/// (seconds since the Unix epoch) such as `30m`, `2h`, `1d` or `1w`, or a UTC date
+/// or time such as `2024-05-01` or `2024-05-01 14:00`.
+pub fn parse_since(spec: &str, now: i64) -> Result<String> {
+    let spec = spec.trim();
+    let unit = match spec.chars().last() {
+        Some('m') => Some(60),
+        Some('h') => Some(3600),
+        Some('d') => Some(86_400),
+        Some('w') => Some(7 * 86_400),
+        _ => None,
+    };
+    if let Some(unit) = unit
+        && let Ok(amount) = spec[..spec.len() - 1].parse::<i64>()
+    {
+        return Ok(format_time(now - amount * unit).replace(' ', "T"));
+    }
+
+    let is_date = spec.len() >= 10
+        && spec.bytes().take(10).enumerate().all(|(i, b)| {
+            if i == 4 || i == 7 {
+                b == b'-'
+            } else {
+                b.is_ascii_digit()
+            }
+        });
+    if is_date {
+        return Ok(spec.replacen(' ', "T", 1));
+    }
+    Err(anyhow!(
+        "Invalid --since '{}': expected e.g. 30m, 2h, 1d, 1w or 2024-05-01.",
+        spec
+    ))
+}
+
+/// Returns the log files in `dir`, oldest first. The date suffix sorts by name.
+pub fn log_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(LOG_FILE_PREFIX))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Splits log text into entries. Lines that do not start with a timestamp and a
+/// level belong to the entry before them; lines before the first entry are skipped.
+pub fn parse_entries(content: &str) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = Vec::new();
+    for line in content.lines() {
+        match entry_start(line) {
+            Some((timestamp, level)) => entries.push(Entry {
+                timestamp: timestamp.to_string(),
+                level,
+                text: line.to_string(),
+            }),
+            None => {
+                if let Some(entry) = entries.last_mut() {
+                    entry.text.push('\n');
+                    entry.text.push_str(line);
+                }
+            }
+        }
+    }
+    entries
+}
+
+/// Reads the timestamp and level at the start of a log line, if it starts an entry.
+fn entry_start(line: &str) -> Option<(&str, LogLevel)> {
+    let mut parts = line.split_whitespace();
+    let timestamp = parts.next()?;
+    if !(timestamp.len() >= 20 && timestamp.as_bytes()[10] == b'T' && timestamp.ends_with('Z')) {
+        return None;
+    }
+    let level = LogLevel::from_str(parts.next()?, true).ok()?;
+    Some((timestamp, level))
+}
+
+/// The last `limit` entries at or after `cutoff` and at least as severe as `level`.
+pub fn select<'a>(
+    entries: &'a [Entry],
+    cutoff: Option<&str>,
+    level: Option<LogLevel>,
+    limit: usize,
+) -> Vec<&'a Entry> {
+    let matching: Vec<&Entry> = entries
+        .iter()
+        .filter(|entry| cutoff.is_none_or(|cutoff| entry.timestamp.as_str() >= cutoff))
+        .filter(|entry| level.is_none_or(|level| entry.level <= level))
+        .collect();
+    matching[matching.len().saturating_sub(limit)..].to_vec()
+}
+
+/// Prints entries appended to the newest log file until the process is stopped,
+/// moving on to the next file when the daily appender starts one.
+fn follow_logs(dir: &Path, mut current: Option<PathBuf>, level: Option<LogLevel>) -> Result<()> {
+    let mut offset = current
+        .as_ref()
+        .and_then(|file| std::fs::metadata(file).ok())
+        .map_or(0, |metadata| metadata.len());
+    let mut pending = String::new();
+    let mut showing = false;
+
+    loop {
+        std::thread::sleep(FOLLOW_INTERVAL);
+        let newest = log_files(dir).pop();
+        if newest.is_some() && newest != current {
+            current = newest;
+            offset = 0;
+        }
+        let Some(file) = &current else {
+            continue;
+        };
+
+        let mut handle = std::fs::File::open(file)?;
+        let length = handle.metadata()?.len();
+        if length < offset {
+            // The file was truncated or replaced
+            offset = 0;
+        }
+        handle.seek(SeekFrom::Start(offset))?;
+        let mut bytes = Vec::new();
+        handle.read_to_end(&mut bytes)?;
+        offset += bytes.len() as u64;
+        pending.push_str(&String::from_utf8_lossy(&bytes));
+
+        // Only complete lines are printed; the rest waits for the next read
+        let Some(end) = pending.rfind('\n') else {
+            continue;
+        };
+        for line in pending[..end].lines() {
+            if let Some((_, entry_level)) = entry_start(line) {
+                showing = level.is_none_or(|level| entry_level <= level);
+            }
+            if showing {
+                println!("{}", line);
+            }
+        }
+        pending.drain(..=end);
+    }
+}
+
+/// Seconds since the Unix epoch.
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "\
+2024-06-15T12:00:00.000001Z  INFO asum::pipeline: AI is analyzing your changes...
+2024-06-15T12:00:01.000001Z  WARN asum::pipeline: A candidate request failed: timeout
+2024-06-15T12:30:00.000001Z ERROR asum: Summarization failed: provider error
+caused by: connection refused
+2024-06-15T13:00:00.000001Z DEBUG asum::git: Running git diff
+";
+
+    #[test]
+    fn test_parse_entries() {
+        let entries = parse_entries(&format!("partial line\n{}", LOG));
+        let levels: Vec<LogLevel> = entries.iter().map(|entry| entry.level).collect();
+        assert_eq!(
+            levels,
+            vec![
+                LogLevel::Info,
+                LogLevel::Warn,
+                LogLevel::Error,
+                LogLevel::Debug
+            ]
+        );
+        assert_eq!(
+            entries[2].text,
+            "2024-06-15T12:30:00.000001Z ERROR asum: Summarization failed: provider error\n\
+             caused by: connection refused"
+        );
+    }
+
+    #[test]
+    fn test_select_table_driven() {
+        let entries = parse_entries(LOG);
+        let cases = vec![
+            (
+                None,
+                None,
+                50,
+                vec!["12:00:00", "12:00:01", "12:30:00", "13:00:00"],
+            ),
+            (None, None, 2, vec!["12:30:00", "13:00:00"]),
+            (None, Some(LogLevel::Warn), 50, vec!["12:00:01", "12:30:00"]),
+            (
+                Some("2024-06-15T12:30"),
+                None,
+                50,
+                vec!["12:30:00", "13:00:00"],
+            ),
+            (
+                Some("2024-06-15T12:30"),
+                Some(LogLevel::Info),
+                50,
+                vec!["12:30:00"],
+            ),
+            (Some("2024-06-16"), None, 50, vec![]),
+        ];
+        for (cutoff, level, limit, expected) in cases {
+            let times: Vec<&str> = select(&entries, cutoff, level, limit)
+                .iter()
+                .map(|entry| &entry.timestamp[11..19])
+                .collect();
+            assert_eq!(
+                times, expected,
+                "cutoff: {:?}, level: {:?}, limit: {}",
+                cutoff, level, limit
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_since_table_driven() {
+        // 2024-06-15 12:30 UTC
+        let now = 1_718_454_600;
+        let cases = vec![
+            ("30m", Some("2024-06-15T12:00")),
+            ("2h", Some("2024-06-15T10:30")),
+            ("1d", Some("2024-06-14T12:30")),
+            ("1w", Some("2024-06-08T12:30")),
+            ("2024-05-01", Some("2024-05-01")),
+            ("2024-05-01 14:00", Some("2024-05-01T14:00")),
+            ("yesterday", None),
+            ("h", None),
+            ("", None),
+        ];
+        for (spec, expected) in cases {
+            assert_eq!(
+                parse_since(spec, now).ok().as_deref(),
+                expected,
+                "spec: {:?}",
+                spec
+            );
+        }
+    }
+
+    #[test]
+    fn test_log_files_sorted_by_date() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["asum.log.2024-06-15", "asum.log.2024-06-14", "other.txt"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        let names: Vec<String> = log_files(dir.path())
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["asum.log.2024-06-14", "asum.log.2024-06-15"]);
+    }
+}
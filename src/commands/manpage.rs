@@ -0,0 +1,208 @@
+//! `asum manpage` command.
+//!
+//! Renders a roff man page for `asum(1)` from the clap command definition, the
+//! documented `asum.toml` keys and the environment variables ASUM reads.
+
+use crate::cli::Cli;
+use crate::config::{FIELD_DOCS, SECTION_DOCS};
+use clap::CommandFactory;
+use clap_mangen::Man;
+use clap_mangen::roff::{Roff, bold, italic, roman};
+
+/// Environment variables read by ASUM as `(name, description)`.
+const ENV_DOCS: &[(&str, &str)] = &[
+    (
+        "ASUM_PROVIDER",
+        "Active provider, overriding active_provider.",
+    ),
+    ("ASUM_MODEL", "Model for the active provider."),
+    ("ASUM_OLLAMA_URL", "Ollama API URL."),
+    (
+        "ASUM_GEMINI_API_KEY, GEMINI_API_KEY",
+        "Gemini API key, overriding [gemini] api_key.",
+    ),
+    (
+        "ASUM_MESSAGE_FILE",
+        "Also write the generated message to this file.",
+    ),
+    (
+        "CODESPACES, REMOTE_CONTAINERS, DEVCONTAINER",
+        "Container mode: copy with the OSC 52 escape sequence and allow running without asum.toml.",
+    ),
+    (
+        "WAYLAND_DISPLAY, TMUX",
+        "With clipboard = \"auto\", also try wl-copy or tmux when the desktop clipboard fails.",
+    ),
+    (
+        "VISUAL, EDITOR",
+        "Editor used to edit messages and prompts.",
+    ),
+    (
+        "GIT_PAGER, PAGER",
+        "Pager for long diffs, tried before and after git's core.pager.",
+    ),
+    ("NO_COLOR", "Disable colored diffs."),
+    ("RUST_LOG", "Extra log filter directives, e.g. asum=trace."),
+];
+
+/// Files read or written by ASUM as `(path, description)`.
+const FILE_DOCS: &[(&str, &str)] = &[
+    ("./asum.toml", "Configuration of the current directory."),
+    (
+        "~/.asum/asum.toml",
+        "Global configuration, used when there is no local file.",
+    ),
+    (
+        "~/.asum/asum.db",
+        "History, feedback, cache and token usage.",
+    ),
+    ("~/.asum/logs", "Daily log files, shown by asum logs."),
+    (
+        "~/.asum/last-message.txt",
+        "Last message that could not be delivered, shown by asum last.",
+    ),
+];
+
+/// Renders the complete man page as roff source.
+pub fn render() -> anyhow::Result<String> {
+    let command = Cli::command();
+    let man = Man::new(command.clone()).date(env!("ASUM_BUILD_DATE"));
+    let mut page = Vec::new();
+    man.render_title(&mut page)?;
+    man.render_name_section(&mut page)?;
+    man.render_synopsis_section(&mut page)?;
+    man.render_description_section(&mut page)?;
+    man.render_options_section(&mut page)?;
+
+    let mut roff = Roff::new();
+    roff.control("SH", ["COMMANDS"]);
+    for sub in command.get_subcommands().filter(|c| !c.is_hide_set()) {
+        render_command(&mut roff, sub, "asum");
+    }
+
+    roff.control("SH", ["CONFIGURATION"]);
+    roff.text([roman(
+        "Settings are read from asum.toml; asum config schema prints a commented example.",
+    )]);
+    for (section, section_doc) in SECTION_DOCS {
+        roff.control("SS", [format!("[{}]", section).as_str()]);
+        roff.text([roman(*section_doc)]);
+        for (_, key, doc) in FIELD_DOCS.iter().filter(|(s, _, _)| s == section) {
+            roff.control("TP", []);
+            roff.text([bold(*key)]);
+            roff.text([roman(*doc)]);
+        }
+    }
+
+    roff.control("SH", ["ENVIRONMENT"]);
+    for (name, doc) in ENV_DOCS {
+        roff.control("TP", []);
+        roff.text([bold(*name)]);
+        roff.text([roman(*doc)]);
+    }
+
+    roff.control("SH", ["FILES"]);
+    for (path, doc) in FILE_DOCS {
+        roff.control("TP", []);
+        roff.text([italic(*path)]);
+        roff.text([roman(*doc)]);
+    }
+    roff.to_writer(&mut page)?;
+
+    man.render_version_section(&mut page)?;
+    Ok(String::from_utf8(page)?)
+}
+
+/// Adds a command, its arguments and its own subcommands to the COMMANDS section.
+fn render_command(roff: &mut Roff, command: &clap::Command, parent: &str) {
+    let name = format!("{} {}", parent, command.get_name());
+    let mut usage = vec![bold(name.as_str())];
+    for arg in command.get_positionals() {
+        let value = arg
+            .get_value_names()
+            .and_then(|names| names.first())
+            .map(|n| n.to_string())
+            .unwrap_or_else(|| arg.get_id().to_string().to_uppercase());
+        usage.push(roman(" "));
+        usage.push(italic(if arg.is_required_set() {
+            value
+        } else {
+            format!("[{}]", value)
+        }));
+    }
+    roff.control("TP", []);
+    roff.text(usage);
+    if let Some(about) = command.get_about() {
+        roff.text([roman(about.to_string())]);
+    }
+
+    let args: Vec<_> = command
+        .get_arguments()
+        .filter(|a| !a.is_hide_set() && !a.is_global_set() && a.get_id() != "help")
+        .collect();
+    if !args.is_empty() {
+        roff.control("RS", []);
+        for arg in args {
+            let mut flags = Vec::new();
+            if let Some(short) = arg.get_short() {
+                flags.push(format!("-{}", short));
+            }
+            if let Some(long) = arg.get_long() {
+                flags.push(format!("--{}", long));
+            }
+            if flags.is_empty() {
+                flags.push(arg.get_id().to_string().to_uppercase());
+            }
+            roff.control("TP", []);
+            roff.text([bold(flags.join(", "))]);
+            if let Some(help) = arg.get_help() {
+                roff.text([roman(help.to_string())]);
+            }
+        }
+        roff.control("RE", []);
+    }
+
+    for sub in command.get_subcommands().filter(|c| !c.is_hide_set()) {
+        render_command(roff, sub, &name);
+    }
+}
+
+/// Prints the man page, e.g. for `asum manpage > asum.1`.
+pub fn run() -> anyhow::Result<()> {
+    print!("{}", render()?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_covers_commands_config_and_environment() {
+        let page = render().unwrap();
+        assert!(page.starts_with(".ie"), "page: {}", &page[..80]);
+        for expected in [
+            ".SH COMMANDS",
+            "asum hook install",
+            "asum config set\\-model",
+            "\\-\\-cron\\-friendly",
+            ".SH CONFIGURATION",
+            "[general]",
+            "max_body_bullets",
+            ".SH ENVIRONMENT",
+            "ASUM_MESSAGE_FILE",
+            ".SH FILES",
+        ] {
+            assert!(page.contains(expected), "missing {:?}", expected);
+        }
+        assert!(!page.contains("asum hook run"));
+    }
+
+    #[test]
+    fn test_every_config_section_is_documented() {
+        let page = render().unwrap();
+        for (section, _) in SECTION_DOCS {
+            assert!(page.contains(&format!(".SS [{}]", section)));
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Subcommand implementations for ASUM.
+//!
+//! Each submodule handles one `asum <command>` invocation.
+
+pub mod amend;
+pub mod branch;
+pub mod cache;
+pub mod changelog;
+pub mod comment;
+pub mod commit;
+pub mod config;
+pub mod explain;
+pub mod feedback;
+pub mod fixup;
+pub mod history;
+pub mod hook;
+pub mod init;
+pub mod jj;
+pub mod last;
+pub mod logs;
+pub mod manpage;
+pub mod pr;
+pub mod prompt;
+pub mod range;
+pub mod redo;
+pub mod report;
+pub mod review;
+pub mod serve;
+pub mod setup;
+pub mod split;
+pub mod squash;
+pub mod squash_prep;
+pub mod standup;
+pub mod stash;
+pub mod stats;
+pub mod tag;
+pub mod translate;
+pub mod tui;
+pub mod verify;
+pub mod version;
+pub mod wip;
@@ -0,0 +1,140 @@
+//! `asum pr` command.
+//!
+//! Drafts a pull request title and markdown description from the commits and
+//! changes of the current branch relative to a base ref.
+
+use crate::cli::Cli;
+use crate::clipboard;
+use crate::git::{get_branch_commits_in_path, get_branch_diff_in_path};
+use crate::pipeline::{LONG_REPLY_NUM_PREDICT, summarize_diff};
+use anyhow::{Context, Result, anyhow};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tracing::warn;
+
+/// System prompt of the pull request: a title and a markdown description of the branch.
+const PR_SYSTEM_PROMPT: &str = r###"You are an expert software engineer writing a pull request.
+Given the commits and the diff of a branch, write:
+1. On the first line, a concise pull request title (imperative mood, max 72 chars, no trailing period).
+2. Then a markdown description with a "## Summary" section (1-3 sentences on what and why)
+   and a "## Changes" section with bullet points ("- ").
+Return ONLY the title and the description. No preamble, no code fences."###;
+
+/// User prompt template; `{{diff}}` is replaced with the commits and the diff.
+const PR_USER_PROMPT: &str = "[BRANCH CHANGES]\n{{diff}}\n\n[PULL REQUEST]";
+
+/// A drafted pull request.
+#[derive(Debug, PartialEq)]
+pub struct PullRequest {
+    pub title: String,
+    pub body: String,
+}
+
+/// Drafts the pull request and prints it, or opens it with `gh` when `create` is set.
+pub async fn run(cli: &Cli, base: &str, create: bool) -> Result<()> {
+    let mut config = cli.load_config()?;
+    let sink = cli.clipboard(&config);
+    let repo = Path::new(".");
+
+    let commits = get_branch_commits_in_path(base, repo)?;
+    if commits.trim().is_empty() {
+        warn!("No commits found between '{}' and HEAD.", base);
+        return Ok(());
+    }
+    let diff = get_branch_diff_in_path(base, &config.pathspecs(), repo)
+        .context("Failed to get branch diff")?;
+
+    config.system_prompt = PR_SYSTEM_PROMPT.to_string();
+    config.user_prompt = PR_USER_PROMPT.to_string();
+    config.ai_num_predict = config.ai_num_predict.max(LONG_REPLY_NUM_PREDICT);
+    let input = format!("Commits:\n{}\nDiff:\n{}", commits, diff);
+    let reply = summarize_diff(config, input).await?;
+    let pr = parse_reply(&reply).ok_or_else(|| anyhow!("AI generated an empty pull request."))?;
+
+    if create {
+        create_with_gh(base, &pr)
+    } else {
+        let text = format!("{}\n\n{}", pr.title, pr.body);
+        println!("{}", text);
+        clipboard::deliver(&text, sink.as_deref());
+        Ok(())
+    }
+}
+
+/// Splits the model's reply into a title (first non-empty line) and a body (the rest).
+/// Leading markdown heading marks and a "Title:" label are removed from the title.
+pub fn parse_reply(reply: &str) -> Option<PullRequest> {
+    let reply = reply.trim();
+    let (first, rest) = reply.split_once('\n').unwrap_or((reply, ""));
+
+    let title = first.trim().trim_start_matches('#').trim();
+    let title = title
+        .strip_prefix("Title:")
+        .or_else(|| title.strip_prefix("title:"))
+        .unwrap_or(title)
+        .trim()
+        .trim_matches(|c| c == '`' || c == '"' || c == '*')
+        .to_string();
+
+    if title.is_empty() {
+        return None;
+    }
+    Some(PullRequest {
+        title,
+        body: rest.trim().to_string(),
+    })
+}
+
+/// Opens the pull request with `gh pr create`, passing the body on stdin.
+fn create_with_gh(base: &str, pr: &PullRequest) -> Result<()> {
+    let mut child = Command::new("gh")
+        .args(["pr", "create", "--base", base, "--title", &pr.title])
+        .args(["--body-file", "-"])
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("Failed to run gh. Install the GitHub CLI from https://cli.github.com")?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(pr.body.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        anyhow::bail!("gh pr create failed ({})", status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reply_table_driven() {
+        let cases = vec![
+            (
+                "Add OAuth2 login\n## Summary\nAdds login.\n## Changes\n- add provider",
+                Some((
+                    "Add OAuth2 login",
+                    "## Summary\nAdds login.\n## Changes\n- add provider",
+                )),
+            ),
+            (
+                "\n# Title: `Fix crash on empty diff`\n\nBody text\n",
+                Some(("Fix crash on empty diff", "Body text")),
+            ),
+            ("Only a title", Some(("Only a title", ""))),
+            ("**Bold title**\n- change", Some(("Bold title", "- change"))),
+            ("", None),
+            ("#\nbody", None),
+        ];
+
+        for (reply, expected) in cases {
+            let expected = expected.map(|(title, body)| PullRequest {
+                title: title.to_string(),
+                body: body.to_string(),
+            });
+            assert_eq!(parse_reply(reply), expected, "reply: {:?}", reply);
+        }
+    }
+}
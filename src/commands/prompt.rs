@@ -0,0 +1,85 @@
+//! `asum prompt` command.
+//!
+//! Shows the prompts asum sends, after defaults, environment variables and
+//! overrides, and edits them in `$EDITOR` so they never have to be written as
+//! TOML multiline strings by hand.
+
+use crate::cli::{Cli, PromptAction, PromptKind};
+use crate::config::{AsumConfig, set_prompt};
+use crate::interactive::edit_in_editor;
+use anyhow::{Result, bail};
+
+/// Executes an `asum prompt` action.
+pub fn run(cli: &Cli, action: &PromptAction) -> Result<()> {
+    match action {
+        PromptAction::Show => print!("{}", render(&cli.load_config()?)),
+        PromptAction::Edit { prompt } => {
+            let path = cli.config_file()?;
+            let config = cli.load_config()?;
+            let (key, current) = match prompt {
+                PromptKind::System => ("system_prompt", config.system_prompt),
+                PromptKind::User => ("user_prompt", config.user_prompt),
+            };
+
+            let edited = edit_in_editor(&current)?;
+            if edited == current.trim_end() {
+                println!("No changes; {} was not modified.", path.display());
+                return Ok(());
+            }
+            validate(*prompt, &edited)?;
+            set_prompt(&path, key, &edited)?;
+            println!("[OK] {} saved to {}", key, path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Renders both prompts under the headings `--dry-run` uses.
+pub fn render(config: &AsumConfig) -> String {
+    format!(
+        "===== SYSTEM PROMPT =====\n{}\n\n===== USER PROMPT =====\n{}\n",
+        config.system_prompt.trim_end(),
+        config.user_prompt.trim_end()
+    )
+}
+
+/// Rejects edited prompts that would break every request: an empty prompt, or a
+/// user prompt without the `{{diff}}` placeholder.
+fn validate(kind: PromptKind, prompt: &str) -> Result<()> {
+    if prompt.trim().is_empty() {
+        bail!("The edited prompt is empty; nothing was saved.");
+    }
+    if kind == PromptKind::User && !prompt.contains("{{diff}}") {
+        bail!("The user prompt must contain the {{{{diff}}}} placeholder; nothing was saved.");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_table_driven() {
+        let cases = vec![
+            (PromptKind::System, "Be brief.", true),
+            (PromptKind::System, " \n", false),
+            (PromptKind::User, "Diff:\n{{diff}}", true),
+            (PromptKind::User, "Diff:\n{diff}", false),
+        ];
+        for (kind, prompt, ok) in cases {
+            assert_eq!(validate(kind, prompt).is_ok(), ok, "prompt: {:?}", prompt);
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let mut config = AsumConfig::from_defaults().unwrap();
+        config.system_prompt = "Be brief.\n".to_string();
+        config.user_prompt = "{{diff}}".to_string();
+        assert_eq!(
+            render(&config),
+            "===== SYSTEM PROMPT =====\nBe brief.\n\n===== USER PROMPT =====\n{{diff}}\n"
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! `asum range` command.
+//!
+//! Summarizes a revision range as a whole, from the individual messages and the
+//! combined diff of its commits, e.g. for a patch series cover letter or the
+//! description of a backport.
+
+use crate::cli::Cli;
+use crate::clipboard;
+use crate::commands::squash::normalize_range;
+use crate::git::{get_range_diff_in_path, get_range_messages_in_path};
+use crate::pipeline::{LONG_REPLY_NUM_PREDICT, summarize_diff};
+use anyhow::{Result, anyhow};
+use std::path::Path;
+
+/// System prompt of the range summary: a title and description for a series of commits.
+const RANGE_SYSTEM_PROMPT: &str = r###"You are an expert software engineer summarizing a series of commits.
+Given the commit messages and the combined diff of a revision range, write:
+1. On the first line, a concise title for the whole series (imperative mood, max 72 chars, no trailing period).
+2. A blank line, then 1-3 sentences on what the series does and why.
+3. A blank line, then bullet points ("- ") for the notable changes, grouping related commits.
+Return ONLY the title and the summary. No preamble, no code fences."###;
+
+/// User prompt template; `{{diff}}` is replaced with the messages and the diff.
+const RANGE_USER_PROMPT: &str = "[COMMIT RANGE]\n{{diff}}\n\n[SUMMARY]";
+
+/// Summarizes `range` and prints the summary.
+pub async fn run(cli: &Cli, range: &str) -> Result<()> {
+    let mut config = cli.load_config()?;
+    let sink = cli.clipboard(&config);
+    let repo = Path::new(".");
+    let range = normalize_range(range);
+
+    let messages = get_range_messages_in_path(&range, repo)?;
+    if messages.is_empty() {
+        return Err(anyhow!("No commits found in {}.", range));
+    }
+    let diff = get_range_diff_in_path(&range, &config.pathspecs(), repo)?;
+
+    config.system_prompt = RANGE_SYSTEM_PROMPT.to_string();
+    config.user_prompt = RANGE_USER_PROMPT.to_string();
+    config.ai_num_predict = config.ai_num_predict.max(LONG_REPLY_NUM_PREDICT);
+    let summary = summarize_diff(config, build_input(&messages, &diff)).await?;
+    let summary = summary.trim();
+    if summary.is_empty() {
+        return Err(anyhow!("AI generated an empty summary."));
+    }
+    println!("{}", summary);
+    clipboard::deliver(summary, sink.as_deref());
+    Ok(())
+}
+
+/// Lists the commits of the range, oldest first, before the combined diff, so the
+/// messages survive when the diff is truncated.
+pub fn build_input(messages: &[String], diff: &str) -> String {
+    let mut input = format!("Commits ({}, oldest first):\n", messages.len());
+    for message in messages {
+        let mut lines = message.lines();
+        input.push_str(&format!("- {}\n", lines.next().unwrap_or_default()));
+        for line in lines.filter(|l| !l.trim().is_empty()) {
+            input.push_str(&format!("  {}\n", line));
+        }
+    }
+    input.push_str("\nCombined diff:\n");
+    input.push_str(diff);
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_input() {
+        let messages = vec![
+            "fix: handle empty diff".to_string(),
+            "feat: add --base\n\nCompare with the merge base.".to_string(),
+        ];
+        let input = build_input(&messages, "diff --git a/x b/x\n");
+        assert_eq!(
+            input,
+            "Commits (2, oldest first):\n- fix: handle empty diff\n- feat: add --base\n  \
+             Compare with the merge base.\n\nCombined diff:\ndiff --git a/x b/x\n"
+        );
+    }
+}
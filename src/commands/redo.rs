@@ -0,0 +1,44 @@
+//! `asum redo` command.
+//!
+//! Regenerates the last message of the current repository from the diff and
+//! prompts stored with it, so the result does not depend on what is staged now.
+
+use crate::cli::Cli;
+use crate::clipboard;
+use crate::db::Db;
+use crate::pipeline::{repo_key, save_generation, summarize_diff, with_instruction};
+use anyhow::{Result, anyhow};
+use std::path::Path;
+
+/// Regenerates the last message and prints it.
+///
+/// # Arguments
+/// * `hint` - Extra instruction for the model, e.g. "mention the migration".
+pub async fn run(cli: &Cli, hint: Option<&str>) -> Result<()> {
+    let mut config = cli.load_config()?;
+    let repo = repo_key(Path::new("."))?;
+    let last = Db::open_default()?.last_request(&repo)?.ok_or_else(|| {
+        anyhow!(
+            "Nothing to redo: no message has been generated in {} yet.",
+            repo
+        )
+    })?;
+
+    config.system_prompt = last.system_prompt;
+    config.user_prompt = last.user_prompt;
+    // The stored system prompt already asks for the body bullet limit
+    config.commit_message = true;
+    // Redo asks for a new message, not the cached one
+    config.no_cache = true;
+    let request = match hint {
+        Some(hint) => with_instruction(&config, hint),
+        None => config.clone(),
+    };
+    let message = summarize_diff(request, last.diff.clone()).await?;
+    println!("{}", cli.format_message(&message));
+
+    // The hint is not stored, so the next redo starts from the same prompts
+    save_generation(&config, Path::new("."), &last.diff, &message);
+    clipboard::deliver(&message, cli.clipboard(&config).as_deref());
+    Ok(())
+}
@@ -0,0 +1,342 @@
+//! `asum report` command.
+//!
+//! Writes a narrative report of the changes in one or more repositories over a
+//! period, for stakeholder updates. Commits are grouped by Conventional Commits
+//! type and scope, with commit counts per type and per author, and the report
+//! is rendered as Markdown or HTML.
+
+use crate::cli::{Cli, ReportFormat};
+use crate::clipboard;
+use crate::commands::translate::header_prefix;
+use crate::git::{get_authored_commits_since_in_path, get_repo_root_in_path};
+use crate::pipeline::{LONG_REPLY_NUM_PREDICT, summarize_diff};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// System prompt of the report: a narrative of one repository's commits for non-developers.
+const REPORT_SYSTEM_PROMPT: &str = r#"You write progress reports for stakeholders who do not read code.
+Given the commits of one repository grouped by type, write two to four short paragraphs:
+- first what was delivered (features), grouped by theme or scope, in plain language
+- then notable fixes and performance work
+- maintenance (refactoring, tests, CI, docs) in one sentence at most
+Give credit by name when a person clearly drove a piece of work.
+Do not list commits one by one. No headings, no bullet points, no code fences."#;
+
+/// User prompt template; `{{diff}}` is replaced with the grouped commits.
+const REPORT_USER_PROMPT: &str = "[COMMITS]\n{{diff}}\n\n[REPORT]";
+
+/// Order in which commit types are listed; other types follow alphabetically.
+const TYPE_ORDER: &[&str] = &[
+    "feat", "fix", "perf", "refactor", "docs", "test", "build", "ci", "chore", "style", "revert",
+];
+
+/// Type used for commits without a Conventional Commits header.
+const OTHER_TYPE: &str = "other";
+
+/// One commit of the report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportCommit {
+    pub author: String,
+    pub kind: String,
+    pub scope: Option<String>,
+    pub description: String,
+}
+
+impl ReportCommit {
+    /// Splits a subject line into type, scope and description.
+    pub fn parse(author: &str, subject: &str) -> Self {
+        let (kind, scope, description) = match header_prefix(subject) {
+            Some(prefix) => {
+                let name = prefix.trim_end_matches('!');
+                let (kind, scope) = match name.split_once('(') {
+                    Some((kind, scope)) => (kind, Some(scope.trim_end_matches(')').to_string())),
+                    None => (name, None),
+                };
+                (kind.to_string(), scope, &subject[prefix.len() + 2..])
+            }
+            None => (OTHER_TYPE.to_string(), None, subject),
+        };
+        Self {
+            author: author.to_string(),
+            kind,
+            scope,
+            description: description.trim().to_string(),
+        }
+    }
+}
+
+/// Generates the report for `repos` and prints it or writes it to `output`.
+pub async fn run(
+    cli: &Cli,
+    since: &str,
+    format: ReportFormat,
+    repos: &[PathBuf],
+    output: Option<&Path>,
+) -> Result<()> {
+    let mut config = cli.load_config()?;
+    config.system_prompt = REPORT_SYSTEM_PROMPT.to_string();
+    config.user_prompt = REPORT_USER_PROMPT.to_string();
+    config.ai_num_predict = config.ai_num_predict.max(LONG_REPLY_NUM_PREDICT);
+
+    let mut markdown = format!("# Changes since {}\n", since);
+    for repo in repos {
+        let root = get_repo_root_in_path(repo)
+            .with_context(|| format!("{} is not a git repository", repo.display()))?;
+        let name = root
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| root.display().to_string());
+
+        let commits: Vec<ReportCommit> = get_authored_commits_since_in_path(since, &root)?
+            .iter()
+            .map(|(author, subject)| ReportCommit::parse(author, subject))
+            .collect();
+        let narrative = if commits.is_empty() {
+            "No changes.".to_string()
+        } else {
+            summarize_diff(config.clone(), build_input(&commits)).await?
+        };
+        markdown.push_str(&render_section(&name, narrative.trim(), &commits));
+    }
+
+    let report = match format {
+        ReportFormat::Markdown => markdown,
+        ReportFormat::Html => to_html(&markdown, &format!("Changes since {}", since)),
+    };
+    match output {
+        Some(path) => {
+            std::fs::write(path, &report)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            println!("[OK] Report written to {}", path.display());
+        }
+        None => {
+            print!("{}", report);
+            clipboard::deliver(&report, cli.clipboard(&config).as_deref());
+        }
+    }
+    Ok(())
+}
+
+/// Lists the commits grouped by type for the prompt, e.g. `- [auth] add login (Alice)`.
+pub fn build_input(commits: &[ReportCommit]) -> String {
+    let mut input = String::new();
+    for (kind, count) in count_by(commits, |c| c.kind.clone(), sort_types) {
+        input.push_str(&format!("{} ({}):\n", kind, count));
+        for commit in commits.iter().filter(|c| c.kind == kind) {
+            let scope = commit
+                .scope
+                .as_ref()
+                .map(|s| format!("[{}] ", s))
+                .unwrap_or_default();
+            input.push_str(&format!(
+                "- {}{} ({})\n",
+                scope, commit.description, commit.author
+            ));
+        }
+        input.push('\n');
+    }
+    input.trim_end().to_string()
+}
+
+/// Renders one repository's section with the narrative and the commit counts.
+pub fn render_section(name: &str, narrative: &str, commits: &[ReportCommit]) -> String {
+    let mut section = format!("\n## {}\n\n{}\n", name, narrative);
+    if commits.is_empty() {
+        return section;
+    }
+
+    let types = count_by(commits, |c| c.kind.clone(), sort_types)
+        .into_iter()
+        .map(|(kind, count)| format!("{} {}", kind, count))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let authors = count_by(commits, |c| c.author.clone(), sort_by_count)
+        .into_iter()
+        .map(|(author, count)| format!("{} ({})", author, count))
+        .collect::<Vec<_>>()
+        .join(", ");
+    section.push_str(&format!(
+        "\n- **Commits:** {} ({})\n- **Contributors:** {}\n",
+        commits.len(),
+        types,
+        authors
+    ));
+    section
+}
+
+/// Counts commits per key and sorts the groups with `sort`.
+fn count_by<K, S>(commits: &[ReportCommit], key: K, sort: S) -> Vec<(String, usize)>
+where
+    K: Fn(&ReportCommit) -> String,
+    S: Fn(&mut [(String, usize)]),
+{
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for commit in commits {
+        let key = key(commit);
+        match counts.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((key, 1)),
+        }
+    }
+    sort(&mut counts);
+    counts
+}
+
+/// Sorts types by `TYPE_ORDER`, then other types alphabetically, with `other` last.
+fn sort_types(counts: &mut [(String, usize)]) {
+    counts.sort_by_key(|(kind, _)| {
+        let rank = match TYPE_ORDER.iter().position(|t| t == kind) {
+            Some(position) => position,
+            None if kind == OTHER_TYPE => usize::MAX,
+            None => TYPE_ORDER.len(),
+        };
+        (rank, kind.clone())
+    });
+}
+
+/// Sorts by count, highest first, then by name.
+fn sort_by_count(counts: &mut [(String, usize)]) {
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+}
+
+/// Converts the report's Markdown (headings, paragraphs, `- ` lists and `**bold**`)
+/// into a standalone HTML page.
+pub fn to_html(markdown: &str, title: &str) -> String {
+    let mut body = String::new();
+    let mut paragraph: Vec<String> = Vec::new();
+    let mut in_list = false;
+
+    let flush_paragraph = |body: &mut String, paragraph: &mut Vec<String>| {
+        if !paragraph.is_empty() {
+            body.push_str(&format!("<p>{}</p>\n", paragraph.join(" ")));
+            paragraph.clear();
+        }
+    };
+
+    for line in markdown.lines() {
+        let line = line.trim();
+        let item = line.strip_prefix("- ");
+        if in_list && item.is_none() {
+            body.push_str("</ul>\n");
+            in_list = false;
+        }
+
+        if let Some(item) = item {
+            flush_paragraph(&mut body, &mut paragraph);
+            if !in_list {
+                body.push_str("<ul>\n");
+                in_list = true;
+            }
+            body.push_str(&format!("<li>{}</li>\n", inline_html(item)));
+        } else if let Some(heading) = line.strip_prefix("## ") {
+            flush_paragraph(&mut body, &mut paragraph);
+            body.push_str(&format!("<h2>{}</h2>\n", inline_html(heading)));
+        } else if let Some(heading) = line.strip_prefix("# ") {
+            flush_paragraph(&mut body, &mut paragraph);
+            body.push_str(&format!("<h1>{}</h1>\n", inline_html(heading)));
+        } else if line.is_empty() {
+            flush_paragraph(&mut body, &mut paragraph);
+        } else {
+            paragraph.push(inline_html(line));
+        }
+    }
+    flush_paragraph(&mut body, &mut paragraph);
+    if in_list {
+        body.push_str("</ul>\n");
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(title),
+        body
+    )
+}
+
+/// Escapes text and turns `**bold**` spans into `<strong>`.
+fn inline_html(text: &str) -> String {
+    escape_html(text)
+        .split("**")
+        .enumerate()
+        .map(|(i, part)| match i % 2 {
+            1 => format!("<strong>{}</strong>", part),
+            _ => part.to_string(),
+        })
+        .collect()
+}
+
+/// Escapes the characters HTML treats specially.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commits() -> Vec<ReportCommit> {
+        [
+            ("Bob", "fix(api): handle empty body"),
+            ("Alice", "feat(auth): add login"),
+            ("Alice", "Update README"),
+            ("Alice", "feat: add dark mode"),
+            ("Bob", "wip(x): try things"),
+        ]
+        .iter()
+        .map(|(author, subject)| ReportCommit::parse(author, subject))
+        .collect()
+    }
+
+    #[test]
+    fn test_parse_table_driven() {
+        let cases = vec![
+            ("feat(auth): add login", "feat", Some("auth"), "add login"),
+            ("fix!: drop v1", "fix", None, "drop v1"),
+            ("Update README", "other", None, "Update README"),
+        ];
+        for (subject, kind, scope, description) in cases {
+            let commit = ReportCommit::parse("Alice", subject);
+            assert_eq!(commit.kind, kind, "subject: {}", subject);
+            assert_eq!(commit.scope.as_deref(), scope, "subject: {}", subject);
+            assert_eq!(commit.description, description, "subject: {}", subject);
+        }
+    }
+
+    #[test]
+    fn test_build_input_groups_by_type() {
+        assert_eq!(
+            build_input(&commits()),
+            "feat (2):\n- [auth] add login (Alice)\n- add dark mode (Alice)\n\n\
+             fix (1):\n- [api] handle empty body (Bob)\n\n\
+             wip (1):\n- [x] try things (Bob)\n\n\
+             other (1):\n- Update README (Alice)"
+        );
+    }
+
+    #[test]
+    fn test_render_section() {
+        assert_eq!(
+            render_section("web", "We shipped login.", &commits()),
+            "\n## web\n\nWe shipped login.\n\n\
+             - **Commits:** 5 (feat 2, fix 1, wip 1, other 1)\n\
+             - **Contributors:** Alice (3), Bob (2)\n"
+        );
+        assert_eq!(
+            render_section("api", "No changes.", &[]),
+            "\n## api\n\nNo changes.\n"
+        );
+    }
+
+    #[test]
+    fn test_to_html() {
+        let markdown = "# Changes since 1 week ago\n\n## web <app>\n\nWe shipped\nlogin & more.\n\n- **Commits:** 5\n- **Contributors:** Alice (3)\n";
+        let html = to_html(markdown, "Changes since 1 week ago");
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Changes since 1 week ago</title>"));
+        assert!(html.contains(
+            "<h1>Changes since 1 week ago</h1>\n<h2>web &lt;app&gt;</h2>\n<p>We shipped login &amp; more.</p>\n<ul>\n<li><strong>Commits:</strong> 5</li>\n<li><strong>Contributors:</strong> Alice (3)</li>\n</ul>\n</body>"
+        ));
+    }
+}
@@ -0,0 +1,41 @@
+//! `asum review` command.
+//!
+//! Asks the model for a code review of the staged changes instead of a
+//! commit message.
+
+use crate::cli::Cli;
+use crate::failure::Failure;
+use crate::git::get_git_diff;
+use crate::pipeline::{LONG_REPLY_NUM_PREDICT, summarize_diff};
+use anyhow::{Context, Result};
+
+/// System prompt of the code review: potential bugs, missing tests and style issues.
+const REVIEW_SYSTEM_PROMPT: &str = r#"You are a senior software engineer reviewing a change before it is committed.
+Review the diff and report, in this order:
+1. Potential bugs: logic errors, unhandled errors or edge cases, security problems.
+2. Missing tests: behavior that is changed or added without test coverage.
+3. Style issues: naming, readability, duplication, inconsistencies with the surrounding code.
+For each finding, name the file and line when possible, and keep it to one or two sentences.
+Use a markdown heading per category and "- " bullet points. Write "None found." under empty categories.
+Do not summarize the change and do not rewrite the code."#;
+
+/// User prompt template; `{{diff}}` is replaced with the staged changes.
+const REVIEW_USER_PROMPT: &str = "[STAGED DIFF]\n{{diff}}\n\n[REVIEW]";
+
+/// Reviews the staged changes and prints the findings.
+pub async fn run(cli: &Cli) -> Result<()> {
+    let mut config = cli.load_config()?;
+
+    let diff_text = get_git_diff(&config.pathspecs()).context("Failed to get git diff")?;
+    if diff_text.is_empty() {
+        return Err(Failure::NoChanges.error("No staged changes found in supported code files."));
+    }
+
+    config.system_prompt = REVIEW_SYSTEM_PROMPT.to_string();
+    config.user_prompt = REVIEW_USER_PROMPT.to_string();
+    config.ai_num_predict = config.ai_num_predict.max(LONG_REPLY_NUM_PREDICT);
+
+    let review = summarize_diff(config, diff_text).await?;
+    println!("{}", review);
+    Ok(())
+}
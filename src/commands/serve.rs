@@ -0,0 +1,430 @@
+//! `asum serve` command.
+//!
+//! Runs a small HTTP API so editors and other tools can reuse one warm process,
+//! and the provider's connection pool, instead of starting the CLI per request:
+//!
+//! - `GET /health` answers `{"status":"ok"}`.
+//! - `POST /summarize` takes a diff as the request body (plain text, or JSON
+//!   `{"diff": "..."}`) and answers `{"message": "..."}`.
+//!
+//! Errors are answered as `{"error": "..."}`. Connections are closed after each response.
+//! Requests carrying an `Origin` header come from a web page, which could otherwise
+//! spend the user's provider quota from any site they visit, and are refused.
+
+use crate::cli::Cli;
+use crate::config::AsumConfig;
+use crate::pipeline::{commit_message_prompts, summarize_with};
+use crate::summarizer::{Summarizer, get_summarizer};
+use anyhow::{Context, Result, anyhow};
+use serde_json::{Value, json};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+/// Largest request body accepted, in bytes.
+const MAX_BODY_BYTES: usize = 10 * 1024 * 1024;
+
+/// Largest number of header lines accepted.
+const MAX_HEADERS: usize = 100;
+
+/// Largest request line or header line accepted, in bytes.
+const MAX_LINE_BYTES: usize = 8 * 1024;
+
+/// Time a client has to send its whole request.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A parsed HTTP request.
+#[derive(Debug, PartialEq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub body: String,
+}
+
+/// An HTTP response with a JSON body.
+#[derive(Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.into() }),
+        }
+    }
+}
+
+/// Serves the API on `listen` (e.g. `127.0.0.1:7821`) until the process is stopped.
+pub async fn run(cli: &Cli, listen: &str) -> Result<()> {
+    let mut config = cli.load_config()?;
+    if config.confirm_send {
+        return Err(anyhow!(
+            "confirm_send is on, but asum serve cannot ask before each request. \
+             Turn it off in the configuration to serve."
+        ));
+    }
+    commit_message_prompts(&mut config);
+    let config = Arc::new(config);
+    let summarizer: Arc<dyn Summarizer> = get_summarizer(config.as_ref().clone()).await?.into();
+    let listener = TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("Failed to listen on {}", listen))?;
+    info!("Listening on http://{}", listener.local_addr()?);
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let config = Arc::clone(&config);
+        let summarizer = Arc::clone(&summarizer);
+        tokio::spawn(async move {
+            if let Err(e) = serve_connection(stream, summarizer.as_ref(), &config).await {
+                warn!("Connection from {} failed: {:#}", peer, e);
+            }
+        });
+    }
+}
+
+/// Reads one request from `stream`, answers it and closes the connection.
+pub async fn serve_connection<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: S,
+    summarizer: &dyn Summarizer,
+    config: &AsumConfig,
+) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let response = match read_request(&mut reader, READ_TIMEOUT).await {
+        Ok(request) => {
+            info!("{} {}", request.method, request.path);
+            route(request, summarizer, config).await
+        }
+        Err(response) => response,
+    };
+    write_response(reader.get_mut(), &response).await
+}
+
+/// Dispatches a request to its handler.
+pub async fn route(request: Request, summarizer: &dyn Summarizer, config: &AsumConfig) -> Response {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/health") => Response {
+            status: 200,
+            body: json!({ "status": "ok" }),
+        },
+        ("POST", "/summarize") => {
+            let diff = extract_diff(&request.body);
+            if diff.trim().is_empty() {
+                return Response::error(400, "The request body must contain a diff.");
+            }
+            match summarize_with(summarizer, config, Path::new("."), diff).await {
+                Ok(message) => Response {
+                    status: 200,
+                    body: json!({ "message": message }),
+                },
+                Err(e) => Response::error(502, format!("{:#}", e)),
+            }
+        }
+        (_, "/health") | (_, "/summarize") => Response::error(405, "Method not allowed."),
+        _ => Response::error(404, "Not found."),
+    }
+}
+
+/// Takes the diff from a JSON body (`{"diff": "..."}`) or uses the body as is.
+fn extract_diff(body: &str) -> String {
+    serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|value| value.get("diff")?.as_str().map(String::from))
+        .unwrap_or_else(|| body.to_string())
+}
+
+/// Reads the request line, headers and body within `timeout`. Malformed, oversized,
+/// slow and cross-origin requests are answered with the error response to send.
+async fn read_request<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+    timeout: Duration,
+) -> std::result::Result<Request, Response> {
+    tokio::time::timeout(timeout, read_request_parts(reader))
+        .await
+        .unwrap_or_else(|_| Err(Response::error(408, "The request was not sent in time.")))
+}
+
+/// Reads one line of at most `MAX_LINE_BYTES` into `line`, answering longer lines
+/// with `too_long`.
+async fn read_line<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+    line: &mut String,
+    too_long: Response,
+) -> std::result::Result<(), Response> {
+    line.clear();
+    (&mut *reader)
+        .take(MAX_LINE_BYTES as u64 + 1)
+        .read_line(line)
+        .await
+        .map_err(|_| Response::error(400, "Could not read the request."))?;
+    if line.len() > MAX_LINE_BYTES {
+        return Err(too_long);
+    }
+    Ok(())
+}
+
+/// Reads a request for `read_request`, without the time limit.
+async fn read_request_parts<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+) -> std::result::Result<Request, Response> {
+    let bad_request = |message: &str| Response::error(400, message);
+    let headers_too_large = || Response::error(431, "The request headers are too large.");
+
+    let mut line = String::new();
+    read_line(
+        reader,
+        &mut line,
+        Response::error(414, "The request line is too long."),
+    )
+    .await?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(bad_request("Malformed request line."));
+    };
+    let method = method.to_string();
+    let path = target.split('?').next().unwrap_or(target).to_string();
+
+    let mut content_length = 0;
+    let mut headers = 0;
+    loop {
+        read_line(reader, &mut line, headers_too_large()).await?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        headers += 1;
+        if headers > MAX_HEADERS {
+            return Err(headers_too_large());
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            continue;
+        };
+        let name = name.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value
+                .trim()
+                .parse()
+                .map_err(|_| bad_request("Invalid Content-Length."))?;
+        } else if name.eq_ignore_ascii_case("origin") {
+            return Err(Response::error(
+                403,
+                "Requests from web pages are not accepted.",
+            ));
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return Err(Response::error(413, "The request body is too large."));
+    }
+
+    let mut body = vec![0; content_length];
+    reader
+        .read_exact(&mut body)
+        .await
+        .map_err(|_| bad_request("The request body is shorter than Content-Length."))?;
+    Ok(Request {
+        method,
+        path,
+        body: String::from_utf8_lossy(&body).to_string(),
+    })
+}
+
+/// Writes `response` as an HTTP/1.1 response that closes the connection.
+async fn write_response<W: AsyncWrite + Unpin>(writer: &mut W, response: &Response) -> Result<()> {
+    let body = response.body.to_string();
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason(response.status),
+        body.len()
+    );
+    writer.write_all(head.as_bytes()).await?;
+    writer.write_all(body.as_bytes()).await?;
+    writer.shutdown().await?;
+    Ok(())
+}
+
+/// Reason phrase for the status codes the API uses.
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        414 => "URI Too Long",
+        431 => "Request Header Fields Too Large",
+        502 => "Bad Gateway",
+        _ => "Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::summarizer::{Completion, MockSummarizer};
+
+    fn mock() -> MockSummarizer {
+        let mut mock = MockSummarizer::new();
+        mock.expect_complete().returning(|diff| match diff {
+            "fail" => Err(anyhow::anyhow!("provider down")),
+            _ => Ok(Completion {
+                message: format!("feat: {}", diff.len()),
+                usage: None,
+            }),
+        });
+        mock
+    }
+
+    async fn exchange(raw: &str) -> String {
+        let (mut client, server) = tokio::io::duplex(64 * 1024);
+        client.write_all(raw.as_bytes()).await.unwrap();
+        let config = AsumConfig::from_defaults().unwrap();
+        serve_connection(server, &mock(), &config).await.unwrap();
+
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_route_table_driven() {
+        let config = AsumConfig::from_defaults().unwrap();
+        let cases = vec![
+            ("GET", "/health", "", 200, json!({ "status": "ok" })),
+            (
+                "POST",
+                "/summarize",
+                "+a",
+                200,
+                json!({ "message": "feat: 2" }),
+            ),
+            (
+                "POST",
+                "/summarize",
+                r#"{"diff": "+abc"}"#,
+                200,
+                json!({ "message": "feat: 4" }),
+            ),
+            (
+                "POST",
+                "/summarize",
+                " \n",
+                400,
+                json!({ "error": "The request body must contain a diff." }),
+            ),
+            (
+                "POST",
+                "/summarize",
+                "fail",
+                502,
+                json!({ "error": "provider down" }),
+            ),
+            (
+                "GET",
+                "/summarize",
+                "",
+                405,
+                json!({ "error": "Method not allowed." }),
+            ),
+            ("GET", "/nope", "", 404, json!({ "error": "Not found." })),
+        ];
+
+        for (method, path, body, status, expected) in cases {
+            let request = Request {
+                method: method.to_string(),
+                path: path.to_string(),
+                body: body.to_string(),
+            };
+            let response = route(request, &mock(), &config).await;
+            assert_eq!(response.status, status, "{} {}", method, path);
+            assert_eq!(response.body, expected, "{} {}", method, path);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_serve_connection_round_trip() {
+        let response =
+            exchange("POST /summarize?x=1 HTTP/1.1\r\nHost: a\r\ncontent-length: 3\r\n\r\n+ab")
+                .await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Length: 21\r\n"));
+        assert!(response.ends_with("\r\n\r\n{\"message\":\"feat: 3\"}"));
+
+        let response = exchange("GET /health HTTP/1.1\r\n\r\n").await;
+        assert!(response.ends_with("{\"status\":\"ok\"}"));
+    }
+
+    #[tokio::test]
+    async fn test_serve_connection_rejects_bad_requests() {
+        let long_target = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE_BYTES));
+        let long_header = format!(
+            "GET /health HTTP/1.1\r\nX: {}\r\n\r\n",
+            "a".repeat(MAX_LINE_BYTES)
+        );
+        let many_headers = format!(
+            "GET /health HTTP/1.1\r\n{}\r\n",
+            "X: a\r\n".repeat(MAX_HEADERS + 1)
+        );
+        let cases = vec![
+            ("\r\n", "HTTP/1.1 400 Bad Request"),
+            (
+                "POST /summarize HTTP/1.1\r\nContent-Length: abc\r\n\r\n",
+                "HTTP/1.1 400 Bad Request",
+            ),
+            (
+                "POST /summarize HTTP/1.1\r\nContent-Length: 99999999\r\n\r\n",
+                "HTTP/1.1 413 Payload Too Large",
+            ),
+            (
+                "POST /summarize HTTP/1.1\r\nContent-Length: 10\r\n\r\nshort",
+                "HTTP/1.1 400 Bad Request",
+            ),
+            (
+                "POST /summarize HTTP/1.1\r\nOrigin: https://example.com\r\n\
+                 Content-Length: 2\r\n\r\n+a",
+                "HTTP/1.1 403 Forbidden",
+            ),
+            (&long_target, "HTTP/1.1 414 URI Too Long"),
+            (&long_header, "HTTP/1.1 431 Request Header Fields Too Large"),
+            (
+                &many_headers,
+                "HTTP/1.1 431 Request Header Fields Too Large",
+            ),
+        ];
+        for (raw, expected) in cases {
+            let (mut client, server) = tokio::io::duplex(64 * 1024);
+            client.write_all(raw.as_bytes()).await.unwrap();
+            // Closing the write half lets the server see the end of a short body
+            client.shutdown().await.unwrap();
+            let config = AsumConfig::from_defaults().unwrap();
+            serve_connection(server, &mock(), &config).await.unwrap();
+
+            let mut response = String::new();
+            client.read_to_string(&mut response).await.unwrap();
+            assert!(response.starts_with(expected), "request: {:?}", raw);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_request_times_out() {
+        // The client keeps the connection open without finishing its headers
+        let (mut client, server) = tokio::io::duplex(1024);
+        client
+            .write_all(b"POST /summarize HTTP/1.1\r\n")
+            .await
+            .unwrap();
+        let mut reader = BufReader::new(server);
+        let response = read_request(&mut reader, Duration::from_millis(50))
+            .await
+            .unwrap_err();
+        assert_eq!(response.status, 408);
+    }
+}
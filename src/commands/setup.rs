@@ -0,0 +1,330 @@
+//! `asum setup` command.
+//!
+//! Onboards a repository in one interactive flow: installs the commit hook, looks for
+//! commitlint and a commit template, writes a repository `asum.toml` with scopes
+//! inferred from the directory layout, and verifies the result.
+
+use crate::commands::{hook, init, verify};
+use crate::git::{get_config_value_in_path, get_repo_root_in_path, get_tracked_files_in_path};
+use crate::interactive::{ask, confirm};
+use crate::scope::WORKSPACE_DIRS;
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// Top-level directories that hold a kind of file rather than a part of the project.
+const GENERIC_DIRS: &[&str] = &[
+    "src",
+    "lib",
+    "test",
+    "tests",
+    "spec",
+    "docs",
+    "doc",
+    "scripts",
+    "vendor",
+    "third_party",
+    "examples",
+    "benches",
+    "assets",
+    "bin",
+    "build",
+    "dist",
+    "target",
+    "node_modules",
+];
+
+/// Largest number of inferred scopes; the most used directories are kept.
+const MAX_SCOPES: usize = 12;
+
+/// Configuration files of commitlint, in the order commitlint looks for them.
+const COMMITLINT_FILES: &[&str] = &[
+    ".commitlintrc",
+    ".commitlintrc.json",
+    ".commitlintrc.yaml",
+    ".commitlintrc.yml",
+    ".commitlintrc.js",
+    ".commitlintrc.cjs",
+    ".commitlintrc.mjs",
+    ".commitlintrc.ts",
+    "commitlint.config.js",
+    "commitlint.config.cjs",
+    "commitlint.config.mjs",
+    "commitlint.config.ts",
+];
+
+/// Runs the onboarding flow on stdin/stdout for the repository in the current
+/// directory, then verifies the configuration, including a request to the provider.
+pub async fn run(force: bool) -> Result<()> {
+    let root = get_repo_root_in_path(Path::new("."))
+        .map_err(|_| anyhow!("asum setup must run inside a git repository."))?;
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock();
+    let mut output = std::io::stdout();
+
+    let path = setup(&mut input, &mut output, &root, force)?;
+    writeln!(output, "\n[4/4] Checking the configuration")?;
+    verify::verify_file(&path, true).await?;
+    writeln!(
+        output,
+        "\n[OK] {} is set up. Stage some changes and run 'git commit' or 'asum'.",
+        root.display()
+    )?;
+    Ok(())
+}
+
+/// Installs the hook, reports the commit conventions found and writes the repository
+/// configuration of the repository at `root`. Existing hooks and configuration files
+/// are kept unless `force` is set. Returns the path of the configuration file.
+pub fn setup<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    root: &Path,
+    force: bool,
+) -> Result<PathBuf> {
+    writeln!(
+        output,
+        "ASUM repository setup - press Enter to accept the [default]."
+    )?;
+
+    writeln!(output, "\n[1/4] Commit hook")?;
+    match hook::status(root)? {
+        hook::HookStatus::Installed => writeln!(output, "[OK] The asum hook is installed.")?,
+        hook::HookStatus::Foreign if !force => writeln!(
+            output,
+            "A different prepare-commit-msg hook exists; leaving it alone (use --force to replace it)."
+        )?,
+        _ => {
+            if confirm(
+                input,
+                output,
+                "Install the prepare-commit-msg hook, so 'git commit' starts with a generated message?",
+                true,
+            )? {
+                let path = hook::install(root, force)?;
+                writeln!(output, "[OK] Installed the hook at {}", path.display())?;
+            }
+        }
+    }
+
+    writeln!(output, "\n[2/4] Commit conventions")?;
+    let commitlint = find_commitlint(root);
+    match &commitlint {
+        Some(file) => writeln!(output, "Found a commitlint configuration in {}.", file)?,
+        None => writeln!(output, "No commitlint configuration found.")?,
+    }
+    if let Some(template) = get_config_value_in_path("commit.template", root) {
+        writeln!(
+            output,
+            "Found the commit template {}. The hook leaves commits started from a template \
+             alone; use 'asum commit' for those.",
+            template
+        )?;
+    }
+
+    writeln!(output, "\n[3/4] Repository configuration")?;
+    let path = root.join("asum.toml");
+    if path.exists() && !force {
+        writeln!(
+            output,
+            "[OK] Keeping the existing {} (use --force to write a new one).",
+            path.display()
+        )?;
+        return Ok(path);
+    }
+    let mut settings = init::ask_provider(input, output)?;
+    let inferred = infer_scopes(&get_tracked_files_in_path(root).unwrap_or_default());
+    let scopes = ask(
+        input,
+        output,
+        "Scopes, comma-separated (empty for none)",
+        Some(&inferred.join(", ")),
+    )?;
+    settings.scopes = scopes
+        .split(',')
+        .map(str::trim)
+        .filter(|scope| !scope.is_empty())
+        .map(String::from)
+        .collect();
+    settings.strict_format = commitlint.is_some()
+        && confirm(
+            input,
+            output,
+            "Hold messages to the strict format commitlint expects (strict_format)?",
+            true,
+        )?;
+    init::write_config(&path, &settings, true)?;
+    writeln!(output, "[OK] Configuration written to {}", path.display())?;
+    if settings
+        .api_key
+        .as_deref()
+        .is_some_and(|key| !key.is_empty())
+    {
+        writeln!(
+            output,
+            "{} holds your API key; keep it out of version control, or leave the key empty \
+             and set GEMINI_API_KEY instead.",
+            path.display()
+        )?;
+    }
+    Ok(path)
+}
+
+/// Infers scopes from the tracked `files`: the packages of a monorepo (`crates/cli`
+/// gives `cli`), the modules under `src`, and other top-level directories. At most
+/// `MAX_SCOPES` of the most used are returned, sorted by name.
+pub fn infer_scopes(files: &[String]) -> Vec<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for file in files {
+        let parts: Vec<&str> = file.split('/').collect();
+        let scope = match parts.as_slice() {
+            [top, name, _, ..] if WORKSPACE_DIRS.contains(top) => name,
+            ["src", name, _, ..] => name,
+            [top, _, ..] if !top.starts_with('.') && !GENERIC_DIRS.contains(top) => top,
+            _ => continue,
+        };
+        *counts.entry(scope).or_default() += 1;
+    }
+    let mut scopes: Vec<(&str, usize)> = counts.into_iter().collect();
+    scopes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    let mut scopes: Vec<String> = scopes
+        .into_iter()
+        .take(MAX_SCOPES)
+        .map(|(scope, _)| scope.to_string())
+        .collect();
+    scopes.sort();
+    scopes
+}
+
+/// Returns the file holding the commitlint configuration of the repository at `root`:
+/// one of `COMMITLINT_FILES`, or `package.json` with a `commitlint` key.
+pub fn find_commitlint(root: &Path) -> Option<String> {
+    if let Some(file) = COMMITLINT_FILES
+        .iter()
+        .find(|file| root.join(file).is_file())
+    {
+        return Some(file.to_string());
+    }
+    let package = std::fs::read_to_string(root.join("package.json")).ok()?;
+    let package: serde_json::Value = serde_json::from_str(&package).ok()?;
+    package
+        .get("commitlint")
+        .map(|_| "package.json".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "ollama")]
+    use std::process::Command;
+
+    #[test]
+    fn test_infer_scopes_table_driven() {
+        let cases = [
+            (
+                vec![
+                    "crates/cli/src/main.rs",
+                    "crates/core/src/lib.rs",
+                    "crates/core/Cargo.toml",
+                    "Cargo.toml",
+                ],
+                vec!["cli", "core"],
+            ),
+            (
+                vec![
+                    "src/parser/mod.rs",
+                    "src/render/html.rs",
+                    "src/main.rs",
+                    "tests/parse.rs",
+                    "docs/guide.md",
+                ],
+                vec!["parser", "render"],
+            ),
+            (
+                vec!["api/server.go", "web/app.ts", ".github/workflows/ci.yml"],
+                vec!["api", "web"],
+            ),
+            (vec!["README.md", "main.go"], vec![]),
+        ];
+        for (files, expected) in cases {
+            let files: Vec<String> = files.into_iter().map(String::from).collect();
+            assert_eq!(infer_scopes(&files), expected, "{:?}", files);
+        }
+    }
+
+    #[test]
+    fn test_infer_scopes_keeps_the_most_used() {
+        let mut files: Vec<String> = (0..20)
+            .map(|i| format!("packages/p{:02}/index.js", i))
+            .collect();
+        files.push("packages/p19/other.js".to_string());
+        let scopes = infer_scopes(&files);
+        assert_eq!(scopes.len(), MAX_SCOPES);
+        assert!(scopes.contains(&"p19".to_string()));
+        assert_eq!(scopes.first().map(String::as_str), Some("p00"));
+    }
+
+    #[test]
+    fn test_find_commitlint() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(find_commitlint(dir.path()), None);
+
+        std::fs::write(dir.path().join("package.json"), r#"{"name": "app"}"#).unwrap();
+        assert_eq!(find_commitlint(dir.path()), None);
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"commitlint": {"extends": ["@commitlint/config-conventional"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(find_commitlint(dir.path()).as_deref(), Some("package.json"));
+
+        std::fs::write(dir.path().join("commitlint.config.js"), "").unwrap();
+        assert_eq!(
+            find_commitlint(dir.path()).as_deref(),
+            Some("commitlint.config.js")
+        );
+    }
+
+    #[cfg(feature = "ollama")]
+    #[test]
+    fn test_setup_installs_the_hook_and_writes_the_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        Command::new("git")
+            .arg("init")
+            .current_dir(root)
+            .output()
+            .unwrap();
+        for file in ["crates/cli/src/main.rs", "crates/core/src/lib.rs"] {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "fn f() {}\n").unwrap();
+        }
+        std::fs::write(root.join(".commitlintrc.json"), "{}").unwrap();
+        Command::new("git")
+            .args(["add", "."])
+            .current_dir(root)
+            .output()
+            .unwrap();
+
+        // Hook, provider, model, URL, scopes and strict format all use their defaults
+        let mut input = std::io::Cursor::new("\n\n\n\n\n\n");
+        let mut output = Vec::new();
+        let path = setup(&mut input, &mut output, root, false).unwrap();
+
+        assert_eq!(hook::status(root).unwrap(), hook::HookStatus::Installed);
+        let config = crate::config::AsumConfig::load_from(&path).unwrap();
+        assert_eq!(config.scopes, vec!["cli", "core"]);
+        assert!(config.strict_format);
+        let printed = String::from_utf8(output).unwrap();
+        assert!(printed.contains("Found a commitlint configuration in .commitlintrc.json."));
+
+        // A second run keeps what is there
+        let mut output = Vec::new();
+        setup(&mut std::io::Cursor::new(""), &mut output, root, false).unwrap();
+        let printed = String::from_utf8(output).unwrap();
+        assert!(printed.contains("[OK] The asum hook is installed."));
+        assert!(printed.contains("[OK] Keeping the existing"));
+    }
+}
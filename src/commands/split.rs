@@ -0,0 +1,256 @@
+//! `asum split` command.
+//!
+//! Proposes splitting the staged changes into several commits. Files are grouped by
+//! the area they belong to: a monorepo package or a `[scopes]` path, a module under
+//! `src/`, or another top-level directory, with the files in the repository root as
+//! a group of their own. Each group gets its own generated message. With `--commit`
+//! the commits are created one after another from the staged content, asking before
+//! each unless `--yes` is given.
+
+use crate::cli::Cli;
+use crate::commands::commit::check_message;
+use crate::config::AsumConfig;
+use crate::diff_source::{GitStaged, file_sections};
+use crate::git::{
+    commit_staged_paths_in_path, get_merge_in_progress_in_path, get_staged_files_in_path,
+};
+use crate::interactive::confirm;
+use crate::pipeline::{adapt_prompts, read_diff, summarize_diff};
+use crate::scope;
+use anyhow::{Result, anyhow};
+use std::path::Path;
+
+/// Most commits proposed; the smallest groups beyond it are merged into one.
+const MAX_GROUPS: usize = 6;
+
+/// Name of the group of files in the repository root.
+const ROOT_GROUP: &str = "root files";
+
+/// Name of the group the smallest groups are merged into.
+const OTHER_GROUP: &str = "other changes";
+
+/// Staged files that belong to one proposed commit.
+#[derive(Debug, PartialEq)]
+pub struct Group {
+    /// Area the files belong to, e.g. `auth` or `src/parser`.
+    pub name: String,
+    /// Paths to commit, including the old paths of renamed files.
+    pub paths: Vec<String>,
+    /// Diff of the files.
+    pub diff: String,
+}
+
+/// Proposes commits for the staged changes and prints them, or creates them when
+/// `commit` is set.
+pub async fn run(cli: &Cli, commit: bool, yes: bool) -> Result<()> {
+    let config = cli.load_config()?;
+    let repo = Path::new(".");
+    if get_merge_in_progress_in_path(0, repo)?.is_some() {
+        return Err(anyhow!(
+            "A merge is in progress; a merge has to be committed as a whole."
+        ));
+    }
+    let diff = read_diff(&config, &GitStaged::current(), repo)?;
+    let groups = group(&config, &diff);
+    if groups.len() < 2 {
+        println!("The staged changes belong together; commit them with 'asum commit'.");
+        return Ok(());
+    }
+
+    let mut messages = Vec::new();
+    for (i, group) in groups.iter().enumerate() {
+        let mut group_config = config.clone();
+        adapt_prompts(&mut group_config, repo, &group.diff).await;
+        let message = summarize_diff(group_config, group.diff.clone()).await?;
+        println!(
+            "[{}/{}] {} ({})\n\n{}\n",
+            i + 1,
+            groups.len(),
+            group.name,
+            group.paths.join(", "),
+            cli.format_message(&message)
+        );
+        messages.push(message);
+    }
+    if !commit {
+        println!("Run 'asum split --commit' to create these commits.");
+        return Ok(());
+    }
+
+    let stdin = std::io::stdin();
+    let mut created = 0;
+    for (i, (group, message)) in groups.iter().zip(&messages).enumerate() {
+        let question = format!(
+            "Create commit {} of {} ({})?",
+            i + 1,
+            groups.len(),
+            group.name
+        );
+        if !yes && !confirm(&mut stdin.lock(), &mut std::io::stdout(), &question, true)? {
+            continue;
+        }
+        check_message(message, yes)?;
+        commit_staged_paths_in_path(&group.paths, message, repo)?;
+        created += 1;
+    }
+    println!("[OK] Created {} of {} commits.", created, groups.len());
+    if !get_staged_files_in_path(".")?.is_empty() {
+        println!("Some changes are still staged; see 'git status'.");
+    }
+    Ok(())
+}
+
+/// Groups the file sections of `diff` by area, in the order the areas first appear.
+/// Beyond `MAX_GROUPS`, the groups with the fewest changed lines are merged into one.
+pub fn group(config: &AsumConfig, diff: &str) -> Vec<Group> {
+    let mut groups: Vec<Group> = Vec::new();
+    for (path, section) in file_sections(diff) {
+        let name = area(config, path);
+        let index = match groups.iter().position(|group| group.name == name) {
+            Some(index) => index,
+            None => {
+                groups.push(Group {
+                    name,
+                    paths: Vec::new(),
+                    diff: String::new(),
+                });
+                groups.len() - 1
+            }
+        };
+        let group = &mut groups[index];
+        group.paths.extend(renamed_from(section).map(String::from));
+        group.paths.push(path.to_string());
+        group.diff.push_str(section);
+    }
+
+    if groups.len() > MAX_GROUPS {
+        let mut by_size: Vec<usize> = (0..groups.len()).collect();
+        by_size.sort_by_key(|&i| std::cmp::Reverse(changed_lines(&groups[i].diff)));
+        let small = by_size.split_off(MAX_GROUPS - 1);
+        let mut other = Group {
+            name: OTHER_GROUP.to_string(),
+            paths: Vec::new(),
+            diff: String::new(),
+        };
+        let mut kept = Vec::new();
+        for (i, group) in groups.into_iter().enumerate() {
+            if small.contains(&i) {
+                other.paths.extend(group.paths);
+                other.diff.push_str(&group.diff);
+            } else {
+                kept.push(group);
+            }
+        }
+        kept.push(other);
+        groups = kept;
+    }
+    groups
+}
+
+/// Area of a changed file: its scope (see `scope::scope_of`), its module under
+/// `src/`, its top-level directory, or `ROOT_GROUP` for files in the root.
+fn area(config: &AsumConfig, path: &str) -> String {
+    if let Some(scope) = scope::scope_of(path, &config.scope_paths) {
+        return scope;
+    }
+    match path.split('/').collect::<Vec<_>>().as_slice() {
+        ["src", module, _, ..] => format!("src/{}", module),
+        [top, _, ..] => top.to_string(),
+        _ => ROOT_GROUP.to_string(),
+    }
+}
+
+/// The old path of a renamed file section, which has to be committed with it.
+fn renamed_from(section: &str) -> Option<&str> {
+    section
+        .lines()
+        .take_while(|line| !line.starts_with("@@"))
+        .find_map(|line| line.strip_prefix("rename from "))
+}
+
+/// Number of added and removed lines in `diff`.
+fn changed_lines(diff: &str) -> usize {
+    diff.lines()
+        .filter(|line| {
+            (line.starts_with('+') && !line.starts_with("+++"))
+                || (line.starts_with('-') && !line.starts_with("---"))
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(path: &str, lines: usize) -> String {
+        format!(
+            "diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n@@ -1 +1 @@\n{}",
+            "+x\n".repeat(lines)
+        )
+    }
+
+    fn names(groups: &[Group]) -> Vec<&str> {
+        groups.iter().map(|group| group.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_group_table_driven() {
+        let mut config = AsumConfig::from_defaults().unwrap();
+        config.scope_paths = vec![("apps/web-client".to_string(), "web".to_string())];
+        let cases = [
+            (
+                vec!["packages/auth/a.ts", "README.md", "packages/auth/b.ts"],
+                vec!["auth", ROOT_GROUP],
+            ),
+            (
+                vec!["src/parser/mod.rs", "src/main.rs", "src/parser/lex.rs"],
+                vec!["src/parser", "src"],
+            ),
+            (
+                vec!["apps/web-client/app.tsx", "docs/guide.md", "Cargo.toml"],
+                vec!["web", "docs", ROOT_GROUP],
+            ),
+            (vec!["src/lib.rs"], vec!["src"]),
+        ];
+        for (paths, expected) in cases {
+            let diff: String = paths.iter().map(|path| section(path, 1)).collect();
+            assert_eq!(names(&group(&config, &diff)), expected, "{:?}", paths);
+        }
+    }
+
+    #[test]
+    fn test_group_keeps_diffs_and_rename_sources() {
+        let config = AsumConfig::from_defaults().unwrap();
+        let rename = "diff --git a/docs/old.md b/docs/new.md\nsimilarity index 100%\n\
+                      rename from docs/old.md\nrename to docs/new.md\n";
+        let diff = format!(
+            "{}{}{}",
+            section("src/a/x.rs", 1),
+            rename,
+            section("src/a/y.rs", 2)
+        );
+        let groups = group(&config, &diff);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].paths, vec!["src/a/x.rs", "src/a/y.rs"]);
+        assert_eq!(
+            groups[0].diff,
+            format!("{}{}", section("src/a/x.rs", 1), section("src/a/y.rs", 2))
+        );
+        assert_eq!(groups[1].paths, vec!["docs/old.md", "docs/new.md"]);
+        assert_eq!(groups[1].diff, rename);
+    }
+
+    #[test]
+    fn test_group_merges_the_smallest_beyond_max_groups() {
+        let config = AsumConfig::from_defaults().unwrap();
+        let diff: String = (0..MAX_GROUPS + 2)
+            .map(|i| section(&format!("dir{}/f.rs", i), i + 1))
+            .collect();
+        let groups = group(&config, &diff);
+        assert_eq!(groups.len(), MAX_GROUPS);
+        assert_eq!(groups[0].name, "dir3");
+        let other = groups.last().unwrap();
+        assert_eq!(other.name, OTHER_GROUP);
+        assert_eq!(other.paths, vec!["dir0/f.rs", "dir1/f.rs", "dir2/f.rs"]);
+    }
+}
@@ -0,0 +1,90 @@
+//! `asum squash` command.
+//!
+//! Proposes a single commit message for squashing a range of commits, from the
+//! combined diff and the individual messages of the range.
+
+use crate::cli::Cli;
+use crate::clipboard;
+use crate::git::{get_range_diff_in_path, get_range_messages_in_path};
+use crate::pipeline::summarize_diff;
+use anyhow::{Result, anyhow};
+use std::path::Path;
+
+/// Generates the squashed commit message for `range` and prints it.
+pub async fn run(cli: &Cli, range: &str) -> Result<()> {
+    let config = cli.load_config()?;
+    let sink = cli.clipboard(&config);
+    let repo = Path::new(".");
+    let range = normalize_range(range);
+
+    let messages = get_range_messages_in_path(&range, repo)?;
+    if messages.is_empty() {
+        return Err(anyhow!("No commits found in {}.", range));
+    }
+    let diff = get_range_diff_in_path(&range, &config.pathspecs(), repo)?;
+
+    // The configured prompts still apply, so the message follows the team's rules
+    let message = summarize_diff(config, build_input(&messages, &diff)).await?;
+    println!("{}", cli.format_message(&message));
+    clipboard::deliver(&message, sink.as_deref());
+    Ok(())
+}
+
+/// Turns a single revision into `<rev>..HEAD`; ranges are kept as they are.
+pub fn normalize_range(range: &str) -> String {
+    if range.contains("..") {
+        range.to_string()
+    } else {
+        format!("{}..HEAD", range)
+    }
+}
+
+/// Combines the original messages and the combined diff into the model input.
+pub fn build_input(messages: &[String], diff: &str) -> String {
+    let mut input = String::from(
+        "These commits are being squashed into one. Write a single commit message \
+         that covers all of them.\n\nOriginal commit messages (oldest first):\n",
+    );
+    for message in messages {
+        let mut lines = message.lines();
+        input.push_str(&format!("- {}\n", lines.next().unwrap_or_default()));
+        for line in lines.filter(|l| !l.trim().is_empty()) {
+            input.push_str(&format!("  {}\n", line));
+        }
+    }
+    input.push_str("\nCombined diff:\n");
+    input.push_str(diff);
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_range_table_driven() {
+        let cases = vec![
+            ("HEAD~5..HEAD", "HEAD~5..HEAD"),
+            ("main...feature", "main...feature"),
+            ("HEAD~3", "HEAD~3..HEAD"),
+            ("abc123", "abc123..HEAD"),
+        ];
+        for (range, expected) in cases {
+            assert_eq!(normalize_range(range), expected, "range: {}", range);
+        }
+    }
+
+    #[test]
+    fn test_build_input() {
+        let messages = vec![
+            "feat: add login".to_string(),
+            "fix: handle empty password\n\n- return an error".to_string(),
+        ];
+        let input = build_input(&messages, "diff --git a/x b/x\n");
+        assert!(
+            input
+                .contains("- feat: add login\n- fix: handle empty password\n  - return an error\n")
+        );
+        assert!(input.ends_with("Combined diff:\ndiff --git a/x b/x\n"));
+    }
+}
@@ -0,0 +1,68 @@
+//! `asum squash-prep` command.
+//!
+//! Proposes the message for squash-merging the current branch: the commits since it
+//! left the default branch are summarized like `asum squash` does, and their
+//! original subjects are listed in a footer.
+
+use crate::cli::Cli;
+use crate::clipboard;
+use crate::commands::squash::build_input;
+use crate::git::{get_branch_diff_in_path, get_default_branch_in_path, get_range_messages_in_path};
+use crate::pipeline::summarize_diff;
+use anyhow::{Result, anyhow};
+use std::path::Path;
+
+/// Generates the squash message for the commits of the current branch that are not
+/// on `base` (the default branch when `None`) and prints it.
+pub async fn run(cli: &Cli, base: Option<&str>) -> Result<()> {
+    let config = cli.load_config()?;
+    let sink = cli.clipboard(&config);
+    let repo = Path::new(".");
+    let base = match base {
+        Some(base) => base.to_string(),
+        None => get_default_branch_in_path(repo)?,
+    };
+
+    let messages = get_range_messages_in_path(&format!("{}..HEAD", base), repo)?;
+    if messages.is_empty() {
+        return Err(anyhow!(
+            "The current branch has no commits that are not on {}.",
+            base
+        ));
+    }
+    // Against the merge base, so changes merged into `base` meanwhile are left out
+    let diff = get_branch_diff_in_path(&base, &config.pathspecs(), repo)?;
+
+    let reply = summarize_diff(config, build_input(&messages, &diff)).await?;
+    let message = with_subjects_footer(&reply, &messages);
+    println!("{}", cli.format_message(&message));
+    clipboard::deliver(&message, sink.as_deref());
+    Ok(())
+}
+
+/// Appends a footer listing the subjects of the squashed `messages`, oldest first.
+pub fn with_subjects_footer(message: &str, messages: &[String]) -> String {
+    let subjects: String = messages
+        .iter()
+        .map(|message| format!("\n- {}", message.lines().next().unwrap_or_default()))
+        .collect();
+    format!("{}\n\nSquashed commits:{}", message.trim_end(), subjects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_subjects_footer() {
+        let messages = vec![
+            "feat: add login\n\nWith a form.".to_string(),
+            "fix: typo in login".to_string(),
+        ];
+        assert_eq!(
+            with_subjects_footer("feat(auth): add login\n\n- Add the form\n", &messages),
+            "feat(auth): add login\n\n- Add the form\n\nSquashed commits:\n\
+             - feat: add login\n- fix: typo in login"
+        );
+    }
+}
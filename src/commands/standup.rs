@@ -0,0 +1,220 @@
+//! `asum standup` command.
+//!
+//! Summarizes the user's recent commits into a short standup update and
+//! prints it, writes it to a markdown file, or posts it to a Slack webhook.
+//! With `--cron-friendly` it runs unattended, e.g. from a daily cron job.
+
+use crate::cli::Cli;
+use crate::clipboard;
+use crate::git::{get_commits_since_in_path, get_config_value_in_path};
+use crate::pipeline::summarize_diff;
+use anyhow::{Context, Result};
+use std::path::Path;
+use tracing::info;
+
+/// System prompt of the standup update: the recent commits as past-tense bullet points.
+const STANDUP_SYSTEM_PROMPT: &str = r#"You write daily standup updates for a software engineer.
+Given the list of their recent commit messages, write a short update of what was done:
+- group related commits into one bullet point ("- ") written in past tense
+- mention features and fixes by what they achieve, not by file names
+- at most 6 bullet points
+Return ONLY the bullet points. No heading, no preamble, no code fences."#;
+
+/// User prompt template; `{{diff}}` is replaced with the commit list.
+const STANDUP_USER_PROMPT: &str = "[COMMITS]\n{{diff}}\n\n[STANDUP UPDATE]";
+
+/// Where the digest is delivered.
+#[derive(Debug, PartialEq)]
+pub enum Destination<'a> {
+    /// Print to stdout.
+    Stdout,
+    /// Write a markdown file.
+    File(&'a Path),
+    /// Post to a Slack incoming webhook.
+    Webhook(&'a str),
+}
+
+impl<'a> Destination<'a> {
+    /// Interprets the `--output` value: URLs are webhooks, anything else is a file path.
+    pub fn parse(output: Option<&'a str>) -> Self {
+        match output {
+            None => Destination::Stdout,
+            Some(url) if url.starts_with("https://") || url.starts_with("http://") => {
+                Destination::Webhook(url)
+            }
+            Some(path) => Destination::File(Path::new(path)),
+        }
+    }
+}
+
+/// Generates the standup digest and delivers it.
+pub async fn run(
+    cli: &Cli,
+    since: &str,
+    author: Option<&str>,
+    output: Option<&str>,
+    cron_friendly: bool,
+) -> Result<()> {
+    let mut config = cli.load_config()?;
+    let sink = cli.clipboard(&config);
+    let repo = Path::new(".");
+
+    let author = author
+        .map(String::from)
+        .or_else(|| get_config_value_in_path("user.email", repo));
+    let commits = get_commits_since_in_path(since, author.as_deref(), repo)?;
+    if commits.trim().is_empty() {
+        // Nothing is written or posted, so a daily job stays silent on days off
+        info!("No commits since {}.", since);
+        return Ok(());
+    }
+
+    config.system_prompt = STANDUP_SYSTEM_PROMPT.to_string();
+    config.user_prompt = STANDUP_USER_PROMPT.to_string();
+    let summary = summarize_diff(config, commits).await?;
+    let markdown = format!("## Standup (since {})\n\n{}\n", since, summary.trim());
+
+    match Destination::parse(output) {
+        Destination::Stdout => {
+            print!("{}", markdown);
+            if !cron_friendly {
+                clipboard::deliver(&markdown, sink.as_deref());
+            }
+        }
+        Destination::File(path) => {
+            std::fs::write(path, &markdown)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            if !cron_friendly {
+                println!("[OK] Standup written to {}", path.display());
+            }
+        }
+        Destination::Webhook(url) => {
+            post_webhook(url, &to_slack(&markdown)).await?;
+            if !cron_friendly {
+                println!("[OK] Standup posted to the webhook");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Converts markdown to Slack's mrkdwn: headings and `**bold**` become `*bold*`
+/// and list items become bullets.
+pub fn to_slack(markdown: &str) -> String {
+    markdown
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with('#') {
+                format!("*{}*", trimmed.trim_start_matches('#').trim())
+            } else if let Some(item) = trimmed
+                .strip_prefix("- ")
+                .or_else(|| trimmed.strip_prefix("* "))
+            {
+                let indent = &line[..line.len() - trimmed.len()];
+                format!("{}• {}", indent, item.replace("**", "*"))
+            } else {
+                line.replace("**", "*")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Posts the text to a Slack incoming webhook.
+#[cfg(any(feature = "gemini", feature = "ollama"))]
+async fn post_webhook(url: &str, text: &str) -> Result<()> {
+    let response = reqwest::Client::new()
+        .post(url)
+        .json(&serde_json::json!({ "text": text }))
+        .send()
+        .await
+        .context("Failed to reach the webhook")?;
+    if !response.status().is_success() {
+        anyhow::bail!("Webhook returned error: {}", response.status());
+    }
+    Ok(())
+}
+
+/// Webhooks need the HTTP client that is compiled in with the providers.
+#[cfg(not(any(feature = "gemini", feature = "ollama")))]
+async fn post_webhook(_url: &str, _text: &str) -> Result<()> {
+    anyhow::bail!("Webhook output is not available in a build without AI providers")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_destination_parse_table_driven() {
+        let cases = vec![
+            (None, Destination::Stdout),
+            (
+                Some("https://hooks.slack.com/services/T/B/X"),
+                Destination::Webhook("https://hooks.slack.com/services/T/B/X"),
+            ),
+            (
+                Some("http://localhost:8080/hook"),
+                Destination::Webhook("http://localhost:8080/hook"),
+            ),
+            (
+                Some("standup.md"),
+                Destination::File(Path::new("standup.md")),
+            ),
+            (
+                Some("/tmp/https.md"),
+                Destination::File(Path::new("/tmp/https.md")),
+            ),
+        ];
+
+        for (output, expected) in cases {
+            assert_eq!(Destination::parse(output), expected, "output: {:?}", output);
+        }
+    }
+
+    #[test]
+    fn test_to_slack_table_driven() {
+        let cases = vec![
+            (
+                "## Standup (since yesterday)",
+                "*Standup (since yesterday)*",
+            ),
+            ("- added **login** flow", "• added *login* flow"),
+            ("  * nested item", "  • nested item"),
+            ("plain **text**", "plain *text*"),
+            ("", ""),
+        ];
+
+        for (markdown, expected) in cases {
+            assert_eq!(to_slack(markdown), expected, "markdown: {:?}", markdown);
+        }
+    }
+
+    #[cfg(feature = "ollama")]
+    #[tokio::test]
+    async fn test_post_webhook() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 1024];
+            let n = tokio::io::AsyncReadExt::read(&mut socket, &mut buf)
+                .await
+                .unwrap();
+            let response = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+            tokio::io::AsyncWriteExt::write_all(&mut socket, response.as_bytes())
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&buf[..n]).to_string()
+        });
+
+        post_webhook(&format!("http://{}/hook", addr), "• done")
+            .await
+            .unwrap();
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /hook "));
+        assert!(request.contains(r#"{"text":"• done"}"#));
+    }
+}
@@ -0,0 +1,102 @@
+//! `asum stash` command.
+//!
+//! Stashes the local changes under a short generated description instead of
+//! git's default "WIP on <branch>".
+
+use crate::cli::Cli;
+use crate::failure::Failure;
+use crate::git::{get_uncommitted_changes_in_path, stash_push_in_path};
+use crate::pipeline::summarize_diff;
+use anyhow::Result;
+use std::path::Path;
+
+/// System prompt of the one-line stash description.
+const STASH_SYSTEM_PROMPT: &str = "You are a git expert. Describe the given work-in-progress changes \
+in one short line (max 60 chars) so they can be recognized later in a list of stashes. \
+Do not use a type prefix, quotes or a trailing period. Reply with the description only.";
+
+/// User prompt template; `{{diff}}` is replaced with the changes.
+const STASH_USER_PROMPT: &str = "Changes:\n{{diff}}\n\nDescription:";
+
+/// Maximum length of the stash message.
+const MAX_STASH_MESSAGE_LENGTH: usize = 72;
+
+/// Generates a description of the local changes and stashes them with it.
+pub async fn run(cli: &Cli, include_untracked: bool) -> Result<()> {
+    let mut config = cli.load_config()?;
+
+    let changes = get_uncommitted_changes_in_path(&config.pathspecs(), include_untracked, ".")?;
+    if changes.is_empty() {
+        return Err(Failure::NoChanges.error("No local changes to stash."));
+    }
+
+    config.system_prompt = STASH_SYSTEM_PROMPT.to_string();
+    config.user_prompt = STASH_USER_PROMPT.to_string();
+    let reply = summarize_diff(config, changes).await?;
+    let message = sanitize_stash_message(&reply).ok_or_else(|| {
+        Failure::InvalidOutput.error(format!(
+            "AI did not return a usable stash message: {}",
+            reply
+        ))
+    })?;
+
+    stash_push_in_path(&message, include_untracked, Path::new("."))?;
+    println!("[OK] Stashed changes: {}", message);
+    Ok(())
+}
+
+/// Turns the model's reply into a one-line stash message: the first non-empty
+/// line without quotes, a leading label or a trailing period, cut to
+/// `MAX_STASH_MESSAGE_LENGTH` characters.
+pub fn sanitize_stash_message(reply: &str) -> Option<String> {
+    let line = reply
+        .lines()
+        .map(|l| l.trim().trim_matches(|c| c == '`' || c == '"' || c == '\''))
+        .find(|l| !l.is_empty())?;
+    let line = line
+        .strip_prefix("Description:")
+        .map_or(line, str::trim_start)
+        .trim_end_matches('.');
+
+    let message: String = line.chars().take(MAX_STASH_MESSAGE_LENGTH).collect();
+    let message = message.trim_end().to_string();
+    (!message.is_empty()).then_some(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_stash_message_table_driven() {
+        let cases = vec![
+            (
+                "half-done retry logic for uploads",
+                Some("half-done retry logic for uploads"),
+            ),
+            (
+                "  \"Try a faster parser.\"  \n",
+                Some("Try a faster parser"),
+            ),
+            (
+                "\n\nDescription: debug logging in the cache\nmore",
+                Some("debug logging in the cache"),
+            ),
+            ("", None),
+            ("```", None),
+        ];
+        for (reply, expected) in cases {
+            assert_eq!(
+                sanitize_stash_message(reply).as_deref(),
+                expected,
+                "reply: {:?}",
+                reply
+            );
+        }
+
+        let long = "word ".repeat(30);
+        let message = sanitize_stash_message(&long).unwrap();
+        assert!(message.chars().count() <= MAX_STASH_MESSAGE_LENGTH);
+        assert!(!message.ends_with(' '));
+    }
+}
@@ -0,0 +1,159 @@
+//! `asum stats` command.
+//!
+//! Summarizes how often generated messages were used, per provider and model,
+//! from the feedback recorded by `asum feedback`, and the tokens and estimated
+//! cost of the provider requests per day.
+
+use crate::db::{DailyUsage, Db, ModelAcceptance, now};
+use anyhow::Result;
+
+/// Prints acceptance statistics and the token usage of the last `days` days.
+pub fn run(days: u32) -> Result<()> {
+    let db = Db::open_default()?;
+    let stats = db.acceptance_by_model()?;
+    if stats.is_empty() {
+        println!("No generated messages recorded yet.");
+    } else {
+        println!("{}", render(&stats));
+    }
+
+    let usage = db.usage_by_day(now() - i64::from(days) * 86_400)?;
+    println!();
+    if usage.is_empty() {
+        println!("No token usage recorded in the last {} days.", days);
+    } else {
+        println!("Token usage in the last {} days:", days);
+        println!("{}", render_usage(&usage));
+    }
+    Ok(())
+}
+
+/// Renders one aligned row per model. The acceptance rate counts accepted messages
+/// among those with feedback; edits are shown separately.
+pub fn render(stats: &[ModelAcceptance]) -> String {
+    let names: Vec<String> = stats
+        .iter()
+        .map(|s| format!("{}/{}", s.provider, s.model))
+        .collect();
+    let width = names.iter().map(String::len).max().unwrap_or(0).max(5);
+
+    let mut lines = vec![format!(
+        "{:<width$}  {:>9}  {:>8}  {:>6}  {:>8}  {:>10}",
+        "model", "generated", "accepted", "edited", "rejected", "acceptance"
+    )];
+    for (name, s) in names.iter().zip(stats) {
+        let rated = s.accepted + s.edited + s.rejected;
+        let rate = if rated == 0 {
+            "-".to_string()
+        } else {
+            format!("{:.0}%", s.accepted as f64 * 100.0 / rated as f64)
+        };
+        lines.push(format!(
+            "{:<width$}  {:>9}  {:>8}  {:>6}  {:>8}  {:>10}",
+            name, s.generated, s.accepted, s.edited, s.rejected, rate
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Renders one aligned row per day and model, followed by a total row. Costs are
+/// estimates; `-` marks models without a known price.
+pub fn render_usage(usage: &[DailyUsage]) -> String {
+    let names: Vec<String> = usage
+        .iter()
+        .map(|u| format!("{}/{}", u.provider, u.model))
+        .collect();
+    let width = names.iter().map(String::len).max().unwrap_or(0).max(5);
+    let cost = |cost: Option<f64>| cost.map_or("-".to_string(), |c| format!("${:.4}", c));
+
+    let mut lines = vec![format!(
+        "{:<10}  {:<width$}  {:>8}  {:>13}  {:>17}  {:>9}",
+        "day", "model", "requests", "prompt tokens", "completion tokens", "cost"
+    )];
+    for (name, u) in names.iter().zip(usage) {
+        lines.push(format!(
+            "{:<10}  {:<width$}  {:>8}  {:>13}  {:>17}  {:>9}",
+            u.day,
+            name,
+            u.requests,
+            u.prompt_tokens,
+            u.completion_tokens,
+            cost(u.cost)
+        ));
+    }
+
+    let costs: Vec<f64> = usage.iter().filter_map(|u| u.cost).collect();
+    lines.push(format!(
+        "{:<10}  {:<width$}  {:>8}  {:>13}  {:>17}  {:>9}",
+        "total",
+        "",
+        usage.iter().map(|u| u.requests).sum::<u64>(),
+        usage.iter().map(|u| u.prompt_tokens).sum::<u64>(),
+        usage.iter().map(|u| u.completion_tokens).sum::<u64>(),
+        cost((!costs.is_empty()).then(|| costs.iter().sum()))
+    ));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let stats = vec![
+            ModelAcceptance {
+                provider: "ollama".to_string(),
+                model: "llama3".to_string(),
+                generated: 10,
+                accepted: 3,
+                edited: 1,
+                rejected: 0,
+            },
+            ModelAcceptance {
+                provider: "gemini".to_string(),
+                model: "gemini-2.0-flash".to_string(),
+                generated: 2,
+                accepted: 0,
+                edited: 0,
+                rejected: 0,
+            },
+        ];
+
+        let output = render(&stats);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines[0],
+            "model                    generated  accepted  edited  rejected  acceptance"
+        );
+        assert_eq!(
+            lines[1],
+            "ollama/llama3                   10         3       1         0         75%"
+        );
+        assert!(lines[2].ends_with("         -"));
+    }
+
+    #[test]
+    fn test_render_usage() {
+        let day = |day: &str, model: &str, requests, cost| DailyUsage {
+            day: day.to_string(),
+            provider: "gemini".to_string(),
+            model: model.to_string(),
+            requests,
+            prompt_tokens: requests * 1000,
+            completion_tokens: requests * 50,
+            cost,
+        };
+        let output = render_usage(&[
+            day("2024-06-15", "gemini-2.0-flash", 3, Some(0.00036)),
+            day("2024-06-14", "gemini-exp", 1, None),
+        ]);
+        assert_eq!(
+            output,
+            "day         model                    requests  prompt tokens  completion tokens       cost\n\
+             2024-06-15  gemini/gemini-2.0-flash         3           3000                150    $0.0004\n\
+             2024-06-14  gemini/gemini-exp               1           1000                 50          -\n\
+             total                                       4           4000                200    $0.0004"
+        );
+    }
+}
@@ -0,0 +1,121 @@
+//! `asum tag` command.
+//!
+//! Writes an annotated tag message that summarizes everything since the previous
+//! tag, from the commit messages and the combined diff of that range.
+
+use crate::cli::Cli;
+use crate::clipboard;
+use crate::git::{
+    create_tag_in_path, get_latest_tag_in_path, get_range_diff_in_path, get_range_messages_in_path,
+};
+use crate::pipeline::{LONG_REPLY_NUM_PREDICT, summarize_diff};
+use anyhow::{Result, anyhow};
+use std::path::Path;
+
+/// System prompt of the tag message: a release title and the notable changes since the last tag.
+const TAG_SYSTEM_PROMPT: &str = r###"You are an expert software engineer writing the message of an annotated release tag.
+Given the commits since the previous release, write:
+1. On the first line, a short release title (max 72 chars, no trailing period).
+2. Then, after a blank line, the notable changes as bullet points ("- "), grouped under
+   plain-text labels such as "Features:", "Fixes:" and "Other changes:". Leave out empty groups.
+Write plain text only: no markdown headings, no code fences and no preamble."###;
+
+/// User prompt template; `{{diff}}` is replaced with the commits and the diff.
+const TAG_USER_PROMPT: &str = "[RELEASE]\n{{diff}}\n\n[TAG MESSAGE]";
+
+/// Generates the tag message for `name` and prints it, or creates the tag when
+/// `create` is set.
+pub async fn run(cli: &Cli, name: &str, create: bool) -> Result<()> {
+    let mut config = cli.load_config()?;
+    let sink = cli.clipboard(&config);
+    let repo = Path::new(".");
+
+    let previous = get_latest_tag_in_path(repo)?;
+    if previous.as_deref() == Some(name) {
+        return Err(anyhow!("Tag '{}' already exists at HEAD.", name));
+    }
+    let range = match &previous {
+        Some(tag) => format!("{}..HEAD", tag),
+        None => "HEAD".to_string(),
+    };
+    let messages = get_range_messages_in_path(&range, repo)?;
+    if messages.is_empty() {
+        return Err(anyhow!("No commits found since {}.", range));
+    }
+    // Without a previous tag the whole history is the release; its diff would
+    // only be truncated, so the commit messages carry it alone
+    let diff = match &previous {
+        Some(_) => get_range_diff_in_path(&range, &config.pathspecs(), repo)?,
+        None => String::new(),
+    };
+
+    config.system_prompt = TAG_SYSTEM_PROMPT.to_string();
+    config.user_prompt = TAG_USER_PROMPT.to_string();
+    config.ai_num_predict = config.ai_num_predict.max(LONG_REPLY_NUM_PREDICT);
+    let input = build_input(name, previous.as_deref(), &messages, &diff);
+    let message = summarize_diff(config, input).await?;
+
+    if create {
+        create_tag_in_path(name, &message, repo)?;
+        println!("[OK] Created tag {}:\n\n{}", name, message);
+    } else {
+        println!("{}", message);
+        clipboard::deliver(&message, sink.as_deref());
+    }
+    Ok(())
+}
+
+/// Combines the tag names, the commit subjects and the diff into the model input.
+pub fn build_input(name: &str, previous: Option<&str>, messages: &[String], diff: &str) -> String {
+    let mut input = match previous {
+        Some(previous) => format!("New tag: {} (previous tag: {})\n", name, previous),
+        None => format!("New tag: {} (first release)\n", name),
+    };
+    input.push_str("\nCommits (oldest first):\n");
+    for message in messages {
+        input.push_str(&format!(
+            "- {}\n",
+            message.lines().next().unwrap_or_default()
+        ));
+    }
+    if !diff.is_empty() {
+        input.push_str("\nCombined diff:\n");
+        input.push_str(diff);
+    }
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_input_table_driven() {
+        let messages = vec![
+            "feat: add tags\n\n- body line".to_string(),
+            "fix: empty range".to_string(),
+        ];
+        let cases = vec![
+            (
+                Some("v1.0.0"),
+                "diff --git a/x b/x\n",
+                "New tag: v2.0.0 (previous tag: v1.0.0)\n\nCommits (oldest first):\n\
+                 - feat: add tags\n- fix: empty range\n\nCombined diff:\ndiff --git a/x b/x\n",
+            ),
+            (
+                None,
+                "",
+                "New tag: v2.0.0 (first release)\n\nCommits (oldest first):\n\
+                 - feat: add tags\n- fix: empty range\n",
+            ),
+        ];
+        for (previous, diff, expected) in cases {
+            assert_eq!(
+                build_input("v2.0.0", previous, &messages, diff),
+                expected,
+                "previous: {:?}",
+                previous
+            );
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! `asum translate` command.
+//!
+//! Translates the messages of existing commits into another language, keeping
+//! the Conventional Commits header intact, e.g. to prepare an English-facing
+//! mirror of a repository with non-English history.
+
+use crate::batch::{Batch, BatchItem};
+use crate::cli::Cli;
+use crate::clipboard;
+use crate::git::{
+    get_commit_message_in_path, get_range_commits_in_path, get_repo_root_in_path,
+    resolve_commit_in_path,
+};
+use crate::pipeline::summarize_diff;
+use anyhow::{Result, anyhow};
+use std::path::Path;
+
+/// System prompt of the translation, which keeps the Conventional Commits header as it is.
+/// `{{language}}` is replaced with the target language.
+const TRANSLATE_SYSTEM_PROMPT: &str = r#"You translate git commit messages into {{language}}.
+Keep the Conventional Commits header exactly as it is: the type, the scope in parentheses and
+a "!" marking a breaking change are never translated. Translate the description after ": ",
+the body and the footer values, but keep footer tokens such as "BREAKING CHANGE:" or
+"Refs:", code identifiers, file paths and issue numbers unchanged.
+Keep the line structure. Reply with the translated message only.
+If the message is already in {{language}}, reply with it unchanged."#;
+
+/// User prompt template; `{{diff}}` is replaced with the commit message.
+const TRANSLATE_USER_PROMPT: &str = "[MESSAGE]\n{{diff}}\n\n[TRANSLATION]";
+
+/// Translates the message of `target` (a commit, or a range such as `v1.0..HEAD`)
+/// into `language` and prints the results.
+pub async fn run(cli: &Cli, target: &str, language: &str) -> Result<()> {
+    let mut config = cli.load_config()?;
+    let repo = Path::new(".");
+
+    let hashes: Vec<String> = if target.contains("..") {
+        get_range_commits_in_path(target, repo)?
+            .into_iter()
+            .map(|(hash, _)| hash)
+            .collect()
+    } else {
+        vec![resolve_commit_in_path(target, repo)?]
+    };
+    if hashes.is_empty() {
+        return Err(anyhow!("No commits found in {}.", target));
+    }
+
+    let mut originals = Vec::with_capacity(hashes.len());
+    let mut items = Vec::with_capacity(hashes.len());
+    for hash in &hashes {
+        let message = get_commit_message_in_path(hash, repo)?;
+        items.push(BatchItem {
+            key: hash.clone(),
+            input: message.clone(),
+        });
+        originals.push(message);
+    }
+
+    config.system_prompt = TRANSLATE_SYSTEM_PROMPT.replace("{{language}}", language);
+    config.user_prompt = TRANSLATE_USER_PROMPT.to_string();
+
+    let batch_id = format!(
+        "translate:{}:{}:{}:{}",
+        language,
+        get_repo_root_in_path(repo)?.display(),
+        config.active_provider,
+        config.active_model().unwrap_or_default()
+    );
+    let mut batch = Batch::new(&batch_id, &config);
+    let translations = batch
+        .run(&items, |input| summarize_diff(config.clone(), input))
+        .await?;
+
+    let output = hashes
+        .iter()
+        .zip(originals.iter().zip(&translations))
+        .map(|(hash, (original, translated))| {
+            format!("commit {}\n\n{}", hash, keep_header(original, translated))
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    println!("{}", output);
+    clipboard::deliver(&output, cli.clipboard(&config).as_deref());
+    Ok(())
+}
+
+/// Returns the Conventional Commits prefix of a subject line, such as
+/// `feat(api)!` for `feat(api)!: add login`, if it has one. The type must be a
+/// lowercase word, which also matches types a model translated by mistake.
+pub fn header_prefix(subject: &str) -> Option<&str> {
+    let (prefix, _) = subject.split_once(": ")?;
+    let name = prefix.strip_suffix('!').unwrap_or(prefix);
+    let kind = match name.split_once('(') {
+        Some((kind, scope)) if scope.ends_with(')') && !scope[..scope.len() - 1].contains(')') => {
+            kind
+        }
+        Some(_) => return None,
+        None => name,
+    };
+    let valid = !kind.is_empty() && kind.chars().all(char::is_lowercase);
+    valid.then_some(prefix)
+}
+
+/// Makes sure the translated message starts with the original header prefix,
+/// replacing a prefix the model translated or dropped.
+pub fn keep_header(original: &str, translated: &str) -> String {
+    let translated = translated.trim();
+    let original_subject = original.lines().next().unwrap_or_default();
+    let Some(prefix) = header_prefix(original_subject) else {
+        return translated.to_string();
+    };
+
+    let (subject, rest) = translated.split_once('\n').unwrap_or((translated, ""));
+    let description = match header_prefix(subject) {
+        Some(translated_prefix) => &subject[translated_prefix.len() + 2..],
+        None => subject,
+    };
+    let mut message = format!("{}: {}", prefix, description.trim());
+    if !rest.is_empty() {
+        message.push('\n');
+        message.push_str(rest);
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_prefix_table_driven() {
+        let cases = vec![
+            ("feat: add login", Some("feat")),
+            ("fix(api)!: drop v1", Some("fix(api)!")),
+            ("feat(ui): 新しいボタン", Some("feat(ui)")),
+            ("Merge branch 'main'", None),
+            ("Note: this is not a header", None),
+            ("fix(a)(b): nope", None),
+        ];
+        for (subject, expected) in cases {
+            assert_eq!(header_prefix(subject), expected, "subject: {}", subject);
+        }
+    }
+
+    #[test]
+    fn test_keep_header_table_driven() {
+        let cases = vec![
+            (
+                "feat(auth): ajouter la connexion",
+                "feat(auth): add login",
+                "feat(auth): add login",
+            ),
+            (
+                "fix(api)!: supprimer v1\n\nBREAKING CHANGE: v1 supprimée",
+                "corrección(api)!: remove v1\n\nBREAKING CHANGE: v1 removed",
+                "fix(api)!: remove v1\n\nBREAKING CHANGE: v1 removed",
+            ),
+            ("docs: mettre à jour", "update docs", "docs: update docs"),
+            ("Mise à jour", "  Update\n", "Update"),
+        ];
+        for (original, translated, expected) in cases {
+            assert_eq!(keep_header(original, translated), expected);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//! `asum tui` command.
+//!
+//! Opens the full-screen interface for the staged changes. The interface is
+//! compiled only with the `tui` Cargo feature.
+
+use crate::cli::Cli;
+use anyhow::Result;
+
+/// Opens the full-screen interface, or explains how to get it in builds without it.
+#[cfg(feature = "tui")]
+pub async fn run(cli: &Cli) -> Result<()> {
+    use crate::diff_source::{DiffSource, GitStaged};
+    use crate::failure::Failure;
+    use crate::git::get_staged_files;
+    use crate::merge;
+    use crate::pipeline::adapt_prompts;
+    use std::path::Path;
+
+    let mut config = cli.load_config()?;
+    let diff = GitStaged::current().read(&config.pathspecs())?;
+    if diff.trim().is_empty() {
+        return Err(
+            Failure::NoChanges.error("No staged changes found. Stage some with `git add` first.")
+        );
+    }
+    merge::apply(&mut config, Path::new("."));
+    adapt_prompts(&mut config, Path::new("."), &diff).await;
+    let files = get_staged_files()?;
+    crate::tui::run(config, files, diff).await
+}
+
+/// Opens the full-screen interface, or explains how to get it in builds without it.
+#[cfg(not(feature = "tui"))]
+pub async fn run(_cli: &Cli) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "The full-screen interface is not available in this build. Rebuild with `--features tui`."
+    ))
+}
@@ -0,0 +1,283 @@
+//! `asum verify` command.
+//!
+//! Checks `asum.toml` beyond its TOML syntax: value ranges, the fields the active
+//! provider needs and, with `--online`, whether the provider can be reached and
+//! offers the configured model.
+
+use crate::cli::Cli;
+use crate::config::{AsumConfig, verify_toml};
+use crate::failure::Failure;
+use crate::profile;
+use crate::spelling;
+use crate::summarizer::{KNOWN_PROVIDERS, ModelInfo, get_summarizer};
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use tracing::error;
+
+/// Verifies the local `asum.toml` (or the one given via `--config`).
+pub async fn run(cli: &Cli, online: bool) -> Result<()> {
+    let path = cli
+        .config
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("asum.toml"));
+    verify_file(&path, online).await
+}
+
+/// Verifies the configuration file at `path`, and with `online` whether the provider
+/// can be reached and offers the configured model.
+pub async fn verify_file(path: &Path, online: bool) -> Result<()> {
+    if !path.exists() {
+        error!("{} not found.", path.display());
+        return Err(Failure::Config.error(format!("{} not found", path.display())));
+    }
+    if let Err(e) = verify_toml(path) {
+        error!("{} syntax error: {}", path.display(), e);
+        return Err(Failure::Config.error(format!("{} syntax error: {}", path.display(), e)));
+    }
+
+    // Checked as asum would run it, so keys given through the environment count
+    let mut config = AsumConfig::load_from(path)?;
+    config.apply_env(|name| std::env::var(name).ok());
+    let problems = check_values(&config);
+    if !problems.is_empty() {
+        for problem in &problems {
+            error!("{}", problem);
+        }
+        return Err(Failure::Config.error(format!(
+            "{} has {} invalid value(s):\n  - {}",
+            path.display(),
+            problems.len(),
+            problems.join("\n  - ")
+        )));
+    }
+    println!("[OK] {} is valid.", path.display());
+
+    if online {
+        check_online(config).await?;
+    }
+    Ok(())
+}
+
+/// Returns the problems with the values of `config`; empty when they look fine.
+pub fn check_values(config: &AsumConfig) -> Vec<String> {
+    let mut problems = Vec::new();
+    if !KNOWN_PROVIDERS.contains(&config.active_provider.as_str()) {
+        problems.push(format!(
+            "active_provider must be one of {} (got '{}').",
+            KNOWN_PROVIDERS.join(", "),
+            config.active_provider
+        ));
+    }
+    if config.max_diff_length == 0 {
+        problems.push("max_diff_length must be greater than 0.".to_string());
+    }
+    if config.requests_per_minute == Some(0) {
+        problems.push("requests_per_minute must be greater than 0.".to_string());
+    }
+    if !profile::PROFILES.contains(&config.repo_profile.as_str()) {
+        problems.push(format!(
+            "repo_profile must be one of {} (got '{}').",
+            profile::PROFILES.join(", "),
+            config.repo_profile
+        ));
+    }
+    if !spelling::MODES.contains(&config.spell_check.as_str()) {
+        problems.push(format!(
+            "spell_check must be one of {} (got '{}').",
+            spelling::MODES.join(", "),
+            config.spell_check
+        ));
+    }
+    if !config.user_prompt.contains("{{diff}}") {
+        problems.push("user_prompt must contain the {{diff}} placeholder.".to_string());
+    }
+    if !(0.0..=2.0).contains(&config.ai_temperature) {
+        problems.push(format!(
+            "temperature must be between 0 and 2 (got {}).",
+            config.ai_temperature
+        ));
+    }
+    if !(0.0..=1.0).contains(&config.ai_top_p) {
+        problems.push(format!(
+            "top_p must be between 0 and 1 (got {}).",
+            config.ai_top_p
+        ));
+    }
+    if config.ai_num_predict <= 0 {
+        problems.push(format!(
+            "num_predict must be greater than 0 (got {}).",
+            config.ai_num_predict
+        ));
+    }
+
+    let missing = |value: &Option<String>| value.as_deref().is_none_or(|v| v.trim().is_empty());
+    match config.active_provider.as_str() {
+        "gemini" => {
+            if missing(&config.gemini_api_key) {
+                problems.push("[gemini] api_key is missing (or set GEMINI_API_KEY).".to_string());
+            }
+            if missing(&config.gemini_model) {
+                problems.push("[gemini] model is missing.".to_string());
+            }
+        }
+        "ollama" => {
+            if let Some(url) = &config.ollama_url
+                && !(url.starts_with("http://") || url.starts_with("https://"))
+            {
+                problems.push(format!(
+                    "[ollama] url must start with http:// or https:// (got '{}').",
+                    url
+                ));
+            }
+            if missing(&config.ollama_model) {
+                problems.push("[ollama] model is missing.".to_string());
+            }
+        }
+        _ => {}
+    }
+    problems
+}
+
+/// Lists the provider's models to confirm it can be reached and offers the
+/// configured model.
+async fn check_online(config: AsumConfig) -> Result<()> {
+    let provider = config.active_provider.clone();
+    let model = config.active_model().unwrap_or_default().to_string();
+    let models = get_summarizer(config)
+        .await?
+        .list_models()
+        .await
+        .map_err(|e| Failure::Provider.tag(e.context(format!("Could not reach {}", provider))))?;
+    if !offers_model(&models, &model) {
+        return Err(Failure::Config.error(format!(
+            "Model '{}' is not offered by {}. Run 'asum models' to see the available models.",
+            model, provider
+        )));
+    }
+    println!("[OK] {} is reachable and offers {}.", provider, model);
+    Ok(())
+}
+
+/// Whether `models` contains `model`. Ollama reports untagged models with the
+/// implicit `:latest` tag.
+pub fn offers_model(models: &[ModelInfo], model: &str) -> bool {
+    models
+        .iter()
+        .any(|m| m.name == model || m.name == format!("{}:latest", model))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn valid_config() -> AsumConfig {
+        let mut config = AsumConfig::from_defaults().unwrap();
+        config.gemini_api_key = Some("key".to_string());
+        config
+    }
+
+    #[test]
+    fn test_check_values_table_driven() {
+        type Edit = fn(&mut AsumConfig);
+        let cases: Vec<(&str, Edit, Option<&str>)> = vec![
+            ("defaults", |_| {}, None),
+            (
+                "gemini defaults",
+                |c| c.active_provider = "gemini".to_string(),
+                None,
+            ),
+            (
+                "unknown provider",
+                |c| c.active_provider = "openai".to_string(),
+                Some("active_provider must be one of gemini, ollama (got 'openai')."),
+            ),
+            (
+                "temperature too high",
+                |c| c.ai_temperature = 2.5,
+                Some("temperature must be between 0 and 2 (got 2.5)."),
+            ),
+            (
+                "negative top_p",
+                |c| c.ai_top_p = -0.1,
+                Some("top_p must be between 0 and 1 (got -0.1)."),
+            ),
+            (
+                "zero num_predict",
+                |c| c.ai_num_predict = 0,
+                Some("num_predict must be greater than 0 (got 0)."),
+            ),
+            (
+                "zero max_diff_length",
+                |c| c.max_diff_length = 0,
+                Some("max_diff_length must be greater than 0."),
+            ),
+            (
+                "unknown profile",
+                |c| c.repo_profile = "dotfiles".to_string(),
+                Some("repo_profile must be one of auto, code, config (got 'dotfiles')."),
+            ),
+            (
+                "unknown spell check mode",
+                |c| c.spell_check = "warn".to_string(),
+                Some("spell_check must be one of fix, flag, off (got 'warn')."),
+            ),
+            (
+                "user prompt without diff",
+                |c| c.user_prompt = "Write a message.".to_string(),
+                Some("user_prompt must contain the {{diff}} placeholder."),
+            ),
+            (
+                "gemini without key",
+                |c| {
+                    c.active_provider = "gemini".to_string();
+                    c.gemini_api_key = Some(" ".to_string());
+                },
+                Some("[gemini] api_key is missing (or set GEMINI_API_KEY)."),
+            ),
+            (
+                "ollama without model",
+                |c| c.ollama_model = None,
+                Some("[ollama] model is missing."),
+            ),
+            (
+                "ollama url without scheme",
+                |c| c.ollama_url = Some("localhost:11434".to_string()),
+                Some("[ollama] url must start with http:// or https:// (got 'localhost:11434')."),
+            ),
+        ];
+
+        for (name, edit, expected) in cases {
+            let mut config = valid_config();
+            edit(&mut config);
+            let problems = check_values(&config);
+            assert_eq!(
+                problems,
+                expected.map(String::from).into_iter().collect::<Vec<_>>(),
+                "case: {}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_offers_model_table_driven() {
+        let models: Vec<ModelInfo> = ["llama3:latest", "qwen2.5-coder:3b"]
+            .iter()
+            .map(|name| ModelInfo {
+                name: name.to_string(),
+                size: None,
+                details: None,
+            })
+            .collect();
+        let cases = vec![
+            ("llama3", true),
+            ("llama3:latest", true),
+            ("qwen2.5-coder:3b", true),
+            ("qwen2.5-coder", false),
+            ("", false),
+        ];
+        for (model, expected) in cases {
+            assert_eq!(offers_model(&models, model), expected, "model: {}", model);
+        }
+    }
+}
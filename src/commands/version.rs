@@ -0,0 +1,100 @@
+//! `asum version` command.
+//!
+//! Prints the version, build metadata, compiled-in providers and the paths
+//! ASUM resolves at runtime, to help triage environment-specific issues.
+
+use crate::config::{db_path, log_dir, resolve_config_path};
+use crate::summarizer::available_providers;
+use std::path::{Path, PathBuf};
+
+/// Build and environment details reported by `asum version`.
+#[derive(Debug, Clone)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    pub git_sha: &'static str,
+    pub build_date: &'static str,
+    pub providers: Vec<&'static str>,
+    pub config_path: Option<PathBuf>,
+    pub log_dir: Option<PathBuf>,
+    pub db_path: Option<PathBuf>,
+}
+
+impl BuildInfo {
+    /// Collects build metadata and resolves runtime paths.
+    /// An explicit `--config` path takes precedence over the default lookup.
+    pub fn collect(config_override: Option<&Path>) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            git_sha: env!("ASUM_GIT_SHA"),
+            build_date: env!("ASUM_BUILD_DATE"),
+            providers: available_providers(),
+            config_path: config_override
+                .map(Path::to_path_buf)
+                .or_else(resolve_config_path),
+            log_dir: log_dir(),
+            db_path: db_path(),
+        }
+    }
+
+    /// Renders the information as aligned `key: value` lines.
+    pub fn render(&self) -> String {
+        let display_path = |path: &Option<PathBuf>| {
+            path.as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| "not found".to_string())
+        };
+
+        [
+            format!("asum {}", self.version),
+            format!("commit:     {}", self.git_sha),
+            format!("build date: {}", self.build_date),
+            format!("providers:  {}", self.providers.join(", ")),
+            format!("config:     {}", display_path(&self.config_path)),
+            format!("logs:       {}", display_path(&self.log_dir)),
+            format!("database:   {}", display_path(&self.db_path)),
+        ]
+        .join("\n")
+    }
+}
+
+/// Prints version and build information.
+pub fn run(config_override: Option<&Path>) -> anyhow::Result<()> {
+    println!("{}", BuildInfo::collect(config_override).render());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_uses_config_override() {
+        let info = BuildInfo::collect(Some(Path::new("/tmp/custom.toml")));
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert!(!info.git_sha.is_empty());
+        assert_eq!(info.build_date.len(), "YYYY-MM-DD".len());
+        assert_eq!(info.config_path, Some(PathBuf::from("/tmp/custom.toml")));
+    }
+
+    #[test]
+    fn test_render() {
+        let info = BuildInfo {
+            version: "1.2.3",
+            git_sha: "abc1234",
+            build_date: "2024-01-31",
+            providers: vec!["gemini", "ollama"],
+            config_path: None,
+            log_dir: Some(PathBuf::from("/home/user/.asum/logs")),
+            db_path: Some(PathBuf::from("/home/user/.asum/asum.db")),
+        };
+
+        let output = info.render();
+        assert!(output.starts_with("asum 1.2.3\n"));
+        assert!(output.contains("commit:     abc1234"));
+        assert!(output.contains("build date: 2024-01-31"));
+        assert!(output.contains("providers:  gemini, ollama"));
+        assert!(output.contains("config:     not found"));
+        assert!(output.contains("logs:       /home/user/.asum/logs"));
+        assert!(output.contains("database:   /home/user/.asum/asum.db"));
+    }
+}
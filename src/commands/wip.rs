@@ -0,0 +1,116 @@
+//! `asum wip` command.
+//!
+//! Generates a terse `wip: #<n> <description>` message for the staged changes, for
+//! work that will be squashed later. A short fixed prompt replaces the configured
+//! commit message prompts, and the number counts up per branch in the database.
+
+use crate::cli::Cli;
+use crate::clipboard;
+use crate::commands::stash::sanitize_stash_message;
+use crate::db::{Db, diff_hash};
+use crate::diff_source::GitStaged;
+use crate::failure::Failure;
+use crate::git::{self, get_current_branch_in_path};
+use crate::pipeline::{read_diff, repo_key, save_generation, summarize_diff};
+use anyhow::Result;
+use std::path::Path;
+
+/// System prompt of the terse description that follows `wip: #<n>`.
+const WIP_SYSTEM_PROMPT: &str = "Describe the given changes in a few lower-case words \
+(max 50 chars), like a note to self. No type prefix, quotes or trailing period. Reply with \
+the description only.";
+
+/// User prompt template; `{{diff}}` is replaced with the changes.
+const WIP_USER_PROMPT: &str = "{{diff}}";
+
+/// Response token budget; the description is a handful of words.
+const WIP_NUM_PREDICT: i32 = 32;
+
+/// Maximum length of the description after the `wip: #<n>` prefix.
+const MAX_WIP_DESCRIPTION_LENGTH: usize = 50;
+
+/// Generates the WIP message for the staged changes and prints it, or commits the
+/// staged changes with it when `create` is set.
+pub async fn run(cli: &Cli, create: bool) -> Result<()> {
+    let mut config = cli.load_config()?;
+    let sink = cli.clipboard(&config);
+    let diff_text = read_diff(&config, &GitStaged::current(), Path::new("."))?;
+
+    config.system_prompt = WIP_SYSTEM_PROMPT.to_string();
+    config.user_prompt = WIP_USER_PROMPT.to_string();
+    config.ai_num_predict = config.ai_num_predict.min(WIP_NUM_PREDICT);
+    config.ai_num_predict_auto = false;
+    let reply = summarize_diff(config.clone(), diff_text.clone()).await?;
+    let description = sanitize_wip_description(&reply).ok_or_else(|| {
+        Failure::InvalidOutput.error(format!("AI did not return a usable description: {}", reply))
+    })?;
+
+    // Summarizing the same staged changes again keeps their number
+    let repo = repo_key(Path::new("."))?;
+    let branch = get_current_branch_in_path(Path::new(".")).unwrap_or_default();
+    let number = Db::open_default()?.wip_number(&repo, &branch, &diff_hash(&diff_text))?;
+    let message = wip_message(number, &description);
+    save_generation(&config, Path::new("."), &diff_text, &message);
+
+    if create {
+        git::commit(&message, false).inspect_err(|_| clipboard::save_restore_point(&message))?;
+        println!("[OK] Committed: {}", message);
+    } else {
+        println!("{}", message);
+        clipboard::deliver(&message, sink.as_deref());
+    }
+    Ok(())
+}
+
+/// Turns the model's reply into a short description: one line as for stash
+/// messages, without a `wip:` prefix of its own, cut to `MAX_WIP_DESCRIPTION_LENGTH`
+/// characters.
+pub fn sanitize_wip_description(reply: &str) -> Option<String> {
+    let line = sanitize_stash_message(reply)?;
+    let line = line
+        .strip_prefix("wip:")
+        .or_else(|| line.strip_prefix("WIP:"))
+        .map_or(line.as_str(), str::trim_start);
+    let description: String = line.chars().take(MAX_WIP_DESCRIPTION_LENGTH).collect();
+    let description = description.trim_end().to_string();
+    (!description.is_empty()).then_some(description)
+}
+
+/// Formats the message for WIP number `number`.
+pub fn wip_message(number: u32, description: &str) -> String {
+    format!("wip: #{} {}", number, description)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_wip_description_table_driven() {
+        let cases = vec![
+            ("retry uploads on timeout", Some("retry uploads on timeout")),
+            ("\"wip: half-done parser.\"\n", Some("half-done parser")),
+            ("WIP: cache keys", Some("cache keys")),
+            ("wip:", None),
+            ("", None),
+        ];
+        for (reply, expected) in cases {
+            assert_eq!(
+                sanitize_wip_description(reply).as_deref(),
+                expected,
+                "reply: {:?}",
+                reply
+            );
+        }
+
+        let long = "word ".repeat(30);
+        let description = sanitize_wip_description(&long).unwrap();
+        assert!(description.chars().count() <= MAX_WIP_DESCRIPTION_LENGTH);
+        assert!(!description.ends_with(' '));
+    }
+
+    #[test]
+    fn test_wip_message() {
+        assert_eq!(wip_message(3, "retry uploads"), "wip: #3 retry uploads");
+    }
+}
@@ -3,10 +3,347 @@
 //! This module handles loading, parsing, and validating the application settings
 //! from local or global TOML configuration files.
 
+use crate::analysis::noise;
+use crate::chunks;
+use crate::clipboard;
+use crate::footers;
+use crate::git::EXCLUDE_MAGIC;
+use crate::pipeline;
+use crate::profile;
+use crate::secrets;
+use crate::spelling;
+use crate::untracked;
 use anyhow::{Context, Result, anyhow};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// File patterns included in the diff when `git_extensions` is not configured.
+const DEFAULT_GIT_EXTENSIONS: &[&str] = &[
+    "*.java", "*.php", "*.js", "*.jsx", "*.ts", "*.tsx", "*.vue", "*.svelte", "*.scss", "*.css",
+    "*.html", "*.rs", "*.py", "*.pyi", "*.go", "*.c", "*.cpp", "*.h", "*.hpp", "*.cs", "*.rb",
+    "*.swift", "*.kt", "*.kts", "*.dart", "*.sh", "*.sql", "*.md", "*.yml", "*.yaml", "*.toml",
+    "*.json", "*.tf", "*.snap", "*.golden",
+];
+
+/// File patterns left out of the diff when `exclude_patterns` is not configured.
+const DEFAULT_EXCLUDE_PATTERNS: &[&str] = &[
+    "*-lock.json",
+    "package-lock.json",
+    "pnpm-lock.yaml",
+    "*.min.js",
+];
+
+/// System prompt used when `[prompts] system_prompt` is not configured.
+const DEFAULT_SYSTEM_PROMPT: &str = r#"# SYSTEM IDENTITY
+You are an expert Git Commit Generator. Your goal is to produce high-quality, professional commit messages following Conventional Commits 1.0.0.
+
+# STRICT RULES
+1. MANDATORY HEADER: Every response MUST start with `<type>(<scope>): <description>`.
+2. TYPES: Only use: feat, fix, docs, style, refactor, perf, test, build, ci, chore, revert.
+3. DESCRIPTION: Use imperative mood, lowercase, no period, max 50 chars.
+4. BODY (OPTIONAL): Use bullet points ("- ") to explain "what" and "why".
+5. OUTPUT: Return ONLY the raw commit message. No preamble, no backticks, no markdown blocks.
+
+# FEW-SHOT EXAMPLES
+
+Example 1 (Simple Fix):
+fix(ui): correct button alignment on mobile
+
+Example 2 (Feature with Body):
+feat(auth): implement oauth2 login flow
+
+- add google and github provider support
+- implement secure callback handling
+- encrypt user tokens before storage
+
+Example 3 (Breaking Change):
+refactor(api)!: migrate to async/await syntax
+
+- rewrite all controllers to be non-blocking
+- update database driver to support pooling
+
+BREAKING CHANGE: the synchronous API is no longer supported."#;
+
+/// User prompt template used when `[prompts] user_prompt` is not configured.
+const DEFAULT_USER_PROMPT: &str = r#"[INPUT DIFF]
+{{diff}}
+
+[OUTPUT]"#;
+
+/// Bullet points allowed in a commit message body when `max_body_bullets` is not configured.
+const DEFAULT_MAX_BODY_BULLETS: usize = 4;
+
+/// Commit subjects `{{recent_commits}}` lists when `recent_commits` is not configured.
+const DEFAULT_RECENT_COMMITS: usize = 10;
+
+/// Response token budget when `num_predict` is "auto": the ceiling commit messages scale
+/// up to with the diff size, and the budget of every other reply.
+const DEFAULT_NUM_PREDICT: i32 = 500;
+
+fn default_git_extensions() -> Vec<String> {
+    DEFAULT_GIT_EXTENSIONS
+        .iter()
+        .map(|e| e.to_string())
+        .collect()
+}
+
+fn default_exclude_patterns() -> Vec<String> {
+    DEFAULT_EXCLUDE_PATTERNS
+        .iter()
+        .map(|e| e.to_string())
+        .collect()
+}
+
+/// Returns `value` of the setting `key` when it is one of `allowed`, otherwise an error
+/// listing them.
+fn one_of(key: &str, value: String, allowed: &[&str]) -> Result<String> {
+    if !allowed.contains(&value.as_str()) {
+        return Err(anyhow!(
+            "Invalid {} '{}': expected one of {}.",
+            key,
+            value,
+            allowed.join(", ")
+        ));
+    }
+    Ok(value)
+}
+
+/// Documentation of every key in `asum.toml` as `(section, key, description)`,
+/// used by `render_schema`.
+pub const FIELD_DOCS: &[(&str, &str, &str)] = &[
+    (
+        "general",
+        "active_provider",
+        "AI provider to use: \"gemini\" or \"ollama\". Overridden by --provider and ASUM_PROVIDER.",
+    ),
+    (
+        "general",
+        "max_diff_length",
+        "Maximum number of characters of the diff sent to the AI; longer diffs are truncated, or summarized in chunks with large_diffs = \"chunk\".",
+    ),
+    (
+        "general",
+        "git_extensions",
+        "Optional. File patterns included in the diff.",
+    ),
+    (
+        "general",
+        "exclude_patterns",
+        "Optional. File patterns left out of the diff even when they match git_extensions, such as lock files. Setting it replaces the defaults: *-lock.json, package-lock.json, pnpm-lock.yaml and *.min.js.",
+    ),
+    (
+        "general",
+        "requests_per_minute",
+        "Optional. Maximum AI requests per minute for batch commands such as changelog; 0 means unlimited. Defaults to 15 for gemini and unlimited for ollama.",
+    ),
+    (
+        "general",
+        "anonymize",
+        "Optional, experimental. Replace identifiers and strings in the diff with placeholders before sending it, and map them back in the message. File paths are still sent.",
+    ),
+    (
+        "general",
+        "secrets",
+        "Optional. What to do with likely secrets in what is sent to the provider (private keys, AWS, GitHub, GitLab, Slack, Google, Stripe and OpenAI-style keys, JWTs, passwords in URLs, values of names like API_KEY or password, long random strings): \"redact\" replaces each with a [REDACTED:<kind>] marker and logs a warning, \"abort\" sends nothing and fails, \"off\" sends them as they are. Defaults to \"redact\".",
+    ),
+    (
+        "general",
+        "repo_profile",
+        "Optional. \"auto\" detects configuration-only repositories (dotfiles, infrastructure as code) and asks for chore/ci/infra types with path-based scopes; \"code\" or \"config\" forces a profile.",
+    ),
+    (
+        "general",
+        "vulnerability_lookup",
+        "Optional. When a dependency manifest changes, ask the OSV database (api.osv.dev) which advisories the version bumps fix and mention them in the message. Sends package names and versions.",
+    ),
+    (
+        "general",
+        "strict_format",
+        "Optional. Holds every commit message to the same format whichever provider is used: providers with structured output return the parts of the message as JSON and asum writes it, and messages that break the rules (Conventional Commits header, lowercase description without a period, subject of at most 72 characters, \"- \" bullets) are requested once more. Defaults to false.",
+    ),
+    (
+        "general",
+        "glossary",
+        "Optional. Product names and other terms the spell-check holds commit messages to, e.g. [\"AsumCloud\", \"GitHub\"]. Identifiers from the diff are added automatically.",
+    ),
+    (
+        "general",
+        "spell_check",
+        "Optional. \"fix\" corrects words in commit messages that are close to a glossary term or a diff identifier, \"flag\" only warns about them, \"off\" disables the check. Defaults to \"fix\".",
+    ),
+    (
+        "general",
+        "message_language",
+        "Optional. Language of commit messages by English name, native name or ISO 639-3 code, e.g. \"German\" or \"deu\". Replies detected in another language are requested once more with a stronger instruction. Empty (the default) leaves the language to the prompt.",
+    ),
+    (
+        "general",
+        "untracked_files",
+        "Optional. Untracked files to show the model as context next to the staged diff: \"names\" lists them, \"content\" adds the start of each text file, \"off\" leaves them out. Defaults to \"off\".",
+    ),
+    (
+        "general",
+        "confirm_send",
+        "Optional. Before a request leaves the machine, show the files, the byte count and the destination host, and ask for confirmation on the terminal; nothing is sent without a yes. Requests fail where there is no terminal to ask on. Defaults to false.",
+    ),
+    (
+        "general",
+        "preflight_report",
+        "Optional. Before each request, log a report of the changed files, insertions and deletions, files left out or summarized, truncation, and the bytes and estimated tokens to send. Always on with --verbose. Defaults to false.",
+    ),
+    (
+        "general",
+        "large_diffs",
+        "Optional. What to do with commit message diffs longer than max_diff_length: \"truncate\" cuts them, \"chunk\" summarizes up to 8 chunks of whole files in parallel and writes the message from those summaries (one extra request per chunk). Not used with anonymize. Defaults to \"truncate\".",
+    ),
+    (
+        "general",
+        "candidate_bodies",
+        "Optional. How -n candidates are generated: \"all\" asks for complete messages, \"picked\" asks for subject lines only and writes the body for the picked subject in one more request. Defaults to \"all\".",
+    ),
+    (
+        "general",
+        "context_window",
+        "Optional. Input tokens the model accepts: a number, \"auto\" to ask the provider (Gemini's input token limit, or the num_ctx an Ollama model sets), or \"off\". When known, the diff is cut to the tokens left after the prompts and num_predict instead of at max_diff_length. Tokens are counted with the cl100k_base tokenizer. Defaults to \"off\".",
+    ),
+    (
+        "general",
+        "clipboard",
+        "Optional. Where messages are copied: \"auto\" tries the desktop clipboard, wl-copy under Wayland, tmux inside tmux and OSC 52 in a terminal (OSC 52 only in remote containers); \"arboard\", \"osc52\", \"wl-copy\" or \"tmux\" use one backend; \"file:<PATH>\" writes a file; \"none\" disables copying. Defaults to \"auto\".",
+    ),
+    (
+        "general",
+        "max_body_bullets",
+        "Optional. Most bullet points a commit message body may have; the prompt asks for at most this many and extra bullets are cut from the reply. 0 means no limit. Defaults to 4.",
+    ),
+    (
+        "general",
+        "recent_commits",
+        "Optional. Number of recent commit subjects {{recent_commits}} lists in the prompts, newest first. Defaults to 10.",
+    ),
+    (
+        "diff",
+        "noise_filters",
+        "Optional. Changes left out of the diff before it is sent, with a note telling the model what was left out: \"whitespace\" drops hunks that only change whitespace, \"imports\" drops import blocks that were only reordered, \"generated\" drops the diffs of files that look generated. Defaults to none.",
+    ),
+    (
+        "diff",
+        "stat_header",
+        "Optional. Whether the prompt starts with the added and removed line counts of every changed file, like git diff --stat, so the model sees the shape of the whole change even when the diff is cut short. Defaults to true.",
+    ),
+    (
+        "diff",
+        "private_paths",
+        "Optional. Paths whose contents are never sent, in gitignore syntax like .asumignore, e.g. \"secrets/\", \"*.pem\" or \"config/prod/*\". Their diffs are replaced by their names with added and removed line counts, and untracked_files = \"content\" lists them by name only. Defaults to none.",
+    ),
+    (
+        "diff",
+        "max_file_diff_length",
+        "Optional. Longest diff of a single file, in bytes, so one huge file such as a snapshot or SQL dump cannot use up the whole diff budget. The diff of a longer file is left out and the file is described in one line with its added and removed line counts. 0 means no limit. Defaults to 0.",
+    ),
+    (
+        "diff",
+        "binary_files",
+        "Optional. Whether staged binary files and Git LFS files are listed after the diff with their size, such as \"binary file added: logo.png (120 KB)\", even when git_extensions leaves them out, and the diffs of LFS pointer files are dropped. Defaults to true.",
+    ),
+    (
+        "conventions",
+        "issue_footers",
+        "Optional. Footers added to commit messages when a file matching a path pattern changes, as \"<pattern> -> <footer>\". The pattern contains {id} once and is matched from the repository root; {id} stands for letters, digits, - and _ with at least one digit, and is replaced in the footer. An empty list turns this off. Defaults to docs/issues/{id}.md and issues/{id}.md, both -> Refs: #{id}.",
+    ),
+    (
+        "conventions",
+        "scopes",
+        "Optional. Scopes commit messages should use, e.g. the crates or packages of a monorepo; the prompt asks for one of them when the change fits. asum setup infers them from the directory layout. Defaults to none.",
+    ),
+    (
+        "conventions",
+        "infer_scope",
+        "Optional. Whether the scope is inferred from the directories a change touches: when every changed file lies in one package of a monorepo (packages/<name>, crates/<name>, apps/<name> and the like) or under one path of the [scopes] table, the message uses that scope. Files in the repository root are ignored. Defaults to true.",
+    ),
+    (
+        "prompts",
+        "system_prompt",
+        "Optional. Identity and rules for the AI (Conventional Commits by default).",
+    ),
+    (
+        "prompts",
+        "user_prompt",
+        "Optional. Template for the user message; {{diff}} is replaced with the staged changes. In this and the other prompts, {{branch}} is replaced with the current branch name and {{recent_commits}} with the latest commit subjects.",
+    ),
+    (
+        "prompts",
+        "truncated_user_prompt",
+        "Optional. Template for the user message of commit messages whose diff was truncated; {{diff}} is replaced with the diff, which starts with a note on what was cut. Empty uses user_prompt.",
+    ),
+    (
+        "ai_params",
+        "num_predict",
+        "Optional. Maximum number of tokens to generate in the response, or \"auto\" (the default) to scale commit messages with the diff size, from a subject line for tiny diffs up to 500 tokens.",
+    ),
+    (
+        "ai_params",
+        "temperature",
+        "Controls randomness: lower is more deterministic.",
+    ),
+    (
+        "ai_params",
+        "top_p",
+        "Nucleus sampling: limits the model to the most likely tokens.",
+    ),
+    (
+        "gemini",
+        "api_key",
+        "Google Gemini API key. Overridden by ASUM_GEMINI_API_KEY or GEMINI_API_KEY.",
+    ),
+    (
+        "gemini",
+        "model",
+        "Gemini model name. Overridden by --model and ASUM_MODEL when gemini is active.",
+    ),
+    (
+        "ollama",
+        "model",
+        "Ollama model name. Overridden by --model and ASUM_MODEL when ollama is active.",
+    ),
+    (
+        "ollama",
+        "url",
+        "Ollama endpoint (/api/chat or /api/generate). Overridden by ASUM_OLLAMA_URL.",
+    ),
+];
+
+/// Documentation of each `asum.toml` section as `(section, description)`.
+pub const SECTION_DOCS: &[(&str, &str)] = &[
+    ("general", "General settings."),
+    (
+        "diff",
+        "Optional. Preprocessing of the diff before it is sent.",
+    ),
+    (
+        "conventions",
+        "Optional. Conventions of the repository applied to commit messages.",
+    ),
+    (
+        "scopes",
+        "Optional. Scopes for path prefixes, as \"<path>\" = \"<scope>\", e.g. \"packages/auth\" = \"auth\". The longest matching path wins, and they take precedence over the scopes inferred from the directory layout.",
+    ),
+    ("prompts", "Optional. Prompt templates sent to the AI."),
+    (
+        "ai_params",
+        "Generation parameters shared by all providers.",
+    ),
+    (
+        "gemini",
+        "Google Gemini provider. Required when active_provider = \"gemini\".",
+    ),
+    (
+        "ollama",
+        "Local Ollama provider. Required when active_provider = \"ollama\".",
+    ),
+];
 
 /// Main configuration structure for the application.
 /// It holds settings for AI providers, git filters, and prompt templates.
@@ -18,16 +355,92 @@ pub struct AsumConfig {
     pub max_diff_length: usize,
     /// List of file extensions to include in the git diff.
     pub git_extensions: Vec<String>,
+    /// File patterns to leave out of the git diff, such as lock files.
+    pub exclude_patterns: Vec<String>,
+    /// Maximum AI requests per minute for batch commands; `None` uses the provider default.
+    pub requests_per_minute: Option<u32>,
+    /// Replace identifiers and strings in the outbound diff with placeholders (experimental).
+    pub anonymize: bool,
+    /// Handling of likely secrets in the outbound text, see `secrets::MODES`.
+    pub secrets: String,
+    /// Repository profile: "auto", "code" or "config" (dotfiles and infrastructure as code).
+    pub repo_profile: String,
+    /// Look up the advisories fixed by dependency bumps in the OSV database.
+    pub vulnerability_lookup: bool,
+    /// Same commit message format for every provider, see `strict`.
+    pub strict_format: bool,
+    /// Most bullet points in a commit message body; 0 means no limit.
+    pub max_body_bullets: usize,
+    /// Commit subjects listed for `{{recent_commits}}`, see `prompt_context`.
+    pub recent_commits: usize,
+    /// Terms commit messages are spell-checked against, besides the diff identifiers.
+    pub glossary: Vec<String>,
+    /// Spell-check of commit messages: "fix", "flag" or "off".
+    pub spell_check: String,
+    /// Untracked files added to the prompt as context: "off", "names" or "content".
+    pub untracked_files: String,
+    /// Language commit messages are asked for and checked against, if any.
+    pub message_language: Option<String>,
+    /// Log what will be sent before each request; also enabled by `--verbose`.
+    pub preflight_report: bool,
+    /// Ask before each request is sent, see `confirm_send`.
+    pub confirm_send: bool,
+    /// Handling of diffs over `max_diff_length`: "truncate" or "chunk" (map-reduce).
+    pub large_diffs: String,
+    /// How `-n` candidates are generated: "all" (complete messages) or "picked"
+    /// (subjects first, then the body of the picked one).
+    pub candidate_bodies: String,
+    /// Clipboard backend, see `clipboard::sink`.
+    pub clipboard: String,
+    /// Noise left out of the diff, see `analysis::noise::FILTERS`.
+    pub noise_filters: Vec<String>,
+    /// Put a per-file line count summary in front of the diff, see `analysis::stat`.
+    pub stat_header: bool,
+    /// Paths whose contents are never sent, see `private_paths`.
+    pub private_paths: Vec<String>,
+    /// Longest diff of one file before it is left out, see `analysis::oversized`.
+    pub max_file_diff_length: usize,
+    /// List staged binary and Git LFS files after the diff, see `binary_files`.
+    pub binary_files: bool,
+    /// `(pattern, footer)` pairs of `issue_footers`, see `footers::issue_footers`.
+    pub issue_footers: Vec<(String, String)>,
+    /// Scopes the prompt asks commit messages to use.
+    pub scopes: Vec<String>,
+    /// Infer the scope from the directories a change touches, see `scope::infer`.
+    pub infer_scope: bool,
+    /// `(path, scope)` pairs of the `[scopes]` table, see `scope::infer`.
+    pub scope_paths: Vec<(String, String)>,
+    /// Input tokens the model accepts; when set, the diff is cut to fit it instead of
+    /// at `max_diff_length`.
+    pub context_window: Option<usize>,
+    /// `context_window` is "auto": the provider is asked for it before each request.
+    pub context_window_auto: bool,
+    /// Print the prompt instead of sending it (`--dry-run`); never read from the file.
+    #[serde(skip)]
+    pub dry_run: bool,
+    /// Ignore cached messages (`--no-cache`); never read from the file.
+    #[serde(skip)]
+    pub no_cache: bool,
+    /// The request is for a commit message, so the reply is trimmed to
+    /// `max_body_bullets` and spell-checked; set by `pipeline::commit_message_prompts`,
+    /// never read from the file.
+    #[serde(skip)]
+    pub commit_message: bool,
     /// System-level instruction for the AI model.
     pub system_prompt: String,
     /// User-level prompt template containing the {{diff}} placeholder.
     pub user_prompt: String,
+    /// User prompt template for commit messages whose diff was truncated, if any.
+    pub truncated_user_prompt: Option<String>,
     /// Controls randomness: lower is more deterministic.
     pub ai_temperature: f64,
     /// Nucleus sampling: limits the model to the most likely tokens.
     pub ai_top_p: f64,
     /// Maximum number of tokens to generate in the response.
     pub ai_num_predict: i32,
+    /// `num_predict` is "auto": commit messages get a budget scaled with the diff size,
+    /// capped at `ai_num_predict`.
+    pub ai_num_predict_auto: bool,
     /// Base URL for the Ollama API.
     pub ollama_url: Option<String>,
     /// Model name for Ollama (e.g., "llama3").
@@ -42,6 +455,9 @@ pub struct AsumConfig {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 struct TomlConfig {
     pub general: GeneralConfig,
+    pub diff: Option<DiffConfig>,
+    pub conventions: Option<ConventionsConfig>,
+    pub scopes: Option<BTreeMap<String, String>>,
     pub prompts: Option<PromptsConfig>,
     pub ai_params: AIParamsConfig,
     pub gemini: Option<GeminiConfig>,
@@ -53,21 +469,73 @@ struct GeneralConfig {
     pub active_provider: String,
     pub max_diff_length: usize,
     pub git_extensions: Option<Vec<String>>,
+    pub exclude_patterns: Option<Vec<String>>,
+    pub requests_per_minute: Option<u32>,
+    pub anonymize: Option<bool>,
+    pub secrets: Option<String>,
+    pub repo_profile: Option<String>,
+    pub vulnerability_lookup: Option<bool>,
+    pub strict_format: Option<bool>,
+    pub max_body_bullets: Option<usize>,
+    pub recent_commits: Option<usize>,
+    pub glossary: Option<Vec<String>>,
+    pub spell_check: Option<String>,
+    pub untracked_files: Option<String>,
+    pub message_language: Option<String>,
+    pub preflight_report: Option<bool>,
+    pub confirm_send: Option<bool>,
+    pub large_diffs: Option<String>,
+    pub candidate_bodies: Option<String>,
+    pub clipboard: Option<String>,
+    pub context_window: Option<ContextWindow>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct DiffConfig {
+    pub noise_filters: Option<Vec<String>>,
+    pub stat_header: Option<bool>,
+    pub private_paths: Option<Vec<String>>,
+    pub max_file_diff_length: Option<usize>,
+    pub binary_files: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct ConventionsConfig {
+    pub issue_footers: Option<Vec<String>>,
+    pub scopes: Option<Vec<String>>,
+    pub infer_scope: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 struct PromptsConfig {
     pub system_prompt: Option<String>,
     pub user_prompt: Option<String>,
+    pub truncated_user_prompt: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 struct AIParamsConfig {
-    pub num_predict: i32,
+    pub num_predict: Option<NumPredict>,
     pub temperature: f64,
     pub top_p: f64,
 }
 
+/// `num_predict` as written in the file: a number of tokens or "auto".
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(untagged)]
+enum NumPredict {
+    Tokens(i32),
+    Keyword(String),
+}
+
+/// `context_window` as written in the file: a number of tokens, "auto" or "off".
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(untagged)]
+enum ContextWindow {
+    Tokens(usize),
+    Keyword(String),
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 struct GeminiConfig {
     pub api_key: String,
@@ -84,80 +552,236 @@ impl AsumConfig {
     /// Loads configuration by searching for 'asum.toml' in the current directory,
     /// then falling back to '~/.asum/asum.toml'.
     pub fn load() -> Result<Self> {
-        // 1. Check local config
-        let local_path = Path::new("asum.toml");
-        if local_path.exists() {
-            return Self::load_from_toml(local_path)
-                .with_context(|| format!("Failed to load local config: {:?}", local_path));
+        match resolve_config_path() {
+            Some(path) => Self::load_from_toml(&path)
+                .with_context(|| format!("Failed to load config: {:?}", path)),
+            None => Err(anyhow!(
+                "Configuration file 'asum.toml' not found locally or in ~/.asum/asum.toml. Run 'asum init' to create one."
+            )),
+        }
+    }
+
+    /// Loads configuration from an explicit path, e.g. one passed via `--config`.
+    pub fn load_from<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(anyhow!("Configuration file {:?} not found", path));
         }
+        Self::load_from_toml(path).with_context(|| format!("Failed to load config: {:?}", path))
+    }
 
-        // 2. Check global config
-        let mut global_path =
-            home::home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
-        global_path.push(".asum");
-        global_path.push("asum.toml");
+    /// Applies command line overrides on top of the values loaded from the file.
+    /// The model override targets whichever provider is active after the provider override.
+    pub fn apply_overrides(&mut self, provider: Option<&str>, model: Option<&str>) {
+        if let Some(provider) = provider {
+            self.active_provider = provider.to_string();
+        }
+        if let Some(model) = model {
+            match self.active_provider.as_str() {
+                "gemini" => self.gemini_model = Some(model.to_string()),
+                "ollama" => self.ollama_model = Some(model.to_string()),
+                _ => {}
+            }
+        }
+    }
 
-        if global_path.exists() {
-            return Self::load_from_toml(&global_path)
-                .with_context(|| format!("Failed to load global config: {:?}", global_path));
+    /// Returns the git pathspecs selecting the files to summarize: the
+    /// `git_extensions`, then each of the `exclude_patterns` as an `:(exclude)` pathspec.
+    pub fn pathspecs(&self) -> Vec<String> {
+        let mut specs = self.git_extensions.clone();
+        specs.extend(
+            self.exclude_patterns
+                .iter()
+                .map(|pattern| format!("{}{}", EXCLUDE_MAGIC, pattern)),
+        );
+        specs
+    }
+
+    /// Returns the model configured for the active provider.
+    pub fn active_model(&self) -> Option<&str> {
+        match self.active_provider.as_str() {
+            "gemini" => self.gemini_model.as_deref(),
+            "ollama" => self.ollama_model.as_deref(),
+            _ => None,
         }
+    }
 
-        Err(anyhow!(
-            "Configuration file 'asum.toml' not found locally or in ~/.asum/asum.toml"
-        ))
+    /// Builds a configuration from the recommended defaults (the same values `asum init`
+    /// writes), for environments that are configured through environment variables only.
+    pub fn from_defaults() -> Result<Self> {
+        let mut config = Self::from_toml_str(&render_config(&InitSettings {
+            provider: "ollama".to_string(),
+            model: "qwen2.5-coder:3b".to_string(),
+            api_key: None,
+            ollama_url: None,
+            scopes: Vec::new(),
+            strict_format: false,
+        })?)?;
+        config.gemini_model = Some("gemini-2.0-flash".to_string());
+        Ok(config)
+    }
+
+    /// Applies overrides from `ASUM_*` environment variables, looked up through `var`.
+    ///
+    /// Supported variables: `ASUM_PROVIDER`, `ASUM_MODEL`, `ASUM_OLLAMA_URL` and
+    /// `ASUM_GEMINI_API_KEY` (falling back to `GEMINI_API_KEY`).
+    pub fn apply_env<F: Fn(&str) -> Option<String>>(&mut self, var: F) {
+        let var = |name: &str| var(name).filter(|value| !value.is_empty());
+
+        self.apply_overrides(
+            var("ASUM_PROVIDER").as_deref(),
+            var("ASUM_MODEL").as_deref(),
+        );
+        if let Some(url) = var("ASUM_OLLAMA_URL") {
+            self.ollama_url = Some(url);
+        }
+        if let Some(key) = var("ASUM_GEMINI_API_KEY").or_else(|| var("GEMINI_API_KEY")) {
+            self.gemini_api_key = Some(key);
+        }
     }
 
     /// Reads and parses a TOML configuration file from the specified path.
     /// Fills in default values for missing optional fields.
     fn load_from_toml<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = fs::read_to_string(path)?;
-        let toml_config: TomlConfig = toml::from_str(&content)?;
-
-        let default_extensions = vec![
-            "*.java", "*.php", "*.js", "*.jsx", "*.ts", "*.tsx", "*.vue", "*.svelte", "*.scss",
-            "*.css", "*.html", "*.rs", "*.py", "*.pyi", "*.go", "*.c", "*.cpp", "*.h", "*.hpp",
-            "*.cs", "*.rb", "*.swift", "*.kt", "*.kts", "*.dart", "*.sh", "*.sql", "*.md", "*.yml",
-            "*.yaml", "*.toml", "*.json",
-        ]
-        .into_iter()
-        .map(String::from)
-        .collect();
-
-        let default_system_prompt = r#"# SYSTEM IDENTITY
-You are an expert Git Commit Generator. Your goal is to produce high-quality, professional commit messages following Conventional Commits 1.0.0.
-
-# STRICT RULES
-1. MANDATORY HEADER: Every response MUST start with `<type>(<scope>): <description>`.
-2. TYPES: Only use: feat, fix, docs, style, refactor, perf, test, build, ci, chore, revert.
-3. DESCRIPTION: Use imperative mood, lowercase, no period, max 50 chars.
-4. BODY (OPTIONAL): Use bullet points ("- ") to explain "what" and "why".
-5. OUTPUT: Return ONLY the raw commit message. No preamble, no backticks, no markdown blocks.
-
-# FEW-SHOT EXAMPLES
-
-Example 1 (Simple Fix):
-fix(ui): correct button alignment on mobile
-
-Example 2 (Feature with Body):
-feat(auth): implement oauth2 login flow
-
-- add google and github provider support
-- implement secure callback handling
-- encrypt user tokens before storage
-
-Example 3 (Breaking Change):
-refactor(api)!: migrate to async/await syntax
-
-- rewrite all controllers to be non-blocking
-- update database driver to support pooling
-
-BREAKING CHANGE: the synchronous API is no longer supported."#.to_string();
-
-        let default_user_prompt = r#"[INPUT DIFF]
-{{diff}}
+        Self::from_toml_str(&content)
+    }
 
-[OUTPUT]"#
-            .to_string();
+    /// Parses TOML configuration content and fills in defaults for optional fields.
+    fn from_toml_str(content: &str) -> Result<Self> {
+        let toml_config: TomlConfig = toml::from_str(content)?;
+        let (ai_num_predict, ai_num_predict_auto) = match &toml_config.ai_params.num_predict {
+            Some(NumPredict::Tokens(tokens)) => (*tokens, false),
+            Some(NumPredict::Keyword(keyword)) if keyword != "auto" => {
+                return Err(anyhow!(
+                    "Invalid num_predict '{}': expected a number of tokens or \"auto\".",
+                    keyword
+                ));
+            }
+            _ => (DEFAULT_NUM_PREDICT, true),
+        };
+        let noise_filters = toml_config
+            .diff
+            .as_ref()
+            .and_then(|d| d.noise_filters.clone())
+            .unwrap_or_default();
+        if let Some(unknown) = noise_filters
+            .iter()
+            .find(|f| !noise::FILTERS.contains(&f.as_str()))
+        {
+            return Err(anyhow!(
+                "Invalid noise filter '{}': expected one of {}.",
+                unknown,
+                noise::FILTERS.join(", ")
+            ));
+        }
+        let issue_footers = toml_config
+            .conventions
+            .as_ref()
+            .and_then(|c| c.issue_footers.clone())
+            .unwrap_or_else(|| {
+                footers::DEFAULT_ISSUE_FOOTERS
+                    .iter()
+                    .map(|mapping| mapping.to_string())
+                    .collect()
+            })
+            .iter()
+            .map(|mapping| {
+                footers::parse_mapping(mapping).ok_or_else(|| {
+                    anyhow!(
+                        "Invalid issue_footers entry '{}': expected \"<pattern> -> <footer>\" with {{id}} once in the pattern.",
+                        mapping
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let scope_paths = toml_config
+            .scopes
+            .iter()
+            .flatten()
+            .map(|(path, scope)| {
+                let (path, scope) = (path.trim().trim_matches('/'), scope.trim());
+                if path.is_empty() || scope.is_empty() {
+                    return Err(anyhow!(
+                        "Invalid [scopes] entry \"{}\" = \"{}\": expected a path and a scope.",
+                        path,
+                        scope
+                    ));
+                }
+                Ok((path.to_string(), scope.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let secrets = one_of(
+            "secrets",
+            toml_config
+                .general
+                .secrets
+                .clone()
+                .unwrap_or_else(|| "redact".to_string()),
+            secrets::MODES,
+        )?;
+        let large_diffs = one_of(
+            "large_diffs",
+            toml_config
+                .general
+                .large_diffs
+                .clone()
+                .unwrap_or_else(|| "truncate".to_string()),
+            chunks::MODES,
+        )?;
+        let candidate_bodies = one_of(
+            "candidate_bodies",
+            toml_config
+                .general
+                .candidate_bodies
+                .clone()
+                .unwrap_or_else(|| "all".to_string()),
+            pipeline::CANDIDATE_BODIES,
+        )?;
+        let clipboard = toml_config
+            .general
+            .clipboard
+            .clone()
+            .unwrap_or_else(|| "auto".to_string());
+        clipboard::sink(&clipboard)?;
+        let spell_check = one_of(
+            "spell_check",
+            toml_config
+                .general
+                .spell_check
+                .clone()
+                .unwrap_or_else(|| "fix".to_string()),
+            spelling::MODES,
+        )?;
+        let untracked_files = one_of(
+            "untracked_files",
+            toml_config
+                .general
+                .untracked_files
+                .clone()
+                .unwrap_or_else(|| "off".to_string()),
+            untracked::MODES,
+        )?;
+        let repo_profile = one_of(
+            "repo_profile",
+            toml_config
+                .general
+                .repo_profile
+                .clone()
+                .unwrap_or_else(|| "auto".to_string()),
+            profile::PROFILES,
+        )?;
+        let (context_window, context_window_auto) = match &toml_config.general.context_window {
+            Some(ContextWindow::Tokens(tokens)) => (Some(*tokens), false),
+            Some(ContextWindow::Keyword(keyword)) if keyword == "auto" => (None, true),
+            Some(ContextWindow::Keyword(keyword)) if keyword != "off" => {
+                return Err(anyhow!(
+                    "Invalid context_window '{}': expected a number of tokens, \"auto\" or \"off\".",
+                    keyword
+                ));
+            }
+            _ => (None, false),
+        };
 
         Ok(AsumConfig {
             active_provider: toml_config.general.active_provider,
@@ -165,20 +789,94 @@ BREAKING CHANGE: the synchronous API is no longer supported."#.to_string();
             git_extensions: toml_config
                 .general
                 .git_extensions
-                .unwrap_or(default_extensions),
+                .unwrap_or_else(default_git_extensions),
+            exclude_patterns: toml_config
+                .general
+                .exclude_patterns
+                .unwrap_or_else(default_exclude_patterns),
+            requests_per_minute: toml_config.general.requests_per_minute,
+            anonymize: toml_config.general.anonymize.unwrap_or(false),
+            secrets,
+            repo_profile,
+            vulnerability_lookup: toml_config.general.vulnerability_lookup.unwrap_or(false),
+            strict_format: toml_config.general.strict_format.unwrap_or(false),
+            max_body_bullets: toml_config
+                .general
+                .max_body_bullets
+                .unwrap_or(DEFAULT_MAX_BODY_BULLETS),
+            recent_commits: toml_config
+                .general
+                .recent_commits
+                .unwrap_or(DEFAULT_RECENT_COMMITS),
+            glossary: toml_config.general.glossary.unwrap_or_default(),
+            spell_check,
+            untracked_files,
+            message_language: toml_config
+                .general
+                .message_language
+                .filter(|language| !language.trim().is_empty()),
+            preflight_report: toml_config.general.preflight_report.unwrap_or(false),
+            confirm_send: toml_config.general.confirm_send.unwrap_or(false),
+            large_diffs,
+            candidate_bodies,
+            clipboard,
+            noise_filters,
+            stat_header: toml_config
+                .diff
+                .as_ref()
+                .and_then(|d| d.stat_header)
+                .unwrap_or(true),
+            private_paths: toml_config
+                .diff
+                .as_ref()
+                .and_then(|d| d.private_paths.clone())
+                .unwrap_or_default(),
+            max_file_diff_length: toml_config
+                .diff
+                .as_ref()
+                .and_then(|d| d.max_file_diff_length)
+                .unwrap_or(0),
+            binary_files: toml_config
+                .diff
+                .as_ref()
+                .and_then(|d| d.binary_files)
+                .unwrap_or(true),
+            issue_footers,
+            scopes: toml_config
+                .conventions
+                .as_ref()
+                .and_then(|c| c.scopes.clone())
+                .unwrap_or_default(),
+            infer_scope: toml_config
+                .conventions
+                .as_ref()
+                .and_then(|c| c.infer_scope)
+                .unwrap_or(true),
+            scope_paths,
+            context_window,
+            context_window_auto,
+            dry_run: false,
+            no_cache: false,
+            commit_message: false,
             system_prompt: toml_config
                 .prompts
                 .as_ref()
                 .and_then(|p| p.system_prompt.clone())
-                .unwrap_or(default_system_prompt),
+                .unwrap_or_else(|| DEFAULT_SYSTEM_PROMPT.to_string()),
             user_prompt: toml_config
                 .prompts
                 .as_ref()
                 .and_then(|p| p.user_prompt.clone())
-                .unwrap_or(default_user_prompt),
+                .unwrap_or_else(|| DEFAULT_USER_PROMPT.to_string()),
+            truncated_user_prompt: toml_config
+                .prompts
+                .as_ref()
+                .and_then(|p| p.truncated_user_prompt.clone())
+                .filter(|prompt| !prompt.trim().is_empty()),
             ai_temperature: toml_config.ai_params.temperature,
             ai_top_p: toml_config.ai_params.top_p,
-            ai_num_predict: toml_config.ai_params.num_predict,
+            ai_num_predict,
+            ai_num_predict_auto,
             ollama_url: toml_config.ollama.as_ref().map(|o| o.url.clone()),
             ollama_model: toml_config.ollama.as_ref().map(|o| o.model.clone()),
             gemini_api_key: toml_config.gemini.as_ref().map(|g| g.api_key.clone()),
@@ -187,10 +885,306 @@ BREAKING CHANGE: the synchronous API is no longer supported."#.to_string();
     }
 }
 
+/// Returns the ASUM home directory (`~/.asum`), if the home directory can be determined.
+pub fn asum_home() -> Option<PathBuf> {
+    home::home_dir().map(|home| home.join(".asum"))
+}
+
+/// Returns the directory where log files are written (`~/.asum/logs`).
+pub fn log_dir() -> Option<PathBuf> {
+    asum_home().map(|dir| dir.join("logs"))
+}
+
+/// Returns the path of the local database (`~/.asum/asum.db`).
+pub fn db_path() -> Option<PathBuf> {
+    asum_home().map(|dir| dir.join("asum.db"))
+}
+
+/// Returns the path of the restore point (`~/.asum/last-message.txt`), where a message
+/// that could not be delivered anywhere is kept.
+pub fn last_message_path() -> Option<PathBuf> {
+    asum_home().map(|dir| dir.join("last-message.txt"))
+}
+
+/// Returns the configuration file `AsumConfig::load` would use: a local 'asum.toml'
+/// first, then '~/.asum/asum.toml'. Returns `None` if neither exists.
+pub fn resolve_config_path() -> Option<PathBuf> {
+    let local_path = PathBuf::from("asum.toml");
+    if local_path.exists() {
+        return Some(local_path);
+    }
+
+    asum_home()
+        .map(|dir| dir.join("asum.toml"))
+        .filter(|path| path.exists())
+}
+
+/// Settings collected by `asum init` to bootstrap a new configuration file.
+#[derive(Debug, Clone)]
+pub struct InitSettings {
+    pub provider: String,
+    pub model: String,
+    pub api_key: Option<String>,
+    pub ollama_url: Option<String>,
+    /// `[conventions] scopes`, written only when not empty.
+    pub scopes: Vec<String>,
+    /// `[general] strict_format`, written only when set.
+    pub strict_format: bool,
+}
+
+/// Renders a complete `asum.toml` for the given settings, using the recommended
+/// defaults for every other value.
+pub fn render_config(settings: &InitSettings) -> Result<String> {
+    let toml_config = TomlConfig {
+        general: GeneralConfig {
+            active_provider: settings.provider.clone(),
+            max_diff_length: 36000,
+            git_extensions: None,
+            exclude_patterns: None,
+            requests_per_minute: None,
+            anonymize: None,
+            secrets: None,
+            repo_profile: None,
+            vulnerability_lookup: None,
+            strict_format: settings.strict_format.then_some(true),
+            max_body_bullets: None,
+            recent_commits: None,
+            glossary: None,
+            spell_check: None,
+            untracked_files: None,
+            message_language: None,
+            preflight_report: None,
+            confirm_send: None,
+            large_diffs: None,
+            candidate_bodies: None,
+            clipboard: None,
+            context_window: None,
+        },
+        diff: None,
+        conventions: (!settings.scopes.is_empty()).then(|| ConventionsConfig {
+            issue_footers: None,
+            scopes: Some(settings.scopes.clone()),
+            infer_scope: None,
+        }),
+        scopes: None,
+        prompts: None,
+        ai_params: AIParamsConfig {
+            num_predict: Some(NumPredict::Keyword("auto".to_string())),
+            temperature: 0.1,
+            top_p: 0.9,
+        },
+        gemini: (settings.provider == "gemini").then(|| GeminiConfig {
+            api_key: settings.api_key.clone().unwrap_or_default(),
+            model: settings.model.clone(),
+        }),
+        ollama: (settings.provider == "ollama").then(|| OllamaConfig {
+            model: settings.model.clone(),
+            url: settings
+                .ollama_url
+                .clone()
+                .unwrap_or_else(|| "http://localhost:11434/api/chat".to_string()),
+        }),
+    };
+
+    Ok(toml::to_string(&toml_config)?)
+}
+
+/// Renders a fully commented example `asum.toml` with every section and option set to
+/// its default. The file is serialized from `AsumConfig::from_defaults` and each key is
+/// annotated from `FIELD_DOCS`, so new options fail loudly until they are documented.
+/// Options without a default value, listed in `UNSET_EXAMPLES`, are shown commented out.
+pub fn render_schema() -> Result<String> {
+    let mut defaults = AsumConfig::from_defaults()?;
+    defaults.gemini_api_key.get_or_insert_default();
+    let toml_config = toml_config_of(&defaults);
+    let rendered = toml::to_string(&toml_config)?;
+    let mut schema = String::from(
+        "# asum.toml reference. Every option is shown with its default value.\n\
+         # Lookup order: --config, ./asum.toml, ~/.asum/asum.toml.\n",
+    );
+    let mut section = "";
+    let mut in_multiline = false;
+    let mut keys = Vec::new();
+
+    for line in rendered.lines() {
+        if !in_multiline {
+            // Blank lines are re-inserted above each comment
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                push_unset_examples(&mut schema, section, &keys)?;
+                section = name;
+                keys.clear();
+                let doc = SECTION_DOCS
+                    .iter()
+                    .find(|(s, _)| *s == section)
+                    .map(|(_, doc)| *doc)
+                    .ok_or_else(|| anyhow!("Missing documentation for section [{}]", section))?;
+                schema.push_str(&format!("\n# {}\n", doc));
+            } else if let Some((key, _)) = line.split_once(" = ") {
+                let doc = FIELD_DOCS
+                    .iter()
+                    .find(|(s, k, _)| *s == section && *k == key)
+                    .map(|(_, _, doc)| *doc)
+                    .ok_or_else(|| anyhow!("Missing documentation for {}.{}", section, key))?;
+                schema.push_str(&format!("\n# {}\n", doc));
+                keys.push(key);
+            }
+        }
+        if line.matches("\"\"\"").count() % 2 == 1 {
+            in_multiline = !in_multiline;
+        }
+        schema.push_str(line);
+        schema.push('\n');
+    }
+    push_unset_examples(&mut schema, section, &keys)?;
+
+    Ok(schema)
+}
+
+/// Options of `render_schema` that are unset by default, where any value written
+/// would change the behavior: (section, key, example value).
+const UNSET_EXAMPLES: &[(&str, &str, &str)] = &[("general", "requests_per_minute", "15")];
+
+/// Appends the documented options of `section` that are not among its rendered
+/// `keys`, commented out with their example from `UNSET_EXAMPLES`.
+fn push_unset_examples(schema: &mut String, section: &str, keys: &[&str]) -> Result<()> {
+    for (_, key, doc) in FIELD_DOCS
+        .iter()
+        .filter(|(s, key, _)| *s == section && !keys.contains(key))
+    {
+        let example = UNSET_EXAMPLES
+            .iter()
+            .find(|(s, k, _)| *s == section && k == key)
+            .map(|(_, _, example)| *example)
+            .ok_or_else(|| anyhow!("Missing default for {}.{}", section, key))?;
+        schema.push_str(&format!("\n# {}\n# {} = {}\n", doc, key, example));
+    }
+    Ok(())
+}
+
+/// The file layout that `AsumConfig::from_toml_str` reads back as `config`.
+fn toml_config_of(config: &AsumConfig) -> TomlConfig {
+    TomlConfig {
+        general: GeneralConfig {
+            active_provider: config.active_provider.clone(),
+            max_diff_length: config.max_diff_length,
+            git_extensions: Some(config.git_extensions.clone()),
+            exclude_patterns: Some(config.exclude_patterns.clone()),
+            requests_per_minute: config.requests_per_minute,
+            anonymize: Some(config.anonymize),
+            secrets: Some(config.secrets.clone()),
+            repo_profile: Some(config.repo_profile.clone()),
+            vulnerability_lookup: Some(config.vulnerability_lookup),
+            strict_format: Some(config.strict_format),
+            max_body_bullets: Some(config.max_body_bullets),
+            recent_commits: Some(config.recent_commits),
+            glossary: Some(config.glossary.clone()),
+            spell_check: Some(config.spell_check.clone()),
+            untracked_files: Some(config.untracked_files.clone()),
+            message_language: Some(config.message_language.clone().unwrap_or_default()),
+            preflight_report: Some(config.preflight_report),
+            confirm_send: Some(config.confirm_send),
+            large_diffs: Some(config.large_diffs.clone()),
+            candidate_bodies: Some(config.candidate_bodies.clone()),
+            clipboard: Some(config.clipboard.clone()),
+            context_window: Some(match config.context_window {
+                Some(tokens) => ContextWindow::Tokens(tokens),
+                None if config.context_window_auto => ContextWindow::Keyword("auto".to_string()),
+                None => ContextWindow::Keyword("off".to_string()),
+            }),
+        },
+        diff: Some(DiffConfig {
+            noise_filters: Some(config.noise_filters.clone()),
+            stat_header: Some(config.stat_header),
+            private_paths: Some(config.private_paths.clone()),
+            max_file_diff_length: Some(config.max_file_diff_length),
+            binary_files: Some(config.binary_files),
+        }),
+        conventions: Some(ConventionsConfig {
+            issue_footers: Some(
+                config
+                    .issue_footers
+                    .iter()
+                    .map(|(pattern, footer)| format!("{} {} {}", pattern, footers::ARROW, footer))
+                    .collect(),
+            ),
+            scopes: Some(config.scopes.clone()),
+            infer_scope: Some(config.infer_scope),
+        }),
+        scopes: Some(config.scope_paths.iter().cloned().collect()),
+        prompts: Some(PromptsConfig {
+            system_prompt: Some(config.system_prompt.clone()),
+            user_prompt: Some(config.user_prompt.clone()),
+            truncated_user_prompt: Some(config.truncated_user_prompt.clone().unwrap_or_default()),
+        }),
+        ai_params: AIParamsConfig {
+            num_predict: Some(if config.ai_num_predict_auto {
+                NumPredict::Keyword("auto".to_string())
+            } else {
+                NumPredict::Tokens(config.ai_num_predict)
+            }),
+            temperature: config.ai_temperature,
+            top_p: config.ai_top_p,
+        },
+        gemini: config.gemini_model.as_ref().map(|model| GeminiConfig {
+            api_key: config.gemini_api_key.clone().unwrap_or_default(),
+            model: model.clone(),
+        }),
+        ollama: config.ollama_model.as_ref().map(|model| OllamaConfig {
+            model: model.clone(),
+            url: config.ollama_url.clone().unwrap_or_default(),
+        }),
+    }
+}
+
+/// Sets `model` in the `[provider]` section of an existing configuration file,
+/// keeping the rest of the file (including comments) untouched.
+pub fn set_model<P: AsRef<Path>>(path: P, provider: &str, model: &str) -> Result<()> {
+    let path = path.as_ref();
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut document: toml_edit::DocumentMut = content
+        .parse()
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    let section = document
+        .get_mut(provider)
+        .and_then(|item| item.as_table_like_mut())
+        .ok_or_else(|| anyhow!("No [{}] section in {}", provider, path.display()))?;
+    section.insert("model", toml_edit::value(model));
+
+    fs::write(path, document.to_string())
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Sets `key` (`system_prompt` or `user_prompt`) in the `[prompts]` section of an
+/// existing configuration file, adding the section when needed and keeping the rest
+/// of the file untouched. Prompts with line breaks are written as multiline strings.
+pub fn set_prompt<P: AsRef<Path>>(path: P, key: &str, prompt: &str) -> Result<()> {
+    let path = path.as_ref();
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut document: toml_edit::DocumentMut = content
+        .parse()
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    let section = document
+        .entry("prompts")
+        .or_insert_with(toml_edit::table)
+        .as_table_like_mut()
+        .ok_or_else(|| anyhow!("'prompts' is not a table in {}", path.display()))?;
+    section.insert(key, toml_edit::value(prompt));
+
+    fs::write(path, document.to_string())
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
 /// Validates that a TOML file follows the expected schema.
 pub fn verify_toml<P: AsRef<Path>>(path: P) -> Result<()> {
     let content = fs::read_to_string(path)?;
-    let _: TomlConfig = toml::from_str(&content)?;
+    AsumConfig::from_toml_str(&content)?;
     Ok(())
 }
 
@@ -201,6 +1195,126 @@ mod tests {
     use std::io::Write;
     use tempfile::NamedTempFile;
 
+    #[test]
+    fn test_render_schema_documents_every_option() {
+        let schema = render_schema().unwrap();
+
+        // The reference is itself a valid configuration holding the defaults
+        let config = AsumConfig::from_toml_str(&schema).unwrap();
+        assert_eq!(config.git_extensions, default_git_extensions());
+        assert_eq!(config.system_prompt, DEFAULT_SYSTEM_PROMPT);
+        assert_eq!(config.user_prompt, DEFAULT_USER_PROMPT);
+        assert_eq!(config.gemini_model.as_deref(), Some("gemini-2.0-flash"));
+        assert_eq!(config.ollama_model.as_deref(), Some("qwen2.5-coder:3b"));
+
+        for (section, _) in SECTION_DOCS {
+            assert!(schema.contains(&format!("[{}]", section)), "{}", section);
+        }
+        for (section, key, doc) in FIELD_DOCS {
+            assert!(
+                schema.contains(&format!("# {}\n{} = ", doc, key))
+                    || schema.contains(&format!("# {}\n# {} = ", doc, key)),
+                "{}.{} is not documented",
+                section,
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_schema_round_trips_every_default() {
+        let schema = render_schema().unwrap();
+        let mut defaults = AsumConfig::from_defaults().unwrap();
+        defaults.gemini_api_key = Some(String::new());
+        let parsed = AsumConfig::from_toml_str(&schema).unwrap();
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::to_value(&defaults).unwrap()
+        );
+
+        // Writing 0 would turn off the rate limit the provider default sets
+        assert_eq!(parsed.requests_per_minute, None);
+        assert!(
+            schema.contains("\n# requests_per_minute = 15\n"),
+            "{}",
+            schema
+        );
+    }
+
+    #[test]
+    fn test_set_prompt_table_driven() {
+        let base = "# my settings\n[general]\nactive_provider = \"ollama\"\nmax_diff_length = 1000\n\n\
+                    [ai_params]\nnum_predict = 100\ntemperature = 0.5\ntop_p = 0.9\n";
+        let cases = vec![
+            (base.to_string(), "system_prompt", "Be brief.\nUse English."),
+            (
+                format!("{}\n[prompts]\nuser_prompt = \"old {{{{diff}}}}\"\n", base),
+                "user_prompt",
+                "Diff:\n{{diff}}",
+            ),
+        ];
+
+        for (content, key, prompt) in cases {
+            let mut file = NamedTempFile::new().unwrap();
+            write!(file, "{}", content).unwrap();
+
+            set_prompt(file.path(), key, prompt).unwrap();
+            let written = fs::read_to_string(file.path()).unwrap();
+            assert!(written.starts_with("# my settings\n"));
+            assert!(written.contains("\"\"\""), "not multiline: {}", written);
+
+            let config = AsumConfig::load_from(file.path()).unwrap();
+            let loaded = match key {
+                "system_prompt" => config.system_prompt,
+                _ => config.user_prompt,
+            };
+            assert_eq!(loaded, prompt);
+        }
+    }
+
+    #[test]
+    fn test_set_model_preserves_file() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(
+            file,
+            r#"# my settings
+[general]
+active_provider = "ollama"
+max_diff_length = 1000
+
+[ai_params]
+num_predict = 100
+temperature = 0.5
+top_p = 0.9
+
+[ollama]
+model = "llama3" # old model
+url = "http://localhost:11434/api/chat"
+"#
+        )
+        .unwrap();
+
+        set_model(file.path(), "ollama", "qwen2.5-coder:7b").unwrap();
+        let content = fs::read_to_string(file.path()).unwrap();
+        assert!(content.starts_with("# my settings\n"));
+        assert!(content.contains("model = \"qwen2.5-coder:7b\""));
+        assert_eq!(
+            AsumConfig::load_from(file.path())
+                .unwrap()
+                .ollama_model
+                .as_deref(),
+            Some("qwen2.5-coder:7b")
+        );
+
+        let result = set_model(file.path(), "gemini", "gemini-pro");
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("No [gemini] section")
+        );
+    }
+
     #[test]
     fn test_load_from_toml_full() {
         let mut file = NamedTempFile::new().unwrap();
@@ -211,6 +1325,7 @@ mod tests {
             active_provider = "gemini"
             max_diff_length = 1000
             git_extensions = [".rs", ".py"]
+            exclude_patterns = ["Cargo.lock", "*.pb.go"]
 
             [ai_params]
             num_predict = 100
@@ -228,6 +1343,10 @@ mod tests {
         assert_eq!(config.active_provider, "gemini");
         assert_eq!(config.max_diff_length, 1000);
         assert_eq!(config.git_extensions, vec![".rs", ".py"]);
+        assert_eq!(
+            config.pathspecs(),
+            vec![".rs", ".py", ":(exclude)Cargo.lock", ":(exclude)*.pb.go"]
+        );
         assert_eq!(config.gemini_api_key.unwrap(), "test_key");
         assert_eq!(config.gemini_model.unwrap(), "gemini-pro");
     }
@@ -255,10 +1374,251 @@ mod tests {
         // Check if default extensions are loaded
         assert!(!config.git_extensions.is_empty());
         assert!(config.git_extensions.contains(&"*.rs".to_string()));
+        assert!(
+            config
+                .exclude_patterns
+                .contains(&"package-lock.json".to_string())
+        );
         // Check if default system prompt is loaded
         assert!(config.system_prompt.contains("expert Git Commit Generator"));
     }
 
+    #[test]
+    fn test_num_predict_table_driven() {
+        let cases = [
+            ("pinned", "num_predict = 250", Some((250, false))),
+            (
+                "auto",
+                "num_predict = \"auto\"",
+                Some((DEFAULT_NUM_PREDICT, true)),
+            ),
+            ("omitted", "", Some((DEFAULT_NUM_PREDICT, true))),
+            ("unknown keyword", "num_predict = \"max\"", None),
+        ];
+
+        for (name, line, expected) in cases {
+            let content = format!(
+                "[general]\nactive_provider = \"ollama\"\nmax_diff_length = 2000\n\
+                 [ai_params]\n{}\ntemperature = 0.1\ntop_p = 0.9\n",
+                line
+            );
+            let result = AsumConfig::from_toml_str(&content)
+                .ok()
+                .map(|c| (c.ai_num_predict, c.ai_num_predict_auto));
+            assert_eq!(result, expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_context_window_table_driven() {
+        let cases = [
+            ("tokens", "context_window = 8192", Some((Some(8192), false))),
+            ("auto", "context_window = \"auto\"", Some((None, true))),
+            ("off", "context_window = \"off\"", Some((None, false))),
+            ("omitted", "", Some((None, false))),
+            ("unknown keyword", "context_window = \"max\"", None),
+        ];
+
+        for (name, line, expected) in cases {
+            let content = format!(
+                "[general]\nactive_provider = \"ollama\"\nmax_diff_length = 2000\n{}\n\
+                 [ai_params]\ntemperature = 0.1\ntop_p = 0.9\n",
+                line
+            );
+            let result = AsumConfig::from_toml_str(&content)
+                .ok()
+                .map(|c| (c.context_window, c.context_window_auto));
+            assert_eq!(result, expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_secrets_table_driven() {
+        let cases = [
+            ("set", "secrets = \"abort\"\n", Some("abort")),
+            ("omitted", "", Some("redact")),
+            ("unknown", "secrets = \"warn\"\n", None),
+        ];
+
+        for (name, setting, expected) in cases {
+            let content = format!(
+                "[general]\nactive_provider = \"ollama\"\nmax_diff_length = 2000\n{}\n\
+                 [ai_params]\ntemperature = 0.1\ntop_p = 0.9\n",
+                setting
+            );
+            let result = AsumConfig::from_toml_str(&content).ok().map(|c| c.secrets);
+            assert_eq!(result.as_deref(), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_choice_settings_table_driven() {
+        let cases = [
+            ("large_diffs = \"chunk\"", None),
+            (
+                "large_diffs = \"split\"",
+                Some("Invalid large_diffs 'split': expected one of truncate, chunk."),
+            ),
+            (
+                "candidate_bodies = \"subjects\"",
+                Some("Invalid candidate_bodies 'subjects': expected one of all, picked."),
+            ),
+            ("clipboard = \"file:/tmp/message.txt\"", None),
+            (
+                "clipboard = \"xclip\"",
+                Some(
+                    "Unknown clipboard 'xclip'; expected auto, arboard, osc52, wl-copy, tmux, none or file:<PATH>.",
+                ),
+            ),
+            (
+                "spell_check = \"warn\"",
+                Some("Invalid spell_check 'warn': expected one of fix, flag, off."),
+            ),
+            (
+                "untracked_files = \"all\"",
+                Some("Invalid untracked_files 'all': expected one of off, names, content."),
+            ),
+            (
+                "repo_profile = \"dotfiles\"",
+                Some("Invalid repo_profile 'dotfiles': expected one of auto, code, config."),
+            ),
+        ];
+
+        for (setting, expected) in cases {
+            let content = format!(
+                "[general]\nactive_provider = \"ollama\"\nmax_diff_length = 2000\n{}\n\
+                 [ai_params]\ntemperature = 0.1\ntop_p = 0.9\n",
+                setting
+            );
+            let error = AsumConfig::from_toml_str(&content)
+                .err()
+                .map(|e| e.to_string());
+            assert_eq!(error.as_deref(), expected, "{}", setting);
+        }
+    }
+
+    #[test]
+    fn test_max_file_diff_length_table_driven() {
+        let cases = [
+            ("set", "[diff]\nmax_file_diff_length = 8000", Some(8000)),
+            ("omitted", "", Some(0)),
+            ("negative", "[diff]\nmax_file_diff_length = -1", None),
+        ];
+
+        for (name, section, expected) in cases {
+            let content = format!(
+                "[general]\nactive_provider = \"ollama\"\nmax_diff_length = 2000\n{}\n\
+                 [ai_params]\ntemperature = 0.1\ntop_p = 0.9\n",
+                section
+            );
+            let result = AsumConfig::from_toml_str(&content)
+                .ok()
+                .map(|c| c.max_file_diff_length);
+            assert_eq!(result, expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_noise_filters_table_driven() {
+        let cases = [
+            (
+                "listed",
+                "[diff]\nnoise_filters = [\"whitespace\", \"imports\"]",
+                Some(vec!["whitespace", "imports"]),
+            ),
+            ("omitted", "", Some(vec![])),
+            (
+                "unknown filter",
+                "[diff]\nnoise_filters = [\"comments\"]",
+                None,
+            ),
+        ];
+
+        for (name, section, expected) in cases {
+            let content = format!(
+                "[general]\nactive_provider = \"ollama\"\nmax_diff_length = 2000\n{}\n\
+                 [ai_params]\ntemperature = 0.1\ntop_p = 0.9\n",
+                section
+            );
+            let result = AsumConfig::from_toml_str(&content)
+                .ok()
+                .map(|c| c.noise_filters);
+            let expected =
+                expected.map(|filters| filters.into_iter().map(String::from).collect::<Vec<_>>());
+            assert_eq!(result, expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_issue_footers_table_driven() {
+        let defaults: Vec<(String, String)> = footers::DEFAULT_ISSUE_FOOTERS
+            .iter()
+            .filter_map(|mapping| footers::parse_mapping(mapping))
+            .collect();
+        let cases = [
+            (
+                "listed",
+                "[conventions]\nissue_footers = [\"tickets/{id}.md -> Closes #{id}\"]",
+                Some(vec![(
+                    "tickets/{id}.md".to_string(),
+                    "Closes #{id}".to_string(),
+                )]),
+            ),
+            (
+                "disabled",
+                "[conventions]\nissue_footers = []",
+                Some(vec![]),
+            ),
+            ("omitted", "", Some(defaults)),
+            (
+                "no placeholder",
+                "[conventions]\nissue_footers = [\"tickets/*.md -> Refs\"]",
+                None,
+            ),
+        ];
+
+        for (name, section, expected) in cases {
+            let content = format!(
+                "[general]\nactive_provider = \"ollama\"\nmax_diff_length = 2000\n{}\n\
+                 [ai_params]\ntemperature = 0.1\ntop_p = 0.9\n",
+                section
+            );
+            let result = AsumConfig::from_toml_str(&content)
+                .ok()
+                .map(|c| c.issue_footers);
+            assert_eq!(result, expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_scope_paths_table_driven() {
+        let cases = [
+            (
+                "listed",
+                "[scopes]\n\"packages/auth/\" = \"auth\"\n\"apps/web\" = \" web \"",
+                Some(vec![
+                    ("apps/web".to_string(), "web".to_string()),
+                    ("packages/auth".to_string(), "auth".to_string()),
+                ]),
+            ),
+            ("omitted", "", Some(vec![])),
+            ("empty scope", "[scopes]\n\"packages/auth\" = \"\"", None),
+            ("empty path", "[scopes]\n\"/\" = \"root\"", None),
+        ];
+
+        for (name, section, expected) in cases {
+            let content = format!(
+                "[general]\nactive_provider = \"ollama\"\nmax_diff_length = 2000\n\
+                 [ai_params]\ntemperature = 0.1\ntop_p = 0.9\n{}\n",
+                section
+            );
+            let result = AsumConfig::from_toml_str(&content)
+                .ok()
+                .map(|c| c.scope_paths);
+            assert_eq!(result, expected, "{}", name);
+        }
+    }
+
     #[test]
     fn test_verify_toml_table_driven() {
         struct TestCase {
@@ -291,6 +1651,19 @@ mod tests {
                 "#,
                 is_ok: false,
             },
+            TestCase {
+                name: "unknown num_predict keyword",
+                content: r#"
+                    [general]
+                    active_provider = "ollama"
+                    max_diff_length = 2000
+                    [ai_params]
+                    num_predict = "max"
+                    temperature = 0.7
+                    top_p = 1.0
+                "#,
+                is_ok: false,
+            },
             TestCase {
                 name: "invalid toml syntax",
                 content: "invalid = [",
@@ -339,6 +1712,7 @@ mod tests {
         assert_eq!(config.active_provider, "ollama");
         assert_eq!(config.max_diff_length, 500);
         assert_eq!(config.ai_num_predict, 10);
+        assert!(!config.ai_num_predict_auto);
         assert!(config.ollama_url.is_none());
         assert!(config.gemini_api_key.is_none());
     }
@@ -372,6 +1746,284 @@ mod tests {
         assert_eq!(config.system_prompt, "Custom system prompt");
     }
 
+    #[test]
+    fn test_load_from_explicit_path() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"
+            [general]
+            active_provider = "gemini"
+            max_diff_length = 700
+            [ai_params]
+            num_predict = 100
+            temperature = 0.7
+            top_p = 1.0
+            "#
+        )
+        .unwrap();
+
+        let config = AsumConfig::load_from(file.path()).unwrap();
+        assert_eq!(config.active_provider, "gemini");
+        assert_eq!(config.max_diff_length, 700);
+
+        let missing = AsumConfig::load_from("does_not_exist.toml");
+        assert!(missing.unwrap_err().to_string().contains("not found"));
+    }
+
+    #[test]
+    fn test_apply_overrides_table_driven() {
+        struct TestCase {
+            name: &'static str,
+            provider: Option<&'static str>,
+            model: Option<&'static str>,
+            expected_provider: &'static str,
+            expected_ollama_model: Option<&'static str>,
+            expected_gemini_model: Option<&'static str>,
+        }
+
+        let cases = vec![
+            TestCase {
+                name: "no overrides",
+                provider: None,
+                model: None,
+                expected_provider: "ollama",
+                expected_ollama_model: Some("llama3"),
+                expected_gemini_model: None,
+            },
+            TestCase {
+                name: "model only targets active provider",
+                provider: None,
+                model: Some("qwen2.5-coder:7b"),
+                expected_provider: "ollama",
+                expected_ollama_model: Some("qwen2.5-coder:7b"),
+                expected_gemini_model: None,
+            },
+            TestCase {
+                name: "provider and model",
+                provider: Some("gemini"),
+                model: Some("gemini-2.0-flash"),
+                expected_provider: "gemini",
+                expected_ollama_model: Some("llama3"),
+                expected_gemini_model: Some("gemini-2.0-flash"),
+            },
+        ];
+
+        for case in cases {
+            let mut file = NamedTempFile::new().unwrap();
+            writeln!(
+                file,
+                r#"
+                [general]
+                active_provider = "ollama"
+                max_diff_length = 1000
+                [ai_params]
+                num_predict = 100
+                temperature = 0.7
+                top_p = 1.0
+                [ollama]
+                model = "llama3"
+                url = "http://localhost:11434/api/chat"
+                "#
+            )
+            .unwrap();
+
+            let mut config = AsumConfig::load_from_toml(file.path()).unwrap();
+            config.apply_overrides(case.provider, case.model);
+            assert_eq!(
+                config.active_provider, case.expected_provider,
+                "{}",
+                case.name
+            );
+            assert_eq!(
+                config.ollama_model.as_deref(),
+                case.expected_ollama_model,
+                "{}",
+                case.name
+            );
+            assert_eq!(
+                config.gemini_model.as_deref(),
+                case.expected_gemini_model,
+                "{}",
+                case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_config_table_driven() {
+        struct TestCase {
+            settings: InitSettings,
+            expected: &'static [&'static str],
+            unexpected: &'static [&'static str],
+        }
+
+        let cases = vec![
+            TestCase {
+                settings: InitSettings {
+                    provider: "ollama".to_string(),
+                    model: "qwen2.5-coder:3b".to_string(),
+                    api_key: None,
+                    ollama_url: None,
+                    scopes: Vec::new(),
+                    strict_format: false,
+                },
+                expected: &[
+                    "active_provider = \"ollama\"",
+                    "[ollama]",
+                    "url = \"http://localhost:11434/api/chat\"",
+                ],
+                unexpected: &["[gemini]"],
+            },
+            TestCase {
+                settings: InitSettings {
+                    provider: "gemini".to_string(),
+                    model: "gemini-2.0-flash".to_string(),
+                    api_key: Some("secret".to_string()),
+                    ollama_url: None,
+                    scopes: Vec::new(),
+                    strict_format: false,
+                },
+                expected: &[
+                    "active_provider = \"gemini\"",
+                    "[gemini]",
+                    "api_key = \"secret\"",
+                ],
+                unexpected: &["[ollama]", "[conventions]", "strict_format"],
+            },
+            TestCase {
+                settings: InitSettings {
+                    provider: "ollama".to_string(),
+                    model: "qwen2.5-coder:3b".to_string(),
+                    api_key: None,
+                    ollama_url: None,
+                    scopes: vec!["cli".to_string(), "core".to_string()],
+                    strict_format: true,
+                },
+                expected: &[
+                    "strict_format = true",
+                    "[conventions]\nscopes = [\"cli\", \"core\"]",
+                ],
+                unexpected: &["issue_footers"],
+            },
+        ];
+
+        for case in cases {
+            let content = render_config(&case.settings).unwrap();
+            for needle in case.expected {
+                assert!(
+                    content.contains(needle),
+                    "missing {} in {}",
+                    needle,
+                    content
+                );
+            }
+            for needle in case.unexpected {
+                assert!(
+                    !content.contains(needle),
+                    "unexpected {} in {}",
+                    needle,
+                    content
+                );
+            }
+
+            let mut file = NamedTempFile::new().unwrap();
+            write!(file, "{}", content).unwrap();
+            let config = AsumConfig::load_from_toml(file.path()).unwrap();
+            assert_eq!(config.active_provider, case.settings.provider);
+        }
+    }
+
+    #[test]
+    fn test_from_defaults() {
+        let config = AsumConfig::from_defaults().unwrap();
+        assert_eq!(config.active_provider, "ollama");
+        assert_eq!(config.ollama_model.as_deref(), Some("qwen2.5-coder:3b"));
+        assert_eq!(config.gemini_model.as_deref(), Some("gemini-2.0-flash"));
+        assert!(config.system_prompt.contains("expert Git Commit Generator"));
+    }
+
+    #[test]
+    fn test_apply_env_table_driven() {
+        struct TestCase {
+            name: &'static str,
+            vars: Vec<(&'static str, &'static str)>,
+            expected_provider: &'static str,
+            expected_gemini_key: Option<&'static str>,
+            expected_gemini_model: Option<&'static str>,
+            expected_ollama_url: Option<&'static str>,
+        }
+
+        let cases = vec![
+            TestCase {
+                name: "no variables",
+                vars: vec![],
+                expected_provider: "ollama",
+                expected_gemini_key: None,
+                expected_gemini_model: Some("gemini-2.0-flash"),
+                expected_ollama_url: Some("http://localhost:11434/api/chat"),
+            },
+            TestCase {
+                name: "provider, model and fallback key",
+                vars: vec![
+                    ("ASUM_PROVIDER", "gemini"),
+                    ("ASUM_MODEL", "gemini-pro"),
+                    ("GEMINI_API_KEY", "fallback"),
+                ],
+                expected_provider: "gemini",
+                expected_gemini_key: Some("fallback"),
+                expected_gemini_model: Some("gemini-pro"),
+                expected_ollama_url: Some("http://localhost:11434/api/chat"),
+            },
+            TestCase {
+                name: "asum key wins and empty values are ignored",
+                vars: vec![
+                    ("ASUM_PROVIDER", ""),
+                    ("ASUM_GEMINI_API_KEY", "primary"),
+                    ("GEMINI_API_KEY", "fallback"),
+                    ("ASUM_OLLAMA_URL", "http://ollama:11434/api/chat"),
+                ],
+                expected_provider: "ollama",
+                expected_gemini_key: Some("primary"),
+                expected_gemini_model: Some("gemini-2.0-flash"),
+                expected_ollama_url: Some("http://ollama:11434/api/chat"),
+            },
+        ];
+
+        for case in cases {
+            let mut config = AsumConfig::from_defaults().unwrap();
+            let vars = case.vars.clone();
+            config.apply_env(|name| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            });
+            assert_eq!(
+                config.active_provider, case.expected_provider,
+                "{}",
+                case.name
+            );
+            assert_eq!(
+                config.gemini_api_key.as_deref(),
+                case.expected_gemini_key,
+                "{}",
+                case.name
+            );
+            assert_eq!(
+                config.gemini_model.as_deref(),
+                case.expected_gemini_model,
+                "{}",
+                case.name
+            );
+            assert_eq!(
+                config.ollama_url.as_deref(),
+                case.expected_ollama_url,
+                "{}",
+                case.name
+            );
+        }
+    }
+
     #[test]
     fn test_asum_config_load_local() {
         let dir = tempfile::tempdir().unwrap();
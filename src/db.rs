@@ -0,0 +1,823 @@
+//! Local SQLite database for ASUM.
+//!
+//! Stores generated messages, the feedback on whether they were used and the
+//! tokens spent on them, in `~/.asum/asum.db`. The schema is versioned with
+//! `PRAGMA user_version`.
+
+use crate::config::db_path;
+use anyhow::{Context, Result, anyhow};
+use rusqlite::{Connection, OptionalExtension, params};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Schema migrations; entry `n` upgrades the database from version `n` to `n + 1`.
+const MIGRATIONS: &[&str] = &[
+    r#"
+    CREATE TABLE generations (
+        id INTEGER PRIMARY KEY,
+        created_at INTEGER NOT NULL,
+        repo TEXT NOT NULL,
+        provider TEXT NOT NULL,
+        model TEXT NOT NULL,
+        message TEXT NOT NULL
+    );
+    CREATE INDEX generations_repo ON generations (repo, created_at);
+    CREATE TABLE feedback (
+        generation_id INTEGER PRIMARY KEY REFERENCES generations (id) ON DELETE CASCADE,
+        created_at INTEGER NOT NULL,
+        verdict TEXT NOT NULL,
+        final_message TEXT NOT NULL
+    );
+"#,
+    r#"
+    CREATE TABLE batch_results (
+        batch TEXT NOT NULL,
+        item TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        output TEXT NOT NULL,
+        PRIMARY KEY (batch, item)
+    );
+"#,
+    r#"
+    ALTER TABLE generations ADD COLUMN branch TEXT NOT NULL DEFAULT '';
+    ALTER TABLE generations ADD COLUMN diff_hash TEXT NOT NULL DEFAULT '';
+    CREATE VIRTUAL TABLE generations_fts USING fts5 (
+        message, content = 'generations', content_rowid = 'id'
+    );
+    INSERT INTO generations_fts (generations_fts) VALUES ('rebuild');
+    CREATE TRIGGER generations_fts_insert AFTER INSERT ON generations BEGIN
+        INSERT INTO generations_fts (rowid, message) VALUES (new.id, new.message);
+    END;
+    CREATE TRIGGER generations_fts_delete AFTER DELETE ON generations BEGIN
+        INSERT INTO generations_fts (generations_fts, rowid, message)
+        VALUES ('delete', old.id, old.message);
+    END;
+"#,
+    r#"
+    CREATE TABLE last_requests (
+        repo TEXT PRIMARY KEY,
+        created_at INTEGER NOT NULL,
+        diff TEXT NOT NULL,
+        system_prompt TEXT NOT NULL,
+        user_prompt TEXT NOT NULL
+    );
+"#,
+    r#"
+    CREATE TABLE message_cache (
+        key TEXT PRIMARY KEY,
+        created_at INTEGER NOT NULL,
+        message TEXT NOT NULL
+    );
+"#,
+    r#"
+    CREATE TABLE usage (
+        id INTEGER PRIMARY KEY,
+        created_at INTEGER NOT NULL,
+        provider TEXT NOT NULL,
+        model TEXT NOT NULL,
+        prompt_tokens INTEGER NOT NULL,
+        completion_tokens INTEGER NOT NULL,
+        cost REAL
+    );
+    CREATE INDEX usage_created_at ON usage (created_at);
+"#,
+    r#"
+    CREATE TABLE wip_numbers (
+        repo TEXT NOT NULL,
+        branch TEXT NOT NULL,
+        number INTEGER NOT NULL,
+        diff_hash TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        PRIMARY KEY (repo, branch, number)
+    );
+"#,
+];
+
+/// Columns selected to build a `Generation`.
+const GENERATION_COLUMNS: &str =
+    "g.id, g.created_at, g.repo, g.branch, g.diff_hash, g.provider, g.model, g.message";
+
+/// How a generated message ended up being used.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    /// Committed as generated.
+    Accepted,
+    /// Committed after changes.
+    Edited,
+    /// Not used.
+    Rejected,
+}
+
+impl Verdict {
+    /// Name stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Accepted => "accepted",
+            Verdict::Edited => "edited",
+            Verdict::Rejected => "rejected",
+        }
+    }
+}
+
+/// A generated message stored in the database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generation {
+    pub id: i64,
+    pub created_at: i64,
+    pub repo: String,
+    pub branch: String,
+    pub diff_hash: String,
+    pub provider: String,
+    pub model: String,
+    pub message: String,
+}
+
+impl Generation {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            created_at: row.get(1)?,
+            repo: row.get(2)?,
+            branch: row.get(3)?,
+            diff_hash: row.get(4)?,
+            provider: row.get(5)?,
+            model: row.get(6)?,
+            message: row.get(7)?,
+        })
+    }
+}
+
+/// The input of the last generation in a repository, kept for `asum redo`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LastRequest {
+    pub diff: String,
+    pub system_prompt: String,
+    pub user_prompt: String,
+}
+
+/// Feedback counts for one provider/model pair.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelAcceptance {
+    pub provider: String,
+    pub model: String,
+    pub generated: u64,
+    pub accepted: u64,
+    pub edited: u64,
+    pub rejected: u64,
+}
+
+/// Token usage and estimated cost for one day, provider and model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyUsage {
+    /// UTC date as `YYYY-MM-DD`.
+    pub day: String,
+    pub provider: String,
+    pub model: String,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// Estimated cost in US dollars; `None` when no request had a known price.
+    pub cost: Option<f64>,
+}
+
+/// Connection to the ASUM database.
+pub struct Db {
+    conn: Connection,
+}
+
+impl Db {
+    /// Opens `~/.asum/asum.db`, creating it if needed.
+    pub fn open_default() -> Result<Self> {
+        let path = db_path().ok_or_else(|| anyhow!("Could not find home directory"))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+        }
+        Self::open(&path)
+    }
+
+    /// Opens the database at `path` and applies pending migrations.
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open database {}", path.display()))?;
+        Self::init(conn)
+    }
+
+    /// Opens a fresh in-memory database.
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+        let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > MIGRATIONS.len() {
+            return Err(anyhow!(
+                "The database was created by a newer version of asum (schema {}).",
+                version
+            ));
+        }
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            conn.execute_batch(migration)
+                .with_context(|| format!("Failed to migrate database to schema {}", i + 1))?;
+            conn.pragma_update(None, "user_version", i + 1)?;
+        }
+        Ok(Self { conn })
+    }
+
+    /// Stores a generated message and returns its id.
+    pub fn record_generation(
+        &self,
+        repo: &str,
+        branch: &str,
+        diff_hash: &str,
+        provider: &str,
+        model: &str,
+        message: &str,
+    ) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO generations (created_at, repo, branch, diff_hash, provider, model, message)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![now(), repo, branch, diff_hash, provider, model, message],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Returns the most recent generation for `repo` that has no feedback yet.
+    pub fn latest_pending_generation(&self, repo: &str) -> Result<Option<Generation>> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM generations g
+                     WHERE g.repo = ?1 AND g.id NOT IN (SELECT generation_id FROM feedback)
+                     ORDER BY g.created_at DESC, g.id DESC LIMIT 1",
+                    GENERATION_COLUMNS
+                ),
+                params![repo],
+                Generation::from_row,
+            )
+            .optional()?)
+    }
+
+    /// Returns up to `limit` generations, newest first, optionally only those for
+    /// `repo` and those whose message matches every word of `search`.
+    pub fn history(
+        &self,
+        repo: Option<&str>,
+        search: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<Generation>> {
+        let mut sql = format!("SELECT {} FROM generations g", GENERATION_COLUMNS);
+        let search = search.map(fts_query).filter(|query| !query.is_empty());
+        if search.is_some() {
+            sql.push_str(" JOIN generations_fts ON generations_fts.rowid = g.id");
+        }
+        sql.push_str(" WHERE (?1 IS NULL OR g.repo = ?1)");
+        if search.is_some() {
+            sql.push_str(" AND generations_fts MATCH ?2");
+        } else {
+            sql.push_str(" AND ?2 IS NULL");
+        }
+        sql.push_str(" ORDER BY g.created_at DESC, g.id DESC LIMIT ?3");
+
+        let mut statement = self.conn.prepare(&sql)?;
+        let rows =
+            statement.query_map(params![repo, search, limit as i64], Generation::from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Stores the feedback for a generation, replacing any earlier verdict.
+    pub fn record_feedback(
+        &self,
+        generation_id: i64,
+        verdict: Verdict,
+        final_message: &str,
+    ) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO feedback (generation_id, created_at, verdict, final_message)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![generation_id, now(), verdict.as_str(), final_message],
+        )?;
+        Ok(())
+    }
+
+    /// Counts generations and feedback verdicts per provider and model.
+    pub fn acceptance_by_model(&self) -> Result<Vec<ModelAcceptance>> {
+        let mut statement = self.conn.prepare(
+            "SELECT g.provider, g.model, COUNT(*),
+                    COUNT(CASE WHEN f.verdict = 'accepted' THEN 1 END),
+                    COUNT(CASE WHEN f.verdict = 'edited' THEN 1 END),
+                    COUNT(CASE WHEN f.verdict = 'rejected' THEN 1 END)
+             FROM generations g LEFT JOIN feedback f ON f.generation_id = g.id
+             GROUP BY g.provider, g.model
+             ORDER BY COUNT(*) DESC, g.provider, g.model",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok(ModelAcceptance {
+                provider: row.get(0)?,
+                model: row.get(1)?,
+                generated: row.get(2)?,
+                accepted: row.get(3)?,
+                edited: row.get(4)?,
+                rejected: row.get(5)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Records the tokens used by one provider request and its estimated cost.
+    pub fn record_usage(
+        &self,
+        provider: &str,
+        model: &str,
+        prompt_tokens: u64,
+        completion_tokens: u64,
+        cost: Option<f64>,
+    ) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO usage (created_at, provider, model, prompt_tokens, completion_tokens, cost)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                now(),
+                provider,
+                model,
+                prompt_tokens,
+                completion_tokens,
+                cost
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Sums the usage recorded since `since` (seconds since the Unix epoch) per UTC
+    /// day, provider and model, newest day first.
+    pub fn usage_by_day(&self, since: i64) -> Result<Vec<DailyUsage>> {
+        let mut statement = self.conn.prepare(
+            "SELECT date(created_at, 'unixepoch') AS day, provider, model, COUNT(*),
+                    SUM(prompt_tokens), SUM(completion_tokens), SUM(cost)
+             FROM usage WHERE created_at >= ?1
+             GROUP BY day, provider, model
+             ORDER BY day DESC, provider, model",
+        )?;
+        let rows = statement.query_map(params![since], |row| {
+            Ok(DailyUsage {
+                day: row.get(0)?,
+                provider: row.get(1)?,
+                model: row.get(2)?,
+                requests: row.get(3)?,
+                prompt_tokens: row.get(4)?,
+                completion_tokens: row.get(5)?,
+                cost: row.get(6)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Stores the input of the last generation in `repo`, replacing the previous one.
+    pub fn save_last_request(&self, repo: &str, request: &LastRequest) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO last_requests (repo, created_at, diff, system_prompt, user_prompt)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                repo,
+                now(),
+                request.diff,
+                request.system_prompt,
+                request.user_prompt
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Returns the input of the last generation in `repo`, if any.
+    pub fn last_request(&self, repo: &str) -> Result<Option<LastRequest>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT diff, system_prompt, user_prompt FROM last_requests WHERE repo = ?1",
+                params![repo],
+                |row| {
+                    Ok(LastRequest {
+                        diff: row.get(0)?,
+                        system_prompt: row.get(1)?,
+                        user_prompt: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Returns the message cached under `key`, if any.
+    pub fn cached_message(&self, key: &str) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT message FROM message_cache WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Caches `message` under `key`, replacing an earlier one.
+    pub fn save_cached_message(&self, key: &str, message: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO message_cache (key, created_at, message) VALUES (?1, ?2, ?3)",
+            params![key, now(), message],
+        )?;
+        Ok(())
+    }
+
+    /// Deletes every cached message and returns how many there were.
+    pub fn clear_cache(&self) -> Result<usize> {
+        Ok(self.conn.execute("DELETE FROM message_cache", [])?)
+    }
+
+    /// Returns the number of the `asum wip` message for the diff with `diff_hash` on
+    /// `branch` of `repo`: the number it got before when the same diff is summarized
+    /// again, or else the next one on the branch.
+    pub fn wip_number(&self, repo: &str, branch: &str, diff_hash: &str) -> Result<u32> {
+        let earlier: Option<u32> = self
+            .conn
+            .query_row(
+                "SELECT number FROM wip_numbers WHERE repo = ?1 AND branch = ?2 AND diff_hash = ?3",
+                params![repo, branch, diff_hash],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(number) = earlier {
+            return Ok(number);
+        }
+        let number: u32 = self.conn.query_row(
+            "SELECT COALESCE(MAX(number), 0) + 1 FROM wip_numbers WHERE repo = ?1 AND branch = ?2",
+            params![repo, branch],
+            |row| row.get(0),
+        )?;
+        self.conn.execute(
+            "INSERT INTO wip_numbers (repo, branch, number, diff_hash, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![repo, branch, number, diff_hash, now()],
+        )?;
+        Ok(number)
+    }
+
+    /// Returns the results already stored for `batch`, keyed by item.
+    pub fn batch_results(&self, batch: &str) -> Result<HashMap<String, String>> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT item, output FROM batch_results WHERE batch = ?1")?;
+        let rows = statement.query_map(params![batch], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Stores the result of one batch item, replacing an earlier one.
+    pub fn save_batch_result(&self, batch: &str, item: &str, output: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO batch_results (batch, item, created_at, output)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![batch, item, now(), output],
+        )?;
+        Ok(())
+    }
+
+    /// Deletes the stored results of a finished batch.
+    pub fn clear_batch(&self, batch: &str) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM batch_results WHERE batch = ?1", params![batch])?;
+        Ok(())
+    }
+}
+
+/// Turns free text into an FTS5 query matching every word, quoting each word so
+/// characters like `:` or `*` in commit messages are not read as query syntax.
+fn fts_query(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Stable 64-bit FNV-1a hash of `text` as 16 hex digits, used to recognize
+/// identical diffs across runs.
+pub fn diff_hash(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Current time as seconds since the Unix epoch.
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_migrates_and_reopens() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("asum.db");
+
+        let db = Db::open(&path).unwrap();
+        db.record_generation("/repo", "main", "", "ollama", "llama3", "feat: a")
+            .unwrap();
+        drop(db);
+
+        // Reopening keeps the data and does not rerun migrations
+        let db = Db::open(&path).unwrap();
+        assert!(db.latest_pending_generation("/repo").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_open_rejects_newer_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("asum.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.pragma_update(None, "user_version", 99).unwrap();
+        drop(conn);
+
+        let err = Db::open(&path).err().unwrap();
+        assert!(err.to_string().contains("newer version"));
+    }
+
+    #[test]
+    fn test_latest_pending_generation_skips_rated() {
+        let db = Db::open_in_memory().unwrap();
+        assert!(db.latest_pending_generation("/repo").unwrap().is_none());
+
+        let first = db
+            .record_generation("/repo", "main", "", "ollama", "llama3", "feat: first")
+            .unwrap();
+        let second = db
+            .record_generation("/repo", "main", "", "ollama", "llama3", "feat: second")
+            .unwrap();
+        db.record_generation("/other", "main", "", "ollama", "llama3", "feat: other")
+            .unwrap();
+
+        let latest = db.latest_pending_generation("/repo").unwrap().unwrap();
+        assert_eq!(latest.id, second);
+        assert_eq!(latest.message, "feat: second");
+
+        db.record_feedback(second, Verdict::Accepted, "feat: second")
+            .unwrap();
+        let latest = db.latest_pending_generation("/repo").unwrap().unwrap();
+        assert_eq!(latest.id, first);
+    }
+
+    #[test]
+    fn test_history_filters_and_searches() {
+        let db = Db::open_in_memory().unwrap();
+        let messages = [
+            ("/repo", "feat(api): add login endpoint"),
+            ("/repo", "fix: handle empty diff"),
+            ("/other", "feat: add login page"),
+            ("/repo", "docs: mention \"login\" in README"),
+        ];
+        for (repo, message) in messages {
+            db.record_generation(repo, "main", "abc", "ollama", "llama3", message)
+                .unwrap();
+        }
+
+        let cases = vec![
+            (
+                None,
+                None,
+                2,
+                vec!["docs: mention \"login\" in README", "feat: add login page"],
+            ),
+            (
+                Some("/repo"),
+                Some("login"),
+                10,
+                vec![
+                    "docs: mention \"login\" in README",
+                    "feat(api): add login endpoint",
+                ],
+            ),
+            (
+                None,
+                Some("add login"),
+                10,
+                vec!["feat: add login page", "feat(api): add login endpoint"],
+            ),
+            // Query syntax characters are searched literally
+            (
+                None,
+                Some("feat(api): \"add"),
+                10,
+                vec!["feat(api): add login endpoint"],
+            ),
+            (None, Some("missing"), 10, vec![]),
+            (
+                Some("/repo"),
+                Some("  "),
+                1,
+                vec!["docs: mention \"login\" in README"],
+            ),
+        ];
+        for (repo, search, limit, expected) in cases {
+            let found: Vec<String> = db
+                .history(repo, search, limit)
+                .unwrap()
+                .into_iter()
+                .map(|g| g.message)
+                .collect();
+            assert_eq!(found, expected, "repo: {:?}, search: {:?}", repo, search);
+        }
+
+        let first = &db.history(None, None, 1).unwrap()[0];
+        assert_eq!(
+            (first.branch.as_str(), first.diff_hash.as_str()),
+            ("main", "abc")
+        );
+    }
+
+    #[test]
+    fn test_migration_indexes_existing_messages() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("asum.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        conn.execute_batch(MIGRATIONS[1]).unwrap();
+        conn.pragma_update(None, "user_version", 2).unwrap();
+        conn.execute(
+            "INSERT INTO generations (created_at, repo, provider, model, message)
+             VALUES (1, '/repo', 'ollama', 'llama3', 'fix: old message')",
+            [],
+        )
+        .unwrap();
+        drop(conn);
+
+        let db = Db::open(&path).unwrap();
+        let found = db.history(None, Some("old"), 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].branch, "");
+    }
+
+    #[test]
+    fn test_diff_hash() {
+        assert_eq!(diff_hash(""), "cbf29ce484222325");
+        assert_eq!(diff_hash("a"), "af63dc4c8601ec8c");
+        assert_ne!(diff_hash("+a\n"), diff_hash("+b\n"));
+    }
+
+    #[test]
+    fn test_batch_results_roundtrip() {
+        let db = Db::open_in_memory().unwrap();
+        db.save_batch_result("changelog", "abc", "first").unwrap();
+        db.save_batch_result("changelog", "abc", "second").unwrap();
+        db.save_batch_result("other", "abc", "other").unwrap();
+
+        let results = db.batch_results("changelog").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results["abc"], "second");
+
+        db.clear_batch("changelog").unwrap();
+        assert!(db.batch_results("changelog").unwrap().is_empty());
+        assert_eq!(db.batch_results("other").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_last_request_roundtrip() {
+        let db = Db::open_in_memory().unwrap();
+        assert!(db.last_request("/repo").unwrap().is_none());
+
+        let request = |diff: &str| LastRequest {
+            diff: diff.to_string(),
+            system_prompt: "system".to_string(),
+            user_prompt: "{{diff}}".to_string(),
+        };
+        db.save_last_request("/repo", &request("+a")).unwrap();
+        db.save_last_request("/repo", &request("+b")).unwrap();
+        db.save_last_request("/other", &request("+c")).unwrap();
+        assert_eq!(db.last_request("/repo").unwrap(), Some(request("+b")));
+    }
+
+    #[test]
+    fn test_wip_number_counts_per_branch() {
+        let db = Db::open_in_memory().unwrap();
+        let cases = [
+            ("/repo", "feature", "a", 1),
+            ("/repo", "feature", "b", 2),
+            ("/repo", "feature", "a", 1),
+            ("/repo", "other", "a", 1),
+            ("/other", "feature", "c", 1),
+            ("/repo", "feature", "c", 3),
+        ];
+        for (repo, branch, hash, expected) in cases {
+            assert_eq!(
+                db.wip_number(repo, branch, hash).unwrap(),
+                expected,
+                "{} {} {}",
+                repo,
+                branch,
+                hash
+            );
+        }
+    }
+
+    #[test]
+    fn test_message_cache_roundtrip() {
+        let db = Db::open_in_memory().unwrap();
+        assert_eq!(db.cached_message("key").unwrap(), None);
+
+        db.save_cached_message("key", "feat: one").unwrap();
+        db.save_cached_message("key", "feat: two").unwrap();
+        db.save_cached_message("other", "fix: three").unwrap();
+        assert_eq!(
+            db.cached_message("key").unwrap(),
+            Some("feat: two".to_string())
+        );
+
+        assert_eq!(db.clear_cache().unwrap(), 2);
+        assert_eq!(db.cached_message("key").unwrap(), None);
+    }
+
+    #[test]
+    fn test_usage_by_day() {
+        let db = Db::open_in_memory().unwrap();
+        db.record_usage("gemini", "gemini-2.0-flash", 1000, 50, Some(0.0002))
+            .unwrap();
+        db.record_usage("gemini", "gemini-2.0-flash", 3000, 150, Some(0.0004))
+            .unwrap();
+        db.record_usage("gemini", "gemini-exp", 10, 1, None)
+            .unwrap();
+        db.record_usage("ollama", "llama3", 500, 20, Some(0.0))
+            .unwrap();
+
+        let usage = db.usage_by_day(0).unwrap();
+        let rows: Vec<(&str, u64, u64, u64, Option<f64>)> = usage
+            .iter()
+            .map(|u| {
+                (
+                    u.model.as_str(),
+                    u.requests,
+                    u.prompt_tokens,
+                    u.completion_tokens,
+                    u.cost.map(|c| (c * 10_000.0).round() / 10_000.0),
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("gemini-2.0-flash", 2, 4000, 200, Some(0.0006)),
+                ("gemini-exp", 1, 10, 1, None),
+                ("llama3", 1, 500, 20, Some(0.0)),
+            ]
+        );
+        assert_eq!(usage[0].day.len(), "2024-06-15".len());
+
+        assert!(db.usage_by_day(now() + 60).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_acceptance_by_model() {
+        let db = Db::open_in_memory().unwrap();
+        let verdicts = [
+            ("llama3", Some(Verdict::Accepted)),
+            ("llama3", Some(Verdict::Edited)),
+            ("llama3", None),
+            ("qwen", Some(Verdict::Rejected)),
+        ];
+        for (model, verdict) in verdicts {
+            let id = db
+                .record_generation("/repo", "main", "", "ollama", model, "feat: x")
+                .unwrap();
+            if let Some(verdict) = verdict {
+                db.record_feedback(id, verdict, "feat: x").unwrap();
+            }
+        }
+
+        let stats = db.acceptance_by_model().unwrap();
+        assert_eq!(
+            stats,
+            vec![
+                ModelAcceptance {
+                    provider: "ollama".to_string(),
+                    model: "llama3".to_string(),
+                    generated: 3,
+                    accepted: 1,
+                    edited: 1,
+                    rejected: 0,
+                },
+                ModelAcceptance {
+                    provider: "ollama".to_string(),
+                    model: "qwen".to_string(),
+                    generated: 1,
+                    accepted: 0,
+                    edited: 0,
+                    rejected: 1,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,813 @@
+//! Diff sources for ASUM.
+//!
+//! The changes to summarize can come from git (staged, working tree or a commit
+//! range), from stdin, from a patch file, or from Mercurial and Jujutsu (jj)
+//! repositories. Each source implements `DiffSource` and is selected with
+//! `--source`.
+
+use crate::encoding::decode_diff;
+use crate::git::{
+    EXCLUDE_MAGIC, get_base_diff_in_path, get_git_diff_in_path, get_range_diff_in_path,
+    get_staged_files_in_path, get_uncommitted_changes_in_path, get_worktree_diff_in_path,
+};
+use crate::granularity::warn_if_unusually_large;
+use anyhow::{Context, Result, anyhow};
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::warn;
+
+/// Somewhere to read the changes to summarize from.
+pub trait DiffSource {
+    /// Short description used in messages, e.g. "staged changes".
+    fn describe(&self) -> String;
+
+    /// Returns the diff, limited to files matching `patterns` (git pathspecs such as
+    /// `*.rs`). An empty string means there is nothing to summarize.
+    fn read(&self, patterns: &[String]) -> Result<String>;
+}
+
+/// Staged changes of a git repository. Falls back to the list of staged files when
+/// no staged file matches the patterns.
+pub struct GitStaged {
+    pub path: PathBuf,
+}
+
+/// Unstaged changes of tracked files in a git working tree.
+pub struct GitWorktree {
+    pub path: PathBuf,
+}
+
+/// Staged and unstaged changes of tracked files relative to `HEAD`: what
+/// `git commit -a` would commit.
+pub struct GitAll {
+    pub path: PathBuf,
+}
+
+/// Combined diff of a git revision range such as `main..HEAD`.
+pub struct GitRange {
+    pub range: String,
+    pub path: PathBuf,
+}
+
+/// Committed and staged changes since the current branch left `base`, e.g. `main`.
+pub struct GitBase {
+    pub base: String,
+    pub path: PathBuf,
+}
+
+/// A diff piped to stdin, e.g. `git diff | asum --source stdin`.
+pub struct Stdin;
+
+/// A patch file on disk.
+pub struct PatchFile {
+    pub file: PathBuf,
+}
+
+/// Uncommitted changes of a Mercurial working directory (`hg diff --git`).
+pub struct Mercurial {
+    pub path: PathBuf,
+}
+
+/// Changes of the current Jujutsu change (`jj diff --git`).
+pub struct Jujutsu {
+    pub path: PathBuf,
+}
+
+impl GitStaged {
+    /// Staged changes of the repository in the current directory.
+    pub fn current() -> Self {
+        Self {
+            path: PathBuf::from("."),
+        }
+    }
+}
+
+impl DiffSource for GitStaged {
+    fn describe(&self) -> String {
+        "staged changes".to_string()
+    }
+
+    fn read(&self, patterns: &[String]) -> Result<String> {
+        let path = self.path.to_string_lossy();
+        let diff_text = get_git_diff_in_path(patterns, &path).context("Failed to get git diff")?;
+
+        // If no code changes are found, try to get a list of staged file names as a fallback
+        if diff_text.is_empty() {
+            let files = get_staged_files_in_path(&path).context("Failed to get staged files")?;
+            if !files.is_empty() {
+                warn!(
+                    "No staged changes found in supported code files. Falling back to file list..."
+                );
+            }
+            return Ok(files);
+        }
+
+        warn_if_unusually_large(&self.path);
+        Ok(diff_text)
+    }
+}
+
+impl DiffSource for GitWorktree {
+    fn describe(&self) -> String {
+        "unstaged changes".to_string()
+    }
+
+    fn read(&self, patterns: &[String]) -> Result<String> {
+        get_worktree_diff_in_path(patterns, &self.path.to_string_lossy())
+            .context("Failed to get the working tree diff")
+    }
+}
+
+impl DiffSource for GitAll {
+    fn describe(&self) -> String {
+        "staged or unstaged changes".to_string()
+    }
+
+    fn read(&self, patterns: &[String]) -> Result<String> {
+        get_uncommitted_changes_in_path(patterns, false, &self.path.to_string_lossy())
+            .context("Failed to get the uncommitted changes")
+    }
+}
+
+impl DiffSource for GitRange {
+    fn describe(&self) -> String {
+        format!("changes in {}", self.range)
+    }
+
+    fn read(&self, patterns: &[String]) -> Result<String> {
+        get_range_diff_in_path(&self.range, patterns, &self.path)
+    }
+}
+
+impl DiffSource for GitBase {
+    fn describe(&self) -> String {
+        format!("changes since {}", self.base)
+    }
+
+    fn read(&self, patterns: &[String]) -> Result<String> {
+        get_base_diff_in_path(&self.base, patterns, &self.path)
+    }
+}
+
+impl DiffSource for Stdin {
+    fn describe(&self) -> String {
+        "diff from stdin".to_string()
+    }
+
+    fn read(&self, patterns: &[String]) -> Result<String> {
+        let mut stdin = std::io::stdin();
+        if stdin.is_terminal() {
+            return Err(anyhow!(
+                "No diff on stdin. Pipe one in, e.g. `git diff | asum --source stdin`."
+            ));
+        }
+        let mut diff = Vec::new();
+        stdin
+            .read_to_end(&mut diff)
+            .context("Failed to read the diff from stdin")?;
+        Ok(filter_diff(&decode_diff(&diff), patterns))
+    }
+}
+
+impl DiffSource for PatchFile {
+    fn describe(&self) -> String {
+        format!("patch {}", self.file.display())
+    }
+
+    fn read(&self, patterns: &[String]) -> Result<String> {
+        let diff = std::fs::read(&self.file)
+            .with_context(|| format!("Failed to read patch file {}", self.file.display()))?;
+        Ok(filter_diff(&decode_diff(&diff), patterns))
+    }
+}
+
+impl DiffSource for Mercurial {
+    fn describe(&self) -> String {
+        "Mercurial working directory changes".to_string()
+    }
+
+    fn read(&self, patterns: &[String]) -> Result<String> {
+        let mut command = Command::new("hg");
+        // HGPLAIN ignores user settings that change the output format, such as colors
+        command
+            .args(["diff", "--git"])
+            .env("HGPLAIN", "1")
+            .current_dir(&self.path);
+        Ok(filter_diff(&run_vcs(command, "Mercurial (hg)")?, patterns))
+    }
+}
+
+impl DiffSource for Jujutsu {
+    fn describe(&self) -> String {
+        "changes in the current jj change".to_string()
+    }
+
+    fn read(&self, patterns: &[String]) -> Result<String> {
+        let mut command = Command::new("jj");
+        command
+            .args(["diff", "--git", "--color=never", "--no-pager"])
+            .current_dir(&self.path);
+        Ok(filter_diff(&run_vcs(command, "Jujutsu (jj)")?, patterns))
+    }
+}
+
+/// Runs a version control command and returns its output, with a clear error when
+/// the tool is not installed or fails.
+pub fn run_vcs(mut command: Command, tool: &str) -> Result<String> {
+    let output = command.output().map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => anyhow!("{} is not installed or not on PATH.", tool),
+        _ => anyhow!("Failed to run {}: {}", tool, e),
+    })?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "{} failed: {}",
+            tool,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(decode_diff(&output.stdout))
+}
+
+/// Creates the source named by a `--source` value, reading from the repository at
+/// `path`: `staged`, `worktree`, `stdin` (or `-`), `hg`, `jj`, `patch:<FILE>` or
+/// `range:<A..B>`.
+pub fn from_spec(spec: &str, path: &Path) -> Result<Box<dyn DiffSource>> {
+    let here = path.to_path_buf();
+    let source: Box<dyn DiffSource> = match spec {
+        "staged" => Box::new(GitStaged { path: here }),
+        "worktree" => Box::new(GitWorktree { path: here }),
+        "all" => Box::new(GitAll { path: here }),
+        "stdin" | "-" => Box::new(Stdin),
+        "hg" => Box::new(Mercurial { path: here }),
+        "jj" => Box::new(Jujutsu { path: here }),
+        _ => match spec.split_once(':') {
+            Some(("patch", file)) if !file.is_empty() => Box::new(PatchFile {
+                file: PathBuf::from(file),
+            }),
+            Some(("range", range)) if !range.is_empty() => Box::new(GitRange {
+                range: range.to_string(),
+                path: here,
+            }),
+            Some(("base", base)) if !base.is_empty() => Box::new(GitBase {
+                base: base.to_string(),
+                path: here,
+            }),
+            _ => {
+                return Err(anyhow!(
+                    "Unknown diff source '{}'. Use staged, worktree, all, stdin, hg, jj, patch:<FILE>, range:<A..B> or base:<REF>.",
+                    spec
+                ));
+            }
+        },
+    };
+    Ok(source)
+}
+
+/// Whether tracked files in the git working tree at `path` have unstaged changes,
+/// used to suggest `--all` when nothing is staged.
+pub fn has_unstaged_changes(path: &Path) -> bool {
+    get_worktree_diff_in_path(&[], &path.to_string_lossy()).is_ok_and(|diff| !diff.is_empty())
+}
+
+/// Keeps the file sections of a git-style diff whose path matches `patterns` and none
+/// of its `:(exclude)` pathspecs. Diffs without `diff --git` headers (e.g. plain
+/// `diff -u` output) cannot be split by file and are returned unchanged, and so are
+/// sections whose header names no path that can be read.
+pub fn filter_diff(diff: &str, patterns: &[String]) -> String {
+    if !diff.lines().any(|line| line.starts_with("diff --git ")) {
+        return diff.to_string();
+    }
+
+    let mut filtered = String::new();
+    let mut keep = false;
+    for line in diff.split_inclusive('\n') {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            keep = match header_path(header) {
+                Some(path) => is_included(path, patterns),
+                None => {
+                    warn!(
+                        "Could not read the path in 'diff --git {}'; keeping the file.",
+                        header.trim_end()
+                    );
+                    true
+                }
+            };
+        }
+        if keep {
+            filtered.push_str(line);
+        }
+    }
+    filtered
+}
+
+/// Lists the paths of the files changed in a git-style diff, in diff order.
+pub fn changed_paths(diff: &str) -> Vec<String> {
+    file_sections(diff)
+        .into_iter()
+        .map(|(path, _)| path.to_string())
+        .collect()
+}
+
+/// Splits a git-style diff into `(path, section)` pairs, one per changed file.
+/// Each section starts with its `diff --git` header, and together they cover the
+/// diff after its `preamble`. A section whose header names no path that can be
+/// read still gets one, see `fallback_path`.
+pub fn file_sections(diff: &str) -> Vec<(&str, &str)> {
+    let starts = section_starts(diff);
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| {
+            let end = starts.get(i + 1).copied().unwrap_or(diff.len());
+            let section = &diff[start..end];
+            let header = section
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .trim_start_matches("diff --git ");
+            let path = header_path(header).unwrap_or_else(|| fallback_path(section));
+            (path, section)
+        })
+        .collect()
+}
+
+/// Path of a file section whose `diff --git` header could not be read: the path
+/// after `rename to` or `copy to`, else the rest of the header as it is.
+fn fallback_path(section: &str) -> &str {
+    let mut lines = section.lines();
+    let header = lines.next().unwrap_or_default();
+    lines
+        .take_while(|line| !line.starts_with("@@"))
+        .find_map(|line| {
+            line.strip_prefix("rename to ")
+                .or_else(|| line.strip_prefix("copy to "))
+        })
+        .unwrap_or_else(|| header.trim_start_matches("diff --git ").trim_end())
+}
+
+/// Path a file section had before the change: the path after `rename from` or
+/// `copy from`, else the one on its `--- ` line. `None` for an added file and for
+/// sections with neither line, such as a mode change. Quoted paths are returned as
+/// written between the quotes, like `header_path` does.
+pub fn old_path(section: &str) -> Option<&str> {
+    let mut old = None;
+    for line in section
+        .lines()
+        .skip(1)
+        .take_while(|line| !line.starts_with("@@"))
+    {
+        if let Some(path) = line
+            .strip_prefix("rename from ")
+            .or_else(|| line.strip_prefix("copy from "))
+        {
+            return Some(unquoted(path.trim_end()));
+        }
+        if let Some(path) = line.strip_prefix("--- ") {
+            let path = unquoted(path.trim_end());
+            old = (path != "/dev/null").then(|| without_prefix(path).unwrap_or(path));
+        }
+    }
+    old
+}
+
+/// `path` without the double quotes git puts around names with special characters.
+fn unquoted(path: &str) -> &str {
+    path.strip_prefix('"')
+        .and_then(|path| path.strip_suffix('"'))
+        .unwrap_or(path)
+}
+
+/// Offsets of the `diff --git` lines of a git-style diff.
+fn section_starts(diff: &str) -> Vec<usize> {
+    diff.match_indices("diff --git ")
+        .map(|(index, _)| index)
+        .filter(|&index| index == 0 || diff.as_bytes()[index - 1] == b'\n')
+        .collect()
+}
+
+/// Text of a git-style diff before its first file section, such as a note added
+/// earlier in the pipeline. A diff without sections is all preamble.
+pub fn preamble(diff: &str) -> &str {
+    let end = section_starts(diff).first().copied().unwrap_or(diff.len());
+    &diff[..end]
+}
+
+/// Returns `diff` without the file sections for which `drop(path, section)` is true.
+/// Text before the first section is kept.
+pub fn without_sections(diff: &str, drop: impl Fn(&str, &str) -> bool) -> String {
+    let mut kept = preamble(diff).to_string();
+    for (path, section) in file_sections(diff) {
+        if !drop(path, section) {
+            kept.push_str(section);
+        }
+    }
+    kept
+}
+
+/// Extracts the new path from the rest of a `diff --git <old> <new>` header. Paths
+/// have a one-letter prefix, `a/` and `b/` unless `diff.mnemonicPrefix` makes them
+/// e.g. `i/` and `w/`, or none with `--no-prefix`. A path git quoted is returned as
+/// written between the quotes.
+pub fn header_path(header: &str) -> Option<&str> {
+    let header = header.trim_end();
+    if let Some(new) = quoted_new_path(header) {
+        return Some(without_prefix(new).unwrap_or(new));
+    }
+    // Unless the file was renamed, both halves name the same path
+    let middle = header.len() / 2;
+    if let (Some(old), Some(new)) = (header.get(..middle), header.get(middle + 1..))
+        && header.len() % 2 == 1
+        && header.as_bytes()[middle] == b' '
+    {
+        if old == new {
+            return Some(new);
+        }
+        if let (Some(old), Some(new)) = (without_prefix(old), without_prefix(new))
+            && old == new
+        {
+            return Some(new);
+        }
+    }
+    // A renamed file: the new path starts at the last space followed by a prefix
+    without_prefix(header)?;
+    if header.starts_with("a/")
+        && let Some((_, new)) = header.rsplit_once(" b/")
+    {
+        return Some(new);
+    }
+    header
+        .match_indices(' ')
+        .rev()
+        .find_map(|(i, _)| without_prefix(&header[i + 1..]))
+}
+
+/// `path` without its one-letter prefix such as `b/` or `w/`, if it has one.
+fn without_prefix(path: &str) -> Option<&str> {
+    match path.as_bytes() {
+        [letter, b'/', ..] if letter.is_ascii_alphanumeric() => Some(&path[2..]),
+        _ => None,
+    }
+}
+
+/// The new path of a header in which git quoted it, still escaped but without the
+/// quotes. An old path without quotes cannot contain any.
+fn quoted_new_path(header: &str) -> Option<&str> {
+    let new = match header.strip_prefix('"') {
+        Some(quoted) => {
+            let mut escaped = false;
+            let end = quoted.char_indices().find_map(|(i, c)| {
+                let closing = c == '"' && !escaped;
+                escaped = c == '\\' && !escaped;
+                closing.then_some(i)
+            })?;
+            quoted[end + 1..].trim_start()
+        }
+        None => &header[header.find('"')?..],
+    };
+    new.strip_prefix('"')?.strip_suffix('"')
+}
+
+/// Whether `path` matches one of the include `patterns` and none of the exclusions.
+fn is_included(path: &str, patterns: &[String]) -> bool {
+    let mut excluded = false;
+    let mut included = false;
+    for spec in patterns {
+        match spec.strip_prefix(EXCLUDE_MAGIC) {
+            Some(pattern) => excluded |= matches_pathspec(path, pattern),
+            None => included |= matches_pathspec(path, spec),
+        }
+    }
+    included && !excluded
+}
+
+/// Matches like a git pathspec: `*` matches any characters including `/`, and a
+/// pattern without `/` also matches the file name in any directory.
+fn matches_pathspec(path: &str, pattern: &str) -> bool {
+    let file_name = Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    glob_match(pattern, path) || (!pattern.contains('/') && glob_match(pattern, &file_name))
+}
+
+/// Minimal glob matching supporting `*` (any run of characters) and `?` (one character).
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/src/main.rs b/src/main.rs
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1 +1 @@
+-fn main() {}
++fn main() { run(); }
+diff --git a/package-lock.json b/package-lock.json
+--- a/package-lock.json
++++ b/package-lock.json
+@@ -1 +1 @@
+-{}
++{\"lockfileVersion\": 3}
+diff --git a/logo.png b/logo.png
+Binary files a/logo.png and b/logo.png differ
+";
+
+    fn patterns(list: &[&str]) -> Vec<String> {
+        list.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_glob_match_table_driven() {
+        let cases = vec![
+            ("*.rs", "src/main.rs", true),
+            ("*.rs", "main.rs", true),
+            ("*.rs", "main.rsx", false),
+            ("src/*.rs", "src/main.rs", true),
+            ("src/*.rs", "lib/main.rs", false),
+            ("Dockerfile", "Dockerfile", true),
+            ("file?.txt", "file1.txt", true),
+            ("*-lock.json", "package-lock.json", true),
+        ];
+        for (pattern, text, expected) in cases {
+            assert_eq!(
+                glob_match(pattern, text),
+                expected,
+                "pattern: {}, text: {}",
+                pattern,
+                text
+            );
+        }
+        assert!(matches_pathspec("docker/Dockerfile", "Dockerfile"));
+        assert!(!matches_pathspec("docker/Dockerfile", "other/Dockerfile"));
+    }
+
+    #[test]
+    fn test_filter_diff_table_driven() {
+        let cases = vec![
+            (patterns(&["*.rs"]), vec!["src/main.rs"]),
+            (
+                patterns(&["*.rs", "*.json"]),
+                vec!["src/main.rs", "package-lock.json"],
+            ),
+            // Excluded files stay out even when their extension is included
+            (
+                patterns(&["*.rs", "*.json", ":(exclude)*-lock.json"]),
+                vec!["src/main.rs"],
+            ),
+            (patterns(&["*.png"]), vec!["logo.png"]),
+            (patterns(&["*.py"]), vec![]),
+        ];
+
+        for (patterns, expected) in cases {
+            let files = changed_paths(&filter_diff(DIFF, &patterns));
+            assert_eq!(files, expected, "patterns: {:?}", patterns);
+        }
+
+        // Plain unified diffs are passed through
+        let plain = "--- a.txt\n+++ b.txt\n@@ -1 +1 @@\n-a\n+b\n";
+        assert_eq!(filter_diff(plain, &patterns(&["*.rs"])), plain);
+    }
+
+    #[test]
+    fn test_header_path_table_driven() {
+        let cases = vec![
+            ("a/src/main.rs b/src/main.rs", Some("src/main.rs")),
+            ("a/old name.rs b/new name.rs", Some("new name.rs")),
+            ("i/src/main.rs w/src/main.rs", Some("src/main.rs")),
+            ("c/docs/a.md i/docs/a.md\n", Some("docs/a.md")),
+            ("o/old.rs w/new.rs", Some("new.rs")),
+            ("src/main.rs src/main.rs", Some("src/main.rs")),
+            ("my file.txt my file.txt", Some("my file.txt")),
+            (
+                "\"a/we\\\"ird.rs\" \"b/we\\\"ird.rs\"",
+                Some("we\\\"ird.rs"),
+            ),
+            ("a/plain.rs \"b/tab\\there.rs\"", Some("tab\\there.rs")),
+            ("old.rs new.rs", None),
+            ("", None),
+        ];
+        for (header, expected) in cases {
+            assert_eq!(header_path(header), expected, "{}", header);
+        }
+
+        // Sections whose path cannot be read are kept rather than dropped
+        let unreadable = "diff --git old.rs new.rs\nrename from old.rs\nrename to new.rs\n";
+        let diff = format!("{}{}", DIFF, unreadable);
+        assert!(filter_diff(&diff, &patterns(&["*.rs"])).ends_with(unreadable));
+    }
+
+    #[test]
+    fn test_file_sections() {
+        let sections = file_sections(DIFF);
+        let paths: Vec<&str> = sections.iter().map(|(path, _)| *path).collect();
+        assert_eq!(paths, vec!["src/main.rs", "package-lock.json", "logo.png"]);
+        assert!(sections[0].1.starts_with("diff --git a/src/main.rs"));
+        assert!(sections[0].1.ends_with("+fn main() { run(); }\n"));
+        assert_eq!(
+            sections.iter().map(|(_, s)| s.len()).sum::<usize>(),
+            DIFF.len()
+        );
+        assert!(file_sections("--- a.txt\n+++ b.txt\n").is_empty());
+
+        // Sections with headers that cannot be read are not dropped
+        let renamed = "diff --git old.rs new.rs\nrename from old.rs\nrename to new.rs\n";
+        let odd = "diff --git odd\n@@ -1 +1 @@\n-a\n+b\n";
+        let diff = format!("{}{}{}", DIFF, renamed, odd);
+        let sections = file_sections(&diff);
+        assert_eq!(sections.len(), 5);
+        assert_eq!(sections[3], ("new.rs", renamed));
+        assert_eq!(sections[4], ("odd", odd));
+        assert_eq!(
+            preamble(&diff).len() + sections.iter().map(|(_, s)| s.len()).sum::<usize>(),
+            diff.len()
+        );
+    }
+
+    #[test]
+    fn test_old_path_table_driven() {
+        let cases = [
+            (file_sections(DIFF)[0].1, Some("src/main.rs")),
+            (file_sections(DIFF)[2].1, None),
+            (
+                "diff --git a/secrets/prod.env b/config/prod.env\nsimilarity index 100%\n\
+                 rename from secrets/prod.env\nrename to config/prod.env\n",
+                Some("secrets/prod.env"),
+            ),
+            (
+                "diff --git a/a.rs b/b.rs\ncopy from a.rs\ncopy to b.rs\n--- a/a.rs\n+++ b/b.rs\n",
+                Some("a.rs"),
+            ),
+            (
+                "diff --git a/new.rs b/new.rs\nnew file mode 100644\n--- /dev/null\n\
+                 +++ b/new.rs\n@@ -0,0 +1 @@\n+fn f() {}\n",
+                None,
+            ),
+            (
+                "diff --git \"a/we ird.rs\" \"b/we ird.rs\"\n--- \"a/we ird.rs\"\n\
+                 +++ \"b/we ird.rs\"\n@@ -1 +1 @@\n--- not a header\n",
+                Some("we ird.rs"),
+            ),
+        ];
+        for (section, expected) in cases {
+            assert_eq!(old_path(section), expected, "{}", section);
+        }
+    }
+
+    #[test]
+    fn test_without_sections() {
+        let kept = without_sections(DIFF, |path, _| path != "src/main.rs");
+        assert_eq!(kept, file_sections(DIFF)[0].1);
+
+        let with_preamble = format!("Subject line\n\n{}", DIFF);
+        let kept = without_sections(&with_preamble, |_, _| true);
+        assert_eq!(kept, "Subject line\n\n");
+        assert_eq!(preamble(&with_preamble), "Subject line\n\n");
+        assert_eq!(preamble(DIFF), "");
+        assert_eq!(preamble("no sections\n"), "no sections\n");
+    }
+
+    #[test]
+    fn test_from_spec_table_driven() {
+        let cases = vec![
+            ("staged", Some("staged changes")),
+            ("worktree", Some("unstaged changes")),
+            ("all", Some("staged or unstaged changes")),
+            ("-", Some("diff from stdin")),
+            ("hg", Some("Mercurial working directory changes")),
+            ("jj", Some("changes in the current jj change")),
+            ("patch:fix.patch", Some("patch fix.patch")),
+            ("range:main..HEAD", Some("changes in main..HEAD")),
+            ("base:main", Some("changes since main")),
+            ("base:", None),
+            ("patch:", None),
+            ("svn", None),
+        ];
+        for (spec, expected) in cases {
+            let described = from_spec(spec, Path::new("."))
+                .ok()
+                .map(|source| source.describe());
+            assert_eq!(described.as_deref(), expected, "spec: {}", spec);
+        }
+    }
+
+    #[test]
+    fn test_patch_file_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("change.patch");
+        std::fs::write(&file, DIFF).unwrap();
+
+        let source = PatchFile { file };
+        let diff = source.read(&patterns(&["*.rs"])).unwrap();
+        assert!(diff.starts_with("diff --git a/src/main.rs"));
+        assert!(!diff.contains("package-lock.json"));
+
+        let missing = PatchFile {
+            file: dir.path().join("missing.patch"),
+        };
+        assert!(missing.read(&patterns(&["*.rs"])).is_err());
+    }
+
+    #[test]
+    fn test_run_vcs_reports_missing_tool() {
+        let error = run_vcs(Command::new("asum-missing-vcs"), "Missing VCS").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Missing VCS is not installed or not on PATH."
+        );
+
+        let error = run_vcs(Command::new("false"), "False VCS").unwrap_err();
+        assert!(error.to_string().starts_with("False VCS failed"));
+    }
+
+    #[test]
+    fn test_git_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_path_buf();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(&path)
+                .output()
+                .unwrap()
+        };
+        git(&["init"]);
+        git(&["config", "user.email", "test@example.com"]);
+        git(&["config", "user.name", "Test"]);
+        std::fs::write(path.join("a.rs"), "fn a() {}\n").unwrap();
+        git(&["add", "a.rs"]);
+        git(&["commit", "-m", "feat: a"]);
+
+        std::fs::write(path.join("a.rs"), "fn a() { b(); }\n").unwrap();
+        let rs = patterns(&["*.rs"]);
+        let worktree = GitWorktree { path: path.clone() };
+        assert!(worktree.read(&rs).unwrap().contains("+fn a() { b(); }"));
+
+        let staged = GitStaged { path: path.clone() };
+        assert!(staged.read(&rs).unwrap().is_empty());
+        assert!(has_unstaged_changes(&path));
+
+        let all = GitAll { path: path.clone() };
+        std::fs::write(path.join("c.rs"), "fn c() {}\n").unwrap();
+        git(&["add", "c.rs"]);
+        let diff = all.read(&rs).unwrap();
+        assert!(diff.contains("+fn a() { b(); }"), "{}", diff);
+        assert!(diff.contains("+fn c() {}"), "{}", diff);
+
+        git(&["add", "a.rs"]);
+        assert!(staged.read(&rs).unwrap().contains("+fn a() { b(); }"));
+        assert!(!has_unstaged_changes(&path));
+
+        git(&["commit", "-m", "feat: call b"]);
+        let range = GitRange {
+            range: "HEAD~1..HEAD".to_string(),
+            path: path.clone(),
+        };
+        assert!(range.read(&rs).unwrap().contains("+fn a() { b(); }"));
+
+        // The base diff covers the commits since the base and the staged changes
+        std::fs::write(path.join("d.rs"), "fn d() {}\n").unwrap();
+        git(&["add", "d.rs"]);
+        let base = GitBase {
+            base: "HEAD~1".to_string(),
+            path: path.clone(),
+        };
+        let diff = base.read(&rs).unwrap();
+        assert!(diff.contains("+fn a() { b(); }"), "{}", diff);
+        assert!(diff.contains("+fn d() {}"), "{}", diff);
+
+        let missing = GitBase {
+            base: "no-such-branch".to_string(),
+            path,
+        };
+        let error = missing.read(&rs).unwrap_err().to_string();
+        assert!(
+            error.starts_with("Could not compare with 'no-such-branch'"),
+            "{}",
+            error
+        );
+    }
+}
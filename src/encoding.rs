@@ -0,0 +1,187 @@
+//! Diffs of files that are not UTF-8.
+//!
+//! Git diffs are bytes. Read as UTF-8, the lines of a file saved as Latin-1 or
+//! Shift-JIS turn into U+FFFD replacement characters, and the model is shown mangled
+//! text. Instead, the hunks of each file that is not valid UTF-8 are decoded from the
+//! legacy encoding they fit: Shift-JIS for Japanese text, else Windows-1252, a
+//! superset of Latin-1. Hunks that fit neither are replaced by a note.
+
+use encoding_rs::{Encoding, SHIFT_JIS, WINDOWS_1252};
+use tracing::debug;
+
+/// Decodes the bytes of a diff. Valid UTF-8 is returned as is; see the module
+/// documentation for the rest.
+pub fn decode_diff(bytes: &[u8]) -> String {
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return text.to_string();
+    }
+    sections(bytes).into_iter().map(decode_section).collect()
+}
+
+/// Splits `bytes` before each `diff --git ` line. Text before the first one is a
+/// section of its own.
+fn sections(bytes: &[u8]) -> Vec<&[u8]> {
+    let mut starts: Vec<usize> = line_starts(bytes)
+        .filter(|&start| bytes[start..].starts_with(b"diff --git "))
+        .collect();
+    if starts.first() != Some(&0) {
+        starts.insert(0, 0);
+    }
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| &bytes[start..starts.get(i + 1).copied().unwrap_or(bytes.len())])
+        .collect()
+}
+
+/// Offsets at which the lines of `bytes` start.
+fn line_starts(bytes: &[u8]) -> impl Iterator<Item = usize> + '_ {
+    std::iter::once(0).chain(
+        bytes
+            .iter()
+            .enumerate()
+            .filter(|(i, byte)| **byte == b'\n' && i + 1 < bytes.len())
+            .map(|(i, _)| i + 1),
+    )
+}
+
+/// Decodes one file section. Its header, with the file names git wrote as UTF-8, is
+/// kept apart from the hunks, which are decoded together.
+fn decode_section(section: &[u8]) -> String {
+    if let Ok(text) = std::str::from_utf8(section) {
+        return text.to_string();
+    }
+    let hunks = hunks(section);
+    let body_start = hunks.first().map_or(section.len(), |hunk| hunk.0);
+    let mut text = String::from_utf8_lossy(&section[..body_start]).into_owned();
+    let body = &section[body_start..];
+    if let Some((decoded, encoding)) = detect(body) {
+        debug!("Decoded a diff section from {}.", encoding.name());
+        text.push_str(&decoded);
+        return text;
+    }
+    for (start, end) in hunks {
+        let hunk = &section[start..end];
+        match std::str::from_utf8(hunk) {
+            Ok(hunk) => text.push_str(hunk),
+            Err(_) => text.push_str(&undecodable(hunk)),
+        }
+    }
+    text
+}
+
+/// Byte ranges of the hunks of a file section, each starting at its `@@` line.
+fn hunks(section: &[u8]) -> Vec<(usize, usize)> {
+    let starts: Vec<usize> = line_starts(section)
+        .filter(|&start| section[start..].starts_with(b"@@"))
+        .collect();
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| (start, starts.get(i + 1).copied().unwrap_or(section.len())))
+        .collect()
+}
+
+/// Decodes `bytes` from the first legacy encoding they fit: Shift-JIS when the text
+/// holds kana, else Windows-1252 when it holds no control characters.
+fn detect(bytes: &[u8]) -> Option<(String, &'static Encoding)> {
+    if let Some(text) = SHIFT_JIS.decode_without_bom_handling_and_without_replacement(bytes)
+        && text.chars().any(is_kana)
+    {
+        return Some((text.into_owned(), SHIFT_JIS));
+    }
+    let text = WINDOWS_1252.decode_without_bom_handling_and_without_replacement(bytes)?;
+    (!text
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t')))
+    .then(|| (text.into_owned(), WINDOWS_1252))
+}
+
+/// Whether `c` is hiragana or katakana, in full or half width.
+fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3040}'..='\u{30ff}' | '\u{ff66}'..='\u{ff9f}')
+}
+
+/// Replaces a hunk that could not be decoded by its position and a note. Any text
+/// git put after the line numbers is dropped with the lines.
+fn undecodable(hunk: &[u8]) -> String {
+    let first_line = hunk.split(|&byte| byte == b'\n').next().unwrap_or_default();
+    let header = String::from_utf8_lossy(first_line);
+    let position = match header.match_indices("@@").nth(1) {
+        Some((end, _)) => &header[..end + 2],
+        None => "@@",
+    };
+    let lines = hunk
+        .split(|&byte| byte == b'\n')
+        .skip(1)
+        .filter(|line| !line.is_empty())
+        .count();
+    format!(
+        "{}\n[UNDECODABLE HUNK: {} line(s) in an unknown encoding were left out]\n",
+        position, lines
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(path: &str, hunks: &[&[u8]]) -> Vec<u8> {
+        let mut bytes =
+            format!("diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n").into_bytes();
+        for hunk in hunks {
+            bytes.extend_from_slice(hunk);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_decode_diff_table_driven() {
+        let (shift_jis, _, _) = SHIFT_JIS.encode("@@ -1 +1 @@\n-こんにちは\n+こんばんは、世界\n");
+        let cases: Vec<(Vec<u8>, String)> = vec![
+            (
+                section("a.rs", &[b"@@ -1 +1 @@\n-fn a() {}\n+fn b() {}\n"]),
+                "@@ -1 +1 @@\n-fn a() {}\n+fn b() {}\n".to_string(),
+            ),
+            (
+                section("menu.txt", &[b"@@ -1 +1 @@\n-caf\xe9\n+caf\xe9 cr\xe8me\n"]),
+                "@@ -1 +1 @@\n-café\n+café crème\n".to_string(),
+            ),
+            (
+                section("greeting.txt", &[&shift_jis]),
+                "@@ -1 +1 @@\n-こんにちは\n+こんばんは、世界\n".to_string(),
+            ),
+            (
+                section(
+                    "data.bin",
+                    &[
+                        b"@@ -1 +1 @@ fn main\n-ok\n+ok too\n",
+                        b"@@ -9,2 +9,2 @@ fn other\n-\x81\n+\x81\x8d\n",
+                    ],
+                ),
+                "@@ -1 +1 @@ fn main\n-ok\n+ok too\n@@ -9,2 +9,2 @@\n\
+                 [UNDECODABLE HUNK: 2 line(s) in an unknown encoding were left out]\n"
+                    .to_string(),
+            ),
+        ];
+        for (bytes, expected_hunks) in cases {
+            let decoded = decode_diff(&bytes);
+            let header_end = decoded.find("@@").unwrap();
+            assert_eq!(&decoded[header_end..], expected_hunks);
+            assert!(!decoded.contains('\u{fffd}'), "{}", decoded);
+        }
+    }
+
+    #[test]
+    fn test_decode_diff_decodes_each_file_on_its_own() {
+        let mut bytes = b"note before the diff\n".to_vec();
+        bytes.extend(section("a.txt", &[b"@@ -1 +1 @@\n+\xe9t\xe9\n"]));
+        bytes.extend(section("b.txt", &["@@ -1 +1 @@\n+été\n".as_bytes()]));
+        assert_eq!(
+            decode_diff(&bytes),
+            "note before the diff\n\
+             diff --git a/a.txt b/a.txt\n--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n+été\n\
+             diff --git a/b.txt b/b.txt\n--- a/b.txt\n+++ b/b.txt\n@@ -1 +1 @@\n+été\n"
+        );
+    }
+}
@@ -0,0 +1,73 @@
+//! Runtime environment detection for ASUM.
+//!
+//! Detects remote development containers (GitHub Codespaces, VS Code Dev Containers)
+//! where the desktop clipboard and a persistent home directory are not available.
+
+/// Environment variables set by remote development containers.
+const CONTAINER_VARS: &[&str] = &["CODESPACES", "REMOTE_CONTAINERS", "DEVCONTAINER"];
+
+/// Returns `true` when running inside Codespaces or a dev container.
+pub fn is_remote_container() -> bool {
+    detect_remote_container(|name| std::env::var(name).ok())
+}
+
+/// Detects a remote container using the given environment lookup.
+/// Variables explicitly set to `false` or `0` are ignored.
+pub fn detect_remote_container<F: Fn(&str) -> Option<String>>(var: F) -> bool {
+    CONTAINER_VARS.iter().any(|name| {
+        var(name).is_some_and(|value| {
+            let value = value.trim().to_lowercase();
+            !value.is_empty() && value != "false" && value != "0"
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_remote_container_table_driven() {
+        struct TestCase {
+            vars: Vec<(&'static str, &'static str)>,
+            expected: bool,
+        }
+
+        let cases = vec![
+            TestCase {
+                vars: vec![],
+                expected: false,
+            },
+            TestCase {
+                vars: vec![("CODESPACES", "true")],
+                expected: true,
+            },
+            TestCase {
+                vars: vec![("REMOTE_CONTAINERS", "true")],
+                expected: true,
+            },
+            TestCase {
+                vars: vec![("DEVCONTAINER", "1")],
+                expected: true,
+            },
+            TestCase {
+                vars: vec![("CODESPACES", "false"), ("REMOTE_CONTAINERS", "")],
+                expected: false,
+            },
+            TestCase {
+                vars: vec![("HOME", "/root")],
+                expected: false,
+            },
+        ];
+
+        for case in cases {
+            let vars = case.vars.clone();
+            let detected = detect_remote_container(|name| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            });
+            assert_eq!(detected, case.expected, "vars: {:?}", case.vars);
+        }
+    }
+}
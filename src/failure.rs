@@ -0,0 +1,146 @@
+//! Exit codes for scripting.
+//!
+//! Failures that git hooks and CI wrappers may want to handle differently are
+//! tagged with a [`Failure`], and `main` exits with its code. Other errors exit
+//! with 1, and command line usage errors with 2 (as reported by clap).
+
+use std::error::Error;
+use std::fmt;
+
+/// Exit code of errors that are not tagged with a [`Failure`].
+pub const GENERAL_ERROR: u8 = 1;
+
+/// A failure with its own exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// There were no changes to summarize (exit code 3).
+    NoChanges,
+    /// The configuration file is missing, unreadable or invalid (exit code 4).
+    Config,
+    /// The provider could not be reached or answered with an error (exit code 5).
+    Provider,
+    /// The provider did not answer in time (exit code 6).
+    Timeout,
+    /// The reply was empty or not a usable message (exit code 7).
+    InvalidOutput,
+}
+
+impl Failure {
+    /// The process exit code for this failure.
+    pub fn code(self) -> u8 {
+        match self {
+            Failure::NoChanges => 3,
+            Failure::Config => 4,
+            Failure::Provider => 5,
+            Failure::Timeout => 6,
+            Failure::InvalidOutput => 7,
+        }
+    }
+
+    /// Creates an error with `message`, tagged with this failure.
+    pub fn error(self, message: impl fmt::Display) -> anyhow::Error {
+        self.tag(anyhow::anyhow!("{}", message))
+    }
+
+    /// Tags `error` with this failure. Its message and causes are unchanged.
+    pub fn tag(self, error: anyhow::Error) -> anyhow::Error {
+        anyhow::Error::new(Tagged {
+            failure: self,
+            error,
+        })
+    }
+}
+
+/// An error tagged with a failure. It displays as the error it wraps.
+#[derive(Debug)]
+struct Tagged {
+    failure: Failure,
+    error: anyhow::Error,
+}
+
+impl fmt::Display for Tagged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl Error for Tagged {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.error.chain().nth(1)
+    }
+}
+
+/// Returns the failure `error` was tagged with, or the one its cause implies:
+/// HTTP errors are provider failures, or timeouts when the request timed out.
+pub fn failure_of(error: &anyhow::Error) -> Option<Failure> {
+    error.chain().find_map(|cause| {
+        if let Some(tagged) = cause.downcast_ref::<Tagged>() {
+            return Some(tagged.failure);
+        }
+        #[cfg(any(feature = "gemini", feature = "ollama"))]
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return Some(if e.is_timeout() {
+                Failure::Timeout
+            } else {
+                Failure::Provider
+            });
+        }
+        cause
+            .downcast_ref::<std::io::Error>()
+            .filter(|e| e.kind() == std::io::ErrorKind::TimedOut)
+            .map(|_| Failure::Timeout)
+    })
+}
+
+/// The process exit code for `error`.
+pub fn exit_code(error: &anyhow::Error) -> u8 {
+    failure_of(error).map_or(GENERAL_ERROR, Failure::code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{Context, anyhow};
+
+    #[test]
+    fn test_exit_code_table_driven() {
+        let cases = vec![
+            ("untagged", anyhow!("boom"), GENERAL_ERROR),
+            (
+                "no changes",
+                Failure::NoChanges.error("No staged changes found."),
+                3,
+            ),
+            (
+                "config with context",
+                Err::<(), _>(Failure::Config.error("missing"))
+                    .context("Failed to load configuration")
+                    .unwrap_err(),
+                4,
+            ),
+            ("provider", Failure::Provider.tag(anyhow!("HTTP 500")), 5),
+            (
+                "io timeout",
+                anyhow::Error::new(std::io::Error::from(std::io::ErrorKind::TimedOut)),
+                6,
+            ),
+            ("invalid output", Failure::InvalidOutput.error("empty"), 7),
+        ];
+        for (name, error, expected) in cases {
+            assert_eq!(exit_code(&error), expected, "case: {}", name);
+        }
+    }
+
+    #[test]
+    fn test_tag_keeps_message_and_causes() {
+        let error = Err::<(), _>(anyhow!("connection refused"))
+            .context("Could not reach ollama")
+            .unwrap_err();
+        let tagged = Failure::Provider.tag(error);
+        assert_eq!(
+            format!("{:#}", tagged),
+            "Could not reach ollama: connection refused"
+        );
+        assert_eq!(tagged.chain().count(), 2);
+    }
+}
@@ -0,0 +1,170 @@
+//! Commit message footers derived from the changed files.
+//!
+//! Some repositories keep their issues as markdown files next to the code, such as
+//! `docs/issues/123.md`. When a commit touches one, the message should reference the
+//! issue. `[conventions] issue_footers` maps path patterns to footers, and matching
+//! footers are appended to generated commit messages.
+
+/// Placeholder for the issue id in patterns and footers.
+pub const ID: &str = "{id}";
+
+/// Separator between the path pattern and the footer in `issue_footers`.
+pub const ARROW: &str = "->";
+
+/// Mappings used when `issue_footers` is not configured.
+pub const DEFAULT_ISSUE_FOOTERS: &[&str] = &[
+    "docs/issues/{id}.md -> Refs: #{id}",
+    "issues/{id}.md -> Refs: #{id}",
+];
+
+/// Parses a `"<pattern> -> <footer>"` mapping. The pattern must contain `{id}` once.
+pub fn parse_mapping(mapping: &str) -> Option<(String, String)> {
+    let (pattern, footer) = mapping.split_once(ARROW)?;
+    let (pattern, footer) = (pattern.trim(), footer.trim());
+    (pattern.matches(ID).count() == 1 && !footer.is_empty())
+        .then(|| (pattern.to_string(), footer.to_string()))
+}
+
+/// Returns the footers of the `mappings` that match one of `paths`, in path order and
+/// without duplicates.
+pub fn issue_footers(mappings: &[(String, String)], paths: &[String]) -> Vec<String> {
+    let mut footers: Vec<String> = Vec::new();
+    for path in paths {
+        for (pattern, footer) in mappings {
+            if let Some(id) = capture(pattern, path) {
+                let footer = footer.replace(ID, id);
+                if !footers.contains(&footer) {
+                    footers.push(footer);
+                }
+            }
+        }
+    }
+    footers
+}
+
+/// Returns the part of `path` that takes the place of `{id}` in `pattern`. Ids are
+/// made of letters, digits, `-` and `_`, and must contain a digit, so a README or
+/// template next to the issues does not count as one.
+fn capture<'a>(pattern: &str, path: &'a str) -> Option<&'a str> {
+    let (prefix, suffix) = pattern.split_once(ID)?;
+    let id = path.strip_prefix(prefix)?.strip_suffix(suffix)?;
+    let valid = id.chars().any(|c| c.is_ascii_digit())
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then_some(id)
+}
+
+/// Appends the `footers` the message does not have yet. They join a trailer block
+/// that ends the message, or start a new paragraph.
+pub fn append(message: &str, footers: &[String]) -> String {
+    let message = message.trim_end();
+    let missing: Vec<&str> = footers
+        .iter()
+        .map(String::as_str)
+        .filter(|footer| !message.lines().any(|line| line.trim() == *footer))
+        .collect();
+    if missing.is_empty() {
+        return message.to_string();
+    }
+    let ends_with_trailers = message
+        .rsplit_once("\n\n")
+        .is_some_and(|(_, last)| last.lines().all(is_trailer));
+    let separator = if ends_with_trailers { "\n" } else { "\n\n" };
+    format!("{}{}{}", message, separator, missing.join("\n"))
+}
+
+/// Whether `line` looks like a git trailer, e.g. `Refs: #12` or `Closes #12`.
+pub fn is_trailer(line: &str) -> bool {
+    if line.starts_with("BREAKING CHANGE: ") {
+        return true;
+    }
+    let Some(end) = line.find([':', ' ']) else {
+        return false;
+    };
+    let token = &line[..end];
+    !token.is_empty()
+        && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        && (line[end..].starts_with(": ") || line[end..].starts_with(" #"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mappings() -> Vec<(String, String)> {
+        DEFAULT_ISSUE_FOOTERS
+            .iter()
+            .filter_map(|mapping| parse_mapping(mapping))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_mapping_table_driven() {
+        let cases = [
+            (
+                "docs/issues/{id}.md -> Refs: #{id}",
+                Some(("docs/issues/{id}.md", "Refs: #{id}")),
+            ),
+            (
+                "tickets/{id}/README.md->Closes {id}",
+                Some(("tickets/{id}/README.md", "Closes {id}")),
+            ),
+            ("docs/issues/*.md -> Refs: #1", None),
+            ("{id}/{id}.md -> Refs: #{id}", None),
+            ("docs/issues/{id}.md", None),
+            ("docs/issues/{id}.md -> ", None),
+        ];
+        for (mapping, expected) in cases {
+            let expected = expected.map(|(p, f): (&str, &str)| (p.to_string(), f.to_string()));
+            assert_eq!(parse_mapping(mapping), expected, "{}", mapping);
+        }
+    }
+
+    #[test]
+    fn test_issue_footers_table_driven() {
+        let cases = [
+            (vec!["docs/issues/123.md"], vec!["Refs: #123"]),
+            (
+                vec!["issues/7.md", "src/main.rs", "docs/issues/42.md"],
+                vec!["Refs: #7", "Refs: #42"],
+            ),
+            (
+                vec!["docs/issues/README.md", "docs/issues/old/1.md"],
+                vec![],
+            ),
+            (
+                vec!["docs/issues/ABC-12.md", "issues/ABC-12.md"],
+                vec!["Refs: #ABC-12"],
+            ),
+            (vec!["src/issues/9.md"], vec![]),
+        ];
+        for (paths, expected) in cases {
+            let paths: Vec<String> = paths.into_iter().map(String::from).collect();
+            assert_eq!(issue_footers(&mappings(), &paths), expected, "{:?}", paths);
+        }
+    }
+
+    #[test]
+    fn test_append_table_driven() {
+        let footers = vec!["Refs: #12".to_string()];
+        let cases = [
+            ("feat: add login", "feat: add login\n\nRefs: #12"),
+            (
+                "feat: add login\n\n- Add the form\n",
+                "feat: add login\n\n- Add the form\n\nRefs: #12",
+            ),
+            (
+                "feat: add login\n\nAdd the form.\n\nReviewed-by: Ann",
+                "feat: add login\n\nAdd the form.\n\nReviewed-by: Ann\nRefs: #12",
+            ),
+            (
+                "feat: add login\n\nRefs: #12",
+                "feat: add login\n\nRefs: #12",
+            ),
+        ];
+        for (message, expected) in cases {
+            assert_eq!(append(message, &footers), expected, "{:?}", message);
+        }
+    }
+}
@@ -1,34 +1,423 @@
 //! Git utility module for ASUM.
 //!
-//! This module interacts with the Git CLI to retrieve staged changes
-//! and file lists for AI analysis.
+//! This module reads staged changes, history and repository metadata through
+//! libgit2 (the `git2` crate), so generating a message does not need a git binary
+//! and never depends on the locale or output settings of one. Creating commits
+//! still runs `git commit`, which applies the user's hooks, editor and signing setup.
 
+use crate::encoding;
+use anyhow::{Context, anyhow};
+use git2::{
+    BranchType, Commit, DescribeFormatOptions, DescribeOptions, Diff, DiffFindOptions, DiffFormat,
+    DiffOptions, ErrorCode, Repository, RevparseMode, Revwalk, Sort, StashFlags, StatusOptions,
+    Tree,
+};
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Builds a git command running in `path`, for the operations that hand over to git.
+/// `core.quotepath` is disabled so non-ASCII file names appear verbatim in git's
+/// output instead of as octal escapes like `"\346\226\207"`. A relative `GIT_DIR` or
+/// `GIT_WORK_TREE` is made absolute, since git would resolve it from `path`.
+pub fn git_command<P: AsRef<Path>>(path: P) -> Command {
+    let mut command = Command::new("git");
+    command.args(["-c", "core.quotepath=off"]).current_dir(path);
+    for name in [GIT_DIR, GIT_WORK_TREE] {
+        if let Some(value) = env_path(name) {
+            command.env(name, value);
+        }
+    }
+    command
+}
+
+/// Environment variable naming the git directory, as git sets it for hooks.
+const GIT_DIR: &str = "GIT_DIR";
+
+/// Environment variable naming the work tree that goes with `GIT_DIR`.
+const GIT_WORK_TREE: &str = "GIT_WORK_TREE";
+
+/// The absolute path in environment variable `name`, if it is set and not empty.
+fn env_path(name: &str) -> Option<PathBuf> {
+    std::env::var_os(name)
+        .filter(|value| !value.is_empty())
+        .and_then(|value| std::path::absolute(value).ok())
+}
+
+/// Opens the repository containing `path`, or the one `GIT_DIR` and `GIT_WORK_TREE`
+/// select, as git would. Linked worktrees, whose `.git` is a file, are found like
+/// any other checkout.
+fn open_repo<P: AsRef<Path>>(path: P) -> anyhow::Result<Repository> {
+    open_repo_with(path.as_ref(), env_path(GIT_DIR), env_path(GIT_WORK_TREE))
+}
+
+/// Opens the repository at `git_dir` when given, with `work_tree` as its work tree.
+/// Without `work_tree` or a `core.worktree` setting, a repository that is not bare
+/// gets `path` as its work tree, like git uses the current directory. Otherwise the
+/// repository containing `path` is opened. Being outside any repository and a
+/// repository git refuses to use get errors that say what to do about them.
+fn open_repo_with(
+    path: &Path,
+    git_dir: Option<PathBuf>,
+    work_tree: Option<PathBuf>,
+) -> anyhow::Result<Repository> {
+    let Some(git_dir) = git_dir else {
+        return discover_repo(path);
+    };
+    let repo = Repository::open(&git_dir).map_err(|e| {
+        anyhow!(
+            "GIT_DIR is set to {}, which is not a git repository: {}",
+            git_dir.display(),
+            e.message()
+        )
+    })?;
+    let configured = repo
+        .config()
+        .is_ok_and(|config| config.get_path("core.worktree").is_ok());
+    let work_tree = match work_tree {
+        Some(work_tree) => Some(work_tree),
+        None if !repo.is_bare() && !configured => Some(std::path::absolute(path)?),
+        None => None,
+    };
+    if let Some(work_tree) = work_tree {
+        repo.set_workdir(&work_tree, false).map_err(|e| {
+            anyhow!(
+                "Could not use {} as the work tree: {}",
+                work_tree.display(),
+                e.message()
+            )
+        })?;
+    }
+    Ok(repo)
+}
+
+/// Opens the repository containing `path`, looking in its parents like git.
+fn discover_repo(path: &Path) -> anyhow::Result<Repository> {
+    Repository::discover(path).map_err(|e| match e.code() {
+        ErrorCode::NotFound => anyhow!(
+            "Not a git repository: neither {} nor any of its parents is one. Run asum inside \
+             a repository (`git init` creates one), or pass --source stdin or \
+             --source patch:<FILE> to summarize a diff from elsewhere.",
+            path.display()
+        ),
+        ErrorCode::Owner => anyhow!(
+            "Git refuses to use the repository at {}: it is owned by another user. If you \
+             trust it, run `git config --global --add safe.directory <DIR>` with its top-level \
+             directory.",
+            path.display()
+        ),
+        _ => anyhow!(
+            "Could not open the git repository at {}: {}",
+            path.display(),
+            e.message()
+        ),
+    })
+}
+
+/// Opens the repository containing `path` for reading its staged changes, which a
+/// bare repository does not have.
+fn open_work_tree<P: AsRef<Path>>(path: P) -> anyhow::Result<Repository> {
+    let repo = open_repo(path)?;
+    if repo.is_bare() {
+        return Err(anyhow!(
+            "{} is a bare repository, which has no work tree or staged changes. Run asum in \
+             a clone of it.",
+            repo.path().display()
+        ));
+    }
+    Ok(repo)
+}
+
+/// Error for a git command that could not be started, telling a missing git binary
+/// apart from other failures.
+pub fn git_spawn_error(error: std::io::Error) -> anyhow::Error {
+    match error.kind() {
+        std::io::ErrorKind::NotFound => {
+            anyhow!("git is not installed or not on PATH; install it or add it to PATH.")
+        }
+        _ => anyhow!("Failed to run git: {}", error),
+    }
+}
 
 /// Retrieves the git diff of staged changes for the specified file extensions in the current directory.
 pub fn get_git_diff(extensions: &[String]) -> anyhow::Result<String> {
     get_git_diff_in_path(extensions, ".")
 }
 
+/// Prefix of the pathspecs that leave files out of a diff, as in git.
+pub const EXCLUDE_MAGIC: &str = ":(exclude)";
+
+/// Marker placed before the diff of an intent-to-add file (`git add -N`).
+/// Such files are tracked but none of their content has been staged yet.
+pub const INTENT_TO_ADD_MARKER: &str = "# new file (not yet staged content)";
+
+/// Returns the pathspecs selecting files that match `extensions` (every file when
+/// none is given) and none of its `:(exclude)` pathspecs, in libgit2 syntax.
+fn pathspecs(extensions: &[String]) -> Vec<String> {
+    // libgit2 applies the first pattern that matches, so the exclusions go first
+    let (excluded, included): (Vec<&String>, Vec<&String>) = extensions
+        .iter()
+        .partition(|spec| spec.starts_with(EXCLUDE_MAGIC));
+    let mut specs: Vec<String> = excluded
+        .iter()
+        .map(|spec| format!("!{}", &spec[EXCLUDE_MAGIC.len()..]))
+        .collect();
+    if included.is_empty() {
+        specs.push("*".to_string());
+    }
+    specs.extend(included.into_iter().cloned());
+    specs
+}
+
+/// Builds diff options limited to the files selected by `pathspecs(extensions)`.
+fn diff_options(extensions: &[String]) -> DiffOptions {
+    let mut options = DiffOptions::new();
+    for spec in pathspecs(extensions) {
+        options.pathspec(spec);
+    }
+    options
+}
+
+/// Escapes the glob characters of `path` so a pathspec matches only that file.
+fn literal_pathspec(path: &str) -> String {
+    path.chars()
+        .flat_map(|c| match c {
+            '*' | '?' | '[' | '\\' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect()
+}
+
+/// Detects renames and copies like `git diff -M -C`, so a moved file shows as a
+/// rename instead of a deletion and an unrelated new file. Copies are found from
+/// files the diff modifies, as with `-C`.
+fn find_renames(diff: &mut Diff) -> Result<(), git2::Error> {
+    diff.find_similar(Some(DiffFindOptions::new().renames(true).copies(true)))
+}
+
+/// Renders `diff` as the patch text `git diff` prints, with non-ASCII file names
+/// left unquoted.
+fn patch_text(diff: &Diff) -> anyhow::Result<String> {
+    let mut text = Vec::new();
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        match line.origin() {
+            '+' | '-' | ' ' => text.push(line.origin() as u8),
+            'F' => {
+                text.extend(unquote_paths(line.content()));
+                return true;
+            }
+            _ => {}
+        }
+        text.extend_from_slice(line.content());
+        true
+    })?;
+    Ok(encoding::decode_diff(&text))
+}
+
+/// Removes the quotes libgit2 puts around file names in diff headers, such as
+/// `"b/\346\226\207.rs"` for non-ASCII names or `"b/we\"ird.rs"` for a quote. Names
+/// with control characters, such as a tab or a newline, keep their quotes, since
+/// written out they would break the header line.
+fn unquote_paths(header: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(header.len());
+    let mut rest = header;
+    while let Some(start) = rest.iter().position(|&b| b == b'"') {
+        result.extend_from_slice(&rest[..start]);
+        let Some((name, length)) = c_unquote(&rest[start + 1..]) else {
+            rest = &rest[start..];
+            break;
+        };
+        if name.iter().any(|b| b.is_ascii_control()) {
+            result.extend_from_slice(&rest[start..start + length + 2]);
+        } else {
+            result.extend(name);
+        }
+        rest = &rest[start + length + 2..];
+    }
+    result.extend_from_slice(rest);
+    result
+}
+
+/// Decodes a name git quoted C-style, from just after its opening quote: `\"`, `\\`,
+/// `\a`, `\b`, `\t`, `\n`, `\v`, `\f`, `\r` and three-digit octal escapes. Returns
+/// the name and the length of the quoted text before the closing quote, or `None`
+/// when there is no closing quote or an escape is not valid.
+fn c_unquote(quoted: &[u8]) -> Option<(Vec<u8>, usize)> {
+    let mut name = Vec::with_capacity(quoted.len());
+    let mut i = 0;
+    loop {
+        match *quoted.get(i)? {
+            b'"' => return Some((name, i)),
+            b'\\' => {
+                let escaped = *quoted.get(i + 1)?;
+                let byte = match escaped {
+                    b'"' | b'\\' => escaped,
+                    b'a' => 0x07,
+                    b'b' => 0x08,
+                    b't' => b'\t',
+                    b'n' => b'\n',
+                    b'v' => 0x0b,
+                    b'f' => 0x0c,
+                    b'r' => b'\r',
+                    b'0'..=b'3' => {
+                        let digits = std::str::from_utf8(quoted.get(i + 1..i + 4)?).ok()?;
+                        i += 2;
+                        u8::from_str_radix(digits, 8).ok()?
+                    }
+                    _ => return None,
+                };
+                name.push(byte);
+                i += 2;
+            }
+            byte => {
+                name.push(byte);
+                i += 1;
+            }
+        }
+    }
+}
+
+/// Renders the files of `diff` as `git diff --name-status` lines. Renames and copies
+/// list the old and the new path, without git's similarity score.
+fn name_status(diff: &Diff) -> String {
+    let path = |file: git2::DiffFile| {
+        String::from_utf8_lossy(file.path_bytes().unwrap_or_default()).into_owned()
+    };
+    diff.deltas()
+        .map(|delta| match delta.status() {
+            git2::Delta::Renamed | git2::Delta::Copied => format!(
+                "{}\t{}\t{}\n",
+                status_letter(delta.status()),
+                path(delta.old_file()),
+                path(delta.new_file())
+            ),
+            git2::Delta::Deleted => format!("D\t{}\n", path(delta.old_file())),
+            status => format!("{}\t{}\n", status_letter(status), path(delta.new_file())),
+        })
+        .collect()
+}
+
+/// Returns the `--name-status` letter of a change.
+fn status_letter(status: git2::Delta) -> char {
+    match status {
+        git2::Delta::Added | git2::Delta::Untracked => 'A',
+        git2::Delta::Deleted => 'D',
+        git2::Delta::Renamed => 'R',
+        git2::Delta::Copied => 'C',
+        git2::Delta::Typechange => 'T',
+        git2::Delta::Conflicted => 'U',
+        _ => 'M',
+    }
+}
+
+/// Returns the tree `HEAD` points to, or `None` before the first commit.
+fn head_tree(repo: &Repository) -> Option<Tree<'_>> {
+    repo.head().ok()?.peel_to_tree().ok()
+}
+
+/// Returns the tree of the first parent of `commit`, or `None` for a root commit.
+fn parent_tree<'r>(commit: &Commit<'r>) -> anyhow::Result<Option<Tree<'r>>> {
+    match commit.parent(0) {
+        Ok(parent) => Ok(Some(parent.tree()?)),
+        Err(e) if e.code() == ErrorCode::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Returns the paths of the intent-to-add entries (`git add -N`) in the index.
+fn intent_to_add_paths(repo: &Repository) -> anyhow::Result<Vec<String>> {
+    Ok(repo
+        .index()?
+        .iter()
+        .filter(|entry| {
+            entry.flags_extended & git2::IndexEntryExtendedFlag::INTENT_TO_ADD.bits() != 0
+        })
+        .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+        .collect())
+}
+
+/// Diffs `HEAD` against the index like `git diff --cached`, limited to the files
+/// selected by `specs` (every file when empty).
+fn staged_diff<'r>(
+    repo: &'r Repository,
+    specs: &[String],
+    context_lines: u32,
+) -> anyhow::Result<Diff<'r>> {
+    index_diff(repo, head_tree(repo).as_ref(), specs, context_lines)
+}
+
+/// Diffs `tree` against the index like `git diff --cached <tree>`, limited to the
+/// files selected by `specs` (every file when empty). libgit2 reports intent-to-add
+/// entries as empty new files, so they are left out like git does.
+fn index_diff<'r>(
+    repo: &'r Repository,
+    tree: Option<&Tree>,
+    specs: &[String],
+    context_lines: u32,
+) -> anyhow::Result<Diff<'r>> {
+    let mut options = DiffOptions::new();
+    options.context_lines(context_lines);
+    let intent_to_add = intent_to_add_paths(repo)?;
+    for path in &intent_to_add {
+        options.pathspec(format!("!{}", literal_pathspec(path)));
+    }
+    if specs.is_empty() && !intent_to_add.is_empty() {
+        options.pathspec("*");
+    }
+    for spec in specs {
+        options.pathspec(spec);
+    }
+
+    let mut diff = repo.diff_tree_to_index(tree, None, Some(&mut options))?;
+    find_renames(&mut diff)?;
+    Ok(diff)
+}
+
+/// Diffs the intent-to-add files selected by `specs` as new files with their working
+/// tree content, like `git diff --diff-filter=A`. Returns `None` when there are none.
+fn intent_to_add_diff<'r>(
+    repo: &'r Repository,
+    specs: &[String],
+) -> anyhow::Result<Option<Diff<'r>>> {
+    let filter = git2::Pathspec::new(specs)?;
+    let paths: Vec<String> = intent_to_add_paths(repo)?
+        .into_iter()
+        .filter(|path| {
+            specs.is_empty() || filter.matches_path(Path::new(path), git2::PathspecFlags::DEFAULT)
+        })
+        .collect();
+    if paths.is_empty() {
+        return Ok(None);
+    }
+
+    // Compared with no tree, the files read as untracked and are shown in full
+    let mut options = DiffOptions::new();
+    options
+        .disable_pathspec_match(true)
+        .include_untracked(true)
+        .show_untracked_content(true);
+    for path in &paths {
+        options.pathspec(path);
+    }
+    Ok(Some(repo.diff_tree_to_workdir(None, Some(&mut options))?))
+}
+
 /// Retrieves the git diff of staged changes for the specified file extensions in a specific directory.
-/// It excludes common lock files and minified scripts to keep the diff clean.
+/// `:(exclude)` pathspecs among the extensions leave files out, such as lock files.
+/// Intent-to-add files are included as full new-file diffs, marked with `INTENT_TO_ADD_MARKER`.
 pub fn get_git_diff_in_path(extensions: &[String], path: &str) -> anyhow::Result<String> {
-    let mut args = vec!["diff", "--cached", "--"];
-    // Add file patterns to include based on configuration
-    for ext in extensions {
-        args.push(ext);
-    }
-    // Explicitly exclude generated or binary-like files that aren't useful for summaries
-    args.extend([
-        ":(exclude)*-lock.json",
-        ":(exclude)package-lock.json",
-        ":(exclude)pnpm-lock.yaml",
-        ":(exclude)*.min.js",
-    ]);
-
-    let output = Command::new("git").args(args).current_dir(path).output()?;
-
-    let diff_text = String::from_utf8_lossy(&output.stdout).to_string();
+    let repo = open_work_tree(path)?;
+    let specs = pathspecs(extensions);
+    let mut diff_text = patch_text(&staged_diff(&repo, &specs, 3)?)?;
+    if let Some(diff) = intent_to_add_diff(&repo, &specs)? {
+        for line in patch_text(&diff)?.split_inclusive('\n') {
+            if line.starts_with("diff --git ") {
+                diff_text.push_str(INTENT_TO_ADD_MARKER);
+                diff_text.push('\n');
+            }
+            diff_text.push_str(line);
+        }
+    }
     Ok(diff_text)
 }
 
@@ -39,22 +428,1111 @@ pub fn get_staged_files() -> anyhow::Result<String> {
 
 /// Retrieves a list of staged files and their status in a specific directory.
 /// This is used as a fallback when no code diff is available.
+/// Intent-to-add files are listed as added, with a note that their content is not staged.
 pub fn get_staged_files_in_path(path: &str) -> anyhow::Result<String> {
-    let args = vec![
-        "diff",
-        "--cached",
-        "--name-status",
-        "--",
-        ":(exclude)*-lock.json",
-        ":(exclude)package-lock.json",
-        ":(exclude)pnpm-lock.yaml",
-        ":(exclude)*.min.js",
-    ];
-    let output = Command::new("git").args(args).current_dir(path).output()?;
-    let files_text = String::from_utf8_lossy(&output.stdout).to_string();
+    let repo = open_work_tree(path)?;
+    let specs = pathspecs(&[]);
+    let mut files_text = name_status(&staged_diff(&repo, &specs, 3)?);
+    if let Some(diff) = intent_to_add_diff(&repo, &specs)? {
+        for line in name_status(&diff).lines() {
+            files_text.push_str(&format!("{} (new file, not yet staged content)\n", line));
+        }
+    }
     Ok(files_text)
 }
 
+/// Retrieves the unstaged working tree diff of tracked files for the specified file extensions
+/// in a specific directory.
+pub fn get_worktree_diff_in_path(extensions: &[String], path: &str) -> anyhow::Result<String> {
+    let repo = open_repo(path)?;
+    let mut options = diff_options(extensions);
+    patch_text(&repo.diff_index_to_workdir(None, Some(&mut options))?)
+}
+
+/// Retrieves the staged and unstaged changes of tracked files relative to `HEAD`
+/// for the specified file extensions in a specific directory: what `git stash`
+/// would save. When none of the changed files match, their names and status are
+/// listed instead. With `untracked`, untracked files are listed as well.
+pub fn get_uncommitted_changes_in_path(
+    extensions: &[String],
+    untracked: bool,
+    path: &str,
+) -> anyhow::Result<String> {
+    let repo = open_repo(path)?;
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_tree())
+        .map_err(|e| anyhow!("Could not read the uncommitted changes: {}", e.message()))?;
+
+    let mut options = diff_options(extensions);
+    let mut diff = repo.diff_tree_to_workdir_with_index(Some(&head), Some(&mut options))?;
+    find_renames(&mut diff)?;
+    let mut changes = patch_text(&diff)?;
+    if changes.is_empty() {
+        let mut diff = repo.diff_tree_to_workdir_with_index(Some(&head), None)?;
+        find_renames(&mut diff)?;
+        changes = name_status(&diff);
+    }
+
+    if untracked {
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .include_ignored(false);
+        for entry in repo.statuses(Some(&mut options))?.iter() {
+            if entry.status().is_wt_new() {
+                changes.push_str(&format!(
+                    "{} (new file, untracked)\n",
+                    String::from_utf8_lossy(entry.path_bytes())
+                ));
+            }
+        }
+    }
+    Ok(changes)
+}
+
+/// Stashes the local changes with `message` in a specific directory, including
+/// untracked files with `untracked`.
+pub fn stash_push_in_path(message: &str, untracked: bool, path: &Path) -> anyhow::Result<()> {
+    let mut repo = open_repo(path)?;
+    let signature = repo.signature()?;
+    let flags = if untracked {
+        StashFlags::INCLUDE_UNTRACKED
+    } else {
+        StashFlags::DEFAULT
+    };
+
+    repo.stash_save2(&signature, Some(message), Some(flags))
+        .map_err(|e| anyhow!("git stash failed: {}", e.message()))?;
+    Ok(())
+}
+
+/// Size of a set of changes: files touched and lines added plus removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DiffStat {
+    pub files: usize,
+    pub lines: usize,
+}
+
+impl DiffStat {
+    /// Measures `diff`. Binary files count as files without lines.
+    fn of(diff: &Diff) -> anyhow::Result<Self> {
+        let stats = diff.stats()?;
+        Ok(Self {
+            files: stats.files_changed(),
+            lines: stats.insertions() + stats.deletions(),
+        })
+    }
+}
+
+/// Retrieves the size of the staged changes in a specific directory.
+pub fn get_staged_stat_in_path(path: &Path) -> anyhow::Result<DiffStat> {
+    let repo = open_repo(path)?;
+    DiffStat::of(&staged_diff(&repo, &[], 3)?)
+}
+
+/// Diffs a commit against its first parent, like `git show`, limited to `options`.
+fn commit_diff<'r>(
+    repo: &'r Repository,
+    commit: &Commit<'r>,
+    options: Option<&mut DiffOptions>,
+) -> anyhow::Result<Diff<'r>> {
+    let mut diff = repo.diff_tree_to_tree(
+        parent_tree(commit)?.as_ref(),
+        Some(&commit.tree()?),
+        options,
+    )?;
+    find_renames(&mut diff)?;
+    Ok(diff)
+}
+
+/// Retrieves the sizes of the last `limit` non-merge commits in a specific directory,
+/// newest first. Returns an empty list for repositories without commits.
+pub fn get_commit_stats_in_path(limit: usize, path: &Path) -> anyhow::Result<Vec<DiffStat>> {
+    let repo = open_repo(path)?;
+    let mut walk = repo.revwalk()?;
+    if walk.push_head().is_err() {
+        return Ok(Vec::new());
+    }
+
+    non_merge_commits(&repo, walk)?
+        .take(limit)
+        .map(|commit| DiffStat::of(&commit_diff(&repo, &commit?, None)?))
+        .collect()
+}
+
+/// Resolves `rev` to a commit, with `git show`-style errors.
+fn find_commit<'r>(repo: &'r Repository, rev: &str) -> anyhow::Result<Commit<'r>> {
+    repo.revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| anyhow!("Could not read commit '{}': {}", rev, e.message()))
+}
+
+/// Retrieves the diff introduced by a commit (as shown by `git show`) in a specific directory,
+/// filtered by the given file extensions. Falls back to the commit's file list when no
+/// matching code changes are found.
+pub fn get_commit_diff_in_path(
+    rev: &str,
+    extensions: &[String],
+    path: &Path,
+) -> anyhow::Result<String> {
+    let repo = open_repo(path)?;
+    let commit = find_commit(&repo, rev)?;
+
+    let diff_text = patch_text(&commit_diff(
+        &repo,
+        &commit,
+        Some(&mut diff_options(extensions)),
+    )?)?;
+    if !diff_text.trim().is_empty() {
+        return Ok(diff_text);
+    }
+    Ok(name_status(&commit_diff(&repo, &commit, None)?))
+}
+
+/// Retrieves the full message of a commit in a specific directory.
+pub fn get_commit_message_in_path(rev: &str, path: &Path) -> anyhow::Result<String> {
+    let repo = open_repo(path)?;
+    let commit = find_commit(&repo, rev)?;
+    Ok(String::from_utf8_lossy(commit.message_bytes())
+        .trim()
+        .to_string())
+}
+
+/// Resolves `rev` to the full hash of a commit in a specific directory.
+pub fn resolve_commit_in_path(rev: &str, path: &Path) -> anyhow::Result<String> {
+    let repo = open_repo(path)?;
+    let commit = find_commit(&repo, rev).map_err(|_| anyhow!("Could not find commit '{}'", rev))?;
+    Ok(commit.id().to_string())
+}
+
+/// Retrieves the committer timestamp (seconds since the Unix epoch) of a commit.
+pub fn get_commit_time_in_path(rev: &str, path: &Path) -> anyhow::Result<i64> {
+    let repo = open_repo(path)?;
+    Ok(find_commit(&repo, rev)?.committer().when().seconds())
+}
+
+/// Resolves the top-level directory of the repository containing `path`.
+pub fn get_repo_root_in_path(path: &Path) -> anyhow::Result<PathBuf> {
+    let repo = open_work_tree(path)?;
+    let root = repo
+        .workdir()
+        .context("Not a git repository: the repository has no work tree")?;
+    // Like git, paths inside the .git directory are not part of the work tree
+    let inside_git_dir = path
+        .canonicalize()
+        .ok()
+        .zip(repo.path().canonicalize().ok())
+        .is_some_and(|(path, git_dir)| path.starts_with(git_dir));
+    if inside_git_dir {
+        anyhow::bail!(
+            "Not a git repository: {} is not in the work tree",
+            path.display()
+        );
+    }
+    Ok(root.to_path_buf())
+}
+
+/// Returns the name of the checked-out branch in a specific directory, or an empty
+/// string when HEAD is detached.
+pub fn get_current_branch_in_path(path: &Path) -> anyhow::Result<String> {
+    let repo = open_repo(path)?;
+    let head = repo
+        .find_reference("HEAD")
+        .map_err(|e| anyhow!("Could not read the current branch: {}", e.message()))?;
+    Ok(head
+        .symbolic_target()
+        .and_then(|target| target.strip_prefix("refs/heads/"))
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// Returns the default branch of the repository in a specific directory: the branch
+/// `origin/HEAD` points to, else a local `main` or `master` branch.
+pub fn get_default_branch_in_path(path: &Path) -> anyhow::Result<String> {
+    let repo = open_repo(path)?;
+    if let Some(target) = repo
+        .find_reference("refs/remotes/origin/HEAD")
+        .ok()
+        .and_then(|head| head.symbolic_target().map(String::from))
+        && let Some(branch) = target.strip_prefix("refs/remotes/")
+    {
+        return Ok(branch.to_string());
+    }
+    ["main", "master"]
+        .into_iter()
+        .find(|name| repo.find_branch(name, BranchType::Local).is_ok())
+        .map(String::from)
+        .ok_or_else(|| {
+            anyhow!("Could not find the default branch: there is no origin/HEAD, main or master.")
+        })
+}
+
+/// Replaces the message of the last commit in the current directory.
+pub fn amend_message(message: &str, edit: bool) -> anyhow::Result<()> {
+    amend_message_in_path(message, edit, Path::new("."))
+}
+
+/// Replaces the message of the last commit in a specific directory.
+/// Uses `--only` so changes that are currently staged stay out of the amended commit.
+pub fn amend_message_in_path(message: &str, edit: bool, path: &Path) -> anyhow::Result<()> {
+    let mut command = git_command(path);
+    command.args(["commit", "--amend", "--only", "-m", message]);
+    if edit {
+        command.arg("--edit");
+    }
+
+    let status = command.status().map_err(git_spawn_error)?;
+    if !status.success() {
+        anyhow::bail!("git commit --amend failed ({})", status);
+    }
+    Ok(())
+}
+
+/// Creates a commit from the staged changes in the current directory.
+pub fn commit(message: &str, edit: bool) -> anyhow::Result<()> {
+    commit_in_path(message, edit, Path::new("."))
+}
+
+/// Creates a commit from the staged changes in a specific directory.
+/// With `edit`, git opens the configured editor pre-filled with the message.
+/// Git's own output (and the editor) is attached to the terminal.
+pub fn commit_in_path(message: &str, edit: bool, path: &Path) -> anyhow::Result<()> {
+    let mut command = git_command(path);
+    command.args(["commit", "-m", message]);
+    if edit {
+        command.arg("--edit");
+    }
+
+    let status = command.status().map_err(git_spawn_error)?;
+    if !status.success() {
+        anyhow::bail!("git commit failed ({})", status);
+    }
+    Ok(())
+}
+
+/// Commits the staged changes of `paths` alone in a specific directory, leaving the
+/// other staged changes staged. The index is reduced to `HEAD` plus the staged
+/// `paths` for `git commit`, and afterwards set back to everything else that was
+/// staged, whether or not the commit succeeded.
+pub fn commit_staged_paths_in_path(
+    paths: &[String],
+    message: &str,
+    path: &Path,
+) -> anyhow::Result<()> {
+    let repo = open_repo(path)?;
+    let mut index = repo.index()?;
+    if index.has_conflicts() {
+        return Err(anyhow!("The index has unresolved conflicts."));
+    }
+    let staged = repo.find_tree(index.write_tree()?)?;
+    let others: Vec<String> = staged_diff(&repo, &[], 0)?
+        .deltas()
+        .flat_map(|delta| [delta.old_file(), delta.new_file()])
+        .filter_map(|file| file.path().map(|p| p.to_string_lossy().into_owned()))
+        .filter(|file| !paths.contains(file))
+        .collect();
+    if !others.is_empty() {
+        let head = repo
+            .head()
+            .ok()
+            .and_then(|head| head.peel(git2::ObjectType::Commit).ok());
+        repo.reset_default(head.as_ref(), &others)?;
+    }
+
+    let committed = commit_in_path(message, false, path);
+    // The commit moved HEAD, so the rest of the staged tree is what is left staged
+    let mut index = repo.index()?;
+    index.read_tree(&staged)?;
+    index.write()?;
+    committed
+}
+
+/// Iterates over the commits of `walk` that are not merges, newest first. Commits
+/// are read as they are needed, so callers can stop early in long histories.
+fn non_merge_commits<'r>(
+    repo: &'r Repository,
+    mut walk: Revwalk<'r>,
+) -> anyhow::Result<impl Iterator<Item = anyhow::Result<Commit<'r>>>> {
+    walk.set_sorting(Sort::TIME)?;
+    Ok(walk
+        .map(|id| Ok(repo.find_commit(id?)?))
+        .filter(|commit| !matches!(commit, Ok(commit) if commit.parent_count() > 1)))
+}
+
+/// Adds the commits of `range` to `walk` like `git log <range>` does: `A..B` (on B
+/// but not A), `A...B` (on either side but not both) or a single revision and its
+/// history.
+fn push_range<'r>(
+    repo: &'r Repository,
+    walk: &mut Revwalk,
+    range: &str,
+) -> Result<(), git2::Error> {
+    let spec = repo.revparse(range)?;
+    let commit = |object: Option<&git2::Object<'r>>| {
+        object
+            .ok_or_else(|| git2::Error::from_str("incomplete revision range"))
+            .and_then(|object| object.peel_to_commit())
+    };
+    if spec.mode().contains(RevparseMode::SINGLE) {
+        return walk.push(commit(spec.from())?.id());
+    }
+
+    let (from, to) = (commit(spec.from())?.id(), commit(spec.to())?.id());
+    walk.push(to)?;
+    if spec.mode().contains(RevparseMode::MERGE_BASE) {
+        walk.push(from)?;
+        walk.hide(repo.merge_base(from, to)?)?;
+    } else {
+        walk.hide(from)?;
+    }
+    Ok(())
+}
+
+/// Returns the commit subject (`%s`): the first paragraph of the message on one line.
+fn subject(commit: &Commit) -> String {
+    String::from_utf8_lossy(commit.summary_bytes().unwrap_or_default()).into_owned()
+}
+
+/// Lists the subjects of the commits on `HEAD` that are not on `base`, oldest first,
+/// in a specific directory. Merge commits are skipped.
+pub fn get_branch_commits_in_path(base: &str, path: &Path) -> anyhow::Result<String> {
+    let repo = open_repo(path)?;
+    let mut walk = repo.revwalk()?;
+    push_range(&repo, &mut walk, &format!("{}..HEAD", base))
+        .map_err(|e| anyhow!("Could not compare with '{}': {}", base, e.message()))?;
+
+    let commits = non_merge_commits(&repo, walk)?.collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(commits
+        .iter()
+        .rev()
+        .map(|commit| format!("- {}\n", subject(commit)))
+        .collect())
+}
+
+/// Finds the commit among the last `limit` non-merge commits on `HEAD` whose changes
+/// the staged changes exactly undo, in a specific directory, and returns it as a
+/// `(hash, subject)` pair. Changes are compared by patch ID, like `git patch-id`, so
+/// a commit with later changes to other lines of its files still matches.
+pub fn find_reverted_commit_in_path(
+    limit: usize,
+    path: &Path,
+) -> anyhow::Result<Option<(String, String)>> {
+    let repo = open_repo(path)?;
+    let staged = staged_diff(&repo, &[], 3)?;
+    if staged.deltas().len() == 0 {
+        return Ok(None);
+    }
+    let staged_id = staged.patchid(None)?;
+
+    let mut walk = repo.revwalk()?;
+    if walk.push_head().is_err() {
+        return Ok(None);
+    }
+    for commit in non_merge_commits(&repo, walk)?.take(limit) {
+        let commit = commit?;
+        let parent = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        // The commit's own diff, backwards: from its tree to its parent's
+        let mut options = DiffOptions::new();
+        options.context_lines(3);
+        let mut reverse =
+            repo.diff_tree_to_tree(Some(&commit.tree()?), parent.as_ref(), Some(&mut options))?;
+        find_renames(&mut reverse)?;
+        if reverse.deltas().len() == staged.deltas().len() && reverse.patchid(None)? == staged_id {
+            return Ok(Some((commit.id().to_string(), subject(&commit))));
+        }
+    }
+    Ok(None)
+}
+
+/// A merge that was started but not committed yet: `MERGE_HEAD` exists, e.g. after
+/// `git merge --no-commit` or a merge that stopped on conflicts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeInProgress {
+    /// Subject git prepared for the merge commit, e.g. `Merge branch 'login'`.
+    pub subject: String,
+    /// Subjects of up to the requested number of incoming non-merge commits that are
+    /// not on `HEAD` yet, newest first.
+    pub commits: Vec<String>,
+    /// Number of incoming non-merge commits, including those not listed.
+    pub total_commits: usize,
+    /// Files git reported conflicts in, from the prepared message.
+    pub conflicts: Vec<String>,
+}
+
+/// Returns the merge in progress in a specific directory, or `None` when there is
+/// none. At most `limit` incoming commits are listed.
+pub fn get_merge_in_progress_in_path(
+    limit: usize,
+    path: &Path,
+) -> anyhow::Result<Option<MergeInProgress>> {
+    let mut repo = open_repo(path)?;
+    let mut heads = Vec::new();
+    match repo.mergehead_foreach(|id| {
+        heads.push(*id);
+        true
+    }) {
+        Ok(()) => {}
+        Err(e) if e.code() == ErrorCode::NotFound => return Ok(None),
+        Err(e) => return Err(anyhow!("Could not read MERGE_HEAD: {}", e.message())),
+    }
+    if heads.is_empty() {
+        return Ok(None);
+    }
+
+    let mut walk = repo.revwalk()?;
+    for head in &heads {
+        walk.push(*head)?;
+    }
+    if let Ok(head) = repo.head().and_then(|head| head.peel_to_commit()) {
+        walk.hide(head.id())?;
+    }
+    let incoming = non_merge_commits(&repo, walk)?.collect::<anyhow::Result<Vec<_>>>()?;
+
+    // git writes the subject and the conflicted files into MERGE_MSG, the conflicts
+    // as "# Conflicts:" followed by "#\t<path>" lines ("Conflicts:" and "\t<path>" in
+    // old versions)
+    let message = repo.message().unwrap_or_default();
+    let title = match message.lines().next().map(str::trim) {
+        Some(line) if !line.is_empty() => line.to_string(),
+        _ => format!("Merge commit '{}'", &heads[0].to_string()[..7]),
+    };
+    let conflicts = message
+        .lines()
+        .skip_while(|line| !matches!(line.trim(), "# Conflicts:" | "Conflicts:"))
+        .skip(1)
+        .map_while(|line| {
+            line.strip_prefix("#\t")
+                .or_else(|| line.strip_prefix('\t'))
+                .map(|path| path.trim().to_string())
+        })
+        .collect();
+
+    Ok(Some(MergeInProgress {
+        subject: title,
+        total_commits: incoming.len(),
+        commits: incoming.iter().take(limit).map(subject).collect(),
+        conflicts,
+    }))
+}
+
+/// Turns a date as accepted by `git log --since` into seconds since the Unix epoch:
+/// `now`, `today`, `yesterday`, `<n> <unit>[s] [ago]` with units from seconds to
+/// years (also written `<n>.<unit>s.ago`), `@<seconds>`, or a UTC date or time such
+/// as `2024-05-01` or `2024-05-01 14:00`.
+pub fn parse_date(spec: &str, now: i64) -> anyhow::Result<i64> {
+    let spec = spec.trim().to_lowercase();
+    let invalid = || {
+        anyhow!(
+            "Invalid date '{}': expected e.g. \"24 hours ago\", \"yesterday\" or 2024-05-01.",
+            spec
+        )
+    };
+
+    if let Some(seconds) = spec.strip_prefix('@') {
+        return seconds.parse().map_err(|_| invalid());
+    }
+    if let Some(time) = parse_utc_time(&spec) {
+        return Ok(time);
+    }
+    match spec.as_str() {
+        "now" => return Ok(now),
+        "today" => return Ok(now - now.rem_euclid(86_400)),
+        "yesterday" => return Ok(now - 86_400),
+        _ => {}
+    }
+
+    let words: Vec<&str> = spec
+        .split(|c: char| c.is_whitespace() || c == '.')
+        .filter(|word| !word.is_empty())
+        .collect();
+    let (amount, unit) = match words.as_slice() {
+        [amount, unit] | [amount, unit, "ago"] => (amount.parse::<i64>().ok(), *unit),
+        _ => (None, ""),
+    };
+    let unit = match unit.trim_end_matches('s') {
+        "second" | "sec" => 1,
+        "minute" | "min" => 60,
+        "hour" => 3_600,
+        "day" => 86_400,
+        "week" => 7 * 86_400,
+        "month" => 30 * 86_400,
+        "year" => 365 * 86_400,
+        _ => return Err(invalid()),
+    };
+    amount.map(|amount| now - amount * unit).ok_or_else(invalid)
+}
+
+/// Parses `YYYY-MM-DD`, optionally followed by ` HH:MM[:SS]` or `THH:MM[:SS]`, as UTC.
+fn parse_utc_time(spec: &str) -> Option<i64> {
+    let (date, time) = match spec.split_once([' ', 't']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (spec, None),
+    };
+    let mut date = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let mut seconds = 0;
+    if let Some(time) = time {
+        let parts: Vec<i64> = time
+            .split(':')
+            .map(|part| part.parse().ok())
+            .collect::<Option<_>>()?;
+        let unit = [3_600, 60, 1];
+        if !(2..=3).contains(&parts.len()) {
+            return None;
+        }
+        seconds = parts.iter().zip(unit).map(|(part, unit)| part * unit).sum();
+    }
+
+    // Days-from-civil conversion (Howard Hinnant's algorithm)
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    Some(days * 86_400 + seconds)
+}
+
+/// Seconds since the Unix epoch.
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+/// Collects the non-merge commits of `walk` committed at or after `since`, oldest first.
+/// The walk stops at the first older commit, like `git log --since` does.
+fn commits_since<'r>(
+    repo: &'r Repository,
+    walk: Revwalk<'r>,
+    since: i64,
+) -> anyhow::Result<Vec<Commit<'r>>> {
+    let mut commits = non_merge_commits(repo, walk)?
+        .take_while(|commit| {
+            commit
+                .as_ref()
+                .map_or(true, |commit| commit.committer().when().seconds() >= since)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    commits.reverse();
+    Ok(commits)
+}
+
+/// Lists the subjects of non-merge commits on any branch made since `since` (e.g.
+/// "24 hours ago", see `parse_date`) in a specific directory, oldest first.
+/// With `author`, only commits whose author name or email contains it are listed.
+pub fn get_commits_since_in_path(
+    since: &str,
+    author: Option<&str>,
+    path: &Path,
+) -> anyhow::Result<String> {
+    get_commits_since_at(since, author, now(), path)
+}
+
+/// Like `get_commits_since_in_path`, with relative dates such as "2 days ago"
+/// counted back from `now` (seconds since the Unix epoch) instead of the clock.
+pub fn get_commits_since_at(
+    since: &str,
+    author: Option<&str>,
+    now: i64,
+    path: &Path,
+) -> anyhow::Result<String> {
+    let since = parse_date(since, now)?;
+    Ok(get_commit_subjects_since_in_path(since, author, path)?
+        .iter()
+        .map(|subject| format!("- {}\n", subject))
+        .collect())
+}
+
+/// Lists the subjects of non-merge commits on any branch committed at or after
+/// `since` (seconds since the Unix epoch) in a specific directory, oldest first.
+/// With `author`, only commits whose author name or email contains it are listed.
+pub fn get_commit_subjects_since_in_path(
+    since: i64,
+    author: Option<&str>,
+    path: &Path,
+) -> anyhow::Result<Vec<String>> {
+    let repo = open_repo(path)?;
+    let mut walk = repo.revwalk()?;
+    // Like `--all`: every ref and HEAD, except the commits that store git notes
+    for reference in repo.references()? {
+        let reference = reference?;
+        if reference.name_bytes().starts_with(b"refs/notes/") {
+            continue;
+        }
+        if let Ok(commit) = reference.peel_to_commit() {
+            walk.push(commit.id())?;
+        }
+    }
+    let _ = walk.push_head();
+
+    Ok(commits_since(&repo, walk, since)?
+        .iter()
+        .filter(|commit| {
+            author.is_none_or(|author| {
+                let signature = commit.author();
+                format!(
+                    "{} <{}>",
+                    String::from_utf8_lossy(signature.name_bytes()),
+                    String::from_utf8_lossy(signature.email_bytes())
+                )
+                .contains(author)
+            })
+        })
+        .map(subject)
+        .collect())
+}
+
+/// Lists the non-merge commits on `HEAD` made since `since` as `(author name, subject)`
+/// pairs, oldest first, in a specific directory.
+pub fn get_authored_commits_since_in_path(
+    since: &str,
+    path: &Path,
+) -> anyhow::Result<Vec<(String, String)>> {
+    let since = parse_date(since, now())?;
+    let repo = open_repo(path)?;
+    let mut walk = repo.revwalk()?;
+    walk.push_head()
+        .map_err(|e| anyhow!("Could not read history: {}", e.message()))?;
+
+    Ok(commits_since(&repo, walk, since)?
+        .iter()
+        .map(|commit| {
+            (
+                String::from_utf8_lossy(commit.author().name_bytes()).into_owned(),
+                subject(commit),
+            )
+        })
+        .collect())
+}
+
+/// Lists the files tracked in the repository at `path`, relative to its root.
+pub fn get_tracked_files_in_path(path: &Path) -> anyhow::Result<Vec<String>> {
+    let repo = open_repo(path)?;
+    let mut files: Vec<String> = repo
+        .index()?
+        .iter()
+        .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+        .collect();
+    // Conflicted files have an entry per stage
+    files.dedup();
+    Ok(files)
+}
+
+/// Lists the untracked, not ignored files in the repository at `path` matching
+/// `extensions`, relative to its root.
+pub fn get_untracked_files_in_path(
+    extensions: &[String],
+    path: &Path,
+) -> anyhow::Result<Vec<String>> {
+    let repo = open_repo(path)?;
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+    for spec in pathspecs(extensions) {
+        options.pathspec(spec);
+    }
+    Ok(repo
+        .statuses(Some(&mut options))?
+        .iter()
+        .filter(|entry| entry.status().is_wt_new())
+        .map(|entry| String::from_utf8_lossy(entry.path_bytes()).into_owned())
+        .collect())
+}
+
+/// A staged file whose content is binary or kept in Git LFS, which a text diff
+/// cannot show.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryFile {
+    /// Path before the change; the same as `path` unless the file was renamed or copied.
+    pub old_path: String,
+    /// Path relative to the repository root.
+    pub path: String,
+    /// Status letter as in `--name-status`, e.g. `A` or `M`.
+    pub status: char,
+    /// Size in bytes before the change, `None` for an added file.
+    pub old_size: Option<u64>,
+    /// Size in bytes after the change, `None` for a deleted file.
+    pub new_size: Option<u64>,
+    /// Whether the file is a Git LFS pointer; the sizes are then those of the stored file.
+    pub lfs: bool,
+}
+
+/// First line of a Git LFS pointer file.
+const LFS_POINTER_VERSION: &str = "version https://git-lfs.github.com/spec/v1";
+
+/// Returns the size of the file a Git LFS pointer stands for, or `None` when
+/// `content` is not a pointer.
+pub fn lfs_pointer_size(content: &[u8]) -> Option<u64> {
+    // Pointers are a few short lines; anything larger is the file itself
+    if content.len() > 1024 {
+        return None;
+    }
+    let text = std::str::from_utf8(content).ok()?;
+    let mut lines = text.lines();
+    if lines.next()? != LFS_POINTER_VERSION {
+        return None;
+    }
+    lines.find_map(|line| line.strip_prefix("size ")?.parse().ok())
+}
+
+/// Lists the staged files of the repository at `path` whose old or new content is
+/// binary or a Git LFS pointer, including files `git_extensions` leaves out of the
+/// diff.
+pub fn get_staged_binary_files_in_path(path: &Path) -> anyhow::Result<Vec<BinaryFile>> {
+    let repo = open_repo(path)?;
+    let diff = staged_diff(&repo, &[], 3)?;
+    // (size, binary, lfs) of one side of a delta, or `None` when that side is absent
+    let content = |file: git2::DiffFile| {
+        if file.id().is_zero() || file.mode() == git2::FileMode::Commit {
+            return None;
+        }
+        let blob = repo.find_blob(file.id()).ok()?;
+        Some(match lfs_pointer_size(blob.content()) {
+            Some(size) => (size, false, true),
+            None => (blob.size() as u64, blob.is_binary(), false),
+        })
+    };
+    let path = |file: git2::DiffFile| {
+        String::from_utf8_lossy(file.path_bytes().unwrap_or_default()).into_owned()
+    };
+
+    let mut files = Vec::new();
+    for delta in diff.deltas() {
+        let old = content(delta.old_file());
+        let new = content(delta.new_file());
+        if ![old, new]
+            .iter()
+            .flatten()
+            .any(|(_, binary, lfs)| *binary || *lfs)
+        {
+            continue;
+        }
+        files.push(BinaryFile {
+            old_path: path(delta.old_file()),
+            path: path(delta.new_file()),
+            status: status_letter(delta.status()),
+            old_size: old.map(|(size, _, _)| size),
+            new_size: new.map(|(size, _, _)| size),
+            lfs: [old, new].iter().flatten().any(|(_, _, lfs)| *lfs),
+        });
+    }
+    Ok(files)
+}
+
+/// Reads a git configuration value in a specific directory, if set. Outside a
+/// repository only the global and system configuration is read.
+pub fn get_config_value_in_path(key: &str, path: &Path) -> Option<String> {
+    let config = match open_repo(path) {
+        Ok(repo) => repo.config().ok()?,
+        Err(_) => git2::Config::open_default().ok()?,
+    };
+    let value = config.get_string(key).ok()?.trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// Diffs the trees selected by `range` like `git diff <range>`: `A..B` compares A with
+/// B, `A...B` compares their merge base with B, and a single revision is compared with
+/// the working tree.
+fn range_diff<'r>(
+    repo: &'r Repository,
+    range: &str,
+    options: &mut DiffOptions,
+) -> Result<Diff<'r>, git2::Error> {
+    let spec = repo.revparse(range)?;
+    let tree = |object: Option<&git2::Object<'r>>| {
+        object
+            .ok_or_else(|| git2::Error::from_str("incomplete revision range"))
+            .and_then(|object| object.peel_to_tree())
+    };
+    if spec.mode().contains(RevparseMode::SINGLE) {
+        return repo.diff_tree_to_workdir_with_index(Some(&tree(spec.from())?), Some(options));
+    }
+
+    let to = tree(spec.to())?;
+    let from = if spec.mode().contains(RevparseMode::MERGE_BASE) {
+        let commit = |object: Option<&git2::Object<'r>>| {
+            object
+                .ok_or_else(|| git2::Error::from_str("incomplete revision range"))
+                .and_then(|object| object.peel_to_commit())
+                .map(|commit| commit.id())
+        };
+        let base = repo.merge_base(commit(spec.from())?, commit(spec.to())?)?;
+        repo.find_commit(base)?.tree()?
+    } else {
+        tree(spec.from())?
+    };
+    let mut diff = repo.diff_tree_to_tree(Some(&from), Some(&to), Some(options))?;
+    find_renames(&mut diff)?;
+    Ok(diff)
+}
+
+/// Retrieves the diff of `HEAD` against its merge base with `base` in a specific directory,
+/// filtered by the given file extensions.
+pub fn get_branch_diff_in_path(
+    base: &str,
+    extensions: &[String],
+    path: &Path,
+) -> anyhow::Result<String> {
+    let repo = open_repo(path)?;
+    let diff = range_diff(
+        &repo,
+        &format!("{}...HEAD", base),
+        &mut diff_options(extensions),
+    )
+    .map_err(|e| anyhow!("Could not compare with '{}': {}", base, e.message()))?;
+    patch_text(&diff)
+}
+
+/// Retrieves the diff of the index against its merge base with `base` in a specific
+/// directory, filtered by the given file extensions: the commits of the current
+/// branch since it left `base`, plus the staged changes.
+pub fn get_base_diff_in_path(
+    base: &str,
+    extensions: &[String],
+    path: &Path,
+) -> anyhow::Result<String> {
+    let repo = open_repo(path)?;
+    let merge_base = repo
+        .revparse_single(base)
+        .and_then(|object| object.peel_to_commit())
+        .and_then(|base| {
+            let head = repo.head()?.peel_to_commit()?;
+            repo.find_commit(repo.merge_base(base.id(), head.id())?)
+        })
+        .map_err(|e| anyhow!("Could not compare with '{}': {}", base, e.message()))?;
+    let tree = merge_base.tree()?;
+    patch_text(&index_diff(&repo, Some(&tree), &pathspecs(extensions), 3)?)
+}
+
+/// Walks the non-merge commits of `range`, oldest first, with the error messages
+/// of the range functions below.
+fn range_commits<'r>(repo: &'r Repository, range: &str) -> anyhow::Result<Vec<Commit<'r>>> {
+    let mut walk = repo.revwalk()?;
+    push_range(repo, &mut walk, range)
+        .map_err(|e| anyhow!("Could not read range '{}': {}", range, e.message()))?;
+    let mut commits = non_merge_commits(repo, walk)?.collect::<anyhow::Result<Vec<_>>>()?;
+    commits.reverse();
+    Ok(commits)
+}
+
+/// Lists the full messages of the non-merge commits in `range` (e.g. `HEAD~3..HEAD`),
+/// oldest first, in a specific directory.
+pub fn get_range_messages_in_path(range: &str, path: &Path) -> anyhow::Result<Vec<String>> {
+    let repo = open_repo(path)?;
+    Ok(range_commits(&repo, range)?
+        .iter()
+        .map(|commit| {
+            String::from_utf8_lossy(commit.message_bytes())
+                .trim()
+                .to_string()
+        })
+        .filter(|message| !message.is_empty())
+        .collect())
+}
+
+/// Lists the non-merge commits in `range` as `(hash, subject)` pairs, oldest first,
+/// in a specific directory.
+pub fn get_range_commits_in_path(
+    range: &str,
+    path: &Path,
+) -> anyhow::Result<Vec<(String, String)>> {
+    let repo = open_repo(path)?;
+    Ok(range_commits(&repo, range)?
+        .iter()
+        .map(|commit| (commit.id().to_string(), subject(commit)))
+        .collect())
+}
+
+/// Retrieves the combined diff of `range` (e.g. `HEAD~3..HEAD`) in a specific directory,
+/// filtered by the given file extensions.
+pub fn get_range_diff_in_path(
+    range: &str,
+    extensions: &[String],
+    path: &Path,
+) -> anyhow::Result<String> {
+    let repo = open_repo(path)?;
+    let diff = range_diff(&repo, range, &mut diff_options(extensions))
+        .map_err(|e| anyhow!("Could not read range '{}': {}", range, e.message()))?;
+    patch_text(&diff)
+}
+
+/// Lists the last `limit` non-merge commits on `HEAD` as `(hash, subject)` pairs,
+/// newest first, in a specific directory.
+pub fn get_recent_commits_in_path(
+    limit: usize,
+    path: &Path,
+) -> anyhow::Result<Vec<(String, String)>> {
+    let repo = open_repo(path)?;
+    let mut walk = repo.revwalk()?;
+    walk.push_head()
+        .map_err(|e| anyhow!("Could not read recent commits: {}", e.message()))?;
+
+    non_merge_commits(&repo, walk)?
+        .take(limit)
+        .map(|commit| commit.map(|commit| (commit.id().to_string(), subject(&commit))))
+        .collect()
+}
+
+/// Returns the git note (`refs/notes/commits`) attached to `rev` in a specific
+/// directory, or `None` when the commit has no note.
+pub fn get_note_in_path(rev: &str, path: &Path) -> anyhow::Result<Option<String>> {
+    let repo = open_repo(path)?;
+    let commit = find_commit(&repo, rev)?;
+    match repo.find_note(None, commit.id()) {
+        Ok(note) => Ok(Some(
+            String::from_utf8_lossy(note.message_bytes())
+                .trim_end()
+                .to_string(),
+        )),
+        Err(e) if e.code() == ErrorCode::NotFound => Ok(None),
+        Err(e) => Err(anyhow!(
+            "Could not read the note of '{}': {}",
+            rev,
+            e.message()
+        )),
+    }
+}
+
+/// Attaches `note` to `rev` as a git note in a specific directory, replacing any
+/// existing note. The note is signed with the configured user name and email.
+pub fn add_note_in_path(rev: &str, note: &str, path: &Path) -> anyhow::Result<()> {
+    let repo = open_repo(path)?;
+    let commit = find_commit(&repo, rev)?;
+    let signature = repo
+        .signature()
+        .map_err(|e| anyhow!("Could not add a note: {}", e.message()))?;
+    repo.note(&signature, &signature, None, commit.id(), note, true)
+        .map_err(|e| anyhow!("Could not add a note to '{}': {}", rev, e.message()))?;
+    Ok(())
+}
+
+/// Retrieves the staged diff without context lines in a specific directory, so
+/// every hunk header covers exactly the changed lines.
+pub fn get_staged_hunks_in_path(path: &Path) -> anyhow::Result<String> {
+    let repo = open_repo(path)?;
+    let diff = staged_diff(&repo, &[], 0)
+        .map_err(|e| anyhow!("Could not read the staged changes: {}", e))?;
+    patch_text(&diff)
+}
+
+/// Retrieves the diff of commit `rev` without context lines in a specific directory.
+pub fn get_commit_hunks_in_path(rev: &str, path: &Path) -> anyhow::Result<String> {
+    let repo = open_repo(path)?;
+    let commit = find_commit(&repo, rev)?;
+    let mut options = DiffOptions::new();
+    options.context_lines(0);
+    patch_text(&commit_diff(&repo, &commit, Some(&mut options))?)
+}
+
+/// Commits the staged changes as `fixup! <subject of target>` in a specific
+/// directory, for a later `git rebase --autosquash`.
+pub fn commit_fixup_in_path(target: &str, path: &Path) -> anyhow::Result<()> {
+    let fixup = format!("--fixup={}", target);
+    let status = git_command(path)
+        .args(["commit", &fixup])
+        .status()
+        .map_err(git_spawn_error)?;
+    if !status.success() {
+        anyhow::bail!("git commit failed ({})", status);
+    }
+    Ok(())
+}
+
+/// Creates a new branch at `HEAD` in a specific directory and switches to it.
+/// Uncommitted changes are carried over to the new branch.
+pub fn create_branch_in_path(name: &str, path: &Path) -> anyhow::Result<()> {
+    let repo = open_repo(path)?;
+    let reference = format!("refs/heads/{}", name);
+    let created = match repo.head() {
+        Ok(head) => head
+            .peel_to_commit()
+            .and_then(|commit| repo.branch(name, &commit, false))
+            .map(|_| ()),
+        // Before the first commit there is nothing to point at yet
+        Err(e) if e.code() == ErrorCode::UnbornBranch => git2::Reference::is_valid_name(&reference)
+            .then_some(())
+            .ok_or_else(|| git2::Error::from_str("not a valid branch name")),
+        Err(e) => Err(e),
+    };
+
+    // The new branch points at HEAD, so the index and working tree stay as they are
+    created
+        .and_then(|_| repo.set_head(&reference))
+        .map_err(|e| anyhow!("Could not create branch '{}': {}", name, e.message()))
+}
+
+/// Returns the most recent tag reachable from `HEAD` in a specific directory, or
+/// `None` when the history has no tags.
+pub fn get_latest_tag_in_path(path: &Path) -> anyhow::Result<Option<String>> {
+    let repo = open_repo(path)?;
+    let head = repo
+        .head()
+        .and_then(|head| head.peel(git2::ObjectType::Commit))
+        .map_err(|e| anyhow!("Could not find the previous tag: {}", e.message()))?;
+
+    let describe = match head.describe(DescribeOptions::new().describe_tags()) {
+        Ok(describe) => describe,
+        // Every describe failure means no tag can describe HEAD
+        Err(e) if e.class() == git2::ErrorClass::Describe => return Ok(None),
+        Err(e) => anyhow::bail!("Could not find the previous tag: {}", e.message()),
+    };
+    Ok(Some(describe.format(Some(
+        DescribeFormatOptions::new().abbreviated_size(0),
+    ))?))
+}
+
+/// Creates the annotated tag `name` at `HEAD` with `message` in a specific directory.
+/// The message is cleaned up like `git tag -a` does.
+pub fn create_tag_in_path(name: &str, message: &str, path: &Path) -> anyhow::Result<()> {
+    let repo = open_repo(path)?;
+    repo.head()
+        .and_then(|head| head.peel(git2::ObjectType::Commit))
+        .and_then(|target| {
+            let message = git2::message_prettify(message, None)?;
+            repo.tag(name, &target, &repo.signature()?, &message, false)
+        })
+        .map_err(|e| anyhow!("Could not create tag '{}': {}", name, e.message()))?;
+    Ok(())
+}
+
+/// Resolves the hooks directory of the repository at `path`, honoring
+/// `core.hooksPath` (relative to the top of the work tree, like git).
+pub fn get_hooks_dir_in_path(path: &Path) -> anyhow::Result<PathBuf> {
+    let repo = open_repo(path)?;
+    if let Some(hooks_path) = repo
+        .config()
+        .ok()
+        .and_then(|config| config.get_path("core.hooksPath").ok())
+    {
+        let base = repo.workdir().unwrap_or_else(|| repo.path());
+        return Ok(base.join(hooks_path));
+    }
+    Ok(repo.commondir().join("hooks"))
+}
+
+/// Resolves `name` inside the `.git` directory of the repository at `path`. In a
+/// linked worktree this is the worktree's own git directory, like
+/// `git rev-parse --git-path` for per-worktree files.
+pub fn get_git_path_in_path(name: &str, path: &Path) -> anyhow::Result<PathBuf> {
+    if name == "hooks" {
+        return get_hooks_dir_in_path(path);
+    }
+    let repo = open_repo(path)?;
+    Ok(repo.path().join(name))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -64,7 +1542,400 @@ mod tests {
     use tempfile::tempdir;
 
     #[test]
-    fn test_get_git_diff_no_staged() {
+    fn test_get_git_diff_no_staged() {
+        let dir = tempdir().unwrap();
+        let repo_path = dir.path();
+
+        Command::new("git")
+            .arg("init")
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+
+        let diff =
+            get_git_diff_in_path(&["*.rs".to_string()], repo_path.to_str().unwrap()).unwrap();
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_errors_outside_a_repository_and_without_git() {
+        let dir = tempdir().unwrap();
+        let error = get_git_diff_in_path(&[], dir.path().to_str().unwrap()).unwrap_err();
+        assert!(
+            error.to_string().starts_with(&format!(
+                "Not a git repository: neither {} nor any of its parents is one.",
+                dir.path().display()
+            )),
+            "{}",
+            error
+        );
+        assert!(get_staged_files_in_path(dir.path().to_str().unwrap()).is_err());
+
+        Command::new("git")
+            .args(["init", "--bare"])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        let error = get_git_diff_in_path(&[], dir.path().to_str().unwrap()).unwrap_err();
+        assert!(
+            error.to_string().contains("is a bare repository"),
+            "{}",
+            error
+        );
+
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert_eq!(
+            git_spawn_error(missing).to_string(),
+            "git is not installed or not on PATH; install it or add it to PATH."
+        );
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert!(
+            git_spawn_error(denied)
+                .to_string()
+                .starts_with("Failed to run git: ")
+        );
+    }
+
+    #[test]
+    fn test_open_repo_with_git_dir_and_work_tree() {
+        let dir = tempdir().unwrap();
+        let (work, store, elsewhere) = (
+            dir.path().join("work"),
+            dir.path().join("store.git"),
+            dir.path().join("elsewhere"),
+        );
+        std::fs::create_dir(&elsewhere).unwrap();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .unwrap();
+        };
+        git(&["init", "-q", "work"]);
+        std::fs::write(work.join("a.txt"), "a\n").unwrap();
+        git(&["-C", "work", "add", "a.txt"]);
+        std::fs::rename(work.join(".git"), &store).unwrap();
+
+        // The git directory lives outside the work tree, as with `GIT_DIR=... GIT_WORK_TREE=...`
+        let repo = open_repo_with(&elsewhere, Some(store.clone()), Some(work.clone())).unwrap();
+        assert_eq!(
+            repo.workdir().unwrap().canonicalize().unwrap(),
+            work.canonicalize().unwrap()
+        );
+        let diff = patch_text(&staged_diff(&repo, &[], 3).unwrap()).unwrap();
+        assert!(diff.contains("+++ b/a.txt"), "{}", diff);
+
+        // Without GIT_WORK_TREE, the directory asum runs in is the work tree
+        let repo = open_repo_with(&work, Some(store.clone()), None).unwrap();
+        assert_eq!(
+            repo.workdir().unwrap().canonicalize().unwrap(),
+            work.canonicalize().unwrap()
+        );
+
+        git(&["init", "-q", "--bare", "bare.git"]);
+        let repo = open_repo_with(&elsewhere, Some(dir.path().join("bare.git")), None).unwrap();
+        assert!(repo.is_bare());
+
+        let error = open_repo_with(&work, Some(elsewhere.clone()), None)
+            .err()
+            .unwrap();
+        assert!(
+            error.to_string().starts_with("GIT_DIR is set to "),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_linked_worktree() {
+        let dir = tempdir().unwrap();
+        let (main, linked) = (dir.path().join("main"), dir.path().join("linked"));
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "{:?}", output);
+        };
+        git(&["init", "-q", "main"]);
+        git(&["-C", "main", "config", "user.email", "asum@example.com"]);
+        git(&["-C", "main", "config", "user.name", "asum"]);
+        std::fs::write(main.join("a.txt"), "a\n").unwrap();
+        git(&["-C", "main", "add", "a.txt"]);
+        git(&["-C", "main", "commit", "-q", "-m", "init"]);
+        git(&[
+            "-C",
+            "main",
+            "worktree",
+            "add",
+            "-q",
+            "-b",
+            "side",
+            "../linked",
+        ]);
+        assert!(linked.join(".git").is_file());
+
+        std::fs::write(linked.join("b.txt"), "b\n").unwrap();
+        git(&["-C", "linked", "add", "b.txt"]);
+        let diff = get_git_diff_in_path(&[], linked.to_str().unwrap()).unwrap();
+        assert!(diff.contains("+++ b/b.txt"), "{}", diff);
+        assert!(
+            get_git_diff_in_path(&[], main.to_str().unwrap())
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            get_repo_root_in_path(&linked)
+                .unwrap()
+                .canonicalize()
+                .unwrap(),
+            linked.canonicalize().unwrap()
+        );
+        assert_eq!(
+            get_git_path_in_path("ASUM_DRAFT_MSG", &linked)
+                .unwrap()
+                .parent()
+                .unwrap()
+                .canonicalize()
+                .unwrap(),
+            main.join(".git/worktrees/linked").canonicalize().unwrap()
+        );
+        assert_eq!(
+            get_hooks_dir_in_path(&linked).unwrap().canonicalize().ok(),
+            main.join(".git/hooks").canonicalize().ok()
+        );
+    }
+
+    #[test]
+    fn test_get_git_diff_with_staged_table_driven() {
+        struct TestCase {
+            name: &'static str,
+            filename: &'static str,
+            content: &'static str,
+            extension: &'static str,
+            should_find: bool,
+        }
+
+        let cases = vec![
+            TestCase {
+                name: "find staged rust file",
+                filename: "test.rs",
+                content: "fn main() {}",
+                extension: "*.rs",
+                should_find: true,
+            },
+            TestCase {
+                name: "exclude non-matching extension",
+                filename: "test.txt",
+                content: "hello",
+                extension: "*.rs",
+                should_find: false,
+            },
+        ];
+
+        for case in cases {
+            let dir = tempdir().unwrap();
+            let repo_path = dir.path();
+
+            Command::new("git")
+                .arg("init")
+                .current_dir(repo_path)
+                .output()
+                .unwrap();
+
+            let file_path = repo_path.join(case.filename);
+            let mut file = File::create(&file_path).unwrap();
+            writeln!(file, "{}", case.content).unwrap();
+
+            Command::new("git")
+                .args(["add", case.filename])
+                .current_dir(repo_path)
+                .output()
+                .unwrap();
+
+            let diff =
+                get_git_diff_in_path(&[case.extension.to_string()], repo_path.to_str().unwrap())
+                    .unwrap();
+            if case.should_find {
+                assert!(!diff.is_empty(), "Failed case: {}", case.name);
+                assert!(diff.contains(case.content), "Failed case: {}", case.name);
+            } else {
+                assert!(diff.is_empty(), "Failed case: {}", case.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_git_diff_exclude_patterns() {
+        let dir = tempdir().unwrap();
+        let repo_path = dir.path();
+
+        Command::new("git")
+            .arg("init")
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+
+        // Create a lock file that should be excluded
+        let lock_file_path = repo_path.join("package-lock.json");
+        let mut lock_file = File::create(&lock_file_path).unwrap();
+        writeln!(lock_file, "{{\"name\": \"test\"}}").unwrap();
+
+        Command::new("git")
+            .args(["add", "package-lock.json"])
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+
+        let specs = ["*.json".to_string(), ":(exclude)*-lock.json".to_string()];
+        let diff = get_git_diff_in_path(&specs, repo_path.to_str().unwrap()).unwrap();
+        assert!(diff.is_empty(), "package-lock.json should be excluded");
+
+        // Create a normal json file that should NOT be excluded
+        let normal_file_path = repo_path.join("test.json");
+        let mut normal_file = File::create(&normal_file_path).unwrap();
+        writeln!(normal_file, "{{\"test\": true}}").unwrap();
+
+        Command::new("git")
+            .args(["add", "test.json"])
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+
+        let diff = get_git_diff_in_path(&specs, repo_path.to_str().unwrap()).unwrap();
+        assert!(!diff.is_empty(), "test.json should be included");
+        assert!(diff.contains("test.json"));
+    }
+
+    #[test]
+    fn test_lfs_pointer_size_table_driven() {
+        let cases: [(&[u8], Option<u64>); 4] = [
+            (
+                b"version https://git-lfs.github.com/spec/v1\noid sha256:4d7a\nsize 12345\n",
+                Some(12345),
+            ),
+            (
+                b"version https://git-lfs.github.com/spec/v1\noid sha256:4d7a\n",
+                None,
+            ),
+            (b"size 12345\n", None),
+            (&[0, 159, 146, 150], None),
+        ];
+        for (content, expected) in cases {
+            assert_eq!(lfs_pointer_size(content), expected, "{:?}", content);
+        }
+    }
+
+    #[test]
+    fn test_get_git_diff_detects_renames_and_copies() {
+        let dir = tempdir().unwrap();
+        let repo_path = dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(repo_path)
+                .output()
+                .unwrap();
+        };
+        git(&["init"]);
+        git(&["config", "user.email", "asum@example.com"]);
+        git(&["config", "user.name", "asum"]);
+        let lines: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(repo_path.join("a.txt"), &lines).unwrap();
+        std::fs::write(repo_path.join("old.txt"), lines.replace("line", "entry")).unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "init"]);
+
+        // a.txt is modified, so its copy is found like `git diff -C` finds it
+        std::fs::write(repo_path.join("a.txt"), format!("{}line 21\n", lines)).unwrap();
+        std::fs::write(repo_path.join("copy.txt"), &lines).unwrap();
+        git(&["mv", "old.txt", "new.txt"]);
+        git(&["add", "."]);
+
+        let diff =
+            get_git_diff_in_path(&["*.txt".to_string()], repo_path.to_str().unwrap()).unwrap();
+        assert!(
+            diff.contains("rename from old.txt\nrename to new.txt\n"),
+            "{}",
+            diff
+        );
+        assert!(
+            diff.contains("copy from a.txt\ncopy to copy.txt\n"),
+            "{}",
+            diff
+        );
+        assert!(!diff.contains("deleted file mode"), "{}", diff);
+    }
+
+    #[test]
+    fn test_get_git_diff_smoke() {
+        // Just a smoke test to ensure it doesn't crash in the current repo
+        let result = get_git_diff(&["*.rs".to_string()]);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_get_staged_files() {
+        let dir = tempdir().unwrap();
+        let repo_path = dir.path();
+
+        Command::new("git")
+            .arg("init")
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+
+        let file_path = repo_path.join("test.txt");
+        File::create(&file_path).unwrap();
+
+        Command::new("git")
+            .args(["add", "test.txt"])
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+
+        let files = get_staged_files_in_path(repo_path.to_str().unwrap()).unwrap();
+        assert!(files.contains("A\ttest.txt"));
+    }
+
+    #[test]
+    fn test_get_hooks_dir_in_path() {
+        let dir = tempdir().unwrap();
+        let repo_path = dir.path();
+
+        Command::new("git")
+            .arg("init")
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+
+        let hooks_dir = get_hooks_dir_in_path(repo_path).unwrap();
+        assert_eq!(hooks_dir, repo_path.join(".git").join("hooks"));
+        assert_eq!(
+            get_git_path_in_path("ASUM_DRAFT_MSG", repo_path).unwrap(),
+            repo_path.join(".git").join("ASUM_DRAFT_MSG")
+        );
+
+        // core.hooksPath takes precedence over the default location
+        Command::new("git")
+            .args(["config", "core.hooksPath", "custom-hooks"])
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+        let hooks_dir = get_hooks_dir_in_path(repo_path).unwrap();
+        assert_eq!(hooks_dir, repo_path.join("custom-hooks"));
+    }
+
+    #[test]
+    fn test_get_hooks_dir_outside_repo() {
+        let dir = tempdir().unwrap();
+        assert!(get_hooks_dir_in_path(dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_intent_to_add_files() {
         let dir = tempdir().unwrap();
         let repo_path = dir.path();
 
@@ -74,141 +1945,616 @@ mod tests {
             .output()
             .unwrap();
 
-        let diff =
-            get_git_diff_in_path(&["*.rs".to_string()], repo_path.to_str().unwrap()).unwrap();
+        std::fs::write(repo_path.join("staged.rs"), "fn staged() {}\n").unwrap();
+        std::fs::write(repo_path.join("planned.rs"), "fn planned() {}\n").unwrap();
+        Command::new("git")
+            .args(["add", "staged.rs"])
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["add", "-N", "planned.rs"])
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+
+        let path = repo_path.to_str().unwrap();
+        let diff = get_git_diff_in_path(&["*.rs".to_string()], path).unwrap();
+        assert!(diff.contains("+fn staged() {}"));
+        assert!(diff.contains("+fn planned() {}"));
+        assert!(diff.contains(&format!(
+            "{}\ndiff --git a/planned.rs b/planned.rs",
+            INTENT_TO_ADD_MARKER
+        )));
+        assert_eq!(diff.matches(INTENT_TO_ADD_MARKER).count(), 1);
+
+        // Extension filters apply to intent-to-add files as well
+        let diff = get_git_diff_in_path(&["*.py".to_string()], path).unwrap();
         assert!(diff.is_empty());
+
+        let files = get_staged_files_in_path(path).unwrap();
+        assert!(files.contains("A\tstaged.rs\n"));
+        assert!(files.contains("A\tplanned.rs (new file, not yet staged content)"));
     }
 
     #[test]
-    fn test_get_git_diff_with_staged_table_driven() {
-        struct TestCase {
-            name: &'static str,
-            filename: &'static str,
-            content: &'static str,
-            extension: &'static str,
-            should_find: bool,
-        }
-
-        let cases = vec![
-            TestCase {
-                name: "find staged rust file",
-                filename: "test.rs",
-                content: "fn main() {}",
-                extension: "*.rs",
-                should_find: true,
-            },
-            TestCase {
-                name: "exclude non-matching extension",
-                filename: "test.txt",
-                content: "hello",
-                extension: "*.rs",
-                should_find: false,
-            },
-        ];
-
-        for case in cases {
-            let dir = tempdir().unwrap();
-            let repo_path = dir.path();
+    fn test_commit_in_path() {
+        let dir = tempdir().unwrap();
+        let repo_path = dir.path();
 
+        for args in [
+            vec!["init"],
+            vec!["config", "user.email", "asum@example.com"],
+            vec!["config", "user.name", "asum"],
+        ] {
             Command::new("git")
-                .arg("init")
+                .args(args)
                 .current_dir(repo_path)
                 .output()
                 .unwrap();
+        }
 
-            let file_path = repo_path.join(case.filename);
-            let mut file = File::create(&file_path).unwrap();
-            writeln!(file, "{}", case.content).unwrap();
+        // Nothing staged yet, so git refuses to commit
+        assert!(commit_in_path("feat: empty", false, repo_path).is_err());
 
-            Command::new("git")
-                .args(["add", case.filename])
-                .current_dir(repo_path)
-                .output()
-                .unwrap();
+        std::fs::write(repo_path.join("main.rs"), "fn main() {}").unwrap();
+        Command::new("git")
+            .args(["add", "main.rs"])
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
 
-            let diff =
-                get_git_diff_in_path(&[case.extension.to_string()], repo_path.to_str().unwrap())
-                    .unwrap();
-            if case.should_find {
-                assert!(!diff.is_empty(), "Failed case: {}", case.name);
-                assert!(diff.contains(case.content), "Failed case: {}", case.name);
-            } else {
-                assert!(diff.is_empty(), "Failed case: {}", case.name);
-            }
-        }
+        commit_in_path("feat: add main", false, repo_path).unwrap();
+        let log = Command::new("git")
+            .args(["log", "-1", "--format=%s"])
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&log.stdout).trim(),
+            "feat: add main"
+        );
     }
 
     #[test]
-    fn test_get_git_diff_exclude_patterns() {
+    fn test_amend_last_commit() {
         let dir = tempdir().unwrap();
         let repo_path = dir.path();
 
+        for args in [
+            vec!["init"],
+            vec!["config", "user.email", "asum@example.com"],
+            vec!["config", "user.name", "asum"],
+        ] {
+            Command::new("git")
+                .args(args)
+                .current_dir(repo_path)
+                .output()
+                .unwrap();
+        }
+
+        // No commits yet
+        assert!(get_commit_diff_in_path("HEAD", &[], repo_path).is_err());
+
+        std::fs::write(repo_path.join("main.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(repo_path.join("notes.txt"), "notes\n").unwrap();
         Command::new("git")
-            .arg("init")
+            .args(["add", "."])
             .current_dir(repo_path)
             .output()
             .unwrap();
+        commit_in_path("wip", false, repo_path).unwrap();
 
-        // Create a lock file that should be excluded
-        let lock_file_path = repo_path.join("package-lock.json");
-        let mut lock_file = File::create(&lock_file_path).unwrap();
-        writeln!(lock_file, "{{\"name\": \"test\"}}").unwrap();
+        let diff = get_commit_diff_in_path("HEAD", &["*.rs".to_string()], repo_path).unwrap();
+        assert!(diff.contains("+fn main() {}"));
+        assert!(!diff.contains("notes.txt"));
+
+        // Falls back to the file list when no code file matches
+        let files = get_commit_diff_in_path("HEAD", &["*.py".to_string()], repo_path).unwrap();
+        assert!(files.contains("A\tmain.rs"));
 
+        // Staged changes are not folded into the amended commit
+        std::fs::write(repo_path.join("lib.rs"), "pub fn lib() {}\n").unwrap();
         Command::new("git")
-            .args(["add", "package-lock.json"])
+            .args(["add", "lib.rs"])
             .current_dir(repo_path)
             .output()
             .unwrap();
 
-        let diff =
-            get_git_diff_in_path(&["*.json".to_string()], repo_path.to_str().unwrap()).unwrap();
-        assert!(diff.is_empty(), "package-lock.json should be excluded");
+        let committed_at = get_commit_time_in_path("HEAD", repo_path).unwrap();
+        assert!(committed_at > 1_600_000_000);
+        assert_eq!(
+            get_repo_root_in_path(&repo_path.join(".git")).ok(),
+            None,
+            "inside .git there is no work tree"
+        );
+        assert_eq!(
+            get_repo_root_in_path(repo_path)
+                .unwrap()
+                .canonicalize()
+                .unwrap(),
+            repo_path.canonicalize().unwrap()
+        );
 
-        // Create a normal json file that should NOT be excluded
-        let normal_file_path = repo_path.join("test.json");
-        let mut normal_file = File::create(&normal_file_path).unwrap();
-        writeln!(normal_file, "{{\"test\": true}}").unwrap();
+        amend_message_in_path("feat: add main entry point", false, repo_path).unwrap();
+        assert_eq!(
+            get_commit_message_in_path("HEAD", repo_path).unwrap(),
+            "feat: add main entry point"
+        );
+        let files = get_commit_diff_in_path("HEAD", &["*.py".to_string()], repo_path).unwrap();
+        assert!(!files.contains("lib.rs"));
+    }
+
+    #[test]
+    fn test_non_ascii_file_names_are_not_quoted() {
+        let dir = tempdir().unwrap();
+        let repo_path = dir.path();
+        let path = repo_path.to_str().unwrap();
 
         Command::new("git")
-            .args(["add", "test.json"])
+            .arg("init")
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+        // Enforce git's default even if the user's global config disables it
+        Command::new("git")
+            .args(["config", "core.quotepath", "true"])
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+        std::fs::write(repo_path.join("文件.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(repo_path.join("we\"ird.rs"), "fn weird() {}\n").unwrap();
+        Command::new("git")
+            .args(["add", "文件.rs", "we\"ird.rs"])
             .current_dir(repo_path)
             .output()
             .unwrap();
 
-        let diff =
-            get_git_diff_in_path(&["*.json".to_string()], repo_path.to_str().unwrap()).unwrap();
-        assert!(!diff.is_empty(), "test.json should be included");
-        assert!(diff.contains("test.json"));
-    }
+        let diff = get_git_diff_in_path(&["*.rs".to_string()], path).unwrap();
+        assert!(diff.contains("diff --git a/文件.rs b/文件.rs"), "{}", diff);
+        assert!(!diff.contains("\\346"));
+        assert!(
+            diff.contains("diff --git a/we\"ird.rs b/we\"ird.rs\n"),
+            "{}",
+            diff
+        );
+        assert!(diff.contains("+++ b/we\"ird.rs\n"), "{}", diff);
 
-    #[test]
-    fn test_get_git_diff_smoke() {
-        // Just a smoke test to ensure it doesn't crash in the current repo
-        let result = get_git_diff(&["*.rs".to_string()]);
-        assert!(result.is_ok());
+        let files = get_staged_files_in_path(path).unwrap();
+        assert_eq!(files.trim(), "A\twe\"ird.rs\nA\t文件.rs");
     }
 
     #[test]
-    fn test_get_staged_files() {
+    fn test_worktree_diff_and_create_branch() {
         let dir = tempdir().unwrap();
         let repo_path = dir.path();
+        let path = repo_path.to_str().unwrap();
 
+        for args in [
+            vec!["init"],
+            vec!["config", "user.email", "asum@example.com"],
+            vec!["config", "user.name", "asum"],
+        ] {
+            Command::new("git")
+                .args(args)
+                .current_dir(repo_path)
+                .output()
+                .unwrap();
+        }
+        std::fs::write(repo_path.join("main.rs"), "fn main() {}\n").unwrap();
         Command::new("git")
-            .arg("init")
+            .args(["add", "main.rs"])
             .current_dir(repo_path)
             .output()
             .unwrap();
+        commit_in_path("init", false, repo_path).unwrap();
 
-        let file_path = repo_path.join("test.txt");
-        File::create(&file_path).unwrap();
+        std::fs::write(repo_path.join("main.rs"), "fn main() { run() }\n").unwrap();
+        let diff = get_worktree_diff_in_path(&["*.rs".to_string()], path).unwrap();
+        assert!(diff.contains("+fn main() { run() }"));
+        assert!(
+            get_git_diff_in_path(&["*.rs".to_string()], path)
+                .unwrap()
+                .is_empty()
+        );
+
+        create_branch_in_path("feat/run-main", repo_path).unwrap();
+        assert_eq!(
+            get_current_branch_in_path(repo_path).unwrap(),
+            "feat/run-main"
+        );
+
+        // Creating the same branch twice fails with git's reason
+        let err = create_branch_in_path("feat/run-main", repo_path).unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+    }
+
+    #[test]
+    fn test_branch_commits_and_diff() {
+        let dir = tempdir().unwrap();
+        let repo_path = dir.path();
+
+        for args in [
+            vec!["init", "-b", "main"],
+            vec!["config", "user.email", "asum@example.com"],
+            vec!["config", "user.name", "asum"],
+        ] {
+            Command::new("git")
+                .args(args)
+                .current_dir(repo_path)
+                .output()
+                .unwrap();
+        }
+        let add_and_commit = |file: &str, content: &str, message: &str| {
+            std::fs::write(repo_path.join(file), content).unwrap();
+            Command::new("git")
+                .args(["add", file])
+                .current_dir(repo_path)
+                .output()
+                .unwrap();
+            commit_in_path(message, false, repo_path).unwrap();
+        };
+
+        add_and_commit("main.rs", "fn main() {}\n", "init");
+        create_branch_in_path("feat/login", repo_path).unwrap();
+        add_and_commit("login.rs", "pub fn login() {}\n", "feat: add login");
+        add_and_commit("notes.txt", "todo\n", "docs: add notes");
 
+        let messages = get_range_messages_in_path("main..HEAD", repo_path).unwrap();
+        assert_eq!(messages, vec!["feat: add login", "docs: add notes"]);
+        let diff = get_range_diff_in_path("main..HEAD", &["*.rs".to_string()], repo_path).unwrap();
+        assert!(diff.contains("+pub fn login() {}"));
+        assert!(get_range_messages_in_path("nope..HEAD", repo_path).is_err());
+        let commits = get_range_commits_in_path("main..HEAD", repo_path).unwrap();
+        let subjects: Vec<_> = commits.iter().map(|(_, s)| s.as_str()).collect();
+        assert_eq!(subjects, vec!["feat: add login", "docs: add notes"]);
+        assert!(commits.iter().all(|(hash, _)| hash.len() == 40));
+        assert_eq!(
+            resolve_commit_in_path("HEAD~1", repo_path).unwrap(),
+            commits[0].0
+        );
+        assert!(resolve_commit_in_path("missing", repo_path).is_err());
+
+        assert_eq!(get_default_branch_in_path(repo_path).unwrap(), "main");
         Command::new("git")
-            .args(["add", "test.txt"])
+            .args([
+                "symbolic-ref",
+                "refs/remotes/origin/HEAD",
+                "refs/remotes/origin/trunk",
+            ])
             .current_dir(repo_path)
             .output()
             .unwrap();
+        assert_eq!(
+            get_default_branch_in_path(repo_path).unwrap(),
+            "origin/trunk"
+        );
 
-        let files = get_staged_files_in_path(repo_path.to_str().unwrap()).unwrap();
-        assert!(files.contains("A\ttest.txt"));
+        let commits = get_branch_commits_in_path("main", repo_path).unwrap();
+        assert_eq!(commits, "- feat: add login\n- docs: add notes\n");
+
+        let diff = get_branch_diff_in_path("main", &["*.rs".to_string()], repo_path).unwrap();
+        assert!(diff.contains("+pub fn login() {}"));
+        assert!(!diff.contains("main.rs"));
+        assert!(!diff.contains("notes.txt"));
+
+        assert!(get_branch_commits_in_path("missing", repo_path).is_err());
+        assert!(get_branch_diff_in_path("missing", &[], repo_path).is_err());
+    }
+
+    #[test]
+    fn test_latest_tag_and_create_tag() {
+        let dir = tempdir().unwrap();
+        let repo_path = dir.path();
+        for args in [
+            vec!["init", "-b", "main"],
+            vec!["config", "user.email", "asum@example.com"],
+            vec!["config", "user.name", "asum"],
+            vec!["commit", "--allow-empty", "-m", "init"],
+        ] {
+            Command::new("git")
+                .args(args)
+                .current_dir(repo_path)
+                .output()
+                .unwrap();
+        }
+
+        assert_eq!(get_latest_tag_in_path(repo_path).unwrap(), None);
+        create_tag_in_path("v1.0.0", "Release 1.0.0\n\n- first", repo_path).unwrap();
+        assert_eq!(
+            get_latest_tag_in_path(repo_path).unwrap().as_deref(),
+            Some("v1.0.0")
+        );
+
+        let err = create_tag_in_path("v1.0.0", "again", repo_path).unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+        assert!(get_latest_tag_in_path(&repo_path.join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_uncommitted_changes_and_stash_push() {
+        let dir = tempdir().unwrap();
+        let repo_path = dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(repo_path)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-b", "main"]);
+        git(&["config", "user.email", "asum@example.com"]);
+        git(&["config", "user.name", "asum"]);
+        std::fs::write(repo_path.join("a.rs"), "fn a() {}\n").unwrap();
+        std::fs::write(repo_path.join("b.txt"), "b\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "init"]);
+
+        // One staged and one unstaged change, plus an untracked file
+        std::fs::write(repo_path.join("a.rs"), "fn a() { todo!() }\n").unwrap();
+        git(&["add", "a.rs"]);
+        std::fs::write(repo_path.join("b.txt"), "changed\n").unwrap();
+        std::fs::write(repo_path.join("new.rs"), "fn new() {}\n").unwrap();
+        let path = repo_path.to_str().unwrap();
+
+        let all = get_uncommitted_changes_in_path(&[], false, path).unwrap();
+        assert!(all.contains("+fn a() { todo!() }"));
+        assert!(all.contains("+changed"));
+        assert!(!all.contains("new.rs"));
+
+        let names = get_uncommitted_changes_in_path(&["*.md".to_string()], true, path).unwrap();
+        assert_eq!(names, "M\ta.rs\nM\tb.txt\nnew.rs (new file, untracked)\n");
+
+        stash_push_in_path("wip: half-done todo", true, repo_path).unwrap();
+        let list = git(&["stash", "list"]);
+        assert!(String::from_utf8_lossy(&list.stdout).contains("On main: wip: half-done todo"));
+        assert!(git(&["status", "--porcelain"]).stdout.is_empty());
+        assert_eq!(
+            get_uncommitted_changes_in_path(&[], true, path).unwrap(),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_recent_commits_hunks_and_fixup() {
+        let dir = tempdir().unwrap();
+        let repo_path = dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(repo_path)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-b", "main"]);
+        git(&["config", "user.email", "asum@example.com"]);
+        git(&["config", "user.name", "asum"]);
+        for (file, content, subject) in [
+            ("a.rs", "one\ntwo\n", "feat: add a"),
+            ("b.rs", "three\n", "feat: add b"),
+        ] {
+            std::fs::write(repo_path.join(file), content).unwrap();
+            git(&["add", file]);
+            git(&["commit", "-m", subject]);
+        }
+
+        let commits = get_recent_commits_in_path(5, repo_path).unwrap();
+        let subjects: Vec<&str> = commits.iter().map(|(_, s)| s.as_str()).collect();
+        assert_eq!(subjects, vec!["feat: add b", "feat: add a"]);
+        assert_eq!(get_recent_commits_in_path(1, repo_path).unwrap().len(), 1);
+        assert!(
+            get_commit_hunks_in_path(&commits[1].0, repo_path)
+                .unwrap()
+                .contains("@@ -0,0 +1,2 @@\n+one\n+two\n")
+        );
+
+        std::fs::write(repo_path.join("a.rs"), "one\n2\n").unwrap();
+        git(&["add", "a.rs"]);
+        let staged = get_staged_hunks_in_path(repo_path).unwrap();
+        assert!(staged.contains("@@ -2 +2 @@"));
+        assert!(staged.contains("\n-two\n+2\n"));
+
+        commit_fixup_in_path(&commits[1].0, repo_path).unwrap();
+        assert_eq!(
+            get_commit_message_in_path("HEAD", repo_path).unwrap(),
+            "fixup! feat: add a"
+        );
+    }
+
+    #[test]
+    fn test_parse_date_table_driven() {
+        // 2024-06-15 12:30 UTC
+        let now = 1_718_454_600;
+        let cases = vec![
+            ("now", Some(now)),
+            ("24 hours ago", Some(now - 86_400)),
+            ("1 week ago", Some(now - 7 * 86_400)),
+            ("2.weeks.ago", Some(now - 14 * 86_400)),
+            ("30 minutes", Some(now - 1_800)),
+            ("yesterday", Some(now - 86_400)),
+            ("Today", Some(1_718_409_600)),
+            ("2024-06-15", Some(1_718_409_600)),
+            ("2024-06-15 12:30", Some(now)),
+            ("2024-06-15T12:30:10", Some(now + 10)),
+            ("1970-01-01", Some(0)),
+            ("@1700000000", Some(1_700_000_000)),
+            ("2024-13-01", None),
+            ("3 fortnights ago", None),
+            ("soon", None),
+            ("", None),
+        ];
+        for (spec, expected) in cases {
+            assert_eq!(parse_date(spec, now).ok(), expected, "spec: {:?}", spec);
+        }
+    }
+
+    #[test]
+    fn test_unquote_paths_table_driven() {
+        let cases = vec![
+            ("diff --git a/x.rs b/x.rs\n", "diff --git a/x.rs b/x.rs\n"),
+            ("+++ \"b/\\346\\226\\207.rs\"\n", "+++ b/文.rs\n"),
+            (
+                "diff --git \"a/we\\\"ird.rs\" \"b/we\\\"ird.rs\"\n",
+                "diff --git a/we\"ird.rs b/we\"ird.rs\n",
+            ),
+            ("+++ \"b/back\\\\slash.rs\"\n", "+++ b/back\\slash.rs\n"),
+            // Names with control characters stay quoted
+            ("+++ \"b/tab\\there.rs\"\n", "+++ \"b/tab\\there.rs\"\n"),
+            ("+++ \"b/new\\nline.rs\"\n", "+++ \"b/new\\nline.rs\"\n"),
+            ("+++ \"b/open\n", "+++ \"b/open\n"),
+            ("+++ \"b/bad\\qescape\"\n", "+++ \"b/bad\\qescape\"\n"),
+        ];
+        for (header, expected) in cases {
+            assert_eq!(
+                String::from_utf8(unquote_paths(header.as_bytes())).unwrap(),
+                expected,
+                "header: {:?}",
+                header
+            );
+        }
+    }
+
+    #[test]
+    fn test_staged_and_commit_stats() {
+        let dir = tempdir().unwrap();
+        let repo_path = dir.path();
+
+        for args in [
+            vec!["init"],
+            vec!["config", "user.email", "asum@example.com"],
+            vec!["config", "user.name", "asum"],
+        ] {
+            Command::new("git")
+                .args(args)
+                .current_dir(repo_path)
+                .output()
+                .unwrap();
+        }
+        assert!(get_commit_stats_in_path(10, repo_path).unwrap().is_empty());
+
+        for (file, content) in [("a.rs", "a\nb\n"), ("b.rs", "c\n")] {
+            std::fs::write(repo_path.join(file), content).unwrap();
+            Command::new("git")
+                .args(["add", file])
+                .current_dir(repo_path)
+                .output()
+                .unwrap();
+            assert_eq!(
+                get_staged_stat_in_path(repo_path).unwrap().files,
+                1,
+                "{}",
+                file
+            );
+            commit_in_path(file, false, repo_path).unwrap();
+        }
+
+        let stats = get_commit_stats_in_path(10, repo_path).unwrap();
+        assert_eq!(
+            stats,
+            vec![
+                DiffStat { files: 1, lines: 1 },
+                DiffStat { files: 1, lines: 2 }
+            ]
+        );
+        assert_eq!(get_commit_stats_in_path(1, repo_path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_commits_since_by_author() {
+        let dir = tempdir().unwrap();
+        let repo_path = dir.path();
+
+        for args in [
+            vec!["init"],
+            vec!["config", "user.email", "me@example.com"],
+            vec!["config", "user.name", "me"],
+        ] {
+            Command::new("git")
+                .args(args)
+                .current_dir(repo_path)
+                .output()
+                .unwrap();
+        }
+        assert_eq!(
+            get_config_value_in_path("user.email", repo_path).as_deref(),
+            Some("me@example.com")
+        );
+        assert!(get_config_value_in_path("asum.missing", repo_path).is_none());
+
+        for (author, message) in [
+            ("me <me@example.com>", "feat: mine"),
+            ("other <other@example.com>", "fix: theirs"),
+        ] {
+            Command::new("git")
+                .args(["commit", "--allow-empty", "-m", message, "--author", author])
+                .current_dir(repo_path)
+                .output()
+                .unwrap();
+        }
+
+        let all = get_commits_since_in_path("1 hour ago", None, repo_path).unwrap();
+        assert_eq!(all, "- feat: mine\n- fix: theirs\n");
+
+        let mine =
+            get_commits_since_in_path("1 hour ago", Some("me@example.com"), repo_path).unwrap();
+        assert_eq!(mine, "- feat: mine\n");
+
+        let future = get_commits_since_in_path("2090-01-01", None, repo_path).unwrap();
+        assert!(future.is_empty());
+
+        assert!(get_tracked_files_in_path(repo_path).unwrap().is_empty());
+        assert!(get_tracked_files_in_path(&repo_path.join("missing")).is_err());
+
+        let authored = get_authored_commits_since_in_path("1 hour ago", repo_path).unwrap();
+        assert_eq!(
+            authored,
+            vec![
+                ("me".to_string(), "feat: mine".to_string()),
+                ("other".to_string(), "fix: theirs".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_commit_staged_paths_keeps_the_rest_staged() {
+        let dir = tempdir().unwrap();
+        let repo_path = dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(repo_path)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-b", "main"]);
+        git(&["config", "user.email", "asum@example.com"]);
+        git(&["config", "user.name", "asum"]);
+        std::fs::write(repo_path.join("a.rs"), "fn a() {}\n").unwrap();
+        std::fs::write(repo_path.join("b.rs"), "fn b() {}\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "init"]);
+
+        // a.rs is partly staged: its unstaged edit must stay in the working tree
+        std::fs::write(repo_path.join("a.rs"), "fn a() { 1 }\n").unwrap();
+        git(&["add", "a.rs"]);
+        std::fs::write(repo_path.join("a.rs"), "fn a() { 2 }\n").unwrap();
+        std::fs::write(repo_path.join("b.rs"), "fn b() { 1 }\n").unwrap();
+        std::fs::write(repo_path.join("c.rs"), "fn c() {}\n").unwrap();
+        git(&["add", "b.rs", "c.rs"]);
+
+        let paths = vec!["a.rs".to_string(), "c.rs".to_string()];
+        commit_staged_paths_in_path(&paths, "feat: a and c", repo_path).unwrap();
+        let show = git(&["show", "--name-only", "--format=%s", "HEAD"]);
+        assert_eq!(
+            String::from_utf8_lossy(&show.stdout),
+            "feat: a and c\n\na.rs\nc.rs\n"
+        );
+        let committed = git(&["show", "HEAD:a.rs"]);
+        assert_eq!(String::from_utf8_lossy(&committed.stdout), "fn a() { 1 }\n");
+        let status = git(&["status", "--porcelain"]);
+        assert_eq!(
+            String::from_utf8_lossy(&status.stdout),
+            " M a.rs\nM  b.rs\n"
+        );
     }
 }
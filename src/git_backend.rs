@@ -0,0 +1,317 @@
+//! Repository access behind a trait for programs embedding ASUM.
+//!
+//! The functions in `git` work on a repository path through libgit2. Servers,
+//! editor plugins and tests that keep repositories elsewhere, or want a fake, can
+//! implement `GitBackend` instead and hand it to `Runner`. `LibGit2` wraps the
+//! functions in `git`; `GitCli` runs the `git` binary for setups where its
+//! configuration (e.g. `includeIf`, credential helpers or custom diff drivers)
+//! must apply.
+
+use crate::diff_source::DiffSource;
+use crate::encoding;
+use crate::git::{
+    self, EXCLUDE_MAGIC, add_note_in_path, get_commit_subjects_since_in_path,
+    get_current_branch_in_path, get_git_diff_in_path, get_note_in_path, get_recent_commits_in_path,
+    get_staged_files_in_path,
+};
+use anyhow::{Context, Result, anyhow};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::warn;
+
+/// The repository operations ASUM needs.
+pub trait GitBackend: Send + Sync {
+    /// Returns the staged diff, limited to files matching `patterns`.
+    fn staged_diff(&self, patterns: &[String]) -> Result<String>;
+
+    /// Lists the staged files as `--name-status` lines, e.g. `M\tsrc/main.rs`.
+    fn staged_files(&self) -> Result<String>;
+
+    /// Returns the checked-out branch, or an empty string when HEAD is detached.
+    fn current_branch(&self) -> Result<String>;
+
+    /// Lists the subjects of non-merge commits on any branch committed at or after
+    /// `since` (seconds since the Unix epoch), oldest first. With `author`, only
+    /// commits whose author name or email contains it are listed.
+    fn commits_since(&self, since: i64, author: Option<&str>) -> Result<Vec<String>>;
+
+    /// Lists the last `limit` non-merge commits on HEAD as `(hash, subject)` pairs,
+    /// newest first.
+    fn recent_commits(&self, limit: usize) -> Result<Vec<(String, String)>>;
+
+    /// Creates a commit from the staged changes.
+    fn commit(&self, message: &str) -> Result<()>;
+
+    /// Returns the git note attached to `rev`, if any.
+    fn note(&self, rev: &str) -> Result<Option<String>>;
+
+    /// Attaches `note` to `rev`, replacing any existing note.
+    fn add_note(&self, rev: &str, note: &str) -> Result<()>;
+}
+
+/// The repository containing `path`, read through libgit2 like the `asum` binary
+/// does. Commits still run `git commit`, so hooks and signing apply.
+pub struct LibGit2 {
+    pub path: PathBuf,
+}
+
+impl GitBackend for LibGit2 {
+    fn staged_diff(&self, patterns: &[String]) -> Result<String> {
+        get_git_diff_in_path(patterns, &self.path.to_string_lossy())
+    }
+
+    fn staged_files(&self) -> Result<String> {
+        get_staged_files_in_path(&self.path.to_string_lossy())
+    }
+
+    fn current_branch(&self) -> Result<String> {
+        get_current_branch_in_path(&self.path)
+    }
+
+    fn commits_since(&self, since: i64, author: Option<&str>) -> Result<Vec<String>> {
+        get_commit_subjects_since_in_path(since, author, &self.path)
+    }
+
+    fn recent_commits(&self, limit: usize) -> Result<Vec<(String, String)>> {
+        get_recent_commits_in_path(limit, &self.path)
+    }
+
+    fn commit(&self, message: &str) -> Result<()> {
+        git::commit_in_path(message, false, &self.path)
+    }
+
+    fn note(&self, rev: &str) -> Result<Option<String>> {
+        get_note_in_path(rev, &self.path)
+    }
+
+    fn add_note(&self, rev: &str, note: &str) -> Result<()> {
+        add_note_in_path(rev, note, &self.path)
+    }
+}
+
+/// The repository containing `path`, read by running the `git` binary.
+pub struct GitCli {
+    pub path: PathBuf,
+}
+
+impl GitCli {
+    /// Runs git with `args` and returns its output, failing with git's error message.
+    fn run(&self, args: &[&str]) -> Result<String> {
+        let output = git::git_command(&self.path)
+            .args(args)
+            .output()
+            .map_err(git::git_spawn_error)?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "git {} failed: {}",
+                args.first().unwrap_or(&""),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(encoding::decode_diff(&output.stdout))
+    }
+}
+
+impl GitBackend for GitCli {
+    fn staged_diff(&self, patterns: &[String]) -> Result<String> {
+        let mut args = vec![
+            "diff",
+            "--cached",
+            "--no-color",
+            "--no-ext-diff",
+            "-M",
+            "-C",
+            "--",
+        ];
+        args.extend(patterns.iter().map(String::as_str));
+        if patterns.iter().all(|spec| spec.starts_with(EXCLUDE_MAGIC)) {
+            args.push(".");
+        }
+        self.run(&args)
+    }
+
+    fn staged_files(&self) -> Result<String> {
+        let files = self.run(&["diff", "--cached", "--name-status", "-M", "-C"])?;
+        // Drop the similarity scores of renames and copies ("R100" becomes "R")
+        Ok(files
+            .lines()
+            .map(|line| match line.split_once('\t') {
+                Some((status, rest)) => format!("{}\t{}\n", &status[..1], rest),
+                None => format!("{}\n", line),
+            })
+            .collect())
+    }
+
+    fn current_branch(&self) -> Result<String> {
+        // `symbolic-ref` fails on a detached HEAD, which is not an error here
+        Ok(self
+            .run(&["symbolic-ref", "--quiet", "--short", "HEAD"])
+            .unwrap_or_default()
+            .trim()
+            .to_string())
+    }
+
+    fn commits_since(&self, since: i64, author: Option<&str>) -> Result<Vec<String>> {
+        let since = format!("--since=@{}", since);
+        let mut args = vec![
+            "log",
+            "--exclude=refs/notes/*",
+            "--all",
+            "--no-merges",
+            "--reverse",
+            "--format=%s",
+            &since,
+        ];
+        let author = author.map(|author| format!("--author={}", author));
+        if let Some(author) = &author {
+            args.extend(["--fixed-strings", author.as_str()]);
+        }
+        Ok(self.run(&args)?.lines().map(String::from).collect())
+    }
+
+    fn recent_commits(&self, limit: usize) -> Result<Vec<(String, String)>> {
+        let limit = format!("--max-count={}", limit);
+        let log = self.run(&["log", "--no-merges", "--format=%H%x09%s", &limit, "HEAD"])?;
+        Ok(log
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(hash, subject)| (hash.to_string(), subject.to_string()))
+            .collect())
+    }
+
+    fn commit(&self, message: &str) -> Result<()> {
+        self.run(&["commit", "--quiet", "-m", message]).map(|_| ())
+    }
+
+    fn note(&self, rev: &str) -> Result<Option<String>> {
+        // `git notes show` fails for commits without a note, so check the list first
+        let commit = self.run(&["rev-parse", "--verify", &format!("{}^{{commit}}", rev)])?;
+        let notes = self.run(&["notes", "list"])?;
+        if !notes.lines().any(|line| line.ends_with(commit.trim())) {
+            return Ok(None);
+        }
+        Ok(Some(
+            self.run(&["notes", "show", commit.trim()])?
+                .trim_end()
+                .to_string(),
+        ))
+    }
+
+    fn add_note(&self, rev: &str, note: &str) -> Result<()> {
+        self.run(&["notes", "add", "--force", "-m", note, rev])
+            .map(|_| ())
+    }
+}
+
+/// Staged changes read through a `GitBackend`. Like `GitStaged`, it falls back to
+/// the list of staged files when no staged file matches the patterns.
+pub struct BackendStaged {
+    pub git: Arc<dyn GitBackend>,
+}
+
+impl DiffSource for BackendStaged {
+    fn describe(&self) -> String {
+        "staged changes".to_string()
+    }
+
+    fn read(&self, patterns: &[String]) -> Result<String> {
+        let diff_text = self
+            .git
+            .staged_diff(patterns)
+            .context("Failed to get git diff")?;
+        if !diff_text.is_empty() {
+            return Ok(diff_text);
+        }
+        let files = self
+            .git
+            .staged_files()
+            .context("Failed to get staged files")?;
+        if !files.is_empty() {
+            warn!("No staged changes found in supported code files. Falling back to file list...");
+        }
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::process::Command;
+
+    fn git(path: &Path, args: &[&str]) {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(path)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?}: {:?}", args, output);
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        git(path, &["init", "-q", "-b", "main"]);
+        git(path, &["config", "user.name", "Test"]);
+        git(path, &["config", "user.email", "test@example.com"]);
+        std::fs::write(path.join("a.rs"), "fn a() {}\n").unwrap();
+        git(path, &["add", "a.rs"]);
+        git(path, &["commit", "-q", "-m", "feat: add a"]);
+        std::fs::write(path.join("b.rs"), "fn b() {}\n").unwrap();
+        std::fs::write(path.join("notes.txt"), "notes\n").unwrap();
+        git(path, &["add", "b.rs", "notes.txt"]);
+
+        let backends: Vec<(&str, Box<dyn GitBackend>)> = vec![
+            (
+                "libgit2",
+                Box::new(LibGit2 {
+                    path: path.to_path_buf(),
+                }),
+            ),
+            (
+                "cli",
+                Box::new(GitCli {
+                    path: path.to_path_buf(),
+                }),
+            ),
+        ];
+        for (name, backend) in &backends {
+            let diff = backend.staged_diff(&["*.rs".to_string()]).unwrap();
+            assert!(diff.contains("+fn b() {}"), "{}: {}", name, diff);
+            assert!(!diff.contains("notes.txt"), "{}: {}", name, diff);
+            assert_eq!(
+                backend.staged_files().unwrap(),
+                "A\tb.rs\nA\tnotes.txt\n",
+                "{}",
+                name
+            );
+            assert_eq!(backend.current_branch().unwrap(), "main", "{}", name);
+            assert_eq!(
+                backend.commits_since(0, Some("test@")).unwrap(),
+                vec!["feat: add a"],
+                "{}",
+                name
+            );
+            assert!(backend.commits_since(0, Some("nobody")).unwrap().is_empty());
+
+            let recent = backend.recent_commits(5).unwrap();
+            assert_eq!(recent.len(), 1, "{}", name);
+            assert_eq!(recent[0].1, "feat: add a", "{}", name);
+
+            assert_eq!(backend.note("HEAD").unwrap(), None, "{}", name);
+            backend.add_note("HEAD", &format!("by {}", name)).unwrap();
+            assert_eq!(
+                backend.note("HEAD").unwrap(),
+                Some(format!("by {}", name)),
+                "{}",
+                name
+            );
+            git(path, &["notes", "remove", "HEAD"]);
+        }
+
+        backends[1].1.commit("feat: add b").unwrap();
+        assert_eq!(backends[0].1.recent_commits(1).unwrap()[0].1, "feat: add b");
+        assert_eq!(backends[0].1.staged_files().unwrap(), "");
+    }
+}
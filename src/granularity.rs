@@ -0,0 +1,137 @@
+//! Commit granularity checks for ASUM.
+//!
+//! Compares the size of the staged changes with the repository's recent
+//! history and nudges users toward smaller, atomic commits.
+
+use crate::git::{DiffStat, get_commit_stats_in_path, get_staged_stat_in_path};
+use std::path::Path;
+use tracing::{debug, warn};
+
+/// Number of recent commits used to compute the typical commit size. Each one is
+/// diffed on every run, so this stays small.
+const HISTORY_LIMIT: usize = 50;
+
+/// Minimum number of commits needed before the history is considered meaningful.
+const MIN_HISTORY: usize = 10;
+
+/// How many times larger than average a commit must be to trigger a warning.
+const SIZE_FACTOR: usize = 3;
+
+/// Commits below this many changed lines are never reported.
+const MIN_LINES: usize = 200;
+
+/// Warns when the staged changes are unusually large for the repository in `path`.
+/// Git errors are only logged, since this check must never block message generation.
+pub fn warn_if_unusually_large(path: &Path) {
+    let stats = get_staged_stat_in_path(path)
+        .and_then(|staged| Ok((staged, get_commit_stats_in_path(HISTORY_LIMIT, path)?)));
+
+    match stats {
+        Ok((staged, history)) => {
+            if let Some(message) = check(staged, &history) {
+                warn!("{}", message);
+            }
+        }
+        Err(e) => debug!("Skipping commit size check: {}", e),
+    }
+}
+
+/// Returns a warning when `staged` is more than `SIZE_FACTOR` times the average size of
+/// the commits in `history` (and at least `MIN_LINES` lines), otherwise `None`.
+pub fn check(staged: DiffStat, history: &[DiffStat]) -> Option<String> {
+    if history.len() < MIN_HISTORY || staged.lines < MIN_LINES {
+        return None;
+    }
+
+    let average = history.iter().map(|s| s.lines).sum::<usize>() / history.len();
+    if staged.lines <= average.max(1) * SIZE_FACTOR {
+        return None;
+    }
+
+    Some(format!(
+        "This commit changes {} lines in {} files, about {}x the average of the last {} commits ({} lines). \
+         Consider splitting it into smaller commits with `asum split`.",
+        staged.lines,
+        staged.files,
+        staged.lines / average.max(1),
+        history.len(),
+        average
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(lines: usize, count: usize) -> Vec<DiffStat> {
+        vec![DiffStat { files: 2, lines }; count]
+    }
+
+    #[test]
+    fn test_check_table_driven() {
+        struct TestCase {
+            staged_lines: usize,
+            history: Vec<DiffStat>,
+            warns: bool,
+        }
+
+        let cases = vec![
+            // Far above the average
+            TestCase {
+                staged_lines: 900,
+                history: history(100, 20),
+                warns: true,
+            },
+            // Exactly at the threshold
+            TestCase {
+                staged_lines: 300,
+                history: history(100, 20),
+                warns: false,
+            },
+            // Large relative to history but below the absolute minimum
+            TestCase {
+                staged_lines: 150,
+                history: history(10, 20),
+                warns: false,
+            },
+            // Not enough history
+            TestCase {
+                staged_lines: 5000,
+                history: history(10, 5),
+                warns: false,
+            },
+            // Empty commits in history
+            TestCase {
+                staged_lines: 500,
+                history: history(0, 20),
+                warns: true,
+            },
+        ];
+
+        for case in cases {
+            let staged = DiffStat {
+                files: 12,
+                lines: case.staged_lines,
+            };
+            assert_eq!(
+                check(staged, &case.history).is_some(),
+                case.warns,
+                "staged: {}, history: {}",
+                case.staged_lines,
+                case.history.len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_message() {
+        let staged = DiffStat {
+            files: 12,
+            lines: 900,
+        };
+        let message = check(staged, &history(100, 20)).unwrap();
+        assert!(message.contains("900 lines in 12 files"));
+        assert!(message.contains("about 9x the average of the last 20 commits (100 lines)"));
+        assert!(message.ends_with("smaller commits with `asum split`."));
+    }
+}
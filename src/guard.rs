@@ -0,0 +1,181 @@
+//! Safety checks for generated commit messages.
+//!
+//! Before a message is committed without a human looking at it (`commit --yes`,
+//! `amend --yes`) or pre-filled by the git hook, it is checked for empty output,
+//! malformed replies and signs that the model was unsure. A rejected message is
+//! saved as a draft instead of being committed.
+
+use crate::git::get_git_path_in_path;
+use anyhow::{Context, Result, anyhow};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// File name of the saved draft, inside the `.git` directory.
+const DRAFT_NAME: &str = "ASUM_DRAFT_MSG";
+
+/// Subject lines longer than this are treated as a malformed reply.
+const MAX_SUBJECT_LENGTH: usize = 100;
+
+/// Phrases that show the model did not produce a real commit message. They only
+/// count as whole words, so "ui can't scroll" or "the api cannot retry" pass.
+const LOW_CONFIDENCE_PHRASES: &[&str] = &[
+    "i'm sorry",
+    "i am sorry",
+    "i cannot",
+    "i can't",
+    "as an ai",
+    "i'm not sure",
+    "i am not sure",
+    "not enough information",
+    "unable to determine",
+    "please provide",
+];
+
+/// Returns the reasons `message` should not be committed automatically; empty when it looks fine.
+pub fn validate(message: &str) -> Vec<String> {
+    let message = message.trim();
+    let Some(subject) = message.lines().next() else {
+        return vec!["The generated message is empty.".to_string()];
+    };
+
+    let mut problems = Vec::new();
+    let length = subject.chars().count();
+    if length > MAX_SUBJECT_LENGTH {
+        problems.push(format!(
+            "The subject line is {} characters long (limit {}).",
+            length, MAX_SUBJECT_LENGTH
+        ));
+    }
+    if message.contains("```") || message.contains("diff --git") {
+        problems.push("The reply contains code or diff output instead of a message.".to_string());
+    }
+    let lower = message.to_lowercase();
+    if let Some(phrase) = LOW_CONFIDENCE_PHRASES
+        .iter()
+        .find(|phrase| contains_words(&lower, phrase))
+    {
+        problems.push(format!(
+            "The reply looks unsure or off-topic (contains \"{}\").",
+            phrase
+        ));
+    }
+    problems
+}
+
+/// Whether `phrase` occurs in `text` with no letter or digit right before or after it.
+fn contains_words(text: &str, phrase: &str) -> bool {
+    text.match_indices(phrase).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + phrase.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// Returns the path of the draft in the `.git` directory of the repository at `repo`.
+pub fn draft_path(repo: &Path) -> Result<PathBuf> {
+    get_git_path_in_path(DRAFT_NAME, repo)
+}
+
+/// Saves `message` as a draft in the `.git` directory of the repository at `repo`.
+pub fn save_draft(message: &str, repo: &Path) -> Result<PathBuf> {
+    let path = draft_path(repo)?;
+    fs::write(&path, format!("{}\n", message.trim_end()))
+        .with_context(|| format!("Failed to save the draft to {}", path.display()))?;
+    Ok(path)
+}
+
+/// Fails with a clear error when `message` must not be committed automatically,
+/// saving it as a draft first so nothing is lost.
+pub fn ensure_committable(message: &str, repo: &Path) -> Result<()> {
+    let problems = validate(message);
+    if problems.is_empty() {
+        return Ok(());
+    }
+
+    let draft = match save_draft(message, repo) {
+        Ok(path) => format!(
+            "The draft was saved to {}; review it and commit with `git commit -e -F {}`.",
+            path.display(),
+            path.display()
+        ),
+        Err(e) => format!("The draft could not be saved: {:#}", e),
+    };
+    Err(anyhow!(
+        "Refusing to commit the generated message:\n  - {}\n{}",
+        problems.join("\n  - "),
+        draft
+    ))
+}
+
+/// Logs the problems found in `message` without blocking, for flows where the
+/// user reviews the message before it is used.
+pub fn warn_problems(message: &str) {
+    for problem in validate(message) {
+        warn!("{}", problem);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn test_validate_table_driven() {
+        let long_subject = format!("feat: {}", "x".repeat(MAX_SUBJECT_LENGTH));
+        let cases = vec![
+            ("feat(auth): add login\n\n- validate password", 0, ""),
+            ("", 1, "empty"),
+            ("  \n\n ", 1, "empty"),
+            (long_subject.as_str(), 1, "characters long"),
+            ("```\nfeat: add login\n```", 1, "code or diff"),
+            ("I'm sorry, I cannot see any changes.", 1, "unsure"),
+            ("Please provide the diff.", 1, "unsure"),
+            ("Unfortunately, I can't tell what changed.", 1, "unsure"),
+            ("fix: ui can't scroll past footer", 0, ""),
+            ("docs: note that the api cannot retry", 0, ""),
+            (
+                "fix(auth): handle wifi cannot connect\n\n- retry once",
+                0,
+                "",
+            ),
+        ];
+
+        for (message, count, expected) in cases {
+            let problems = validate(message);
+            assert_eq!(problems.len(), count, "message: {:?}", message);
+            if count > 0 {
+                assert!(
+                    problems[0].contains(expected),
+                    "message: {:?}, problems: {:?}",
+                    message,
+                    problems
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_ensure_committable_saves_draft() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        Command::new("git")
+            .arg("init")
+            .current_dir(repo)
+            .output()
+            .unwrap();
+
+        assert!(ensure_committable("fix: handle empty input", repo).is_ok());
+        assert!(!repo.join(".git").join(DRAFT_NAME).exists());
+
+        let err = ensure_committable("I cannot summarize this diff.", repo).unwrap_err();
+        let text = err.to_string();
+        assert!(text.contains("Refusing to commit"));
+        assert!(text.contains(DRAFT_NAME));
+        assert_eq!(
+            fs::read_to_string(repo.join(".git").join(DRAFT_NAME)).unwrap(),
+            "I cannot summarize this diff.\n"
+        );
+    }
+}
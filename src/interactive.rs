@@ -0,0 +1,369 @@
+//! Interactive terminal prompts for ASUM.
+//!
+//! Small helpers for asking questions on a reader/writer pair, so commands
+//! can be driven by stdin in practice and by in-memory buffers in tests.
+
+use anyhow::{Context, Result, anyhow};
+use std::io::{BufRead, BufReader, IsTerminal, Write};
+use std::process::Command;
+
+/// What to do with a generated message after reviewing it.
+#[derive(Debug, PartialEq)]
+pub enum Review {
+    /// Use the message as is.
+    Accept,
+    /// Edit the message in the user's editor.
+    Edit,
+    /// Generate a new message, with an optional extra instruction for the model.
+    Regenerate(Option<String>),
+    /// Show the diff in the pager, then ask again.
+    Diff,
+    /// Stop without using the message.
+    Quit,
+}
+
+/// Prints a question and reads one line of input.
+/// Empty answers (and end of input) fall back to the default, if any.
+pub fn ask<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    question: &str,
+    default: Option<&str>,
+) -> Result<String> {
+    match default {
+        Some(d) if !d.is_empty() => write!(output, "{} [{}]: ", question, d)?,
+        _ => write!(output, "{}: ", question)?,
+    }
+    output.flush()?;
+
+    let mut line = String::new();
+    let read = input.read_line(&mut line)?;
+    let answer = line.trim();
+
+    if answer.is_empty() {
+        return match default {
+            Some(d) => Ok(d.to_string()),
+            None if read == 0 => Err(anyhow!("Unexpected end of input")),
+            None => Ok(String::new()),
+        };
+    }
+    Ok(answer.to_string())
+}
+
+/// Like `ask` with an empty default, but the answer is not echoed while stdin is a
+/// terminal, so secrets such as API keys stay out of the scrollback. The echo is only
+/// turned off on Unix.
+pub fn ask_secret<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    question: &str,
+) -> Result<String> {
+    #[cfg(unix)]
+    let _hidden = EchoOff::stdin();
+    ask(input, output, question, Some(""))
+}
+
+/// Turns off the echo of the terminal on stdin until dropped. Enter still moves to
+/// the next line.
+#[cfg(unix)]
+struct EchoOff(libc::termios);
+
+#[cfg(unix)]
+impl EchoOff {
+    /// Turns off the echo, or returns `None` when stdin is not a terminal.
+    fn stdin() -> Option<Self> {
+        if !std::io::stdin().is_terminal() {
+            return None;
+        }
+        // SAFETY: `termios` is plain data, filled in by `tcgetattr` before it is read
+        let mut saved: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut saved) } != 0 {
+            return None;
+        }
+        let mut hidden = saved;
+        hidden.c_lflag &= !libc::ECHO;
+        hidden.c_lflag |= libc::ECHONL;
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &hidden) } != 0 {
+            return None;
+        }
+        Some(Self(saved))
+    }
+}
+
+#[cfg(unix)]
+impl Drop for EchoOff {
+    fn drop(&mut self) {
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.0) };
+    }
+}
+
+/// Asks a yes/no question. Empty answers use `default`; end of input counts as "no".
+pub fn confirm<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    question: &str,
+    default: bool,
+) -> Result<bool> {
+    let hint = if default { "Y/n" } else { "y/N" };
+    write!(output, "{} [{}]: ", question, hint)?;
+    output.flush()?;
+
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(false);
+    }
+    match line.trim().to_lowercase().as_str() {
+        "" => Ok(default),
+        "y" | "yes" => Ok(true),
+        _ => Ok(false),
+    }
+}
+
+/// Asks a yes/no question on the terminal: on stdin and stderr when stdin is a
+/// terminal, else on `/dev/tty`, as inside a git hook. Fails when there is no
+/// terminal to ask on.
+pub fn confirm_on_terminal(question: &str, default: bool) -> Result<bool> {
+    if std::io::stdin().is_terminal() {
+        let stdin = std::io::stdin();
+        return confirm(&mut stdin.lock(), &mut std::io::stderr(), question, default);
+    }
+    let tty = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .context("There is no terminal to ask on")?;
+    let mut output = tty.try_clone()?;
+    confirm(&mut BufReader::new(tty), &mut output, question, default)
+}
+
+/// Prints `options` as a numbered list and asks for one by number.
+/// Pressing Enter (or end of input) picks the first option. Returns its index.
+pub fn choose<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    question: &str,
+    options: &[String],
+) -> Result<usize> {
+    if options.is_empty() {
+        return Err(anyhow!("Nothing to choose from"));
+    }
+    for (i, option) in options.iter().enumerate() {
+        let mut lines = option.lines();
+        writeln!(output, "{}) {}", i + 1, lines.next().unwrap_or_default())?;
+        for line in lines {
+            writeln!(output, "   {}", line)?;
+        }
+    }
+
+    loop {
+        let answer = ask(input, output, question, Some("1"))?;
+        match answer.parse::<usize>() {
+            Ok(n) if (1..=options.len()).contains(&n) => return Ok(n - 1),
+            _ => writeln!(output, "Please enter a number from 1 to {}.", options.len())?,
+        }
+    }
+}
+
+/// Asks whether to accept, edit, regenerate or drop a message. Pressing Enter accepts;
+/// end of input quits. Regenerating asks for an optional extra instruction.
+pub fn review<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> Result<Review> {
+    loop {
+        write!(
+            output,
+            "(a)ccept, (e)dit, (r)egenerate, (d)iff, (q)uit [a]: "
+        )?;
+        output.flush()?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(Review::Quit);
+        }
+        match line.trim().to_lowercase().as_str() {
+            "" | "a" | "accept" => return Ok(Review::Accept),
+            "e" | "edit" => return Ok(Review::Edit),
+            "q" | "quit" => return Ok(Review::Quit),
+            "d" | "diff" => return Ok(Review::Diff),
+            "r" | "regenerate" => {
+                let instruction = ask(
+                    input,
+                    output,
+                    "Extra instruction (optional, e.g. \"mention the migration\")",
+                    Some(""),
+                )?;
+                return Ok(Review::Regenerate(
+                    Some(instruction).filter(|i| !i.is_empty()),
+                ));
+            }
+            _ => writeln!(output, "Please answer a, e, r, d or q.")?,
+        }
+    }
+}
+
+/// Opens `text` in `$VISUAL` or `$EDITOR` (falling back to `vi`) and returns the
+/// edited text without trailing whitespace.
+pub fn edit_in_editor(text: &str) -> Result<String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // Editors are often configured with arguments, e.g. "code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+
+    let file = std::env::temp_dir().join(format!("asum-message-{}.txt", std::process::id()));
+    std::fs::write(&file, text).context("Failed to write the message for editing")?;
+    let status = Command::new(program)
+        .args(parts)
+        .arg(&file)
+        .status()
+        .with_context(|| format!("Failed to start editor '{}'", editor));
+    let edited = std::fs::read_to_string(&file);
+    let _ = std::fs::remove_file(&file);
+
+    if !status?.success() {
+        return Err(anyhow!("Editor '{}' exited with an error", editor));
+    }
+    Ok(edited
+        .context("Failed to read the edited message")?
+        .trim_end()
+        .to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_ask_table_driven() {
+        struct TestCase {
+            input: &'static str,
+            default: Option<&'static str>,
+            expected: Option<&'static str>,
+        }
+
+        let cases = vec![
+            TestCase {
+                input: "value\n",
+                default: Some("default"),
+                expected: Some("value"),
+            },
+            TestCase {
+                input: "\n",
+                default: Some("default"),
+                expected: Some("default"),
+            },
+            TestCase {
+                input: "",
+                default: Some("default"),
+                expected: Some("default"),
+            },
+            TestCase {
+                input: "\n",
+                default: None,
+                expected: Some(""),
+            },
+            TestCase {
+                input: "",
+                default: None,
+                expected: None,
+            },
+        ];
+
+        for case in cases {
+            let mut output = Vec::new();
+            let result = ask(
+                &mut Cursor::new(case.input),
+                &mut output,
+                "Question",
+                case.default,
+            );
+            assert_eq!(
+                result.ok().as_deref(),
+                case.expected,
+                "input: {:?}",
+                case.input
+            );
+        }
+    }
+
+    #[test]
+    fn test_confirm_table_driven() {
+        let cases = vec![
+            ("y\n", false, true),
+            ("YES\n", false, true),
+            ("n\n", true, false),
+            ("\n", true, true),
+            ("\n", false, false),
+            ("", true, false),
+            ("maybe\n", true, false),
+        ];
+
+        for (input, default, expected) in cases {
+            let mut output = Vec::new();
+            let answer =
+                confirm(&mut Cursor::new(input), &mut output, "Proceed?", default).unwrap();
+            assert_eq!(answer, expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_choose_table_driven() {
+        let options = vec![
+            "feat: first\n- detail".to_string(),
+            "feat: second".to_string(),
+        ];
+        let cases = vec![("\n", 0), ("", 0), ("2\n", 1), ("7\nx\n1\n", 0)];
+
+        for (input, expected) in cases {
+            let mut output = Vec::new();
+            let index = choose(&mut Cursor::new(input), &mut output, "Pick", &options).unwrap();
+            assert_eq!(index, expected, "input: {:?}", input);
+
+            let printed = String::from_utf8(output).unwrap();
+            assert!(printed.starts_with("1) feat: first\n   - detail\n2) feat: second\n"));
+        }
+
+        assert!(choose(&mut Cursor::new(""), &mut Vec::new(), "Pick", &[]).is_err());
+    }
+
+    #[test]
+    fn test_review_table_driven() {
+        let cases = vec![
+            ("\n", Review::Accept),
+            ("A\n", Review::Accept),
+            ("e\n", Review::Edit),
+            ("q\n", Review::Quit),
+            ("d\n", Review::Diff),
+            ("", Review::Quit),
+            ("r\n\n", Review::Regenerate(None)),
+            (
+                "x\nr\nmention the migration\n",
+                Review::Regenerate(Some("mention the migration".to_string())),
+            ),
+        ];
+
+        for (input, expected) in cases {
+            let mut output = Vec::new();
+            let action = review(&mut Cursor::new(input), &mut output).unwrap();
+            assert_eq!(action, expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_edit_in_editor() {
+        let _lock = crate::test_utils::TEST_MUTEX.lock().unwrap();
+        let old_visual = std::env::var("VISUAL").ok();
+        // `true` leaves the file unchanged, so the text comes back as written
+        unsafe { std::env::set_var("VISUAL", "true --ignored") };
+        let edited = edit_in_editor("feat: keep me\n\n");
+        unsafe { std::env::set_var("VISUAL", "false") };
+        let failed = edit_in_editor("feat: keep me");
+        match old_visual {
+            Some(v) => unsafe { std::env::set_var("VISUAL", v) },
+            None => unsafe { std::env::remove_var("VISUAL") },
+        }
+
+        assert_eq!(edited.unwrap(), "feat: keep me");
+        assert!(failed.is_err());
+    }
+}
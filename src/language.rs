@@ -0,0 +1,131 @@
+//! Commit message language checks for ASUM.
+//!
+//! With `message_language` set, the prompt asks for messages in that language and
+//! replies are checked with `whatlang`, since small local models often answer in
+//! English (or in the language of the code comments) regardless.
+
+use whatlang::Lang;
+
+/// Fewest words of prose a message needs before its language is judged; shorter
+/// texts are detected too unreliably.
+const MIN_WORDS: usize = 4;
+
+/// Looks up a language by English name ("German"), native name ("Deutsch") or
+/// ISO 639-3 code ("deu"), ignoring case.
+pub fn parse(spec: &str) -> Option<Lang> {
+    let spec = spec.trim().to_lowercase();
+    Lang::from_code(spec.as_str()).or_else(|| {
+        Lang::all().iter().copied().find(|lang| {
+            lang.eng_name().to_lowercase() == spec || lang.name().to_lowercase() == spec
+        })
+    })
+}
+
+/// The instruction added to commit message prompts for `language`.
+pub fn instruction(language: &str) -> String {
+    format!(
+        "\n\n[LANGUAGE]\nWrite the description and body in {}. Keep the Conventional Commits \
+         type and scope, code identifiers and file paths unchanged.",
+        language
+    )
+}
+
+/// The instruction added when a reply came back in `detected` instead of `language`.
+pub fn insistence(language: &str, detected: Lang) -> String {
+    format!(
+        "\n\n[LANGUAGE]\nYour previous reply was in {} instead of {}. The message MUST be \
+         written in {}, even though the diff and code comments may be in another language.",
+        detected.eng_name(),
+        language,
+        language
+    )
+}
+
+/// Returns the language `message` was written in when it is reliably not `language`.
+/// Unknown languages and messages with too little prose are never reported.
+pub fn mismatch(message: &str, language: &str) -> Option<Lang> {
+    let expected = parse(language)?;
+    let prose = prose(message);
+    if prose.split_whitespace().count() < MIN_WORDS {
+        return None;
+    }
+    let info = whatlang::detect(&prose)?;
+    (info.is_reliable() && info.lang() != expected).then_some(info.lang())
+}
+
+/// The natural language part of a message: the description and body without the
+/// Conventional Commits header, code spans, identifiers and paths.
+fn prose(message: &str) -> String {
+    let mut lines = message.lines();
+    let subject = lines.next().unwrap_or_default();
+    let description = match subject.split_once(": ") {
+        Some((header, description)) if !header.contains(' ') => description,
+        _ => subject,
+    };
+
+    let mut text = String::new();
+    for line in std::iter::once(description).chain(lines) {
+        // Every other part of a line split at backticks is a code span
+        for part in line.split('`').step_by(2) {
+            for word in part.split_whitespace() {
+                let word = word.trim_start_matches(['-', '*']);
+                let technical = word.contains(['_', '/', '(', ')', '=', '<', '>'])
+                    || word.contains("::")
+                    || word.trim_end_matches(['.', ',', ':', ';']).contains('.');
+                if !word.is_empty() && !technical {
+                    text.push_str(word);
+                    text.push(' ');
+                }
+            }
+        }
+    }
+    text.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_table_driven() {
+        let cases = vec![
+            ("German", Some(Lang::Deu)),
+            ("deutsch", Some(Lang::Deu)),
+            ("deu", Some(Lang::Deu)),
+            (" English ", Some(Lang::Eng)),
+            ("Klingon", None),
+        ];
+        for (spec, expected) in cases {
+            assert_eq!(parse(spec), expected, "{}", spec);
+        }
+    }
+
+    #[test]
+    fn test_prose_drops_header_and_code() {
+        assert_eq!(
+            prose("feat(auth): add `otp_secret` to the user model\n\n- update src/user.rs"),
+            "add to the user model update"
+        );
+        assert_eq!(prose("Merge branch 'main'"), "Merge branch 'main'");
+    }
+
+    #[test]
+    fn test_mismatch_table_driven() {
+        let english = "feat(auth): add one-time password login\n\n\
+                       - Add a page where users enter the code from their phone\n\
+                       - Show a clear error message when the code has expired";
+        let german = "feat(auth): Anmeldung mit Einmalpasswort hinzufügen\n\n\
+                      - Neue Seite, auf der Benutzer den Code von ihrem Telefon eingeben\n\
+                      - Verständliche Fehlermeldung, wenn der Code abgelaufen ist";
+        let cases = vec![
+            ("english for german", english, "German", Some(Lang::Eng)),
+            ("german for german", german, "German", None),
+            ("german for english", german, "eng", Some(Lang::Deu)),
+            ("too short to judge", "fix: typo", "German", None),
+            ("unknown language", english, "Klingon", None),
+        ];
+        for (name, message, language, expected) in cases {
+            assert_eq!(mismatch(message, language), expected, "{}", name);
+        }
+    }
+}
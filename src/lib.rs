@@ -0,0 +1,83 @@
+//! ASUM - AI Commit Summarizer
+//!
+//! Generates professional commit messages from staged changes using AI providers
+//! like Google Gemini or local Ollama instances. The `asum` binary is a thin
+//! wrapper around `app::run`; other tools can embed the same pipeline through
+//! `Runner`, which takes its repository, diff source, provider, clipboard, git
+//! backend and clock as arguments instead of reading the process state.
+
+pub mod analysis;
+pub mod anonymize;
+pub mod app;
+pub mod asumignore;
+pub mod batch;
+pub mod binary_files;
+pub mod chunks;
+pub mod cli;
+pub mod clipboard;
+pub mod commands;
+pub mod config;
+pub mod db;
+pub mod diff_source;
+pub mod encoding;
+pub mod environment;
+pub mod failure;
+pub mod footers;
+pub mod git;
+pub mod git_backend;
+pub mod granularity;
+pub mod guard;
+pub mod interactive;
+pub mod language;
+pub mod merge;
+pub mod pager;
+pub mod pipeline;
+pub mod private_paths;
+pub mod profile;
+pub mod prompt_context;
+pub mod revert;
+pub mod runner;
+pub mod scope;
+pub mod secrets;
+pub mod shrink;
+pub mod spelling;
+pub mod strict;
+pub mod summarizer;
+pub mod tokens;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod untracked;
+
+pub use runner::Runner;
+
+#[cfg(test)]
+pub mod test_utils {
+    use std::ffi::OsString;
+    use std::sync::Mutex;
+    pub static TEST_MUTEX: Mutex<()> = Mutex::new(());
+
+    /// Points `HOME` at a temporary directory until dropped, so tests never write to
+    /// the real `~/.asum`. Hold `TEST_MUTEX` while it is alive.
+    pub struct TempHome {
+        pub dir: tempfile::TempDir,
+        old: Option<OsString>,
+    }
+
+    impl TempHome {
+        pub fn create() -> Self {
+            let dir = tempfile::tempdir().unwrap();
+            let old = std::env::var_os("HOME");
+            unsafe { std::env::set_var("HOME", dir.path()) };
+            Self { dir, old }
+        }
+    }
+
+    impl Drop for TempHome {
+        fn drop(&mut self) {
+            match &self.old {
+                Some(home) => unsafe { std::env::set_var("HOME", home) },
+                None => unsafe { std::env::remove_var("HOME") },
+            }
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! Merge commit messages for ASUM.
+//!
+//! While a merge is in progress (`MERGE_HEAD` exists), the staged changes are what
+//! the incoming branch brings plus any conflict resolutions. Summarized like any
+//! other change they read as new work under a conventional-commit type. Instead the
+//! prompt asks for a merge message: git's merge subject, a short summary of what
+//! the incoming branch brings, and how the conflicts were resolved.
+
+use crate::config::AsumConfig;
+use crate::git::{MergeInProgress, get_merge_in_progress_in_path};
+use std::path::Path;
+use tracing::{info, warn};
+
+/// Most incoming commit subjects listed in the prompt.
+const MAX_COMMITS: usize = 30;
+
+/// System prompt used instead of the configured one while a merge is in progress.
+const MERGE_SYSTEM_PROMPT: &str = "You write the commit message of a git merge. The first \
+line is the merge subject given below, unchanged. After a blank line, summarize in a few \
+\"- \" bullet points what the incoming branch brings, grouped by topic rather than commit by \
+commit. If files had conflicts, end with a blank line, a \"Conflicts resolved:\" line and one \
+\"- <file>: <how it was resolved>\" bullet per file, judged from the diff. Do not add a \
+conventional commit type. Reply with the message only.";
+
+/// Switches `config` to merge messages when a merge is in progress in the repository
+/// at `repo`: the system prompt is replaced, and the conventional-commit rules
+/// (`strict_format`, `scopes`, scope inference, the repository profile and the body
+/// bullet limit) are turned off. Returns whether a merge is in progress. Git errors
+/// are only logged, and the usual prompts are kept.
+pub fn apply(config: &mut AsumConfig, repo: &Path) -> bool {
+    let merge = match get_merge_in_progress_in_path(MAX_COMMITS, repo) {
+        Ok(Some(merge)) => merge,
+        Ok(None) => return false,
+        Err(e) => {
+            warn!("Could not check for a merge in progress: {:#}", e);
+            return false;
+        }
+    };
+    info!("Merge in progress; generating a merge commit message.");
+    config.system_prompt = format!("{}\n\n{}", MERGE_SYSTEM_PROMPT, context(&merge));
+    config.strict_format = false;
+    config.scopes.clear();
+    config.infer_scope = false;
+    config.repo_profile = "code".to_string();
+    config.max_body_bullets = 0;
+    true
+}
+
+/// Renders the `[MERGE]` section describing `merge` for the prompt.
+fn context(merge: &MergeInProgress) -> String {
+    let mut section = format!("[MERGE]\nMerge subject: {}\n", merge.subject);
+    if !merge.commits.is_empty() {
+        section.push_str("Incoming commits (newest first):\n");
+        for subject in &merge.commits {
+            section.push_str(&format!("- {}\n", subject));
+        }
+        if merge.total_commits > merge.commits.len() {
+            section.push_str(&format!(
+                "- ... and {} more\n",
+                merge.total_commits - merge.commits.len()
+            ));
+        }
+    }
+    if merge.conflicts.is_empty() {
+        section.push_str("Files with conflicts: none");
+    } else {
+        section.push_str(&format!(
+            "Files with conflicts: {}",
+            merge.conflicts.join(", ")
+        ));
+    }
+    section
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn git(repo: &Path, args: &[&str]) {
+        Command::new("git")
+            .args(args)
+            .current_dir(repo)
+            .output()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_context_table_driven() {
+        let merge = |commits: &[&str], total_commits, conflicts: &[&str]| MergeInProgress {
+            subject: "Merge branch 'login'".to_string(),
+            commits: commits.iter().map(|s| s.to_string()).collect(),
+            total_commits,
+            conflicts: conflicts.iter().map(|s| s.to_string()).collect(),
+        };
+        let cases = [
+            (
+                merge(&["feat: add login", "fix: check tokens"], 2, &[]),
+                "[MERGE]\nMerge subject: Merge branch 'login'\nIncoming commits (newest first):\n\
+                 - feat: add login\n- fix: check tokens\nFiles with conflicts: none",
+            ),
+            (
+                merge(&["feat: add login"], 5, &["src/a.rs", "src/b.rs"]),
+                "[MERGE]\nMerge subject: Merge branch 'login'\nIncoming commits (newest first):\n\
+                 - feat: add login\n- ... and 4 more\nFiles with conflicts: src/a.rs, src/b.rs",
+            ),
+            (
+                merge(&[], 0, &[]),
+                "[MERGE]\nMerge subject: Merge branch 'login'\nFiles with conflicts: none",
+            ),
+        ];
+        for (merge, expected) in cases {
+            assert_eq!(context(&merge), expected);
+        }
+    }
+
+    #[test]
+    fn test_apply_switches_to_merge_messages() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        git(repo, &["init", "-b", "main"]);
+        git(repo, &["config", "user.email", "asum@example.com"]);
+        git(repo, &["config", "user.name", "asum"]);
+        std::fs::write(repo.join("app.rs"), "fn main() {}\n").unwrap();
+        git(repo, &["add", "."]);
+        git(repo, &["commit", "-m", "feat: start"]);
+        git(repo, &["checkout", "-b", "login"]);
+        std::fs::write(repo.join("app.rs"), "fn main() { login(); }\n").unwrap();
+        git(repo, &["commit", "-am", "feat: add login"]);
+        git(repo, &["checkout", "main"]);
+        std::fs::write(repo.join("app.rs"), "fn main() { run(); }\n").unwrap();
+        git(repo, &["commit", "-am", "feat: add run"]);
+
+        let mut config = AsumConfig::from_defaults().unwrap();
+        let system_prompt = config.system_prompt.clone();
+        assert!(!apply(&mut config, repo));
+        assert_eq!(config.system_prompt, system_prompt);
+
+        // The merge stops on the conflict in app.rs
+        git(repo, &["merge", "login"]);
+        config.strict_format = true;
+        config.scopes = vec!["cli".to_string()];
+        assert!(apply(&mut config, repo));
+        assert!(config.system_prompt.starts_with(MERGE_SYSTEM_PROMPT));
+        assert!(config.system_prompt.ends_with(
+            "[MERGE]\nMerge subject: Merge branch 'login'\nIncoming commits (newest first):\n\
+             - feat: add login\nFiles with conflicts: app.rs"
+        ));
+        assert!(!config.strict_format);
+        assert!(config.scopes.is_empty());
+        assert!(!config.infer_scope);
+        assert_eq!(config.max_body_bullets, 0);
+    }
+}
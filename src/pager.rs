@@ -0,0 +1,403 @@
+//! Paging of long output such as diffs and dry-run payloads.
+//!
+//! Text is shown through the same pager git would use, with diff lines colored
+//! (added green, removed red, hunk headers cyan, file headers bold), so a diff
+//! can be scrolled and searched before it is sent to the provider. With the
+//! `highlight` feature, the code on each line is also highlighted by the syntax of
+//! its file with syntect, on a green or red background for added and removed lines.
+
+use crate::diff_source::header_path;
+use crate::git::get_config_value_in_path;
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tracing::debug;
+
+/// Pager used when neither git nor the environment names one.
+const DEFAULT_PAGER: &str = "less";
+
+/// Options git gives `less` when `LESS` is unset: quit if the text fits on one
+/// screen, keep colors, and leave the text on screen afterwards.
+const DEFAULT_LESS: &str = "FRX";
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const CYAN: &str = "\x1b[36m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Backgrounds of added and removed lines whose code is highlighted.
+const ADDED_BACKGROUND: &str = "\x1b[48;2;0;48;0m";
+const REMOVED_BACKGROUND: &str = "\x1b[48;2;64;0;0m";
+
+/// Picks the pager the way git does: `GIT_PAGER`, then `core.pager`, then `PAGER`,
+/// then `less`. Returns `None` when paging is turned off (an empty value or `cat`).
+pub fn resolve_pager(
+    git_pager: Option<String>,
+    core_pager: Option<String>,
+    pager: Option<String>,
+) -> Option<String> {
+    let command = git_pager
+        .or(core_pager)
+        .or(pager)
+        .unwrap_or_else(|| DEFAULT_PAGER.to_string());
+    let command = command.trim();
+    (!command.is_empty() && command != "cat").then(|| command.to_string())
+}
+
+/// How a piece of a diff line is colored.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Paint {
+    /// File headers: `diff --git`, `---` and `+++`.
+    Header,
+    /// Added lines, or their background when the code is highlighted.
+    Added,
+    /// Removed lines, or their background when the code is highlighted.
+    Removed,
+    /// Hunk headers.
+    Hunk,
+    /// Highlighted code, in the RGB color of its syntax.
+    Code(u8, u8, u8),
+}
+
+/// One line of a painted diff: its pieces, each with its color if it has one, and
+/// the background of the whole line.
+#[derive(Debug, Default, PartialEq)]
+pub struct PaintedLine {
+    pub pieces: Vec<(String, Option<Paint>)>,
+    pub background: Option<Paint>,
+}
+
+/// Splits each line of `text` (without its newline) into colored pieces. Lines that
+/// are not part of a diff stay plain, so whole dry-run payloads can be passed in.
+/// File headers are only recognized before the first hunk of a file, so a removed
+/// `-- comment` that reads `--- comment` is still a removed line.
+pub fn paint_diff(text: &str) -> Vec<PaintedLine> {
+    let mut painted = Vec::new();
+    let mut in_header = false;
+    let mut code: Option<CodeHighlighter> = None;
+    for line in text.split_inclusive('\n') {
+        let content = line.strip_suffix('\n').unwrap_or(line);
+        let whole = |paint| PaintedLine {
+            pieces: vec![(content.to_string(), Some(paint))],
+            background: None,
+        };
+        let line = if let Some(header) = content.strip_prefix("diff --git ") {
+            in_header = true;
+            code = header_path(header).and_then(CodeHighlighter::for_path);
+            whole(Paint::Header)
+        } else if in_header && (content.starts_with("+++ ") || content.starts_with("--- ")) {
+            whole(Paint::Header)
+        } else if content.starts_with("@@") {
+            in_header = false;
+            whole(Paint::Hunk)
+        } else if in_header {
+            PaintedLine {
+                pieces: vec![(content.to_string(), None)],
+                background: None,
+            }
+        } else {
+            let (marker, paint) = match content.chars().next() {
+                Some('+') => ("+", Some(Paint::Added)),
+                Some('-') => ("-", Some(Paint::Removed)),
+                Some(' ') => (" ", None),
+                _ => ("", None),
+            };
+            let highlighted = code
+                .as_mut()
+                .filter(|_| !marker.is_empty())
+                .and_then(|code| code.highlight(&content[marker.len()..]));
+            match (highlighted, paint) {
+                (Some(pieces), _) => {
+                    let mut line = PaintedLine {
+                        pieces: vec![(marker.to_string(), paint)],
+                        background: paint,
+                    };
+                    line.pieces.extend(pieces);
+                    line
+                }
+                (None, Some(paint)) => whole(paint),
+                (None, None) => PaintedLine {
+                    pieces: vec![(content.to_string(), None)],
+                    background: None,
+                },
+            }
+        };
+        painted.push(line);
+    }
+    painted
+}
+
+/// Colors a diff with ANSI escapes, see `paint_diff`.
+pub fn colorize_diff(text: &str) -> String {
+    let mut colored = String::with_capacity(text.len() + text.len() / 8);
+    for (line, painted) in text.split_inclusive('\n').zip(paint_diff(text)) {
+        if painted.pieces.iter().all(|(_, paint)| paint.is_none()) {
+            colored.push_str(line);
+            continue;
+        }
+        if let Some(background) = painted.background {
+            colored.push_str(background_escape(background));
+        }
+        for (piece, paint) in &painted.pieces {
+            match paint {
+                Some(paint) => colored.push_str(&format!("{}{}", escape(*paint), piece)),
+                None => colored.push_str(piece),
+            }
+            if painted.background.is_none() {
+                colored.push_str(RESET);
+            }
+        }
+        if painted.background.is_some() {
+            colored.push_str(RESET);
+        }
+        if line.ends_with('\n') {
+            colored.push('\n');
+        }
+    }
+    colored
+}
+
+/// The ANSI escape that starts text in `paint`.
+fn escape(paint: Paint) -> String {
+    match paint {
+        Paint::Header => BOLD.to_string(),
+        Paint::Added => GREEN.to_string(),
+        Paint::Removed => RED.to_string(),
+        Paint::Hunk => CYAN.to_string(),
+        Paint::Code(r, g, b) => format!("\x1b[38;2;{};{};{}m", r, g, b),
+    }
+}
+
+/// The ANSI escape of the background of a highlighted line in `paint`.
+fn background_escape(paint: Paint) -> &'static str {
+    match paint {
+        Paint::Added => ADDED_BACKGROUND,
+        Paint::Removed => REMOVED_BACKGROUND,
+        _ => "",
+    }
+}
+
+#[cfg(feature = "highlight")]
+use syntax::CodeHighlighter;
+
+/// Code highlighting with syntect, behind the `highlight` feature.
+#[cfg(feature = "highlight")]
+mod syntax {
+    use super::Paint;
+    use std::path::Path;
+    use std::sync::OnceLock;
+    use syntect::easy::HighlightLines;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    /// Theme of the highlighted code.
+    const THEME: &str = "base16-ocean.dark";
+
+    /// Highlights the code of one file by the syntax its extension names, line by
+    /// line so multi-line strings and comments carry over.
+    pub struct CodeHighlighter(HighlightLines<'static>);
+
+    impl CodeHighlighter {
+        /// Returns `None` for files whose syntax is not known.
+        pub fn for_path(path: &str) -> Option<Self> {
+            static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+            let extension = Path::new(path).extension()?.to_str()?;
+            let syntax = syntaxes().find_syntax_by_extension(extension)?;
+            let theme = &THEMES.get_or_init(ThemeSet::load_defaults).themes[THEME];
+            Some(Self(HighlightLines::new(syntax, theme)))
+        }
+
+        /// Splits `code` into pieces in the colors of its syntax.
+        pub fn highlight(&mut self, code: &str) -> Option<Vec<(String, Option<Paint>)>> {
+            let ranges = self.0.highlight_line(code, syntaxes()).ok()?;
+            Some(
+                ranges
+                    .into_iter()
+                    .map(|(style, piece)| {
+                        let color = style.foreground;
+                        (
+                            piece.to_string(),
+                            Some(Paint::Code(color.r, color.g, color.b)),
+                        )
+                    })
+                    .collect(),
+            )
+        }
+    }
+
+    /// Syntaxes known to syntect, loaded once.
+    fn syntaxes() -> &'static SyntaxSet {
+        static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+        SYNTAXES.get_or_init(SyntaxSet::load_defaults_nonewlines)
+    }
+}
+
+/// Without the `highlight` feature, no code is highlighted.
+#[cfg(not(feature = "highlight"))]
+struct CodeHighlighter;
+
+#[cfg(not(feature = "highlight"))]
+impl CodeHighlighter {
+    fn for_path(_path: &str) -> Option<Self> {
+        None
+    }
+
+    fn highlight(&mut self, _code: &str) -> Option<Vec<(String, Option<Paint>)>> {
+        None
+    }
+}
+
+/// Shows `text` in the pager when stdout is a terminal, colored unless `NO_COLOR`
+/// is set. Otherwise, or when the pager cannot be started, prints it as is.
+pub fn show(text: &str) {
+    let stdout = std::io::stdout();
+    if !stdout.is_terminal() {
+        print!("{}", text);
+        return;
+    }
+    let text = match std::env::var_os("NO_COLOR").filter(|v| !v.is_empty()) {
+        Some(_) => text.to_string(),
+        None => colorize_diff(text),
+    };
+
+    let pager = resolve_pager(
+        std::env::var("GIT_PAGER").ok(),
+        get_config_value_in_path("core.pager", Path::new(".")),
+        std::env::var("PAGER").ok(),
+    );
+    let Some(pager) = pager else {
+        print!("{}", text);
+        return;
+    };
+    if let Err(e) = page(&pager, &text) {
+        debug!("Could not run the pager '{}': {}", pager, e);
+        print!("{}", text);
+    }
+}
+
+/// Pipes `text` into `pager`, run through the shell like git does, and waits
+/// for it to exit.
+fn page(pager: &str, text: &str) -> std::io::Result<()> {
+    let mut command = Command::new("sh");
+    command.args(["-c", pager]).stdin(Stdio::piped());
+    if std::env::var_os("LESS").is_none() {
+        command.env("LESS", DEFAULT_LESS);
+    }
+
+    let mut child = command.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Quitting the pager early closes the pipe, which is not an error
+        match stdin.write_all(text.as_bytes()) {
+            Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(e),
+            _ => {}
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_pager_table_driven() {
+        let some = |s: &str| Some(s.to_string());
+        let cases = vec![
+            (None, None, None, Some("less")),
+            (None, None, some("more"), Some("more")),
+            (None, some("delta"), some("more"), Some("delta")),
+            (
+                some("less -S"),
+                some("delta"),
+                some("more"),
+                Some("less -S"),
+            ),
+            (some(""), some("delta"), None, None),
+            (None, some("cat"), None, None),
+        ];
+        for (git_pager, core_pager, pager, expected) in cases {
+            assert_eq!(
+                resolve_pager(git_pager.clone(), core_pager.clone(), pager.clone()).as_deref(),
+                expected,
+                "GIT_PAGER: {:?}, core.pager: {:?}, PAGER: {:?}",
+                git_pager,
+                core_pager,
+                pager
+            );
+        }
+    }
+
+    #[test]
+    fn test_colorize_diff() {
+        let diff = "===== USER PROMPT =====\ndiff --git a/x b/x\n--- a/x\n+++ b/x\n@@ -1 +1 @@\n-old\n+new\n ctx";
+        assert_eq!(
+            colorize_diff(diff),
+            "===== USER PROMPT =====\n\
+             \x1b[1mdiff --git a/x b/x\x1b[0m\n\
+             \x1b[1m--- a/x\x1b[0m\n\
+             \x1b[1m+++ b/x\x1b[0m\n\
+             \x1b[36m@@ -1 +1 @@\x1b[0m\n\
+             \x1b[31m-old\x1b[0m\n\
+             \x1b[32m+new\x1b[0m\n \
+             ctx"
+        );
+    }
+
+    #[test]
+    fn test_paint_diff_headers_only_before_hunks() {
+        let diff = "diff --git a/q.sql b/q.sql\n--- a/q.sql\n+++ b/q.sql\n@@ -1 +1 @@\n\
+                    --- old comment\n+++ new comment\n";
+        let paints: Vec<Option<Paint>> = paint_diff(diff)
+            .iter()
+            .map(|line| line.pieces[0].1)
+            .collect();
+        assert_eq!(
+            paints,
+            vec![
+                Some(Paint::Header),
+                Some(Paint::Header),
+                Some(Paint::Header),
+                Some(Paint::Hunk),
+                Some(Paint::Removed),
+                Some(Paint::Added),
+            ]
+        );
+    }
+
+    #[cfg(feature = "highlight")]
+    #[test]
+    fn test_colorize_diff_highlights_known_syntaxes() {
+        let strip = |text: &str| {
+            let mut plain = String::new();
+            let mut chars = text.chars();
+            while let Some(c) = chars.next() {
+                if c == '\x1b' {
+                    chars.by_ref().find(|&c| c == 'm');
+                } else {
+                    plain.push(c);
+                }
+            }
+            plain
+        };
+        let diff = "diff --git a/src/a.rs b/src/a.rs\n@@ -1 +1 @@\n-fn old() {}\n+fn new() {}\n";
+        let colored = colorize_diff(diff);
+        assert!(colored.contains("\x1b[38;2;"), "{:?}", colored);
+        assert!(colored.contains(ADDED_BACKGROUND), "{:?}", colored);
+        assert!(colored.contains(REMOVED_BACKGROUND), "{:?}", colored);
+        assert_eq!(strip(&colored), diff);
+
+        // Files of an unknown syntax keep the plain diff colors
+        let unknown = "diff --git a/notes.zzz b/notes.zzz\n@@ -1 +1 @@\n+note\n";
+        assert!(colorize_diff(unknown).ends_with("\x1b[32m+note\x1b[0m\n"));
+    }
+
+    #[test]
+    fn test_page_pipes_text_to_the_pager() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("paged.txt");
+        page(&format!("cat > '{}'", out.display()), "paged text\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "paged text\n");
+    }
+}
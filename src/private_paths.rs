@@ -0,0 +1,142 @@
+//! Private paths whose contents are never sent.
+//!
+//! `.asumignore` leaves files out of the prompt entirely, so the message cannot
+//! mention them. Some files should be named but not shown: production settings,
+//! certificates, anything under `secrets/`. Paths matching `[diff] private_paths`
+//! (gitignore syntax, like `.asumignore`) have their diffs replaced by one line each
+//! with the file name and its added and removed line counts.
+
+use crate::analysis::stat;
+use crate::asumignore::AsumIgnore;
+use crate::config::AsumConfig;
+use crate::diff_source::{file_sections, preamble};
+use tracing::info;
+
+/// The rules of `private_paths`.
+pub fn rules(config: &AsumConfig) -> AsumIgnore {
+    AsumIgnore::parse(&config.private_paths.join("\n"))
+}
+
+/// Replaces the file sections of private paths in `diff` by a `[PRIVATE FILES]`
+/// section in front of it that lists their names and line counts.
+pub fn apply(config: &AsumConfig, diff: String) -> String {
+    let (diff, hidden) = redact(config, diff);
+    if hidden > 0 {
+        info!(
+            "Sent only the names and line counts of {} private file(s).",
+            hidden
+        );
+    }
+    diff
+}
+
+/// Like `apply`, without logging. Also returns the number of private files.
+pub fn redact(config: &AsumConfig, diff: String) -> (String, usize) {
+    let rules = rules(config);
+    if rules.is_empty() {
+        return (diff, 0);
+    }
+    let mut kept = preamble(&diff).to_string();
+    let mut lines = Vec::new();
+    for (path, section) in file_sections(&diff) {
+        if !rules.is_section_ignored(path, section) {
+            kept.push_str(section);
+            continue;
+        }
+        lines.push(match stat::counts(section) {
+            Some((added, removed)) => format!("- {} (+{} -{})", path, added, removed),
+            None => format!("- {} (binary)", path),
+        });
+    }
+    if lines.is_empty() {
+        return (diff, 0);
+    }
+    let hidden = lines.len();
+    let diff = format!(
+        "[PRIVATE FILES]\nThese files changed, but their contents are private and were not \
+         sent. Mention them by name only where they matter:\n{}\n\n{}",
+        lines.join("\n"),
+        kept
+    );
+    (diff, hidden)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_table_driven() {
+        let section = |path: &str| {
+            format!(
+                "diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n@@ -1 +1,2 @@\n\
+                 -old\n+new\n+more\n"
+            )
+        };
+        let diff = format!(
+            "{}{}{}{}",
+            section("src/main.rs"),
+            section("config/prod/db.yml"),
+            section("deploy/tls.pem"),
+            section("secrets/api/key.txt"),
+        );
+        let heading = "[PRIVATE FILES]\nThese files changed, but their contents are private and \
+                       were not sent. Mention them by name only where they matter:\n";
+        let cases = [
+            (vec![], diff.clone()),
+            (vec!["docs/"], diff.clone()),
+            (
+                vec!["secrets/", "*.pem", "config/prod/*"],
+                format!(
+                    "{}- config/prod/db.yml (+2 -1)\n- deploy/tls.pem (+2 -1)\n\
+                     - secrets/api/key.txt (+2 -1)\n\n{}",
+                    heading,
+                    section("src/main.rs")
+                ),
+            ),
+            (vec!["*.pem", "!deploy/tls.pem"], diff.clone()),
+        ];
+        let mut config = AsumConfig::from_defaults().unwrap();
+        for (patterns, expected) in cases {
+            config.private_paths = patterns.iter().map(|p| p.to_string()).collect();
+            assert_eq!(apply(&config, diff.clone()), expected, "{:?}", patterns);
+        }
+    }
+
+    #[test]
+    fn test_apply_next_to_quoted_and_unreadable_headers() {
+        let private = "diff --git a/secrets/key.txt b/secrets/key.txt\n--- a/secrets/key.txt\n\
+                       +++ b/secrets/key.txt\n@@ -1 +1 @@\n-old-key\n+new-key\n";
+        let quoted = "diff --git \"a/we\\\"ird name.rs\" \"b/we\\\"ird name.rs\"\n\
+                      @@ -1 +1 @@\n-a\n+b\n";
+        let unreadable = "diff --git odd\n@@ -1 +1 @@\n-c\n+d\n";
+        let mut config = AsumConfig::from_defaults().unwrap();
+        config.private_paths = vec!["secrets/".to_string()];
+        let diff = format!("note\n{}{}{}", private, quoted, unreadable);
+        let sent = apply(&config, diff);
+        assert!(!sent.contains("new-key"), "{}", sent);
+        assert!(
+            sent.contains("- secrets/key.txt (+1 -1)\n\nnote\n"),
+            "{}",
+            sent
+        );
+        assert!(
+            sent.ends_with(&format!("{}{}", quoted, unreadable)),
+            "{}",
+            sent
+        );
+    }
+
+    #[test]
+    fn test_apply_to_a_file_renamed_out_of_a_private_path() {
+        let renamed = "diff --git a/secrets/prod.env b/config/prod.env\nsimilarity index 80%\n\
+                       rename from secrets/prod.env\nrename to config/prod.env\n\
+                       --- a/secrets/prod.env\n+++ b/config/prod.env\n@@ -1,2 +1,2 @@\n \
+                       HOST=db\n-PASSWORD=old\n+PASSWORD=new\n";
+        let mut config = AsumConfig::from_defaults().unwrap();
+        config.private_paths = vec!["secrets/".to_string()];
+        let sent = apply(&config, renamed.to_string());
+        assert!(!sent.contains("PASSWORD"), "{}", sent);
+        assert!(sent.contains("- config/prod.env (+1 -1)\n"), "{}", sent);
+    }
+}
@@ -0,0 +1,333 @@
+//! Repository profiles for ASUM.
+//!
+//! The default prompt is tuned for application code. Repositories that only hold
+//! configuration, such as dotfiles or infrastructure as code, get extra
+//! instructions that favor `chore`/`ci`/`infra` types and path-based scopes
+//! like `nvim` or `terraform/network`.
+
+use crate::config::AsumConfig;
+use crate::git::get_tracked_files_in_path;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// Values accepted in `repo_profile`.
+pub const PROFILES: &[&str] = &["auto", "code", "config"];
+
+/// Extensions of configuration files.
+const CONFIG_EXTENSIONS: &[&str] = &[
+    "toml",
+    "yaml",
+    "yml",
+    "json",
+    "jsonc",
+    "ini",
+    "conf",
+    "cfg",
+    "tf",
+    "tfvars",
+    "hcl",
+    "nix",
+    "properties",
+    "plist",
+    "kdl",
+    "xml",
+    "env",
+];
+
+/// File names of configuration files without a telling extension.
+const CONFIG_NAMES: &[&str] = &["Dockerfile", "Brewfile", "Vagrantfile", "Caddyfile"];
+
+/// Top-level directories that hold tool configuration in dotfiles repositories.
+const DOTFILE_DIRS: &[&str] = &[
+    "nvim",
+    "vim",
+    "zsh",
+    "bash",
+    "fish",
+    "tmux",
+    "git",
+    "alacritty",
+    "kitty",
+    "wezterm",
+    "hypr",
+    "i3",
+    "sway",
+    "waybar",
+    "emacs",
+    "config",
+];
+
+/// Extensions of application source code.
+const CODE_EXTENSIONS: &[&str] = &[
+    "rs", "py", "js", "jsx", "ts", "tsx", "go", "java", "kt", "c", "h", "cpp", "hpp", "cc", "cs",
+    "rb", "php", "swift", "scala", "ex", "exs", "hs", "ml", "clj", "dart", "lua", "vue", "svelte",
+];
+
+/// Top-level directories of infrastructure code, whose scope includes the next level
+/// (e.g. `terraform/network`).
+const INFRA_DIRS: &[&str] = &[
+    "terraform",
+    "infra",
+    "k8s",
+    "kubernetes",
+    "helm",
+    "charts",
+    "ansible",
+    "deploy",
+];
+
+/// Instructions added to the system prompt for configuration-only repositories.
+const CONFIG_INSTRUCTIONS: &str = r#"
+
+[REPOSITORY PROFILE]
+This repository contains configuration rather than application code (dotfiles or
+infrastructure as code). Choose the type for configuration changes:
+- chore: settings, tweaks, version or dependency bumps
+- ci: CI/CD pipelines and automation
+- infra: infrastructure resources (Terraform, Kubernetes, cloud settings)
+- feat or fix only when the change adds or repairs a capability of the setup
+Use the path of the changed area as the scope, e.g. "nvim" or "terraform/network"."#;
+
+/// What a repository mainly contains.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RepoProfile {
+    Code,
+    Config,
+}
+
+/// How a single file counts towards the profile.
+#[derive(Debug, PartialEq)]
+enum FileKind {
+    Code,
+    Config,
+    /// Documentation, scripts and anything else that fits both profiles.
+    Neutral,
+}
+
+/// Classifies a path by its location, name and extension.
+fn classify(path: &str) -> FileKind {
+    let path = Path::new(path);
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let top = path
+        .components()
+        .next()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .unwrap_or_default();
+    let in_dotfile_dir = path.components().count() > 1
+        && top != ".github"
+        && (top.starts_with('.') || DOTFILE_DIRS.contains(&top.as_ref()));
+
+    if in_dotfile_dir
+        || name.starts_with('.')
+        || CONFIG_NAMES.contains(&name.as_ref())
+        || CONFIG_EXTENSIONS.contains(&extension.as_str())
+    {
+        FileKind::Config
+    } else if CODE_EXTENSIONS.contains(&extension.as_str()) {
+        FileKind::Code
+    } else {
+        FileKind::Neutral
+    }
+}
+
+/// Detects the profile from the tracked files: a repository is configuration-only when
+/// it has configuration files and at most one code file for every ten of them.
+pub fn detect(files: &[String]) -> RepoProfile {
+    let (mut code, mut config) = (0, 0);
+    for file in files {
+        match classify(file) {
+            FileKind::Code => code += 1,
+            FileKind::Config => config += 1,
+            FileKind::Neutral => {}
+        }
+    }
+    if config > 0 && code * 10 <= config {
+        RepoProfile::Config
+    } else {
+        RepoProfile::Code
+    }
+}
+
+/// Suggests a path-based scope shared by all `paths`, such as `nvim` for
+/// `.config/nvim/init.lua` or `terraform/network` for `terraform/network/main.tf`.
+pub fn suggest_scope(paths: &[String]) -> Option<String> {
+    let scopes: Vec<String> = paths.iter().map(|path| scope_of(path)).collect();
+    let first = scopes.first()?;
+    scopes
+        .iter()
+        .all(|scope| scope == first)
+        .then(|| first.clone())
+        .filter(|scope| !scope.is_empty())
+}
+
+/// Scope of one path: its top-level directory without a leading dot (skipping
+/// `.config`), plus the next level for infrastructure directories. Files in the root
+/// use their name without dot and extension.
+fn scope_of(path: &str) -> String {
+    let mut parts: Vec<&str> = path.split('/').collect();
+    let file = parts.pop().unwrap_or_default();
+    if parts.first() == Some(&".config") {
+        parts.remove(0);
+    }
+
+    match parts.as_slice() {
+        [] => {
+            let name = file.trim_start_matches('.');
+            name.split('.').next().unwrap_or(name).to_string()
+        }
+        [top, next, ..] if INFRA_DIRS.contains(top) => format!("{}/{}", top, next),
+        [top, ..] => top.trim_start_matches('.').to_string(),
+    }
+}
+
+/// Adds the configuration-repository instructions to the system prompt when the
+/// profile (configured, or detected from the repository at `repo`) is `config`.
+/// `changed` lists the changed paths used to suggest a scope.
+pub fn apply(config: &mut AsumConfig, repo: &Path, changed: &[String]) {
+    let profile = match config.repo_profile.as_str() {
+        "code" => RepoProfile::Code,
+        "config" => RepoProfile::Config,
+        other => {
+            if other != "auto" {
+                warn!(
+                    "Unknown repo_profile '{}'; expected auto, code or config. Detecting it instead.",
+                    other
+                );
+            }
+            match get_tracked_files_in_path(repo) {
+                Ok(files) => detect(&files),
+                Err(_) => RepoProfile::Code,
+            }
+        }
+    };
+    if profile == RepoProfile::Code {
+        return;
+    }
+
+    info!("Configuration repository detected; asking for configuration-style commit types.");
+    config.system_prompt.push_str(CONFIG_INSTRUCTIONS);
+    if let Some(scope) = suggest_scope(changed) {
+        config
+            .system_prompt
+            .push_str(&format!("\nSuggested scope for this change: {}", scope));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(list: &[&str]) -> Vec<String> {
+        list.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_classify_table_driven() {
+        let cases = vec![
+            (".zshrc", FileKind::Config),
+            (".config/nvim/init.lua", FileKind::Config),
+            ("nvim/lua/plugins.lua", FileKind::Config),
+            ("terraform/network/main.tf", FileKind::Config),
+            ("k8s/deploy.yaml", FileKind::Config),
+            ("Dockerfile", FileKind::Config),
+            ("src/main.rs", FileKind::Code),
+            ("lua/plugin.lua", FileKind::Code),
+            (".github/workflows/ci.yml", FileKind::Config),
+            (".github/scripts/check.py", FileKind::Code),
+            ("README.md", FileKind::Neutral),
+            ("install.sh", FileKind::Neutral),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(classify(path), expected, "path: {}", path);
+        }
+    }
+
+    #[test]
+    fn test_detect_table_driven() {
+        let cases = vec![
+            (
+                paths(&[".zshrc", "nvim/init.lua", "README.md", "install.sh"]),
+                RepoProfile::Config,
+            ),
+            (
+                paths(&["terraform/main.tf", "terraform/variables.tf"]),
+                RepoProfile::Config,
+            ),
+            (
+                paths(&["Cargo.toml", "src/main.rs", "src/lib.rs"]),
+                RepoProfile::Code,
+            ),
+            (paths(&["README.md"]), RepoProfile::Code),
+            (paths(&[]), RepoProfile::Code),
+        ];
+        for (files, expected) in cases {
+            assert_eq!(detect(&files), expected, "files: {:?}", files);
+        }
+    }
+
+    #[test]
+    fn test_suggest_scope_table_driven() {
+        let cases = vec![
+            (paths(&[".config/nvim/init.lua"]), Some("nvim")),
+            (
+                paths(&["nvim/init.lua", "nvim/lua/plugins.lua"]),
+                Some("nvim"),
+            ),
+            (
+                paths(&["terraform/network/main.tf", "terraform/network/vpc.tf"]),
+                Some("terraform/network"),
+            ),
+            (paths(&[".zshrc"]), Some("zshrc")),
+            (paths(&[".tmux.conf"]), Some("tmux")),
+            (paths(&["nvim/init.lua", "zsh/aliases.zsh"]), None),
+            (paths(&[]), None),
+        ];
+        for (changed, expected) in cases {
+            assert_eq!(
+                suggest_scope(&changed).as_deref(),
+                expected,
+                "paths: {:?}",
+                changed
+            );
+        }
+    }
+
+    #[test]
+    fn test_apply_table_driven() {
+        let dir = tempfile::tempdir().unwrap();
+        let changed = paths(&["nvim/init.lua"]);
+        let cases = vec![
+            ("code", false),
+            ("config", true),
+            // Outside a git repository nothing can be detected
+            ("auto", false),
+        ];
+
+        for (profile, expected) in cases {
+            let mut config = AsumConfig::from_defaults().unwrap();
+            config.repo_profile = profile.to_string();
+            apply(&mut config, dir.path(), &changed);
+            assert_eq!(
+                config.system_prompt.contains("[REPOSITORY PROFILE]"),
+                expected,
+                "profile: {}",
+                profile
+            );
+            assert_eq!(
+                config
+                    .system_prompt
+                    .ends_with("Suggested scope for this change: nvim"),
+                expected,
+                "profile: {}",
+                profile
+            );
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Repository context placeholders for ASUM prompts.
+//!
+//! Prompts may contain `{{branch}}`, replaced with the checked-out branch, and
+//! `{{recent_commits}}`, replaced with the subjects of the latest commits. Branch
+//! names often encode a ticket, and recent subjects show the style the repository
+//! already follows. Nothing is read from git unless a prompt uses a placeholder.
+
+use crate::config::AsumConfig;
+use crate::git::{get_current_branch_in_path, get_recent_commits_in_path};
+use std::path::Path;
+use tracing::warn;
+
+/// Placeholder for the current branch name.
+pub const BRANCH: &str = "{{branch}}";
+
+/// Placeholder for the recent commit subjects.
+pub const RECENT_COMMITS: &str = "{{recent_commits}}";
+
+/// Shown for `{{branch}}` when HEAD is detached.
+const DETACHED: &str = "(detached HEAD)";
+
+/// Replaces the placeholders in the prompts of `config` with the context of the
+/// repository at `repo`.
+pub fn apply(config: &mut AsumConfig, repo: &Path) {
+    let mut prompts = vec![&mut config.system_prompt, &mut config.user_prompt];
+    prompts.extend(config.truncated_user_prompt.as_mut());
+    let uses = |placeholder: &str| prompts.iter().any(|prompt| prompt.contains(placeholder));
+
+    let branch = uses(BRANCH).then(|| match get_current_branch_in_path(repo) {
+        Ok(branch) if branch.is_empty() => DETACHED.to_string(),
+        Ok(branch) => branch,
+        Err(e) => {
+            warn!("Could not read the current branch: {:#}", e);
+            String::new()
+        }
+    });
+    let recent = uses(RECENT_COMMITS).then(|| {
+        match get_recent_commits_in_path(config.recent_commits, repo) {
+            Ok(commits) => subject_list(commits.into_iter().map(|(_, subject)| subject)),
+            Err(e) => {
+                warn!("Could not read the recent commits: {:#}", e);
+                String::new()
+            }
+        }
+    });
+
+    for prompt in prompts {
+        if let Some(branch) = &branch {
+            *prompt = prompt.replace(BRANCH, branch);
+        }
+        if let Some(recent) = &recent {
+            *prompt = prompt.replace(RECENT_COMMITS, recent);
+        }
+    }
+}
+
+/// Lists `subjects` one per line as `- subject`.
+fn subject_list(subjects: impl Iterator<Item = String>) -> String {
+    subjects
+        .map(|subject| format!("- {}", subject))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::tempdir;
+
+    fn git(repo: &Path, args: &[&str]) {
+        Command::new("git")
+            .args(args)
+            .current_dir(repo)
+            .output()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_apply_fills_the_placeholders() {
+        let dir = tempdir().unwrap();
+        let repo = dir.path();
+        git(repo, &["init", "-b", "feature/PROJ-42-login"]);
+        git(repo, &["config", "user.email", "asum@example.com"]);
+        git(repo, &["config", "user.name", "asum"]);
+        for (i, subject) in ["feat: add a", "fix(b): handle b", "docs: describe c"]
+            .into_iter()
+            .enumerate()
+        {
+            // Recent commits are sorted by time, so each gets its own second
+            Command::new("git")
+                .args(["commit", "--allow-empty", "-m", subject])
+                .env("GIT_COMMITTER_DATE", format!("{} +0000", 1_700_000_000 + i))
+                .current_dir(repo)
+                .output()
+                .unwrap();
+        }
+
+        let mut config = AsumConfig::from_defaults().unwrap();
+        config.recent_commits = 2;
+        config.system_prompt = "Branch: {{branch}}".to_string();
+        config.user_prompt = "[RECENT]\n{{recent_commits}}\n[DIFF]\n{{diff}}".to_string();
+        apply(&mut config, repo);
+        assert_eq!(config.system_prompt, "Branch: feature/PROJ-42-login");
+        assert_eq!(
+            config.user_prompt,
+            "[RECENT]\n- docs: describe c\n- fix(b): handle b\n[DIFF]\n{{diff}}"
+        );
+    }
+
+    #[test]
+    fn test_subject_list() {
+        let subjects = ["feat: a", "fix: b"].map(String::from);
+        assert_eq!(subject_list(subjects.into_iter()), "- feat: a\n- fix: b");
+    }
+}
@@ -0,0 +1,108 @@
+//! Revert commit messages for ASUM.
+//!
+//! Undoing a commit by hand, or with `git revert --no-commit`, stages changes that
+//! exactly reverse it. Described from the diff alone they read as new work, e.g.
+//! "remove the login form" for a revert of "add login form". When the staged changes
+//! undo one of the recent commits, the message says so instead, without asking the
+//! model.
+
+use crate::git::find_reverted_commit_in_path;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// Number of recent commits the staged changes are compared with.
+const MAX_COMMITS: usize = 20;
+
+/// Returns the revert message when the staged changes of the repository at `repo`
+/// exactly undo one of its last `MAX_COMMITS` commits. Git errors are only logged.
+pub fn message(repo: &Path) -> Option<String> {
+    match find_reverted_commit_in_path(MAX_COMMITS, repo) {
+        Ok(Some((hash, subject))) => {
+            info!(
+                "The staged changes revert {}; writing a revert message.",
+                hash
+            );
+            Some(revert_message(&hash, &subject))
+        }
+        Ok(None) => None,
+        Err(e) => {
+            warn!(
+                "Could not compare the staged changes with recent commits: {:#}",
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Formats the message reverting commit `hash` with subject `subject`.
+pub fn revert_message(hash: &str, subject: &str) -> String {
+    format!("revert: {}\n\nThis reverts commit {}.", subject, hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn git(repo: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(repo)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[test]
+    fn test_revert_message() {
+        assert_eq!(
+            revert_message("4d7a1b2", "feat(auth): add login"),
+            "revert: feat(auth): add login\n\nThis reverts commit 4d7a1b2."
+        );
+    }
+
+    #[test]
+    fn test_message_detects_reverted_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        git(repo, &["init"]);
+        git(repo, &["config", "user.email", "asum@example.com"]);
+        git(repo, &["config", "user.name", "asum"]);
+        let lines: Vec<String> = (1..=20).map(|i| format!("line {}", i)).collect();
+        std::fs::write(repo.join("app.txt"), lines.join("\n") + "\n").unwrap();
+        git(repo, &["add", "."]);
+        git(repo, &["commit", "-m", "feat: start"]);
+        std::fs::write(repo.join("login.txt"), "login\n").unwrap();
+        let edited = lines.join("\n").replace("line 2\n", "line two\n") + "\n";
+        std::fs::write(repo.join("app.txt"), edited).unwrap();
+        git(repo, &["add", "."]);
+        git(repo, &["commit", "-m", "feat: add login"]);
+        let login = git(repo, &["rev-parse", "HEAD"]);
+        let later = lines.join("\n").replace("line 2\n", "line two\n");
+        let later = later.replace("line 19\n", "line nineteen\n") + "\n";
+        std::fs::write(repo.join("app.txt"), later).unwrap();
+        git(repo, &["commit", "-am", "fix: rename line 19"]);
+
+        // Nothing staged, then a change that reverses nothing
+        assert_eq!(message(repo), None);
+        std::fs::write(repo.join("other.txt"), "other\n").unwrap();
+        git(repo, &["add", "other.txt"]);
+        assert_eq!(message(repo), None);
+        git(repo, &["reset", "-q", "other.txt"]);
+
+        // Reverting an older commit still matches after later changes to its file
+        git(repo, &["revert", "--no-commit", &login]);
+        assert_eq!(
+            message(repo),
+            Some(format!(
+                "revert: feat: add login\n\nThis reverts commit {}.",
+                login
+            ))
+        );
+
+        // Undoing only part of it is new work
+        git(repo, &["reset", "-q", "--", "login.txt"]);
+        assert_eq!(message(repo), None);
+    }
+}
@@ -0,0 +1,217 @@
+//! Programmatic entry point to the ASUM pipeline.
+//!
+//! The `asum` binary works on the current directory, the configuration file, the
+//! system clipboard, the wall clock and `~/.asum/asum.db`. `Runner` takes each of
+//! them as an argument instead, so integration tests and tools embedding ASUM can
+//! generate messages without changing the working directory or environment.
+
+use crate::binary_files;
+use crate::clipboard::ClipboardSink;
+use crate::config::AsumConfig;
+use crate::db::{Db, diff_hash};
+use crate::diff_source::DiffSource;
+use crate::git::{self, parse_date};
+use crate::git_backend::{BackendStaged, GitBackend, LibGit2};
+use crate::merge;
+use crate::pipeline::{adapt_prompts, complete_with, read_diff, repo_key};
+use crate::summarizer::pricing::estimate_cost;
+use crate::summarizer::{Completion, Summarizer, get_summarizer};
+use crate::untracked;
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Source of the current time, in seconds since the Unix epoch.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> i64;
+}
+
+/// The wall clock.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        git::now()
+    }
+}
+
+/// A clock stopped at the given time, for reproducible relative dates.
+pub struct FixedClock(pub i64);
+
+impl Clock for FixedClock {
+    fn now(&self) -> i64 {
+        self.0
+    }
+}
+
+/// Runs the message generation pipeline with explicit dependencies.
+///
+/// Defaults: the staged changes of `repo` (the current directory unless set) read
+/// through libgit2, the provider selected by the configuration, no clipboard, the
+/// wall clock and no history. Nothing is written to `~/.asum` unless `history` points there.
+pub struct Runner {
+    config: AsumConfig,
+    repo: PathBuf,
+    source: Option<Box<dyn DiffSource + Send + Sync>>,
+    summarizer: Option<Arc<dyn Summarizer>>,
+    clipboard: Option<Arc<dyn ClipboardSink>>,
+    git: Option<Arc<dyn GitBackend>>,
+    clock: Arc<dyn Clock>,
+    history: Option<PathBuf>,
+}
+
+impl Runner {
+    pub fn new(config: AsumConfig) -> Self {
+        Self {
+            config,
+            repo: PathBuf::from("."),
+            source: None,
+            summarizer: None,
+            clipboard: None,
+            git: None,
+            clock: Arc::new(SystemClock),
+            history: None,
+        }
+    }
+
+    /// Repository to work on.
+    pub fn repo(mut self, path: impl Into<PathBuf>) -> Self {
+        self.repo = path.into();
+        self
+    }
+
+    /// Diff to summarize instead of the staged changes of the repository.
+    pub fn source(mut self, source: impl DiffSource + Send + Sync + 'static) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+
+    /// Provider to ask instead of the one selected by the configuration.
+    pub fn summarizer(mut self, summarizer: Arc<dyn Summarizer>) -> Self {
+        self.summarizer = Some(summarizer);
+        self
+    }
+
+    /// Where generated messages are copied to, e.g. `clipboard::Auto` for the
+    /// backend the `asum` binary picks by default.
+    pub fn clipboard(mut self, clipboard: Arc<dyn ClipboardSink>) -> Self {
+        self.clipboard = Some(clipboard);
+        self
+    }
+
+    /// Repository access instead of libgit2 on `repo`. Untracked and binary
+    /// files, merges in progress, the repository profile and the history key still read `repo` directly.
+    pub fn git(mut self, git: Arc<dyn GitBackend>) -> Self {
+        self.git = Some(git);
+        self
+    }
+
+    /// Clock used for relative dates such as "yesterday".
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Database generated messages and token usage are recorded in.
+    pub fn history(mut self, db_path: impl Into<PathBuf>) -> Self {
+        self.history = Some(db_path.into());
+        self
+    }
+
+    /// Generates a commit message for the diff of the source, records it when a
+    /// history is set and copies it when a clipboard is set.
+    /// Fails with `Failure::NoChanges` when there is nothing to summarize.
+    pub async fn generate(&self) -> Result<String> {
+        let staged;
+        let source: &dyn DiffSource = match &self.source {
+            Some(source) => source.as_ref(),
+            None => {
+                staged = BackendStaged {
+                    git: self.backend(),
+                };
+                &staged
+            }
+        };
+        let mut config = self.config.clone();
+        let diff_text = read_diff(&config, source, &self.repo)?;
+        let diff_text = untracked::append(&config, &self.repo, diff_text);
+        let diff_text = match self.source {
+            Some(_) => diff_text,
+            None => {
+                merge::apply(&mut config, &self.repo);
+                binary_files::append(&config, &self.repo, diff_text)
+            }
+        };
+        adapt_prompts(&mut config, &self.repo, &diff_text).await;
+
+        let provided;
+        let summarizer: &dyn Summarizer = match &self.summarizer {
+            Some(summarizer) => summarizer.as_ref(),
+            None => {
+                provided = get_summarizer(config.clone()).await?;
+                provided.as_ref()
+            }
+        };
+        let completion = complete_with(summarizer, &config, diff_text.clone()).await?;
+
+        if let Some(db_path) = &self.history {
+            self.record(db_path, &config, &diff_text, &completion)?;
+        }
+        if let Some(clipboard) = &self.clipboard {
+            clipboard.copy(&completion.message)?;
+        }
+        Ok(completion.message)
+    }
+
+    /// Returns the commits since `since` (a date or a relative date counted back
+    /// from the clock), optionally only those by `author`.
+    pub fn commits_since(&self, since: &str, author: Option<&str>) -> Result<String> {
+        let since = parse_date(since, self.clock.now())?;
+        Ok(self
+            .backend()
+            .commits_since(since, author)?
+            .iter()
+            .map(|subject| format!("- {}\n", subject))
+            .collect())
+    }
+
+    /// The repository access set with `git`, or libgit2 on `repo`.
+    fn backend(&self) -> Arc<dyn GitBackend> {
+        self.git.clone().unwrap_or_else(|| {
+            Arc::new(LibGit2 {
+                path: self.repo.clone(),
+            })
+        })
+    }
+
+    /// Stores the message and the tokens it took in the database at `db_path`.
+    fn record(
+        &self,
+        db_path: &Path,
+        config: &AsumConfig,
+        diff_text: &str,
+        completion: &Completion,
+    ) -> Result<()> {
+        let db = Db::open(db_path)?;
+        let provider = &config.active_provider;
+        let model = config.active_model().unwrap_or_default();
+        db.record_generation(
+            &repo_key(&self.repo)?,
+            &self.backend().current_branch().unwrap_or_default(),
+            &diff_hash(diff_text),
+            provider,
+            model,
+            &completion.message,
+        )?;
+        if let Some(usage) = completion.usage {
+            db.record_usage(
+                provider,
+                model,
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                estimate_cost(provider, model, usage),
+            )?;
+        }
+        Ok(())
+    }
+}
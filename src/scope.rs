@@ -0,0 +1,170 @@
+//! Scopes inferred from the changed paths.
+//!
+//! In a monorepo the scope of a change is usually the package it touches: a change
+//! under `packages/auth/` reads as `feat(auth): ...`. When every changed file lies
+//! in one package, or under one path of the `[scopes]` table, the prompt names that
+//! scope, and a message that comes back without a scope gets it added.
+
+use crate::config::AsumConfig;
+use crate::strict::TYPES;
+
+/// Directories whose subdirectories are the packages of a monorepo.
+pub const WORKSPACE_DIRS: &[&str] = &[
+    "crates",
+    "packages",
+    "apps",
+    "libs",
+    "services",
+    "modules",
+    "plugins",
+    "components",
+];
+
+/// Scope of one changed file: the scope of the longest `scope_paths` prefix it lies
+/// under, else its package when it is inside one of `WORKSPACE_DIRS`.
+pub fn scope_of(path: &str, scope_paths: &[(String, String)]) -> Option<String> {
+    let mapped = scope_paths
+        .iter()
+        .filter(|(prefix, _)| {
+            path.strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+        .max_by_key(|(prefix, _)| prefix.len());
+    if let Some((_, scope)) = mapped {
+        return Some(scope.clone());
+    }
+    match path.split('/').collect::<Vec<_>>().as_slice() {
+        [top, name, _, ..] if WORKSPACE_DIRS.contains(top) => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+/// Infers the scope shared by the `changed` paths, when `infer_scope` is on. Files in
+/// the repository root, such as lock files, are ignored; any other file outside the
+/// scope gives none. With `scopes` configured, an inferred package must be one of
+/// them, while scopes from `[scopes]` are always used.
+pub fn infer(config: &AsumConfig, changed: &[String]) -> Option<String> {
+    if !config.infer_scope {
+        return None;
+    }
+    let mut scopes = changed
+        .iter()
+        .filter(|path| path.contains('/'))
+        .map(|path| scope_of(path, &config.scope_paths));
+    let first = scopes.next()??;
+    if !scopes.all(|scope| scope.as_ref() == Some(&first)) {
+        return None;
+    }
+    let allowed = config.scopes.is_empty()
+        || config.scopes.contains(&first)
+        || config.scope_paths.iter().any(|(_, scope)| *scope == first);
+    allowed.then_some(first)
+}
+
+/// Instruction added to the system prompt when the change has the scope `scope`.
+pub fn instruction(scope: &str) -> String {
+    format!(
+        "\n\n[SCOPE]\nEvery changed file belongs to \"{}\"; use it as the scope, e.g. \
+         `feat({}): ...`.",
+        scope, scope
+    )
+}
+
+/// Adds `scope` to the subject of `message` when it is `<type>: ...` or
+/// `<type>!: ...` without a scope. Other messages are returned unchanged.
+pub fn fill(message: &str, scope: &str) -> String {
+    let (subject, rest) = message.split_once('\n').unwrap_or((message, ""));
+    let Some((prefix, description)) = subject.split_once(": ") else {
+        return message.to_string();
+    };
+    let (kind, bang) = match prefix.strip_suffix('!') {
+        Some(kind) => (kind, "!"),
+        None => (prefix, ""),
+    };
+    if !TYPES.contains(&kind) {
+        return message.to_string();
+    }
+    let subject = format!("{}({}){}: {}", kind, scope, bang, description);
+    if message.contains('\n') {
+        format!("{}\n{}", subject, rest)
+    } else {
+        subject
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|path| path.to_string()).collect()
+    }
+
+    #[test]
+    fn test_infer_table_driven() {
+        let mut config = AsumConfig::from_defaults().unwrap();
+        config.scope_paths = vec![
+            ("apps/web-client".to_string(), "web".to_string()),
+            ("docs".to_string(), "docs".to_string()),
+            ("docs/api".to_string(), "api-docs".to_string()),
+        ];
+        let cases = [
+            (
+                paths(&["packages/auth/src/login.ts", "packages/auth/package.json"]),
+                Some("auth"),
+            ),
+            (
+                paths(&["packages/auth/src/login.ts", "pnpm-lock.yaml"]),
+                Some("auth"),
+            ),
+            (
+                paths(&["packages/auth/index.ts", "packages/ui/index.ts"]),
+                None,
+            ),
+            (paths(&["packages/auth/index.ts", "src/main.ts"]), None),
+            (paths(&["apps/web-client/src/app.tsx"]), Some("web")),
+            (
+                paths(&["docs/api/auth.md", "docs/api/ui.md"]),
+                Some("api-docs"),
+            ),
+            (paths(&["docs/guide.md"]), Some("docs")),
+            (paths(&["docsite/index.md"]), None),
+            (paths(&["packages/README.md"]), None),
+            (paths(&["Cargo.toml"]), None),
+            (paths(&[]), None),
+        ];
+        for (changed, expected) in cases {
+            assert_eq!(
+                infer(&config, &changed).as_deref(),
+                expected,
+                "{:?}",
+                changed
+            );
+        }
+
+        let changed = paths(&["crates/core/src/lib.rs"]);
+        config.scopes = vec!["cli".to_string()];
+        assert_eq!(infer(&config, &changed), None);
+        config.scopes.push("core".to_string());
+        assert_eq!(infer(&config, &changed).as_deref(), Some("core"));
+        config.infer_scope = false;
+        assert_eq!(infer(&config, &changed), None);
+    }
+
+    #[test]
+    fn test_fill_table_driven() {
+        let cases = [
+            ("feat: add login", "feat(auth): add login"),
+            (
+                "fix!: drop tokens\n\n- remove them",
+                "fix(auth)!: drop tokens\n\n- remove them",
+            ),
+            ("feat(ui): add login", "feat(ui): add login"),
+            ("Merge branch 'login'", "Merge branch 'login'"),
+            ("update: things", "update: things"),
+        ];
+        for (message, expected) in cases {
+            assert_eq!(fill(message, "auth"), expected, "{}", message);
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Boundary-aware shrinking of diffs that are too long for a request.
+//!
+//! Cutting a diff at a fixed length keeps unchanged context lines while dropping
+//! changed ones, and leaves the last hunk half shown. `shrink` removes the context
+//! lines first, then keeps whole hunks in diff order as long as they fit, and only
+//! cuts inside a hunk, at a line boundary, when not even one hunk fits.
+
+use crate::tokens;
+
+/// How the length of a diff is measured against its limit.
+pub trait Measure {
+    /// Returns the length of `text`.
+    fn size(&self, text: &str) -> usize;
+
+    /// Returns the longest prefix of `text` no longer than `max`.
+    fn cut<'a>(&self, text: &'a str, max: usize) -> &'a str;
+}
+
+/// Length in characters, as `max_diff_length` counts it.
+pub struct Chars;
+
+impl Measure for Chars {
+    fn size(&self, text: &str) -> usize {
+        text.chars().count()
+    }
+
+    fn cut<'a>(&self, text: &'a str, max: usize) -> &'a str {
+        match text.char_indices().nth(max) {
+            Some((end, _)) => &text[..end],
+            None => text,
+        }
+    }
+}
+
+/// Length in tokens, for `context_window`.
+pub struct Tokens;
+
+impl Measure for Tokens {
+    fn size(&self, text: &str) -> usize {
+        tokens::count(text)
+    }
+
+    fn cut<'a>(&self, text: &'a str, max: usize) -> &'a str {
+        tokens::truncate(text, max)
+    }
+}
+
+/// Shrinks `diff` to at most `max` as measured by `measure`. Context lines are
+/// dropped first; if that is not enough, whole hunks are kept in diff order while
+/// they fit, with the header of their file. Files without hunks (renames, binary
+/// files) keep their header when it fits.
+pub fn shrink(diff: &str, max: usize, measure: &dyn Measure) -> String {
+    if measure.size(diff) <= max {
+        return diff.to_string();
+    }
+    let changes = drop_context(diff);
+    if measure.size(&changes) <= max {
+        return changes;
+    }
+
+    let mut shrunk = String::new();
+    let mut used = 0;
+    let mut add = |text: &str, shrunk: &mut String| {
+        let size = measure.size(text);
+        let fits = used + size <= max;
+        if fits {
+            shrunk.push_str(text);
+            used += size;
+        }
+        fits
+    };
+    // The header of the current file until one of its hunks is kept
+    let mut header: Option<&str> = None;
+    let mut has_hunks = false;
+    for (is_hunk, unit) in units(&changes) {
+        if !is_hunk {
+            if let Some(header) = header.filter(|_| !has_hunks) {
+                add(header, &mut shrunk);
+            }
+            header = Some(unit);
+            has_hunks = false;
+            continue;
+        }
+        has_hunks = true;
+        let with_header = format!("{}{}", header.unwrap_or_default(), unit);
+        if add(&with_header, &mut shrunk) {
+            header = None;
+        }
+    }
+    if let Some(header) = header.filter(|_| !has_hunks) {
+        add(header, &mut shrunk);
+    }
+    if !shrunk.is_empty() {
+        return shrunk;
+    }
+
+    // Not even one hunk fits: cut the first one at a line boundary
+    let cut = measure.cut(&changes, max);
+    match cut.rfind('\n') {
+        Some(end) => cut[..=end].to_string(),
+        None => cut.to_string(),
+    }
+}
+
+/// Removes the unchanged context lines from the hunks of `diff`.
+fn drop_context(diff: &str) -> String {
+    let mut in_hunk = false;
+    diff.split_inclusive('\n')
+        .filter(|line| {
+            if line.starts_with("diff --git ") {
+                in_hunk = false;
+            } else if line.starts_with("@@") {
+                in_hunk = true;
+            } else if in_hunk && (line.starts_with(' ') || line.trim_end_matches('\n').is_empty()) {
+                return false;
+            }
+            true
+        })
+        .collect()
+}
+
+/// Splits `diff` into file headers (`false`) and hunks (`true`), in order.
+fn units(diff: &str) -> Vec<(bool, &str)> {
+    let mut units = Vec::new();
+    let mut start = 0;
+    let mut is_hunk = false;
+    let mut offset = 0;
+    for line in diff.split_inclusive('\n') {
+        let starts_file = line.starts_with("diff --git ");
+        let starts_hunk = line.starts_with("@@");
+        if (starts_file || starts_hunk) && offset > start {
+            units.push((is_hunk, &diff[start..offset]));
+            start = offset;
+        }
+        if starts_file || starts_hunk {
+            is_hunk = starts_hunk;
+        }
+        offset += line.len();
+    }
+    if offset > start {
+        units.push((is_hunk, &diff[start..offset]));
+    }
+    units
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n\
+                        @@ -1,3 +1,3 @@\n fn keep() {}\n-fn old() {}\n+fn new() {}\n\
+                        @@ -10,2 +10,3 @@\n fn ctx() {}\n+fn added() {}\n\
+                        diff --git a/logo.png b/logo.png\nBinary files differ\n\
+                        diff --git a/b.rs b/b.rs\n--- a/b.rs\n+++ b/b.rs\n\
+                        @@ -1 +1 @@\n-fn b() {}\n+fn b2() {}\n";
+
+    #[test]
+    fn test_shrink_table_driven() {
+        let without_context = DIFF
+            .replace(" fn keep() {}\n", "")
+            .replace(" fn ctx() {}\n", "");
+        let first_hunk = "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n\
+                          @@ -1,3 +1,3 @@\n-fn old() {}\n+fn new() {}\n";
+        let cases = vec![
+            ("fits", DIFF.len(), DIFF.to_string()),
+            ("context dropped", without_context.len(), without_context),
+            (
+                "whole hunks",
+                first_hunk.len() + 40,
+                format!("{}@@ -10,2 +10,3 @@\n+fn added() {{}}\n", first_hunk),
+            ),
+            (
+                "header without hunks",
+                first_hunk.len() + 90,
+                format!(
+                    "{}@@ -10,2 +10,3 @@\n+fn added() {{}}\n\
+                     diff --git a/logo.png b/logo.png\nBinary files differ\n",
+                    first_hunk
+                ),
+            ),
+            (
+                "cut at a line",
+                50,
+                "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n".to_string(),
+            ),
+        ];
+
+        for (name, max, expected) in cases {
+            let shrunk = shrink(DIFF, max, &Chars);
+            assert_eq!(shrunk, expected, "{}", name);
+            assert!(shrunk.chars().count() <= max, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_shrink_skips_hunks_that_do_not_fit() {
+        let big = format!(
+            "diff --git a/big.rs b/big.rs\n@@ -1 +1 @@\n{}",
+            "+x\n".repeat(100)
+        );
+        let small = "diff --git a/small.rs b/small.rs\n@@ -1 +1 @@\n+y\n";
+        let diff = format!("{}{}", big, small);
+        assert_eq!(shrink(&diff, 60, &Chars), small);
+    }
+
+    #[test]
+    fn test_shrink_counts_tokens() {
+        let shrunk = shrink(DIFF, 30, &Tokens);
+        assert!(tokens::count(&shrunk) <= 30, "{}", shrunk);
+        assert!(shrunk.starts_with("diff --git "), "{}", shrunk);
+        assert!(shrunk.ends_with('\n'));
+    }
+}
@@ -0,0 +1,280 @@
+//! Spell-check of generated messages against a project dictionary.
+//!
+//! Models occasionally misspell product names and identifiers, and a typo in a
+//! commit message stays in the history forever. There is no general English
+//! dictionary: words in the reply are compared with the configured `glossary`
+//! and the identifiers in the diff, and a word that is close to one of them
+//! without matching it is reported, with the dictionary spelling as the fix.
+
+use std::collections::HashMap;
+
+/// Values accepted in `spell_check`.
+pub const MODES: &[&str] = &["fix", "flag", "off"];
+
+/// Words shorter than this are never checked; short words collide too easily.
+const MIN_WORD_LENGTH: usize = 4;
+
+/// Words at least this long may be two edits away from a dictionary term.
+const LONG_WORD_LENGTH: usize = 8;
+
+/// Known spellings, keyed by their lowercase form.
+#[derive(Debug, Default)]
+pub struct Dictionary {
+    terms: HashMap<String, Term>,
+}
+
+/// A known spelling and where it came from.
+#[derive(Debug, Clone)]
+struct Term {
+    spelling: String,
+    /// Glossary terms also have their capitalization enforced.
+    glossary: bool,
+}
+
+/// A word of the message and the dictionary spelling it should have.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Correction {
+    pub word: String,
+    pub suggestion: String,
+}
+
+impl Dictionary {
+    /// Builds the dictionary from the glossary and the identifiers in `diff`.
+    /// Only identifiers that cannot be ordinary words (`snake_case`, `camelCase`
+    /// or with digits) are taken, so English words near a variable name are left alone.
+    pub fn new(glossary: &[String], diff: &str) -> Self {
+        let mut terms = HashMap::new();
+        for line in diff.lines() {
+            let is_change = (line.starts_with('+') && !line.starts_with("+++"))
+                || (line.starts_with('-') && !line.starts_with("---"));
+            if !is_change {
+                continue;
+            }
+            for (_, word) in words(&line[1..]) {
+                if word.chars().count() >= MIN_WORD_LENGTH && is_identifier(word) {
+                    terms.entry(word.to_lowercase()).or_insert(Term {
+                        spelling: word.to_string(),
+                        glossary: false,
+                    });
+                }
+            }
+        }
+        for term in glossary
+            .iter()
+            .map(|term| term.trim())
+            .filter(|t| !t.is_empty())
+        {
+            terms.insert(
+                term.to_lowercase(),
+                Term {
+                    spelling: term.to_string(),
+                    glossary: true,
+                },
+            );
+        }
+        Self { terms }
+    }
+
+    /// Returns the dictionary spelling `word` was probably meant to have, if it
+    /// differs from `word`.
+    fn suggest(&self, word: &str) -> Option<&str> {
+        let lower = word.to_lowercase();
+        if let Some(term) = self.terms.get(&lower) {
+            // The right word; only glossary terms have their case enforced
+            return (term.glossary && term.spelling != word).then_some(term.spelling.as_str());
+        }
+
+        let length = lower.chars().count();
+        if length < MIN_WORD_LENGTH {
+            return None;
+        }
+        let max_distance = if length >= LONG_WORD_LENGTH { 2 } else { 1 };
+        let mut best: Option<(usize, &Term)> = None;
+        let mut tie = false;
+        for (key, term) in &self.terms {
+            if key.chars().next() != lower.chars().next() {
+                continue;
+            }
+            let distance = edit_distance(&lower, key);
+            if distance > max_distance {
+                continue;
+            }
+            match best {
+                Some((best_distance, _)) if distance > best_distance => {}
+                Some((best_distance, _)) if distance == best_distance => tie = true,
+                _ => {
+                    best = Some((distance, term));
+                    tie = false;
+                }
+            }
+        }
+        // Two equally close terms leave the intended one unclear
+        best.filter(|_| !tie)
+            .map(|(_, term)| term.spelling.as_str())
+    }
+}
+
+/// Returns the likely misspellings in `message`, once per word.
+pub fn check(message: &str, dictionary: &Dictionary) -> Vec<Correction> {
+    let mut corrections: Vec<Correction> = Vec::new();
+    for (_, word) in words(message) {
+        if corrections.iter().any(|c| c.word == word) {
+            continue;
+        }
+        if let Some(suggestion) = dictionary.suggest(word) {
+            corrections.push(Correction {
+                word: word.to_string(),
+                suggestion: suggestion.to_string(),
+            });
+        }
+    }
+    corrections
+}
+
+/// Replaces every whole-word occurrence of the corrected words in `message`.
+pub fn fix(message: &str, corrections: &[Correction]) -> String {
+    let mut fixed = String::with_capacity(message.len());
+    let mut end = 0;
+    for (start, word) in words(message) {
+        if let Some(correction) = corrections.iter().find(|c| c.word == word) {
+            fixed.push_str(&message[end..start]);
+            fixed.push_str(&correction.suggestion);
+            end = start + word.len();
+        }
+    }
+    fixed.push_str(&message[end..]);
+    fixed
+}
+
+/// Splits `text` into words (letters, digits and underscores) with their byte offsets.
+fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let mut rest = text.char_indices().peekable();
+    std::iter::from_fn(move || {
+        let (start, _) = rest.by_ref().find(|(_, c)| is_word_char(*c))?;
+        let mut end = text.len();
+        while let Some(&(index, c)) = rest.peek() {
+            if !is_word_char(c) {
+                end = index;
+                break;
+            }
+            rest.next();
+        }
+        Some((start, &text[start..end]))
+    })
+}
+
+/// Whether `word` is written like an identifier rather than an ordinary word.
+fn is_identifier(word: &str) -> bool {
+    word.contains('_')
+        || word.chars().any(|c| c.is_ascii_digit())
+        || word.chars().skip(1).any(|c| c.is_uppercase())
+}
+
+/// Number of insertions, deletions, substitutions and adjacent swaps that turn
+/// `a` into `b` (optimal string alignment distance).
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/src/sync.rs b/src/sync.rs
+--- a/src/sync.rs
++++ b/src/sync.rs
+@@ -1,2 +1,3 @@
+-fn fetch_remote() {}
++fn fetch_remote_items() {}
++let client = CloudClient::new(retries);
+";
+
+    fn dictionary() -> Dictionary {
+        Dictionary::new(&["AsumCloud".to_string(), "GitHub".to_string()], DIFF)
+    }
+
+    #[test]
+    fn test_check_table_driven() {
+        let cases = vec![
+            ("feat: sync items to AsumCloud", vec![]),
+            (
+                "feat: sync items to AsumClod",
+                vec![("AsumClod", "AsumCloud")],
+            ),
+            ("docs: link to the Github page", vec![("Github", "GitHub")]),
+            (
+                "fix: rename fetch_remote_itmes",
+                vec![("fetch_remote_itmes", "fetch_remote_items")],
+            ),
+            (
+                "fix: use the CloudCleint",
+                vec![("CloudCleint", "CloudClient")],
+            ),
+            // Ordinary words near plain identifiers are not touched
+            ("fix: retry the client", vec![]),
+            // Case is only enforced for glossary terms
+            ("fix: rename Fetch_Remote", vec![]),
+        ];
+        for (message, expected) in cases {
+            let expected: Vec<Correction> = expected
+                .into_iter()
+                .map(|(word, suggestion)| Correction {
+                    word: word.to_string(),
+                    suggestion: suggestion.to_string(),
+                })
+                .collect();
+            assert_eq!(
+                check(message, &dictionary()),
+                expected,
+                "message: {}",
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn test_fix_replaces_whole_words() {
+        let message = "feat: sync AsumClod items\n\n- AsumClod now retries, unlike AsumClodPlus";
+        let corrections = check(message, &dictionary());
+        assert_eq!(
+            fix(message, &corrections),
+            "feat: sync AsumCloud items\n\n- AsumCloud now retries, unlike AsumClodPlus"
+        );
+    }
+
+    #[test]
+    fn test_edit_distance_table_driven() {
+        let cases = vec![
+            ("cloud", "cloud", 0),
+            ("clod", "cloud", 1),
+            ("cleint", "client", 1),
+            ("kitten", "sitting", 3),
+            ("", "abc", 3),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(edit_distance(a, b), expected, "{} -> {}", a, b);
+        }
+    }
+}
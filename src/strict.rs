@@ -0,0 +1,275 @@
+//! Strict commit message formatting for ASUM.
+//!
+//! Providers and models differ in how they format commit messages: capitalized
+//! descriptions, trailing periods, `*` bullets, prose bodies. With `strict_format`,
+//! providers that support structured output return the parts of the message as JSON
+//! and ASUM writes the message itself, and every message is linted against the same
+//! rules, with one retry when it breaks them. A team sharing a history gets the same
+//! style whichever provider each member uses.
+
+use crate::footers;
+use serde::Deserialize;
+use serde_json::json;
+
+/// Conventional Commits types a message may use.
+pub const TYPES: &[&str] = &[
+    "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert",
+];
+
+/// Longest subject line the lint accepts.
+pub const MAX_SUBJECT_LENGTH: usize = 72;
+
+/// Added to the system prompt of providers with structured output.
+const JSON_INSTRUCTION: &str = "\n\n[OUTPUT FORMAT]\nReply with a JSON object instead of the \
+    message: \"type\" (one of the types above), \"scope\" (empty when there is none), \
+    \"description\" (imperative mood, lowercase, no period), \"body\" (a list of short \
+    points, empty for small changes) and \"breaking_change\" (what breaks, empty when \
+    nothing does).";
+
+/// Added to the system prompt of other providers.
+const TEXT_INSTRUCTION: &str = "\n\n[OUTPUT FORMAT]\nFollow these rules exactly: the first line \
+    is `<type>(<scope>): <description>` or `<type>: <description>`, at most 72 characters, \
+    with a lowercase description and no period. If there is a body, leave one blank line \
+    after the subject and write every line of it as a \"- \" bullet point.";
+
+/// The parts of a commit message, as returned by structured output.
+#[derive(Debug, Deserialize)]
+struct Parts {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    scope: String,
+    description: String,
+    #[serde(default)]
+    body: Vec<String>,
+    #[serde(default)]
+    breaking_change: String,
+}
+
+/// JSON schema of the structured reply, for the providers' structured output.
+pub fn schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "type": { "type": "string", "enum": TYPES },
+            "scope": { "type": "string" },
+            "description": { "type": "string" },
+            "body": { "type": "array", "items": { "type": "string" } },
+            "breaking_change": { "type": "string" }
+        },
+        "required": ["type", "scope", "description", "body", "breaking_change"]
+    })
+}
+
+/// The format instruction added to the system prompt, depending on whether the
+/// provider returns structured output.
+pub fn instruction(structured: bool) -> &'static str {
+    if structured {
+        JSON_INSTRUCTION
+    } else {
+        TEXT_INSTRUCTION
+    }
+}
+
+/// Writes the commit message described by a structured `reply`, or returns `None`
+/// when the reply is not such a JSON object.
+pub fn render(reply: &str) -> Option<String> {
+    let reply = reply.trim();
+    let reply = reply
+        .strip_prefix("```json")
+        .and_then(|rest| rest.strip_suffix("```"))
+        .unwrap_or(reply);
+    let parts: Parts = serde_json::from_str(reply.trim()).ok()?;
+    let scope = parts.scope.trim();
+    let breaking = parts.breaking_change.trim();
+    let mut message = format!(
+        "{}{}{}: {}",
+        parts.kind.trim().to_lowercase(),
+        if scope.is_empty() {
+            String::new()
+        } else {
+            format!("({})", scope)
+        },
+        if breaking.is_empty() { "" } else { "!" },
+        sentence(&parts.description)
+    );
+    let bullets: Vec<String> = parts
+        .body
+        .iter()
+        .map(|point| sentence(point.trim().trim_start_matches(['-', '*']).trim()))
+        .filter(|point| !point.is_empty())
+        .map(|point| format!("- {}", point))
+        .collect();
+    if !bullets.is_empty() {
+        message.push_str(&format!("\n\n{}", bullets.join("\n")));
+    }
+    if !breaking.is_empty() {
+        message.push_str(&format!("\n\nBREAKING CHANGE: {}", breaking));
+    }
+    Some(message)
+}
+
+/// Trims `text`, drops a trailing period and lowercases the first letter unless the
+/// first word is an acronym such as `API`.
+fn sentence(text: &str) -> String {
+    let text = text.trim().trim_end_matches('.').trim_end();
+    let first_word = text.split_whitespace().next().unwrap_or_default();
+    let acronym = first_word.chars().filter(|c| c.is_alphabetic()).count() > 1
+        && !first_word.chars().any(|c| c.is_lowercase());
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) if !acronym => first.to_lowercase().chain(chars).collect(),
+        _ => text.to_string(),
+    }
+}
+
+/// Returns the rules `message` breaks, or nothing when it follows all of them.
+pub fn lint(message: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut lines = message.lines();
+    let subject = lines.next().unwrap_or_default();
+    match subject.split_once(": ") {
+        Some((prefix, description)) if is_header(prefix) => {
+            if description.ends_with('.') {
+                problems.push("The description ends with a period.".to_string());
+            }
+            if description != sentence(description) {
+                problems.push("The description starts with a capital letter.".to_string());
+            }
+        }
+        _ => problems.push(format!(
+            "The subject is not `<type>(<scope>): <description>` with a type from {}.",
+            TYPES.join(", ")
+        )),
+    }
+    let length = subject.chars().count();
+    if length > MAX_SUBJECT_LENGTH {
+        problems.push(format!(
+            "The subject is {} characters long; the limit is {}.",
+            length, MAX_SUBJECT_LENGTH
+        ));
+    }
+    match lines.next() {
+        Some(line) if !line.trim().is_empty() => {
+            problems.push("There is no blank line after the subject.".to_string());
+        }
+        _ => {}
+    }
+    if lines.any(|line| {
+        let line = line.trim_end();
+        !line.is_empty()
+            && !line.starts_with("- ")
+            && !line.starts_with("  ")
+            && !footers::is_trailer(line)
+    }) {
+        problems.push("The body has lines that are not \"- \" bullet points.".to_string());
+    }
+    problems
+}
+
+/// Whether `prefix`, the part of a subject before `: `, is `type`, `type(scope)`,
+/// optionally followed by `!`, with a known type.
+fn is_header(prefix: &str) -> bool {
+    let prefix = prefix.strip_suffix('!').unwrap_or(prefix);
+    let kind = match prefix.split_once('(') {
+        Some((kind, scope)) => match scope.strip_suffix(')') {
+            Some(scope) if !scope.is_empty() && !scope.contains(['(', ')']) => kind,
+            _ => return false,
+        },
+        None => prefix,
+    };
+    TYPES.contains(&kind)
+}
+
+/// Instruction for the retry of a message that broke the rules in `problems`.
+pub fn correction(problems: &[String]) -> String {
+    format!(
+        "\n\n[FORMAT ERRORS]\nA previous reply broke these formatting rules; follow them \
+         this time:\n{}",
+        problems
+            .iter()
+            .map(|problem| format!("- {}", problem))
+            .collect::<Vec<_>>()
+            .join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_table_driven() {
+        let cases = [
+            (
+                r#"{"type": "feat", "scope": "auth", "description": "Add OAuth login.",
+                    "body": ["Support Google", "- store tokens encrypted."], "breaking_change": ""}"#,
+                Some("feat(auth): add OAuth login\n\n- support Google\n- store tokens encrypted"),
+            ),
+            (
+                r#"{"type": "fix", "scope": "", "description": "API keys leak in logs",
+                    "body": [], "breaking_change": ""}"#,
+                Some("fix: API keys leak in logs"),
+            ),
+            (
+                "```json\n{\"type\": \"refactor\", \"scope\": \"api\", \"description\": \
+                 \"drop sync handlers\", \"body\": [], \"breaking_change\": \"sync calls are gone\"}\n```",
+                Some("refactor(api)!: drop sync handlers\n\nBREAKING CHANGE: sync calls are gone"),
+            ),
+            ("feat: add login", None),
+            (r#"{"scope": "auth"}"#, None),
+        ];
+        for (reply, expected) in cases {
+            assert_eq!(render(reply).as_deref(), expected, "{}", reply);
+        }
+    }
+
+    #[test]
+    fn test_lint_table_driven() {
+        let cases: [(&str, &[&str]); 8] = [
+            ("feat(auth): add login\n\n- add the form", &[]),
+            (
+                "fix!: drop v1\n\n- remove it\n\nBREAKING CHANGE: v1 is gone\nRefs: #4",
+                &[],
+            ),
+            ("Add login", &["The subject is not"]),
+            ("feature: add login", &["The subject is not"]),
+            ("feat(): add login", &["The subject is not"]),
+            (
+                "feat: Add login.",
+                &["ends with a period", "capital letter"],
+            ),
+            ("feat: add login\n- add the form", &["no blank line"]),
+            (
+                "feat: add login\n\nAdds the form.",
+                &["not \"- \" bullet points"],
+            ),
+        ];
+        for (message, expected) in cases {
+            let problems = lint(message);
+            assert_eq!(
+                problems.len(),
+                expected.len(),
+                "{}: {:?}",
+                message,
+                problems
+            );
+            for (problem, part) in problems.iter().zip(expected) {
+                assert!(problem.contains(part), "{}: {}", message, problem);
+            }
+        }
+
+        let long = format!("feat: {}", "a".repeat(70));
+        assert_eq!(
+            lint(&long),
+            vec!["The subject is 76 characters long; the limit is 72."]
+        );
+    }
+
+    #[test]
+    fn test_rendered_messages_pass_the_lint() {
+        let reply = r#"{"type": "perf", "scope": "db", "description": "Batch inserts.",
+                       "body": ["Group rows by table."], "breaking_change": ""}"#;
+        assert_eq!(lint(&render(reply).unwrap()), Vec::<String>::new());
+    }
+}
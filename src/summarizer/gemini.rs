@@ -3,7 +3,11 @@
 //! This module implements the `Summarizer` trait using Google's Gemini API
 //! to generate commit messages.
 
-use crate::summarizer::{AIConfig, Summarizer, generate_prompt};
+use crate::failure::Failure;
+use crate::summarizer::{
+    AIConfig, Completion, ModelInfo, Summarizer, TokenUsage, clean_reply, generate_prompt,
+    http_client,
+};
 use anyhow::Context;
 use async_trait::async_trait;
 use reqwest::Client;
@@ -24,7 +28,7 @@ impl GeminiProvider {
     pub fn new(config: AIConfig) -> Self {
         Self {
             config,
-            client: Client::new(),
+            client: http_client(),
             base_url: "https://generativelanguage.googleapis.com".to_string(),
         }
     }
@@ -33,7 +37,7 @@ impl GeminiProvider {
     pub fn new_with_url(config: AIConfig, url: String) -> Self {
         Self {
             config,
-            client: Client::new(),
+            client: http_client(),
             base_url: url,
         }
     }
@@ -42,13 +46,19 @@ impl GeminiProvider {
 #[async_trait]
 impl Summarizer for GeminiProvider {
     /// Generates a commit summary using the Gemini API.
-    /// Implements retry logic for rate limits and cleans the output message.
     async fn summarize(&self, diff: &str) -> anyhow::Result<String> {
+        Ok(self.complete(diff).await?.message)
+    }
+
+    /// Implements retry logic for rate limits, cleans the output message and reads
+    /// the token counts from `usageMetadata`.
+    async fn complete(&self, diff: &str) -> anyhow::Result<Completion> {
         let api_key = self
             .config
             .api_key
             .as_deref()
-            .context("Gemini API key is missing")?;
+            .context("Gemini API key is missing")
+            .map_err(|e| Failure::Config.tag(e))?;
 
         let prompt = generate_prompt(&self.config.user_prompt, diff);
 
@@ -62,6 +72,16 @@ impl Summarizer for GeminiProvider {
         let max_retries = 3;
         let mut backoff = 2;
 
+        let mut generation_config = json!({
+            "temperature": self.config.temperature,
+            "topP": self.config.top_p,
+            "maxOutputTokens": self.config.num_predict,
+        });
+        if let Some(schema) = &self.config.response_schema {
+            generation_config["responseMimeType"] = json!("application/json");
+            generation_config["responseJsonSchema"] = schema.clone();
+        }
+
         let response = loop {
             let res = self
                 .client
@@ -77,11 +97,7 @@ impl Summarizer for GeminiProvider {
                             "text": &prompt
                         }]
                     }],
-                    "generationConfig": {
-                        "temperature": self.config.temperature,
-                        "topP": self.config.top_p,
-                        "maxOutputTokens": self.config.num_predict,
-                    }
+                    "generationConfig": &generation_config
                 }))
                 .send()
                 .await?;
@@ -103,7 +119,10 @@ impl Summarizer for GeminiProvider {
                     .text()
                     .await
                     .unwrap_or_else(|_| "Unknown error".to_string());
-                anyhow::bail!("Gemini API returned error: {} - {}", status, error_text);
+                return Err(Failure::Provider.error(format!(
+                    "Gemini API returned error: {} - {}",
+                    status, error_text
+                )));
             }
 
             break res;
@@ -120,22 +139,92 @@ impl Summarizer for GeminiProvider {
 
         // Post-process the generated message to remove boilerplate text
         // that AI models sometimes include in their responses.
-        let final_msg = commit_msg
-            .lines()
-            .map(|l| l.trim())
-            .filter(|l| {
-                !l.is_empty()
-                    && !l.to_lowercase().contains("diff to analyze")
-                    && !l.to_lowercase().contains("input diff")
+        let final_msg = clean_reply(commit_msg)?;
+
+        let metadata = &res_json["usageMetadata"];
+        let usage = metadata.is_object().then(|| TokenUsage {
+            prompt_tokens: metadata["promptTokenCount"].as_u64().unwrap_or(0),
+            completion_tokens: metadata["candidatesTokenCount"].as_u64().unwrap_or(0),
+        });
+        Ok(Completion {
+            message: final_msg,
+            usage,
+        })
+    }
+
+    /// Lists the models that support `generateContent`.
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let api_key = self
+            .config
+            .api_key
+            .as_deref()
+            .context("Gemini API key is missing")
+            .map_err(|e| Failure::Config.tag(e))?;
+
+        let url = format!(
+            "{}/v1beta/models?pageSize=1000&key={}",
+            self.base_url, api_key
+        );
+        let response = self.client.get(&url).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(Failure::Provider.error(format!(
+                "Gemini API returned error: {} - {}",
+                status, error_text
+            )));
+        }
+
+        let res_json: serde_json::Value = response.json().await?;
+        Ok(res_json["models"]
+            .as_array()
+            .map(|models| {
+                models
+                    .iter()
+                    .filter(|m| {
+                        m["supportedGenerationMethods"]
+                            .as_array()
+                            .is_some_and(|methods| methods.iter().any(|v| v == "generateContent"))
+                    })
+                    .filter_map(|m| {
+                        let name = m["name"].as_str()?;
+                        Some(ModelInfo {
+                            name: name.strip_prefix("models/").unwrap_or(name).to_string(),
+                            size: None,
+                            details: m["displayName"].as_str().map(String::from),
+                        })
+                    })
+                    .collect()
             })
-            .collect::<Vec<_>>()
-            .join("\n");
+            .unwrap_or_default())
+    }
 
-        if final_msg.is_empty() {
-            anyhow::bail!("AI generated an empty or invalid message.");
+    /// Reads the `inputTokenLimit` of the configured model.
+    async fn context_window(&self) -> anyhow::Result<Option<usize>> {
+        let api_key = self
+            .config
+            .api_key
+            .as_deref()
+            .context("Gemini API key is missing")
+            .map_err(|e| Failure::Config.tag(e))?;
+
+        let url = format!(
+            "{}/v1beta/models/{}?key={}",
+            self.base_url, self.config.model, api_key
+        );
+        let response = self.client.get(&url).send().await?;
+        if !response.status().is_success() {
+            return Err(Failure::Provider
+                .error(format!("Gemini API returned error: {}", response.status())));
         }
 
-        Ok(final_msg)
+        let res_json: serde_json::Value = response.json().await?;
+        Ok(res_json["inputTokenLimit"]
+            .as_u64()
+            .and_then(|limit| usize::try_from(limit).ok()))
     }
 }
 
@@ -155,6 +244,7 @@ mod tests {
             api_key: Some("key".to_string()),
             system_prompt: "sys".to_string(),
             user_prompt: "user".to_string(),
+            response_schema: None,
         };
         let provider = GeminiProvider::new(ai_config);
         assert_eq!(provider.config.model, "gemini-pro");
@@ -163,16 +253,7 @@ mod tests {
     #[test]
     fn test_gemini_filtering() {
         let commit_msg = "fix: bug\n\nInput diff:\n...\nResult";
-        let final_msg = commit_msg
-            .lines()
-            .map(|l| l.trim())
-            .filter(|l| {
-                !l.is_empty()
-                    && !l.to_lowercase().contains("diff to analyze")
-                    && !l.to_lowercase().contains("input diff")
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
+        let final_msg = clean_reply(commit_msg).unwrap();
 
         assert!(final_msg.contains("fix: bug"));
         assert!(final_msg.contains("Result"));
@@ -190,6 +271,7 @@ mod tests {
             api_key: None,
             system_prompt: "sys".to_string(),
             user_prompt: "user".to_string(),
+            response_schema: None,
         };
         let provider = GeminiProvider::new(ai_config);
         let result = provider.summarize("diff").await;
@@ -215,7 +297,95 @@ mod tests {
                 .await
                 .unwrap();
 
-            let response = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"fix: gemini success\"}]}}]}";
+            let response = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"candidates\": [{\"content\": {\"parts\": [{\"text\": \"fix: gemini success\"}]}}], \"usageMetadata\": {\"promptTokenCount\": 200, \"candidatesTokenCount\": 12}}";
+            tokio::io::AsyncWriteExt::write_all(&mut socket, response.as_bytes())
+                .await
+                .unwrap();
+        });
+
+        let ai_config = AIConfig {
+            model: "gemini-pro".to_string(),
+            temperature: 0.7,
+            top_p: 1.0,
+            num_predict: 100,
+            api_url: None,
+            api_key: Some("test_key".to_string()),
+            system_prompt: "sys".to_string(),
+            user_prompt: "user".to_string(),
+            response_schema: None,
+        };
+        let provider = GeminiProvider::new_with_url(ai_config, url);
+        let result = provider.complete("diff").await.unwrap();
+        assert_eq!(result.message, "fix: gemini success");
+        assert_eq!(
+            result.usage,
+            Some(TokenUsage {
+                prompt_tokens: 200,
+                completion_tokens: 12
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_gemini_list_models() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let url = format!("http://{}", addr);
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 1024];
+            let _ = tokio::io::AsyncReadExt::read(&mut socket, &mut buf)
+                .await
+                .unwrap();
+
+            let response = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"models\": [{\"name\": \"models/gemini-2.0-flash\", \"displayName\": \"Gemini 2.0 Flash\", \"supportedGenerationMethods\": [\"generateContent\", \"countTokens\"]}, {\"name\": \"models/text-embedding-004\", \"supportedGenerationMethods\": [\"embedContent\"]}]}";
+            tokio::io::AsyncWriteExt::write_all(&mut socket, response.as_bytes())
+                .await
+                .unwrap();
+        });
+
+        let ai_config = AIConfig {
+            model: "gemini-pro".to_string(),
+            temperature: 0.7,
+            top_p: 1.0,
+            num_predict: 100,
+            api_url: None,
+            api_key: Some("test_key".to_string()),
+            system_prompt: "sys".to_string(),
+            user_prompt: "user".to_string(),
+            response_schema: None,
+        };
+        let provider = GeminiProvider::new_with_url(ai_config, url);
+        let models = provider.list_models().await.unwrap();
+        assert_eq!(
+            models,
+            vec![ModelInfo {
+                name: "gemini-2.0-flash".to_string(),
+                size: None,
+                details: Some("Gemini 2.0 Flash".to_string()),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_gemini_context_window() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let url = format!("http://{}", addr);
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 1024];
+            let n = tokio::io::AsyncReadExt::read(&mut socket, &mut buf)
+                .await
+                .unwrap();
+            assert!(
+                String::from_utf8_lossy(&buf[..n])
+                    .starts_with("GET /v1beta/models/gemini-pro?key=test_key ")
+            );
+
+            let response = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"name\": \"models/gemini-pro\", \"inputTokenLimit\": 30720, \"outputTokenLimit\": 2048}";
             tokio::io::AsyncWriteExt::write_all(&mut socket, response.as_bytes())
                 .await
                 .unwrap();
@@ -230,9 +400,9 @@ mod tests {
             api_key: Some("test_key".to_string()),
             system_prompt: "sys".to_string(),
             user_prompt: "user".to_string(),
+            response_schema: None,
         };
         let provider = GeminiProvider::new_with_url(ai_config, url);
-        let result = provider.summarize("diff").await.unwrap();
-        assert_eq!(result, "fix: gemini success");
+        assert_eq!(provider.context_window().await.unwrap(), Some(30720));
     }
 }
@@ -3,16 +3,31 @@
 //! This module defines the summarization interface and factory logic
 //! for various AI providers like Gemini and Ollama.
 
+#[cfg(feature = "gemini")]
 pub mod gemini;
+#[cfg(feature = "ollama")]
 pub mod ollama;
+pub mod pricing;
 
 use crate::config::AsumConfig;
+use crate::failure::Failure;
+use crate::strict;
 use async_trait::async_trait;
-use tracing::info;
+use tracing::{info, warn};
+
+/// How long to wait for a connection to the provider.
+#[cfg(any(feature = "gemini", feature = "ollama"))]
+const CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// How long to wait for a whole provider request. Local models on a CPU can take
+/// minutes for a long diff.
+#[cfg(any(feature = "gemini", feature = "ollama"))]
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
 
 /// Configuration specifically for the AI model execution.
 /// This is derived from the main `AsumConfig` but tailored for the providers.
 #[derive(Debug, Clone)]
+#[cfg_attr(not(all(feature = "gemini", feature = "ollama")), allow(dead_code))]
 pub struct AIConfig {
     pub model: String,
     pub temperature: f64,
@@ -22,6 +37,34 @@ pub struct AIConfig {
     pub api_key: Option<String>,
     pub system_prompt: String,
     pub user_prompt: String,
+    /// JSON schema the reply must follow, for providers with structured output.
+    pub response_schema: Option<serde_json::Value>,
+}
+
+/// A model offered by a provider, as reported by its API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelInfo {
+    /// Name to use in the configuration.
+    pub name: String,
+    /// Download size in bytes, when the provider reports it.
+    pub size: Option<u64>,
+    /// Short detail such as the parameter count ("8.0B") or a display name.
+    pub details: Option<String>,
+}
+
+/// Tokens used by one request, as reported by the provider.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+/// A generated message and the tokens it took.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub message: String,
+    /// `None` when the provider did not report token counts.
+    pub usage: Option<TokenUsage>,
 }
 
 /// Trait defining the behavior of an AI commit summarizer.
@@ -31,6 +74,68 @@ pub struct AIConfig {
 pub trait Summarizer: Send + Sync {
     /// Takes a git diff and returns a generated commit message.
     async fn summarize(&self, diff: &str) -> anyhow::Result<String>;
+
+    /// Like `summarize`, but also returns the token counts reported by the provider.
+    async fn complete(&self, diff: &str) -> anyhow::Result<Completion> {
+        Ok(Completion {
+            message: self.summarize(diff).await?,
+            usage: None,
+        })
+    }
+
+    /// Lists the models the provider currently offers.
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        anyhow::bail!("This provider cannot list its models")
+    }
+
+    /// Returns the number of input tokens the configured model accepts, or `None`
+    /// when the provider does not report it.
+    async fn context_window(&self) -> anyhow::Result<Option<usize>> {
+        Ok(None)
+    }
+}
+
+/// Constructor shared by every provider in the registry.
+type ProviderFactory = fn(AIConfig) -> Box<dyn Summarizer>;
+
+/// Registry of the providers compiled into this binary, keyed by the name used
+/// in `active_provider`. Each provider is behind a Cargo feature of the same name.
+const PROVIDERS: &[(&str, ProviderFactory)] = &[
+    #[cfg(feature = "gemini")]
+    ("gemini", |config| {
+        Box::new(gemini::GeminiProvider::new(config))
+    }),
+    #[cfg(feature = "ollama")]
+    ("ollama", |config| {
+        Box::new(ollama::OllamaProvider::new(config))
+    }),
+];
+
+/// Every provider ASUM knows about, whether or not it is compiled in.
+pub const KNOWN_PROVIDERS: &[&str] = &["gemini", "ollama"];
+
+/// Providers that can be held to a JSON schema (structured output).
+pub const STRUCTURED_OUTPUT_PROVIDERS: &[&str] = &["gemini", "ollama"];
+
+/// Whether `provider` returns structured output, so `strict_format` can ask it for the
+/// parts of a commit message as JSON.
+pub fn supports_structured_output(provider: &str) -> bool {
+    STRUCTURED_OUTPUT_PROVIDERS.contains(&provider)
+}
+
+/// HTTP client shared by the providers, with the connection and request timeouts.
+#[cfg(any(feature = "gemini", feature = "ollama"))]
+pub fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+}
+
+/// Names of the AI providers compiled into this binary.
+pub fn available_providers() -> Vec<&'static str> {
+    PROVIDERS.iter().map(|(name, _)| *name).collect()
 }
 
 /// Factory function that returns a concrete implementation of a `Summarizer`
@@ -53,6 +158,10 @@ pub async fn get_summarizer(config: AsumConfig) -> anyhow::Result<Box<dyn Summar
         api_key: config.gemini_api_key.clone(),
         system_prompt: config.system_prompt.clone(),
         user_prompt: config.user_prompt.clone(),
+        response_schema: (config.strict_format
+            && config.commit_message
+            && supports_structured_output(&provider))
+        .then(strict::schema),
     };
 
     info!("Using provider: {}", provider);
@@ -66,15 +175,48 @@ pub async fn get_summarizer(config: AsumConfig) -> anyhow::Result<Box<dyn Summar
         info!("Using API key: {}", masked_key);
     }
 
-    match provider.as_str() {
-        "ollama" => Ok(Box::new(ollama::OllamaProvider::new(ai_config)) as Box<dyn Summarizer>),
-        "gemini" => Ok(Box::new(gemini::GeminiProvider::new(ai_config)) as Box<dyn Summarizer>),
-        _ => Err(anyhow::anyhow!("Unknown provider: {}", provider)),
+    match PROVIDERS.iter().find(|(name, _)| *name == provider) {
+        Some((_, create)) => Ok(create(ai_config)),
+        None if KNOWN_PROVIDERS.contains(&provider.as_str()) => Err(Failure::Config.error(format!(
+            "Provider '{}' is not available in this build (compiled providers: {}). Rebuild with `--features {}`.",
+            provider,
+            available_providers().join(", "),
+            provider
+        ))),
+        None => Err(Failure::Config.error(format!("Unknown provider: {}", provider))),
     }
 }
 
+/// Removes the boilerplate models sometimes add to a reply: blank lines and lines
+/// echoing the diff instructions. When that would leave nothing, the raw reply is
+/// returned with a warning rather than lost to the filter; only an empty reply fails
+/// with `Failure::InvalidOutput`.
+#[cfg_attr(not(any(feature = "gemini", feature = "ollama")), allow(dead_code))]
+pub fn clean_reply(reply: &str) -> anyhow::Result<String> {
+    let reply = reply.trim();
+    if reply.is_empty() {
+        return Err(Failure::InvalidOutput.error("AI generated an empty or invalid message."));
+    }
+    let cleaned = reply
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| {
+            !l.is_empty()
+                && !l.to_lowercase().contains("diff to analyze")
+                && !l.to_lowercase().contains("input diff")
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if cleaned.is_empty() {
+        warn!("Post-processing removed every line of the reply; using the raw reply instead.");
+        return Ok(reply.to_string());
+    }
+    Ok(cleaned)
+}
+
 /// Injects the git diff into the provided prompt template.
 /// Replaces the `{{diff}}` placeholder with the actual diff content.
+#[cfg_attr(not(any(feature = "gemini", feature = "ollama")), allow(dead_code))]
 pub fn generate_prompt(prompt_template: &str, diff: &str) -> String {
     prompt_template.replace("{{diff}}", diff)
 }
@@ -83,6 +225,37 @@ pub fn generate_prompt(prompt_template: &str, diff: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_clean_reply_table_driven() {
+        let cases = vec![
+            ("plain", "feat: add x", Some("feat: add x")),
+            (
+                "boilerplate removed",
+                "feat: add x\n\nInput diff to analyze:\n- detail",
+                Some("feat: add x\n- detail"),
+            ),
+            (
+                "every line filtered keeps the raw reply",
+                " Input diff: updated parser \n",
+                Some("Input diff: updated parser"),
+            ),
+            ("empty", " \n ", None),
+        ];
+
+        for (name, reply, expected) in cases {
+            let result = clean_reply(reply);
+            match expected {
+                Some(message) => assert_eq!(result.unwrap(), message, "{}", name),
+                None => assert_eq!(
+                    crate::failure::failure_of(&result.unwrap_err()),
+                    Some(Failure::InvalidOutput),
+                    "{}",
+                    name
+                ),
+            }
+        }
+    }
+
     #[test]
     fn test_generate_prompt_table_driven() {
         struct TestCase {
@@ -154,17 +327,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_available_providers_match_features() {
+        let providers = available_providers();
+        assert_eq!(providers.contains(&"gemini"), cfg!(feature = "gemini"));
+        assert_eq!(providers.contains(&"ollama"), cfg!(feature = "ollama"));
+        for provider in providers {
+            assert!(KNOWN_PROVIDERS.contains(&provider));
+        }
+    }
+
+    #[cfg(feature = "ollama")]
     #[tokio::test]
     async fn test_get_summarizer_ollama() {
         let config = AsumConfig {
             active_provider: "ollama".to_string(),
             max_diff_length: 1000,
             git_extensions: vec![],
+            exclude_patterns: vec![],
+            requests_per_minute: None,
+            anonymize: false,
+            secrets: "off".to_string(),
+            repo_profile: "auto".to_string(),
+            vulnerability_lookup: false,
+            strict_format: false,
+            max_body_bullets: 4,
+            recent_commits: 10,
+            glossary: Vec::new(),
+            spell_check: "fix".to_string(),
+            untracked_files: "off".to_string(),
+            large_diffs: "truncate".to_string(),
+            candidate_bodies: "all".to_string(),
+            clipboard: "auto".to_string(),
+            noise_filters: Vec::new(),
+            stat_header: false,
+            private_paths: Vec::new(),
+            max_file_diff_length: 0,
+            binary_files: false,
+            issue_footers: Vec::new(),
+            scopes: Vec::new(),
+            infer_scope: false,
+            scope_paths: Vec::new(),
+            context_window: None,
+            context_window_auto: false,
+            truncated_user_prompt: None,
+            message_language: None,
+            preflight_report: false,
+            confirm_send: false,
+            dry_run: false,
+            no_cache: false,
+            commit_message: false,
             system_prompt: "sys".to_string(),
             user_prompt: "user".to_string(),
             ai_temperature: 0.7,
             ai_top_p: 1.0,
             ai_num_predict: 100,
+            ai_num_predict_auto: false,
             ollama_url: Some("http://localhost:11434".to_string()),
             ollama_model: Some("llama3".to_string()),
             gemini_api_key: None,
@@ -179,17 +397,52 @@ mod tests {
         assert!(summarizer.summarize("test").await.is_err()); // Should error because no server is running
     }
 
+    #[cfg(feature = "gemini")]
     #[tokio::test]
     async fn test_get_summarizer_gemini() {
         let config = AsumConfig {
             active_provider: "gemini".to_string(),
             max_diff_length: 1000,
             git_extensions: vec![],
+            exclude_patterns: vec![],
+            requests_per_minute: None,
+            anonymize: false,
+            secrets: "off".to_string(),
+            repo_profile: "auto".to_string(),
+            vulnerability_lookup: false,
+            strict_format: false,
+            max_body_bullets: 4,
+            recent_commits: 10,
+            glossary: Vec::new(),
+            spell_check: "fix".to_string(),
+            untracked_files: "off".to_string(),
+            large_diffs: "truncate".to_string(),
+            candidate_bodies: "all".to_string(),
+            clipboard: "auto".to_string(),
+            noise_filters: Vec::new(),
+            stat_header: false,
+            private_paths: Vec::new(),
+            max_file_diff_length: 0,
+            binary_files: false,
+            issue_footers: Vec::new(),
+            scopes: Vec::new(),
+            infer_scope: false,
+            scope_paths: Vec::new(),
+            context_window: None,
+            context_window_auto: false,
+            truncated_user_prompt: None,
+            message_language: None,
+            preflight_report: false,
+            confirm_send: false,
+            dry_run: false,
+            no_cache: false,
+            commit_message: false,
             system_prompt: "sys".to_string(),
             user_prompt: "user".to_string(),
             ai_temperature: 0.7,
             ai_top_p: 1.0,
             ai_num_predict: 100,
+            ai_num_predict_auto: false,
             ollama_url: None,
             ollama_model: None,
             gemini_api_key: Some("test_key".to_string()),
@@ -202,17 +455,52 @@ mod tests {
         assert!(summarizer.summarize("test").await.is_err());
     }
 
+    #[cfg(feature = "gemini")]
     #[tokio::test]
     async fn test_get_summarizer_gemini_long_key() {
         let config = AsumConfig {
             active_provider: "gemini".to_string(),
             max_diff_length: 1000,
             git_extensions: vec![],
+            exclude_patterns: vec![],
+            requests_per_minute: None,
+            anonymize: false,
+            secrets: "off".to_string(),
+            repo_profile: "auto".to_string(),
+            vulnerability_lookup: false,
+            strict_format: false,
+            max_body_bullets: 4,
+            recent_commits: 10,
+            glossary: Vec::new(),
+            spell_check: "fix".to_string(),
+            untracked_files: "off".to_string(),
+            large_diffs: "truncate".to_string(),
+            candidate_bodies: "all".to_string(),
+            clipboard: "auto".to_string(),
+            noise_filters: Vec::new(),
+            stat_header: false,
+            private_paths: Vec::new(),
+            max_file_diff_length: 0,
+            binary_files: false,
+            issue_footers: Vec::new(),
+            scopes: Vec::new(),
+            infer_scope: false,
+            scope_paths: Vec::new(),
+            context_window: None,
+            context_window_auto: false,
+            truncated_user_prompt: None,
+            message_language: None,
+            preflight_report: false,
+            confirm_send: false,
+            dry_run: false,
+            no_cache: false,
+            commit_message: false,
             system_prompt: "sys".to_string(),
             user_prompt: "user".to_string(),
             ai_temperature: 0.7,
             ai_top_p: 1.0,
             ai_num_predict: 100,
+            ai_num_predict_auto: false,
             ollama_url: None,
             ollama_model: None,
             gemini_api_key: Some("very_long_api_key_for_testing".to_string()),
@@ -223,17 +511,108 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[cfg(not(feature = "gemini"))]
+    #[tokio::test]
+    async fn test_get_summarizer_disabled_provider() {
+        let config = AsumConfig {
+            active_provider: "gemini".to_string(),
+            max_diff_length: 1000,
+            git_extensions: vec![],
+            exclude_patterns: vec![],
+            requests_per_minute: None,
+            anonymize: false,
+            secrets: "off".to_string(),
+            repo_profile: "auto".to_string(),
+            vulnerability_lookup: false,
+            strict_format: false,
+            max_body_bullets: 4,
+            recent_commits: 10,
+            glossary: Vec::new(),
+            spell_check: "fix".to_string(),
+            untracked_files: "off".to_string(),
+            large_diffs: "truncate".to_string(),
+            candidate_bodies: "all".to_string(),
+            clipboard: "auto".to_string(),
+            noise_filters: Vec::new(),
+            stat_header: false,
+            private_paths: Vec::new(),
+            max_file_diff_length: 0,
+            binary_files: false,
+            issue_footers: Vec::new(),
+            scopes: Vec::new(),
+            infer_scope: false,
+            scope_paths: Vec::new(),
+            context_window: None,
+            context_window_auto: false,
+            truncated_user_prompt: None,
+            message_language: None,
+            preflight_report: false,
+            confirm_send: false,
+            dry_run: false,
+            no_cache: false,
+            commit_message: false,
+            system_prompt: "sys".to_string(),
+            user_prompt: "user".to_string(),
+            ai_temperature: 0.7,
+            ai_top_p: 1.0,
+            ai_num_predict: 100,
+            ai_num_predict_auto: false,
+            ollama_url: None,
+            ollama_model: None,
+            gemini_api_key: None,
+            gemini_model: None,
+        };
+
+        let result = get_summarizer(config).await;
+        let message = result.err().unwrap().to_string();
+        assert!(message.contains("not available in this build"));
+    }
+
     #[tokio::test]
     async fn test_get_summarizer_unknown() {
         let config = AsumConfig {
             active_provider: "unknown".to_string(),
             max_diff_length: 1000,
             git_extensions: vec![],
+            exclude_patterns: vec![],
+            requests_per_minute: None,
+            anonymize: false,
+            secrets: "off".to_string(),
+            repo_profile: "auto".to_string(),
+            vulnerability_lookup: false,
+            strict_format: false,
+            max_body_bullets: 4,
+            recent_commits: 10,
+            glossary: Vec::new(),
+            spell_check: "fix".to_string(),
+            untracked_files: "off".to_string(),
+            large_diffs: "truncate".to_string(),
+            candidate_bodies: "all".to_string(),
+            clipboard: "auto".to_string(),
+            noise_filters: Vec::new(),
+            stat_header: false,
+            private_paths: Vec::new(),
+            max_file_diff_length: 0,
+            binary_files: false,
+            issue_footers: Vec::new(),
+            scopes: Vec::new(),
+            infer_scope: false,
+            scope_paths: Vec::new(),
+            context_window: None,
+            context_window_auto: false,
+            truncated_user_prompt: None,
+            message_language: None,
+            preflight_report: false,
+            confirm_send: false,
+            dry_run: false,
+            no_cache: false,
+            commit_message: false,
             system_prompt: "sys".to_string(),
             user_prompt: "user".to_string(),
             ai_temperature: 0.7,
             ai_top_p: 1.0,
             ai_num_predict: 100,
+            ai_num_predict_auto: false,
             ollama_url: None,
             ollama_model: None,
             gemini_api_key: None,
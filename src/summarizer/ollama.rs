@@ -3,7 +3,11 @@
 //! This module implements the `Summarizer` trait using the Ollama API
 //! (local or remote) to generate commit messages.
 
-use crate::summarizer::{AIConfig, Summarizer, generate_prompt};
+use crate::failure::Failure;
+use crate::summarizer::{
+    AIConfig, Completion, ModelInfo, Summarizer, TokenUsage, clean_reply, generate_prompt,
+    http_client,
+};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde_json::json;
@@ -19,16 +23,44 @@ impl OllamaProvider {
     pub fn new(config: AIConfig) -> Self {
         Self {
             config,
-            client: Client::new(),
+            client: http_client(),
         }
     }
+
+    /// Returns the `/api/tags` endpoint on the same server as the configured chat URL.
+    fn tags_url(&self) -> String {
+        format!("{}/api/tags", self.server_url())
+    }
+
+    /// Returns the `/api/show` endpoint on the same server as the configured chat URL.
+    fn show_url(&self) -> String {
+        format!("{}/api/show", self.server_url())
+    }
+
+    /// Returns the configured chat URL without its `/api/chat` or `/api/generate` path.
+    fn server_url(&self) -> &str {
+        let url = self
+            .config
+            .api_url
+            .as_deref()
+            .unwrap_or("http://localhost:11434/api/chat")
+            .trim_end_matches('/');
+        url.strip_suffix("/api/chat")
+            .or_else(|| url.strip_suffix("/api/generate"))
+            .unwrap_or(url)
+    }
 }
 
 #[async_trait]
 impl Summarizer for OllamaProvider {
     /// Generates a commit summary using the Ollama API.
-    /// Sends the system prompt and the diff to the configured model.
     async fn summarize(&self, diff: &str) -> anyhow::Result<String> {
+        Ok(self.complete(diff).await?.message)
+    }
+
+    /// Sends the system prompt and the diff to the configured model, and reads the
+    /// token counts from `prompt_eval_count` and `eval_count`.
+    async fn complete(&self, diff: &str) -> anyhow::Result<Completion> {
         let prompt = generate_prompt(&self.config.user_prompt, diff);
 
         // Determine the Ollama API endpoint, defaulting to localhost
@@ -41,7 +73,7 @@ impl Summarizer for OllamaProvider {
         let is_generate_api = url.ends_with("/api/generate");
 
         // Prepare the request payload based on the API endpoint
-        let payload = if is_generate_api {
+        let mut payload = if is_generate_api {
             json!({
                 "model": self.config.model,
                 "prompt": format!("{}\n\n{}", self.config.system_prompt, prompt),
@@ -73,12 +105,16 @@ impl Summarizer for OllamaProvider {
                 }
             })
         };
+        if let Some(schema) = &self.config.response_schema {
+            payload["format"] = schema.clone();
+        }
 
         // Send the request to the Ollama model
         let response = self.client.post(url).json(&payload).send().await?;
 
         if !response.status().is_success() {
-            anyhow::bail!("Ollama API returned error: {}", response.status());
+            return Err(Failure::Provider
+                .error(format!("Ollama API returned error: {}", response.status())));
         }
 
         // Parse the JSON response from Ollama
@@ -93,23 +129,73 @@ impl Summarizer for OllamaProvider {
 
         // Post-process the generated message to remove boilerplate text
         // that AI models sometimes include in their responses.
-        let final_msg = commit_msg
-            .lines()
-            .map(|l| l.trim())
-            .filter(|l| {
-                // Remove empty lines and lines that echo the input diff instructions
-                !l.is_empty()
-                    && !l.to_lowercase().contains("diff to analyze")
-                    && !l.to_lowercase().contains("input diff")
+        let final_msg = clean_reply(commit_msg)?;
+
+        let usage = match (
+            res_json["prompt_eval_count"].as_u64(),
+            res_json["eval_count"].as_u64(),
+        ) {
+            (None, None) => None,
+            (prompt, completion) => Some(TokenUsage {
+                prompt_tokens: prompt.unwrap_or(0),
+                completion_tokens: completion.unwrap_or(0),
+            }),
+        };
+        Ok(Completion {
+            message: final_msg,
+            usage,
+        })
+    }
+
+    /// Lists the locally installed models via `/api/tags`.
+    async fn list_models(&self) -> anyhow::Result<Vec<ModelInfo>> {
+        let response = self.client.get(self.tags_url()).send().await?;
+        if !response.status().is_success() {
+            return Err(Failure::Provider
+                .error(format!("Ollama API returned error: {}", response.status())));
+        }
+
+        let res_json: serde_json::Value = response.json().await?;
+        Ok(res_json["models"]
+            .as_array()
+            .map(|models| {
+                models
+                    .iter()
+                    .filter_map(|m| {
+                        Some(ModelInfo {
+                            name: m["name"].as_str()?.to_string(),
+                            size: m["size"].as_u64(),
+                            details: m["details"]["parameter_size"].as_str().map(String::from),
+                        })
+                    })
+                    .collect()
             })
-            .collect::<Vec<_>>()
-            .join("\n");
+            .unwrap_or_default())
+    }
 
-        if final_msg.is_empty() {
-            anyhow::bail!("AI generated an empty or invalid message.");
+    /// Reads the `num_ctx` parameter of the configured model. Models that do not set
+    /// it run with the server's default context length, which the API does not report.
+    async fn context_window(&self) -> anyhow::Result<Option<usize>> {
+        let response = self
+            .client
+            .post(self.show_url())
+            .json(&json!({ "model": self.config.model }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(Failure::Provider
+                .error(format!("Ollama API returned error: {}", response.status())));
         }
 
-        Ok(final_msg)
+        let res_json: serde_json::Value = response.json().await?;
+        Ok(res_json["parameters"].as_str().and_then(|parameters| {
+            parameters.lines().find_map(|line| {
+                let mut words = line.split_whitespace();
+                (words.next() == Some("num_ctx"))
+                    .then(|| words.next()?.parse().ok())
+                    .flatten()
+            })
+        }))
     }
 }
 
@@ -129,6 +215,7 @@ mod tests {
             api_key: None,
             system_prompt: "sys".to_string(),
             user_prompt: "user".to_string(),
+            response_schema: None,
         };
         let provider = OllamaProvider::new(ai_config);
         assert_eq!(provider.config.model, "llama3");
@@ -137,16 +224,7 @@ mod tests {
     #[test]
     fn test_ollama_filtering() {
         let commit_msg = "feat: add feature\n\nInput diff to analyze:\nSome diff\nActual message";
-        let final_msg = commit_msg
-            .lines()
-            .map(|l| l.trim())
-            .filter(|l| {
-                !l.is_empty()
-                    && !l.to_lowercase().contains("diff to analyze")
-                    && !l.to_lowercase().contains("input diff")
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
+        let final_msg = clean_reply(commit_msg).unwrap();
 
         assert!(final_msg.contains("feat: add feature"));
         assert!(final_msg.contains("Actual message"));
@@ -164,6 +242,7 @@ mod tests {
             api_key: None,
             system_prompt: "sys".to_string(),
             user_prompt: "user".to_string(),
+            response_schema: None,
         };
         let provider = OllamaProvider::new(ai_config);
         let result = provider.summarize("diff").await;
@@ -198,6 +277,7 @@ mod tests {
             api_key: None,
             system_prompt: "sys".to_string(),
             user_prompt: "user".to_string(),
+            response_schema: None,
         };
         let provider = OllamaProvider::new(ai_config);
         let result = provider.summarize("diff").await.unwrap();
@@ -218,7 +298,7 @@ mod tests {
                 .unwrap();
 
             // Ollama /api/generate returns "response" field
-            let response = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"response\": \"feat: success from generate\"}";
+            let response = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"response\": \"feat: success from generate\", \"prompt_eval_count\": 120, \"eval_count\": 15}";
             tokio::io::AsyncWriteExt::write_all(&mut socket, response.as_bytes())
                 .await
                 .unwrap();
@@ -233,9 +313,131 @@ mod tests {
             api_key: None,
             system_prompt: "sys".to_string(),
             user_prompt: "user".to_string(),
+            response_schema: None,
         };
         let provider = OllamaProvider::new(ai_config);
-        let result = provider.summarize("diff").await.unwrap();
-        assert_eq!(result, "feat: success from generate");
+        let result = provider.complete("diff").await.unwrap();
+        assert_eq!(result.message, "feat: success from generate");
+        assert_eq!(
+            result.usage,
+            Some(TokenUsage {
+                prompt_tokens: 120,
+                completion_tokens: 15
+            })
+        );
+    }
+
+    #[test]
+    fn test_tags_url_table_driven() {
+        let cases = vec![
+            (None, "http://localhost:11434/api/tags"),
+            (
+                Some("http://host:11434/api/chat"),
+                "http://host:11434/api/tags",
+            ),
+            (
+                Some("http://host:11434/api/generate"),
+                "http://host:11434/api/tags",
+            ),
+            (Some("http://host:11434/"), "http://host:11434/api/tags"),
+        ];
+
+        for (api_url, expected) in cases {
+            let provider = OllamaProvider::new(AIConfig {
+                model: "llama3".to_string(),
+                temperature: 0.7,
+                top_p: 1.0,
+                num_predict: 100,
+                api_url: api_url.map(String::from),
+                api_key: None,
+                system_prompt: "sys".to_string(),
+                user_prompt: "user".to_string(),
+                response_schema: None,
+            });
+            assert_eq!(provider.tags_url(), expected, "api_url: {:?}", api_url);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ollama_list_models() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let url = format!("http://{}/api/chat", addr);
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 1024];
+            let n = tokio::io::AsyncReadExt::read(&mut socket, &mut buf)
+                .await
+                .unwrap();
+            assert!(String::from_utf8_lossy(&buf[..n]).starts_with("GET /api/tags "));
+
+            let response = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"models\": [{\"name\": \"llama3:latest\", \"size\": 4661224676, \"details\": {\"parameter_size\": \"8.0B\"}}, {\"name\": \"tiny\"}]}";
+            tokio::io::AsyncWriteExt::write_all(&mut socket, response.as_bytes())
+                .await
+                .unwrap();
+        });
+
+        let provider = OllamaProvider::new(AIConfig {
+            model: "llama3".to_string(),
+            temperature: 0.7,
+            top_p: 1.0,
+            num_predict: 100,
+            api_url: Some(url),
+            api_key: None,
+            system_prompt: "sys".to_string(),
+            user_prompt: "user".to_string(),
+            response_schema: None,
+        });
+        let models = provider.list_models().await.unwrap();
+        assert_eq!(
+            models,
+            vec![
+                ModelInfo {
+                    name: "llama3:latest".to_string(),
+                    size: Some(4661224676),
+                    details: Some("8.0B".to_string()),
+                },
+                ModelInfo {
+                    name: "tiny".to_string(),
+                    size: None,
+                    details: None,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ollama_context_window() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let url = format!("http://{}/api/chat", addr);
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 1024];
+            let n = tokio::io::AsyncReadExt::read(&mut socket, &mut buf)
+                .await
+                .unwrap();
+            assert!(String::from_utf8_lossy(&buf[..n]).starts_with("POST /api/show "));
+
+            let response = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"parameters\": \"stop                           \\\"<|im_end|>\\\"\\nnum_ctx                        8192\", \"model_info\": {\"qwen2.context_length\": 32768}}";
+            tokio::io::AsyncWriteExt::write_all(&mut socket, response.as_bytes())
+                .await
+                .unwrap();
+        });
+
+        let provider = OllamaProvider::new(AIConfig {
+            model: "qwen2.5-coder:3b".to_string(),
+            temperature: 0.7,
+            top_p: 1.0,
+            num_predict: 100,
+            api_url: Some(url),
+            api_key: None,
+            system_prompt: "sys".to_string(),
+            user_prompt: "user".to_string(),
+            response_schema: None,
+        });
+        assert_eq!(provider.context_window().await.unwrap(), Some(8192));
     }
 }
@@ -0,0 +1,66 @@
+//! Cost estimates for provider requests.
+//!
+//! Prices are list prices in US dollars per million tokens and only meant for
+//! rough usage reports; local providers cost nothing.
+
+use crate::summarizer::TokenUsage;
+
+/// `(model prefix, input price, output price)` per million tokens for Gemini.
+/// More specific prefixes come first.
+const GEMINI_PRICES: &[(&str, f64, f64)] = &[
+    ("gemini-2.5-flash-lite", 0.10, 0.40),
+    ("gemini-2.5-flash", 0.30, 2.50),
+    ("gemini-2.5-pro", 1.25, 10.00),
+    ("gemini-2.0-flash-lite", 0.075, 0.30),
+    ("gemini-2.0-flash", 0.10, 0.40),
+    ("gemini-1.5-flash-8b", 0.0375, 0.15),
+    ("gemini-1.5-flash", 0.075, 0.30),
+    ("gemini-1.5-pro", 1.25, 5.00),
+];
+
+/// Estimated cost in US dollars of a request, or `None` when the model's price
+/// is unknown.
+pub fn estimate_cost(provider: &str, model: &str, usage: TokenUsage) -> Option<f64> {
+    let (input, output) = match provider {
+        "ollama" => (0.0, 0.0),
+        "gemini" => GEMINI_PRICES
+            .iter()
+            .find(|(prefix, _, _)| model.starts_with(prefix))
+            .map(|(_, input, output)| (*input, *output))?,
+        _ => return None,
+    };
+    Some(
+        (usage.prompt_tokens as f64 * input + usage.completion_tokens as f64 * output)
+            / 1_000_000.0,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_cost_table_driven() {
+        let usage = TokenUsage {
+            prompt_tokens: 1_000_000,
+            completion_tokens: 100_000,
+        };
+        let cases = vec![
+            ("ollama", "llama3", Some(0.0)),
+            ("gemini", "gemini-2.0-flash", Some(0.14)),
+            ("gemini", "gemini-2.0-flash-lite-001", Some(0.105)),
+            ("gemini", "gemini-1.5-pro-latest", Some(1.75)),
+            ("gemini", "gemini-experimental", None),
+            ("other", "model", None),
+        ];
+        for (provider, model, expected) in cases {
+            let cost = estimate_cost(provider, model, usage);
+            match (cost, expected) {
+                (Some(cost), Some(expected)) => {
+                    assert!((cost - expected).abs() < 1e-9, "{}: {}", model, cost)
+                }
+                _ => assert_eq!(cost, expected, "model: {}", model),
+            }
+        }
+    }
+}
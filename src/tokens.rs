@@ -0,0 +1,95 @@
+//! Token counting for ASUM.
+//!
+//! Prompts are measured in tokens to fit them into the model's context window and
+//! for the `--dry-run` and pre-flight reports. With the `tokenizer` feature (on by
+//! default) text is encoded with the cl100k_base BPE tokenizer. Gemini and the Ollama
+//! models have vocabularies of their own, so the counts are close rather than exact.
+//! Without the feature, tokens are estimated from the number of characters.
+
+/// How tokens are counted, for the reports.
+#[cfg(feature = "tokenizer")]
+pub const METHOD: &str = "cl100k_base tokenizer";
+#[cfg(not(feature = "tokenizer"))]
+pub const METHOD: &str = "4 characters per token";
+
+/// Upper bound on the bytes in one cl100k_base token.
+#[cfg(feature = "tokenizer")]
+const MAX_TOKEN_BYTES: usize = 128;
+
+/// Rough number of characters per token without the tokenizer.
+#[cfg(not(feature = "tokenizer"))]
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Returns the number of tokens in `text`.
+#[cfg(feature = "tokenizer")]
+pub fn count(text: &str) -> usize {
+    tiktoken_rs::cl100k_base_singleton()
+        .encode_ordinary(text)
+        .len()
+}
+
+/// Returns the number of tokens in `text`.
+#[cfg(not(feature = "tokenizer"))]
+pub fn count(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Returns the longest prefix of `text` with at most `max` tokens.
+#[cfg(feature = "tokenizer")]
+pub fn truncate(text: &str, max: usize) -> &str {
+    // No token spans more than MAX_TOKEN_BYTES, so only the start of a huge diff is encoded
+    let text = &text[..text.floor_char_boundary(max.saturating_mul(MAX_TOKEN_BYTES))];
+    let bpe = tiktoken_rs::cl100k_base_singleton();
+    let tokens = bpe.encode_ordinary(text);
+    if tokens.len() <= max {
+        return text;
+    }
+    // A token may end inside a multi-byte character; drop tokens until it does not
+    let mut end = max;
+    loop {
+        if let Ok(prefix) = bpe.decode(tokens[..end].to_vec()) {
+            return &text[..text.floor_char_boundary(prefix.len())];
+        }
+        end -= 1;
+    }
+}
+
+/// Returns the longest prefix of `text` with at most `max` tokens.
+#[cfg(not(feature = "tokenizer"))]
+pub fn truncate(text: &str, max: usize) -> &str {
+    match text.char_indices().nth(max.saturating_mul(CHARS_PER_TOKEN)) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_table_driven() {
+        let diff =
+            "diff --git a/src/main.rs b/src/main.rs\n+fn main() { println!(\"héllo wörld\"); }\n";
+        let cases = [
+            ("fits", diff, count(diff), diff.len()),
+            ("no tokens", diff, 0, 0),
+            ("empty", "", 10, 0),
+        ];
+
+        for (name, text, max, expected) in cases {
+            assert_eq!(truncate(text, max).len(), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_truncate_fits_the_budget() {
+        let diff = "+let größe = \"日本語のテキスト\";\n".repeat(20);
+        for max in [1, 5, 17, 64, 200] {
+            let prefix = truncate(&diff, max);
+            assert!(count(prefix) <= max, "{}: {}", max, count(prefix));
+            assert!(diff.starts_with(prefix), "{}", max);
+        }
+        assert!(count(truncate(&diff, 64)) > 50);
+    }
+}
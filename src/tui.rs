@@ -0,0 +1,419 @@
+//! Full-screen terminal interface for ASUM (`asum tui`, behind the `tui` feature).
+//!
+//! Shows the staged files and diff on the left and the generated commit message
+//! on the right. The message can be regenerated, edited in `$EDITOR` and
+//! committed without leaving the screen.
+
+use crate::clipboard;
+use crate::config::AsumConfig;
+use crate::git;
+use crate::interactive::edit_in_editor;
+use crate::pager::{self, Paint};
+use crate::pipeline::{save_generation, summarize_diff};
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::path::Path;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// How often the screen checks for key presses and finished requests.
+const TICK: Duration = Duration::from_millis(100);
+
+/// Lines scrolled by PageUp and PageDown.
+const PAGE: u16 = 10;
+
+/// Share of the width (in percent) the diff pane starts with, changes by with
+/// `<` and `>`, and stays within.
+const SPLIT: u16 = 50;
+const SPLIT_STEP: u16 = 10;
+const SPLIT_RANGE: (u16, u16) = (20, 80);
+
+/// Backgrounds of added and removed lines whose code is highlighted.
+const ADDED_BACKGROUND: Color = Color::Rgb(0, 48, 0);
+const REMOVED_BACKGROUND: Color = Color::Rgb(64, 0, 0);
+
+/// Key bindings shown in the status bar.
+const HELP: &str = " r regenerate  e edit  c commit  p pager  ↑↓ scroll  <> resize  q quit ";
+
+/// Something the user asked for that needs more than a state change.
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    Regenerate,
+    Edit,
+    Commit,
+    Page,
+    Quit,
+}
+
+/// State of the screen.
+pub struct App {
+    /// Staged files as printed by `git diff --cached --name-status`.
+    pub files: String,
+    /// Staged diff.
+    pub diff: String,
+    /// The generated (or edited) message, once there is one.
+    pub message: Option<String>,
+    /// Last status or error line.
+    pub status: String,
+    /// Whether a request is in flight.
+    pub generating: bool,
+    /// Vertical scroll offset of the diff.
+    pub scroll: u16,
+    /// Width of the diff pane, in percent of the screen.
+    pub split: u16,
+}
+
+impl App {
+    pub fn new(files: String, diff: String) -> Self {
+        Self {
+            // Tabs from `--name-status` would not line up in the widget
+            files: files.replace('\t', "  "),
+            diff,
+            message: None,
+            status: String::new(),
+            generating: false,
+            scroll: 0,
+            split: SPLIT,
+        }
+    }
+
+    /// Updates the state for a key press and returns the action to run, if any.
+    pub fn handle_key(&mut self, code: KeyCode) -> Option<Action> {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return Some(Action::Quit),
+            KeyCode::Char('r') if self.generating => {
+                self.status = "A message is already being generated.".to_string();
+            }
+            KeyCode::Char('r') => return Some(Action::Regenerate),
+            KeyCode::Char('e') | KeyCode::Char('c') if self.message.is_none() => {
+                self.status = "There is no message yet.".to_string();
+            }
+            KeyCode::Char('e') => return Some(Action::Edit),
+            KeyCode::Char('c') => return Some(Action::Commit),
+            KeyCode::Char('p') => return Some(Action::Page),
+            KeyCode::Char('<') => {
+                self.split = self.split.saturating_sub(SPLIT_STEP).max(SPLIT_RANGE.0)
+            }
+            KeyCode::Char('>') => self.split = (self.split + SPLIT_STEP).min(SPLIT_RANGE.1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(PAGE),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(PAGE),
+            _ => {}
+        }
+        None
+    }
+
+    /// Draws the file list and diff on the left, the message on the right and
+    /// a status bar at the bottom.
+    pub fn draw(&self, frame: &mut Frame) {
+        let [main, status_bar] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+        let [left, right] = Layout::horizontal([
+            Constraint::Percentage(self.split),
+            Constraint::Percentage(100 - self.split),
+        ])
+        .areas(main);
+        let file_rows = self.files.lines().count().clamp(1, 10) as u16 + 2;
+        let [files_area, diff_area] =
+            Layout::vertical([Constraint::Length(file_rows), Constraint::Min(3)]).areas(left);
+
+        let files = Paragraph::new(self.files.as_str()).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Staged files "),
+        );
+        frame.render_widget(files, files_area);
+
+        let diff = Paragraph::new(highlight_diff(&self.diff))
+            .block(Block::default().borders(Borders::ALL).title(" Diff "))
+            .scroll((self.scroll, 0));
+        frame.render_widget(diff, diff_area);
+
+        let message = match (&self.message, self.generating) {
+            (_, true) => Text::styled(
+                "AI is analyzing your changes...",
+                Style::default().add_modifier(Modifier::ITALIC),
+            ),
+            (Some(message), false) => Text::raw(message.as_str()),
+            (None, false) => Text::raw(""),
+        };
+        let message = Paragraph::new(message)
+            .block(Block::default().borders(Borders::ALL).title(" Message "))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(message, right);
+
+        let status = Line::from(vec![
+            Span::styled(HELP, Style::default().add_modifier(Modifier::REVERSED)),
+            Span::raw(format!(" {}", self.status)),
+        ]);
+        frame.render_widget(Paragraph::new(status), status_bar);
+    }
+}
+
+/// Colors the diff like the pager does, see `pager::paint_diff`.
+fn highlight_diff(diff: &str) -> Text<'static> {
+    pager::paint_diff(diff)
+        .into_iter()
+        .map(|painted| {
+            let background = painted.background.map_or(Style::default(), |paint| {
+                Style::default().bg(match paint {
+                    Paint::Removed => REMOVED_BACKGROUND,
+                    _ => ADDED_BACKGROUND,
+                })
+            });
+            painted
+                .pieces
+                .into_iter()
+                .map(|(piece, paint)| Span::styled(piece, paint.map_or(Style::default(), style)))
+                .collect::<Line>()
+                .style(background)
+        })
+        .collect::<Vec<_>>()
+        .into()
+}
+
+/// The style of a piece of the diff in `paint`.
+fn style(paint: Paint) -> Style {
+    match paint {
+        Paint::Header => Style::default().add_modifier(Modifier::BOLD),
+        Paint::Added => Style::default().fg(Color::Green),
+        Paint::Removed => Style::default().fg(Color::Red),
+        Paint::Hunk => Style::default().fg(Color::Cyan),
+        Paint::Code(r, g, b) => Style::default().fg(Color::Rgb(r, g, b)),
+    }
+}
+
+/// Runs the interface until the user commits or quits.
+pub async fn run(config: AsumConfig, files: String, diff: String) -> Result<()> {
+    let mut app = App::new(files, diff);
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut app, &config).await;
+    ratatui::restore();
+
+    match result? {
+        Some(message) => {
+            save_generation(&config, Path::new("."), &app.diff, &message);
+            git::commit(&message, false).inspect_err(|_| clipboard::save_restore_point(&message))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Handles keys and requests. Returns the message to commit, or `None` on quit.
+async fn event_loop(
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+    config: &AsumConfig,
+) -> Result<Option<String>> {
+    let mut request = Some(start_request(app, config));
+
+    loop {
+        if let Some(handle) = request.take_if(|r| r.is_finished()) {
+            app.generating = false;
+            match handle.await? {
+                Ok(message) => {
+                    app.message = Some(message);
+                    app.status.clear();
+                }
+                Err(e) => app.status = format!("Generation failed: {:#}", e),
+            }
+        }
+
+        terminal.draw(|frame| app.draw(frame))?;
+
+        if !event::poll(TICK)? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match app.handle_key(key.code) {
+            Some(Action::Quit) => return Ok(None),
+            Some(Action::Regenerate) => {
+                // Regenerating asks for a new message, not the cached one
+                let mut config = config.clone();
+                config.no_cache = true;
+                request = Some(start_request(app, &config));
+            }
+            Some(Action::Edit) => {
+                let current = app.message.clone().unwrap_or_default();
+                // The editor needs the normal screen
+                ratatui::restore();
+                let edited = edit_in_editor(&current);
+                *terminal = ratatui::init();
+                match edited {
+                    Ok(message) if !message.is_empty() => app.message = Some(message),
+                    Ok(_) => app.status = "The edited message is empty; kept the old one.".into(),
+                    Err(e) => app.status = format!("{:#}", e),
+                }
+            }
+            Some(Action::Page) => {
+                // The pager needs the normal screen, like the editor
+                ratatui::restore();
+                pager::show(&app.diff);
+                *terminal = ratatui::init();
+            }
+            Some(Action::Commit) => return Ok(app.message.clone()),
+            None => {}
+        }
+    }
+}
+
+/// Starts generating a message in the background.
+fn start_request(app: &mut App, config: &AsumConfig) -> JoinHandle<Result<String>> {
+    app.generating = true;
+    app.status.clear();
+    tokio::spawn(summarize_diff(config.clone(), app.diff.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn app() -> App {
+        App::new(
+            "M\tsrc/main.rs".to_string(),
+            "diff --git a/src/main.rs b/src/main.rs\n@@ -1 +1 @@\n-old\n+new".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_handle_key_table_driven() {
+        struct TestCase {
+            key: KeyCode,
+            message: Option<&'static str>,
+            generating: bool,
+            expected: Option<Action>,
+            status: &'static str,
+        }
+
+        let cases = vec![
+            TestCase {
+                key: KeyCode::Char('q'),
+                message: None,
+                generating: true,
+                expected: Some(Action::Quit),
+                status: "",
+            },
+            TestCase {
+                key: KeyCode::Char('r'),
+                message: Some("feat: a"),
+                generating: false,
+                expected: Some(Action::Regenerate),
+                status: "",
+            },
+            TestCase {
+                key: KeyCode::Char('r'),
+                message: None,
+                generating: true,
+                expected: None,
+                status: "A message is already being generated.",
+            },
+            TestCase {
+                key: KeyCode::Char('c'),
+                message: None,
+                generating: false,
+                expected: None,
+                status: "There is no message yet.",
+            },
+            TestCase {
+                key: KeyCode::Char('c'),
+                message: Some("feat: a"),
+                generating: false,
+                expected: Some(Action::Commit),
+                status: "",
+            },
+            TestCase {
+                key: KeyCode::Char('e'),
+                message: Some("feat: a"),
+                generating: false,
+                expected: Some(Action::Edit),
+                status: "",
+            },
+            TestCase {
+                key: KeyCode::Char('p'),
+                message: None,
+                generating: true,
+                expected: Some(Action::Page),
+                status: "",
+            },
+        ];
+
+        for case in cases {
+            let mut app = app();
+            app.message = case.message.map(String::from);
+            app.generating = case.generating;
+            assert_eq!(
+                app.handle_key(case.key),
+                case.expected,
+                "key: {:?}",
+                case.key
+            );
+            assert_eq!(app.status, case.status, "key: {:?}", case.key);
+        }
+    }
+
+    #[test]
+    fn test_scrolling() {
+        let mut app = app();
+        app.handle_key(KeyCode::Up);
+        assert_eq!(app.scroll, 0);
+        app.handle_key(KeyCode::PageDown);
+        app.handle_key(KeyCode::Char('j'));
+        assert_eq!(app.scroll, 11);
+        app.handle_key(KeyCode::PageUp);
+        assert_eq!(app.scroll, 1);
+    }
+
+    #[test]
+    fn test_resizing() {
+        let mut app = app();
+        app.handle_key(KeyCode::Char('>'));
+        assert_eq!(app.split, SPLIT + SPLIT_STEP);
+        for _ in 0..10 {
+            app.handle_key(KeyCode::Char('<'));
+        }
+        assert_eq!(app.split, SPLIT_RANGE.0);
+        for _ in 0..10 {
+            app.handle_key(KeyCode::Char('>'));
+        }
+        assert_eq!(app.split, SPLIT_RANGE.1);
+    }
+
+    #[test]
+    fn test_draw_shows_files_diff_and_message() {
+        let mut app = app();
+        app.message = Some("feat(core): use new value".to_string());
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+
+        for expected in [
+            "src/main.rs",
+            "+new",
+            "feat(core): use new value",
+            "r regenerate",
+        ] {
+            assert!(screen.contains(expected), "missing {:?}", expected);
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! Untracked files as prompt context for ASUM.
+//!
+//! A commit that adds brand-new files often comes with files that were not staged
+//! yet. With `untracked_files = "names"` or `"content"`, they are listed after the
+//! diff, optionally with the start of each text file, so the model knows they exist.
+
+use crate::asumignore::AsumIgnore;
+use crate::config::AsumConfig;
+use crate::git::{get_repo_root_in_path, get_untracked_files_in_path};
+use crate::private_paths;
+use std::path::Path;
+use tracing::{debug, warn};
+
+/// Values accepted in `untracked_files`.
+pub const MODES: &[&str] = &["off", "names", "content"];
+
+/// Bytes of each untracked file shown with `untracked_files = "content"`.
+const MAX_FILE_BYTES: usize = 2000;
+
+/// Most untracked files listed, so a forgotten build directory cannot fill the prompt.
+const MAX_FILES: usize = 50;
+
+/// What is known about an untracked file.
+#[derive(Debug, PartialEq)]
+pub enum Content {
+    /// Only the name is shown.
+    Skipped,
+    /// The file holds binary data.
+    Binary,
+    /// The start of the file, and whether it was cut at `MAX_FILE_BYTES`.
+    Text(String, bool),
+}
+
+/// Appends the untracked files of the repository at `repo` to `diff_text` as
+/// configured by `untracked_files`. Git errors are only logged, since the context
+/// must never block message generation.
+pub fn append(config: &AsumConfig, repo: &Path, diff_text: String) -> String {
+    let with_content = match config.untracked_files.as_str() {
+        "off" => return diff_text,
+        "names" => false,
+        "content" => true,
+        other => {
+            warn!(
+                "Unknown untracked_files '{}'; expected off, names or content. Leaving untracked files out.",
+                other
+            );
+            return diff_text;
+        }
+    };
+
+    let ignore = AsumIgnore::load(repo);
+    let private = private_paths::rules(config);
+    let files = get_repo_root_in_path(repo).and_then(|root| {
+        let files = get_untracked_files_in_path(&config.pathspecs(), repo)?;
+        Ok(files
+            .into_iter()
+            .filter(|file| !ignore.is_ignored(file))
+            .map(|file| {
+                let content = if with_content && !private.is_ignored(&file) {
+                    read_content(&root.join(&file))
+                } else {
+                    Content::Skipped
+                };
+                (file, content)
+            })
+            .collect::<Vec<_>>())
+    });
+    match files {
+        Ok(files) if files.is_empty() => diff_text,
+        Ok(files) => format!("{}\n\n{}", diff_text.trim_end(), render(&files)),
+        Err(e) => {
+            debug!("Skipping untracked files: {}", e);
+            diff_text
+        }
+    }
+}
+
+/// Reads the start of `path`, up to `MAX_FILE_BYTES` and on a character boundary.
+fn read_content(path: &Path) -> Content {
+    let Ok(bytes) = std::fs::read(path) else {
+        return Content::Skipped;
+    };
+    if bytes.contains(&0) {
+        return Content::Binary;
+    }
+    let text = String::from_utf8_lossy(&bytes);
+    if text.len() <= MAX_FILE_BYTES {
+        return Content::Text(text.into_owned(), false);
+    }
+    let end = text.floor_char_boundary(MAX_FILE_BYTES);
+    Content::Text(text[..end].to_string(), true)
+}
+
+/// Renders the `[UNTRACKED FILES]` section for `files`, listing at most `MAX_FILES`.
+pub fn render(files: &[(String, Content)]) -> String {
+    let mut section = String::from(
+        "[UNTRACKED FILES]\nThese files are not tracked by git yet and are not part of the diff. \
+         Mention them only if they clearly belong to this change.\n",
+    );
+    for (file, content) in files.iter().take(MAX_FILES) {
+        match content {
+            Content::Skipped => section.push_str(&format!("{}\n", file)),
+            Content::Binary => section.push_str(&format!("{} (binary)\n", file)),
+            Content::Text(text, truncated) => {
+                section.push_str(&format!("--- {}\n{}", file, text));
+                if !text.ends_with('\n') {
+                    section.push('\n');
+                }
+                if *truncated {
+                    section.push_str("(truncated)\n");
+                }
+            }
+        }
+    }
+    if files.len() > MAX_FILES {
+        section.push_str(&format!("... and {} more\n", files.len() - MAX_FILES));
+    }
+    section
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn test_render_table_driven() {
+        let header = "[UNTRACKED FILES]\nThese files are not tracked by git yet and are not part \
+                      of the diff. Mention them only if they clearly belong to this change.\n";
+        let cases = vec![
+            (
+                "names",
+                vec![("src/new.rs".to_string(), Content::Skipped)],
+                "src/new.rs\n",
+            ),
+            (
+                "binary",
+                vec![("logo.png".to_string(), Content::Binary)],
+                "logo.png (binary)\n",
+            ),
+            (
+                "content",
+                vec![(
+                    "src/new.rs".to_string(),
+                    Content::Text("fn new() {}".to_string(), false),
+                )],
+                "--- src/new.rs\nfn new() {}\n",
+            ),
+            (
+                "truncated content",
+                vec![(
+                    "notes.md".to_string(),
+                    Content::Text("# Notes\n".to_string(), true),
+                )],
+                "--- notes.md\n# Notes\n(truncated)\n",
+            ),
+        ];
+
+        for (name, files, expected) in cases {
+            assert_eq!(
+                render(&files),
+                format!("{}{}", header, expected),
+                "{}",
+                name
+            );
+        }
+
+        let many: Vec<_> = (0..MAX_FILES + 2)
+            .map(|i| (format!("f{}.rs", i), Content::Skipped))
+            .collect();
+        assert!(render(&many).ends_with("f49.rs\n... and 2 more\n"));
+    }
+
+    #[test]
+    fn test_append_lists_untracked_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+        Command::new("git")
+            .arg("init")
+            .current_dir(path)
+            .output()
+            .unwrap();
+        std::fs::write(path.join("new.rs"), "fn new() {}\n").unwrap();
+        std::fs::write(path.join("blob.rs"), b"\0\x01").unwrap();
+        std::fs::write(path.join("notes.txt"), "not a source file\n").unwrap();
+
+        let mut config = AsumConfig::from_defaults().unwrap();
+        config.git_extensions = vec!["*.rs".to_string()];
+        assert_eq!(append(&config, path, "diff\n".to_string()), "diff\n");
+
+        config.untracked_files = "names".to_string();
+        let names = append(&config, path, "diff\n".to_string());
+        assert!(
+            names.starts_with("diff\n\n[UNTRACKED FILES]\n"),
+            "{}",
+            names
+        );
+        assert!(names.contains("\nnew.rs\n"), "{}", names);
+        assert!(!names.contains("notes.txt"), "{}", names);
+
+        config.untracked_files = "content".to_string();
+        let content = append(&config, path, "diff\n".to_string());
+        assert!(content.contains("--- new.rs\nfn new() {}\n"), "{}", content);
+        assert!(content.contains("blob.rs (binary)\n"), "{}", content);
+
+        // Private paths are listed by name only
+        config.private_paths = vec!["new.rs".to_string()];
+        let content = append(&config, path, "diff\n".to_string());
+        assert!(content.contains("\nnew.rs\n"), "{}", content);
+        assert!(!content.contains("fn new()"), "{}", content);
+    }
+}
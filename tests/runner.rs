@@ -0,0 +1,188 @@
+//! Integration tests for the `Runner` API: a temporary repository, a fake provider,
+//! an in-memory clipboard and a fixed clock, without touching the working
+//! directory, the environment or `~/.asum`.
+
+use asum::Runner;
+use asum::clipboard::MemoryClipboard;
+use asum::config::AsumConfig;
+use asum::db::Db;
+use asum::failure::{Failure, failure_of};
+use asum::git_backend::GitBackend;
+use asum::runner::FixedClock;
+use asum::summarizer::Summarizer;
+use async_trait::async_trait;
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+/// 2024-03-15 12:00:00 UTC.
+const NOW: i64 = 1_710_504_000;
+
+/// Replies with a fixed message and keeps the diffs it was given.
+#[derive(Default)]
+struct FakeSummarizer {
+    diffs: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl Summarizer for FakeSummarizer {
+    async fn summarize(&self, diff: &str) -> anyhow::Result<String> {
+        self.diffs.lock().unwrap().push(diff.to_string());
+        Ok("feat: add greeting".to_string())
+    }
+}
+
+fn git(path: &Path, args: &[&str], date: Option<&str>) {
+    let mut command = Command::new("git");
+    command.args(args).current_dir(path);
+    if let Some(date) = date {
+        command
+            .env("GIT_AUTHOR_DATE", date)
+            .env("GIT_COMMITTER_DATE", date);
+    }
+    let output = command.output().unwrap();
+    assert!(output.status.success(), "git {:?}: {:?}", args, output);
+}
+
+fn init_repo(path: &Path) {
+    git(path, &["init", "-q"], None);
+    git(path, &["config", "user.name", "Test"], None);
+    git(path, &["config", "user.email", "test@example.com"], None);
+}
+
+fn config() -> AsumConfig {
+    let mut config = AsumConfig::from_defaults().unwrap();
+    config.git_extensions = vec!["*.rs".to_string()];
+    config
+}
+
+#[tokio::test]
+async fn test_generate_with_injected_dependencies() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    init_repo(repo);
+    std::fs::write(repo.join("hello.rs"), "fn hello() {}\n").unwrap();
+    git(repo, &["add", "hello.rs"], None);
+
+    let summarizer = Arc::new(FakeSummarizer::default());
+    let clipboard = Arc::new(MemoryClipboard::default());
+    let db_path = repo.join("history.db");
+    let runner = Runner::new(config())
+        .repo(repo)
+        .summarizer(summarizer.clone())
+        .clipboard(clipboard.clone())
+        .history(&db_path);
+
+    let message = runner.generate().await.unwrap();
+    assert_eq!(message, "feat: add greeting");
+    assert_eq!(clipboard.contents().as_deref(), Some("feat: add greeting"));
+
+    let diffs = summarizer.diffs.lock().unwrap();
+    assert_eq!(diffs.len(), 1);
+    assert!(diffs[0].contains("+fn hello() {}"), "{}", diffs[0]);
+
+    let history = Db::open(&db_path).unwrap().history(None, None, 10).unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].message, "feat: add greeting");
+}
+
+#[tokio::test]
+async fn test_generate_without_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    init_repo(dir.path());
+    let clipboard = Arc::new(MemoryClipboard::default());
+    let runner = Runner::new(config())
+        .repo(dir.path())
+        .summarizer(Arc::new(FakeSummarizer::default()))
+        .clipboard(clipboard.clone());
+
+    let error = runner.generate().await.unwrap_err();
+    assert_eq!(failure_of(&error), Some(Failure::NoChanges));
+    assert_eq!(clipboard.contents(), None);
+}
+
+#[test]
+fn test_commits_since_uses_the_clock() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    init_repo(repo);
+    for (file, subject, date) in [
+        ("old.rs", "feat: old work", "2024-03-10T12:00:00Z"),
+        ("new.rs", "fix: recent work", "2024-03-14T12:00:00Z"),
+    ] {
+        std::fs::write(repo.join(file), "fn f() {}\n").unwrap();
+        git(repo, &["add", file], None);
+        git(repo, &["commit", "-q", "-m", subject], Some(date));
+    }
+
+    let runner = Runner::new(config())
+        .repo(repo)
+        .clock(Arc::new(FixedClock(NOW)));
+    assert_eq!(
+        runner.commits_since("2 days ago", None).unwrap(),
+        "- fix: recent work\n"
+    );
+    assert_eq!(
+        runner
+            .commits_since("1 week ago", Some("test@example.com"))
+            .unwrap(),
+        "- feat: old work\n- fix: recent work\n"
+    );
+}
+
+/// A repository that exists only in memory.
+struct FakeGit;
+
+impl GitBackend for FakeGit {
+    fn staged_diff(&self, _patterns: &[String]) -> anyhow::Result<String> {
+        Ok("diff --git a/lib.rs b/lib.rs\n+pub fn fake() {}\n".to_string())
+    }
+
+    fn staged_files(&self) -> anyhow::Result<String> {
+        Ok("A\tlib.rs\n".to_string())
+    }
+
+    fn current_branch(&self) -> anyhow::Result<String> {
+        Ok("main".to_string())
+    }
+
+    fn commits_since(&self, since: i64, _author: Option<&str>) -> anyhow::Result<Vec<String>> {
+        assert_eq!(since, NOW - 86_400);
+        Ok(vec!["feat: remembered".to_string()])
+    }
+
+    fn recent_commits(&self, _limit: usize) -> anyhow::Result<Vec<(String, String)>> {
+        Ok(Vec::new())
+    }
+
+    fn commit(&self, _message: &str) -> anyhow::Result<()> {
+        anyhow::bail!("read-only")
+    }
+
+    fn note(&self, _rev: &str) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn add_note(&self, _rev: &str, _note: &str) -> anyhow::Result<()> {
+        anyhow::bail!("read-only")
+    }
+}
+
+#[tokio::test]
+async fn test_generate_with_an_injected_git_backend() {
+    // Not a repository: everything comes from the fake
+    let dir = tempfile::tempdir().unwrap();
+    let summarizer = Arc::new(FakeSummarizer::default());
+    let runner = Runner::new(config())
+        .repo(dir.path())
+        .git(Arc::new(FakeGit))
+        .summarizer(summarizer.clone())
+        .clock(Arc::new(FixedClock(NOW)));
+
+    assert_eq!(runner.generate().await.unwrap(), "feat: add greeting");
+    assert!(summarizer.diffs.lock().unwrap()[0].contains("+pub fn fake() {}"));
+    assert_eq!(
+        runner.commits_since("yesterday", None).unwrap(),
+        "- feat: remembered\n"
+    );
+}